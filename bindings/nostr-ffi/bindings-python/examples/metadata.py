@@ -0,0 +1,30 @@
+from nostr_protocol import Metadata, MetadataRecord
+
+# Build metadata
+metadata = Metadata()\
+    .name("username")\
+    .display_name("My Username")\
+    .about("Description")\
+    .picture("https://example.com/avatar.png")\
+    .banner("https://example.com/banner.png")\
+    .nip05("username@example.com")\
+    .lud16("yuki@getalby.com")\
+    .website("https://example.com")
+
+print(f"Metadata: {metadata.as_json()}")
+
+assert metadata.get_name() == "username"
+assert metadata.get_display_name() == "My Username"
+assert metadata.get_nip05() == "username@example.com"
+assert metadata.get_lud16() == "yuki@getalby.com"
+assert metadata.get_lud06() is None
+
+# JSON round-trip
+parsed = Metadata.from_json(metadata.as_json())
+assert parsed.as_json() == metadata.as_json()
+
+# Record
+record: MetadataRecord = metadata.as_record()
+assert record.name == "username"
+assert record.website == "https://example.com/"
+assert Metadata.from_record(record).as_json() == metadata.as_json()
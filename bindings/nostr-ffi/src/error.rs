@@ -26,6 +26,44 @@ impl fmt::Display for NostrError {
     }
 }
 
+/// Event verification error
+#[derive(Debug, Error)]
+#[uniffi(flat_error)]
+pub enum EventVerificationError {
+    /// Malformed event JSON
+    Json(String),
+    /// The event ID doesn't match the event fields
+    InvalidId,
+    /// The signature doesn't match the event ID and author
+    InvalidSignature,
+    /// Other error
+    Generic(String),
+}
+
+impl std::error::Error for EventVerificationError {}
+
+impl fmt::Display for EventVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "Json: {e}"),
+            Self::InvalidId => write!(f, "Invalid event id"),
+            Self::InvalidSignature => write!(f, "Invalid signature"),
+            Self::Generic(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<nostr::event::Error> for EventVerificationError {
+    fn from(e: nostr::event::Error) -> EventVerificationError {
+        match e {
+            nostr::event::Error::Json(e) => Self::Json(e),
+            nostr::event::Error::InvalidId => Self::InvalidId,
+            nostr::event::Error::InvalidSignature => Self::InvalidSignature,
+            e => Self::Generic(e.to_string()),
+        }
+    }
+}
+
 impl From<std::num::ParseIntError> for NostrError {
     fn from(e: std::num::ParseIntError) -> NostrError {
         Self::Generic(e.to_string())
@@ -360,7 +360,7 @@ impl EventBuilder {
         pubkey_awarded: &PublicKey,
     ) -> Result<Self> {
         Ok(Self {
-            inner: nostr::EventBuilder::profile_badges(
+            inner: nostr::EventBuilder::profile_badges_from_events(
                 badge_definitions
                     .iter()
                     .map(|b| b.as_ref().deref().clone())
@@ -407,11 +407,11 @@ impl EventBuilder {
         job_request: &Event,
         status: DataVendingMachineStatus,
         extra_info: Option<String>,
-        amount_millisats: u64,
+        amount_millisats: Option<u64>,
         bolt11: Option<String>,
         payload: Option<String>,
-    ) -> Self {
-        Self {
+    ) -> Result<Self> {
+        Ok(Self {
             inner: nostr::EventBuilder::job_feedback(
                 job_request.deref(),
                 status.into(),
@@ -419,8 +419,8 @@ impl EventBuilder {
                 amount_millisats,
                 bolt11,
                 payload,
-            ),
-        }
+            )?,
+        })
     }
 
     #[uniffi::constructor]
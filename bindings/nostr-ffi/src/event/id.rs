@@ -102,6 +102,16 @@ impl EventId {
         Ok(self.inner.to_bech32()?)
     }
 
+    /// Get the first `chars` characters of the `hex` event ID
+    pub fn short_hex(&self, chars: u64) -> String {
+        self.inner.short_hex(chars as usize)
+    }
+
+    /// Check if event ID starts with `prefix` (`hex` or `note` bech32)
+    pub fn matches_prefix(&self, prefix: &str) -> bool {
+        self.inner.matches_prefix(prefix)
+    }
+
     pub fn to_nostr_uri(&self) -> Result<String> {
         Ok(self.inner.to_nostr_uri()?)
     }
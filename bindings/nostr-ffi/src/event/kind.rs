@@ -54,6 +54,11 @@ impl Kind {
         self.inner.into()
     }
 
+    /// Get human-readable description of the `Kind`
+    pub fn description(&self) -> String {
+        self.inner.category().description.to_string()
+    }
+
     /// Check if `Kind` match another `Kind`
     pub fn r#match(&self, other: &Self) -> bool {
         self.inner == other.inner
@@ -228,6 +233,8 @@ pub enum KindEnum {
     FileMetadata,
     /// HTTP Auth (NIP98)
     HttpAuth,
+    /// Classified Listing (NIP99)
+    ClassifiedListing,
     /// Set stall (NIP15)
     SetStall,
     /// Set product (NIP15)
@@ -320,6 +327,7 @@ impl From<nostr::Kind> for KindEnum {
             nostr::Kind::ApplicationSpecificData => Self::ApplicationSpecificData,
             nostr::Kind::FileMetadata => Self::FileMetadata,
             nostr::Kind::HttpAuth => Self::HttpAuth,
+            nostr::Kind::ClassifiedListing => Self::ClassifiedListing,
             nostr::Kind::SetStall => Self::SetStall,
             nostr::Kind::SetProduct => Self::SetProduct,
             nostr::Kind::JobFeedback => Self::JobFeedback,
@@ -397,6 +405,7 @@ impl From<KindEnum> for nostr::Kind {
             KindEnum::ApplicationSpecificData => Self::ApplicationSpecificData,
             KindEnum::FileMetadata => Self::FileMetadata,
             KindEnum::HttpAuth => Self::HttpAuth,
+            KindEnum::ClassifiedListing => Self::ClassifiedListing,
             KindEnum::SetStall => Self::SetStall,
             KindEnum::SetProduct => Self::SetProduct,
             KindEnum::JobFeedback => Self::JobFeedback,
@@ -20,7 +20,7 @@ pub use self::id::EventId;
 pub use self::kind::{Kind, KindEnum};
 pub use self::tag::{RelayMetadata, Tag, TagEnum, TagKind};
 pub use self::unsigned::UnsignedEvent;
-use crate::error::Result;
+use crate::error::{EventVerificationError, Result};
 use crate::nips::nip01::Coordinate;
 use crate::{PublicKey, Timestamp};
 
@@ -187,7 +187,110 @@ impl Event {
         })
     }
 
+    /// Deserialize event from JSON and, if `verify` is `true`, check both `EventId` and `Signature`
+    #[uniffi::constructor]
+    pub fn from_json_with_verification(
+        json: String,
+        verify: bool,
+    ) -> Result<Self, EventVerificationError> {
+        let event: nostr::Event = nostr::Event::from_json(json)?;
+        if verify {
+            event.verify()?;
+        }
+        Ok(Self { inner: event })
+    }
+
     pub fn as_json(&self) -> String {
         self.inner.as_json()
     }
 }
+
+/// Deserialize event from JSON and verify both `EventId` and `Signature`
+///
+/// Each failure mode is reported with a distinct error variant.
+#[uniffi::export]
+pub fn verify_event_json(json: String) -> Result<Event, EventVerificationError> {
+    Event::from_json_with_verification(json, true)
+}
+
+/// Verify both `EventId` and `Signature` of an event
+#[uniffi::export]
+pub fn verify_event(event: &Event) -> bool {
+    event.verify()
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::serde_json::{self, Value};
+    use nostr::{EventBuilder, Keys};
+
+    use super::*;
+
+    fn signed_event_json() -> String {
+        let keys = Keys::generate();
+        EventBuilder::text_note("Hello", [])
+            .to_event(&keys)
+            .unwrap()
+            .as_json()
+    }
+
+    fn tamper(json: &str, field: &str, value: Value) -> String {
+        let mut event: Value = serde_json::from_str(json).unwrap();
+        event[field] = value;
+        event.to_string()
+    }
+
+    #[test]
+    fn test_verify_event_json() {
+        let json = signed_event_json();
+        let event = verify_event_json(json.clone()).unwrap();
+        assert!(verify_event(&event));
+        assert!(Event::from_json_with_verification(json, true).is_ok());
+    }
+
+    #[test]
+    fn test_verify_event_json_tampered_content() {
+        let json = tamper(&signed_event_json(), "content", "Tampered".into());
+        assert!(matches!(
+            verify_event_json(json.clone()),
+            Err(EventVerificationError::InvalidId)
+        ));
+
+        // Skip verification
+        let event = Event::from_json_with_verification(json, false).unwrap();
+        assert!(!verify_event(&event));
+    }
+
+    #[test]
+    fn test_verify_event_json_tampered_signature() {
+        let json = signed_event_json();
+        let other = signed_event_json();
+        let value: Value = serde_json::from_str(&other).unwrap();
+        let json = tamper(&json, "sig", value["sig"].clone());
+        assert!(matches!(
+            verify_event_json(json),
+            Err(EventVerificationError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_verify_event_json_mismatched_id() {
+        let json = tamper(
+            &signed_event_json(),
+            "id",
+            "2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45".into(),
+        );
+        assert!(matches!(
+            verify_event_json(json),
+            Err(EventVerificationError::InvalidId)
+        ));
+    }
+
+    #[test]
+    fn test_verify_event_json_malformed() {
+        assert!(matches!(
+            verify_event_json(String::from("{\"id\":")),
+            Err(EventVerificationError::Json(..))
+        ));
+    }
+}
@@ -63,6 +63,11 @@ impl UnsignedEvent {
         self.inner.content.clone()
     }
 
+    /// Verify if the `EventId` it's composed correctly
+    pub fn verify_id(&self) -> Result<()> {
+        Ok(self.inner.verify_id()?)
+    }
+
     pub fn sign(&self, keys: &Keys) -> Result<Event> {
         Ok(Event::from(self.inner.clone().sign(keys.deref())?))
     }
@@ -3,9 +3,10 @@
 // Distributed under the MIT software license
 
 use std::ops::Deref;
+use std::str::FromStr;
 
 use nostr::key;
-use nostr::nips::nip06::FromMnemonic;
+use nostr::nips::nip06::{self, DerivationPath, FromMnemonic};
 use nostr::secp256k1::Message;
 use uniffi::Object;
 
@@ -114,6 +115,31 @@ impl Keys {
         })
     }
 
+    /// Derive `Keys` from BIP-39 mnemonics with a **custom** BIP-32 derivation path (ENGLISH wordlist).
+    ///
+    /// The `path` must be in the `m/44'/1237'/0'/0/0` format.
+    #[uniffi::constructor]
+    pub fn from_mnemonic_with_path(
+        mnemonic: String,
+        passphrase: Option<String>,
+        path: String,
+    ) -> Result<Self> {
+        let path = DerivationPath::from_str(&path).map_err(nip06::Error::from)?;
+        Ok(Self {
+            inner: key::Keys::from_mnemonic_with_path(mnemonic, passphrase, &path)?,
+        })
+    }
+
+    /// Derive an app-scoped pseudonym from BIP-39 mnemonics (ENGLISH wordlist).
+    ///
+    /// The same `scope` (ex. a community name) always produce the same keys.
+    #[uniffi::constructor]
+    pub fn pseudonym(mnemonic: String, passphrase: Option<String>, scope: String) -> Result<Self> {
+        Ok(Self {
+            inner: key::Keys::pseudonym(mnemonic, passphrase, &scope)?,
+        })
+    }
+
     pub fn public_key(&self) -> PublicKey {
         self.inner.public_key().into()
     }
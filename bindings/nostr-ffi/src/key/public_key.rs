@@ -75,6 +75,16 @@ impl PublicKey {
         Ok(self.inner.to_bech32()?)
     }
 
+    /// Get shortened `bech32` public key (i.e. `npub1abc…xyz`)
+    pub fn to_short_bech32(&self, chars: u64) -> Result<String> {
+        Ok(self.inner.to_short_bech32(chars as usize)?)
+    }
+
+    /// Check if public key starts with `prefix` (`hex` or `npub` bech32)
+    pub fn matches_prefix(&self, prefix: &str) -> bool {
+        self.inner.matches_prefix(prefix)
+    }
+
     pub fn to_nostr_uri(&self) -> Result<String> {
         Ok(self.inner.to_nostr_uri()?)
     }
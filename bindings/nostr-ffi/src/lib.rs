@@ -17,13 +17,15 @@ pub mod nips;
 pub mod types;
 pub mod util;
 
-pub use crate::error::NostrError;
+pub use crate::error::{EventVerificationError, NostrError};
 pub use crate::event::{
-    Event, EventBuilder, EventId, Kind, KindEnum, RelayMetadata, Tag, TagEnum, TagKind,
-    UnsignedEvent,
+    verify_event, verify_event_json, Event, EventBuilder, EventId, Kind, KindEnum, RelayMetadata,
+    Tag, TagEnum, TagKind, UnsignedEvent,
 };
 pub use crate::key::{Keys, PublicKey, SecretKey};
-pub use crate::message::{ClientMessage, ClientMessageEnum, RelayMessage, RelayMessageEnum};
+pub use crate::message::{
+    ClientMessage, ClientMessageEnum, RelayMessage, RelayMessageEnum, SubscriptionId,
+};
 pub use crate::nips::nip04::{nip04_decrypt, nip04_encrypt};
 pub use crate::nips::nip05::{get_nip05_profile, verify_nip05};
 pub use crate::nips::nip11::RelayInformationDocument;
@@ -33,7 +35,10 @@ pub use crate::nips::nip94::FileMetadata;
 pub use crate::types::{
     Alphabet, Contact, Filter, ImageDimensions, Metadata, SingleLetterTag, Timestamp,
 };
-pub use crate::util::{generate_shared_key, JsonValue};
+pub use crate::util::{
+    generate_shared_key, sanitize_content, truncate_graphemes, ControlChars, JsonValue,
+    SanitizeOptions, SanitizeReport, SanitizedContent,
+};
 
 #[derive(Object)]
 pub struct NostrLibrary;
@@ -4,6 +4,8 @@
 
 pub mod client;
 pub mod relay;
+pub mod subscription_id;
 
 pub use self::client::{ClientMessage, ClientMessageEnum};
 pub use self::relay::{RelayMessage, RelayMessageEnum};
+pub use self::subscription_id::SubscriptionId;
@@ -0,0 +1,48 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+use std::ops::Deref;
+
+use uniffi::Object;
+
+#[derive(Object)]
+pub struct SubscriptionId {
+    inner: nostr::SubscriptionId,
+}
+
+impl Deref for SubscriptionId {
+    type Target = nostr::SubscriptionId;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl From<nostr::SubscriptionId> for SubscriptionId {
+    fn from(inner: nostr::SubscriptionId) -> Self {
+        Self { inner }
+    }
+}
+
+#[uniffi::export]
+impl SubscriptionId {
+    #[uniffi::constructor]
+    pub fn new(id: String) -> Self {
+        Self {
+            inner: nostr::SubscriptionId::new(id),
+        }
+    }
+
+    /// Generate random subscription ID
+    #[uniffi::constructor]
+    pub fn generate() -> Self {
+        Self {
+            inner: nostr::SubscriptionId::generate(),
+        }
+    }
+
+    pub fn as_str(&self) -> String {
+        self.inner.to_string()
+    }
+}
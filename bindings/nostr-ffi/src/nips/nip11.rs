@@ -10,7 +10,7 @@ use nostr::Url;
 use uniffi::{Enum, Object, Record};
 
 use crate::error::Result;
-use crate::Timestamp;
+use crate::{PublicKey, Timestamp};
 
 #[derive(Object)]
 pub struct RelayInformationDocument {
@@ -57,6 +57,14 @@ impl RelayInformationDocument {
         self.inner.pubkey.clone()
     }
 
+    /// Get the relay public key, if advertised and valid
+    pub fn public_key(&self) -> Option<Arc<PublicKey>> {
+        let pubkey: &str = self.inner.pubkey.as_deref()?;
+        nostr::PublicKey::parse(pubkey)
+            .ok()
+            .map(|p| Arc::new(p.into()))
+    }
+
     pub fn contact(&self) -> Option<String> {
         self.inner.contact.clone()
     }
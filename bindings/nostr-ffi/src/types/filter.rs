@@ -365,6 +365,42 @@ impl Filter {
         self.inner.is_empty()
     }
 
+    /// Get event IDs (hex)
+    pub fn get_ids(&self) -> Option<Vec<String>> {
+        self.inner
+            .ids
+            .as_ref()
+            .map(|ids| ids.iter().map(|id| id.to_hex()).collect())
+    }
+
+    pub fn get_authors(&self) -> Option<Vec<Arc<PublicKey>>> {
+        self.inner
+            .authors
+            .as_ref()
+            .map(|authors| authors.iter().map(|p| Arc::new((*p).into())).collect())
+    }
+
+    pub fn get_kinds(&self) -> Option<Vec<u64>> {
+        self.inner
+            .kinds
+            .as_ref()
+            .map(|kinds| kinds.iter().map(|k| k.as_u64()).collect())
+    }
+
+    /// Get `since` unix timestamp
+    pub fn get_since(&self) -> Option<u64> {
+        self.inner.since.map(|t| t.as_u64())
+    }
+
+    /// Get `until` unix timestamp
+    pub fn get_until(&self) -> Option<u64> {
+        self.inner.until.map(|t| t.as_u64())
+    }
+
+    pub fn get_limit(&self) -> Option<u64> {
+        self.inner.limit.map(|l| l as u64)
+    }
+
     /// Determine if `Filter` match given `Event`.
     ///
     /// The `search` filed is not supported yet!
@@ -393,6 +429,92 @@ impl Filter {
     pub fn as_json(&self) -> String {
         self.inner.as_json()
     }
+
+    /// Profile metadata of a public key (latest event only)
+    #[uniffi::constructor]
+    pub fn profile(public_key: &PublicKey) -> Self {
+        Self {
+            inner: nostr::Filter::profile(**public_key),
+        }
+    }
+
+    /// Contact list of a public key (latest event only)
+    #[uniffi::constructor]
+    pub fn contact_list(public_key: &PublicKey) -> Self {
+        Self {
+            inner: nostr::Filter::contact_list(**public_key),
+        }
+    }
+
+    /// Relay list of a public key (latest event only)
+    #[uniffi::constructor]
+    pub fn relay_list(public_key: &PublicKey) -> Self {
+        Self {
+            inner: nostr::Filter::relay_list(**public_key),
+        }
+    }
+
+    /// Text notes of the public keys, published after `since`
+    #[uniffi::constructor]
+    pub fn text_notes_by(public_keys: &[Arc<PublicKey>], since: &Timestamp) -> Self {
+        Self {
+            inner: nostr::Filter::text_notes_by(public_keys.iter().map(|pk| ***pk), **since),
+        }
+    }
+
+    /// Text notes replying to (or mentioning) an event
+    #[uniffi::constructor]
+    pub fn replies_to(event_id: &EventId) -> Self {
+        Self {
+            inner: nostr::Filter::replies_to(**event_id),
+        }
+    }
+
+    /// Reactions to an event
+    #[uniffi::constructor]
+    pub fn reactions_to(event_id: &EventId) -> Self {
+        Self {
+            inner: nostr::Filter::reactions_to(**event_id),
+        }
+    }
+
+    /// Zap receipts of an event
+    #[uniffi::constructor]
+    pub fn zaps_for_event(event_id: &EventId) -> Self {
+        Self {
+            inner: nostr::Filter::zaps_for(**event_id),
+        }
+    }
+
+    /// Zap receipts of a public key
+    #[uniffi::constructor]
+    pub fn zaps_for_pubkey(public_key: &PublicKey) -> Self {
+        Self {
+            inner: nostr::Filter::zaps_for(**public_key),
+        }
+    }
+
+    /// Gift wraps for a public key
+    ///
+    /// The `created_at` of gift wraps is randomized up to 2 days in the past:
+    /// if a `since` is needed, move it back by at least 2 days to not miss events.
+    #[uniffi::constructor]
+    pub fn gift_wraps_for(public_key: &PublicKey) -> Self {
+        Self {
+            inner: nostr::Filter::gift_wraps_for(**public_key),
+        }
+    }
+}
+
+/// Encrypted direct messages between two public keys, in both directions
+///
+/// Return two filters: the messages sent by `me` to `them` and the messages sent by `them` to `me`.
+#[uniffi::export]
+pub fn dms_with_filters(me: &PublicKey, them: &PublicKey) -> Vec<Arc<Filter>> {
+    nostr::Filter::dms_with(**me, **them)
+        .into_iter()
+        .map(|f| Arc::new(f.into()))
+        .collect()
 }
 
 #[derive(Record)]
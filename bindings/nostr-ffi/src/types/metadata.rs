@@ -116,7 +116,7 @@ impl Metadata {
         self.inner.as_json()
     }
 
-    pub fn set_name(self: Arc<Self>, name: String) -> Self {
+    pub fn name(self: Arc<Self>, name: String) -> Self {
         let mut builder = unwrap_or_clone_arc(self);
         builder.inner = builder.inner.name(name);
         builder
@@ -126,7 +126,7 @@ impl Metadata {
         self.inner.name.clone()
     }
 
-    pub fn set_display_name(self: Arc<Self>, display_name: String) -> Self {
+    pub fn display_name(self: Arc<Self>, display_name: String) -> Self {
         let mut builder = unwrap_or_clone_arc(self);
         builder.inner = builder.inner.display_name(display_name);
         builder
@@ -136,7 +136,7 @@ impl Metadata {
         self.inner.display_name.clone()
     }
 
-    pub fn set_about(self: Arc<Self>, about: String) -> Self {
+    pub fn about(self: Arc<Self>, about: String) -> Self {
         let mut builder = unwrap_or_clone_arc(self);
         builder.inner = builder.inner.about(about);
         builder
@@ -146,7 +146,7 @@ impl Metadata {
         self.inner.about.clone()
     }
 
-    pub fn set_website(self: Arc<Self>, website: String) -> Result<Self> {
+    pub fn website(self: Arc<Self>, website: String) -> Result<Self> {
         let website = Url::parse(&website)?;
         let mut builder = unwrap_or_clone_arc(self);
         builder.inner = builder.inner.website(website);
@@ -157,7 +157,7 @@ impl Metadata {
         self.inner.website.clone()
     }
 
-    pub fn set_picture(self: Arc<Self>, picture: String) -> Result<Self> {
+    pub fn picture(self: Arc<Self>, picture: String) -> Result<Self> {
         let picture = Url::parse(&picture)?;
         let mut builder = unwrap_or_clone_arc(self);
         builder.inner = builder.inner.picture(picture);
@@ -168,7 +168,7 @@ impl Metadata {
         self.inner.picture.clone()
     }
 
-    pub fn set_banner(self: Arc<Self>, banner: String) -> Result<Self> {
+    pub fn banner(self: Arc<Self>, banner: String) -> Result<Self> {
         let banner = Url::parse(&banner)?;
         let mut builder = unwrap_or_clone_arc(self);
         builder.inner = builder.inner.banner(banner);
@@ -179,7 +179,7 @@ impl Metadata {
         self.inner.banner.clone()
     }
 
-    pub fn set_nip05(self: Arc<Self>, nip05: String) -> Self {
+    pub fn nip05(self: Arc<Self>, nip05: String) -> Self {
         let mut builder = unwrap_or_clone_arc(self);
         builder.inner = builder.inner.nip05(nip05);
         builder
@@ -189,7 +189,7 @@ impl Metadata {
         self.inner.nip05.clone()
     }
 
-    pub fn set_lud06(self: Arc<Self>, lud06: String) -> Self {
+    pub fn lud06(self: Arc<Self>, lud06: String) -> Self {
         let mut builder = unwrap_or_clone_arc(self);
         builder.inner = builder.inner.lud06(lud06);
         builder
@@ -199,7 +199,7 @@ impl Metadata {
         self.inner.lud06.clone()
     }
 
-    pub fn set_lud16(self: Arc<Self>, lud16: String) -> Self {
+    pub fn lud16(self: Arc<Self>, lud16: String) -> Self {
         let mut builder = unwrap_or_clone_arc(self);
         builder.inner = builder.inner.lud16(lud16);
         builder
@@ -209,7 +209,7 @@ impl Metadata {
         self.inner.lud16.clone()
     }
 
-    pub fn set_custom_field(self: Arc<Self>, key: String, value: JsonValue) -> Result<Self> {
+    pub fn custom_field(self: Arc<Self>, key: String, value: JsonValue) -> Result<Self> {
         let value: Value = value.try_into()?;
         let mut builder = unwrap_or_clone_arc(self);
         builder.inner = builder.inner.custom_field(key, value);
@@ -222,4 +222,54 @@ impl Metadata {
             None => Ok(None),
         }
     }
+
+    /// Deprecated: use `name` instead. Will be removed in the next release.
+    pub fn set_name(self: Arc<Self>, name: String) -> Self {
+        self.name(name)
+    }
+
+    /// Deprecated: use `display_name` instead. Will be removed in the next release.
+    pub fn set_display_name(self: Arc<Self>, display_name: String) -> Self {
+        self.display_name(display_name)
+    }
+
+    /// Deprecated: use `about` instead. Will be removed in the next release.
+    pub fn set_about(self: Arc<Self>, about: String) -> Self {
+        self.about(about)
+    }
+
+    /// Deprecated: use `website` instead. Will be removed in the next release.
+    pub fn set_website(self: Arc<Self>, website: String) -> Result<Self> {
+        self.website(website)
+    }
+
+    /// Deprecated: use `picture` instead. Will be removed in the next release.
+    pub fn set_picture(self: Arc<Self>, picture: String) -> Result<Self> {
+        self.picture(picture)
+    }
+
+    /// Deprecated: use `banner` instead. Will be removed in the next release.
+    pub fn set_banner(self: Arc<Self>, banner: String) -> Result<Self> {
+        self.banner(banner)
+    }
+
+    /// Deprecated: use `nip05` instead. Will be removed in the next release.
+    pub fn set_nip05(self: Arc<Self>, nip05: String) -> Self {
+        self.nip05(nip05)
+    }
+
+    /// Deprecated: use `lud06` instead. Will be removed in the next release.
+    pub fn set_lud06(self: Arc<Self>, lud06: String) -> Self {
+        self.lud06(lud06)
+    }
+
+    /// Deprecated: use `lud16` instead. Will be removed in the next release.
+    pub fn set_lud16(self: Arc<Self>, lud16: String) -> Self {
+        self.lud16(lud16)
+    }
+
+    /// Deprecated: use `custom_field` instead. Will be removed in the next release.
+    pub fn set_custom_field(self: Arc<Self>, key: String, value: JsonValue) -> Result<Self> {
+        self.custom_field(key, value)
+    }
 }
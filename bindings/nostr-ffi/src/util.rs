@@ -4,12 +4,15 @@
 
 use std::collections::HashMap;
 use std::ops::Deref;
+use std::sync::Arc;
 
 use nostr::serde_json::{Number, Value};
 use nostr::util;
-use uniffi::Enum;
+use nostr::util::content;
+use uniffi::{Enum, Object, Record};
 
 use crate::error::Result;
+use crate::helper::unwrap_or_clone_arc;
 use crate::{NostrError, PublicKey, SecretKey};
 
 #[uniffi::export]
@@ -97,3 +100,151 @@ impl TryFrom<Value> for JsonValue {
         })
     }
 }
+
+/// Control characters handling
+///
+/// Newlines and tabs are never considered control characters.
+#[derive(Enum)]
+pub enum ControlChars {
+    /// Remove them
+    Strip,
+    /// Replace them with their escape sequence (ex. `\u{7}`)
+    Escape,
+}
+
+impl From<ControlChars> for content::ControlChars {
+    fn from(value: ControlChars) -> Self {
+        match value {
+            ControlChars::Strip => Self::Strip,
+            ControlChars::Escape => Self::Escape,
+        }
+    }
+}
+
+#[derive(Clone, Object)]
+pub struct SanitizeOptions {
+    inner: content::SanitizeOptions,
+}
+
+impl Deref for SanitizeOptions {
+    type Target = content::SanitizeOptions;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+#[uniffi::export]
+impl SanitizeOptions {
+    #[uniffi::constructor]
+    pub fn new() -> Self {
+        Self {
+            inner: content::SanitizeOptions::new(),
+        }
+    }
+
+    /// Control characters handling (default: strip)
+    pub fn control_chars(self: Arc<Self>, control_chars: ControlChars) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder.inner.control_chars(control_chars.into());
+        builder
+    }
+
+    /// Normalize to NFC (default: true)
+    pub fn normalize(self: Arc<Self>, normalize: bool) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder.inner.normalize(normalize);
+        builder
+    }
+
+    /// Remove bidi embedding, override and isolate characters (default: true)
+    pub fn remove_bidi_overrides(self: Arc<Self>, remove: bool) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder.inner.remove_bidi_overrides(remove);
+        builder
+    }
+
+    /// Remove zero-width characters, except the joiners required by emoji sequences (default: true)
+    pub fn remove_zero_width(self: Arc<Self>, remove: bool) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder.inner.remove_zero_width(remove);
+        builder
+    }
+
+    /// Max number of consecutive newlines (default: 2)
+    pub fn max_consecutive_newlines(self: Arc<Self>, max: Option<u64>) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder
+            .inner
+            .max_consecutive_newlines(max.map(|m| m as usize));
+        builder
+    }
+
+    /// Max number of consecutive combining marks (default: 8)
+    pub fn max_combining_marks(self: Arc<Self>, max: Option<u64>) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder.inner.max_combining_marks(max.map(|m| m as usize));
+        builder
+    }
+
+    /// Truncate to the max number of grapheme clusters (default: None)
+    pub fn max_graphemes(self: Arc<Self>, max: Option<u64>) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder.inner.max_graphemes(max.map(|m| m as usize));
+        builder
+    }
+}
+
+/// What was removed by `sanitize_content`
+#[derive(Record)]
+pub struct SanitizeReport {
+    /// Control characters removed or escaped
+    pub control_chars: u64,
+    /// Bidi characters removed
+    pub bidi_overrides: u64,
+    /// Zero-width characters removed
+    pub zero_width: u64,
+    /// Combining marks removed
+    pub combining_marks: u64,
+    /// Newlines removed
+    pub newlines: u64,
+    /// Content truncated
+    pub truncated: bool,
+}
+
+impl From<content::SanitizeReport> for SanitizeReport {
+    fn from(value: content::SanitizeReport) -> Self {
+        Self {
+            control_chars: value.control_chars as u64,
+            bidi_overrides: value.bidi_overrides as u64,
+            zero_width: value.zero_width as u64,
+            combining_marks: value.combining_marks as u64,
+            newlines: value.newlines as u64,
+            truncated: value.truncated,
+        }
+    }
+}
+
+#[derive(Record)]
+pub struct SanitizedContent {
+    pub content: String,
+    pub report: SanitizeReport,
+}
+
+/// Sanitize content for rendering
+///
+/// Same behavior of the Rust `nostr::util::content::sanitize`.
+#[uniffi::export]
+pub fn sanitize_content(content: &str, opts: &SanitizeOptions) -> SanitizedContent {
+    let (content, report) = content::sanitize(content, **opts);
+    SanitizedContent {
+        content,
+        report: report.into(),
+    }
+}
+
+/// Truncate to `n` grapheme clusters, appending `…` if truncated
+#[uniffi::export]
+pub fn truncate_graphemes(s: &str, n: u64) -> String {
+    content::truncate_graphemes(s, n as usize)
+}
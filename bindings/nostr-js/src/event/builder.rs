@@ -344,7 +344,7 @@ impl JsEventBuilder {
         pubkey_awarded: &JsPublicKey,
     ) -> Result<JsEventBuilder> {
         Ok(Self {
-            inner: EventBuilder::profile_badges(
+            inner: EventBuilder::profile_badges_from_events(
                 badge_definitions.into_iter().map(|e| e.into()).collect(),
                 badge_awards.into_iter().map(|e| e.into()).collect(),
                 pubkey_awarded.deref(),
@@ -382,20 +382,21 @@ impl JsEventBuilder {
         job_request: &JsEvent,
         status: JsDataVendingMachineStatus,
         extra_info: Option<String>,
-        amount_millisats: u64,
+        amount_millisats: Option<f64>,
         bolt11: Option<String>,
         payload: Option<String>,
-    ) -> Self {
-        Self {
+    ) -> Result<JsEventBuilder> {
+        Ok(Self {
             inner: EventBuilder::job_feedback(
                 job_request.deref(),
                 status.into(),
                 extra_info,
-                amount_millisats,
+                amount_millisats.map(|a| a as u64),
                 bolt11,
                 payload,
-            ),
-        }
+            )
+            .map_err(into_err)?,
+        })
     }
 
     #[wasm_bindgen(js_name = fileMetadata)]
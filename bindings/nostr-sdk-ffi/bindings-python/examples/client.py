@@ -31,7 +31,7 @@ client.connect()
 # Send an event using the Nostr Signer
 builder = EventBuilder.text_note("Test from Rust Nostr Python!", [])
 client.send_event_builder(builder)
-client.set_metadata(Metadata().set_name("Testing Rust Nostr"))
+client.set_metadata(Metadata().name("Testing Rust Nostr"))
 
 # Mine a POW event and sign it with custom keys
 custom_keys = Keys.generate() 
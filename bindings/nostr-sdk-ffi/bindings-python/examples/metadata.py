@@ -11,13 +11,13 @@ client.connect()
 
 # Set metadata
 metadata = Metadata()\
-    .set_name("username")\
-    .set_display_name("My Username")\
-    .set_about("Description")\
-    .set_picture("https://example.com/avatar.png")\
-    .set_banner("https://example.com/banner.png")\
-    .set_nip05("username@example.com")\
-    .set_lud16("yuki@getalby.com")
+    .name("username")\
+    .display_name("My Username")\
+    .about("Description")\
+    .picture("https://example.com/avatar.png")\
+    .banner("https://example.com/banner.png")\
+    .nip05("username@example.com")\
+    .lud16("yuki@getalby.com")
 
 print(f"Setting profile metadata for {keys.public_key().to_bech32()}...")
 print(metadata.as_json())
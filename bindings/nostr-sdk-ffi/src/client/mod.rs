@@ -4,42 +4,77 @@
 
 use std::collections::HashMap;
 use std::ops::Deref;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use async_utility::futures_util::stream;
 use async_utility::thread;
 use nostr_ffi::{
-    ClientMessage, Event, EventBuilder, EventId, FileMetadata, Filter, Metadata, PublicKey,
+    ClientMessage, Event, EventBuilder, EventId, FileMetadata, Filter, Kind, Metadata, PublicKey,
     Timestamp,
 };
+use nostr_sdk::client::pagination::PaginationCursor;
 use nostr_sdk::client::Client as ClientSdk;
 use nostr_sdk::pool::RelayPoolNotification as RelayPoolNotificationSdk;
-use nostr_sdk::{block_on, spawn_blocking, SubscriptionId, UncheckedUrl};
+use nostr_sdk::{block_on, spawn_blocking, JsonUtil, RelayMessage, SubscriptionId, UncheckedUrl};
 use uniffi::Object;
 
 mod builder;
 mod options;
+pub mod pagination;
+pub mod plan;
 pub mod signer;
 pub mod zapper;
 
 pub use self::builder::ClientBuilder;
 pub use self::options::Options;
+use self::pagination::{Pagination, PaginationSource};
+use self::plan::SendPlan;
 pub use self::signer::NostrSigner;
 use self::zapper::{ZapDetails, ZapEntity};
 use crate::abortable::AbortHandle;
 use crate::error::Result;
+use crate::pool::fetch::{FetchOutput, FetchPolicy};
+use crate::pool::AckStatus;
 use crate::relay::options::{NegentropyOptions, SubscribeAutoCloseOptions};
-use crate::relay::RelayOptions;
-use crate::{HandleNotification, NostrDatabase, Relay};
+use crate::relay::{RelayOptions, SubscriptionStats, TrafficReport};
+use crate::{
+    EoseHandler, EventHandler, HandleAckTimeout, HandleNotification, HandleOrderedEvent,
+    NostrDatabase, Relay,
+};
 
 #[derive(Object)]
 pub struct Client {
     inner: ClientSdk,
+    /// Listeners of the subscriptions created with `subscribe_async`
+    listeners: Mutex<HashMap<SubscriptionId, stream::AbortHandle>>,
 }
 
 impl From<ClientSdk> for Client {
     fn from(inner: ClientSdk) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            listeners: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Client {
+    /// Stop the listener of a subscription created with `subscribe_async`
+    fn abort_listener(&self, id: &SubscriptionId) {
+        if let Ok(mut listeners) = self.listeners.lock() {
+            if let Some(handle) = listeners.remove(id) {
+                handle.abort();
+            }
+        }
+    }
+
+    fn abort_all_listeners(&self) {
+        if let Ok(mut listeners) = self.listeners.lock() {
+            for (_, handle) in listeners.drain() {
+                handle.abort();
+            }
+        }
     }
 }
 
@@ -52,17 +87,16 @@ impl Client {
 
     #[uniffi::constructor]
     pub fn with_opts(signer: Option<Arc<NostrSigner>>, opts: Arc<Options>) -> Self {
-        Self {
-            inner: match signer {
-                Some(signer) => ClientSdk::with_opts(
-                    signer.as_ref().deref().clone(),
-                    opts.as_ref().deref().clone(),
-                ),
-                None => nostr_sdk::ClientBuilder::new()
-                    .opts(opts.as_ref().deref().clone())
-                    .build(),
-            },
-        }
+        let inner: ClientSdk = match signer {
+            Some(signer) => ClientSdk::with_opts(
+                signer.as_ref().deref().clone(),
+                opts.as_ref().deref().clone(),
+            ),
+            None => nostr_sdk::ClientBuilder::new()
+                .opts(opts.as_ref().deref().clone())
+                .build(),
+        };
+        inner.into()
     }
 
     pub fn update_difficulty(&self, difficulty: u8) {
@@ -182,6 +216,29 @@ impl Client {
         })
     }
 
+    /// Get the incoming traffic stats of a subscription, summed over all relays
+    pub fn subscription_stats(&self, id: String) -> SubscriptionStats {
+        block_on(async move {
+            self.inner
+                .subscription_stats(&SubscriptionId::new(id))
+                .await
+                .into()
+        })
+    }
+
+    /// Get the incoming traffic stats of all the subscriptions, summed over all relays
+    pub fn traffic_report(&self) -> TrafficReport {
+        block_on(async move { self.inner.traffic_report().await.into() })
+    }
+
+    /// Reset the incoming traffic stats of a subscription or, if `None`, of all the subscriptions
+    pub fn reset_traffic_stats(&self, id: Option<String>) {
+        block_on(async move {
+            let id: Option<SubscriptionId> = id.map(SubscriptionId::new);
+            self.inner.reset_traffic_stats(id.as_ref()).await
+        })
+    }
+
     /// Subscribe to filters
     ///
     /// ### Auto-closing subscription
@@ -227,14 +284,13 @@ impl Client {
     }
 
     pub fn unsubscribe(&self, subscription_id: String) {
-        block_on(async move {
-            self.inner
-                .unsubscribe(SubscriptionId::new(subscription_id))
-                .await
-        })
+        let id = SubscriptionId::new(subscription_id);
+        self.abort_listener(&id);
+        block_on(async move { self.inner.unsubscribe(id).await })
     }
 
     pub fn unsubscribe_all(&self) {
+        self.abort_all_listeners();
         block_on(async move { self.inner.unsubscribe_all().await })
     }
 
@@ -258,6 +314,26 @@ impl Client {
         })
     }
 
+    /// Page through the events history, from the newest to the oldest events
+    ///
+    /// Pass a `cursor` got from `Pagination::cursor` to resume a previous pagination.
+    pub fn paginate(
+        &self,
+        filter: Arc<Filter>,
+        page_size: u64,
+        source: PaginationSource,
+        cursor: Option<String>,
+    ) -> Result<Arc<Pagination>> {
+        let mut pagination = self
+            .inner
+            .paginate(filter.as_ref().deref().clone(), page_size as usize)
+            .source(source.into());
+        if let Some(cursor) = cursor {
+            pagination = pagination.resume(PaginationCursor::from_json(cursor)?);
+        }
+        Ok(Arc::new(pagination.into()))
+    }
+
     /// Get events of filters from specific relays
     ///
     /// Get events both from **local database** and **relays**
@@ -284,6 +360,67 @@ impl Client {
         })
     }
 
+    /// Full-text search (NIP50)
+    ///
+    /// Query only the relays that advertise NIP50 support. Results vary by relay implementation.
+    pub fn search(
+        &self,
+        query: String,
+        kinds: Vec<Arc<Kind>>,
+        limit: u32,
+        timeout: Duration,
+    ) -> Result<Vec<Arc<Event>>> {
+        let kinds = kinds.into_iter().map(|k| **k).collect();
+        block_on(async move {
+            Ok(self
+                .inner
+                .search(query, kinds, limit, timeout)
+                .await?
+                .into_iter()
+                .map(|e| Arc::new(e.into()))
+                .collect())
+        })
+    }
+
+    /// Search profiles (NIP50)
+    pub fn search_profiles(
+        &self,
+        query: String,
+        limit: u32,
+        timeout: Duration,
+    ) -> Result<Vec<Arc<Metadata>>> {
+        block_on(async move {
+            Ok(self
+                .inner
+                .search_profiles(query, limit, timeout)
+                .await?
+                .into_iter()
+                .map(|m| Arc::new(m.into()))
+                .collect())
+        })
+    }
+
+    /// Get events of filters from **all relays**, following a fetch policy
+    ///
+    /// Allow to return early with partial results and a per-relay report.
+    pub fn get_events_with_policy(
+        &self,
+        filters: Vec<Arc<Filter>>,
+        policy: &FetchPolicy,
+    ) -> Result<FetchOutput> {
+        let filters = filters
+            .into_iter()
+            .map(|f| f.as_ref().deref().clone())
+            .collect();
+        block_on(async move {
+            Ok(self
+                .inner
+                .get_events_with_policy(filters, **policy)
+                .await?
+                .into())
+        })
+    }
+
     pub fn send_msg(&self, msg: Arc<ClientMessage>) -> Result<()> {
         block_on(async move { Ok(self.inner.send_msg(msg.as_ref().deref().clone()).await?) })
     }
@@ -319,6 +456,23 @@ impl Client {
         })
     }
 
+    /// Compute where an event would be sent, without sending it
+    pub fn plan_send(&self, event: Arc<Event>) -> Arc<SendPlan> {
+        block_on(async move { Arc::new(self.inner.plan_send(event.as_ref().deref()).await.into()) })
+    }
+
+    /// Send the event exactly as planned by `plan_send`
+    pub fn execute(&self, plan: Arc<SendPlan>) -> Result<Arc<EventId>> {
+        block_on(async move {
+            Ok(Arc::new(
+                self.inner
+                    .execute(plan.as_ref().deref().clone())
+                    .await?
+                    .into(),
+            ))
+        })
+    }
+
     /// Signs the `EventBuilder` into an `Event` using the `NostrSigner`
     pub fn sign_event_builder(&self, builder: Arc<EventBuilder>) -> Result<Arc<Event>> {
         block_on(async move {
@@ -557,4 +711,151 @@ impl Client {
         })?;
         Ok(Arc::new(handle.into()))
     }
+
+    /// Handle the events of subscriptions with reordering enabled, in `created_at` order
+    ///
+    /// Check `SubscribeOptions::reorder`.
+    ///
+    /// **This method spawn a thread**, so ensure to keep up the app after calling this (if needed).
+    pub fn handle_ordered_events(
+        self: Arc<Self>,
+        handler: Box<dyn HandleOrderedEvent>,
+    ) -> Result<Arc<AbortHandle>> {
+        let handle = thread::abortable(async move {
+            let handler = Arc::new(handler);
+            self.inner
+                .handle_notifications(|notification| async {
+                    if let RelayPoolNotificationSdk::OrderedEvent {
+                        relay_url,
+                        subscription_id,
+                        event,
+                        out_of_order,
+                    } = notification
+                    {
+                        let h = handler.clone();
+                        let _ = spawn_blocking(move || {
+                            h.handle(
+                                relay_url.to_string(),
+                                subscription_id.to_string(),
+                                Arc::new((*event).into()),
+                                out_of_order,
+                            )
+                        })
+                        .await;
+                    }
+                    Ok(false)
+                })
+                .await
+        })?;
+        Ok(Arc::new(handle.into()))
+    }
+
+    /// Get the acknowledgement (`OK` message) status of a sent event, per relay
+    pub fn ack_status(&self, event_id: &EventId) -> HashMap<String, AckStatus> {
+        self.inner
+            .ack_status(event_id.deref())
+            .into_iter()
+            .map(|(u, s)| (u.to_string(), s.into()))
+            .collect()
+    }
+
+    /// Handle the sent events not acknowledged by a relay within the timeout
+    ///
+    /// **This method spawn a thread**, so ensure to keep up the app after calling this (if needed).
+    pub fn handle_ack_timeouts(
+        self: Arc<Self>,
+        handler: Box<dyn HandleAckTimeout>,
+    ) -> Result<Arc<AbortHandle>> {
+        let handle = thread::abortable(async move {
+            let handler = Arc::new(handler);
+            self.inner
+                .handle_notifications(|notification| async {
+                    if let RelayPoolNotificationSdk::AckTimeout {
+                        relay_url,
+                        event_id,
+                    } = notification
+                    {
+                        let h = handler.clone();
+                        let _ = spawn_blocking(move || {
+                            h.handle(relay_url.to_string(), Arc::new(event_id.into()))
+                        })
+                        .await;
+                    }
+                    Ok(false)
+                })
+                .await
+        })?;
+        Ok(Arc::new(handle.into()))
+    }
+}
+
+#[uniffi::export(async_runtime = "tokio")]
+impl Client {
+    /// Subscribe to filters, calling the handlers for the events and the EOSE of this subscription only
+    ///
+    /// The handlers are called on the Rust runtime thread pool, so they must be thread-safe.
+    /// The listener is stopped by `unsubscribe_async`, `unsubscribe` or `unsubscribe_all`.
+    pub async fn subscribe_async(
+        &self,
+        filters: Vec<Arc<Filter>>,
+        on_event: Arc<dyn EventHandler>,
+        on_eose: Arc<dyn EoseHandler>,
+    ) -> Result<Arc<nostr_ffi::SubscriptionId>> {
+        let filters = filters
+            .into_iter()
+            .map(|f| f.as_ref().deref().clone())
+            .collect();
+        let id = SubscriptionId::generate();
+
+        // Listen before subscribing, to not miss any event
+        let mut notifications = self.inner.notifications();
+        let sub_id = id.clone();
+        let handle = thread::abortable(async move {
+            while let Ok(notification) = notifications.recv().await {
+                match notification {
+                    RelayPoolNotificationSdk::Event {
+                        relay_url,
+                        subscription_id,
+                        event,
+                    } if subscription_id == sub_id => {
+                        let h = on_event.clone();
+                        let _ = spawn_blocking(move || {
+                            h.on_event(relay_url.to_string(), Arc::new((*event).into()))
+                        })
+                        .await;
+                    }
+                    RelayPoolNotificationSdk::Message {
+                        relay_url,
+                        message: RelayMessage::EndOfStoredEvents(subscription_id),
+                    } if subscription_id == sub_id => {
+                        let h = on_eose.clone();
+                        let _ = spawn_blocking(move || {
+                            h.on_eose(relay_url.to_string(), Arc::new(subscription_id.into()))
+                        })
+                        .await;
+                    }
+                    RelayPoolNotificationSdk::Stop | RelayPoolNotificationSdk::Shutdown => break,
+                    _ => (),
+                }
+            }
+        })?;
+
+        if let Ok(mut listeners) = self.listeners.lock() {
+            listeners.insert(id.clone(), handle);
+        }
+
+        self.inner
+            .subscribe_with_id(id.clone(), filters, None)
+            .await;
+
+        Ok(Arc::new(id.into()))
+    }
+
+    /// Close a subscription created with `subscribe_async` and stop its listener
+    pub async fn unsubscribe_async(&self, id: Arc<nostr_ffi::SubscriptionId>) -> Result<()> {
+        let id: SubscriptionId = id.as_ref().deref().clone();
+        self.abort_listener(&id);
+        self.inner.unsubscribe(id).await;
+        Ok(())
+    }
 }
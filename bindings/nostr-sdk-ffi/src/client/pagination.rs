@@ -0,0 +1,69 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+use std::sync::Arc;
+
+use nostr_ffi::Event;
+use nostr_sdk::client::pagination;
+use nostr_sdk::{block_on, JsonUtil};
+use uniffi::{Enum, Object};
+
+use crate::error::Result;
+
+#[derive(Enum)]
+pub enum PaginationSource {
+    /// Relays (and local database)
+    Relays,
+    /// Local database only
+    Database,
+}
+
+impl From<PaginationSource> for pagination::PaginationSource {
+    fn from(value: PaginationSource) -> Self {
+        match value {
+            PaginationSource::Relays => Self::Relays,
+            PaginationSource::Database => Self::Database,
+        }
+    }
+}
+
+/// Pagination through the events history, from the newest to the oldest
+#[derive(Object)]
+pub struct Pagination {
+    inner: pagination::Pagination,
+}
+
+impl From<pagination::Pagination> for Pagination {
+    fn from(inner: pagination::Pagination) -> Self {
+        Self { inner }
+    }
+}
+
+#[uniffi::export]
+impl Pagination {
+    /// Fetch the next page
+    ///
+    /// Return an empty list if the end of history was reached.
+    pub fn next_page(&self) -> Result<Vec<Arc<Event>>> {
+        block_on(async move {
+            Ok(self
+                .inner
+                .next_page()
+                .await?
+                .into_iter()
+                .map(|e| Arc::new(e.into()))
+                .collect())
+        })
+    }
+
+    /// Check if there may be more events to fetch
+    pub fn has_more(&self) -> bool {
+        block_on(async move { self.inner.has_more().await })
+    }
+
+    /// Get the opaque cursor, to persist and resume the pagination later
+    pub fn cursor(&self) -> String {
+        block_on(async move { self.inner.cursor().await.as_json() })
+    }
+}
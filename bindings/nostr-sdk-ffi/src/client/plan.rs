@@ -0,0 +1,97 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+use std::ops::Deref;
+use std::sync::Arc;
+
+use nostr_ffi::Event;
+use nostr_sdk::client::plan;
+use uniffi::{Enum, Object, Record};
+
+#[derive(Enum)]
+pub enum SendPlanReason {
+    /// Relay has the `WRITE` flag
+    Write,
+    /// Relay has the `WRITE` flag but the hourly bandwidth cap is reached
+    WriteRateLimited,
+    /// Relay doesn't have the `WRITE` flag
+    WriteDisabled,
+    /// Relay is disconnected and has a poor connection history
+    Unreachable,
+}
+
+impl From<plan::SendPlanReason> for SendPlanReason {
+    fn from(value: plan::SendPlanReason) -> Self {
+        match value {
+            plan::SendPlanReason::Write => Self::Write,
+            plan::SendPlanReason::WriteRateLimited => Self::WriteRateLimited,
+            plan::SendPlanReason::WriteDisabled => Self::WriteDisabled,
+            plan::SendPlanReason::Unreachable => Self::Unreachable,
+        }
+    }
+}
+
+#[derive(Record)]
+pub struct RelaySendPlan {
+    pub url: String,
+    pub targeted: bool,
+    pub reason: SendPlanReason,
+}
+
+impl From<plan::RelaySendPlan> for RelaySendPlan {
+    fn from(value: plan::RelaySendPlan) -> Self {
+        Self {
+            url: value.url.to_string(),
+            targeted: value.is_targeted(),
+            reason: value.reason.into(),
+        }
+    }
+}
+
+/// Where an event would be sent
+#[derive(Object)]
+pub struct SendPlan {
+    inner: plan::SendPlan,
+}
+
+impl Deref for SendPlan {
+    type Target = plan::SendPlan;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl From<plan::SendPlan> for SendPlan {
+    fn from(inner: plan::SendPlan) -> Self {
+        Self { inner }
+    }
+}
+
+#[uniffi::export]
+impl SendPlan {
+    pub fn event(&self) -> Arc<Event> {
+        Arc::new(self.inner.event().clone().into())
+    }
+
+    /// Get routing decision for every relay in the pool
+    pub fn relays(&self) -> Vec<RelaySendPlan> {
+        self.inner
+            .relays()
+            .iter()
+            .cloned()
+            .map(|r| r.into())
+            .collect()
+    }
+
+    /// Get urls of the relays that would be targeted
+    pub fn targets(&self) -> Vec<String> {
+        self.inner.targets().map(|u| u.to_string()).collect()
+    }
+
+    /// Check if the event would be put in the offline queue instead of being sent
+    pub fn is_queued_offline(&self) -> bool {
+        self.inner.is_queued_offline()
+    }
+}
@@ -63,6 +63,16 @@ impl NostrSigner {
         })
     }
 
+    pub fn sign_event_builder_pow(&self, builder: &EventBuilder, difficulty: u8) -> Result<Event> {
+        block_on(async move {
+            Ok(self
+                .inner
+                .sign_event_builder_pow(builder.deref().clone(), difficulty)
+                .await?
+                .into())
+        })
+    }
+
     pub fn sign_event(&self, unsigned_event: &UnsignedEvent) -> Result<Event> {
         block_on(async move {
             Ok(self
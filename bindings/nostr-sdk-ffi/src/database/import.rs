@@ -0,0 +1,53 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use nostr_sdk::database;
+use uniffi::Record;
+
+#[uniffi::export(callback_interface)]
+pub trait ImportProgressCallback: Send + Sync + Debug {
+    fn on_progress(&self, read: u64, valid: u64, skipped: u64);
+}
+
+#[derive(Record)]
+pub struct SkippedRecord {
+    pub line: u64,
+    pub error: String,
+}
+
+impl From<database::SkippedRecord> for SkippedRecord {
+    fn from(value: database::SkippedRecord) -> Self {
+        Self {
+            line: value.line as u64,
+            error: value.error,
+        }
+    }
+}
+
+#[derive(Record)]
+pub struct ImportReport {
+    pub read: u64,
+    /// Valid events per kind
+    pub kinds: HashMap<u64, u64>,
+    pub skipped: Vec<SkippedRecord>,
+    pub dry_run: bool,
+}
+
+impl From<database::ImportReport> for ImportReport {
+    fn from(value: database::ImportReport) -> Self {
+        Self {
+            read: value.read as u64,
+            kinds: value
+                .kinds
+                .into_iter()
+                .map(|(kind, count)| (kind.as_u64(), count as u64))
+                .collect(),
+            skipped: value.skipped.into_iter().map(|s| s.into()).collect(),
+            dry_run: value.dry_run,
+        }
+    }
+}
@@ -2,17 +2,22 @@
 // Copyright (c) 2023-2024 Rust Nostr Developers
 // Distributed under the MIT software license
 
+use std::fs::File;
 use std::ops::Deref;
 use std::sync::Arc;
 
 use nostr_ffi::{Event, EventId, Filter, PublicKey};
-use nostr_sdk::database::{DynNostrDatabase, IntoNostrDatabase, NostrDatabaseExt, Order};
+use nostr_sdk::database::{
+    self, DynNostrDatabase, ImportError, ImportOptions, IntoNostrDatabase, NostrDatabaseExt, Order,
+};
 use nostr_sdk::{block_on, SQLiteDatabase};
 use uniffi::Object;
 
 pub mod custom;
+pub mod import;
 
 use self::custom::{CustomNostrDatabase, IntermediateCustomNostrDatabase};
+use self::import::{ImportProgressCallback, ImportReport};
 use crate::error::Result;
 use crate::profile::Profile;
 
@@ -69,6 +74,16 @@ impl NostrDatabase {
         })
     }
 
+    /// Get the relays that delivered the event (provenance)
+    ///
+    /// Return an empty list if the event was never seen.
+    pub fn event_seen_on(&self, event_id: &EventId) -> Result<Vec<String>> {
+        block_on(async move {
+            let relays = self.inner.event_seen_on(**event_id).await?;
+            Ok(relays.into_iter().map(|u| u.to_string()).collect())
+        })
+    }
+
     /// Get [`Event`] by [`EventId`]
     pub fn event_by_id(&self, event_id: &EventId) -> Result<Arc<Event>> {
         block_on(async move { Ok(Arc::new(self.inner.event_by_id(**event_id).await?.into())) })
@@ -108,7 +123,55 @@ impl NostrDatabase {
         block_on(async move { Ok(self.inner.wipe().await?) })
     }
 
+    /// Import events from a backup file of another client (JSON Lines or JSON array)
+    ///
+    /// Invalid records are skipped and reported with their line number.
+    /// If `dry_run` is `true`, events are only counted.
+    pub fn import_backup(
+        &self,
+        path: String,
+        verify: bool,
+        dry_run: bool,
+        progress: Option<Box<dyn ImportProgressCallback>>,
+    ) -> Result<ImportReport> {
+        let file = File::open(path).map_err(ImportError::from)?;
+        let mut opts = ImportOptions::new().verify(verify).dry_run(dry_run);
+        if let Some(progress) = progress {
+            opts = opts.progress(move |p| {
+                progress.on_progress(p.read as u64, p.valid as u64, p.skipped as u64)
+            });
+        }
+        block_on(async move {
+            let report = database::import_events(self.inner.as_ref(), file, opts).await?;
+            Ok(report.into())
+        })
+    }
+
     pub fn profile(&self, public_key: &PublicKey) -> Result<Arc<Profile>> {
         block_on(async move { Ok(Arc::new(self.inner.profile(**public_key).await?.into())) })
     }
+
+    /// Get IDs of stored events starting with `prefix` (`hex` or `note` bech32)
+    pub fn event_ids_with_prefix(&self, prefix: String) -> Result<Vec<Arc<EventId>>> {
+        block_on(async move {
+            Ok(self
+                .inner
+                .event_ids_with_prefix(&prefix)
+                .await?
+                .into_iter()
+                .map(|id| Arc::new(id.into()))
+                .collect())
+        })
+    }
+
+    /// Resolve a short event ID (`hex` or `note` bech32 prefix)
+    ///
+    /// Return an error if no event or more than one event match.
+    pub fn event_id_by_prefix(&self, prefix: String) -> Result<Arc<EventId>> {
+        block_on(async move {
+            Ok(Arc::new(
+                self.inner.event_id_by_prefix(&prefix).await?.into(),
+            ))
+        })
+    }
 }
@@ -74,6 +74,12 @@ impl From<nostr_sdk::database::DatabaseError> for NostrSdkError {
     }
 }
 
+impl From<nostr_sdk::database::ImportError> for NostrSdkError {
+    fn from(e: nostr_sdk::database::ImportError) -> NostrSdkError {
+        Self::Generic(e.to_string())
+    }
+}
+
 impl From<nostr_sdk::SQLiteError> for NostrSdkError {
     fn from(e: nostr_sdk::SQLiteError) -> NostrSdkError {
         Self::Generic(e.to_string())
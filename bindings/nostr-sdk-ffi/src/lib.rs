@@ -26,7 +26,9 @@ pub use self::client::{Client, ClientBuilder, Options};
 pub use self::database::NostrDatabase;
 pub use self::error::NostrSdkError;
 pub use self::logger::{init_logger, LogLevel};
-pub use self::notifications::HandleNotification;
+pub use self::notifications::{
+    EoseHandler, EventHandler, HandleAckTimeout, HandleNotification, HandleOrderedEvent,
+};
 pub use self::relay::{Relay, RelayConnectionStats, RelayStatus};
 
 uniffi::setup_scaffolding!("nostr_sdk");
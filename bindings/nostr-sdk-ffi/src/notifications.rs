@@ -5,10 +5,45 @@
 use std::fmt::Debug;
 use std::sync::Arc;
 
-use nostr_ffi::{Event, RelayMessage};
+use nostr_ffi::{Event, EventId, RelayMessage, SubscriptionId};
 
 #[uniffi::export(callback_interface)]
 pub trait HandleNotification: Send + Sync + Debug {
     fn handle_msg(&self, relay_url: String, msg: Arc<RelayMessage>);
     fn handle(&self, relay_url: String, subscription_id: String, event: Arc<Event>);
 }
+
+/// Handler of the events of subscriptions with reordering enabled
+#[uniffi::export(callback_interface)]
+pub trait HandleOrderedEvent: Send + Sync + Debug {
+    /// `out_of_order` is `true` if the event is older than an event already delivered
+    fn handle(
+        &self,
+        relay_url: String,
+        subscription_id: String,
+        event: Arc<Event>,
+        out_of_order: bool,
+    );
+}
+
+/// Handler of the sent events not acknowledged by a relay within the timeout
+#[uniffi::export(callback_interface)]
+pub trait HandleAckTimeout: Send + Sync + Debug {
+    fn handle(&self, relay_url: String, event_id: Arc<EventId>);
+}
+
+/// Handler of the events of a subscription (check `Client::subscribe_async`)
+///
+/// Called on the Rust runtime thread pool: the implementation must be thread-safe.
+#[uniffi::export(with_foreign)]
+pub trait EventHandler: Send + Sync {
+    fn on_event(&self, relay_url: String, event: Arc<Event>);
+}
+
+/// Handler of the EOSE of a subscription (check `Client::subscribe_async`)
+///
+/// Called on the Rust runtime thread pool: the implementation must be thread-safe.
+#[uniffi::export(with_foreign)]
+pub trait EoseHandler: Send + Sync {
+    fn on_eose(&self, relay_url: String, sub_id: Arc<SubscriptionId>);
+}
@@ -0,0 +1,136 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
+
+use nostr_ffi::helper::unwrap_or_clone_arc;
+use nostr_ffi::Event;
+use nostr_sdk::pool::fetch;
+use uniffi::{Enum, Object, Record};
+
+/// Fetch policy
+#[derive(Clone, Object)]
+pub struct FetchPolicy {
+    inner: fetch::FetchPolicy,
+}
+
+impl Deref for FetchPolicy {
+    type Target = fetch::FetchPolicy;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+#[uniffi::export]
+impl FetchPolicy {
+    /// New default fetch policy
+    #[uniffi::constructor]
+    pub fn new() -> Self {
+        Self {
+            inner: fetch::FetchPolicy::default(),
+        }
+    }
+
+    /// Soft deadline (default: None)
+    ///
+    /// After this time, return the events received so far if at least one relay sent the EOSE.
+    pub fn soft_deadline(self: Arc<Self>, deadline: Option<Duration>) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder.inner.soft_deadline(deadline);
+        builder
+    }
+
+    /// Hard deadline (default: 10 secs)
+    pub fn hard_deadline(self: Arc<Self>, deadline: Duration) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder.inner.hard_deadline(deadline);
+        builder
+    }
+}
+
+#[derive(Enum)]
+pub enum RelayFetchStatus {
+    Pending,
+    Eose,
+    TimedOut,
+    Error { message: String },
+}
+
+impl From<fetch::RelayFetchStatus> for RelayFetchStatus {
+    fn from(value: fetch::RelayFetchStatus) -> Self {
+        match value {
+            fetch::RelayFetchStatus::Pending => Self::Pending,
+            fetch::RelayFetchStatus::Eose => Self::Eose,
+            fetch::RelayFetchStatus::TimedOut => Self::TimedOut,
+            fetch::RelayFetchStatus::Error(message) => Self::Error { message },
+        }
+    }
+}
+
+#[derive(Record)]
+pub struct RelayFetchReport {
+    pub status: RelayFetchStatus,
+    pub events: u64,
+}
+
+impl From<fetch::RelayFetchReport> for RelayFetchReport {
+    fn from(value: fetch::RelayFetchReport) -> Self {
+        Self {
+            status: value.status.into(),
+            events: value.events as u64,
+        }
+    }
+}
+
+#[derive(Record)]
+pub struct FetchReport {
+    pub relays: HashMap<String, RelayFetchReport>,
+}
+
+impl From<fetch::FetchReport> for FetchReport {
+    fn from(value: fetch::FetchReport) -> Self {
+        Self {
+            relays: value
+                .relays
+                .into_iter()
+                .map(|(url, report)| (url.to_string(), report.into()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Record)]
+pub struct FetchOutput {
+    pub events: Vec<Arc<Event>>,
+    pub report: FetchReport,
+    /// Relays that delivered each event (key: event ID hex)
+    pub seen_on: HashMap<String, Vec<String>>,
+}
+
+impl From<fetch::FetchOutput> for FetchOutput {
+    fn from(value: fetch::FetchOutput) -> Self {
+        Self {
+            events: value
+                .events
+                .into_iter()
+                .map(|e| Arc::new(e.into()))
+                .collect(),
+            report: value.report.into(),
+            seen_on: value
+                .seen_on
+                .into_iter()
+                .map(|(id, urls)| {
+                    (
+                        id.to_hex(),
+                        urls.into_iter().map(|u| u.to_string()).collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
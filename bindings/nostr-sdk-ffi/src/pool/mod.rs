@@ -11,14 +11,41 @@ use async_utility::thread;
 use nostr_ffi::{ClientMessage, Event, EventId, Filter};
 use nostr_sdk::database::DynNostrDatabase;
 use nostr_sdk::{block_on, spawn_blocking, RelayPoolOptions, SubscriptionId};
-use uniffi::Object;
+use uniffi::{Enum, Object};
 
+pub mod fetch;
+
+use self::fetch::{FetchOutput, FetchPolicy};
 use crate::error::Result;
 use crate::negentropy::NegentropyItem;
 use crate::relay::options::{FilterOptions, NegentropyOptions};
 use crate::relay::{RelayOptions, RelaySendOptions, SubscribeOptions};
 use crate::{HandleNotification, NostrDatabase, Relay};
 
+/// Acknowledgement status of an event sent to a relay
+#[derive(Enum)]
+pub enum AckStatus {
+    /// Waiting for the `OK` message
+    Pending,
+    /// Accepted by the relay
+    Accepted,
+    /// Rejected by the relay
+    Rejected { reason: String },
+    /// No `OK` message received within the timeout
+    TimedOut,
+}
+
+impl From<nostr_sdk::AckStatus> for AckStatus {
+    fn from(value: nostr_sdk::AckStatus) -> Self {
+        match value {
+            nostr_sdk::AckStatus::Pending => Self::Pending,
+            nostr_sdk::AckStatus::Accepted => Self::Accepted,
+            nostr_sdk::AckStatus::Rejected(reason) => Self::Rejected { reason },
+            nostr_sdk::AckStatus::TimedOut => Self::TimedOut,
+        }
+    }
+}
+
 #[derive(Object)]
 pub struct RelayPool {
     inner: nostr_sdk::RelayPool,
@@ -236,6 +263,15 @@ impl RelayPool {
         block_on(async move { Ok(self.inner.batch_event_to(urls, events, **opts).await?) })
     }
 
+    /// Get the acknowledgement (`OK` message) status of a sent event, per relay
+    pub fn ack_status(&self, event_id: &EventId) -> HashMap<String, AckStatus> {
+        self.inner
+            .ack_status(event_id.deref())
+            .into_iter()
+            .map(|(u, s)| (u.to_string(), s.into()))
+            .collect()
+    }
+
     /// Subscribe to filters
     ///
     /// ### Auto-closing subscription
@@ -350,6 +386,28 @@ impl RelayPool {
         })
     }
 
+    /// Get events of filters from **specific relays**, following a fetch policy
+    ///
+    /// Allow to return early with partial results and a per-relay report.
+    pub fn get_events_with_policy(
+        &self,
+        urls: Vec<String>,
+        filters: Vec<Arc<Filter>>,
+        policy: &FetchPolicy,
+    ) -> Result<FetchOutput> {
+        block_on(async move {
+            let filters = filters
+                .into_iter()
+                .map(|f| f.as_ref().deref().clone())
+                .collect();
+            Ok(self
+                .inner
+                .get_events_with_policy(urls, filters, **policy)
+                .await?
+                .into())
+        })
+    }
+
     /// Negentropy reconciliation
     ///
     /// Use events stored in database
@@ -13,15 +13,21 @@ use nostr_sdk::{block_on, pool, FilterOptions, SubscriptionId, Url};
 use uniffi::Object;
 
 pub mod limits;
+pub mod notice;
 pub mod options;
+pub mod probe;
 pub mod stats;
 pub mod status;
+pub mod traffic;
 
 pub use self::limits::RelayLimits;
+pub use self::notice::RelayNotice;
 use self::options::NegentropyOptions;
 pub use self::options::{RelayOptions, RelaySendOptions, SubscribeOptions};
+pub use self::probe::{probe_relay, ProbeFailure, ProbeOptions, ProbeReport, TlsCertificate};
 pub use self::stats::RelayConnectionStats;
 pub use self::status::RelayStatus;
+pub use self::traffic::{SubscriptionStats, TrafficReport};
 use crate::error::Result;
 use crate::negentropy::NegentropyItem;
 use crate::NostrDatabase;
@@ -137,6 +143,28 @@ impl Relay {
         self.inner.queue() as u64
     }
 
+    /// Get number of bytes sent in the current hour
+    pub fn bytes_sent_this_hour(&self) -> u64 {
+        self.inner.bytes_sent_this_hour()
+    }
+
+    /// Check if outgoing messages are held because the hourly bandwidth cap is reached
+    pub fn send_rate_limited(&self) -> bool {
+        self.inner.send_rate_limited()
+    }
+
+    /// Get the last `NOTICE` and `CLOSED` messages received from the relay, newest first
+    pub fn recent_notices(&self) -> Vec<RelayNotice> {
+        block_on(async move {
+            self.inner
+                .recent_notices()
+                .await
+                .into_iter()
+                .map(|n| n.into())
+                .collect()
+        })
+    }
+
     // TODO: add notifications
 
     /// Connect to relay and keep alive connection
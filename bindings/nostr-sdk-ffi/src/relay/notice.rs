@@ -0,0 +1,31 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+use std::sync::Arc;
+
+use nostr_ffi::Timestamp;
+use nostr_sdk::pool;
+use uniffi::Record;
+
+/// `NOTICE` or `CLOSED` message received from a relay
+#[derive(Record)]
+pub struct RelayNotice {
+    pub message: String,
+    pub timestamp: Arc<Timestamp>,
+    /// Closed subscription ID (`None` for `NOTICE` messages)
+    pub subscription_id: Option<String>,
+    /// Machine-readable prefix of the message (ex. `rate-limited`), if any
+    pub prefix: Option<String>,
+}
+
+impl From<pool::RelayNotice> for RelayNotice {
+    fn from(value: pool::RelayNotice) -> Self {
+        Self {
+            message: value.message,
+            timestamp: Arc::new(value.timestamp.into()),
+            subscription_id: value.subscription_id.map(|id| id.to_string()),
+            prefix: value.prefix.map(|p| p.to_string()),
+        }
+    }
+}
@@ -137,6 +137,50 @@ impl RelayOptions {
         builder.inner = builder.inner.limits(**limits);
         builder
     }
+
+    /// Max number of active subscriptions (default: None)
+    ///
+    /// Used only if the relay not advertise the `max_subscriptions` limitation in its NIP11 document.
+    /// If `None`, the number of subscriptions is unlimited.
+    pub fn max_subscriptions(self: Arc<Self>, max: Option<u64>) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder.inner.max_subscriptions(max.map(|m| m as usize));
+        builder
+    }
+
+    /// What to do when a new subscription would exceed the max number of subscriptions (default: reject)
+    pub fn subscription_limit_policy(self: Arc<Self>, policy: SubscriptionLimitPolicy) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder.inner.subscription_limit_policy(policy.into());
+        builder
+    }
+
+    /// Max number of bytes that can be sent to the relay per hour (default: None)
+    ///
+    /// When the cap is reached, outgoing messages are held until the next hour.
+    pub fn max_bytes_per_hour(self: Arc<Self>, max: Option<u64>) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder.inner.max_bytes_per_hour(max);
+        builder
+    }
+}
+
+/// Policy applied when a new subscription would exceed the relay subscriptions limit
+#[derive(Enum)]
+pub enum SubscriptionLimitPolicy {
+    /// Reject the new subscription
+    Reject,
+    /// Close the least recently active subscription
+    EvictLeastRecentlyActive,
+}
+
+impl From<SubscriptionLimitPolicy> for nostr_sdk::SubscriptionLimitPolicy {
+    fn from(value: SubscriptionLimitPolicy) -> Self {
+        match value {
+            SubscriptionLimitPolicy::Reject => Self::Reject,
+            SubscriptionLimitPolicy::EvictLeastRecentlyActive => Self::EvictLeastRecentlyActive,
+        }
+    }
 }
 
 #[derive(Clone, Object)]
@@ -283,6 +327,18 @@ impl SubscribeOptions {
         builder.inner = builder.inner.send_opts(**opts);
         builder
     }
+
+    /// Deliver events in `created_at` order, holding them for `window` (default: disabled)
+    ///
+    /// Events are delivered to the `HandleOrderedEvent` handler.
+    /// Events older than an already delivered one are delivered immediately with the `out_of_order` flag.
+    pub fn reorder(self: Arc<Self>, window: Option<Duration>) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder
+            .inner
+            .reorder(window.map(nostr_sdk::ReorderOptions::new));
+        builder
+    }
 }
 
 #[derive(Enum)]
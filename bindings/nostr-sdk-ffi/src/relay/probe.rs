@@ -0,0 +1,170 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+use std::net::SocketAddr;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
+
+use nostr_ffi::helper::unwrap_or_clone_arc;
+use nostr_ffi::{RelayInformationDocument, Timestamp};
+use nostr_sdk::{block_on, pool, Url};
+use uniffi::{Enum, Object, Record};
+
+use crate::error::Result;
+
+/// Relay probe options
+#[derive(Clone, Object)]
+pub struct ProbeOptions {
+    inner: pool::ProbeOptions,
+}
+
+impl Deref for ProbeOptions {
+    type Target = pool::ProbeOptions;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+#[uniffi::export]
+impl ProbeOptions {
+    /// New default probe options
+    #[uniffi::constructor]
+    pub fn new() -> Self {
+        Self {
+            inner: pool::ProbeOptions::new(),
+        }
+    }
+
+    /// Overall timeout (default: 10 secs)
+    pub fn timeout(self: Arc<Self>, timeout: Duration) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder.inner.timeout(timeout);
+        builder
+    }
+
+    /// Set proxy
+    pub fn proxy(self: Arc<Self>, proxy: Option<String>) -> Result<Self> {
+        let mut builder = unwrap_or_clone_arc(self);
+        let proxy: Option<SocketAddr> = match proxy {
+            Some(proxy) => Some(proxy.parse()?),
+            None => None,
+        };
+        builder.inner = builder.inner.proxy(proxy);
+        Ok(builder)
+    }
+
+    /// Fetch the `NIP11` document (default: true)
+    pub fn nip11(self: Arc<Self>, nip11: bool) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder.inner.nip11(nip11);
+        builder
+    }
+}
+
+/// Probe failure
+///
+/// Every variant is a distinct stage of the probe.
+#[derive(Enum)]
+pub enum ProbeFailure {
+    InvalidUrl,
+    Proxy {
+        message: String,
+    },
+    Connect {
+        message: String,
+    },
+    ConnectTimeout,
+    Tls {
+        message: String,
+    },
+    WebSocket {
+        message: String,
+    },
+    WebSocketTimeout,
+    QueryClosed {
+        message: String,
+    },
+    Disconnected,
+    /// Connects but never answers queries
+    NoEose,
+}
+
+impl From<pool::ProbeFailure> for ProbeFailure {
+    fn from(value: pool::ProbeFailure) -> Self {
+        match value {
+            pool::ProbeFailure::InvalidUrl => Self::InvalidUrl,
+            pool::ProbeFailure::Proxy(message) => Self::Proxy { message },
+            pool::ProbeFailure::Connect(message) => Self::Connect { message },
+            pool::ProbeFailure::ConnectTimeout => Self::ConnectTimeout,
+            pool::ProbeFailure::Tls(message) => Self::Tls { message },
+            pool::ProbeFailure::WebSocket(message) => Self::WebSocket { message },
+            pool::ProbeFailure::WebSocketTimeout => Self::WebSocketTimeout,
+            pool::ProbeFailure::QueryClosed(message) => Self::QueryClosed { message },
+            pool::ProbeFailure::Disconnected => Self::Disconnected,
+            pool::ProbeFailure::NoEose => Self::NoEose,
+        }
+    }
+}
+
+#[derive(Record)]
+pub struct TlsCertificate {
+    pub issuer: String,
+    pub subject: String,
+    pub not_before: Arc<Timestamp>,
+    pub not_after: Arc<Timestamp>,
+}
+
+impl From<pool::TlsCertificate> for TlsCertificate {
+    fn from(value: pool::TlsCertificate) -> Self {
+        Self {
+            issuer: value.issuer,
+            subject: value.subject,
+            not_before: Arc::new(value.not_before.into()),
+            not_after: Arc::new(value.not_after.into()),
+        }
+    }
+}
+
+#[derive(Record)]
+pub struct ProbeReport {
+    pub url: String,
+    pub tcp_connect: Option<Duration>,
+    pub websocket_connect: Option<Duration>,
+    /// Time between the `REQ` and the `EOSE`
+    pub eose: Option<Duration>,
+    pub auth_required: bool,
+    pub certificate: Option<TlsCertificate>,
+    pub document: Option<Arc<RelayInformationDocument>>,
+    pub document_error: Option<String>,
+    /// First failed stage, if any
+    pub failure: Option<ProbeFailure>,
+}
+
+impl From<pool::ProbeReport> for ProbeReport {
+    fn from(value: pool::ProbeReport) -> Self {
+        Self {
+            url: value.url.to_string(),
+            tcp_connect: value.tcp_connect,
+            websocket_connect: value.websocket_connect,
+            eose: value.eose,
+            auth_required: value.auth_required,
+            certificate: value.certificate.map(|c| c.into()),
+            document: value.document.map(|d| Arc::new(d.into())),
+            document_error: value.document_error,
+            failure: value.failure.map(|f| f.into()),
+        }
+    }
+}
+
+/// Probe a relay url, without adding it to a pool
+///
+/// Useful to validate a relay url before adding it.
+#[uniffi::export]
+pub fn probe_relay(url: String, opts: &ProbeOptions) -> Result<ProbeReport> {
+    let url: Url = Url::parse(&url)?;
+    let opts = opts.deref().clone();
+    block_on(async move { Ok(nostr_sdk::Relay::probe(url, opts).await.into()) })
+}
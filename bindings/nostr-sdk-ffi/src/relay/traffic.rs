@@ -0,0 +1,80 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+use std::collections::HashMap;
+
+use nostr_sdk::pool;
+use uniffi::Record;
+
+/// Incoming traffic stats of a subscription
+#[derive(Record)]
+pub struct SubscriptionStats {
+    /// Events received
+    pub received: u64,
+    /// Bytes received (events only)
+    pub bytes: u64,
+    /// Events already received (from another subscription or relay)
+    pub duplicates: u64,
+    /// Events dropped for insufficient POW
+    pub dropped_pow: u64,
+    /// Events dropped by a policy (unsolicited, deleted, expired, invalid, not matching filters, spam, ...)
+    pub dropped_policy: u64,
+    /// Events delivered to the notification consumers
+    pub delivered: u64,
+    /// Percentage of the received events delivered to consumers
+    pub delivered_percent: f64,
+    /// Percentage of the received events that were duplicates
+    pub duplicates_percent: f64,
+    /// Percentage of the received events dropped (POW or policy)
+    pub dropped_percent: f64,
+    /// Events received per kind
+    pub kinds: HashMap<u64, u64>,
+}
+
+impl From<pool::SubscriptionStats> for SubscriptionStats {
+    fn from(stats: pool::SubscriptionStats) -> Self {
+        Self {
+            received: stats.received,
+            bytes: stats.bytes,
+            duplicates: stats.duplicates,
+            dropped_pow: stats.dropped_pow,
+            dropped_policy: stats.dropped_policy,
+            delivered: stats.delivered,
+            delivered_percent: stats.delivered_percent(),
+            duplicates_percent: stats.duplicates_percent(),
+            dropped_percent: stats.dropped_percent(),
+            kinds: stats
+                .kinds
+                .into_iter()
+                .map(|(kind, count)| (kind.as_u64(), count))
+                .collect(),
+        }
+    }
+}
+
+/// Incoming traffic report of all the subscriptions
+#[derive(Record)]
+pub struct TrafficReport {
+    /// Sum of the stats of all the subscriptions
+    pub total: SubscriptionStats,
+    /// Stats per subscription
+    pub subscriptions: HashMap<String, SubscriptionStats>,
+    /// Report serialized as JSON
+    pub json: String,
+}
+
+impl From<pool::TrafficReport> for TrafficReport {
+    fn from(report: pool::TrafficReport) -> Self {
+        let json: String = report.as_json();
+        Self {
+            total: report.total().into(),
+            subscriptions: report
+                .subscriptions
+                .into_iter()
+                .map(|(id, stats)| (id.to_string(), stats.into()))
+                .collect(),
+            json,
+        }
+    }
+}
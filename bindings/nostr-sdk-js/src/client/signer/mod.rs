@@ -73,6 +73,20 @@ impl JsNostrSigner {
             .into())
     }
 
+    #[wasm_bindgen(js_name = signEventBuilderPow)]
+    pub async fn sign_event_builder_pow(
+        &self,
+        builder: &JsEventBuilder,
+        difficulty: u8,
+    ) -> Result<JsEvent> {
+        Ok(self
+            .inner
+            .sign_event_builder_pow(builder.deref().clone(), difficulty)
+            .await
+            .map_err(into_err)?
+            .into())
+    }
+
     #[wasm_bindgen(js_name = signEvent)]
     pub async fn sign_event(&self, unsigned: &JsUnsignedEvent) -> Result<JsEvent> {
         Ok(self
@@ -27,6 +27,9 @@ buildargs=(
     "-p nostr --no-default-features --features alloc"
     "-p nostr --no-default-features --features alloc,all-nips"
     "-p nostr --features blocking"
+    "-p nostr --features cbor"
+    "-p nostr --no-default-features --features alloc,cbor"
+    "-p nostr --features test-utils,nip59"
     "-p nostr-database"
     "-p nostr-zapper"
     "-p nostr-sdk"
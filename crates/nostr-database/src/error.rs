@@ -24,6 +24,12 @@ pub enum DatabaseError {
     /// Not found
     #[error("not found")]
     NotFound,
+    /// Prefix match more than one item
+    #[error("ambiguous prefix: {0} matches")]
+    AmbiguousPrefix(usize),
+    /// Invalid iteration cursor
+    #[error("invalid cursor")]
+    InvalidCursor,
 }
 
 impl DatabaseError {
@@ -0,0 +1,566 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Import events from other clients exports
+//!
+//! Supported formats:
+//! * JSON Lines (one event per line, ex. `strfry export`)
+//! * JSON array of events (ex. nostrudel backups)
+//!
+//! Zip archives (ex. Primal exports) are detected but not decompressed: extract them and import the inner files.
+//!
+//! Records may include the [`SEEN_ON_FIELD`] extension field with the relays that delivered the event.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read};
+use std::sync::Arc;
+
+use nostr::serde_json::{self, Value};
+use nostr::{Event, Kind, Url};
+use thiserror::Error;
+
+use crate::{DatabaseError, NostrDatabase};
+
+/// Default number of events written to the database in a single batch
+pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Name of the provenance extension field (array of relay URLs), not part of the signed event
+pub const SEEN_ON_FIELD: &str = "seen_on";
+
+/// Import error
+#[derive(Debug, Error)]
+pub enum ImportError {
+    /// I/O error
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// Database error
+    #[error(transparent)]
+    Database(#[from] DatabaseError),
+    /// Format not supported
+    #[error("unsupported format: {0}")]
+    UnsupportedFormat(String),
+}
+
+/// Import format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImportFormat {
+    /// JSON Lines: one event per line
+    Jsonl,
+    /// JSON array of events
+    JsonArray,
+}
+
+impl ImportFormat {
+    /// Detect format from the beginning of the file
+    ///
+    /// Return `None` if the input is empty.
+    pub fn detect(buf: &[u8]) -> Result<Option<Self>, ImportError> {
+        if buf.starts_with(ZIP_MAGIC) {
+            return Err(ImportError::UnsupportedFormat(String::from(
+                "zip archive (extract it before importing)",
+            )));
+        }
+
+        let buf: &[u8] = buf.strip_prefix(UTF8_BOM).unwrap_or(buf);
+        match buf.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'[') => Ok(Some(Self::JsonArray)),
+            Some(b'{') => Ok(Some(Self::Jsonl)),
+            Some(b) => Err(ImportError::UnsupportedFormat(format!(
+                "unexpected first byte 0x{b:02x}"
+            ))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Import progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportProgress {
+    /// Records read
+    pub read: usize,
+    /// Valid events
+    pub valid: usize,
+    /// Skipped records
+    pub skipped: usize,
+}
+
+/// Import options
+#[derive(Clone)]
+pub struct ImportOptions {
+    format: Option<ImportFormat>,
+    verify: bool,
+    dry_run: bool,
+    batch_size: usize,
+    progress: Option<Arc<dyn Fn(ImportProgress) + Send + Sync>>,
+}
+
+impl fmt::Debug for ImportOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImportOptions")
+            .field("format", &self.format)
+            .field("verify", &self.verify)
+            .field("dry_run", &self.dry_run)
+            .field("batch_size", &self.batch_size)
+            .finish()
+    }
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            format: None,
+            verify: true,
+            dry_run: false,
+            batch_size: DEFAULT_BATCH_SIZE,
+            progress: None,
+        }
+    }
+}
+
+impl ImportOptions {
+    /// New default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Force format (default: auto-detect)
+    pub fn format(mut self, format: ImportFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Verify event IDs and signatures (default: true)
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Parse and count the events without writing to the database (default: false)
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Number of events written to the database in a single batch (default: 1000)
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// Progress callback, called after every batch
+    pub fn progress<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(ImportProgress) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+}
+
+/// Skipped record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedRecord {
+    /// Line number (starting from 1) where the record begin
+    pub line: usize,
+    /// Error
+    pub error: String,
+}
+
+/// Import report
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Detected or forced format
+    pub format: Option<ImportFormat>,
+    /// Records read
+    pub read: usize,
+    /// Valid events, per kind
+    pub kinds: BTreeMap<Kind, usize>,
+    /// Skipped records
+    pub skipped: Vec<SkippedRecord>,
+    /// Dry run: nothing written to the database
+    pub dry_run: bool,
+}
+
+impl ImportReport {
+    /// Number of valid events
+    pub fn valid(&self) -> usize {
+        self.kinds.values().sum()
+    }
+
+    fn progress(&self) -> ImportProgress {
+        ImportProgress {
+            read: self.read,
+            valid: self.valid(),
+            skipped: self.skipped.len(),
+        }
+    }
+}
+
+/// Raw record
+struct Record {
+    line: usize,
+    json: Vec<u8>,
+}
+
+/// Streaming record reader
+struct RecordReader<R> {
+    reader: R,
+    format: ImportFormat,
+    /// Current line (starting from 1)
+    line: usize,
+    /// JSON array: opening bracket already consumed
+    started: bool,
+    done: bool,
+}
+
+impl<R> RecordReader<R>
+where
+    R: BufRead,
+{
+    fn new(reader: R, format: ImportFormat) -> Self {
+        Self {
+            reader,
+            format,
+            line: 1,
+            started: false,
+            done: false,
+        }
+    }
+
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        let byte: Option<u8> = self.reader.fill_buf()?.first().copied();
+        if let Some(b) = byte {
+            self.reader.consume(1);
+            if b == b'\n' {
+                self.line += 1;
+            }
+        }
+        Ok(byte)
+    }
+
+    fn next_line(&mut self) -> io::Result<Option<Record>> {
+        let mut buf: Vec<u8> = Vec::new();
+        loop {
+            buf.clear();
+            let line: usize = self.line;
+            if self.reader.read_until(b'\n', &mut buf)? == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+
+            if !buf.iter().all(|b| b.is_ascii_whitespace()) {
+                return Ok(Some(Record { line, json: buf }));
+            }
+        }
+    }
+
+    /// Extract the next element of the JSON array, tracking nesting and strings
+    fn next_element(&mut self) -> io::Result<Option<Record>> {
+        if !self.started {
+            while let Some(b) = self.next_byte()? {
+                if b == b'[' {
+                    break;
+                }
+            }
+            self.started = true;
+        }
+
+        // Skip separators
+        let first: u8 = loop {
+            match self.next_byte()? {
+                Some(b) if b.is_ascii_whitespace() || b == b',' => continue,
+                Some(b']') | None => return Ok(None),
+                Some(b) => break b,
+            }
+        };
+
+        let line: usize = self.line;
+        let mut json: Vec<u8> = vec![first];
+        let mut depth: usize = usize::from(matches!(first, b'{' | b'['));
+        let mut in_string: bool = first == b'"';
+        let mut escaped: bool = false;
+
+        while let Some(b) = self.next_byte()? {
+            if in_string {
+                json.push(b);
+                if escaped {
+                    escaped = false;
+                } else if b == b'\\' {
+                    escaped = true;
+                } else if b == b'"' {
+                    in_string = false;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                continue;
+            }
+
+            match b {
+                b'"' => in_string = true,
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' if depth > 0 => depth -= 1,
+                b',' | b']' if depth == 0 => {
+                    if b == b']' {
+                        self.done = true;
+                    }
+                    return Ok(Some(Record { line, json }));
+                }
+                _ => {}
+            }
+
+            json.push(b);
+
+            if depth == 0 && matches!(b, b'}' | b']') {
+                break;
+            }
+        }
+
+        Ok(Some(Record { line, json }))
+    }
+}
+
+impl<R> Iterator for RecordReader<R>
+where
+    R: BufRead,
+{
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let record = match self.format {
+            ImportFormat::Jsonl => self.next_line(),
+            ImportFormat::JsonArray => self.next_element(),
+        };
+
+        match record {
+            Ok(Some(record)) => Some(Ok(record)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Parse the event and the optional `seen_on` extension field (relays that delivered the event)
+fn parse_record(record: &Record, verify: bool) -> Result<(Event, Vec<Url>), String> {
+    let mut value: Value = serde_json::from_slice(&record.json).map_err(|e| e.to_string())?;
+
+    // Extension fields are outside the signed payload: remove them before deserializing the event
+    let seen_on: Vec<Url> = match value.as_object_mut().and_then(|o| o.remove(SEEN_ON_FIELD)) {
+        Some(Value::Array(urls)) => urls
+            .iter()
+            .filter_map(|u| u.as_str())
+            .filter_map(|u| Url::parse(u).ok())
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let event: Event = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    if verify {
+        event.verify().map_err(|e| e.to_string())?;
+    }
+    Ok((event, seen_on))
+}
+
+/// Import events into the database
+///
+/// Corrupted or invalid records are skipped and reported with their line number.
+/// Events are written in batches with [`NostrDatabase::bulk_import`],
+/// so replaceable events and deletions are handled as for events received from relays.
+///
+/// **The reader is blocking**: spawn a blocking task for large files if required.
+pub async fn import_events<D, R>(
+    database: &D,
+    reader: R,
+    opts: ImportOptions,
+) -> Result<ImportReport, ImportError>
+where
+    D: NostrDatabase + ?Sized,
+    R: Read,
+{
+    let mut reader = BufReader::new(reader);
+
+    let format: ImportFormat = match opts.format {
+        Some(format) => format,
+        None => match ImportFormat::detect(reader.fill_buf()?)? {
+            Some(format) => format,
+            None => {
+                return Ok(ImportReport {
+                    dry_run: opts.dry_run,
+                    ..Default::default()
+                })
+            }
+        },
+    };
+
+    // Strip BOM
+    if reader.fill_buf()?.starts_with(UTF8_BOM) {
+        reader.consume(UTF8_BOM.len());
+    }
+
+    let mut report: ImportReport = ImportReport {
+        format: Some(format),
+        dry_run: opts.dry_run,
+        ..Default::default()
+    };
+    let mut batch: BTreeSet<Event> = BTreeSet::new();
+
+    for record in RecordReader::new(reader, format) {
+        let record: Record = record?;
+        report.read += 1;
+
+        match parse_record(&record, opts.verify) {
+            Ok((event, seen_on)) => {
+                *report.kinds.entry(event.kind).or_default() += 1;
+                if !opts.dry_run {
+                    for url in seen_on.into_iter() {
+                        database
+                            .event_id_seen(event.id, url)
+                            .await
+                            .map_err(|e| ImportError::Database(e.into()))?;
+                    }
+                    batch.insert(event);
+                }
+            }
+            Err(error) => {
+                tracing::warn!("Skipping record at line {}: {error}", record.line);
+                report.skipped.push(SkippedRecord {
+                    line: record.line,
+                    error,
+                });
+            }
+        }
+
+        if report.read % opts.batch_size == 0 {
+            flush(database, &mut batch).await?;
+            if let Some(progress) = &opts.progress {
+                progress(report.progress());
+            }
+        }
+    }
+
+    flush(database, &mut batch).await?;
+    if let Some(progress) = &opts.progress {
+        progress(report.progress());
+    }
+
+    Ok(report)
+}
+
+async fn flush<D>(database: &D, batch: &mut BTreeSet<Event>) -> Result<(), ImportError>
+where
+    D: NostrDatabase + ?Sized,
+{
+    if !batch.is_empty() {
+        database
+            .bulk_import(std::mem::take(batch))
+            .await
+            .map_err(|e| ImportError::Database(e.into()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Filter, JsonUtil, Keys};
+
+    use super::*;
+    use crate::{MemoryDatabase, NostrDatabaseExt};
+
+    fn events(keys: &Keys) -> Vec<Event> {
+        vec![
+            EventBuilder::text_note("first, with \"quotes\" and ] brackets", [])
+                .to_event(keys)
+                .unwrap(),
+            EventBuilder::text_note("second", [])
+                .to_event(keys)
+                .unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(
+            ImportFormat::detect(b"  [{}]").unwrap(),
+            Some(ImportFormat::JsonArray)
+        );
+        assert_eq!(
+            ImportFormat::detect(b"\xEF\xBB\xBF{}\n{}").unwrap(),
+            Some(ImportFormat::Jsonl)
+        );
+        assert_eq!(ImportFormat::detect(b"  \n").unwrap(), None);
+        assert!(ImportFormat::detect(b"PK\x03\x04....").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_jsonl() {
+        let keys = Keys::generate();
+        let events = events(&keys);
+        let mut second: Value = serde_json::from_str(&events[1].as_json()).unwrap();
+        second[SEEN_ON_FIELD] = serde_json::json!(["wss://relay.damus.io"]);
+        let input: String = format!("{}\n\nnot an event\n{}\n", events[0].as_json(), second);
+
+        let database = MemoryDatabase::with_opts(crate::MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        });
+        let report = import_events(&database, input.as_bytes(), ImportOptions::new())
+            .await
+            .unwrap();
+
+        assert_eq!(report.format, Some(ImportFormat::Jsonl));
+        assert_eq!(report.read, 3);
+        assert_eq!(report.valid(), 2);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].line, 3);
+        assert_eq!(database.count(vec![Filter::new()]).await.unwrap(), 2);
+
+        // Provenance extension field
+        let seen_on = database.event_seen_on(events[1].id).await.unwrap();
+        assert!(seen_on.contains(&Url::parse("wss://relay.damus.io").unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_import_json_array_dry_run() {
+        let keys = Keys::generate();
+        let events = events(&keys);
+        let input: String = format!(
+            "[\n  {},\n  {{\"id\": \"broken\"}},\n  {}\n]",
+            events[0].as_json(),
+            events[1].as_json()
+        );
+
+        let database = MemoryDatabase::with_opts(crate::MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        });
+        let report = import_events(
+            &database,
+            input.as_bytes(),
+            ImportOptions::new().dry_run(true),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.format, Some(ImportFormat::JsonArray));
+        assert_eq!(report.read, 3);
+        assert_eq!(report.kinds.get(&Kind::TextNote), Some(&2));
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].line, 3);
+        assert_eq!(database.count(vec![Filter::new()]).await.unwrap(), 0);
+    }
+}
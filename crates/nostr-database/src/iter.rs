@@ -0,0 +1,261 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Database event iterator
+
+use core::fmt;
+use core::str::FromStr;
+use std::collections::VecDeque;
+
+use nostr::{Event, EventId, Filter, Timestamp};
+
+use crate::{DatabaseError, NostrDatabase, Order};
+
+/// Default number of events fetched per page
+pub const DEFAULT_PAGE_SIZE: usize = 500;
+
+/// Iteration cursor
+///
+/// Point to the last event yielded by an [`EventIter`].
+/// Can be converted to an opaque token (with [`ToString`]) and parsed back (with [`FromStr`]) to resume the iteration later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IterCursor {
+    created_at: Timestamp,
+    id: Option<EventId>,
+}
+
+impl fmt::Display for IterCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.id {
+            Some(id) => write!(f, "{}:{id}", self.created_at.as_u64()),
+            None => write!(f, "{}", self.created_at.as_u64()),
+        }
+    }
+}
+
+impl FromStr for IterCursor {
+    type Err = DatabaseError;
+
+    fn from_str(token: &str) -> Result<Self, Self::Err> {
+        let mut split = token.split(':');
+        let created_at: u64 = split
+            .next()
+            .and_then(|t| t.parse().ok())
+            .ok_or(DatabaseError::InvalidCursor)?;
+        let id: Option<EventId> = match split.next() {
+            Some(id) => Some(EventId::from_hex(id).map_err(|_| DatabaseError::InvalidCursor)?),
+            None => None,
+        };
+
+        if split.next().is_some() {
+            return Err(DatabaseError::InvalidCursor);
+        }
+
+        Ok(Self {
+            created_at: Timestamp::from(created_at),
+            id,
+        })
+    }
+}
+
+/// Paginated event iterator
+///
+/// Events are yielded ordered by `created_at` (descending), with ties broken by event ID (ascending),
+/// the same order used by [`DatabaseIndexes`](crate::DatabaseIndexes).
+///
+/// Only a page of events (plus the events sharing the `created_at` of the page boundary) is kept in memory,
+/// regardless of how many events match the filter.
+///
+/// # Consistency
+///
+/// Every page is a fresh query, so the iteration is **not** a snapshot:
+/// events saved during the iteration are yielded only if they sort after the current cursor,
+/// deleted or replaced events are skipped if not yet yielded.
+/// Since the cursor only moves forward, the same event is never yielded twice.
+/// This applies to all the backends built on top of [`DatabaseIndexes`](crate::DatabaseIndexes)
+/// (memory, SQLite, RocksDB and IndexedDB), since the pagination relies on their ordering.
+#[derive(Debug)]
+pub struct EventIter<'a, D>
+where
+    D: NostrDatabase + ?Sized,
+{
+    db: &'a D,
+    filter: Filter,
+    remaining: Option<usize>,
+    page_size: usize,
+    cursor: Option<IterCursor>,
+    buffer: VecDeque<Event>,
+    done: bool,
+}
+
+impl<'a, D> EventIter<'a, D>
+where
+    D: NostrDatabase + ?Sized,
+{
+    pub(crate) fn new(db: &'a D, filter: Filter, cursor: Option<IterCursor>) -> Self {
+        Self {
+            db,
+            remaining: filter.limit,
+            filter: filter.remove_limit(),
+            page_size: DEFAULT_PAGE_SIZE,
+            cursor,
+            buffer: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Set number of events fetched per page (default: 500)
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Get cursor of the last yielded event
+    ///
+    /// Use [`NostrDatabaseExt::iter_from`](crate::NostrDatabaseExt::iter_from) to resume the iteration.
+    pub fn cursor(&self) -> Option<IterCursor> {
+        self.cursor
+    }
+
+    /// Get next event
+    ///
+    /// Return `None` when all the matching events have been yielded.
+    pub async fn next(&mut self) -> Result<Option<Event>, D::Err> {
+        if self.remaining == Some(0) {
+            return Ok(None);
+        }
+
+        if self.buffer.is_empty() && !self.done {
+            self.fill().await?;
+        }
+
+        match self.buffer.pop_front() {
+            Some(event) => {
+                self.cursor = Some(IterCursor {
+                    created_at: event.created_at,
+                    id: Some(event.id),
+                });
+
+                if let Some(remaining) = self.remaining.as_mut() {
+                    *remaining = remaining.saturating_sub(1);
+                }
+
+                Ok(Some(event))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn fill(&mut self) -> Result<(), D::Err> {
+        let filter: Filter = match self.cursor {
+            Some(cursor) => {
+                if self
+                    .filter
+                    .since
+                    .map_or(false, |since| since > cursor.created_at)
+                {
+                    self.done = true;
+                    return Ok(());
+                }
+
+                // Events left at the boundary timestamp
+                let filter: Filter = self
+                    .filter
+                    .clone()
+                    .since(cursor.created_at)
+                    .until(cursor.created_at);
+                let mut same: Vec<Event> = self.db.query(vec![filter], Order::Desc).await?;
+                same.retain(|e| cursor.id.map_or(true, |id| e.id > id));
+                if !same.is_empty() {
+                    same.sort_by_key(|e| e.id);
+                    self.buffer.extend(same);
+                    return Ok(());
+                }
+
+                match cursor.created_at.as_u64().checked_sub(1) {
+                    Some(until) => self.filter.clone().until(Timestamp::from(until)),
+                    None => {
+                        self.done = true;
+                        return Ok(());
+                    }
+                }
+            }
+            None => self.filter.clone(),
+        };
+
+        let page: Vec<Event> = self
+            .db
+            .query(vec![filter.limit(self.page_size)], Order::Desc)
+            .await?;
+
+        if page.is_empty() {
+            self.done = true;
+        }
+
+        self.buffer.extend(page);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys, Kind};
+
+    use super::*;
+    use crate::{MemoryDatabase, MemoryDatabaseOptions, NostrDatabaseExt};
+
+    #[test]
+    fn test_cursor_token() {
+        let cursor = IterCursor {
+            created_at: Timestamp::from(1704644581),
+            id: Some(
+                EventId::from_hex(
+                    "b7b1fb52ad8461a03e949820ae29a9ea07e35bcd79c95c4b59b0254944f62805",
+                )
+                .unwrap(),
+            ),
+        };
+        let token: String = cursor.to_string();
+        assert_eq!(IterCursor::from_str(&token).unwrap(), cursor);
+        assert!(IterCursor::from_str("abc").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_iter_pagination() {
+        let opts = MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        };
+        let db = MemoryDatabase::with_opts(opts);
+        let keys = Keys::generate();
+
+        // Many events sharing the same timestamps
+        for i in 0..30u64 {
+            let event = EventBuilder::new(Kind::TextNote, i.to_string(), [])
+                .custom_created_at(Timestamp::from(1000 + i / 4))
+                .to_event(&keys)
+                .unwrap();
+            db.save_event(&event).await.unwrap();
+        }
+
+        let expected: Vec<Event> = db.query(vec![Filter::new()], Order::Desc).await.unwrap();
+
+        let mut iter = db.iter(Filter::new()).page_size(3);
+        let mut events: Vec<Event> = Vec::new();
+        for _ in 0..10 {
+            events.push(iter.next().await.unwrap().unwrap());
+        }
+
+        // Resume from cursor token
+        let token: String = iter.cursor().unwrap().to_string();
+        let cursor = IterCursor::from_str(&token).unwrap();
+        let mut iter = db.iter_from(Filter::new(), cursor).page_size(3);
+        while let Some(event) = iter.next().await.unwrap() {
+            events.push(event);
+        }
+
+        assert_eq!(events, expected);
+    }
+}
@@ -0,0 +1,475 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Streaming JSON Lines writer and reader
+//!
+//! Write events one per line, without collecting them in memory (ex. relay dumps).
+//! Gzip compression requires the `gzip` feature.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "gzip")]
+use flate2::read::MultiGzDecoder;
+#[cfg(feature = "gzip")]
+use flate2::write::GzEncoder;
+#[cfg(feature = "gzip")]
+use flate2::Compression;
+use nostr::{Event, JsonUtil};
+
+/// Default number of events written between flushes
+pub const DEFAULT_FLUSH_EVERY: usize = 1000;
+
+#[cfg(feature = "gzip")]
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+
+/// When to `fsync` the written files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FsyncPolicy {
+    /// Never (default)
+    #[default]
+    Never,
+    /// After every periodic flush
+    OnFlush,
+    /// When a file is completed (rotation or finish)
+    OnRotate,
+}
+
+/// [`JsonlWriter`] options
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonlWriterOptions {
+    flush_every: usize,
+    fsync: FsyncPolicy,
+    max_file_size: Option<u64>,
+    #[cfg(feature = "gzip")]
+    gzip: bool,
+}
+
+impl Default for JsonlWriterOptions {
+    fn default() -> Self {
+        Self {
+            flush_every: DEFAULT_FLUSH_EVERY,
+            fsync: FsyncPolicy::default(),
+            max_file_size: None,
+            #[cfg(feature = "gzip")]
+            gzip: false,
+        }
+    }
+}
+
+impl JsonlWriterOptions {
+    /// New default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Flush every `n` events (default: [`DEFAULT_FLUSH_EVERY`])
+    ///
+    /// `0` disable periodic flushes.
+    pub fn flush_every(mut self, n: usize) -> Self {
+        self.flush_every = n;
+        self
+    }
+
+    /// Set [`FsyncPolicy`]
+    pub fn fsync(mut self, policy: FsyncPolicy) -> Self {
+        self.fsync = policy;
+        self
+    }
+
+    /// Rotate the file when it reach `size` bytes (default: `None`)
+    ///
+    /// The size is computed on the uncompressed output.
+    /// Rotated files are named `<stem>-<index>.<ext>` (ex. `dump-00001.jsonl.gz`).
+    pub fn max_file_size(mut self, size: Option<u64>) -> Self {
+        self.max_file_size = size;
+        self
+    }
+
+    /// Compress the output with gzip (default: false)
+    #[cfg(feature = "gzip")]
+    pub fn gzip(mut self, gzip: bool) -> Self {
+        self.gzip = gzip;
+        self
+    }
+}
+
+enum Sink {
+    Plain(BufWriter<File>),
+    #[cfg(feature = "gzip")]
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl Sink {
+    fn new(file: File, _opts: &JsonlWriterOptions) -> Self {
+        let writer = BufWriter::new(file);
+        #[cfg(feature = "gzip")]
+        if _opts.gzip {
+            return Self::Gzip(GzEncoder::new(writer, Compression::default()));
+        }
+        Self::Plain(writer)
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Self::Plain(w) => w,
+            #[cfg(feature = "gzip")]
+            Self::Gzip(w) => w,
+        }
+    }
+
+    fn file(&self) -> &File {
+        match self {
+            Self::Plain(w) => w.get_ref(),
+            #[cfg(feature = "gzip")]
+            Self::Gzip(w) => w.get_ref().get_ref(),
+        }
+    }
+
+    /// Write the trailer (if any) and flush
+    fn finish(self) -> io::Result<File> {
+        let writer: BufWriter<File> = match self {
+            Self::Plain(w) => w,
+            #[cfg(feature = "gzip")]
+            Self::Gzip(w) => w.finish()?,
+        };
+        writer.into_inner().map_err(|e| e.into_error())
+    }
+}
+
+/// Streaming JSON Lines events writer
+pub struct JsonlWriter {
+    path: PathBuf,
+    opts: JsonlWriterOptions,
+    sink: Option<Sink>,
+    index: usize,
+    file_size: u64,
+    unflushed: usize,
+    written: Vec<PathBuf>,
+    buf: Vec<u8>,
+}
+
+impl JsonlWriter {
+    /// Create the output file
+    ///
+    /// Existing files are truncated.
+    pub fn create<P>(path: P, opts: JsonlWriterOptions) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let mut writer = Self {
+            path: path.as_ref().to_path_buf(),
+            opts,
+            sink: None,
+            index: 0,
+            file_size: 0,
+            unflushed: 0,
+            written: Vec::new(),
+            buf: Vec::new(),
+        };
+        writer.open_next()?;
+        Ok(writer)
+    }
+
+    /// Path of the file currently written
+    pub fn current_path(&self) -> Option<&Path> {
+        self.written.last().map(|p| p.as_path())
+    }
+
+    /// Write an event, followed by a newline
+    pub fn write(&mut self, event: &Event) -> io::Result<()> {
+        self.buf.clear();
+        event
+            .write_json(&mut self.buf)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.buf.push(b'\n');
+
+        // Rotate before exceeding the limit (a single event larger than the limit get its own file)
+        if let Some(max) = self.opts.max_file_size {
+            if self.file_size > 0 && self.file_size + self.buf.len() as u64 > max {
+                self.rotate()?;
+            }
+        }
+
+        let sink: &mut Sink = self.sink.as_mut().ok_or_else(closed)?;
+        sink.writer().write_all(&self.buf)?;
+        self.file_size += self.buf.len() as u64;
+        self.unflushed += 1;
+
+        if self.opts.flush_every > 0 && self.unflushed >= self.opts.flush_every {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Flush buffered data
+    ///
+    /// With gzip, data still in the compressor is not flushed until the file is completed.
+    pub fn flush(&mut self) -> io::Result<()> {
+        let sink: &mut Sink = self.sink.as_mut().ok_or_else(closed)?;
+        sink.writer().flush()?;
+        if self.opts.fsync == FsyncPolicy::OnFlush {
+            sink.file().sync_data()?;
+        }
+        self.unflushed = 0;
+        Ok(())
+    }
+
+    /// Complete the current file and return the paths of all the written files
+    pub fn finish(mut self) -> io::Result<Vec<PathBuf>> {
+        self.close_current()?;
+        Ok(std::mem::take(&mut self.written))
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.close_current()?;
+        self.open_next()
+    }
+
+    fn close_current(&mut self) -> io::Result<()> {
+        if let Some(sink) = self.sink.take() {
+            let file: File = sink.finish()?;
+            if self.opts.fsync != FsyncPolicy::Never {
+                file.sync_all()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn open_next(&mut self) -> io::Result<()> {
+        let path: PathBuf = if self.opts.max_file_size.is_some() {
+            rotated_path(&self.path, self.index)
+        } else {
+            self.path.clone()
+        };
+        let file: File = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        self.sink = Some(Sink::new(file, &self.opts));
+        self.written.push(path);
+        self.index += 1;
+        self.file_size = 0;
+        self.unflushed = 0;
+        Ok(())
+    }
+}
+
+impl Drop for JsonlWriter {
+    fn drop(&mut self) {
+        let _ = self.close_current();
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let name: String = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // Keep all the extensions (ex. `.jsonl.gz`)
+    let name: String = match name.split_once('.') {
+        Some((stem, ext)) => format!("{stem}-{index:05}.{ext}"),
+        None => format!("{name}-{index:05}"),
+    };
+    path.with_file_name(name)
+}
+
+fn closed() -> io::Error {
+    io::Error::new(io::ErrorKind::Other, "writer already finished")
+}
+
+/// Streaming JSON Lines events reader
+///
+/// Empty lines are skipped.
+pub struct JsonlReader<R> {
+    reader: R,
+    line: String,
+}
+
+impl<R> JsonlReader<R>
+where
+    R: BufRead,
+{
+    /// New reader
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: String::new(),
+        }
+    }
+}
+
+impl JsonlReader<Box<dyn BufRead>> {
+    /// Open a file
+    ///
+    /// Gzip files are detected and decompressed if the `gzip` feature is enabled.
+    pub fn open<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(Self::new(decode(reader)?))
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn decode(mut reader: BufReader<File>) -> io::Result<Box<dyn BufRead>> {
+    if reader.fill_buf()?.starts_with(GZIP_MAGIC) {
+        let decoder = MultiGzDecoder::new(reader);
+        return Ok(Box::new(BufReader::new(decoder)));
+    }
+    Ok(Box::new(reader))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn decode(reader: BufReader<File>) -> io::Result<Box<dyn BufRead>> {
+    Ok(Box::new(reader))
+}
+
+impl<R> Iterator for JsonlReader<R>
+where
+    R: BufRead,
+{
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(..) => {
+                    let line: &str = self.line.trim();
+                    if line.is_empty() {
+                        continue;
+                    }
+                    return Some(
+                        Event::from_json(line)
+                            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+                    );
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Read all the events from a reader
+pub fn read_events<R>(reader: R) -> io::Result<Vec<Event>>
+where
+    R: Read,
+{
+    JsonlReader::new(BufReader::new(reader)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use nostr::{EventBuilder, Keys, Kind};
+
+    use super::*;
+
+    fn tmp_dir(name: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("nostr-jsonl-{name}-{nanos}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn events(n: usize) -> Vec<Event> {
+        let keys = Keys::generate();
+        (0..n)
+            .map(|i| {
+                EventBuilder::new(Kind::TextNote, i.to_string(), Vec::new())
+                    .to_event(&keys)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_jsonl_round_trip() {
+        let dir = tmp_dir("round-trip");
+        let path = dir.join("dump.jsonl");
+        let events = events(10);
+
+        let opts = JsonlWriterOptions::new()
+            .flush_every(3)
+            .fsync(FsyncPolicy::OnRotate);
+        let mut writer = JsonlWriter::create(&path, opts).unwrap();
+        for event in events.iter() {
+            writer.write(event).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap(), vec![path.clone()]);
+
+        let read: Vec<Event> = JsonlReader::open(&path)
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(read, events);
+
+        // Empty lines
+        let json = format!("\n{}\n\n{}", events[0].as_json(), events[1].as_json());
+        assert_eq!(read_events(json.as_bytes()).unwrap(), events[..2].to_vec());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_jsonl_rotation() {
+        let dir = tmp_dir("rotation");
+        let path = dir.join("dump.jsonl");
+        let events = events(10);
+        let line_len = events[0].as_json().len() as u64 + 1;
+
+        // 3 events per file
+        let opts = JsonlWriterOptions::new().max_file_size(Some(line_len * 3 + line_len / 2));
+        let mut writer = JsonlWriter::create(&path, opts).unwrap();
+        for event in events.iter() {
+            writer.write(event).unwrap();
+        }
+        let paths = writer.finish().unwrap();
+        assert_eq!(paths.len(), 4);
+        assert_eq!(paths[0], dir.join("dump-00000.jsonl"));
+        assert_eq!(paths[3], dir.join("dump-00003.jsonl"));
+
+        let read: Vec<Event> = paths
+            .iter()
+            .flat_map(|p| JsonlReader::open(p).unwrap())
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(read, events);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn test_jsonl_gzip() {
+        let dir = tmp_dir("gzip");
+        let path = dir.join("dump.jsonl.gz");
+        let events = events(5);
+
+        let opts = JsonlWriterOptions::new().gzip(true);
+        let mut writer = JsonlWriter::create(&path, opts).unwrap();
+        for event in events.iter() {
+            writer.write(event).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let read: Vec<Event> = JsonlReader::open(&path)
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(read, events);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -19,7 +19,10 @@ use nostr::{Event, EventId, Filter, JsonUtil, Kind, Metadata, PublicKey, Timesta
 mod error;
 #[cfg(feature = "flatbuf")]
 pub mod flatbuffers;
+pub mod import;
 pub mod index;
+pub mod iter;
+pub mod jsonl;
 pub mod memory;
 pub mod profile;
 mod tag_indexes;
@@ -29,8 +32,14 @@ mod temp;
 pub use self::error::DatabaseError;
 #[cfg(feature = "flatbuf")]
 pub use self::flatbuffers::{FlatBufferBuilder, FlatBufferDecode, FlatBufferEncode};
+pub use self::import::{
+    import_events, ImportError, ImportFormat, ImportOptions, ImportProgress, ImportReport,
+    SkippedRecord,
+};
 pub use self::index::{DatabaseIndexes, EventIndexResult};
-pub use self::memory::{MemoryDatabase, MemoryDatabaseOptions};
+pub use self::iter::{EventIter, IterCursor};
+pub use self::jsonl::{FsyncPolicy, JsonlReader, JsonlWriter, JsonlWriterOptions};
+pub use self::memory::{MemoryDatabase, MemoryDatabaseOptions, MemoryUsage};
 pub use self::profile::Profile;
 #[cfg(feature = "flatbuf")]
 pub use self::temp::TempEvent;
@@ -150,6 +159,13 @@ pub trait NostrDatabase: AsyncTraitDeps {
         event_id: EventId,
     ) -> Result<Option<HashSet<Url>>, Self::Err>;
 
+    /// Get the usage of the in-memory seen-on cache (used to deduplicate the received events)
+    ///
+    /// Return `None` if the backend doesn't keep one (ex. the seen relays are persisted to disk).
+    async fn seen_cache_usage(&self) -> Result<Option<MemoryUsage>, Self::Err> {
+        Ok(None)
+    }
+
     /// Get [`Event`] by [`EventId`]
     async fn event_by_id(&self, event_id: EventId) -> Result<Event, Self::Err>;
 
@@ -255,6 +271,58 @@ pub trait NostrDatabaseExt: NostrDatabase {
             None => Ok(BTreeSet::new()),
         }
     }
+
+    /// Get IDs of stored events starting with `prefix` (`hex` or `note` bech32)
+    ///
+    /// Note: this method scan all the stored event IDs.
+    #[tracing::instrument(skip_all, level = "trace")]
+    async fn event_ids_with_prefix(&self, prefix: &str) -> Result<Vec<EventId>, Self::Err> {
+        let ids: Vec<EventId> = self
+            .event_ids_by_filters(vec![Filter::new()], Order::Desc)
+            .await?;
+        Ok(ids
+            .into_iter()
+            .filter(|id| id.matches_prefix(prefix))
+            .collect())
+    }
+
+    /// Resolve a short event ID (`hex` or `note` bech32 prefix)
+    ///
+    /// Return [`DatabaseError::NotFound`] if no event match or
+    /// [`DatabaseError::AmbiguousPrefix`] if more than one event match.
+    #[tracing::instrument(skip_all, level = "trace")]
+    async fn event_id_by_prefix(&self, prefix: &str) -> Result<EventId, Self::Err> {
+        let mut ids: Vec<EventId> = self.event_ids_with_prefix(prefix).await?;
+        match ids.len() {
+            0 => Err(DatabaseError::NotFound.into()),
+            1 => Ok(ids.remove(0)),
+            len => Err(DatabaseError::AmbiguousPrefix(len).into()),
+        }
+    }
+
+    /// Get the relays that delivered the event (provenance)
+    ///
+    /// Updated also when duplicates of an already stored event are received.
+    /// Return an empty set if the event was never seen.
+    #[tracing::instrument(skip_all, level = "trace")]
+    async fn event_seen_on(&self, event_id: EventId) -> Result<HashSet<Url>, Self::Err> {
+        Ok(self
+            .event_seen_on_relays(event_id)
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Iterate over the events matching the filter, without loading all of them in memory
+    ///
+    /// Check [`EventIter`] for order and consistency guarantees.
+    fn iter(&self, filter: Filter) -> EventIter<'_, Self> {
+        EventIter::new(self, filter, None)
+    }
+
+    /// Resume an iteration from a [`IterCursor`] (see [`EventIter::cursor`])
+    fn iter_from(&self, filter: Filter, cursor: IterCursor) -> EventIter<'_, Self> {
+        EventIter::new(self, filter, Some(cursor))
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -336,6 +404,10 @@ impl<T: NostrDatabase> NostrDatabase for EraseNostrDatabaseError<T> {
             .map_err(Into::into)
     }
 
+    async fn seen_cache_usage(&self) -> Result<Option<MemoryUsage>, Self::Err> {
+        self.0.seen_cache_usage().await.map_err(Into::into)
+    }
+
     async fn event_by_id(&self, event_id: EventId) -> Result<Event, Self::Err> {
         self.0.event_by_id(event_id).await.map_err(Into::into)
     }
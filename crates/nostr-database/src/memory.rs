@@ -6,6 +6,7 @@
 
 use std::collections::{BTreeSet, HashSet};
 use std::hash::Hash;
+use std::mem;
 use std::num::NonZeroUsize;
 use std::sync::Arc;
 
@@ -17,6 +18,31 @@ use tokio::sync::Mutex;
 
 use crate::{Backend, DatabaseError, DatabaseIndexes, EventIndexResult, NostrDatabase, Order};
 
+/// Default max number of event IDs kept in the seen-on cache when [`MemoryDatabaseOptions::max_events`] is `None`
+pub const DEFAULT_MAX_SEEN_EVENT_IDS: usize = 100_000;
+
+/// Memory usage of a component
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Number of entries
+    pub entries: usize,
+    /// Max number of entries, if bounded
+    pub limit: Option<usize>,
+    /// Approximate size, in bytes
+    pub bytes: usize,
+}
+
+impl MemoryUsage {
+    /// New memory usage
+    pub fn new(entries: usize, limit: Option<usize>, bytes: usize) -> Self {
+        Self {
+            entries,
+            limit,
+            bytes,
+        }
+    }
+}
+
 /// Database options
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct MemoryDatabaseOptions {
@@ -24,7 +50,8 @@ pub struct MemoryDatabaseOptions {
     pub events: bool,
     /// Max events and IDs to store in memory
     ///
-    /// `None` means no limits.
+    /// `None` means no limits for the events:
+    /// the seen-on cache is anyway capped at [`DEFAULT_MAX_SEEN_EVENT_IDS`].
     pub max_events: Option<usize>,
 }
 
@@ -69,7 +96,11 @@ impl MemoryDatabase {
     pub fn with_opts(opts: MemoryDatabaseOptions) -> Self {
         Self {
             opts,
-            seen_event_ids: Arc::new(Mutex::new(new_lru_cache(opts.max_events))),
+            seen_event_ids: Arc::new(Mutex::new(new_lru_cache(Some(
+                opts.max_events
+                    .filter(|max| *max > 0)
+                    .unwrap_or(DEFAULT_MAX_SEEN_EVENT_IDS),
+            )))),
             events: Arc::new(Mutex::new(new_lru_cache(opts.max_events))),
             indexes: DatabaseIndexes::new(),
         }
@@ -190,6 +221,22 @@ impl NostrDatabase for MemoryDatabase {
         Ok(seen_event_ids.get(&event_id).cloned())
     }
 
+    async fn seen_cache_usage(&self) -> Result<Option<MemoryUsage>, Self::Err> {
+        let seen_event_ids = self.seen_event_ids.lock().await;
+        let bytes: usize = seen_event_ids
+            .iter()
+            .map(|(_, urls)| {
+                mem::size_of::<(EventId, HashSet<Url>)>()
+                    + urls.iter().map(|u| u.as_str().len()).sum::<usize>()
+            })
+            .sum();
+        Ok(Some(MemoryUsage::new(
+            seen_event_ids.len(),
+            Some(seen_event_ids.cap().get()),
+            bytes,
+        )))
+    }
+
     async fn event_by_id(&self, event_id: EventId) -> Result<Event, Self::Err> {
         if self.opts.events {
             let mut events = self.events.lock().await;
@@ -14,13 +14,28 @@ pub mod pool;
 pub mod prelude;
 pub mod relay;
 
+pub use self::pool::ack::AckStatus;
+pub use self::pool::fetch::{
+    FetchOutput, FetchPolicy, FetchReport, FetchStream, RelayFetchReport, RelayFetchStatus,
+};
+pub use self::pool::handler::RawEventHandler;
+pub use self::pool::memory::{MemoryLimits, MemoryReport, MemoryUsage};
 pub use self::pool::options::RelayPoolOptions;
-pub use self::pool::{RelayPool, RelayPoolNotification};
+pub use self::pool::{AuthReason, RelayPool, RelayPoolNotification};
 pub use self::relay::flags::{AtomicRelayServiceFlags, RelayServiceFlags};
+pub use self::relay::health::{RelayHealth, RelayHealthDetails, RelayHealthThresholds};
 pub use self::relay::limits::RelayLimits;
+pub use self::relay::notice::RelayNotice;
 pub use self::relay::options::{
     FilterOptions, NegentropyDirection, NegentropyOptions, RelayOptions, RelaySendOptions,
-    SubscribeAutoCloseOptions, SubscribeOptions,
+    ReorderOptions, SubscribeAutoCloseOptions, SubscribeOptions, SubscriptionLimitPolicy,
+    UnsolicitedEventPolicy,
 };
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::relay::probe::{ProbeFailure, ProbeOptions, ProbeReport, TlsCertificate};
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::relay::socks::SocksAuth;
 pub use self::relay::stats::RelayConnectionStats;
+pub use self::relay::subscription::SubscriptionActivity;
+pub use self::relay::traffic::{SubscriptionStats, TrafficReport};
 pub use self::relay::{Relay, RelayNotification, RelayStatus};
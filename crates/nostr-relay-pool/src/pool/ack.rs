@@ -0,0 +1,291 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Event acknowledgements
+
+use std::collections::{HashMap, VecDeque};
+use std::mem;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use nostr::types::time::Instant;
+use nostr::{EventId, Url};
+
+/// Acknowledgement status of an event sent to a relay
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AckStatus {
+    /// Waiting for the `OK` message
+    Pending,
+    /// Accepted by the relay
+    Accepted,
+    /// Rejected by the relay
+    Rejected(String),
+    /// No `OK` message received within the timeout (see [`RelayPoolOptions::ack_timeout`](super::RelayPoolOptions::ack_timeout))
+    TimedOut,
+}
+
+#[derive(Debug)]
+struct Entry {
+    sent_at: Instant,
+    status: AckStatus,
+    resolved_at: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+struct InnerAckTracker {
+    entries: HashMap<(Url, EventId), Entry>,
+    /// Insertion order, used to evict the oldest entries
+    order: VecDeque<(Url, EventId, Instant)>,
+}
+
+/// Track the `OK` messages of the events sent to the relays
+#[derive(Debug, Clone)]
+pub(crate) struct AckTracker {
+    inner: Arc<RwLock<InnerAckTracker>>,
+    capacity: usize,
+}
+
+impl AckTracker {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(InnerAckTracker::default())),
+            capacity,
+        }
+    }
+
+    /// Mark event as pending
+    ///
+    /// If the tracker is full, the oldest entries are dropped.
+    pub fn pending(&self, relay_url: Url, event_id: EventId, now: Instant) {
+        let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        inner.entries.insert(
+            (relay_url.clone(), event_id),
+            Entry {
+                sent_at: now,
+                status: AckStatus::Pending,
+                resolved_at: None,
+            },
+        );
+        inner.order.push_back((relay_url, event_id, now));
+
+        while inner.entries.len() > self.capacity {
+            match inner.order.pop_front() {
+                Some((url, id, sent_at)) => {
+                    let key = (url, id);
+                    // Skip entries already removed or sent again
+                    if matches!(inner.entries.get(&key), Some(entry) if entry.sent_at == sent_at) {
+                        inner.entries.remove(&key);
+                    }
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Forget event (ex. failed to send it)
+    pub fn remove(&self, relay_url: &Url, event_id: &EventId) {
+        let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        inner.entries.remove(&(relay_url.clone(), *event_id));
+    }
+
+    /// Handle `OK` message
+    ///
+    /// A late `OK` message overrides [`AckStatus::TimedOut`].
+    pub fn resolve(
+        &self,
+        relay_url: Url,
+        event_id: EventId,
+        status: bool,
+        message: String,
+        now: Instant,
+    ) {
+        let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = inner.entries.get_mut(&(relay_url, event_id)) {
+            entry.status = if status {
+                AckStatus::Accepted
+            } else {
+                AckStatus::Rejected(message)
+            };
+            entry.resolved_at = Some(now);
+        }
+    }
+
+    /// Number of entries and their approximate size, in bytes
+    pub fn usage(&self) -> (usize, usize) {
+        let inner = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        let entry_size: usize =
+            mem::size_of::<((Url, EventId), Entry)>() + mem::size_of::<(Url, EventId, Instant)>();
+        let bytes: usize = inner
+            .entries
+            .iter()
+            .map(|((url, _), entry)| {
+                let reason: usize = match &entry.status {
+                    AckStatus::Rejected(reason) => reason.len(),
+                    _ => 0,
+                };
+                entry_size + url.as_str().len() * 2 + reason
+            })
+            .sum();
+        (inner.entries.len(), bytes)
+    }
+
+    /// Get status of event for every relay it was sent to
+    pub fn status(&self, event_id: &EventId) -> HashMap<Url, AckStatus> {
+        let inner = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        inner
+            .entries
+            .iter()
+            .filter(|((_, id), _)| id == event_id)
+            .map(|((url, _), entry)| (url.clone(), entry.status.clone()))
+            .collect()
+    }
+
+    /// Mark as timed out the pending entries older than `timeout` and drop the entries resolved more than `grace_period` ago
+    ///
+    /// Return the entries that timed out.
+    pub fn sweep(
+        &self,
+        timeout: Duration,
+        grace_period: Duration,
+        now: Instant,
+    ) -> Vec<(Url, EventId)> {
+        let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        let mut timed_out = Vec::new();
+
+        inner
+            .entries
+            .retain(|(url, id), entry| match entry.resolved_at {
+                Some(resolved_at) => now.saturating_duration_since(resolved_at) < grace_period,
+                None => {
+                    if now.saturating_duration_since(entry.sent_at) >= timeout {
+                        entry.status = AckStatus::TimedOut;
+                        entry.resolved_at = Some(now);
+                        timed_out.push((url.clone(), *id));
+                    }
+                    true
+                }
+            });
+
+        // Drop stale keys
+        let entries = &inner.entries;
+        let order: VecDeque<(Url, EventId, Instant)> = inner
+            .order
+            .iter()
+            .filter(|(url, id, sent_at)| {
+                matches!(entries.get(&(url.clone(), *id)), Some(entry) if entry.sent_at == *sent_at)
+            })
+            .cloned()
+            .collect();
+        inner.order = order;
+
+        timed_out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+    const GRACE_PERIOD: Duration = Duration::from_secs(60);
+
+    fn url(n: u8) -> Url {
+        Url::parse(&format!("wss://relay{n}.example.com")).unwrap()
+    }
+
+    fn id(n: u8) -> EventId {
+        EventId::from_slice(&[n; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_ack_status() {
+        let tracker = AckTracker::new(100);
+        let now = Instant::now();
+
+        tracker.pending(url(1), id(1), now);
+        tracker.pending(url(2), id(1), now);
+        tracker.pending(url(3), id(1), now);
+        tracker.resolve(url(1), id(1), true, String::new(), now);
+        tracker.resolve(url(2), id(1), false, String::from("blocked: spam"), now);
+
+        let status = tracker.status(&id(1));
+        assert_eq!(status.len(), 3);
+        assert_eq!(status.get(&url(1)), Some(&AckStatus::Accepted));
+        assert_eq!(
+            status.get(&url(2)),
+            Some(&AckStatus::Rejected(String::from("blocked: spam")))
+        );
+        assert_eq!(status.get(&url(3)), Some(&AckStatus::Pending));
+
+        // Unknown event
+        tracker.resolve(url(1), id(2), true, String::new(), now);
+        assert!(tracker.status(&id(2)).is_empty());
+    }
+
+    #[test]
+    fn test_sweep() {
+        let tracker = AckTracker::new(100);
+        let now = Instant::now();
+
+        tracker.pending(url(1), id(1), now);
+        tracker.pending(url(2), id(1), now);
+        tracker.resolve(url(1), id(1), true, String::new(), now);
+
+        // Not yet timed out
+        assert!(tracker.sweep(TIMEOUT, GRACE_PERIOD, now).is_empty());
+
+        // Timed out
+        let now = now + TIMEOUT;
+        assert_eq!(
+            tracker.sweep(TIMEOUT, GRACE_PERIOD, now),
+            vec![(url(2), id(1))]
+        );
+        assert_eq!(
+            tracker.status(&id(1)).get(&url(2)),
+            Some(&AckStatus::TimedOut)
+        );
+        assert!(tracker.sweep(TIMEOUT, GRACE_PERIOD, now).is_empty());
+
+        // Late OK
+        tracker.resolve(url(2), id(1), true, String::new(), now);
+        assert_eq!(
+            tracker.status(&id(1)).get(&url(2)),
+            Some(&AckStatus::Accepted)
+        );
+
+        // Grace period elapsed
+        tracker.sweep(TIMEOUT, GRACE_PERIOD, now + GRACE_PERIOD);
+        assert!(tracker.status(&id(1)).is_empty());
+    }
+
+    #[test]
+    fn test_capacity() {
+        let tracker = AckTracker::new(2);
+        let now = Instant::now();
+
+        tracker.pending(url(1), id(1), now);
+        tracker.pending(url(1), id(2), now);
+        tracker.pending(url(1), id(3), now);
+
+        assert!(tracker.status(&id(1)).is_empty());
+        assert_eq!(tracker.status(&id(2)).len(), 1);
+        assert_eq!(tracker.status(&id(3)).len(), 1);
+    }
+
+    #[test]
+    fn test_capacity_sent_again() {
+        let tracker = AckTracker::new(2);
+        let now = Instant::now();
+
+        tracker.pending(url(1), id(1), now);
+        tracker.pending(url(1), id(2), now);
+        // Sent again: no longer the oldest
+        tracker.pending(url(1), id(1), now + TIMEOUT);
+        tracker.pending(url(1), id(3), now + TIMEOUT);
+
+        assert_eq!(tracker.status(&id(1)).len(), 1);
+        assert!(tracker.status(&id(2)).is_empty());
+        assert_eq!(tracker.status(&id(3)).len(), 1);
+    }
+}
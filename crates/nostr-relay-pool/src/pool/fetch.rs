@@ -0,0 +1,229 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Latency-budgeted fetch
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::Duration;
+
+use nostr::{Event, EventId, Filter, Url};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+/// Fetch policy
+///
+/// Allow to return early with partial results, without waiting for all relays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FetchPolicy {
+    pub(crate) soft_deadline: Option<Duration>,
+    pub(crate) hard_deadline: Duration,
+    pub(crate) stream_latecomers: bool,
+}
+
+impl Default for FetchPolicy {
+    fn default() -> Self {
+        Self {
+            soft_deadline: None,
+            hard_deadline: Duration::from_secs(10),
+            stream_latecomers: false,
+        }
+    }
+}
+
+impl FetchPolicy {
+    /// New default [`FetchPolicy`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Soft deadline (default: None)
+    ///
+    /// After this time, return the events received so far if at least one relay sent the EOSE.
+    /// If no relay sent the EOSE yet, return as soon as the first one does it (or at the hard deadline).
+    pub fn soft_deadline(mut self, deadline: Option<Duration>) -> Self {
+        self.soft_deadline = deadline;
+        self
+    }
+
+    /// Hard deadline (default: 10 secs)
+    ///
+    /// Max time to wait for relays.
+    pub fn hard_deadline(mut self, deadline: Duration) -> Self {
+        self.hard_deadline = deadline;
+        self
+    }
+
+    /// Keep streaming events of relays that not completed before returning (default: false)
+    ///
+    /// Check [`FetchOutput::latecomers`].
+    pub fn stream_latecomers(mut self, stream: bool) -> Self {
+        self.stream_latecomers = stream;
+        self
+    }
+}
+
+/// Relay fetch status
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelayFetchStatus {
+    /// Still waiting for the relay (returned before the hard deadline)
+    Pending,
+    /// End of stored events received
+    Eose,
+    /// Timeout reached before receiving EOSE
+    TimedOut,
+    /// Error
+    Error(String),
+}
+
+/// Relay fetch report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayFetchReport {
+    /// Status
+    pub status: RelayFetchStatus,
+    /// Number of received events (including the duplicates)
+    pub events: usize,
+}
+
+impl Default for RelayFetchReport {
+    fn default() -> Self {
+        Self {
+            status: RelayFetchStatus::Pending,
+            events: 0,
+        }
+    }
+}
+
+/// Fetch report
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FetchReport {
+    /// Per-relay report
+    pub relays: HashMap<Url, RelayFetchReport>,
+}
+
+impl FetchReport {
+    /// Number of relays that sent the EOSE
+    pub fn completed(&self) -> usize {
+        self.relays
+            .values()
+            .filter(|r| r.status == RelayFetchStatus::Eose)
+            .count()
+    }
+
+    /// Number of queried relays
+    pub fn total(&self) -> usize {
+        self.relays.len()
+    }
+
+    /// Check if all relays sent the EOSE
+    pub fn is_complete(&self) -> bool {
+        self.completed() == self.total()
+    }
+}
+
+/// Fetch output
+#[derive(Debug)]
+pub struct FetchOutput {
+    /// Events
+    pub events: Vec<Event>,
+    /// Report
+    pub report: FetchReport,
+    /// Relays that delivered each event, duplicates included (provenance)
+    ///
+    /// Events loaded from the database and not received from any relay are missing.
+    pub seen_on: HashMap<EventId, HashSet<Url>>,
+    /// Stream of events received from relays that not completed in time
+    ///
+    /// Available only if [`FetchPolicy::stream_latecomers`] is enabled and some relays are still pending.
+    pub latecomers: Option<FetchStream>,
+}
+
+/// Stream of events received after the fetch returned
+#[derive(Debug)]
+pub struct FetchStream {
+    pub(crate) receiver: UnboundedReceiver<Event>,
+}
+
+impl FetchStream {
+    /// Get next event
+    ///
+    /// Return `None` when all the pending relays completed (or reached the hard deadline).
+    pub async fn next(&mut self) -> Option<Event> {
+        self.receiver.recv().await
+    }
+}
+
+/// Events attributed to the filters they match
+///
+/// The attribution is computed once per event, when it's received.
+#[derive(Debug, Default)]
+pub(crate) struct FilterMatches {
+    filters: Vec<Filter>,
+    ids: HashSet<EventId>,
+    events: Vec<BTreeSet<Event>>,
+}
+
+impl FilterMatches {
+    pub fn new(filters: Vec<Filter>) -> Self {
+        Self {
+            events: vec![BTreeSet::new(); filters.len()],
+            filters,
+            ids: HashSet::new(),
+        }
+    }
+
+    /// Add the event to the collections of all the filters it matches
+    pub fn insert(&mut self, event: Event) {
+        if !self.ids.insert(event.id) {
+            return;
+        }
+
+        for (filter, events) in self.filters.iter().zip(self.events.iter_mut()) {
+            if filter.match_event(&event) {
+                events.insert(event.clone());
+            }
+        }
+    }
+
+    /// Events aligned with the filters, newest first
+    pub fn into_events(self) -> Vec<Vec<Event>> {
+        self.events
+            .into_iter()
+            .map(|events| events.into_iter().rev().collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys, Kind};
+
+    use super::*;
+
+    #[test]
+    fn test_filter_matches() {
+        let keys = Keys::generate();
+        let note = EventBuilder::text_note("Text note", [])
+            .to_event(&keys)
+            .unwrap();
+        let metadata = EventBuilder::new(Kind::Metadata, "{}", [])
+            .to_event(&keys)
+            .unwrap();
+
+        let filters = vec![
+            Filter::new().kind(Kind::TextNote),
+            Filter::new().author(keys.public_key()),
+            Filter::new().kind(Kind::ContactList),
+        ];
+        let mut matches = FilterMatches::new(filters);
+        matches.insert(note.clone());
+        matches.insert(metadata.clone());
+        matches.insert(note.clone());
+
+        let events = matches.into_events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0], vec![note.clone()]);
+        assert_eq!(events[1].len(), 2);
+        assert!(events[1].contains(&note) && events[1].contains(&metadata));
+        assert!(events[2].is_empty());
+    }
+}
@@ -0,0 +1,35 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Raw event handler
+
+use core::fmt;
+use std::sync::Arc;
+
+use nostr::{Event, Url};
+
+/// Low-level event handler
+///
+/// [`RawEventHandler::on_event`] is called **synchronously** on the relay receiving task,
+/// for every new event received, without spawning tasks or passing through channels.
+///
+/// # Constraints
+///
+/// The handler **must return immediately**: never block, await I/O or run heavy computations inside it.
+/// While the handler is running, the relay doesn't read any other message.
+///
+/// Use it for cheap operations, like high-frequency logging, metrics collection or event counting.
+pub trait RawEventHandler {
+    /// Handle event
+    fn on_event(&self, relay_url: &Url, sub_id: &str, event: &Event);
+}
+
+#[derive(Clone)]
+pub(crate) struct SharedRawEventHandler(pub Arc<dyn RawEventHandler + Send + Sync>);
+
+impl fmt::Debug for SharedRawEventHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SharedRawEventHandler").finish()
+    }
+}
@@ -4,7 +4,7 @@
 
 //! Relay Pool
 
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -12,17 +12,34 @@ use std::time::Duration;
 use async_utility::{thread, time};
 use atomic_destructor::AtomicDestroyer;
 use nostr::message::MessageHandleError;
-use nostr::{ClientMessage, Event, EventId, Filter, SubscriptionId, Timestamp, TryIntoUrl, Url};
+use nostr::types::time::Instant;
+use nostr::{
+    ClientMessage, Event, EventId, Filter, FromRelayStr, JsonUtil, RelayMessage, SubscriptionId,
+    Timestamp, TryIntoUrl, Url,
+};
 use nostr_database::{DatabaseError, DynNostrDatabase, IntoNostrDatabase, Order};
 use thiserror::Error;
-use tokio::sync::{broadcast, Mutex, RwLock};
-
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
+
+use super::ack::{AckStatus, AckTracker};
+use super::fetch::{
+    FetchOutput, FetchPolicy, FetchReport, FetchStream, FilterMatches, RelayFetchReport,
+    RelayFetchStatus,
+};
+use super::handler::{RawEventHandler, SharedRawEventHandler};
+use super::memory::{MemoryReport, MemoryUsage};
 use super::options::RelayPoolOptions;
+use super::reorder::{Released, ReorderBuffer};
 use super::RelayPoolNotification;
-use crate::relay::options::{FilterOptions, NegentropyOptions, RelayOptions, RelaySendOptions};
-use crate::relay::{Error as RelayError, Relay};
+use crate::relay::options::{
+    FilterOptions, NegentropyOptions, RelayOptions, RelaySendOptions, ReorderOptions,
+};
+use crate::relay::{Error as RelayError, Relay, SubscriptionActivity};
 use crate::SubscribeOptions;
 
+/// Interval of the acknowledgements sweeps
+const ACK_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
 /// [`RelayPool`](super::RelayPool) error
 #[derive(Debug, Error)]
 pub enum Error {
@@ -64,13 +81,76 @@ pub enum Error {
     Handler(String),
 }
 
+struct FetchState {
+    ids: HashSet<EventId>,
+    events: BTreeSet<Event>,
+    seen_on: HashMap<EventId, HashSet<Url>>,
+    report: FetchReport,
+    /// Sender for events received after the fetch returned
+    latecomers: Option<mpsc::UnboundedSender<Event>>,
+}
+
+impl FetchState {
+    fn on_event(&mut self, url: &Url, event: Event) {
+        if let Some(report) = self.report.relays.get_mut(url) {
+            report.events += 1;
+        }
+
+        // Record provenance also for duplicates
+        self.seen_on
+            .entry(event.id())
+            .or_default()
+            .insert(url.clone());
+
+        if self.ids.insert(event.id()) {
+            if let Some(tx) = &self.latecomers {
+                let _ = tx.send(event.clone());
+            }
+            self.events.insert(event);
+        }
+    }
+
+    fn on_complete(&mut self, url: &Url, status: RelayFetchStatus) {
+        if let Some(report) = self.report.relays.get_mut(url) {
+            report.status = status;
+        }
+
+        // Close the latecomers stream when no more relays are pending
+        if self
+            .report
+            .relays
+            .values()
+            .all(|r| r.status != RelayFetchStatus::Pending)
+        {
+            self.latecomers = None;
+        }
+    }
+
+    /// Hard deadline reached: the relays still pending timed out
+    fn on_hard_deadline(&mut self) {
+        for report in self.report.relays.values_mut() {
+            if report.status == RelayFetchStatus::Pending {
+                report.status = RelayFetchStatus::TimedOut;
+            }
+        }
+
+        self.latecomers = None;
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct InternalRelayPool {
     database: Arc<DynNostrDatabase>,
     relays: Arc<RwLock<HashMap<Url, Relay>>>,
     notification_sender: broadcast::Sender<RelayPoolNotification>,
     subscriptions: Arc<RwLock<HashMap<SubscriptionId, Vec<Filter>>>>,
-    // opts: RelayPoolOptions,
+    /// Subscriptions insertion order, used to evict the oldest ones
+    subscriptions_order: Arc<RwLock<VecDeque<SubscriptionId>>>,
+    reordered: Arc<RwLock<HashSet<SubscriptionId>>>,
+    raw_event_handler: Arc<RwLock<Option<SharedRawEventHandler>>>,
+    ack: AckTracker,
+    ack_listener: Arc<AtomicBool>,
+    opts: RelayPoolOptions,
 }
 
 impl AtomicDestroyer for InternalRelayPool {
@@ -100,7 +180,12 @@ impl InternalRelayPool {
             relays: Arc::new(RwLock::new(HashMap::new())),
             notification_sender,
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
-            //opts,
+            subscriptions_order: Arc::new(RwLock::new(VecDeque::new())),
+            reordered: Arc::new(RwLock::new(HashSet::new())),
+            raw_event_handler: Arc::new(RwLock::new(None)),
+            ack: AckTracker::new(opts.memory_limits.acks),
+            ack_listener: Arc::new(AtomicBool::new(false)),
+            opts,
         }
     }
 
@@ -136,6 +221,23 @@ impl InternalRelayPool {
         self.notification_sender.subscribe()
     }
 
+    pub async fn set_global_event_handler(
+        &self,
+        handler: Option<Arc<dyn RawEventHandler + Send + Sync>>,
+    ) {
+        let mut raw_event_handler = self.raw_event_handler.write().await;
+        *raw_event_handler = handler.clone().map(SharedRawEventHandler);
+
+        let relays = self.relays.read().await;
+        for relay in relays.values() {
+            relay.set_raw_event_handler(handler.clone()).await;
+        }
+    }
+
+    pub fn send_notification(&self, notification: RelayPoolNotification) {
+        let _ = self.notification_sender.send(notification);
+    }
+
     pub fn database(&self) -> Arc<DynNostrDatabase> {
         self.database.clone()
     }
@@ -168,20 +270,192 @@ impl InternalRelayPool {
         subscriptions.get(id).cloned()
     }
 
-    async fn update_subscription(&self, id: SubscriptionId, filters: Vec<Filter>) {
+    pub async fn subscriptions_activity(
+        &self,
+    ) -> HashMap<Url, HashMap<SubscriptionId, SubscriptionActivity>> {
+        let relays = self.relays().await;
+        let mut activity = HashMap::with_capacity(relays.len());
+        for (url, relay) in relays.into_iter() {
+            activity.insert(url, relay.subscriptions_activity().await);
+        }
+        activity
+    }
+
+    /// Update subscription
+    ///
+    /// Return the subscriptions evicted to respect [`MemoryLimits::subscriptions`](super::MemoryLimits::subscriptions).
+    async fn update_subscription(
+        &self,
+        id: SubscriptionId,
+        filters: Vec<Filter>,
+    ) -> Vec<SubscriptionId> {
         let mut subscriptions = self.subscriptions.write().await;
-        let current: &mut Vec<Filter> = subscriptions.entry(id).or_default();
-        *current = filters;
+        let mut order = self.subscriptions_order.write().await;
+        let mut evicted: Vec<SubscriptionId> = Vec::new();
+
+        if !subscriptions.contains_key(&id) {
+            while subscriptions.len() >= self.opts.memory_limits.subscriptions {
+                match order.pop_front() {
+                    Some(oldest) => {
+                        subscriptions.remove(&oldest);
+                        evicted.push(oldest);
+                    }
+                    None => break,
+                }
+            }
+            order.push_back(id.clone());
+        }
+
+        subscriptions.insert(id, filters);
+        evicted
     }
 
     pub(crate) async fn remove_subscription(&self, id: &SubscriptionId) {
         let mut subscriptions = self.subscriptions.write().await;
         subscriptions.remove(id);
+        drop(subscriptions);
+
+        let mut order = self.subscriptions_order.write().await;
+        order.retain(|i| i != id);
+        drop(order);
+
+        let mut reordered = self.reordered.write().await;
+        reordered.remove(id);
     }
 
     pub(crate) async fn remove_all_subscriptions(&self) {
         let mut subscriptions = self.subscriptions.write().await;
         subscriptions.clear();
+        drop(subscriptions);
+
+        let mut order = self.subscriptions_order.write().await;
+        order.clear();
+        drop(order);
+
+        let mut reordered = self.reordered.write().await;
+        reordered.clear();
+    }
+
+    /// Deliver the events of the subscription in `created_at` order, until it's removed
+    async fn spawn_reorder_buffer(&self, id: SubscriptionId, opts: ReorderOptions) {
+        let mut reordered = self.reordered.write().await;
+        if !reordered.insert(id.clone()) {
+            return;
+        }
+        drop(reordered);
+
+        let pool = self.clone();
+        let mut notifications = self.notifications();
+        let _ = thread::spawn(async move {
+            let mut buffer = ReorderBuffer::new(opts);
+
+            loop {
+                let timeout: Duration = buffer.next_deadline(Instant::now());
+                let mut released: Vec<Released> =
+                    match time::timeout(Some(timeout), notifications.recv()).await {
+                        Some(Ok(RelayPoolNotification::Event {
+                            relay_url,
+                            subscription_id,
+                            event,
+                        })) if subscription_id == id => {
+                            buffer.push(relay_url, event, Instant::now())
+                        }
+                        Some(Ok(RelayPoolNotification::Message {
+                            message: RelayMessage::EndOfStoredEvents(subscription_id),
+                            ..
+                        })) if subscription_id == id => buffer.flush(),
+                        Some(Ok(RelayPoolNotification::Stop)) => buffer.flush(),
+                        Some(Ok(RelayPoolNotification::Shutdown))
+                        | Some(Err(broadcast::error::RecvError::Closed)) => break,
+                        _ => Vec::new(),
+                    };
+                released.extend(buffer.poll(Instant::now()));
+
+                for r in released.into_iter() {
+                    pool.send_notification(RelayPoolNotification::OrderedEvent {
+                        relay_url: r.relay_url,
+                        subscription_id: id.clone(),
+                        event: r.event,
+                        out_of_order: r.out_of_order,
+                    });
+                }
+
+                // Check if subscription was removed
+                let reordered = pool.reordered.read().await;
+                if !reordered.contains(&id) {
+                    break;
+                }
+            }
+
+            tracing::debug!("Reorder buffer of subscription {id} dropped");
+        });
+    }
+
+    /// Resolve the pending acknowledgements and notify the timed out ones
+    fn spawn_ack_listener(&self) {
+        if self.ack_listener.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let pool = self.clone();
+        let mut notifications = self.notifications();
+        let res = thread::spawn(async move {
+            loop {
+                match time::timeout(Some(ACK_SWEEP_INTERVAL), notifications.recv()).await {
+                    Some(Ok(RelayPoolNotification::Message {
+                        relay_url,
+                        message:
+                            RelayMessage::Ok {
+                                event_id,
+                                status,
+                                message,
+                            },
+                    })) => pool
+                        .ack
+                        .resolve(relay_url, event_id, status, message, Instant::now()),
+                    Some(Ok(RelayPoolNotification::Shutdown))
+                    | Some(Err(broadcast::error::RecvError::Closed)) => break,
+                    _ => (),
+                }
+
+                let timed_out = pool.ack.sweep(
+                    pool.opts.ack_timeout,
+                    pool.opts.ack_grace_period,
+                    Instant::now(),
+                );
+                for (relay_url, event_id) in timed_out.into_iter() {
+                    pool.send_notification(RelayPoolNotification::AckTimeout {
+                        relay_url,
+                        event_id,
+                    });
+                }
+            }
+
+            pool.ack_listener.store(false, Ordering::SeqCst);
+            tracing::debug!("Acknowledgements listener exited");
+        });
+
+        if let Err(e) = res {
+            self.ack_listener.store(false, Ordering::SeqCst);
+            tracing::error!("Impossible to spawn acknowledgements listener: {e}");
+        }
+    }
+
+    pub fn ack_status(&self, event_id: &EventId) -> HashMap<Url, AckStatus> {
+        self.ack.status(event_id)
+    }
+
+    fn ack_pending(&self, url: &Url, events: &[Event]) {
+        let now = Instant::now();
+        for event in events.iter() {
+            self.ack.pending(url.clone(), event.id, now);
+        }
+    }
+
+    fn ack_remove(ack: &AckTracker, url: &Url, events: &[Event]) {
+        for event in events.iter() {
+            ack.remove(url, &event.id);
+        }
     }
 
     pub async fn add_relay<U>(&self, url: U, opts: RelayOptions) -> Result<bool, Error>
@@ -190,12 +464,30 @@ impl InternalRelayPool {
         Error: From<<U as TryIntoUrl>::Err>,
     {
         let url: Url = url.try_into_url()?;
+        let url: Url = Url::from_relay_str(url.as_str())?;
         let mut relays = self.relays.write().await;
         if !relays.contains_key(&url) {
+            let opts: RelayOptions = match self.opts.initial_connection_jitter {
+                Some(max) if !opts.has_initial_connection_delay() => {
+                    opts.initial_connection_delay(Duration::ZERO..max)
+                }
+                _ => opts,
+            };
+
+            // Apply memory limits
+            let limits = self.opts.memory_limits;
+            let notices: usize = opts.get_notices_history().min(limits.notices);
+            let opts: RelayOptions = opts
+                .notices_history(notices)
+                .subscriptions_ceiling(limits.subscriptions);
             let relay = Relay::custom(url, self.database.clone(), opts);
             relay
                 .set_notification_sender(Some(self.notification_sender.clone()))
                 .await;
+            let raw_event_handler = self.raw_event_handler.read().await;
+            relay
+                .set_raw_event_handler(raw_event_handler.as_ref().map(|h| h.0.clone()))
+                .await;
             relays.insert(relay.url(), relay);
             Ok(true)
         } else {
@@ -277,7 +569,7 @@ impl InternalRelayPool {
         // Save events into database
         for msg in msgs.iter() {
             if let ClientMessage::Event(event) = msg {
-                self.database.save_event(event).await?;
+                self.save_event(event).await?;
             }
         }
 
@@ -328,6 +620,17 @@ impl InternalRelayPool {
         Ok(())
     }
 
+    /// Save sent event into database
+    ///
+    /// Ephemeral events are skipped, unless [`RelayPoolOptions::save_ephemeral_events`] is enabled.
+    async fn save_event(&self, event: &Event) -> Result<(), Error> {
+        if event.is_ephemeral() && !self.opts.save_ephemeral_events {
+            return Ok(());
+        }
+        self.database.save_event(event).await?;
+        Ok(())
+    }
+
     pub async fn send_event(&self, event: Event, opts: RelaySendOptions) -> Result<EventId, Error> {
         let relays: HashMap<Url, Relay> = self.relays().await;
         self.send_event_to(relays.into_keys(), event, opts).await
@@ -382,7 +685,7 @@ impl InternalRelayPool {
 
         // Save events into database
         for event in events.iter() {
-            self.database.save_event(event).await?;
+            self.save_event(event).await?;
         }
 
         // Get relays
@@ -392,11 +695,18 @@ impl InternalRelayPool {
             return Err(Error::NoRelays);
         }
 
+        // Track acknowledgements
+        self.spawn_ack_listener();
+
         // If passed only 1 url, not use threads
         if urls.len() == 1 {
             let url: Url = urls.into_iter().next().ok_or(Error::RelayNotFound)?;
             let relay: &Relay = relays.get(&url).ok_or(Error::RelayNotFound)?;
-            relay.batch_event(events, opts).await?;
+            self.ack_pending(&url, &events);
+            if let Err(e) = relay.batch_event(events.clone(), opts).await {
+                Self::ack_remove(&self.ack, &url, &events);
+                return Err(e.into());
+            }
         } else {
             // Check if urls set contains ONLY already added relays
             if !urls.iter().all(|url| relays.contains_key(url)) {
@@ -409,12 +719,17 @@ impl InternalRelayPool {
             for (url, relay) in relays.into_iter().filter(|(url, ..)| urls.contains(url)) {
                 let events = events.clone();
                 let sent = sent_to_at_least_one_relay.clone();
+                let ack = self.ack.clone();
+                self.ack_pending(&url, &events);
                 let handle = thread::spawn(async move {
-                    match relay.batch_event(events, opts).await {
+                    match relay.batch_event(events.clone(), opts).await {
                         Ok(_) => {
                             sent.store(true, Ordering::SeqCst);
                         }
-                        Err(e) => tracing::error!("Impossible to send event to {url}: {e}"),
+                        Err(e) => {
+                            Self::ack_remove(&ack, &url, &events);
+                            tracing::error!("Impossible to send event to {url}: {e}");
+                        }
                     }
                 })?;
                 handles.push(handle);
@@ -450,20 +765,142 @@ impl InternalRelayPool {
         // Check if isn't auto-closing subscription
         if !opts.is_auto_closing() {
             // Update pool subscriptions
-            self.update_subscription(id.clone(), filters.clone()).await;
+            let evicted = self.update_subscription(id.clone(), filters.clone()).await;
+            for evicted in evicted.into_iter() {
+                self.evict_subscription(&relays, evicted).await;
+            }
+
+            // Reorder events
+            if let Some(reorder) = opts.reorder {
+                self.spawn_reorder_buffer(id.clone(), reorder).await;
+            }
         }
 
         // Subscribe
         for relay in relays.values() {
-            if let Err(e) = relay
+            match relay
+                .inner
                 .subscribe_with_id(id.clone(), filters.clone(), opts)
                 .await
+            {
+                Ok(evicted) => {
+                    // Evicted by the relay limit: stop tracking them in the pool
+                    for evicted in evicted.iter() {
+                        self.remove_subscription(evicted).await;
+                    }
+                }
+                Err(e) => tracing::error!("{e}"),
+            }
+        }
+    }
+
+    /// Close a subscription evicted from the pool
+    async fn evict_subscription(&self, relays: &HashMap<Url, Relay>, id: SubscriptionId) {
+        tracing::warn!(
+            "Subscriptions limit reached (max. {}): evicted subscription {id}",
+            self.opts.memory_limits.subscriptions
+        );
+
+        let mut reordered = self.reordered.write().await;
+        reordered.remove(&id);
+        drop(reordered);
+
+        for (url, relay) in relays.iter() {
+            if let Err(e) = relay
+                .unsubscribe(id.clone(), RelaySendOptions::default())
+                .await
             {
                 tracing::error!("{e}");
             }
+            self.send_notification(RelayPoolNotification::SubscriptionEvicted {
+                relay_url: url.clone(),
+                subscription_id: id.clone(),
+            });
         }
     }
 
+    pub async fn memory_report(&self) -> MemoryReport {
+        let limits = self.opts.memory_limits;
+        let mut report = MemoryReport::default();
+
+        let subscriptions = self.subscriptions.read().await;
+        let bytes: usize = subscriptions
+            .iter()
+            .map(|(id, filters)| {
+                id.to_string().len() + filters.iter().map(|f| f.as_json().len()).sum::<usize>()
+            })
+            .sum();
+        report.insert(
+            "pool.subscriptions",
+            MemoryUsage::new(subscriptions.len(), Some(limits.subscriptions), bytes),
+        );
+        drop(subscriptions);
+
+        let (entries, bytes) = self.ack.usage();
+        report.insert(
+            "pool.acks",
+            MemoryUsage::new(entries, Some(limits.acks), bytes),
+        );
+
+        let relays = self.relays().await;
+        let mut subscriptions = MemoryUsage::new(
+            0,
+            Some(limits.subscriptions.saturating_mul(relays.len())),
+            0,
+        );
+        let mut notices = MemoryUsage::new(0, Some(limits.notices.saturating_mul(relays.len())), 0);
+        let mut requested = MemoryUsage::new(0, Some(0), 0);
+        let mut traffic = MemoryUsage::new(0, Some(0), 0);
+        let mut queue = MemoryUsage::new(0, Some(0), 0);
+        #[cfg(feature = "nip11")]
+        let mut documents = MemoryUsage::new(relays.len(), Some(relays.len()), 0);
+        for relay in relays.values() {
+            let (entries, bytes) = relay.inner.subscriptions_usage().await;
+            subscriptions.entries += entries;
+            subscriptions.bytes += bytes;
+
+            let (entries, bytes) = relay.inner.notices.usage().await;
+            notices.entries += entries;
+            notices.bytes += bytes;
+
+            let (entries, limit, bytes) = relay.inner.requested_usage().await;
+            requested.entries += entries;
+            requested.limit = requested.limit.map(|l| l.saturating_add(limit));
+            requested.bytes += bytes;
+
+            let (entries, limit, bytes) = relay.inner.traffic_usage();
+            traffic.entries += entries;
+            traffic.limit = traffic.limit.map(|l| l.saturating_add(limit));
+            traffic.bytes += bytes;
+
+            let (entries, limit, bytes) = relay.inner.queue_usage();
+            queue.entries += entries;
+            queue.limit = queue.limit.map(|l| l.saturating_add(limit));
+            queue.bytes += bytes;
+
+            #[cfg(feature = "nip11")]
+            {
+                documents.bytes += relay.inner.document_usage().await;
+            }
+        }
+        report.insert("relay.subscriptions", subscriptions);
+        report.insert("relay.notices", notices);
+        report.insert("relay.requested", requested);
+        report.insert("relay.traffic", traffic);
+        report.insert("relay.queue", queue);
+        #[cfg(feature = "nip11")]
+        report.insert("relay.nip11", documents);
+
+        // Events deduplication
+        match self.database.seen_cache_usage().await {
+            Ok(Some(usage)) => report.insert("database.seen", usage),
+            Ok(None) => (),
+            Err(e) => tracing::error!("Impossible to get seen cache usage: {e}"),
+        }
+
+        report
+    }
+
     pub async fn unsubscribe(&self, id: SubscriptionId, opts: RelaySendOptions) {
         let relays = self.relays().await;
         self.remove_subscription(&id).await;
@@ -566,6 +1003,216 @@ impl InternalRelayPool {
         }
     }
 
+    pub async fn get_events_map_from<I, U>(
+        &self,
+        urls: I,
+        filters: Vec<Filter>,
+        timeout: Duration,
+        opts: FilterOptions,
+    ) -> Result<Vec<Vec<Event>>, Error>
+    where
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let urls: HashSet<Url> = urls
+            .into_iter()
+            .map(|u| u.try_into_url())
+            .collect::<Result<_, _>>()?;
+
+        let relays: HashMap<Url, Relay> = self.relays().await;
+
+        // Check if urls set contains ONLY already added relays
+        if !urls.iter().all(|url| relays.contains_key(url)) {
+            return Err(Error::RelayNotFound);
+        }
+
+        let stored_events: Vec<Event> = self
+            .database
+            .query(filters.clone(), Order::Desc)
+            .await
+            .unwrap_or_default();
+
+        let mut matches: FilterMatches = FilterMatches::new(filters.clone());
+        for event in stored_events.into_iter() {
+            matches.insert(event);
+        }
+        let matches: Arc<Mutex<FilterMatches>> = Arc::new(Mutex::new(matches));
+
+        // Filter relays and start query (single REQ with all the filters)
+        let mut handles = Vec::with_capacity(urls.len());
+        for (url, relay) in relays.into_iter().filter(|(url, ..)| urls.contains(url)) {
+            let filters = filters.clone();
+            let matches = matches.clone();
+            let handle = thread::spawn(async move {
+                if let Err(e) = relay
+                    .get_events_of_with_callback(filters, timeout, opts, |event| async {
+                        let mut matches = matches.lock().await;
+                        matches.insert(event);
+                    })
+                    .await
+                {
+                    tracing::error!("Failed to get events from {url}: {e}");
+                }
+            })?;
+            handles.push(handle);
+        }
+
+        // Join threads
+        for handle in handles.into_iter() {
+            handle.join().await?;
+        }
+
+        let mut matches = matches.lock().await;
+        Ok(std::mem::take(&mut *matches).into_events())
+    }
+
+    pub async fn get_events_with_policy<I, U>(
+        &self,
+        urls: I,
+        filters: Vec<Filter>,
+        policy: FetchPolicy,
+    ) -> Result<FetchOutput, Error>
+    where
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let urls: HashSet<Url> = urls
+            .into_iter()
+            .map(|u| u.try_into_url())
+            .collect::<Result<_, _>>()?;
+
+        if urls.is_empty() {
+            return Err(Error::NoRelaysSpecified);
+        }
+
+        let relays: HashMap<Url, Relay> = self.relays().await;
+
+        // Check if urls set contains ONLY already added relays
+        if !urls.iter().all(|url| relays.contains_key(url)) {
+            return Err(Error::RelayNotFound);
+        }
+
+        let stored_events: Vec<Event> = self
+            .database
+            .query(filters.clone(), Order::Desc)
+            .await
+            .unwrap_or_default();
+
+        let state: Arc<Mutex<FetchState>> = Arc::new(Mutex::new(FetchState {
+            ids: stored_events.iter().map(|e| e.id()).collect(),
+            events: stored_events.into_iter().collect(),
+            seen_on: HashMap::new(),
+            report: FetchReport {
+                relays: urls
+                    .iter()
+                    .map(|url| (url.clone(), RelayFetchReport::default()))
+                    .collect(),
+            },
+            latecomers: None,
+        }));
+
+        // Used by relay tasks to notify completion
+        let (done_tx, mut done_rx) = mpsc::unbounded_channel::<()>();
+
+        let total: usize = urls.len();
+        let timeout: Duration = policy.hard_deadline;
+        for (url, relay) in relays.into_iter().filter(|(url, ..)| urls.contains(url)) {
+            let filters = filters.clone();
+            let state = state.clone();
+            let done_tx = done_tx.clone();
+            thread::spawn(async move {
+                let res = relay
+                    .get_events_of_with_callback(
+                        filters,
+                        timeout,
+                        FilterOptions::ExitOnEOSE,
+                        |event| async {
+                            let mut state = state.lock().await;
+                            state.on_event(&url, event);
+                        },
+                    )
+                    .await;
+
+                let status: RelayFetchStatus = match res {
+                    Ok(..) => RelayFetchStatus::Eose,
+                    Err(RelayError::Timeout) => RelayFetchStatus::TimedOut,
+                    Err(e) => {
+                        tracing::error!("Failed to get events from {url}: {e}");
+                        RelayFetchStatus::Error(e.to_string())
+                    }
+                };
+
+                let mut state = state.lock().await;
+                state.on_complete(&url, status);
+                drop(state);
+
+                let _ = done_tx.send(());
+            })?;
+        }
+
+        // Drop the original sender, so `done_rx` will be closed when all tasks complete
+        drop(done_tx);
+
+        let mut completed: usize = 0;
+
+        // Wait for all relays until the soft deadline (or the hard one, if not set)
+        let first_deadline: Duration = match policy.soft_deadline {
+            Some(soft) => soft.min(policy.hard_deadline),
+            None => policy.hard_deadline,
+        };
+        let res = time::timeout(Some(first_deadline), async {
+            while completed < total && done_rx.recv().await.is_some() {
+                completed += 1;
+            }
+        })
+        .await;
+        let mut hard_deadline_reached: bool =
+            res.is_none() && first_deadline >= policy.hard_deadline;
+
+        // Soft deadline reached: wait until at least one relay send EOSE (or the hard deadline)
+        if completed < total && !hard_deadline_reached {
+            if let Some(remaining) = policy.hard_deadline.checked_sub(first_deadline) {
+                let res = time::timeout(Some(remaining), async {
+                    while state.lock().await.report.completed() == 0
+                        && done_rx.recv().await.is_some()
+                    {}
+                })
+                .await;
+                hard_deadline_reached = res.is_none();
+            }
+        }
+
+        let mut state = state.lock().await;
+
+        // Don't report as pending the relays that not completed in time
+        if hard_deadline_reached {
+            state.on_hard_deadline();
+        }
+
+        let latecomers: Option<FetchStream> = if policy.stream_latecomers
+            && state
+                .report
+                .relays
+                .values()
+                .any(|r| r.status == RelayFetchStatus::Pending)
+        {
+            let (tx, rx) = mpsc::unbounded_channel();
+            state.latecomers = Some(tx);
+            Some(FetchStream { receiver: rx })
+        } else {
+            None
+        };
+
+        Ok(FetchOutput {
+            events: state.events.iter().rev().cloned().collect(),
+            report: state.report.clone(),
+            seen_on: state.seen_on.clone(),
+            latecomers,
+        })
+    }
+
     pub async fn connect(&self, connection_timeout: Option<Duration>) {
         let relays: HashMap<Url, Relay> = self.relays().await;
 
@@ -0,0 +1,101 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Memory limits and usage report
+
+use std::collections::BTreeMap;
+
+pub use nostr_database::MemoryUsage;
+
+/// Default max number of subscriptions
+pub const DEFAULT_MAX_SUBSCRIPTIONS: usize = 10_000;
+/// Default max number of tracked acknowledgements
+pub const DEFAULT_MAX_ACKS: usize = 10_000;
+/// Default max number of notices kept per relay
+pub const DEFAULT_MAX_NOTICES: usize = 1_000;
+
+/// Hard caps of the internal collections
+///
+/// Every collection that can grow over time has a ceiling, with its own eviction policy:
+/// * **subscriptions**: ceiling for the subscriptions of the pool and of each relay.
+///   When full, the oldest subscription of the pool is closed and notified with [`RelayPoolNotification::SubscriptionEvicted`](super::RelayPoolNotification::SubscriptionEvicted).
+///   At relay level, the [`SubscriptionLimitPolicy`](crate::SubscriptionLimitPolicy) is applied;
+/// * **acks**: tracked acknowledgements (see [`RelayPool::ack_status`](super::RelayPool::ack_status)). When full, the oldest entries are dropped;
+/// * **notices**: ceiling for [`RelayOptions::notices_history`](crate::RelayOptions::notices_history). When full, the oldest notices are dropped.
+///
+/// The other collections are bounded by these limits too:
+/// * the filters of the open `REQ`s of each relay, used to detect unsolicited events: twice the **subscriptions** ceiling.
+///   When full, the least recently requested are dropped;
+/// * the traffic stats of each relay (see [`Relay::traffic_report`](crate::Relay::traffic_report)): **subscriptions** ceiling.
+///   When full, the stats of the oldest subscription are dropped;
+/// * the messages waiting to be sent to each relay: fixed size. When full, the new messages are rejected.
+///
+/// The events deduplication cache is owned by the database (see [`MemoryDatabaseOptions::max_events`](nostr_database::MemoryDatabaseOptions::max_events)).
+///
+/// The notification channel has a fixed size (see [`RelayPoolOptions::notification_channel_size`](super::RelayPoolOptions::notification_channel_size)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryLimits {
+    pub(super) subscriptions: usize,
+    pub(super) acks: usize,
+    pub(super) notices: usize,
+}
+
+impl Default for MemoryLimits {
+    fn default() -> Self {
+        Self {
+            subscriptions: DEFAULT_MAX_SUBSCRIPTIONS,
+            acks: DEFAULT_MAX_ACKS,
+            notices: DEFAULT_MAX_NOTICES,
+        }
+    }
+}
+
+impl MemoryLimits {
+    /// New default limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Max number of subscriptions (default: [`DEFAULT_MAX_SUBSCRIPTIONS`])
+    pub fn subscriptions(mut self, max: usize) -> Self {
+        self.subscriptions = max.max(1);
+        self
+    }
+
+    /// Max number of tracked acknowledgements (default: [`DEFAULT_MAX_ACKS`])
+    pub fn acks(mut self, max: usize) -> Self {
+        self.acks = max;
+        self
+    }
+
+    /// Max number of notices kept per relay (default: [`DEFAULT_MAX_NOTICES`])
+    pub fn notices(mut self, max: usize) -> Self {
+        self.notices = max;
+        self
+    }
+}
+
+/// Memory usage report, per component
+///
+/// Sizes are approximate: useful to see where memory goes, not to account it exactly.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    /// Components usage
+    pub components: BTreeMap<String, MemoryUsage>,
+}
+
+impl MemoryReport {
+    /// Add component usage
+    pub fn insert<S>(&mut self, component: S, usage: MemoryUsage)
+    where
+        S: Into<String>,
+    {
+        self.components.insert(component.into(), usage);
+    }
+
+    /// Approximate total size, in bytes
+    pub fn total_bytes(&self) -> usize {
+        self.components.values().map(|u| u.bytes).sum()
+    }
+}
@@ -11,22 +11,44 @@ use std::time::Duration;
 
 use atomic_destructor::AtomicDestructor;
 use nostr::{
-    ClientMessage, Event, EventId, Filter, RelayMessage, Result, SubscriptionId, Timestamp,
-    TryIntoUrl, Url,
+    ClientMessage, Event, EventId, Filter, Kind, MachineReadablePrefix, RelayMessage, Result,
+    SubscriptionId, Timestamp, TryIntoUrl, Url,
 };
 use nostr_database::{DynNostrDatabase, IntoNostrDatabase, MemoryDatabase};
 use tokio::sync::broadcast;
 
+pub mod ack;
+pub mod fetch;
+pub mod handler;
 mod internal;
+pub mod memory;
 pub mod options;
+mod reorder;
 
+pub use self::ack::AckStatus;
+pub use self::fetch::{
+    FetchOutput, FetchPolicy, FetchReport, FetchStream, RelayFetchReport, RelayFetchStatus,
+};
+pub use self::handler::RawEventHandler;
 pub use self::internal::Error;
 use self::internal::InternalRelayPool;
+pub use self::memory::{MemoryLimits, MemoryReport, MemoryUsage};
 pub use self::options::RelayPoolOptions;
 use crate::relay::options::{FilterOptions, NegentropyOptions, RelayOptions, RelaySendOptions};
-use crate::relay::{Relay, RelayStatus};
+use crate::relay::{
+    Relay, RelayHealth, RelayStatus, SubscriptionActivity, SubscriptionStats, TrafficReport,
+};
 use crate::SubscribeOptions;
 
+/// Why a relay requested the authentication (NIP42)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AuthReason {
+    /// Subscription closed with `auth-required`
+    Subscription(SubscriptionId),
+    /// Event rejected with `auth-required`
+    Publish(EventId),
+}
+
 /// Relay Pool Notification
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RelayPoolNotification {
@@ -39,6 +61,18 @@ pub enum RelayPoolNotification {
         /// Event
         event: Box<Event>,
     },
+    /// Received an [`Event`] of a subscription with reordering enabled (see [`SubscribeOptions::reorder`]),
+    /// delivered in `created_at` order.
+    OrderedEvent {
+        /// Relay url
+        relay_url: Url,
+        /// Subscription ID
+        subscription_id: SubscriptionId,
+        /// Event
+        event: Box<Event>,
+        /// The event is older than an event already delivered
+        out_of_order: bool,
+    },
     /// Received a [`RelayMessage`]. Includes messages wrapping events that were sent by this client.
     Message {
         /// Relay url
@@ -53,6 +87,61 @@ pub enum RelayPoolNotification {
         /// Relay Status
         status: RelayStatus,
     },
+    /// Subscription closed to respect the relay subscriptions limit
+    SubscriptionEvicted {
+        /// Relay url
+        relay_url: Url,
+        /// Subscription ID
+        subscription_id: SubscriptionId,
+    },
+    /// Subscription closed by the relay (`CLOSED` message)
+    SubscriptionClosed {
+        /// Relay url
+        relay_url: Url,
+        /// Subscription ID
+        subscription_id: SubscriptionId,
+        /// Machine-readable prefix of the reason, if any
+        prefix: Option<MachineReadablePrefix>,
+        /// Reason
+        reason: String,
+    },
+    /// Relay health classification changed
+    RelayHealthChanged {
+        /// Relay url
+        relay_url: Url,
+        /// Health
+        health: RelayHealth,
+    },
+    /// A relay task panicked
+    ///
+    /// The relay has been terminated: call `Relay::connect` to revive it.
+    RelayError {
+        /// Relay url
+        relay_url: Url,
+        /// Error
+        error: String,
+    },
+    /// The relay requested the authentication (NIP42) but the auth policy declined it
+    AuthDeclined {
+        /// Relay url
+        relay_url: Url,
+        /// Reason
+        reason: AuthReason,
+    },
+    /// No `OK` message received for a sent event within [`RelayPoolOptions::ack_timeout`]
+    AckTimeout {
+        /// Relay url
+        relay_url: Url,
+        /// Event ID
+        event_id: EventId,
+    },
+    /// The newest known version of one of the user's own replaceable events changed
+    SelfUpdated {
+        /// Kind
+        kind: Kind,
+        /// Newest event
+        event: Box<Event>,
+    },
     /// Stop
     Stop,
     /// Shutdown
@@ -104,6 +193,25 @@ impl RelayPool {
         self.inner.notifications()
     }
 
+    /// Set a global low-level event handler, shared by all the relays of the pool
+    ///
+    /// The handler is called **synchronously** on the relay receiving tasks, so it must return immediately.
+    /// Check [`RawEventHandler`] for the constraints.
+    ///
+    /// Use `None` to remove the current handler.
+    #[inline]
+    pub async fn set_global_event_handler(
+        &self,
+        handler: Option<Arc<dyn RawEventHandler + Send + Sync>>,
+    ) {
+        self.inner.set_global_event_handler(handler).await
+    }
+
+    /// Send a notification to the **pool** notification listeners
+    pub fn send_notification(&self, notification: RelayPoolNotification) {
+        self.inner.send_notification(notification)
+    }
+
     /// Get database
     pub fn database(&self) -> Arc<DynNostrDatabase> {
         self.inner.database()
@@ -181,6 +289,46 @@ impl RelayPool {
         self.inner.subscription(id).await
     }
 
+    /// Get activity of subscriptions, per relay
+    pub async fn subscriptions_activity(
+        &self,
+    ) -> HashMap<Url, HashMap<SubscriptionId, SubscriptionActivity>> {
+        self.inner.subscriptions_activity().await
+    }
+
+    /// Get the incoming traffic stats of a subscription, summed over all relays
+    pub async fn subscription_stats(&self, id: &SubscriptionId) -> SubscriptionStats {
+        let mut stats = SubscriptionStats::default();
+        for relay in self.relays().await.into_values() {
+            if let Some(relay_stats) = relay.subscription_stats(id) {
+                stats.merge(&relay_stats);
+            }
+        }
+        stats
+    }
+
+    /// Get the incoming traffic stats of all the subscriptions, summed over all relays
+    pub async fn traffic_report(&self) -> TrafficReport {
+        let mut report = TrafficReport::default();
+        for relay in self.relays().await.into_values() {
+            for (id, relay_stats) in relay.traffic_report().subscriptions.into_iter() {
+                report
+                    .subscriptions
+                    .entry(id)
+                    .or_default()
+                    .merge(&relay_stats);
+            }
+        }
+        report
+    }
+
+    /// Reset the incoming traffic stats of a subscription or, if `None`, of all the subscriptions
+    pub async fn reset_traffic_stats(&self, id: Option<&SubscriptionId>) {
+        for relay in self.relays().await.into_values() {
+            relay.reset_traffic_stats(id);
+        }
+    }
+
     /// Send client message to all connected relays
     pub async fn send_msg(&self, msg: ClientMessage, opts: RelaySendOptions) -> Result<(), Error> {
         self.inner.send_msg(msg, opts).await
@@ -273,6 +421,23 @@ impl RelayPool {
         self.inner.batch_event_to(urls, events, opts).await
     }
 
+    /// Get the number of entries and the approximate size of the internal collections
+    ///
+    /// Check [`MemoryLimits`] for the ceilings.
+    #[inline]
+    pub async fn memory_report(&self) -> MemoryReport {
+        self.inner.memory_report().await
+    }
+
+    /// Get the acknowledgement status of an event for every relay it was sent to
+    ///
+    /// Only the events sent through the pool are tracked.
+    /// Resolved entries are dropped after [`RelayPoolOptions::ack_grace_period`].
+    #[inline]
+    pub fn ack_status(&self, event_id: &EventId) -> HashMap<Url, AckStatus> {
+        self.inner.ack_status(event_id)
+    }
+
     /// Subscribe to filters
     ///
     /// ### Auto-closing subscription
@@ -346,6 +511,48 @@ impl RelayPool {
             .await
     }
 
+    /// Get events of filters from **specific relays**, grouped by filter
+    ///
+    /// All the filters are sent in a single REQ. The returned collections are aligned with the filters:
+    /// an event is included in the collection of every filter it matches (checked as it arrives).
+    ///
+    /// Get events both from **local database** and **relays**
+    pub async fn get_events_map_from<I, U>(
+        &self,
+        urls: I,
+        filters: Vec<Filter>,
+        timeout: Duration,
+        opts: FilterOptions,
+    ) -> Result<Vec<Vec<Event>>, Error>
+    where
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        Error: From<<U as TryIntoUrl>::Err>,
+    {
+        self.inner
+            .get_events_map_from(urls, filters, timeout, opts)
+            .await
+    }
+
+    /// Get events of filters from **specific relays**, following a [`FetchPolicy`]
+    ///
+    /// Allow to return early with partial results and a per-relay [`FetchReport`].
+    pub async fn get_events_with_policy<I, U>(
+        &self,
+        urls: I,
+        filters: Vec<Filter>,
+        policy: FetchPolicy,
+    ) -> Result<FetchOutput, Error>
+    where
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        Error: From<<U as TryIntoUrl>::Err>,
+    {
+        self.inner
+            .get_events_with_policy(urls, filters, policy)
+            .await
+    }
+
     /// Negentropy reconciliation
     pub async fn reconcile(&self, filter: Filter, opts: NegentropyOptions) -> Result<(), Error> {
         self.inner.reconcile(filter, opts).await
@@ -381,3 +588,353 @@ impl RelayPool {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use async_wsocket::futures_util::{SinkExt, StreamExt};
+    use nostr::{EventBuilder, JsonUtil, Keys};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::Message;
+
+    use super::*;
+    use crate::relay::SubscriptionLimitPolicy;
+
+    struct PanickingHandler;
+
+    impl RawEventHandler for PanickingHandler {
+        fn on_event(&self, _relay_url: &Url, _sub_id: &str, _event: &Event) {
+            panic!("malformed url");
+        }
+    }
+
+    /// Minimal relay: answer to every `REQ` with a text note and `EOSE`
+    async fn spawn_relay() -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let event = EventBuilder::text_note("test", [])
+            .to_event(&Keys::generate())
+            .unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let event = event.clone();
+                tokio::spawn(async move {
+                    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    while let Some(Ok(msg)) = ws.next().await {
+                        if let Message::Text(json) = msg {
+                            if let Ok(ClientMessage::Req {
+                                subscription_id, ..
+                            }) = ClientMessage::from_json(json)
+                            {
+                                let msgs = [
+                                    RelayMessage::event(subscription_id.clone(), event.clone()),
+                                    RelayMessage::eose(subscription_id),
+                                ];
+                                for msg in msgs {
+                                    if ws.send(Message::Text(msg.as_json())).await.is_err() {
+                                        return;
+                                    }
+                                }
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        Url::parse(&format!("ws://{addr}")).unwrap()
+    }
+
+    /// Relay that accept the connection but never answer
+    async fn spawn_silent_relay() -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    while let Some(Ok(..)) = ws.next().await {}
+                });
+            }
+        });
+        Url::parse(&format!("ws://{addr}")).unwrap()
+    }
+
+    async fn wait_relay_error(
+        notifications: &mut broadcast::Receiver<RelayPoolNotification>,
+    ) -> (Url, String) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(RelayPoolNotification::RelayError { relay_url, error }) =
+                    notifications.recv().await
+                {
+                    return (relay_url, error);
+                }
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_add_equivalent_relay_urls() {
+        let pool = RelayPool::default();
+        assert!(pool
+            .add_relay("wss://relay.example", RelayOptions::default())
+            .await
+            .unwrap());
+        for url in [
+            "wss://relay.example/",
+            "WSS://RELAY.EXAMPLE",
+            "wss://Relay.Example:443/",
+        ] {
+            assert!(!pool.add_relay(url, RelayOptions::default()).await.unwrap());
+        }
+        assert_eq!(pool.relays().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_memory_limits() {
+        let limits = MemoryLimits::new().subscriptions(2).notices(5);
+        let pool = RelayPool::new(RelayPoolOptions::new().memory_limits(limits));
+
+        // Oldest subscription evicted
+        let first = pool
+            .subscribe(vec![Filter::new()], SubscribeOptions::default())
+            .await;
+        let second = pool
+            .subscribe(vec![Filter::new()], SubscribeOptions::default())
+            .await;
+        let third = pool
+            .subscribe(vec![Filter::new()], SubscribeOptions::default())
+            .await;
+        let subscriptions = pool.subscriptions().await;
+        assert_eq!(subscriptions.len(), 2);
+        assert!(!subscriptions.contains_key(&first));
+        assert!(subscriptions.contains_key(&second));
+        assert!(subscriptions.contains_key(&third));
+
+        // Notices history capped
+        let url = Url::parse("wss://relay.example.com").unwrap();
+        pool.add_relay(url.clone(), RelayOptions::new().notices_history(100))
+            .await
+            .unwrap();
+        let relay = pool.relay(url).await.unwrap();
+        assert_eq!(relay.opts().get_notices_history(), 5);
+        assert_eq!(relay.opts().get_subscriptions_ceiling(), Some(2));
+
+        let report = pool.memory_report().await;
+        let usage = report.components.get("pool.subscriptions").unwrap();
+        assert_eq!(usage.entries, 2);
+        assert_eq!(usage.limit, Some(2));
+
+        // Every cache is bounded
+        for component in [
+            "pool.acks",
+            "relay.notices",
+            "relay.requested",
+            "relay.traffic",
+            "relay.queue",
+            "database.seen",
+        ] {
+            let usage = report.components.get(component).unwrap();
+            assert!(usage.limit.is_some(), "{component} is unbounded");
+        }
+        assert_eq!(
+            report.components.get("relay.requested").unwrap().limit,
+            Some(4)
+        );
+        assert_eq!(
+            report.components.get("relay.traffic").unwrap().limit,
+            Some(2)
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_ack_capacity() {
+        let opts = RelayPoolOptions::new().ack_capacity(5);
+        assert_eq!(opts.memory_limits, MemoryLimits::new().acks(5));
+    }
+
+    #[tokio::test]
+    async fn test_relay_subscription_eviction() {
+        let pool = RelayPool::default();
+        let url = spawn_relay().await;
+        let opts = RelayOptions::new()
+            .max_subscriptions(Some(1))
+            .subscription_limit_policy(SubscriptionLimitPolicy::EvictLeastRecentlyActive);
+        pool.add_relay(url, opts).await.unwrap();
+        pool.connect(Some(Duration::from_secs(5))).await;
+
+        let first = pool
+            .subscribe(vec![Filter::new()], SubscribeOptions::default())
+            .await;
+        let second = pool
+            .subscribe(vec![Filter::new()], SubscribeOptions::default())
+            .await;
+
+        // Evicted by the relay: removed also from the pool
+        let subscriptions = pool.subscriptions().await;
+        assert_eq!(subscriptions.len(), 1);
+        assert!(!subscriptions.contains_key(&first));
+        assert!(subscriptions.contains_key(&second));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_hard_deadline_status() {
+        let pool = RelayPool::default();
+        let url = spawn_relay().await;
+        let silent_url = spawn_silent_relay().await;
+        pool.add_relay(url.clone(), RelayOptions::default())
+            .await
+            .unwrap();
+        pool.add_relay(silent_url.clone(), RelayOptions::default())
+            .await
+            .unwrap();
+        pool.connect(Some(Duration::from_secs(5))).await;
+
+        let policy = FetchPolicy::new()
+            .hard_deadline(Duration::from_millis(500))
+            .stream_latecomers(true);
+        let output = pool
+            .get_events_with_policy(
+                [url.clone(), silent_url.clone()],
+                vec![Filter::new()],
+                policy,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(output.events.len(), 1);
+        assert_eq!(
+            output.report.relays.get(&url).unwrap().status,
+            RelayFetchStatus::Eose
+        );
+        assert_eq!(
+            output.report.relays.get(&silent_url).unwrap().status,
+            RelayFetchStatus::TimedOut
+        );
+        assert!(!output.report.is_complete());
+        assert!(output.latecomers.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_soft_deadline_status() {
+        let pool = RelayPool::default();
+        let url = spawn_relay().await;
+        let silent_url = spawn_silent_relay().await;
+        pool.add_relay(url.clone(), RelayOptions::default())
+            .await
+            .unwrap();
+        pool.add_relay(silent_url.clone(), RelayOptions::default())
+            .await
+            .unwrap();
+        pool.connect(Some(Duration::from_secs(5))).await;
+
+        let policy = FetchPolicy::new()
+            .soft_deadline(Some(Duration::from_millis(200)))
+            .hard_deadline(Duration::from_secs(1))
+            .stream_latecomers(true);
+        let output = pool
+            .get_events_with_policy(
+                [url.clone(), silent_url.clone()],
+                vec![Filter::new()],
+                policy,
+            )
+            .await
+            .unwrap();
+
+        // Returned early: the silent relay is still pending
+        assert_eq!(
+            output.report.relays.get(&url).unwrap().status,
+            RelayFetchStatus::Eose
+        );
+        assert_eq!(
+            output.report.relays.get(&silent_url).unwrap().status,
+            RelayFetchStatus::Pending
+        );
+
+        // Stream closed at the hard deadline
+        let mut latecomers = output.latecomers.unwrap();
+        let next = tokio::time::timeout(Duration::from_secs(5), latecomers.next()).await;
+        assert_eq!(next.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_relay_task_panic() {
+        let pool = RelayPool::default();
+        let panicking_url = spawn_relay().await;
+        let healthy_url = spawn_relay().await;
+        pool.add_relay(panicking_url.clone(), RelayOptions::default())
+            .await
+            .unwrap();
+        pool.add_relay(healthy_url.clone(), RelayOptions::default())
+            .await
+            .unwrap();
+
+        let relay = pool.relay(panicking_url.clone()).await.unwrap();
+        relay
+            .set_raw_event_handler(Some(Arc::new(PanickingHandler)))
+            .await;
+
+        let mut notifications = pool.notifications();
+        pool.connect(Some(Duration::from_secs(5))).await;
+        pool.subscribe(vec![Filter::new()], SubscribeOptions::default())
+            .await;
+
+        // The panic terminated only the relay
+        let (relay_url, error) = wait_relay_error(&mut notifications).await;
+        assert_eq!(relay_url, panicking_url);
+        assert!(error.contains("malformed url"));
+        assert_eq!(relay.status().await, RelayStatus::Terminated);
+        assert_eq!(relay.last_connection_error().await, Some(error));
+
+        let healthy = pool.relay(healthy_url).await.unwrap();
+        assert_eq!(healthy.status().await, RelayStatus::Connected);
+
+        // Revive it
+        relay.set_raw_event_handler(None).await;
+        relay.connect(Some(Duration::from_secs(5))).await;
+        assert_eq!(relay.status().await, RelayStatus::Connected);
+        assert_eq!(relay.last_connection_error().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_notification_consumer_panic() {
+        let pool = RelayPool::default();
+        let url = spawn_relay().await;
+        pool.add_relay(url.clone(), RelayOptions::default())
+            .await
+            .unwrap();
+        pool.connect(Some(Duration::from_secs(5))).await;
+
+        let consumer = {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                pool.handle_notifications(|_| async { panic!("consumer panicked") })
+                    .await
+            })
+        };
+
+        let mut notifications = pool.notifications();
+        pool.subscribe(vec![Filter::new()], SubscribeOptions::default())
+            .await;
+        assert!(consumer.await.unwrap_err().is_panic());
+
+        // The pool keeps delivering notifications
+        let event = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(RelayPoolNotification::Event { relay_url, .. }) =
+                    notifications.recv().await
+                {
+                    return relay_url;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(event, url);
+
+        let relay = pool.relay(url).await.unwrap();
+        assert_eq!(relay.status().await, RelayStatus::Connected);
+    }
+}
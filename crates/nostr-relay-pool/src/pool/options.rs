@@ -4,16 +4,30 @@
 
 //! Pool options
 
+use std::time::Duration;
+
+use super::memory::MemoryLimits;
+
 /// Relay Pool Options
 #[derive(Debug, Clone, Copy)]
 pub struct RelayPoolOptions {
     pub(super) notification_channel_size: usize,
+    pub(super) save_ephemeral_events: bool,
+    pub(super) initial_connection_jitter: Option<Duration>,
+    pub(super) ack_timeout: Duration,
+    pub(super) ack_grace_period: Duration,
+    pub(super) memory_limits: MemoryLimits,
 }
 
 impl Default for RelayPoolOptions {
     fn default() -> Self {
         Self {
             notification_channel_size: 4096,
+            save_ephemeral_events: false,
+            initial_connection_jitter: None,
+            ack_timeout: Duration::from_secs(30),
+            ack_grace_period: Duration::from_secs(300),
+            memory_limits: MemoryLimits::default(),
         }
     }
 }
@@ -29,4 +43,51 @@ impl RelayPoolOptions {
         self.notification_channel_size = size;
         self
     }
+
+    /// Save sent ephemeral events into the database (default: false)
+    ///
+    /// Ephemeral events are not expected to be stored: enable this only to archive them with a custom database.
+    pub fn save_ephemeral_events(mut self, save: bool) -> Self {
+        self.save_ephemeral_events = save;
+        self
+    }
+
+    /// Max random delay of the initial connection of the relays (default: None)
+    ///
+    /// Applied to the relays added without a custom [`RelayOptions::initial_connection_delay`](crate::RelayOptions::initial_connection_delay)
+    /// (ex. `Duration::from_millis(200)`).
+    pub fn default_initial_connection_jitter(mut self, max: Duration) -> Self {
+        self.initial_connection_jitter = Some(max);
+        self
+    }
+
+    /// Time to wait for the `OK` message of a sent event before marking it as [`AckStatus::TimedOut`](super::AckStatus::TimedOut) (default: 30 secs)
+    pub fn ack_timeout(mut self, timeout: Duration) -> Self {
+        self.ack_timeout = timeout;
+        self
+    }
+
+    /// Time to keep the resolved acknowledgements before forgetting them (default: 300 secs)
+    pub fn ack_grace_period(mut self, grace_period: Duration) -> Self {
+        self.ack_grace_period = grace_period;
+        self
+    }
+
+    /// Max number of tracked acknowledgements (default: 10_000)
+    ///
+    /// When full, the oldest entries are dropped.
+    #[deprecated(
+        since = "0.30.0",
+        note = "use `memory_limits` with `MemoryLimits::acks` instead"
+    )]
+    pub fn ack_capacity(mut self, capacity: usize) -> Self {
+        self.memory_limits = self.memory_limits.acks(capacity);
+        self
+    }
+
+    /// Hard caps of the internal collections (default: [`MemoryLimits::default`])
+    pub fn memory_limits(mut self, limits: MemoryLimits) -> Self {
+        self.memory_limits = limits;
+        self
+    }
 }
@@ -0,0 +1,212 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Subscription reordering buffer
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use nostr::types::time::Instant;
+use nostr::{Event, EventId, Timestamp, Url};
+
+use crate::relay::options::ReorderOptions;
+
+#[derive(Debug)]
+struct Buffered {
+    relay_url: Url,
+    event: Box<Event>,
+    received_at: Instant,
+}
+
+/// Event released by the [`ReorderBuffer`]
+#[derive(Debug)]
+pub(super) struct Released {
+    pub relay_url: Url,
+    pub event: Box<Event>,
+    /// Older than an event already released
+    pub out_of_order: bool,
+}
+
+/// Hold events for a window and release them in `created_at` order
+#[derive(Debug)]
+pub(super) struct ReorderBuffer {
+    window: Duration,
+    capacity: usize,
+    buffer: BTreeMap<(Timestamp, EventId), Buffered>,
+    last_released: Option<Timestamp>,
+}
+
+impl ReorderBuffer {
+    pub fn new(opts: ReorderOptions) -> Self {
+        Self {
+            window: opts.window,
+            capacity: opts.capacity.max(1),
+            buffer: BTreeMap::new(),
+            last_released: None,
+        }
+    }
+
+    /// Time before the oldest buffered event must be released
+    pub fn next_deadline(&self, now: Instant) -> Duration {
+        self.buffer
+            .values()
+            .map(|b| {
+                self.window
+                    .saturating_sub(now.duration_since(b.received_at))
+            })
+            .min()
+            .unwrap_or(self.window)
+    }
+
+    /// Add an event
+    ///
+    /// If the buffer is full, the oldest event is released immediately.
+    /// If the event is older than the last released one, it's released immediately and flagged as out of order.
+    pub fn push(&mut self, relay_url: Url, event: Box<Event>, now: Instant) -> Vec<Released> {
+        let mut released: Vec<Released> = Vec::new();
+
+        // Spill
+        if self.buffer.len() >= self.capacity {
+            if let Some(key) = self.buffer.keys().next().copied() {
+                released.extend(self.release(key));
+            }
+        }
+
+        match self.last_released {
+            Some(last) if event.created_at < last => released.push(Released {
+                relay_url,
+                event,
+                out_of_order: true,
+            }),
+            _ => {
+                self.buffer.insert(
+                    (event.created_at, event.id),
+                    Buffered {
+                        relay_url,
+                        event,
+                        received_at: now,
+                    },
+                );
+            }
+        }
+
+        released
+    }
+
+    /// Release the events held for the whole window, and all the events older than them
+    pub fn poll(&mut self, now: Instant) -> Vec<Released> {
+        let expired: Option<(Timestamp, EventId)> = self
+            .buffer
+            .iter()
+            .rev()
+            .find(|(_, b)| now.duration_since(b.received_at) >= self.window)
+            .map(|(key, _)| *key);
+        match expired {
+            Some(key) => self.release(key),
+            None => Vec::new(),
+        }
+    }
+
+    /// Release all the events
+    pub fn flush(&mut self) -> Vec<Released> {
+        match self.buffer.keys().next_back().copied() {
+            Some(key) => self.release(key),
+            None => Vec::new(),
+        }
+    }
+
+    /// Release all the events up to `until` (included), in order
+    fn release(&mut self, until: (Timestamp, EventId)) -> Vec<Released> {
+        let mut released: Vec<Released> = Vec::new();
+        while let Some(key) = self.buffer.keys().next().copied() {
+            if key > until {
+                break;
+            }
+
+            if let Some(b) = self.buffer.remove(&key) {
+                self.last_released = Some(b.event.created_at);
+                released.push(Released {
+                    relay_url: b.relay_url,
+                    event: b.event,
+                    out_of_order: false,
+                });
+            }
+        }
+        released
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys, Kind};
+
+    use super::*;
+
+    fn event(keys: &Keys, created_at: u64) -> Box<Event> {
+        Box::new(
+            EventBuilder::new(Kind::TextNote, created_at.to_string(), Vec::new())
+                .custom_created_at(Timestamp::from(created_at))
+                .to_event(keys)
+                .unwrap(),
+        )
+    }
+
+    fn timestamps(released: &[Released]) -> Vec<(u64, bool)> {
+        released
+            .iter()
+            .map(|r| (r.event.created_at.as_u64(), r.out_of_order))
+            .collect()
+    }
+
+    #[test]
+    fn test_reorder() {
+        let keys = Keys::generate();
+        let url = Url::parse("wss://relay.example.com").unwrap();
+        let window = Duration::from_secs(2);
+        let mut buffer = ReorderBuffer::new(ReorderOptions::new(window));
+
+        let now = Instant::now();
+        for created_at in [3, 1, 2] {
+            assert!(buffer
+                .push(url.clone(), event(&keys, created_at), now)
+                .is_empty());
+        }
+        assert!(buffer.poll(now).is_empty());
+        assert_eq!(buffer.next_deadline(now), window);
+
+        let later = now + window;
+        assert_eq!(
+            timestamps(&buffer.poll(later)),
+            vec![(1, false), (2, false), (3, false)]
+        );
+
+        // Older than the last released
+        assert_eq!(
+            timestamps(&buffer.push(url.clone(), event(&keys, 2), later)),
+            vec![(2, true)]
+        );
+
+        // Flush (EOSE)
+        buffer.push(url.clone(), event(&keys, 5), later);
+        buffer.push(url.clone(), event(&keys, 4), later);
+        assert_eq!(timestamps(&buffer.flush()), vec![(4, false), (5, false)]);
+    }
+
+    #[test]
+    fn test_reorder_capacity() {
+        let keys = Keys::generate();
+        let url = Url::parse("wss://relay.example.com").unwrap();
+        let opts = ReorderOptions::new(Duration::from_secs(2)).capacity(2);
+        let mut buffer = ReorderBuffer::new(opts);
+
+        let now = Instant::now();
+        buffer.push(url.clone(), event(&keys, 2), now);
+        buffer.push(url.clone(), event(&keys, 3), now);
+        assert_eq!(
+            timestamps(&buffer.push(url.clone(), event(&keys, 1), now)),
+            vec![(2, false), (1, true)]
+        );
+        assert_eq!(timestamps(&buffer.flush()), vec![(3, false)]);
+    }
+}
@@ -0,0 +1,151 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Relay write bandwidth metering
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use nostr::types::time::Instant;
+use nostr::Timestamp;
+
+const WINDOW_SECS: u64 = 60 * 60;
+
+/// Token bucket
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    /// Available bytes (negative after an oversized message)
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Count the bytes sent to a relay and limit them to an hourly cap
+///
+/// The limit is a token bucket holding at most `cap` bytes,
+/// refilled proportionally with the elapsed time (`cap` bytes per hour).
+#[derive(Debug, Clone)]
+pub(crate) struct BandwidthMeter {
+    window_start: Arc<AtomicU64>,
+    bytes_sent: Arc<AtomicU64>,
+    rate_limited: Arc<AtomicBool>,
+    /// `None` until the first reservation (full bucket)
+    bucket: Arc<Mutex<Option<Bucket>>>,
+}
+
+impl Default for BandwidthMeter {
+    fn default() -> Self {
+        Self {
+            window_start: Arc::new(AtomicU64::new(Timestamp::now().as_u64())),
+            bytes_sent: Arc::new(AtomicU64::new(0)),
+            rate_limited: Arc::new(AtomicBool::new(false)),
+            bucket: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl BandwidthMeter {
+    /// Reset the hourly counter if the current window is expired
+    fn check_window(&self) {
+        let now: u64 = Timestamp::now().as_u64();
+        let start: u64 = self.window_start.load(Ordering::SeqCst);
+        if now.saturating_sub(start) >= WINDOW_SECS {
+            self.window_start.store(now, Ordering::SeqCst);
+            self.bytes_sent.store(0, Ordering::SeqCst);
+        }
+    }
+
+    pub fn bytes_sent_this_hour(&self) -> u64 {
+        self.check_window();
+        self.bytes_sent.load(Ordering::SeqCst)
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        self.rate_limited.load(Ordering::SeqCst)
+    }
+
+    /// Try to reserve `size` bytes of the hourly `cap`
+    ///
+    /// Return `None` if the bytes can be sent now, otherwise the time to wait before retrying.
+    #[inline]
+    pub fn reserve(&self, size: u64, cap: u64) -> Option<Duration> {
+        self.reserve_at(size, cap, Instant::now())
+    }
+
+    /// A message bigger than the `cap` is allowed only with a full bucket.
+    fn reserve_at(&self, size: u64, cap: u64, now: Instant) -> Option<Duration> {
+        if cap == 0 {
+            self.rate_limited.store(true, Ordering::SeqCst);
+            return Some(Duration::from_secs(WINDOW_SECS));
+        }
+
+        let cap_f: f64 = cap as f64;
+        let rate: f64 = cap_f / WINDOW_SECS as f64;
+
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let bucket: &mut Bucket = bucket.get_or_insert(Bucket {
+            tokens: cap_f,
+            last_refill: now,
+        });
+
+        // Refill
+        let elapsed: f64 = now
+            .saturating_duration_since(bucket.last_refill)
+            .as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(cap_f);
+        bucket.last_refill = now;
+
+        let needed: f64 = (size as f64).min(cap_f);
+        if bucket.tokens >= needed {
+            bucket.tokens -= size as f64;
+            self.rate_limited.store(false, Ordering::SeqCst);
+            self.check_window();
+            self.bytes_sent.fetch_add(size, Ordering::SeqCst);
+            None
+        } else {
+            self.rate_limited.store(true, Ordering::SeqCst);
+            Some(Duration::from_secs_f64((needed - bucket.tokens) / rate))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cap_enforcement() {
+        let meter = BandwidthMeter::default();
+        let start = Instant::now();
+        // 1 byte per second
+        let cap: u64 = 3600;
+
+        assert_eq!(meter.reserve_at(3000, cap, start), None);
+        assert_eq!(meter.bytes_sent_this_hour(), 3000);
+        assert!(!meter.is_rate_limited());
+
+        // 600 bytes left
+        let wait = meter.reserve_at(1000, cap, start).unwrap();
+        assert_eq!(wait.as_secs(), 400);
+        assert!(meter.is_rate_limited());
+        assert_eq!(meter.bytes_sent_this_hour(), 3000);
+
+        // Proportional refill
+        let later = start + Duration::from_secs(399);
+        assert!(meter.reserve_at(1000, cap, later).is_some());
+        let later = start + Duration::from_secs(400);
+        assert_eq!(meter.reserve_at(1000, cap, later), None);
+        assert!(!meter.is_rate_limited());
+
+        // Oversized message: allowed only with a full bucket
+        assert!(meter.reserve_at(5000, cap, later).is_some());
+        let full = later + Duration::from_secs(3600);
+        assert_eq!(meter.reserve_at(5000, cap, full), None);
+        let wait = meter.reserve_at(1, cap, full).unwrap();
+        assert_eq!(wait.as_secs(), 1401);
+
+        // Nothing allowed
+        assert!(BandwidthMeter::default().reserve(1, 0).is_some());
+    }
+}
@@ -7,6 +7,7 @@ use std::collections::HashMap;
 use async_utility::thread;
 use nostr::message::relay::NegentropyErrorCode;
 use nostr::message::MessageHandleError;
+use nostr::nips::nip77;
 use nostr::{event, negentropy, EventId};
 use nostr_database::DatabaseError;
 use thiserror::Error;
@@ -26,6 +27,9 @@ pub enum Error {
     /// Negentropy error
     #[error(transparent)]
     Negentropy(#[from] negentropy::Error),
+    /// NIP77 error
+    #[error(transparent)]
+    NIP77(#[from] nip77::Error),
     /// Database error
     #[error(transparent)]
     Database(#[from] DatabaseError),
@@ -47,6 +51,9 @@ pub enum Error {
     /// Relay not connected
     #[error("relay not connected (status changed)")]
     NotConnectedStatusChanged,
+    /// Connection failed
+    #[error("connection failed: {0}")]
+    ConnectionFailed(String),
     /// Event not published
     #[error("event not published: {0}")]
     EventNotPublished(String),
@@ -85,6 +92,9 @@ pub enum Error {
     /// Unknown negentropy error
     #[error("unknown negentropy error")]
     UnknownNegentropyError,
+    /// Negentropy sync interrupted before completion (relay stopped or terminated)
+    #[error("negentropy sync interrupted")]
+    NegentropyInterrupted,
     /// Relay message too large
     #[error("Received message too large: size={size}, max_size={max_size}")]
     RelayMessageTooLarge {
@@ -109,6 +119,12 @@ pub enum Error {
         /// Max tags num
         max_size: usize,
     },
+    /// Too many subscriptions
+    #[error("too many subscriptions (max. {max})")]
+    TooManySubscriptions {
+        /// Max number of subscriptions
+        max: usize,
+    },
     /// Event expired
     #[error("event expired")]
     EventExpired,
@@ -0,0 +1,384 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Relay health
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use nostr::Timestamp;
+
+/// Number of buckets of the sliding window
+const BUCKETS: usize = 10;
+
+/// Relay health
+///
+/// Simple classification of the connection quality (ex. for UI indicators).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RelayHealth {
+    /// Not enough data
+    #[default]
+    Unknown,
+    /// Good
+    Good,
+    /// Degraded
+    Degraded,
+    /// Bad
+    Bad,
+}
+
+/// Relay health thresholds
+///
+/// Every input is classified against its thresholds (value `>=` threshold): the worst classification wins.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RelayHealthThresholds {
+    /// Sliding window (default: 5 min)
+    pub window: Duration,
+    /// Ping latency for degraded health (default: 500 ms)
+    pub degraded_latency: Duration,
+    /// Ping latency for bad health (default: 2 secs)
+    pub bad_latency: Duration,
+    /// Connection attempts in the window for degraded health (default: 3)
+    pub degraded_reconnects: u64,
+    /// Connection attempts in the window for bad health (default: 6)
+    pub bad_reconnects: u64,
+    /// Rate of rejected events for degraded health (default: 0.25)
+    pub degraded_rejected_rate: f64,
+    /// Rate of rejected events for bad health (default: 0.5)
+    pub bad_rejected_rate: f64,
+    /// Min number of `OK` received in the window to consider the rejected rate (default: 5)
+    pub min_sends: u64,
+    /// Notices in the window for degraded health (default: 5)
+    pub degraded_notices: u64,
+    /// Notices in the window for bad health (default: 20)
+    pub bad_notices: u64,
+    /// Unsolicited events flagged in the window for degraded health (default: 1)
+    pub degraded_violations: u64,
+    /// Unsolicited events flagged in the window for bad health (default: 10)
+    pub bad_violations: u64,
+}
+
+impl Default for RelayHealthThresholds {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(5 * 60),
+            degraded_latency: Duration::from_millis(500),
+            bad_latency: Duration::from_secs(2),
+            degraded_reconnects: 3,
+            bad_reconnects: 6,
+            degraded_rejected_rate: 0.25,
+            bad_rejected_rate: 0.5,
+            min_sends: 5,
+            degraded_notices: 5,
+            bad_notices: 20,
+            degraded_violations: 1,
+            bad_violations: 10,
+        }
+    }
+}
+
+/// Inputs that drove the [`RelayHealth`] classification
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelayHealthDetails {
+    /// Health
+    pub health: RelayHealth,
+    /// Average ping latency
+    pub latency: Option<Duration>,
+    /// Connection attempts in the window
+    pub reconnects: u64,
+    /// `OK` messages received in the window
+    pub sends: u64,
+    /// Rejected events (`OK` with `false` status) in the window
+    pub rejected: u64,
+    /// Notices received in the window
+    pub notices: u64,
+    /// Unsolicited events flagged in the window
+    pub violations: u64,
+    /// Sliding window
+    pub window: Duration,
+}
+
+impl RelayHealthDetails {
+    /// Rate of rejected events
+    pub fn rejected_rate(&self) -> f64 {
+        if self.sends > 0 {
+            self.rejected as f64 / self.sends as f64
+        } else {
+            0.0
+        }
+    }
+
+    fn classify(mut self, thresholds: &RelayHealthThresholds) -> Self {
+        if self.latency.is_none()
+            && self.reconnects == 0
+            && self.sends == 0
+            && self.notices == 0
+            && self.violations == 0
+        {
+            self.health = RelayHealth::Unknown;
+            return self;
+        }
+
+        let mut is_degraded: bool = false;
+        let mut is_bad: bool = false;
+
+        let mut check = |degraded: bool, bad: bool| {
+            is_degraded |= degraded;
+            is_bad |= bad;
+        };
+
+        if let Some(latency) = self.latency {
+            check(
+                latency >= thresholds.degraded_latency,
+                latency >= thresholds.bad_latency,
+            );
+        }
+
+        check(
+            self.reconnects >= thresholds.degraded_reconnects,
+            self.reconnects >= thresholds.bad_reconnects,
+        );
+
+        if self.sends >= thresholds.min_sends {
+            let rate: f64 = self.rejected_rate();
+            check(
+                rate >= thresholds.degraded_rejected_rate,
+                rate >= thresholds.bad_rejected_rate,
+            );
+        }
+
+        check(
+            self.notices >= thresholds.degraded_notices,
+            self.notices >= thresholds.bad_notices,
+        );
+
+        check(
+            self.violations >= thresholds.degraded_violations,
+            self.violations >= thresholds.bad_violations,
+        );
+
+        self.health = if is_bad {
+            RelayHealth::Bad
+        } else if is_degraded {
+            RelayHealth::Degraded
+        } else {
+            RelayHealth::Good
+        };
+        self
+    }
+}
+
+/// Counter over a sliding window of [`BUCKETS`] buckets
+#[derive(Debug, Default)]
+struct WindowCounter {
+    buckets: [u64; BUCKETS],
+    total: u64,
+}
+
+impl WindowCounter {
+    fn increment(&mut self, slot: u64) {
+        self.buckets[slot as usize % BUCKETS] += 1;
+        self.total += 1;
+    }
+
+    fn expire(&mut self, slot: u64) {
+        let bucket: &mut u64 = &mut self.buckets[slot as usize % BUCKETS];
+        self.total -= *bucket;
+        *bucket = 0;
+    }
+}
+
+#[derive(Debug, Default)]
+struct HealthWindow {
+    slot: u64,
+    reconnects: WindowCounter,
+    sends: WindowCounter,
+    rejected: WindowCounter,
+    notices: WindowCounter,
+    violations: WindowCounter,
+    last: RelayHealth,
+}
+
+impl HealthWindow {
+    /// Move to the current slot, expiring the old buckets
+    ///
+    /// At most [`BUCKETS`] buckets are expired, so it's O(1).
+    fn advance(&mut self, slot: u64) {
+        if slot <= self.slot {
+            return;
+        }
+
+        let steps: u64 = (slot - self.slot).min(BUCKETS as u64);
+        for i in 1..=steps {
+            let expired: u64 = self.slot + i;
+            self.reconnects.expire(expired);
+            self.sends.expire(expired);
+            self.rejected.expire(expired);
+            self.notices.expire(expired);
+            self.violations.expire(expired);
+        }
+        self.slot = slot;
+    }
+}
+
+/// Relay health tracker
+#[derive(Debug, Clone)]
+pub(crate) struct HealthTracker {
+    thresholds: RelayHealthThresholds,
+    window: Arc<Mutex<HealthWindow>>,
+}
+
+impl HealthTracker {
+    pub fn new(thresholds: RelayHealthThresholds) -> Self {
+        Self {
+            thresholds,
+            window: Arc::new(Mutex::new(HealthWindow::default())),
+        }
+    }
+
+    fn bucket_secs(&self) -> u64 {
+        (self.thresholds.window.as_secs() / BUCKETS as u64).max(1)
+    }
+
+    fn update<F, R>(&self, now: Timestamp, f: F) -> R
+    where
+        F: FnOnce(&mut HealthWindow, u64) -> R,
+    {
+        let slot: u64 = now.as_u64() / self.bucket_secs();
+        let mut window = match self.window.lock() {
+            Ok(window) => window,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        window.advance(slot);
+        f(&mut window, slot)
+    }
+
+    pub fn record_reconnect(&self) {
+        self.update(Timestamp::now(), |w, slot| w.reconnects.increment(slot));
+    }
+
+    pub fn record_ok(&self, status: bool) {
+        self.update(Timestamp::now(), |w, slot| {
+            w.sends.increment(slot);
+            if !status {
+                w.rejected.increment(slot);
+            }
+        });
+    }
+
+    pub fn record_notice(&self) {
+        self.update(Timestamp::now(), |w, slot| w.notices.increment(slot));
+    }
+
+    pub fn record_violation(&self) {
+        self.update(Timestamp::now(), |w, slot| w.violations.increment(slot));
+    }
+
+    /// Compute health details
+    pub fn details(&self, latency: Option<Duration>) -> RelayHealthDetails {
+        self.details_at(Timestamp::now(), latency)
+    }
+
+    fn details_at(&self, now: Timestamp, latency: Option<Duration>) -> RelayHealthDetails {
+        let details: RelayHealthDetails = self.update(now, |w, _| RelayHealthDetails {
+            health: RelayHealth::Unknown,
+            latency,
+            reconnects: w.reconnects.total,
+            sends: w.sends.total,
+            rejected: w.rejected.total,
+            notices: w.notices.total,
+            violations: w.violations.total,
+            window: self.thresholds.window,
+        });
+        details.classify(&self.thresholds)
+    }
+
+    /// Store the new health classification
+    ///
+    /// Return `true` if it changed.
+    pub fn set_last(&self, health: RelayHealth) -> bool {
+        let mut window = match self.window.lock() {
+            Ok(window) => window,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let changed: bool = window.last != health;
+        window.last = health;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_classification() {
+        let tracker = HealthTracker::new(RelayHealthThresholds::default());
+        let now = Timestamp::from(1_700_000_000);
+
+        assert_eq!(tracker.details_at(now, None).health, RelayHealth::Unknown);
+        assert_eq!(
+            tracker
+                .details_at(now, Some(Duration::from_millis(100)))
+                .health,
+            RelayHealth::Good
+        );
+        assert_eq!(
+            tracker.details_at(now, Some(Duration::from_secs(1))).health,
+            RelayHealth::Degraded
+        );
+
+        // Rejected events
+        for i in 0..10 {
+            tracker.update(now, |w, slot| {
+                w.sends.increment(slot);
+                if i % 2 == 0 {
+                    w.rejected.increment(slot);
+                }
+            });
+        }
+        let details = tracker.details_at(now, None);
+        assert_eq!(details.sends, 10);
+        assert_eq!(details.rejected, 5);
+        assert_eq!(details.health, RelayHealth::Bad);
+    }
+
+    #[test]
+    fn test_violations() {
+        let tracker = HealthTracker::new(RelayHealthThresholds::default());
+        let now = Timestamp::from(1_700_000_000);
+
+        tracker.update(now, |w, slot| w.violations.increment(slot));
+        let details = tracker.details_at(now, None);
+        assert_eq!(details.violations, 1);
+        assert_eq!(details.health, RelayHealth::Degraded);
+
+        for _ in 0..9 {
+            tracker.update(now, |w, slot| w.violations.increment(slot));
+        }
+        assert_eq!(tracker.details_at(now, None).health, RelayHealth::Bad);
+    }
+
+    #[test]
+    fn test_sliding_window() {
+        let thresholds = RelayHealthThresholds::default();
+        let tracker = HealthTracker::new(thresholds);
+        let now = Timestamp::from(1_700_000_000);
+
+        for _ in 0..3 {
+            tracker.update(now, |w, slot| w.reconnects.increment(slot));
+        }
+        assert_eq!(tracker.details_at(now, None).health, RelayHealth::Degraded);
+
+        // Half window later: still counted
+        let later = now + thresholds.window.as_secs() / 2;
+        assert_eq!(tracker.details_at(later, None).reconnects, 3);
+
+        // After the window: expired
+        let later = now + thresholds.window.as_secs() + tracker.bucket_secs();
+        let details = tracker.details_at(later, None);
+        assert_eq!(details.reconnects, 0);
+        assert_eq!(details.health, RelayHealth::Unknown);
+    }
+}
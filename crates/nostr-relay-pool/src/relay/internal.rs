@@ -4,24 +4,31 @@
 
 //! Internal Relay
 
+use std::any::Any;
 use std::cmp;
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::mem;
 use std::net::SocketAddr;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::num::NonZeroUsize;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use async_utility::futures_util::stream::AbortHandle;
 use async_utility::{futures_util, thread, time};
-use async_wsocket::futures_util::{Future, SinkExt, StreamExt};
+use async_wsocket::futures_util::future::{self, Either};
+use async_wsocket::futures_util::{Future, FutureExt, SinkExt, StreamExt};
 use async_wsocket::{Sink, Stream, WsMessage};
 use atomic_destructor::AtomicDestroyer;
+use lru::LruCache;
 #[cfg(not(target_arch = "wasm32"))]
 use nostr::message::MessageHandleError;
 use nostr::negentropy::{Bytes, Negentropy};
 use nostr::nips::nip01::Coordinate;
 #[cfg(feature = "nip11")]
 use nostr::nips::nip11::RelayInformationDocument;
+use nostr::nips::nip77::{self, NegentropyResult, NegentropySync};
 use nostr::secp256k1::rand::{self, Rng};
 use nostr::{
     ClientMessage, Event, EventId, Filter, JsonUtil, Keys, MissingPartialEvent, PartialEvent,
@@ -31,14 +38,25 @@ use nostr_database::{DynNostrDatabase, Order};
 use tokio::sync::mpsc::{self, Receiver, Sender};
 use tokio::sync::{broadcast, oneshot, Mutex, RwLock};
 
+use super::bandwidth::BandwidthMeter;
 use super::flags::AtomicRelayServiceFlags;
+use super::health::{HealthTracker, RelayHealth, RelayHealthDetails};
+use super::notice::{RelayNotice, RelayNotices};
 use super::options::{
     FilterOptions, NegentropyOptions, RelayOptions, RelaySendOptions, SubscribeAutoCloseOptions,
     SubscribeOptions, MAX_ADJ_RETRY_SEC, MIN_RETRY_SEC, NEGENTROPY_BATCH_SIZE_DOWN,
     NEGENTROPY_HIGH_WATER_UP, NEGENTROPY_LOW_WATER_UP,
 };
+#[cfg(not(target_arch = "wasm32"))]
+use super::socks;
 use super::stats::RelayConnectionStats;
-use super::{Error, RelayNotification, RelayStatus};
+use super::subscription::SubscriptionActivity;
+use super::traffic::TrafficMeter;
+use super::{
+    Error, RelayNotification, RelayStatus, SubscriptionLimitPolicy, UnsolicitedEventPolicy,
+};
+use crate::pool::handler::{RawEventHandler, SharedRawEventHandler};
+use crate::pool::memory::DEFAULT_MAX_SUBSCRIPTIONS;
 use crate::pool::RelayPoolNotification;
 
 type Message = (RelayEvent, Option<oneshot::Sender<bool>>);
@@ -47,6 +65,8 @@ const MIN_ATTEMPTS: usize = 1;
 const MIN_UPTIME: f64 = 0.90;
 #[cfg(not(target_arch = "wasm32"))]
 const PING_INTERVAL: u64 = 55;
+/// Max number of messages waiting to be sent
+const RELAY_QUEUE_CAPACITY: usize = 1024;
 
 /// Relay event
 #[derive(Debug)]
@@ -65,16 +85,93 @@ enum RelayEvent {
     Stop,
     /// Completely disconnect
     Terminate,
+    /// Close the connection after a panic
+    Abort,
+}
+
+#[inline]
+fn is_ephemeral_event(msg: &ClientMessage) -> bool {
+    matches!(msg, ClientMessage::Event(event) if event.is_ephemeral())
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.as_str()
+    } else {
+        "unknown panic"
+    }
+}
+
+/// Send `NEG-CLOSE` when dropped
+struct NegCloseGuard<'a> {
+    relay: &'a InternalRelay,
+    subscription_id: SubscriptionId,
+}
+
+impl<'a> Drop for NegCloseGuard<'a> {
+    fn drop(&mut self) {
+        let msg = ClientMessage::NegClose {
+            subscription_id: self.subscription_id.clone(),
+        };
+        if let Err(e) = self
+            .relay
+            .send_relay_event(RelayEvent::Batch(vec![msg]), None)
+        {
+            tracing::warn!(
+                "Impossible to close negentropy with {}: {e}",
+                self.relay.url
+            );
+        }
+    }
+}
+
+/// Batch waiting to be sent
+struct HeldBatch {
+    msgs: Vec<String>,
+    size: usize,
+    oneshot_sender: Option<oneshot::Sender<bool>>,
+}
+
+#[derive(Debug)]
+struct InternalSubscription {
+    filters: Vec<Filter>,
+    subscribed_at: Timestamp,
+    /// Timestamp of the last received event (`0` if none), updated under the read lock
+    last_event_at: AtomicU64,
+}
+
+impl InternalSubscription {
+    fn new(filters: Vec<Filter>) -> Self {
+        Self {
+            filters,
+            subscribed_at: Timestamp::now(),
+            last_event_at: AtomicU64::new(0),
+        }
+    }
+
+    fn activity(&self) -> SubscriptionActivity {
+        let last_event_at: u64 = self.last_event_at.load(Ordering::SeqCst);
+        SubscriptionActivity {
+            subscribed_at: self.subscribed_at,
+            last_event_at: (last_event_at > 0).then(|| Timestamp::from(last_event_at)),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub(crate) struct InternalRelay {
     pub(super) url: Url,
     status: Arc<RwLock<RelayStatus>>,
+    last_connection_error: Arc<RwLock<Option<String>>>,
     #[cfg(feature = "nip11")]
     document: Arc<RwLock<RelayInformationDocument>>,
     opts: RelayOptions,
     stats: RelayConnectionStats,
+    pub(super) bandwidth: BandwidthMeter,
+    pub(super) traffic: TrafficMeter,
+    health: HealthTracker,
     database: Arc<DynNostrDatabase>,
     scheduled_for_stop: Arc<AtomicBool>,
     scheduled_for_termination: Arc<AtomicBool>,
@@ -82,7 +179,13 @@ pub(crate) struct InternalRelay {
     relay_receiver: Arc<Mutex<Receiver<Message>>>,
     pub(super) internal_notification_sender: broadcast::Sender<RelayNotification>,
     external_notification_sender: Arc<RwLock<Option<broadcast::Sender<RelayPoolNotification>>>>,
-    subscriptions: Arc<RwLock<HashMap<SubscriptionId, Vec<Filter>>>>,
+    raw_event_handler: Arc<RwLock<Option<SharedRawEventHandler>>>,
+    pub(crate) notices: RelayNotices,
+    subscriptions: Arc<RwLock<HashMap<SubscriptionId, InternalSubscription>>>,
+    /// Filters of every open `REQ` (including auto-closing ones), used to detect unsolicited events
+    ///
+    /// Bounded to twice the subscriptions ceiling: when full, the least recently requested are dropped.
+    requested: Arc<RwLock<LruCache<SubscriptionId, Vec<Filter>>>>,
 }
 
 impl AtomicDestroyer for InternalRelay {
@@ -102,16 +205,26 @@ impl AtomicDestroyer for InternalRelay {
 
 impl InternalRelay {
     pub fn new(url: Url, database: Arc<DynNostrDatabase>, opts: RelayOptions) -> Self {
-        let (relay_sender, relay_receiver) = mpsc::channel::<Message>(1024);
+        let (relay_sender, relay_receiver) = mpsc::channel::<Message>(RELAY_QUEUE_CAPACITY);
         let (relay_notification_sender, ..) = broadcast::channel::<RelayNotification>(2048);
+        let health = HealthTracker::new(opts.get_health_thresholds());
+        let max_subscriptions: usize = opts
+            .get_subscriptions_ceiling()
+            .unwrap_or(DEFAULT_MAX_SUBSCRIPTIONS);
+        let max_requested: NonZeroUsize = NonZeroUsize::new(max_subscriptions.saturating_mul(2))
+            .expect("Capacity must not be zero");
 
         Self {
             url,
             status: Arc::new(RwLock::new(RelayStatus::Initialized)),
+            last_connection_error: Arc::new(RwLock::new(None)),
             #[cfg(feature = "nip11")]
             document: Arc::new(RwLock::new(RelayInformationDocument::new())),
             opts,
             stats: RelayConnectionStats::new(),
+            bandwidth: BandwidthMeter::default(),
+            traffic: TrafficMeter::new(max_subscriptions),
+            health,
             database,
             scheduled_for_stop: Arc::new(AtomicBool::new(false)),
             scheduled_for_termination: Arc::new(AtomicBool::new(false)),
@@ -119,7 +232,10 @@ impl InternalRelay {
             relay_receiver: Arc::new(Mutex::new(relay_receiver)),
             internal_notification_sender: relay_notification_sender,
             external_notification_sender: Arc::new(RwLock::new(None)),
+            raw_event_handler: Arc::new(RwLock::new(None)),
+            notices: RelayNotices::default(),
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            requested: Arc::new(RwLock::new(LruCache::new(max_requested))),
         }
     }
 
@@ -143,6 +259,15 @@ impl InternalRelay {
         *status
     }
 
+    pub async fn last_connection_error(&self) -> Option<String> {
+        self.last_connection_error.read().await.clone()
+    }
+
+    async fn set_last_connection_error(&self, error: Option<String>) {
+        let mut last = self.last_connection_error.write().await;
+        *last = error;
+    }
+
     async fn set_status(&self, status: RelayStatus) {
         // Change status
         let mut s = self.status.write().await;
@@ -158,6 +283,13 @@ impl InternalRelay {
         self.opts.flags.clone()
     }
 
+    /// Check if relay is disconnected and has a poor connection history
+    pub async fn is_unreachable(&self) -> bool {
+        !self.is_connected().await
+            && self.stats.attempts() > MIN_ATTEMPTS
+            && self.stats.uptime() < MIN_UPTIME
+    }
+
     #[inline]
     pub async fn is_connected(&self) -> bool {
         self.status().await == RelayStatus::Connected
@@ -176,19 +308,43 @@ impl InternalRelay {
     }
 
     pub async fn subscriptions(&self) -> HashMap<SubscriptionId, Vec<Filter>> {
-        let subscription = self.subscriptions.read().await;
-        subscription.clone()
+        let subscriptions = self.subscriptions.read().await;
+        subscriptions
+            .iter()
+            .map(|(id, s)| (id.clone(), s.filters.clone()))
+            .collect()
     }
 
     pub async fn subscription(&self, id: &SubscriptionId) -> Option<Vec<Filter>> {
-        let subscription = self.subscriptions.read().await;
-        subscription.get(id).cloned()
+        let subscriptions = self.subscriptions.read().await;
+        subscriptions.get(id).map(|s| s.filters.clone())
+    }
+
+    pub async fn subscriptions_activity(&self) -> HashMap<SubscriptionId, SubscriptionActivity> {
+        let subscriptions = self.subscriptions.read().await;
+        subscriptions
+            .iter()
+            .map(|(id, s)| (id.clone(), s.activity()))
+            .collect()
     }
 
     pub(crate) async fn update_subscription(&self, id: SubscriptionId, filters: Vec<Filter>) {
         let mut subscriptions = self.subscriptions.write().await;
-        let current: &mut Vec<Filter> = subscriptions.entry(id).or_default();
-        *current = filters;
+        match subscriptions.get_mut(&id) {
+            Some(current) => current.filters = filters,
+            None => {
+                subscriptions.insert(id, InternalSubscription::new(filters));
+            }
+        }
+    }
+
+    async fn set_subscription_last_event(&self, id: &SubscriptionId, timestamp: Timestamp) {
+        let subscriptions = self.subscriptions.read().await;
+        if let Some(subscription) = subscriptions.get(id) {
+            subscription
+                .last_event_at
+                .store(timestamp.as_u64(), Ordering::SeqCst);
+        }
     }
 
     pub(crate) async fn remove_subscription(&self, id: &SubscriptionId) {
@@ -196,6 +352,209 @@ impl InternalRelay {
         subscriptions.remove(id);
     }
 
+    async fn update_requested(&self, msgs: &[ClientMessage]) {
+        let mut requested = self.requested.write().await;
+        for msg in msgs.iter() {
+            match msg {
+                ClientMessage::Req {
+                    subscription_id,
+                    filters,
+                } => {
+                    requested.put(subscription_id.clone(), filters.clone());
+                }
+                ClientMessage::Close(subscription_id) => {
+                    requested.pop(subscription_id);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Check if the event was requested by the subscription
+    async fn is_solicited(&self, subscription_id: &SubscriptionId, event: Option<&Event>) -> bool {
+        let requested = self.requested.read().await;
+        match (requested.peek(subscription_id), event) {
+            (Some(filters), Some(event)) => filters.iter().any(|f| f.match_event(event)),
+            (Some(..), None) => true,
+            (None, ..) => false,
+        }
+    }
+
+    /// Apply the [`UnsolicitedEventPolicy`]
+    ///
+    /// Return `true` if the event must be dropped.
+    async fn handle_unsolicited_event(&self, subscription_id: &str, event_id: &EventId) -> bool {
+        let policy: UnsolicitedEventPolicy = self.opts.get_unsolicited_events();
+
+        if let UnsolicitedEventPolicy::Accept = policy {
+            return false;
+        }
+
+        tracing::warn!(
+            "Received unsolicited event {event_id} from {}: subscription_id={subscription_id}",
+            self.url
+        );
+
+        if let UnsolicitedEventPolicy::DropAndFlag = policy {
+            let violations: usize = self.stats.new_violation();
+            self.health.record_violation();
+            self.update_health().await;
+
+            if let Some(max) = self.opts.get_max_violations() {
+                if violations >= max {
+                    tracing::warn!(
+                        "Stopping {}: too many unsolicited events ({violations})",
+                        self.url
+                    );
+                    if let Err(e) = self.stop().await {
+                        tracing::error!("Impossible to stop {}: {e}", self.url);
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Get max number of subscriptions
+    ///
+    /// The limit advertised in the NIP11 document takes precedence over the one set in [`RelayOptions`].
+    /// Both are capped by the pool memory limits.
+    async fn max_subscriptions(&self) -> Option<usize> {
+        let max: Option<usize> = self.advertised_max_subscriptions().await;
+        let max: Option<usize> = max.or_else(|| self.opts.get_max_subscriptions());
+        match (max, self.opts.get_subscriptions_ceiling()) {
+            (Some(max), Some(ceiling)) => Some(max.min(ceiling)),
+            (max, ceiling) => max.or(ceiling),
+        }
+    }
+
+    #[cfg(feature = "nip11")]
+    async fn advertised_max_subscriptions(&self) -> Option<usize> {
+        let document = self.document.read().await;
+        document
+            .limitation
+            .as_ref()
+            .and_then(|l| l.max_subscriptions)
+            .filter(|max| *max > 0)
+            .map(|max| max as usize)
+    }
+
+    #[cfg(not(feature = "nip11"))]
+    async fn advertised_max_subscriptions(&self) -> Option<usize> {
+        None
+    }
+
+    /// Number of subscriptions and their approximate size, in bytes
+    pub(crate) async fn subscriptions_usage(&self) -> (usize, usize) {
+        let subscriptions = self.subscriptions.read().await;
+        let bytes: usize = subscriptions
+            .iter()
+            .map(|(id, s)| {
+                id.to_string().len() + s.filters.iter().map(|f| f.as_json().len()).sum::<usize>()
+            })
+            .sum();
+        (subscriptions.len(), bytes)
+    }
+
+    /// Number of open `REQ`s, their max number and approximate size, in bytes
+    pub(crate) async fn requested_usage(&self) -> (usize, usize, usize) {
+        let requested = self.requested.read().await;
+        let bytes: usize = requested
+            .iter()
+            .map(|(id, filters)| {
+                id.to_string().len() + filters.iter().map(|f| f.as_json().len()).sum::<usize>()
+            })
+            .sum();
+        (requested.len(), requested.cap().get(), bytes)
+    }
+
+    /// Number of subscriptions with traffic stats, their max number and approximate size, in bytes
+    #[inline]
+    pub(crate) fn traffic_usage(&self) -> (usize, usize, usize) {
+        self.traffic.usage()
+    }
+
+    /// Approximate size of the NIP11 document, in bytes
+    #[cfg(feature = "nip11")]
+    pub(crate) async fn document_usage(&self) -> usize {
+        let document = self.document.read().await;
+        nostr::serde_json::to_string(&*document).map_or(0, |json| json.len())
+    }
+
+    /// Insert or update a subscription, checking the subscriptions limit under the same lock
+    ///
+    /// If the limit is reached, reject it or evict the least recently active subscriptions,
+    /// according to [`SubscriptionLimitPolicy`].
+    ///
+    /// Return if the subscription is new and the evicted subscriptions.
+    async fn insert_subscription(
+        &self,
+        id: &SubscriptionId,
+        filters: Vec<Filter>,
+    ) -> Result<(bool, Vec<SubscriptionId>), Error> {
+        let max: Option<usize> = self.max_subscriptions().await;
+        let mut evicted: Vec<SubscriptionId> = Vec::new();
+
+        let mut subscriptions = self.subscriptions.write().await;
+
+        // Updating an existing subscription not change the count
+        if let Some(current) = subscriptions.get_mut(id) {
+            current.filters = filters;
+            return Ok((false, evicted));
+        }
+
+        if let Some(max) = max {
+            while subscriptions.len() >= max {
+                match self.opts.get_subscription_limit_policy() {
+                    SubscriptionLimitPolicy::Reject => {
+                        return Err(Error::TooManySubscriptions { max });
+                    }
+                    SubscriptionLimitPolicy::EvictLeastRecentlyActive => {
+                        let oldest: Option<SubscriptionId> = subscriptions
+                            .iter()
+                            .min_by_key(|(_, s)| s.activity().last_active_at())
+                            .map(|(id, _)| id.clone());
+                        match oldest {
+                            Some(oldest) => {
+                                subscriptions.remove(&oldest);
+                                evicted.push(oldest);
+                            }
+                            None => return Err(Error::TooManySubscriptions { max }),
+                        }
+                    }
+                }
+            }
+        }
+
+        subscriptions.insert(id.clone(), InternalSubscription::new(filters));
+
+        Ok((true, evicted))
+    }
+
+    /// Close and notify the evicted subscriptions
+    async fn close_evicted_subscriptions(
+        &self,
+        evicted: &[SubscriptionId],
+        opts: RelaySendOptions,
+    ) -> Result<(), Error> {
+        for id in evicted.iter() {
+            tracing::warn!(
+                "Subscriptions limit reached for {}: evicted subscription {id}",
+                self.url
+            );
+
+            self.send_msg(ClientMessage::close(id.clone()), opts)
+                .await?;
+            self.send_notification(RelayNotification::SubscriptionEvicted {
+                subscription_id: id.clone(),
+            })
+            .await;
+        }
+
+        Ok(())
+    }
+
     #[inline]
     pub fn opts(&self) -> RelayOptions {
         self.opts.clone()
@@ -211,6 +570,16 @@ impl InternalRelay {
         self.relay_sender.max_capacity() - self.relay_sender.capacity()
     }
 
+    /// Number of messages waiting to be sent, their max number and approximate size, in bytes
+    pub(crate) fn queue_usage(&self) -> (usize, usize, usize) {
+        let entries: usize = self.queue();
+        (
+            entries,
+            self.relay_sender.max_capacity(),
+            entries * mem::size_of::<Message>(),
+        )
+    }
+
     #[inline]
     fn is_scheduled_for_stop(&self) -> bool {
         self.scheduled_for_stop.load(Ordering::SeqCst)
@@ -240,6 +609,36 @@ impl InternalRelay {
         *external_notification_sender = notification_sender;
     }
 
+    pub async fn set_raw_event_handler(
+        &self,
+        handler: Option<Arc<dyn RawEventHandler + Send + Sync>>,
+    ) {
+        let mut raw_event_handler = self.raw_event_handler.write().await;
+        *raw_event_handler = handler.map(SharedRawEventHandler);
+    }
+
+    pub async fn health_details(&self) -> RelayHealthDetails {
+        #[cfg(not(target_arch = "wasm32"))]
+        let latency: Option<Duration> = self.stats.latency().await;
+        #[cfg(target_arch = "wasm32")]
+        let latency: Option<Duration> = None;
+        self.health.details(latency)
+    }
+
+    /// Re-compute the health and notify if the classification changed
+    async fn update_health(&self) {
+        let health: RelayHealth = self.health_details().await.health;
+        if self.health.set_last(health) {
+            self.send_notification(RelayNotification::HealthChanged { health })
+                .await;
+        }
+    }
+
+    async fn save_notice(&self, notice: RelayNotice) {
+        let capacity: usize = self.opts.get_notices_history();
+        self.notices.push(notice, capacity).await;
+    }
+
     async fn send_notification(&self, notification: RelayNotification) {
         // Send internal notification
         let _ = self.internal_notification_sender.send(notification.clone());
@@ -265,6 +664,32 @@ impl InternalRelay {
                     relay_url: self.url(),
                     status,
                 },
+                RelayNotification::SubscriptionEvicted { subscription_id } => {
+                    RelayPoolNotification::SubscriptionEvicted {
+                        relay_url: self.url(),
+                        subscription_id,
+                    }
+                }
+                RelayNotification::SubscriptionClosed {
+                    subscription_id,
+                    prefix,
+                    reason,
+                } => RelayPoolNotification::SubscriptionClosed {
+                    relay_url: self.url(),
+                    subscription_id,
+                    prefix,
+                    reason,
+                },
+                RelayNotification::HealthChanged { health } => {
+                    RelayPoolNotification::RelayHealthChanged {
+                        relay_url: self.url(),
+                        health,
+                    }
+                }
+                RelayNotification::Error { error } => RelayPoolNotification::RelayError {
+                    relay_url: self.url(),
+                    error,
+                },
                 RelayNotification::Shutdown => RelayPoolNotification::Shutdown,
                 RelayNotification::Stop => RelayPoolNotification::Stop,
             };
@@ -274,15 +699,60 @@ impl InternalRelay {
         }
     }
 
+    /// Run a relay task, catching its panics
+    ///
+    /// On panic, the relay is terminated and a [`RelayNotification::Error`] is sent,
+    /// unless [`RelayOptions::propagate_panics`] is enabled.
+    ///
+    /// Return `true` if the task panicked.
+    async fn catch_panics<F>(&self, task: &str, future: F) -> bool
+    where
+        F: Future<Output = ()>,
+    {
+        if self.opts.get_propagate_panics() {
+            future.await;
+            return false;
+        }
+
+        match AssertUnwindSafe(future).catch_unwind().await {
+            Ok(()) => false,
+            Err(payload) => {
+                let error: String = format!("{task} panicked: {}", panic_message(payload.as_ref()));
+                tracing::error!("Relay {}: {error}", self.url);
+
+                self.set_last_connection_error(Some(error.clone())).await;
+                self.set_status(RelayStatus::Terminated).await;
+
+                // Close the connection
+                // Ignored by the message sender if the relay is revived in the meantime.
+                let _ = self.send_relay_event(RelayEvent::Abort, None);
+
+                self.send_notification(RelayNotification::Error { error })
+                    .await;
+
+                true
+            }
+        }
+    }
+
     pub async fn connect(&self, connection_timeout: Option<Duration>) {
         self.schedule_for_stop(false);
         self.schedule_for_termination(false);
 
-        if let RelayStatus::Initialized | RelayStatus::Stopped | RelayStatus::Terminated =
-            self.status().await
-        {
+        let status: RelayStatus = self.status().await;
+        if let RelayStatus::Initialized | RelayStatus::Stopped | RelayStatus::Terminated = status {
+            // Random delay of the initial connection
+            let initial_delay: Option<Duration> = if status == RelayStatus::Initialized {
+                self.opts.get_initial_connection_delay()
+            } else {
+                None
+            };
+
             if self.opts.get_reconnect() {
                 if connection_timeout.is_some() {
+                    if let Some(delay) = initial_delay {
+                        thread::sleep(delay).await;
+                    }
                     self.try_connect(connection_timeout).await
                 }
 
@@ -294,75 +764,104 @@ impl InternalRelay {
 
                 let relay = self.clone();
                 let _ = thread::spawn(async move {
-                    loop {
-                        let queue = relay.queue();
-                        if queue > 0 {
-                            tracing::info!(
-                                "{} messages queued for {} (capacity: {})",
-                                queue,
-                                relay.url(),
-                                relay.relay_sender.capacity()
-                            );
-                        }
+                    relay
+                        .catch_panics("auto connect loop", async {
+                            if connection_timeout.is_none() {
+                                if let Some(delay) = initial_delay {
+                                    thread::sleep(delay).await;
+                                }
+                            }
 
-                        // Schedule relay for termination
-                        // Needed to terminate the auto reconnect loop, also if the relay is not connected yet.
-                        if relay.is_scheduled_for_stop() {
-                            relay.set_status(RelayStatus::Stopped).await;
-                            relay.schedule_for_stop(false);
-                            tracing::debug!(
-                                "Auto connect loop terminated for {} [stop - schedule]",
-                                relay.url
-                            );
-                            break;
-                        } else if relay.is_scheduled_for_termination() {
-                            relay.set_status(RelayStatus::Terminated).await;
-                            relay.schedule_for_termination(false);
-                            tracing::debug!(
-                                "Auto connect loop terminated for {} [schedule]",
-                                relay.url
-                            );
-                            break;
-                        }
+                            loop {
+                                let queue = relay.queue();
+                                if queue > 0 {
+                                    tracing::info!(
+                                        "{} messages queued for {} (capacity: {})",
+                                        queue,
+                                        relay.url(),
+                                        relay.relay_sender.capacity()
+                                    );
+                                }
 
-                        // Check status
-                        match relay.status().await {
-                            RelayStatus::Initialized
-                            | RelayStatus::Pending
-                            | RelayStatus::Disconnected => {
-                                relay.try_connect(connection_timeout).await
-                            }
-                            RelayStatus::Stopped | RelayStatus::Terminated => {
-                                tracing::debug!("Auto connect loop terminated for {}", relay.url);
-                                break;
-                            }
-                            _ => (),
-                        };
+                                // Schedule relay for termination
+                                // Needed to terminate the auto reconnect loop, also if the relay is not connected yet.
+                                if relay.is_scheduled_for_stop() {
+                                    relay.set_status(RelayStatus::Stopped).await;
+                                    relay.schedule_for_stop(false);
+                                    tracing::debug!(
+                                        "Auto connect loop terminated for {} [stop - schedule]",
+                                        relay.url
+                                    );
+                                    break;
+                                } else if relay.is_scheduled_for_termination() {
+                                    relay.set_status(RelayStatus::Terminated).await;
+                                    relay.schedule_for_termination(false);
+                                    tracing::debug!(
+                                        "Auto connect loop terminated for {} [schedule]",
+                                        relay.url
+                                    );
+                                    break;
+                                }
 
-                        let retry_sec: u64 = if relay.opts.get_adjust_retry_sec() {
-                            let var: u64 =
-                                relay.stats.attempts().saturating_sub(relay.stats.success()) as u64;
-                            if var >= 3 {
-                                let retry_interval: i64 =
-                                    cmp::min(MIN_RETRY_SEC * (1 + var), MAX_ADJ_RETRY_SEC) as i64;
-                                let jitter: i64 = rand::thread_rng().gen_range(-1..=1);
-                                retry_interval.saturating_add(jitter) as u64
-                            } else {
-                                relay.opts().get_retry_sec()
-                            }
-                        } else {
-                            relay.opts().get_retry_sec()
-                        };
+                                // Check status
+                                match relay.status().await {
+                                    RelayStatus::Initialized
+                                    | RelayStatus::Pending
+                                    | RelayStatus::Disconnected => {
+                                        relay.try_connect(connection_timeout).await
+                                    }
+                                    RelayStatus::Stopped | RelayStatus::Terminated => {
+                                        tracing::debug!(
+                                            "Auto connect loop terminated for {}",
+                                            relay.url
+                                        );
+                                        break;
+                                    }
+                                    _ => (),
+                                };
 
-                        tracing::trace!("{} retry time set to {retry_sec} secs", relay.url);
-                        thread::sleep(Duration::from_secs(retry_sec)).await;
-                    }
+                                let retry_sec: u64 = if relay.opts.get_adjust_retry_sec() {
+                                    let var: u64 = relay
+                                        .stats
+                                        .attempts()
+                                        .saturating_sub(relay.stats.success())
+                                        as u64;
+                                    if var >= 3 {
+                                        let retry_interval: i64 =
+                                            cmp::min(MIN_RETRY_SEC * (1 + var), MAX_ADJ_RETRY_SEC)
+                                                as i64;
+                                        let jitter: i64 = rand::thread_rng().gen_range(-1..=1);
+                                        retry_interval.saturating_add(jitter) as u64
+                                    } else {
+                                        relay.opts().get_retry_sec()
+                                    }
+                                } else {
+                                    relay.opts().get_retry_sec()
+                                };
+
+                                tracing::trace!("{} retry time set to {retry_sec} secs", relay.url);
+                                thread::sleep(Duration::from_secs(retry_sec)).await;
+                            }
+                        })
+                        .await;
                 });
             } else if connection_timeout.is_some() {
+                if let Some(delay) = initial_delay {
+                    thread::sleep(delay).await;
+                }
                 self.try_connect(connection_timeout).await
             } else {
                 let relay = self.clone();
-                let _ = thread::spawn(async move { relay.try_connect(connection_timeout).await });
+                let _ = thread::spawn(async move {
+                    relay
+                        .catch_panics("connection", async {
+                            if let Some(delay) = initial_delay {
+                                thread::sleep(delay).await;
+                            }
+                            relay.try_connect(connection_timeout).await
+                        })
+                        .await;
+                });
             }
         }
     }
@@ -386,34 +885,39 @@ impl InternalRelay {
     fn spawn_pinger(&self) -> Option<AbortHandle> {
         let relay = self.clone();
         thread::abortable(async move {
-            if relay.opts.flags.has_ping() {
-                tracing::debug!("Relay Ping Thread Started");
-
-                loop {
-                    if relay.stats.ping.last_nonce() != 0 && !relay.stats.ping.replied() {
-                        tracing::warn!("{} not replied to ping", relay.url);
-                        relay.stats.ping.reset();
-                        break;
-                    }
+            relay
+                .catch_panics("pinger", async {
+                    if relay.opts.flags.has_ping() {
+                        tracing::debug!("Relay Ping Thread Started");
+
+                        loop {
+                            if relay.stats.ping.last_nonce() != 0 && !relay.stats.ping.replied() {
+                                tracing::warn!("{} not replied to ping", relay.url);
+                                relay.stats.ping.reset();
+                                break;
+                            }
 
-                    let nonce: u64 = rand::random();
-                    relay.stats.ping.set_last_nonce(nonce);
-                    relay.stats.ping.set_replied(false);
+                            let nonce: u64 = rand::random();
+                            relay.stats.ping.set_last_nonce(nonce);
+                            relay.stats.ping.set_replied(false);
 
-                    if let Err(e) = relay.send_relay_event(RelayEvent::Ping { nonce }, None) {
-                        tracing::error!("Impossible to ping {}: {e}", relay.url);
-                        break;
-                    };
+                            if let Err(e) = relay.send_relay_event(RelayEvent::Ping { nonce }, None)
+                            {
+                                tracing::error!("Impossible to ping {}: {e}", relay.url);
+                                break;
+                            };
 
-                    thread::sleep(Duration::from_secs(PING_INTERVAL)).await;
-                }
+                            thread::sleep(Duration::from_secs(PING_INTERVAL)).await;
+                        }
 
-                tracing::debug!("Exited from Ping Thread of {}", relay.url);
+                        tracing::debug!("Exited from Ping Thread of {}", relay.url);
 
-                if let Err(err) = relay.disconnect().await {
-                    tracing::error!("Impossible to disconnect {}: {}", relay.url, err);
-                }
-            }
+                        if let Err(err) = relay.disconnect().await {
+                            tracing::error!("Impossible to disconnect {}: {}", relay.url, err);
+                        }
+                    }
+                })
+                .await;
         })
         .ok()
     }
@@ -424,104 +928,186 @@ impl InternalRelay {
         None
     }
 
+    /// Reserve the bandwidth for a batch
+    ///
+    /// Return the time to wait if the bandwidth cap is reached.
+    fn reserve_bandwidth(&self, size: usize, held: usize) -> Option<Duration> {
+        let cap: u64 = self.opts.get_max_bytes_per_hour()?;
+        let limited: bool = self.bandwidth.is_rate_limited();
+        let wait: Duration = self.bandwidth.reserve(size as u64, cap)?;
+        if !limited {
+            tracing::warn!(
+                "Send rate limit reached for {} ({cap} bytes/hour): holding {held} batches for {} secs",
+                self.url,
+                wait.as_secs()
+            );
+        }
+        Some(wait)
+    }
+
+    /// Send a batch
+    ///
+    /// Return `false` if the connection is broken.
+    async fn send_batch(&self, ws_tx: &mut Sink, batch: HeldBatch) -> bool {
+        let HeldBatch {
+            msgs,
+            size,
+            oneshot_sender,
+        } = batch;
+        let len: usize = msgs.len();
+
+        if len == 1 {
+            if let Some(json) = msgs.first() {
+                tracing::debug!("Sending {json} to {} (size: {size} bytes)", self.url);
+            }
+        } else {
+            tracing::debug!(
+                "Sending {len} messages to {} (size: {size} bytes)",
+                self.url
+            );
+        }
+
+        let msgs = msgs.into_iter().map(|msg| Ok(WsMessage::Text(msg)));
+        let mut stream = futures_util::stream::iter(msgs);
+        let sent: bool = match ws_tx.send_all(&mut stream).await {
+            Ok(_) => {
+                self.stats.add_bytes_sent(size);
+                true
+            }
+            Err(e) => {
+                tracing::error!("Impossible to send {len} messages to {}: {e}", self.url);
+                false
+            }
+        };
+
+        if let Some(sender) = oneshot_sender {
+            if let Err(e) = sender.send(sent) {
+                tracing::error!("Impossible to send oneshot msg: {}", e);
+            }
+        }
+
+        sent
+    }
+
     fn spawn_message_sender(&self, mut ws_tx: Sink, _ping_abort_handle: Option<AbortHandle>) {
         let relay = self.clone();
         let _ = thread::spawn(async move {
-            tracing::debug!("Relay Event Thread Started");
-            let mut rx = relay.relay_receiver.lock().await;
-            while let Some((relay_event, oneshot_sender)) = rx.recv().await {
-                match relay_event {
-                    RelayEvent::Batch(msgs) => {
-                        let msgs: Vec<String> = msgs.into_iter().map(|msg| msg.as_json()).collect();
-                        let size: usize = msgs.iter().map(|msg| msg.as_bytes().len()).sum();
-                        let len = msgs.len();
-
-                        if len == 1 {
-                            if let Some(json) = msgs.first() {
-                                tracing::debug!(
-                                    "Sending {json} to {} (size: {size} bytes)",
-                                    relay.url
-                                );
+            let panicked: bool = relay
+                .catch_panics("message sender", async {
+                    tracing::debug!("Relay Event Thread Started");
+                    let mut rx = relay.relay_receiver.lock().await;
+
+                    // Batches held by the bandwidth cap (control frames are never held)
+                    let mut held: VecDeque<HeldBatch> = VecDeque::new();
+
+                    loop {
+                        // Send the held batches allowed by the bandwidth cap
+                        let mut wait: Option<Duration> = None;
+                        while let Some(batch) = held.pop_front() {
+                            if let Some(w) = relay.reserve_bandwidth(batch.size, held.len() + 1) {
+                                held.push_front(batch);
+                                wait = Some(w);
+                                break;
+                            }
+
+                            if !relay.send_batch(&mut ws_tx, batch).await {
+                                return;
                             }
-                        } else {
-                            tracing::debug!(
-                                "Sending {len} messages to {} (size: {size} bytes)",
-                                relay.url
-                            );
                         }
 
-                        let msgs = msgs.into_iter().map(|msg| Ok(WsMessage::Text(msg)));
-                        let mut stream = futures_util::stream::iter(msgs);
-                        match ws_tx.send_all(&mut stream).await {
-                            Ok(_) => {
-                                relay.stats.add_bytes_sent(size);
-                                if let Some(sender) = oneshot_sender {
-                                    if let Err(e) = sender.send(true) {
-                                        tracing::error!("Impossible to send oneshot msg: {}", e);
-                                    }
+                        // Wait for the next message or, if throttled, for the bandwidth
+                        let next = match wait {
+                            Some(wait) => {
+                                let recv = rx.recv();
+                                let sleep = thread::sleep(wait);
+                                futures_util::pin_mut!(recv);
+                                futures_util::pin_mut!(sleep);
+                                match future::select(recv, sleep).await {
+                                    Either::Left((next, _)) => next,
+                                    Either::Right(..) => continue,
                                 }
                             }
-                            Err(e) => {
-                                tracing::error!(
-                                    "Impossible to send {len} messages to {}: {}",
-                                    relay.url(),
-                                    e.to_string()
-                                );
-                                if let Some(sender) = oneshot_sender {
-                                    if let Err(e) = sender.send(false) {
-                                        tracing::error!("Impossible to send oneshot msg: {}", e);
+                            None => rx.recv().await,
+                        };
+
+                        let (relay_event, oneshot_sender) = match next {
+                            Some(next) => next,
+                            None => break,
+                        };
+
+                        match relay_event {
+                            RelayEvent::Batch(msgs) => {
+                                let msgs: Vec<String> =
+                                    msgs.into_iter().map(|msg| msg.as_json()).collect();
+                                let size: usize = msgs.iter().map(|msg| msg.as_bytes().len()).sum();
+                                held.push_back(HeldBatch {
+                                    msgs,
+                                    size,
+                                    oneshot_sender,
+                                });
+                            }
+                            #[cfg(not(target_arch = "wasm32"))]
+                            RelayEvent::Ping { nonce } => {
+                                if relay.opts.flags.has_ping() {
+                                    match ws_tx
+                                        .send(WsMessage::Ping(
+                                            nonce.to_string().as_bytes().to_vec(),
+                                        ))
+                                        .await
+                                    {
+                                        Ok(_) => {
+                                            relay.stats.ping.just_sent().await;
+                                            tracing::debug!("Ping {} (nonce {})", relay.url, nonce);
+                                        }
+                                        Err(e) => {
+                                            tracing::error!(
+                                                "Impossible to ping {}: {}",
+                                                relay.url(),
+                                                e.to_string()
+                                            );
+                                        }
                                     }
                                 }
+                            }
+                            RelayEvent::Close => {
+                                let _ = ws_tx.close().await;
+                                relay.set_status(RelayStatus::Disconnected).await;
+                                tracing::info!("Disconnected from {}", relay.url);
                                 break;
                             }
-                        }
-                    }
-                    #[cfg(not(target_arch = "wasm32"))]
-                    RelayEvent::Ping { nonce } => {
-                        if relay.opts.flags.has_ping() {
-                            match ws_tx
-                                .send(WsMessage::Ping(nonce.to_string().as_bytes().to_vec()))
-                                .await
-                            {
-                                Ok(_) => {
-                                    relay.stats.ping.just_sent().await;
-                                    tracing::debug!("Ping {} (nonce {})", relay.url, nonce);
+                            RelayEvent::Stop => {
+                                if relay.is_scheduled_for_stop() {
+                                    let _ = ws_tx.close().await;
+                                    relay.set_status(RelayStatus::Stopped).await;
+                                    relay.schedule_for_stop(false);
+                                    tracing::info!("Stopped {}", relay.url);
+                                    break;
                                 }
-                                Err(e) => {
-                                    tracing::error!(
-                                        "Impossible to ping {}: {}",
-                                        relay.url(),
-                                        e.to_string()
-                                    );
+                            }
+                            RelayEvent::Terminate => {
+                                if relay.is_scheduled_for_termination() {
+                                    let _ = ws_tx.close().await;
+                                    relay.set_status(RelayStatus::Terminated).await;
+                                    relay.schedule_for_termination(false);
+                                    tracing::info!("Completely disconnected from {}", relay.url);
+                                    break;
+                                }
+                            }
+                            RelayEvent::Abort => {
+                                if relay.status().await == RelayStatus::Terminated {
+                                    let _ = ws_tx.close().await;
+                                    tracing::info!("Aborted connection to {}", relay.url);
+                                    break;
                                 }
                             }
                         }
                     }
-                    RelayEvent::Close => {
-                        let _ = ws_tx.close().await;
-                        relay.set_status(RelayStatus::Disconnected).await;
-                        tracing::info!("Disconnected from {}", relay.url);
-                        break;
-                    }
-                    RelayEvent::Stop => {
-                        if relay.is_scheduled_for_stop() {
-                            let _ = ws_tx.close().await;
-                            relay.set_status(RelayStatus::Stopped).await;
-                            relay.schedule_for_stop(false);
-                            tracing::info!("Stopped {}", relay.url);
-                            break;
-                        }
-                    }
-                    RelayEvent::Terminate => {
-                        if relay.is_scheduled_for_termination() {
-                            let _ = ws_tx.close().await;
-                            relay.set_status(RelayStatus::Terminated).await;
-                            relay.schedule_for_termination(false);
-                            tracing::info!("Completely disconnected from {}", relay.url);
-                            break;
-                        }
-                    }
-                }
+                })
+                .await;
+
+            // Close the connection after a panic
+            if panicked {
+                let _ = ws_tx.close().await;
             }
 
             tracing::debug!("Exited from Relay Event Thread");
@@ -536,145 +1122,198 @@ impl InternalRelay {
     fn spawn_message_receiver(&self, mut ws_rx: Stream) {
         let relay = self.clone();
         let _ = thread::spawn(async move {
-            tracing::debug!("Relay Message Thread Started");
+            relay
+                .catch_panics("message receiver", async {
+                    tracing::debug!("Relay Message Thread Started");
 
-            async fn func(relay: &InternalRelay, data: Vec<u8>) -> Result<bool, Error> {
-                let size: usize = data.len();
-                let max_size: usize = relay.opts.limits.messages.max_size as usize;
-                relay.stats.add_bytes_received(size);
+                    async fn func(relay: &InternalRelay, data: Vec<u8>) -> Result<bool, Error> {
+                        let size: usize = data.len();
+                        let max_size: usize = relay.opts.limits.messages.max_size as usize;
+                        relay.stats.add_bytes_received(size);
 
-                if size > max_size {
-                    return Err(Error::RelayMessageTooLarge { size, max_size });
-                }
-
-                let msg = RawRelayMessage::from_json(&data)?;
-                tracing::trace!("Received message from {}: {:?}", relay.url, msg);
-
-                if let RawRelayMessage::Event { event, .. } = &msg {
-                    // Check event size
-                    let size: usize = event.as_json().as_bytes().len();
-                    let max_size: usize = relay.opts.limits.events.max_size as usize;
-                    if size > max_size {
-                        return Err(Error::EventTooLarge { size, max_size });
-                    }
+                        if size > max_size {
+                            return Err(Error::RelayMessageTooLarge { size, max_size });
+                        }
 
-                    // Check tags limit
-                    let size: usize = event.tags.len();
-                    let max_num_tags: usize = relay.opts.limits.events.max_num_tags as usize;
-                    if size > max_num_tags {
-                        return Err(Error::TooManyTags {
-                            size,
-                            max_size: max_num_tags,
-                        });
-                    }
-                }
+                        let msg = RawRelayMessage::from_json(&data)?;
+                        tracing::trace!("Received message from {}: {:?}", relay.url, msg);
 
-                match relay.handle_relay_message(msg).await {
-                    Ok(Some(msg)) => {
-                        // Send notification
-                        relay
-                            .send_notification(RelayNotification::Message {
-                                message: msg.clone(),
-                            })
-                            .await;
-
-                        match msg {
-                            RelayMessage::Notice { message } => {
-                                tracing::warn!("Notice from {}: {message}", relay.url)
+                        if let RawRelayMessage::Event {
+                            subscription_id,
+                            event,
+                        } = &msg
+                        {
+                            // Update traffic stats
+                            let id: SubscriptionId = SubscriptionId::new(subscription_id);
+                            relay.traffic.received(&id, event.kind, size);
+
+                            // Check event size
+                            let size: usize = event.as_json().as_bytes().len();
+                            let max_size: usize = relay.opts.limits.events.max_size as usize;
+                            if size > max_size {
+                                relay.traffic.dropped_policy(&id);
+                                return Err(Error::EventTooLarge { size, max_size });
                             }
-                            RelayMessage::Ok {
-                                event_id,
-                                status,
-                                message,
-                            } => {
-                                tracing::debug!("Received OK from {} for event {event_id}: status={status}, message={message}", relay.url);
+
+                            // Check tags limit
+                            let size: usize = event.tags.len();
+                            let max_num_tags: usize =
+                                relay.opts.limits.events.max_num_tags as usize;
+                            if size > max_num_tags {
+                                relay.traffic.dropped_policy(&id);
+                                return Err(Error::TooManyTags {
+                                    size,
+                                    max_size: max_num_tags,
+                                });
                             }
-                            _ => (),
                         }
-                    }
-                    Ok(None) => (),
-                    Err(e) => tracing::error!(
-                        "Impossible to handle relay message from {}: {e}",
-                        relay.url
-                    ),
-                }
 
-                Ok(false)
-            }
+                        match relay.handle_relay_message(msg).await {
+                            Ok(Some(msg)) => {
+                                // Send notification
+                                relay
+                                    .send_notification(RelayNotification::Message {
+                                        message: msg.clone(),
+                                    })
+                                    .await;
 
-            #[cfg(not(target_arch = "wasm32"))]
-            while let Some(msg_res) = ws_rx.next().await {
-                if let Ok(msg) = msg_res {
-                    match msg {
-                        WsMessage::Pong(bytes) => {
-                            if relay.opts.flags.has_ping() {
-                                match String::from_utf8(bytes) {
-                                    Ok(nonce) => match nonce.parse::<u64>() {
-                                        Ok(nonce) => {
-                                            if relay.stats.ping.last_nonce() == nonce {
-                                                tracing::debug!(
-                                                    "Pong from {} match nonce: {}",
-                                                    relay.url,
-                                                    nonce
-                                                );
-                                                relay.stats.ping.set_replied(true);
-                                                let sent_at = relay.stats.ping.sent_at().await;
-                                                relay.stats.save_latency(sent_at.elapsed()).await;
-                                            } else {
-                                                tracing::error!("Pong nonce not match: received={nonce}, expected={}", relay.stats.ping.last_nonce());
-                                            }
-                                        }
-                                        Err(e) => tracing::error!("{e}"),
-                                    },
-                                    Err(e) => tracing::error!("{e}"),
+                                match msg {
+                                    RelayMessage::Notice { message } => {
+                                        tracing::warn!("Notice from {}: {message}", relay.url);
+                                        relay.save_notice(RelayNotice::new(message, None)).await;
+                                        relay.health.record_notice();
+                                        relay.update_health().await;
+                                    }
+                                    RelayMessage::Closed {
+                                        subscription_id,
+                                        message,
+                                    } => {
+                                        tracing::debug!(
+                                            "Subscription {subscription_id} closed by {}: {message}",
+                                            relay.url
+                                        );
+                                        relay.requested.write().await.pop(&subscription_id);
+                                        let notice = RelayNotice::new(
+                                            message,
+                                            Some(subscription_id.clone()),
+                                        );
+                                        relay.save_notice(notice.clone()).await;
+                                        relay
+                                            .send_notification(
+                                                RelayNotification::SubscriptionClosed {
+                                                    subscription_id,
+                                                    prefix: notice.prefix,
+                                                    reason: notice.message,
+                                                },
+                                            )
+                                            .await;
+                                    }
+                                    RelayMessage::Ok {
+                                        event_id,
+                                        status,
+                                        message,
+                                    } => {
+                                        tracing::debug!("Received OK from {} for event {event_id}: status={status}, message={message}", relay.url);
+                                        relay.health.record_ok(status);
+                                        relay.update_health().await;
+                                    }
+                                    _ => (),
                                 }
                             }
+                            Ok(None) => (),
+                            Err(e) => tracing::error!(
+                                "Impossible to handle relay message from {}: {e}",
+                                relay.url
+                            ),
                         }
-                        _ => {
-                            let data: Vec<u8> = msg.into_data();
-                            match func(&relay, data).await {
-                                Ok(exit) => {
-                                    if exit {
-                                        break;
+
+                        Ok(false)
+                    }
+
+                    #[cfg(not(target_arch = "wasm32"))]
+                    while let Some(msg_res) = ws_rx.next().await {
+                        if let Ok(msg) = msg_res {
+                            match msg {
+                                WsMessage::Pong(bytes) => {
+                                    if relay.opts.flags.has_ping() {
+                                        match String::from_utf8(bytes) {
+                                            Ok(nonce) => match nonce.parse::<u64>() {
+                                                Ok(nonce) => {
+                                                    if relay.stats.ping.last_nonce() == nonce {
+                                                        tracing::debug!(
+                                                            "Pong from {} match nonce: {}",
+                                                            relay.url,
+                                                            nonce
+                                                        );
+                                                        relay.stats.ping.set_replied(true);
+                                                        let sent_at =
+                                                            relay.stats.ping.sent_at().await;
+                                                        relay
+                                                            .stats
+                                                            .save_latency(sent_at.elapsed())
+                                                            .await;
+                                                        relay.update_health().await;
+                                                    } else {
+                                                        tracing::error!("Pong nonce not match: received={nonce}, expected={}", relay.stats.ping.last_nonce());
+                                                    }
+                                                }
+                                                Err(e) => tracing::error!("{e}"),
+                                            },
+                                            Err(e) => tracing::error!("{e}"),
+                                        }
+                                    }
+                                }
+                                _ => {
+                                    let data: Vec<u8> = msg.into_data();
+                                    match func(&relay, data).await {
+                                        Ok(exit) => {
+                                            if exit {
+                                                break;
+                                            }
+                                        }
+                                        Err(Error::MessageHandle(
+                                            MessageHandleError::EmptyMsg,
+                                        )) => {}
+                                        Err(e) => tracing::error!(
+                                            "Impossible to handle relay message from {}: {e}",
+                                            relay.url
+                                        ),
                                     }
                                 }
-                                Err(Error::MessageHandle(MessageHandleError::EmptyMsg)) => {}
-                                Err(e) => tracing::error!(
-                                    "Impossible to handle relay message from {}: {e}",
-                                    relay.url
-                                ),
                             }
                         }
                     }
-                }
-            }
 
-            #[cfg(target_arch = "wasm32")]
-            while let Some(msg) = ws_rx.next().await {
-                let data: Vec<u8> = msg.as_ref().to_vec();
-                match func(&relay, data).await {
-                    Ok(exit) => {
-                        if exit {
-                            break;
+                    #[cfg(target_arch = "wasm32")]
+                    while let Some(msg) = ws_rx.next().await {
+                        let data: Vec<u8> = msg.as_ref().to_vec();
+                        match func(&relay, data).await {
+                            Ok(exit) => {
+                                if exit {
+                                    break;
+                                }
+                            }
+                            Err(e) => tracing::error!(
+                                "Impossible to handle relay message from {}: {e}",
+                                relay.url
+                            ),
                         }
                     }
-                    Err(e) => tracing::error!(
-                        "Impossible to handle relay message from {}: {e}",
-                        relay.url
-                    ),
-                }
-            }
 
-            tracing::debug!("Exited from Message Thread of {}", relay.url);
+                    tracing::debug!("Exited from Message Thread of {}", relay.url);
 
-            if let Err(err) = relay.disconnect().await {
-                tracing::error!("Impossible to disconnect {}: {}", relay.url, err);
-            }
+                    if let Err(err) = relay.disconnect().await {
+                        tracing::error!("Impossible to disconnect {}: {}", relay.url, err);
+                    }
+                })
+                .await;
         });
     }
 
     async fn try_connect(&self, connection_timeout: Option<Duration>) {
         self.stats.new_attempt();
+        self.health.record_reconnect();
+        self.update_health().await;
 
         let url: String = self.url.to_string();
 
@@ -696,8 +1335,22 @@ impl InternalRelay {
         };
 
         // Connect
-        match async_wsocket::connect(&self.url, self.proxy(), timeout).await {
+        #[cfg(not(target_arch = "wasm32"))]
+        let res = match (self.opts.proxy, &self.opts.proxy_auth) {
+            (Some(proxy), Some(auth)) => socks::connect(&self.url, proxy, auth, timeout)
+                .await
+                .map_err(|e| e.to_string()),
+            _ => async_wsocket::connect(&self.url, self.proxy(), timeout)
+                .await
+                .map_err(|e| e.to_string()),
+        };
+
+        #[cfg(target_arch = "wasm32")]
+        let res = async_wsocket::connect(&self.url, self.proxy(), timeout).await;
+
+        match res {
             Ok((ws_tx, ws_rx)) => {
+                self.set_last_connection_error(None).await;
                 self.set_status(RelayStatus::Connected).await;
                 tracing::info!("Connected to {url}");
 
@@ -727,6 +1380,7 @@ impl InternalRelay {
                 }
             }
             Err(err) => {
+                self.set_last_connection_error(Some(err.to_string())).await;
                 self.set_status(RelayStatus::Disconnected).await;
                 tracing::error!("Impossible to connect to {}: {}", url, err);
             }
@@ -746,9 +1400,21 @@ impl InternalRelay {
                 // Deserialize partial event (id, pubkey and sig)
                 let partial_event: PartialEvent = PartialEvent::from_raw(&event)?;
 
+                // Check if subscription exists
+                let id: SubscriptionId = SubscriptionId::new(&subscription_id);
+                if !self.is_solicited(&id, None).await
+                    && self
+                        .handle_unsolicited_event(&subscription_id, &partial_event.id)
+                        .await
+                {
+                    self.traffic.dropped_policy(&id);
+                    return Ok(None);
+                }
+
                 // Check min POW
                 let difficulty: u8 = self.opts.get_pow_difficulty();
                 if difficulty > 0 && !partial_event.id.check_pow(difficulty) {
+                    self.traffic.dropped_pow(&id);
                     return Err(Error::PowDifficultyTooLow { min: difficulty });
                 }
 
@@ -763,6 +1429,7 @@ impl InternalRelay {
                         partial_event.id,
                         self.url
                     );
+                    self.traffic.dropped_policy(&id);
                     return Ok(None);
                 }
 
@@ -785,6 +1452,7 @@ impl InternalRelay {
                             partial_event.id,
                             self.url
                         );
+                        self.traffic.dropped_policy(&id);
                         return Ok(None);
                     }
                 }
@@ -814,6 +1482,7 @@ impl InternalRelay {
                     .await?
                 {
                     tracing::trace!("Event {} already saved into database", partial_event.id);
+                    self.traffic.duplicate(&id);
                     return Ok(None);
                 }
 
@@ -822,30 +1491,59 @@ impl InternalRelay {
 
                 // Check if it's expired
                 if event.is_expired() {
+                    self.traffic.dropped_policy(&id);
                     return Err(Error::EventExpired);
                 }
 
                 // Verify event
-                event.verify()?;
+                if let Err(e) = event.verify() {
+                    self.traffic.dropped_policy(&id);
+                    return Err(e.into());
+                }
+
+                // Update subscription activity
+                self.set_subscription_last_event(&id, Timestamp::now())
+                    .await;
+
+                // Check if event match the subscription filters
+                if self.opts.get_verify_subscription_filters()
+                    && !self.is_solicited(&id, Some(&event)).await
+                    && self
+                        .handle_unsolicited_event(&subscription_id, &event.id)
+                        .await
+                {
+                    self.traffic.dropped_policy(&id);
+                    return Ok(None);
+                }
 
                 // Save event
                 self.database.save_event(&event).await?;
 
+                // Call raw event handler (synchronously)
+                let raw_event_handler = self.raw_event_handler.read().await;
+                if let Some(handler) = raw_event_handler.as_ref() {
+                    handler.0.on_event(&self.url, &subscription_id, &event);
+                }
+                drop(raw_event_handler);
+
                 // Box event
                 let event: Box<Event> = Box::new(event);
 
                 // Check if seen
-                if !seen {
+                if seen {
+                    self.traffic.duplicate(&id);
+                } else {
                     // Send notification
                     self.send_notification(RelayNotification::Event {
-                        subscription_id: SubscriptionId::new(&subscription_id),
+                        subscription_id: id.clone(),
                         event: event.clone(),
                     })
                     .await;
+                    self.traffic.delivered(&id);
                 }
 
                 Ok(Some(RelayMessage::Event {
-                    subscription_id: SubscriptionId::new(subscription_id),
+                    subscription_id: id,
                     event,
                 }))
             }
@@ -898,7 +1596,7 @@ impl InternalRelay {
 
     pub async fn batch_msg(
         &self,
-        msgs: Vec<ClientMessage>,
+        mut msgs: Vec<ClientMessage>,
         opts: RelaySendOptions,
     ) -> Result<(), Error> {
         if !self.opts.flags.has_write() && msgs.iter().any(|msg| msg.is_event()) {
@@ -909,14 +1607,21 @@ impl InternalRelay {
             return Err(Error::ReadDisabled);
         }
 
-        if opts.skip_disconnected
-            && !self.is_connected().await
-            && self.stats.attempts() > MIN_ATTEMPTS
-            && self.stats.uptime() < MIN_UPTIME
-        {
+        if opts.skip_disconnected && self.is_unreachable().await {
             return Err(Error::NotConnected);
         }
 
+        // Ephemeral events are never held to be sent after reconnection
+        if msgs.iter().any(is_ephemeral_event) && !self.is_connected().await {
+            msgs.retain(|msg| !is_ephemeral_event(msg));
+            if msgs.is_empty() {
+                return Err(Error::NotConnected);
+            }
+        }
+
+        // Track requested filters
+        self.update_requested(&msgs).await;
+
         if opts.skip_send_confirmation {
             self.send_relay_event(RelayEvent::Batch(msgs), None)
         } else {
@@ -947,13 +1652,21 @@ impl InternalRelay {
 
     pub async fn batch_event(
         &self,
-        events: Vec<Event>,
+        mut events: Vec<Event>,
         opts: RelaySendOptions,
     ) -> Result<(), Error> {
         if events.is_empty() {
             return Err(Error::BatchEventEmpty);
         }
 
+        // Ephemeral events are never held to be sent after reconnection
+        if events.iter().any(|e| e.is_ephemeral()) && !self.is_connected().await {
+            events.retain(|e| !e.is_ephemeral());
+            if events.is_empty() {
+                return Err(Error::NotConnected);
+            }
+        }
+
         let events_len: usize = events.len();
         let mut msgs: Vec<ClientMessage> = Vec::with_capacity(events_len);
         let mut missing: HashSet<EventId> = HashSet::with_capacity(events_len);
@@ -1054,12 +1767,15 @@ impl InternalRelay {
         Ok(id)
     }
 
+    /// Subscribe
+    ///
+    /// Return the subscriptions evicted to respect the subscriptions limit.
     pub async fn subscribe_with_id(
         &self,
         id: SubscriptionId,
         filters: Vec<Filter>,
         opts: SubscribeOptions,
-    ) -> Result<(), Error> {
+    ) -> Result<Vec<SubscriptionId>, Error> {
         // Check if relay has READ flags disabled
         if !self.opts.flags.has_read() {
             return Err(Error::ReadDisabled);
@@ -1070,8 +1786,31 @@ impl InternalRelay {
             return Err(Error::FiltersEmpty);
         }
 
+        // Track subscription, checking the limit (auto-closing subscriptions aren't tracked)
+        if !opts.is_auto_closing() {
+            let (inserted, evicted) = self.insert_subscription(&id, filters.clone()).await?;
+
+            if let Err(e) = self
+                .close_evicted_subscriptions(&evicted, opts.send_opts)
+                .await
+            {
+                tracing::error!("Impossible to close evicted subscriptions: {e}");
+            }
+
+            // Compose and send message
+            let msg: ClientMessage = ClientMessage::req(id.clone(), filters);
+            if let Err(e) = self.send_msg(msg, opts.send_opts).await {
+                if inserted {
+                    self.remove_subscription(&id).await;
+                }
+                return Err(e);
+            }
+
+            return Ok(evicted);
+        }
+
         // Compose and send message
-        let msg: ClientMessage = ClientMessage::req(id.clone(), filters.clone());
+        let msg: ClientMessage = ClientMessage::req(id.clone(), filters);
         self.send_msg(msg, opts.send_opts).await?;
 
         // Check if auto-close condition is set
@@ -1176,12 +1915,9 @@ impl InternalRelay {
 
                 Ok::<(), Error>(())
             })?;
-        } else {
-            // No auto-close subscription: update subscription filters
-            self.update_subscription(id.clone(), filters).await;
         }
 
-        Ok(())
+        Ok(Vec::new())
     }
 
     pub async fn unsubscribe(
@@ -1231,10 +1967,7 @@ impl InternalRelay {
         F: Future<Output = ()>,
     {
         // Check if relay is connected
-        if !self.is_connected().await
-            && self.stats.attempts() > MIN_ATTEMPTS
-            && self.stats.uptime() < MIN_UPTIME
-        {
+        if self.is_unreachable().await {
             return Err(Error::NotConnected);
         }
 
@@ -1416,10 +2149,7 @@ impl InternalRelay {
         }
 
         // Check if relay is connected
-        if !self.is_connected().await
-            && self.stats.attempts() > MIN_ATTEMPTS
-            && self.stats.uptime() < MIN_UPTIME
-        {
+        if self.is_unreachable().await {
             return Err(Error::NotConnected);
         }
 
@@ -1659,6 +2389,101 @@ impl InternalRelay {
         Ok(())
     }
 
+    pub async fn sync_with_negentropy(
+        &self,
+        filter: Filter,
+        timeout: Duration,
+    ) -> Result<NegentropyResult, Error> {
+        // Check if read option is disabled
+        if !self.opts.flags.has_read() {
+            return Err(Error::ReadDisabled);
+        }
+
+        // Check if relay is connected
+        if self.is_unreachable().await {
+            return Err(Error::NotConnected);
+        }
+
+        let items = self.database.negentropy_items(filter.clone()).await?;
+        let mut sync = NegentropySync::new(items)?;
+
+        let sub_id = SubscriptionId::generate();
+        let send_opts = RelaySendOptions::default().skip_send_confirmation(true);
+        let mut notifications = self.internal_notification_sender.subscribe();
+
+        // Send initial negentropy message
+        let open_msg = ClientMessage::NegOpen {
+            subscription_id: sub_id.clone(),
+            filter: Box::new(filter),
+            id_size: nip77::ID_SIZE as u8,
+            initial_message: Bytes::from(sync.initial_message()).to_hex(),
+        };
+        self.send_msg(open_msg, send_opts).await?;
+
+        // Close negentropy on every exit path
+        let _close = NegCloseGuard {
+            relay: self,
+            subscription_id: sub_id.clone(),
+        };
+
+        let mut result = NegentropyResult::default();
+
+        time::timeout(Some(timeout), async {
+            while let Ok(notification) = notifications.recv().await {
+                match notification {
+                    RelayNotification::Message {
+                        message:
+                            RelayMessage::NegMsg {
+                                subscription_id,
+                                message,
+                            },
+                    } if subscription_id == sub_id => {
+                        let query: Bytes = Bytes::from_hex(message)?;
+                        let res: NegentropyResult = sync.reconcile(query.as_bytes())?;
+                        result.have.extend(res.have);
+                        result.need.extend(res.need);
+                        match res.next_message {
+                            Some(msg) => {
+                                self.send_msg(
+                                    ClientMessage::NegMsg {
+                                        subscription_id: sub_id.clone(),
+                                        message: Bytes::from(msg).to_hex(),
+                                    },
+                                    send_opts,
+                                )
+                                .await?;
+                            }
+                            None => return Ok(()),
+                        }
+                    }
+                    RelayNotification::Message {
+                        message:
+                            RelayMessage::NegErr {
+                                subscription_id,
+                                code,
+                            },
+                    } if subscription_id == sub_id => {
+                        return Err(Error::NegentropyReconciliation(code));
+                    }
+                    RelayNotification::RelayStatus { status } if status.is_disconnected() => {
+                        return Err(Error::NotConnectedStatusChanged);
+                    }
+                    RelayNotification::Stop | RelayNotification::Shutdown => {
+                        return Err(Error::NegentropyInterrupted);
+                    }
+                    _ => (),
+                }
+            }
+
+            // Notifications channel closed or lagged: the result would be partial
+            Err(Error::NegentropyInterrupted)
+        })
+        .await
+        .ok_or(Error::Timeout)??;
+
+        Ok(result)
+    }
+
     pub async fn support_negentropy(&self) -> Result<bool, Error> {
         let pk = Keys::generate();
         let filter = Filter::new().author(pk.public_key());
@@ -1676,3 +2501,206 @@ impl InternalRelay {
         }
     }
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use nostr_database::{MemoryDatabase, MemoryDatabaseOptions};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc::UnboundedSender;
+    use tokio_tungstenite::tungstenite::Message as TungsteniteMessage;
+
+    use super::*;
+
+    /// Minimal relay: forward the received text frames
+    async fn spawn_relay(frames: UnboundedSender<String>) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let frames = frames.clone();
+                tokio::spawn(async move {
+                    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    // Pings are answered automatically while reading
+                    while let Some(Ok(msg)) = ws.next().await {
+                        if let TungsteniteMessage::Text(json) = msg {
+                            let _ = frames.send(json);
+                        }
+                    }
+                });
+            }
+        });
+        Url::parse(&format!("ws://{addr}")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_ping_while_throttled() {
+        let (frames_tx, mut frames_rx) = mpsc::unbounded_channel();
+        let url = spawn_relay(frames_tx).await;
+        let opts = RelayOptions::new().max_bytes_per_hour(Some(1000));
+        let relay = InternalRelay::new(url, Arc::new(MemoryDatabase::default()), opts);
+        relay.connect(Some(Duration::from_secs(5))).await;
+        assert!(relay.is_connected().await);
+
+        // Oversized batch: sent with the full bucket, then the cap is exhausted
+        let big = ClientMessage::close(SubscriptionId::new("a".repeat(2000)));
+        let small = ClientMessage::close(SubscriptionId::new("b"));
+        relay
+            .send_relay_event(RelayEvent::Batch(vec![big]), None)
+            .unwrap();
+        relay
+            .send_relay_event(RelayEvent::Batch(vec![small]), None)
+            .unwrap();
+        time::timeout(Some(Duration::from_secs(5)), frames_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+
+        // Wait for the second batch to be held
+        time::timeout(Some(Duration::from_secs(5)), async {
+            while !relay.bandwidth.is_rate_limited() {
+                thread::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        // Pings still flow
+        let nonce: u64 = 42;
+        relay.stats.ping.set_last_nonce(nonce);
+        relay.stats.ping.set_replied(false);
+        relay
+            .send_relay_event(RelayEvent::Ping { nonce }, None)
+            .unwrap();
+        time::timeout(Some(Duration::from_secs(5)), async {
+            while !relay.stats.ping.replied() {
+                thread::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        // The held batch is not sent
+        assert!(relay.bandwidth.is_rate_limited());
+        assert!(frames_rx.try_recv().is_err());
+    }
+
+    async fn recv_neg_close(frames_rx: &mut mpsc::UnboundedReceiver<String>) -> SubscriptionId {
+        time::timeout(Some(Duration::from_secs(5)), async {
+            loop {
+                let json = frames_rx.recv().await.unwrap();
+                if let Ok(ClientMessage::NegClose { subscription_id }) =
+                    ClientMessage::from_json(json)
+                {
+                    return subscription_id;
+                }
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sync_with_negentropy_close() {
+        let (frames_tx, mut frames_rx) = mpsc::unbounded_channel();
+        let url = spawn_relay(frames_tx).await;
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        });
+        let relay = InternalRelay::new(url, Arc::new(database), RelayOptions::default());
+        relay.connect(Some(Duration::from_secs(5))).await;
+
+        // The relay never answers: timeout
+        let res = relay
+            .sync_with_negentropy(Filter::new(), Duration::from_millis(200))
+            .await;
+        assert!(matches!(res, Err(Error::Timeout)));
+        recv_neg_close(&mut frames_rx).await;
+
+        // Relay stopped: no partial result
+        let sync = relay.sync_with_negentropy(Filter::new(), Duration::from_secs(5));
+        let stop = async {
+            thread::sleep(Duration::from_millis(200)).await;
+            relay.send_notification(RelayNotification::Stop).await;
+        };
+        let (res, ()) = futures_util::join!(sync, stop);
+        assert!(matches!(res, Err(Error::NegentropyInterrupted)));
+        recv_neg_close(&mut frames_rx).await;
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_limit() {
+        let (frames_tx, mut frames_rx) = mpsc::unbounded_channel();
+        let url = spawn_relay(frames_tx).await;
+        let opts = RelayOptions::new().max_subscriptions(Some(2));
+        let relay = InternalRelay::new(url, Arc::new(MemoryDatabase::default()), opts);
+        relay.connect(Some(Duration::from_secs(5))).await;
+
+        let filters = vec![Filter::new()];
+        let opts = SubscribeOptions::default();
+        let first = relay.subscribe(filters.clone(), opts).await.unwrap();
+        relay.subscribe(filters.clone(), opts).await.unwrap();
+
+        // Rejected
+        let res = relay.subscribe(filters.clone(), opts).await;
+        assert!(matches!(res, Err(Error::TooManySubscriptions { max: 2 })));
+        assert_eq!(relay.subscriptions().await.len(), 2);
+
+        // Updating an existing subscription is allowed
+        let evicted = relay.subscribe_with_id(first, filters, opts).await.unwrap();
+        assert!(evicted.is_empty());
+
+        // Auto-closing subscriptions aren't counted
+        let opts = opts.close_on(Some(SubscribeAutoCloseOptions::default()));
+        relay.subscribe(vec![Filter::new()], opts).await.unwrap();
+        assert_eq!(relay.subscriptions().await.len(), 2);
+
+        while frames_rx.try_recv().is_ok() {}
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_eviction() {
+        let (frames_tx, mut frames_rx) = mpsc::unbounded_channel();
+        let url = spawn_relay(frames_tx).await;
+        let opts = RelayOptions::new()
+            .max_subscriptions(Some(2))
+            .subscription_limit_policy(SubscriptionLimitPolicy::EvictLeastRecentlyActive);
+        let relay = InternalRelay::new(url, Arc::new(MemoryDatabase::default()), opts);
+        relay.connect(Some(Duration::from_secs(5))).await;
+
+        let filters = vec![Filter::new()];
+        let opts = SubscribeOptions::default();
+        let first = relay.subscribe(filters.clone(), opts).await.unwrap();
+        let second = relay.subscribe(filters.clone(), opts).await.unwrap();
+
+        // The first subscription is the most recently active
+        relay
+            .set_subscription_last_event(&first, Timestamp::now() + Duration::from_secs(60))
+            .await;
+
+        let third = SubscriptionId::generate();
+        let evicted = relay
+            .subscribe_with_id(third.clone(), filters, opts)
+            .await
+            .unwrap();
+        assert_eq!(evicted, vec![second.clone()]);
+
+        let subscriptions = relay.subscriptions().await;
+        assert_eq!(subscriptions.len(), 2);
+        assert!(subscriptions.contains_key(&first));
+        assert!(subscriptions.contains_key(&third));
+
+        // CLOSE sent for the evicted subscription
+        time::timeout(Some(Duration::from_secs(5)), async {
+            loop {
+                let json = frames_rx.recv().await.unwrap();
+                if let Ok(ClientMessage::Close(id)) = ClientMessage::from_json(json) {
+                    assert_eq!(id, second);
+                    break;
+                }
+            }
+        })
+        .await
+        .unwrap();
+    }
+}
@@ -15,28 +15,51 @@ use async_wsocket::futures_util::Future;
 use atomic_destructor::AtomicDestructor;
 #[cfg(feature = "nip11")]
 use nostr::nips::nip11::RelayInformationDocument;
-use nostr::{ClientMessage, Event, EventId, Filter, RelayMessage, SubscriptionId, Timestamp, Url};
+use nostr::nips::nip77::NegentropyResult;
+use nostr::{
+    ClientMessage, Event, EventId, Filter, MachineReadablePrefix, RelayMessage, SubscriptionId,
+    Timestamp, Url,
+};
 use nostr_database::{DynNostrDatabase, MemoryDatabase};
 use tokio::sync::broadcast;
 
+mod bandwidth;
 mod error;
 pub mod flags;
+pub mod health;
 mod internal;
 pub mod limits;
+pub mod notice;
 pub mod options;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod probe;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod socks;
 pub mod stats;
 mod status;
+pub mod subscription;
+pub mod traffic;
 
 pub use self::error::Error;
 pub use self::flags::{AtomicRelayServiceFlags, RelayServiceFlags};
+pub use self::health::{RelayHealth, RelayHealthDetails, RelayHealthThresholds};
 use self::internal::InternalRelay;
 pub use self::limits::RelayLimits;
+pub use self::notice::RelayNotice;
 pub use self::options::{
     FilterOptions, NegentropyDirection, NegentropyOptions, RelayOptions, RelaySendOptions,
-    SubscribeAutoCloseOptions, SubscribeOptions,
+    ReorderOptions, SubscribeAutoCloseOptions, SubscribeOptions, SubscriptionLimitPolicy,
+    UnsolicitedEventPolicy,
 };
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::probe::{ProbeFailure, ProbeOptions, ProbeReport, TlsCertificate};
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::socks::SocksAuth;
 pub use self::stats::RelayConnectionStats;
 pub use self::status::RelayStatus;
+pub use self::subscription::SubscriptionActivity;
+pub use self::traffic::{SubscriptionStats, TrafficReport};
+use crate::pool::handler::RawEventHandler;
 use crate::pool::RelayPoolNotification;
 
 /// Relay Notification
@@ -59,6 +82,32 @@ pub enum RelayNotification {
         /// Relay Status
         status: RelayStatus,
     },
+    /// Subscription closed to respect the relay subscriptions limit
+    ///
+    /// Sent only if [`SubscriptionLimitPolicy::EvictLeastRecentlyActive`] is set.
+    SubscriptionEvicted {
+        /// Subscription ID
+        subscription_id: SubscriptionId,
+    },
+    /// Subscription closed by the relay (`CLOSED` message)
+    SubscriptionClosed {
+        /// Subscription ID
+        subscription_id: SubscriptionId,
+        /// Machine-readable prefix of the reason, if any
+        prefix: Option<MachineReadablePrefix>,
+        /// Reason
+        reason: String,
+    },
+    /// Relay health classification changed
+    HealthChanged {
+        /// Health
+        health: RelayHealth,
+    },
+    /// A relay task panicked: the relay has been terminated
+    Error {
+        /// Error
+        error: String,
+    },
     /// Stop
     Stop,
     /// Shutdown
@@ -110,6 +159,16 @@ impl Relay {
         }
     }
 
+    /// Probe a relay url, without adding it to a pool or connecting a [`Relay`]
+    ///
+    /// Check the connection, the `NIP11` document, if the relay answers to a query and if it requires `AUTH`
+    /// (ex. to validate a relay url before adding it). Check [`ProbeReport`].
+    #[inline]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn probe(url: Url, opts: ProbeOptions) -> ProbeReport {
+        probe::probe(url, opts).await
+    }
+
     /// Get relay url
     #[inline]
     pub fn url(&self) -> Url {
@@ -129,6 +188,15 @@ impl Relay {
         self.inner.status().await
     }
 
+    /// Get the error of the last failed connection attempt
+    ///
+    /// Also set to the panic message if a relay task panicked (see [`RelayOptions::propagate_panics`]).
+    /// Reset to `None` when the connection succeeds.
+    #[inline]
+    pub async fn last_connection_error(&self) -> Option<String> {
+        self.inner.last_connection_error().await
+    }
+
     /// Get Relay Service Flags
     #[inline]
     pub fn flags(&self) -> AtomicRelayServiceFlags {
@@ -141,6 +209,14 @@ impl Relay {
         self.inner.is_connected().await
     }
 
+    /// Check if [`Relay`] is disconnected and has a poor connection history
+    ///
+    /// Unreachable relays are skipped by send methods if [`RelaySendOptions::skip_disconnected`] is enabled.
+    #[inline]
+    pub async fn is_unreachable(&self) -> bool {
+        self.inner.is_unreachable().await
+    }
+
     /// Get [`RelayInformationDocument`]
     #[inline]
     #[cfg(feature = "nip11")]
@@ -160,6 +236,40 @@ impl Relay {
         self.inner.subscription(id).await
     }
 
+    /// Get activity of subscriptions (i.e. timestamp of last received event)
+    #[inline]
+    pub async fn subscriptions_activity(&self) -> HashMap<SubscriptionId, SubscriptionActivity> {
+        self.inner.subscriptions_activity().await
+    }
+
+    /// Get the incoming traffic stats of a subscription
+    #[inline]
+    pub fn subscription_stats(&self, id: &SubscriptionId) -> Option<SubscriptionStats> {
+        self.inner.traffic.stats(id)
+    }
+
+    /// Get the incoming traffic stats of all the subscriptions
+    #[inline]
+    pub fn traffic_report(&self) -> TrafficReport {
+        TrafficReport {
+            subscriptions: self.inner.traffic.all(),
+        }
+    }
+
+    /// Reset the incoming traffic stats of a subscription or, if `None`, of all the subscriptions
+    #[inline]
+    pub fn reset_traffic_stats(&self, id: Option<&SubscriptionId>) {
+        self.inner.traffic.reset(id)
+    }
+
+    /// Move an event from delivered to dropped in the traffic stats
+    ///
+    /// Used to account the events dropped by the notification consumers (ex. spam detector).
+    #[inline]
+    pub fn record_undelivered(&self, id: &SubscriptionId) {
+        self.inner.traffic.undelivered(id)
+    }
+
     /// Get [`RelayOptions`]
     #[inline]
     pub fn opts(&self) -> RelayOptions {
@@ -172,12 +282,41 @@ impl Relay {
         self.inner.stats()
     }
 
+    /// Get relay health
+    ///
+    /// Computed from ping latency, connection attempts, rejected events rate and notices
+    /// in a sliding window (check [`RelayOptions::health_thresholds`]).
+    #[inline]
+    pub async fn health(&self) -> RelayHealth {
+        self.inner.health_details().await.health
+    }
+
+    /// Get relay health with the inputs that drove the classification
+    #[inline]
+    pub async fn health_details(&self) -> RelayHealthDetails {
+        self.inner.health_details().await
+    }
+
     /// Get queue len
     #[inline]
     pub fn queue(&self) -> usize {
         self.inner.queue()
     }
 
+    /// Get number of bytes sent in the current hour
+    #[inline]
+    pub fn bytes_sent_this_hour(&self) -> u64 {
+        self.inner.bandwidth.bytes_sent_this_hour()
+    }
+
+    /// Check if outgoing messages are held because the hourly bandwidth cap is reached
+    ///
+    /// Check [`RelayOptions::max_bytes_per_hour`].
+    #[inline]
+    pub fn send_rate_limited(&self) -> bool {
+        self.inner.bandwidth.is_rate_limited()
+    }
+
     /// Get new **relay** notification listener
     #[inline]
     pub fn notifications(&self) -> broadcast::Receiver<RelayNotification> {
@@ -195,7 +334,28 @@ impl Relay {
             .await
     }
 
+    /// Get the last `NOTICE` and `CLOSED` messages received from the relay, newest first
+    ///
+    /// The history size can be set with [`RelayOptions::notices_history`].
+    #[inline]
+    pub async fn recent_notices(&self) -> Vec<RelayNotice> {
+        self.inner.notices.recent().await
+    }
+
+    /// Set raw event handler
+    ///
+    /// Check [`RawEventHandler`] for the constraints.
+    #[inline]
+    pub async fn set_raw_event_handler(
+        &self,
+        handler: Option<Arc<dyn RawEventHandler + Send + Sync>>,
+    ) {
+        self.inner.set_raw_event_handler(handler).await
+    }
+
     /// Connect to relay and keep alive connection
+    ///
+    /// Revive also a relay terminated by a panic.
     #[inline]
     pub async fn connect(&self, connection_timeout: Option<Duration>) {
         self.inner.connect(connection_timeout).await
@@ -277,7 +437,8 @@ impl Relay {
         filters: Vec<Filter>,
         opts: SubscribeOptions,
     ) -> Result<(), Error> {
-        self.inner.subscribe_with_id(id, filters, opts).await
+        self.inner.subscribe_with_id(id, filters, opts).await?;
+        Ok(())
     }
 
     /// Unsubscribe
@@ -355,6 +516,22 @@ impl Relay {
         self.inner.reconcile_with_items(filter, items, opts).await
     }
 
+    /// Negentropy sync (NIP77)
+    ///
+    /// Reconcile the events stored in database matching the filter, without transferring them:
+    /// return which events the relay needs and which ones are missing locally.
+    ///
+    /// Return an error if the sync doesn't complete (i.e. timeout or relay stopped).
+    /// `NEG-CLOSE` is sent in any case.
+    #[inline]
+    pub async fn sync_with_negentropy(
+        &self,
+        filter: Filter,
+        timeout: Duration,
+    ) -> Result<NegentropyResult, Error> {
+        self.inner.sync_with_negentropy(filter, timeout).await
+    }
+
     /// Check if relay support negentropy protocol
     #[inline]
     pub async fn support_negentropy(&self) -> Result<bool, Error> {
@@ -0,0 +1,112 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Relay notices
+
+use std::collections::VecDeque;
+use std::mem;
+use std::sync::Arc;
+
+use nostr::{MachineReadablePrefix, SubscriptionId, Timestamp};
+use tokio::sync::Mutex;
+
+/// `NOTICE` or `CLOSED` message received from a relay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayNotice {
+    /// Message
+    pub message: String,
+    /// When the message was received
+    pub timestamp: Timestamp,
+    /// Closed subscription ID (`None` for `NOTICE` messages)
+    pub subscription_id: Option<SubscriptionId>,
+    /// Machine-readable prefix of the message, if any
+    pub prefix: Option<MachineReadablePrefix>,
+}
+
+impl RelayNotice {
+    pub(crate) fn new(message: String, subscription_id: Option<SubscriptionId>) -> Self {
+        Self {
+            prefix: MachineReadablePrefix::parse(&message),
+            message,
+            timestamp: Timestamp::now(),
+            subscription_id,
+        }
+    }
+}
+
+/// Bounded history of the last notices
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RelayNotices {
+    list: Arc<Mutex<VecDeque<RelayNotice>>>,
+}
+
+impl RelayNotices {
+    pub async fn push(&self, notice: RelayNotice, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+
+        let mut list = self.list.lock().await;
+        while list.len() >= capacity {
+            list.pop_front();
+        }
+        list.push_back(notice);
+    }
+
+    /// Number of notices and their approximate size, in bytes
+    pub async fn usage(&self) -> (usize, usize) {
+        let list = self.list.lock().await;
+        let bytes: usize = list
+            .iter()
+            .map(|n| mem::size_of::<RelayNotice>() + n.message.len())
+            .sum();
+        (list.len(), bytes)
+    }
+
+    /// Get notices, newest first
+    pub async fn recent(&self) -> Vec<RelayNotice> {
+        let list = self.list.lock().await;
+        list.iter().rev().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notice(message: &str) -> RelayNotice {
+        RelayNotice::new(message.to_string(), None)
+    }
+
+    #[tokio::test]
+    async fn test_notices_capacity() {
+        let notices = RelayNotices::default();
+        notices.push(notice("a"), 2).await;
+        notices.push(notice("b"), 2).await;
+        notices.push(notice("c"), 2).await;
+
+        // Oldest dropped
+        let messages: Vec<String> = notices
+            .recent()
+            .await
+            .into_iter()
+            .map(|n| n.message)
+            .collect();
+        assert_eq!(messages, vec![String::from("c"), String::from("b")]);
+
+        // Capacity reduced
+        notices.push(notice("d"), 1).await;
+        let messages: Vec<String> = notices
+            .recent()
+            .await
+            .into_iter()
+            .map(|n| n.message)
+            .collect();
+        assert_eq!(messages, vec![String::from("d")]);
+
+        // Disabled
+        notices.push(notice("e"), 0).await;
+        assert_eq!(notices.usage().await.0, 1);
+    }
+}
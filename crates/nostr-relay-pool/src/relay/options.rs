@@ -6,16 +6,27 @@
 
 #[cfg(not(target_arch = "wasm32"))]
 use std::net::SocketAddr;
+use std::ops::Range;
 use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
+use nostr::secp256k1::rand::rngs::OsRng;
+use nostr::secp256k1::rand::Rng;
+
 use super::flags::{AtomicRelayServiceFlags, RelayServiceFlags};
+use super::health::RelayHealthThresholds;
+#[cfg(not(target_arch = "wasm32"))]
+use super::socks::SocksAuth;
 use crate::RelayLimits;
 
 /// Default send timeout
 pub const DEFAULT_SEND_TIMEOUT: Duration = Duration::from_secs(20);
 pub(super) const DEFAULT_RETRY_SEC: u64 = 10;
+pub(super) const DEFAULT_NOTICES_HISTORY: usize = 20;
+pub(super) const DEFAULT_MAX_VIOLATIONS: usize = 100;
+const DEFAULT_REORDER_WINDOW: Duration = Duration::from_secs(2);
+const DEFAULT_REORDER_CAPACITY: usize = 1000;
 pub(super) const MIN_RETRY_SEC: u64 = 5;
 pub(super) const MAX_ADJ_RETRY_SEC: u64 = 60;
 pub(super) const NEGENTROPY_HIGH_WATER_UP: usize = 100;
@@ -27,12 +38,25 @@ pub(super) const NEGENTROPY_BATCH_SIZE_DOWN: usize = 50;
 pub struct RelayOptions {
     #[cfg(not(target_arch = "wasm32"))]
     pub(super) proxy: Option<SocketAddr>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(super) proxy_auth: Option<SocksAuth>,
     pub(super) flags: AtomicRelayServiceFlags,
     pow: Arc<AtomicU8>,
     reconnect: Arc<AtomicBool>,
     retry_sec: Arc<AtomicU64>,
     adjust_retry_sec: Arc<AtomicBool>,
     pub(super) limits: RelayLimits,
+    max_subscriptions: Option<usize>,
+    subscriptions_ceiling: Option<usize>,
+    subscription_limit_policy: SubscriptionLimitPolicy,
+    max_bytes_per_hour: Option<u64>,
+    notices_history: usize,
+    initial_connection_delay: Option<Range<Duration>>,
+    health_thresholds: RelayHealthThresholds,
+    unsolicited_events: UnsolicitedEventPolicy,
+    verify_subscription_filters: bool,
+    max_violations: Option<usize>,
+    propagate_panics: bool,
 }
 
 impl Default for RelayOptions {
@@ -40,12 +64,25 @@ impl Default for RelayOptions {
         Self {
             #[cfg(not(target_arch = "wasm32"))]
             proxy: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            proxy_auth: None,
             flags: AtomicRelayServiceFlags::default(),
             pow: Arc::new(AtomicU8::new(0)),
             reconnect: Arc::new(AtomicBool::new(true)),
             retry_sec: Arc::new(AtomicU64::new(DEFAULT_RETRY_SEC)),
             adjust_retry_sec: Arc::new(AtomicBool::new(true)),
             limits: RelayLimits::default(),
+            max_subscriptions: None,
+            subscriptions_ceiling: None,
+            subscription_limit_policy: SubscriptionLimitPolicy::default(),
+            max_bytes_per_hour: None,
+            notices_history: DEFAULT_NOTICES_HISTORY,
+            initial_connection_delay: None,
+            health_thresholds: RelayHealthThresholds::default(),
+            unsolicited_events: UnsolicitedEventPolicy::default(),
+            verify_subscription_filters: false,
+            max_violations: Some(DEFAULT_MAX_VIOLATIONS),
+            propagate_panics: false,
         }
     }
 }
@@ -56,10 +93,21 @@ impl RelayOptions {
         Self::default()
     }
 
-    /// Set proxy
+    /// Set proxy (SOCKS5 without authentication)
     #[cfg(not(target_arch = "wasm32"))]
     pub fn proxy(mut self, proxy: Option<SocketAddr>) -> Self {
         self.proxy = proxy;
+        self.proxy_auth = None;
+        self
+    }
+
+    /// Set SOCKS5 proxy with username/password authentication
+    ///
+    /// The authentication is used only for the relay connection, not for the `NIP11` document request.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn proxy_with_auth(mut self, proxy: SocketAddr, auth: SocksAuth) -> Self {
+        self.proxy = Some(proxy);
+        self.proxy_auth = Some(auth);
         self
     }
 
@@ -105,7 +153,8 @@ impl RelayOptions {
         self
     }
 
-    pub(crate) fn get_pow_difficulty(&self) -> u8 {
+    /// Get minimum POW for received events
+    pub fn get_pow_difficulty(&self) -> u8 {
         self.pow.load(Ordering::SeqCst)
     }
 
@@ -182,6 +231,171 @@ impl RelayOptions {
         self.limits = limits;
         self
     }
+
+    /// Max number of active subscriptions (default: None)
+    ///
+    /// Used only if the relay not advertise the `max_subscriptions` limitation in its NIP11 document.
+    /// If `None`, the number of subscriptions is unlimited.
+    pub fn max_subscriptions(mut self, max: Option<usize>) -> Self {
+        self.max_subscriptions = max;
+        self
+    }
+
+    pub(crate) fn get_max_subscriptions(&self) -> Option<usize> {
+        self.max_subscriptions
+    }
+
+    /// Hard cap of the number of subscriptions, applied also to the limit advertised by the relay
+    pub(crate) fn subscriptions_ceiling(mut self, max: usize) -> Self {
+        self.subscriptions_ceiling = Some(max);
+        self
+    }
+
+    pub(crate) fn get_subscriptions_ceiling(&self) -> Option<usize> {
+        self.subscriptions_ceiling
+    }
+
+    /// What to do when a new subscription would exceed the max number of subscriptions (default: reject)
+    pub fn subscription_limit_policy(mut self, policy: SubscriptionLimitPolicy) -> Self {
+        self.subscription_limit_policy = policy;
+        self
+    }
+
+    pub(crate) fn get_subscription_limit_policy(&self) -> SubscriptionLimitPolicy {
+        self.subscription_limit_policy
+    }
+
+    /// Max number of bytes that can be sent to the relay per hour (default: None)
+    ///
+    /// The bandwidth is refilled proportionally (i.e. with a cap of 3600 bytes, 1 byte per second).
+    /// When the cap is reached, outgoing messages are held until enough bandwidth is available:
+    /// pings and control frames are never held.
+    /// If `None`, the bandwidth is unlimited.
+    pub fn max_bytes_per_hour(mut self, max: Option<u64>) -> Self {
+        self.max_bytes_per_hour = max;
+        self
+    }
+
+    pub(crate) fn get_max_bytes_per_hour(&self) -> Option<u64> {
+        self.max_bytes_per_hour
+    }
+
+    /// Number of `NOTICE` and `CLOSED` messages to keep in the relay history (default: 20)
+    ///
+    /// Use `0` to disable the history.
+    pub fn notices_history(mut self, size: usize) -> Self {
+        self.notices_history = size;
+        self
+    }
+
+    pub(crate) fn get_notices_history(&self) -> usize {
+        self.notices_history
+    }
+
+    /// Delay the initial connection attempt by a random duration within `range` (default: None)
+    ///
+    /// Useful to avoid opening many connections at the same time when a lot of relays are added at once.
+    /// The delay is picked using the OS RNG, so it's not correlated across different clients.
+    pub fn initial_connection_delay(mut self, range: Range<Duration>) -> Self {
+        self.initial_connection_delay = Some(range);
+        self
+    }
+
+    pub(crate) fn has_initial_connection_delay(&self) -> bool {
+        self.initial_connection_delay.is_some()
+    }
+
+    /// Thresholds used to classify the relay health (see [`Relay::health`](super::Relay::health))
+    pub fn health_thresholds(mut self, thresholds: RelayHealthThresholds) -> Self {
+        self.health_thresholds = thresholds;
+        self
+    }
+
+    pub(crate) fn get_health_thresholds(&self) -> RelayHealthThresholds {
+        self.health_thresholds
+    }
+
+    /// Policy for unsolicited events (default: [`UnsolicitedEventPolicy::Drop`])
+    ///
+    /// An event is unsolicited if its subscription ID is unknown (cheap check, always done) or,
+    /// if [`RelayOptions::verify_subscription_filters`] is enabled, if it doesn't match the subscription filters.
+    pub fn unsolicited_events(mut self, policy: UnsolicitedEventPolicy) -> Self {
+        self.unsolicited_events = policy;
+        self
+    }
+
+    pub(crate) fn get_unsolicited_events(&self) -> UnsolicitedEventPolicy {
+        self.unsolicited_events
+    }
+
+    /// Check that every received event match the filters of its subscription (default: false)
+    pub fn verify_subscription_filters(mut self, verify: bool) -> Self {
+        self.verify_subscription_filters = verify;
+        self
+    }
+
+    pub(crate) fn get_verify_subscription_filters(&self) -> bool {
+        self.verify_subscription_filters
+    }
+
+    /// Stop the relay after this number of flagged violations (default: 100)
+    ///
+    /// Used only with [`UnsolicitedEventPolicy::DropAndFlag`]. Use `None` to never stop the relay.
+    pub fn max_violations(mut self, max: Option<usize>) -> Self {
+        self.max_violations = max;
+        self
+    }
+
+    pub(crate) fn get_max_violations(&self) -> Option<usize> {
+        self.max_violations
+    }
+
+    /// Let the panics of the relay tasks propagate (default: false)
+    ///
+    /// By default, a panic inside a relay task is caught: the relay is terminated
+    /// and a [`RelayPoolNotification::RelayError`](crate::RelayPoolNotification::RelayError) is sent.
+    /// Enable it in tests, to not hide bugs.
+    pub fn propagate_panics(mut self, propagate: bool) -> Self {
+        self.propagate_panics = propagate;
+        self
+    }
+
+    pub(crate) fn get_propagate_panics(&self) -> bool {
+        self.propagate_panics
+    }
+
+    /// Pick a random initial connection delay
+    pub(crate) fn get_initial_connection_delay(&self) -> Option<Duration> {
+        let range: &Range<Duration> = self.initial_connection_delay.as_ref()?;
+        if range.is_empty() {
+            return Some(range.start);
+        }
+        Some(OsRng.gen_range(range.clone()))
+    }
+}
+
+/// Policy applied when a new subscription would exceed the relay subscriptions limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SubscriptionLimitPolicy {
+    /// Reject the new subscription with [`Error::TooManySubscriptions`](super::Error::TooManySubscriptions)
+    #[default]
+    Reject,
+    /// Close the least recently active subscription and send a `SubscriptionEvicted` notification
+    EvictLeastRecentlyActive,
+}
+
+/// Policy applied to events not requested by any subscription
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnsolicitedEventPolicy {
+    /// Accept the event, as if it was requested
+    Accept,
+    /// Drop the event
+    #[default]
+    Drop,
+    /// Drop the event and count a violation
+    ///
+    /// Violations lower the relay health and, at [`RelayOptions::max_violations`], stop the relay.
+    DropAndFlag,
 }
 
 /// [`Relay`](super::Relay) send options
@@ -250,11 +464,48 @@ impl SubscribeAutoCloseOptions {
     }
 }
 
+/// Reordering options of a subscription
+///
+/// Check [`SubscribeOptions::reorder`].
+#[derive(Debug, Clone, Copy)]
+pub struct ReorderOptions {
+    pub(crate) window: Duration,
+    pub(crate) capacity: usize,
+}
+
+impl Default for ReorderOptions {
+    fn default() -> Self {
+        Self {
+            window: DEFAULT_REORDER_WINDOW,
+            capacity: DEFAULT_REORDER_CAPACITY,
+        }
+    }
+}
+
+impl ReorderOptions {
+    /// New reordering options with the window events are held for
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            ..Default::default()
+        }
+    }
+
+    /// Max number of buffered events (default: 1000)
+    ///
+    /// When full, the oldest event is delivered immediately.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+}
+
 /// Subscribe options
 #[derive(Debug, Clone, Copy, Default)]
 pub struct SubscribeOptions {
     pub(super) auto_close: Option<SubscribeAutoCloseOptions>,
     pub(super) send_opts: RelaySendOptions,
+    pub(crate) reorder: Option<ReorderOptions>,
 }
 
 impl SubscribeOptions {
@@ -270,6 +521,20 @@ impl SubscribeOptions {
         self
     }
 
+    /// Deliver events in `created_at` order (default: disabled)
+    ///
+    /// Events are held for the [`ReorderOptions`] window and sent, in order,
+    /// as [`RelayPoolNotification::OrderedEvent`](crate::RelayPoolNotification::OrderedEvent),
+    /// in addition to the usual [`RelayPoolNotification::Event`](crate::RelayPoolNotification::Event).
+    /// Events older than an already delivered one are sent immediately with the `out_of_order` flag.
+    /// EOSE flushes the buffer.
+    ///
+    /// Ignored for auto-closing subscriptions.
+    pub fn reorder(mut self, opts: Option<ReorderOptions>) -> Self {
+        self.reorder = opts;
+        self
+    }
+
     pub(crate) fn is_auto_closing(&self) -> bool {
         self.auto_close.is_some()
     }
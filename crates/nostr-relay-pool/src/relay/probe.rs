@@ -0,0 +1,645 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Relay probe
+//!
+//! Check if a relay URL is reachable and answers to queries, without adding it to a pool
+//! (ex. to validate the URL in an "add relay" dialog).
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_utility::time;
+use async_wsocket::futures_util::{future, SinkExt, StreamExt};
+#[cfg(feature = "nip11")]
+use nostr::nips::nip11::RelayInformationDocument;
+use nostr::{
+    ClientMessage, Filter, JsonUtil, MachineReadablePrefix, RelayMessage, SubscriptionId,
+    Timestamp, Url,
+};
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+use tokio_socks::tcp::Socks5Stream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+use super::socks::SocksAuth;
+
+/// Default overall timeout
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Probe options
+#[derive(Debug, Clone)]
+pub struct ProbeOptions {
+    timeout: Duration,
+    proxy: Option<SocketAddr>,
+    proxy_auth: Option<SocksAuth>,
+    #[cfg(feature = "nip11")]
+    nip11: bool,
+}
+
+impl Default for ProbeOptions {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            proxy: None,
+            proxy_auth: None,
+            #[cfg(feature = "nip11")]
+            nip11: true,
+        }
+    }
+}
+
+impl ProbeOptions {
+    /// New default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overall timeout (default: 10 secs)
+    ///
+    /// The probe never takes longer than this, whatever the stage it's in.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set proxy (SOCKS5 without authentication)
+    pub fn proxy(mut self, proxy: Option<SocketAddr>) -> Self {
+        self.proxy = proxy;
+        self.proxy_auth = None;
+        self
+    }
+
+    /// Set SOCKS5 proxy with username/password authentication
+    ///
+    /// The authentication is used only for the relay connection, not for the `NIP11` document request.
+    pub fn proxy_with_auth(mut self, proxy: SocketAddr, auth: SocksAuth) -> Self {
+        self.proxy = Some(proxy);
+        self.proxy_auth = Some(auth);
+        self
+    }
+
+    /// Fetch the `NIP11` document (default: true)
+    #[cfg(feature = "nip11")]
+    pub fn nip11(mut self, nip11: bool) -> Self {
+        self.nip11 = nip11;
+        self
+    }
+}
+
+/// Probe failure
+///
+/// Every variant is a distinct stage of the probe.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ProbeFailure {
+    /// Not a `ws` or `wss` URL, or missing host
+    #[error("invalid relay url")]
+    InvalidUrl,
+    /// Impossible to connect through the proxy
+    #[error("proxy error: {0}")]
+    Proxy(String),
+    /// Impossible to open the TCP connection
+    #[error("impossible to connect: {0}")]
+    Connect(String),
+    /// TCP connection not established in time
+    #[error("connection timeout")]
+    ConnectTimeout,
+    /// TLS handshake failed (ex. expired or invalid certificate)
+    #[error("tls error: {0}")]
+    Tls(String),
+    /// WebSocket handshake failed (ex. not a websocket endpoint)
+    #[error("websocket error: {0}")]
+    WebSocket(String),
+    /// WebSocket handshake not completed in time
+    #[error("websocket handshake timeout")]
+    WebSocketTimeout,
+    /// Subscription closed by the relay (`CLOSED` message)
+    #[error("query closed by relay: {0}")]
+    QueryClosed(String),
+    /// Connection closed while waiting for the `EOSE`
+    #[error("connection closed during query")]
+    Disconnected,
+    /// `EOSE` not received in time
+    #[error("connects but never answers queries")]
+    NoEose,
+}
+
+/// TLS certificate details
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsCertificate {
+    /// Issuer (ex. `C=US, O=Let's Encrypt, CN=R3`)
+    pub issuer: String,
+    /// Subject
+    pub subject: String,
+    /// Start of validity
+    pub not_before: Timestamp,
+    /// Expiration
+    pub not_after: Timestamp,
+}
+
+impl TlsCertificate {
+    /// Check if the certificate is expired
+    pub fn is_expired(&self) -> bool {
+        self.not_after < Timestamp::now()
+    }
+}
+
+/// Probe report
+#[derive(Debug, Clone)]
+pub struct ProbeReport {
+    /// Relay url
+    pub url: Url,
+    /// TCP connection time (through the proxy, if set)
+    pub tcp_connect: Option<Duration>,
+    /// TLS and WebSocket handshake time
+    pub websocket_connect: Option<Duration>,
+    /// Time between the `REQ` and the `EOSE`
+    pub eose: Option<Duration>,
+    /// The relay sent an `AUTH` challenge or closed the query with `auth-required`
+    pub auth_required: bool,
+    /// Leaf TLS certificate (`wss` only)
+    pub certificate: Option<TlsCertificate>,
+    /// `NIP11` document
+    #[cfg(feature = "nip11")]
+    pub document: Option<RelayInformationDocument>,
+    /// `NIP11` document request error
+    ///
+    /// The document is optional, so this doesn't make the probe fail.
+    #[cfg(feature = "nip11")]
+    pub document_error: Option<String>,
+    /// First failed stage, if any
+    pub failure: Option<ProbeFailure>,
+}
+
+impl ProbeReport {
+    fn new(url: Url) -> Self {
+        Self {
+            url,
+            tcp_connect: None,
+            websocket_connect: None,
+            eose: None,
+            auth_required: false,
+            certificate: None,
+            #[cfg(feature = "nip11")]
+            document: None,
+            #[cfg(feature = "nip11")]
+            document_error: None,
+            failure: None,
+        }
+    }
+
+    /// Check if the relay is reachable and answered the query
+    pub fn is_ok(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Probe a relay
+pub(super) async fn probe(url: Url, opts: ProbeOptions) -> ProbeReport {
+    let deadline: Instant = Instant::now() + opts.timeout;
+    let mut report = ProbeReport::new(url.clone());
+
+    #[cfg(feature = "nip11")]
+    let nip11 = async {
+        if opts.nip11 {
+            let fut = RelayInformationDocument::get(url.clone(), opts.proxy);
+            Some(time::timeout(Some(remaining(deadline)), fut).await)
+        } else {
+            None
+        }
+    };
+    #[cfg(not(feature = "nip11"))]
+    let nip11 = future::ready(());
+
+    let (res, _document) =
+        future::join(connect_and_query(&url, &opts, deadline, &mut report), nip11).await;
+    report.failure = res.err();
+
+    #[cfg(feature = "nip11")]
+    match _document {
+        Some(Some(Ok(document))) => report.document = Some(document),
+        Some(Some(Err(e))) => report.document_error = Some(e.to_string()),
+        Some(None) => report.document_error = Some(String::from("timeout")),
+        None => {}
+    }
+
+    report
+}
+
+fn remaining(deadline: Instant) -> Duration {
+    deadline.saturating_duration_since(Instant::now())
+}
+
+async fn connect_and_query(
+    url: &Url,
+    opts: &ProbeOptions,
+    deadline: Instant,
+    report: &mut ProbeReport,
+) -> Result<(), ProbeFailure> {
+    let tls: bool = match url.scheme() {
+        "wss" => true,
+        "ws" => false,
+        _ => return Err(ProbeFailure::InvalidUrl),
+    };
+    let host: String = url.host_str().ok_or(ProbeFailure::InvalidUrl)?.to_string();
+    let port: u16 = url
+        .port_or_known_default()
+        .ok_or(ProbeFailure::InvalidUrl)?;
+
+    // TCP
+    let start = Instant::now();
+    let stream: TcpStream = time::timeout(Some(remaining(deadline)), async {
+        match (opts.proxy, &opts.proxy_auth) {
+            (Some(proxy), Some(auth)) => Socks5Stream::connect_with_password(
+                proxy,
+                (host.as_str(), port),
+                &auth.username,
+                &auth.password,
+            )
+            .await
+            .map(|s| s.into_inner())
+            .map_err(|e| ProbeFailure::Proxy(e.to_string())),
+            (Some(proxy), None) => Socks5Stream::connect(proxy, (host.as_str(), port))
+                .await
+                .map(|s| s.into_inner())
+                .map_err(|e| ProbeFailure::Proxy(e.to_string())),
+            (None, _) => TcpStream::connect((host.as_str(), port))
+                .await
+                .map_err(|e| ProbeFailure::Connect(e.to_string())),
+        }
+    })
+    .await
+    .ok_or(ProbeFailure::ConnectTimeout)??;
+    report.tcp_connect = Some(start.elapsed());
+
+    // TLS and WebSocket
+    let start = Instant::now();
+    if tls {
+        let stream = time::timeout(Some(remaining(deadline)), tls_connect(host, stream))
+            .await
+            .ok_or(ProbeFailure::WebSocketTimeout)??;
+        report.certificate = stream
+            .get_ref()
+            .1
+            .peer_certificates()
+            .and_then(|certs| certs.first())
+            .and_then(|cert| parse_certificate(cert.as_ref()));
+        let mut ws = websocket_connect(url, stream, deadline).await?;
+        report.websocket_connect = Some(start.elapsed());
+        query(&mut ws, deadline, report).await
+    } else {
+        let mut ws = websocket_connect(url, stream, deadline).await?;
+        report.websocket_connect = Some(start.elapsed());
+        query(&mut ws, deadline, report).await
+    }
+}
+
+async fn tls_connect(
+    host: String,
+    stream: TcpStream,
+) -> Result<tokio_rustls::client::TlsStream<TcpStream>, ProbeFailure> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let config = ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let connector = TlsConnector::from(Arc::new(config));
+    let domain = ServerName::try_from(host).map_err(|_| ProbeFailure::InvalidUrl)?;
+    connector
+        .connect(domain, stream)
+        .await
+        .map_err(|e| ProbeFailure::Tls(e.to_string()))
+}
+
+async fn websocket_connect<S>(
+    url: &Url,
+    stream: S,
+    deadline: Instant,
+) -> Result<WebSocketStream<S>, ProbeFailure>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (ws, _) = time::timeout(
+        Some(remaining(deadline)),
+        tokio_tungstenite::client_async(url.as_str(), stream),
+    )
+    .await
+    .ok_or(ProbeFailure::WebSocketTimeout)?
+    .map_err(|e| ProbeFailure::WebSocket(e.to_string()))?;
+    Ok(ws)
+}
+
+/// Send a `REQ` with `limit: 1` and wait for the `EOSE`
+async fn query<S>(
+    ws: &mut WebSocketStream<S>,
+    deadline: Instant,
+    report: &mut ProbeReport,
+) -> Result<(), ProbeFailure>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let id = SubscriptionId::generate();
+    let req = ClientMessage::req(id.clone(), vec![Filter::new().limit(1)]);
+    ws.send(Message::Text(req.as_json()))
+        .await
+        .map_err(|_| ProbeFailure::Disconnected)?;
+
+    let start = Instant::now();
+    loop {
+        let msg = time::timeout(Some(remaining(deadline)), ws.next())
+            .await
+            .ok_or(ProbeFailure::NoEose)?;
+        let json: String = match msg {
+            Some(Ok(Message::Text(json))) => json,
+            Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {
+                return Err(ProbeFailure::Disconnected)
+            }
+            Some(Ok(_)) => continue,
+        };
+
+        match RelayMessage::from_json(json) {
+            Ok(RelayMessage::EndOfStoredEvents(subscription_id)) if subscription_id == id => {
+                report.eose = Some(start.elapsed());
+                break;
+            }
+            Ok(RelayMessage::Auth { .. }) => report.auth_required = true,
+            Ok(RelayMessage::Closed {
+                subscription_id,
+                message,
+            }) if subscription_id == id => {
+                if MachineReadablePrefix::parse(&message)
+                    == Some(MachineReadablePrefix::AuthRequired)
+                {
+                    report.auth_required = true;
+                }
+                return Err(ProbeFailure::QueryClosed(message));
+            }
+            _ => {}
+        }
+    }
+
+    // Clean up, ignoring errors
+    let _ = ws
+        .send(Message::Text(ClientMessage::close(id).as_json()))
+        .await;
+    let _ = ws.close(None).await;
+
+    Ok(())
+}
+
+/// Read a DER TLV, returning the tag, the content and the rest of the input
+fn der_read(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (tag, input) = input.split_first()?;
+    let (first, input) = input.split_first()?;
+    let (len, input): (usize, &[u8]) = if *first < 0x80 {
+        (*first as usize, input)
+    } else {
+        let n: usize = (*first & 0x7F) as usize;
+        if n == 0 || n > 4 || input.len() < n {
+            return None;
+        }
+        let len = input[..n]
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        (len, &input[n..])
+    };
+    if input.len() < len {
+        return None;
+    }
+    Some((*tag, &input[..len], &input[len..]))
+}
+
+/// Format an X.509 `Name` (ex. `C=US, O=Let's Encrypt, CN=R3`)
+fn parse_name(name: &[u8]) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut rdns: &[u8] = name;
+    while let Some((_, set, rest)) = der_read(rdns) {
+        rdns = rest;
+        let mut attrs: &[u8] = set;
+        while let Some((_, attr, rest)) = der_read(attrs) {
+            attrs = rest;
+            if let Some((0x06, oid, value)) = der_read(attr) {
+                let label: &str = match oid {
+                    [0x55, 0x04, 0x03] => "CN",
+                    [0x55, 0x04, 0x06] => "C",
+                    [0x55, 0x04, 0x0A] => "O",
+                    [0x55, 0x04, 0x0B] => "OU",
+                    _ => continue,
+                };
+                if let Some((_, value, _)) = der_read(value) {
+                    parts.push(format!("{label}={}", String::from_utf8_lossy(value)));
+                }
+            }
+        }
+    }
+    parts.join(", ")
+}
+
+/// Parse `UTCTime` (`YYMMDDHHMMSSZ`) or `GeneralizedTime` (`YYYYMMDDHHMMSSZ`)
+fn parse_time(tag: u8, value: &[u8]) -> Option<Timestamp> {
+    let value: &str = core::str::from_utf8(value).ok()?.strip_suffix('Z')?;
+    let (year, rest): (i64, &str) = match (tag, value.len()) {
+        (0x17, 12) => {
+            let yy: i64 = value.get(..2)?.parse().ok()?;
+            (if yy >= 50 { 1900 + yy } else { 2000 + yy }, &value[2..])
+        }
+        (0x18, 14) => (value.get(..4)?.parse().ok()?, &value[4..]),
+        _ => return None,
+    };
+    let field = |i: usize| -> Option<i64> { rest.get(i..i + 2)?.parse().ok() };
+    let (month, day) = (field(0)?, field(2)?);
+    let (hour, minute, second) = (field(4)?, field(6)?, field(8)?);
+
+    // Days from civil (proleptic Gregorian calendar)
+    let y: i64 = if month <= 2 { year - 1 } else { year };
+    let era: i64 = y.div_euclid(400);
+    let yoe: i64 = y - era * 400;
+    let mp: i64 = (month + 9) % 12;
+    let doy: i64 = (153 * mp + 2) / 5 + day - 1;
+    let doe: i64 = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days: i64 = era * 146097 + doe - 719468;
+
+    let secs: i64 = days * 86400 + hour * 3600 + minute * 60 + second;
+    Some(Timestamp::from(u64::try_from(secs).ok()?))
+}
+
+/// Extract issuer, subject and validity from a DER X.509 certificate
+fn parse_certificate(der: &[u8]) -> Option<TlsCertificate> {
+    let (_, cert, _) = der_read(der)?;
+    let (_, tbs, _) = der_read(cert)?;
+
+    let (tag, _, mut rest) = der_read(tbs)?;
+    // Skip optional version
+    if tag == 0xA0 {
+        let (_, _, r) = der_read(rest)?; // Serial number
+        rest = r;
+    }
+    let (_, _, rest) = der_read(rest)?; // Signature algorithm
+    let (_, issuer, rest) = der_read(rest)?;
+    let (_, validity, rest) = der_read(rest)?;
+    let (_, subject, _) = der_read(rest)?;
+
+    let (tag, not_before, validity) = der_read(validity)?;
+    let not_before: Timestamp = parse_time(tag, not_before)?;
+    let (tag, not_after, _) = der_read(validity)?;
+    let not_after: Timestamp = parse_time(tag, not_after)?;
+
+    Some(TlsCertificate {
+        issuer: parse_name(issuer),
+        subject: parse_name(subject),
+        not_before,
+        not_after,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Self-signed `relay.example.com` certificate
+    const CERT: &str = concat!(
+        "308201c53082016aa003020102020101300a06082a8648ce3d0403023041310b300906035504061302555331",
+        "163014060355040a0c0d4e6f7374722054657374204341311a301806035504030c1172656c61792e6578616d",
+        "706c652e636f6d301e170d3236313031373139323631335a170d3236313031383139323631335a3041310b30",
+        "0906035504061302555331163014060355040a0c0d4e6f7374722054657374204341311a301806035504030c",
+        "1172656c61792e6578616d706c652e636f6d3059301306072a8648ce3d020106082a8648ce3d030107034200",
+        "04bb9a28d6492874474696f01b2ff75cdfa74f3d764ec73ad1b7eb3de846b987e02b6dc456485b764a0ae49f",
+        "0fe6ea84ccfd97c5b31ba21a7b136f0ec8bad9a1b3a3533051301d0603551d0e04160414a11564e08368b850",
+        "d014172de7427728be78d295301f0603551d23041830168014a11564e08368b850d014172de7427728be78d2",
+        "95300f0603551d130101ff040530030101ff300a06082a8648ce3d0403020349003046022100f414acc9308b",
+        "8ad831bb45d60dbbaac3be1e3f0248cfd121957bcc1064bab418022100a8a14b33879179cbdfe568a27e9b05",
+        "8e08db154b6418cfdaf571380149ded836",
+    );
+
+    /// Minimal relay: answer to the `REQ` with `responses`, or ignore it if empty
+    async fn spawn_relay(responses: Vec<String>) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let responses = responses.clone();
+                tokio::spawn(async move {
+                    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    while let Some(Ok(msg)) = ws.next().await {
+                        if let Message::Text(json) = msg {
+                            if let Ok(ClientMessage::Req {
+                                subscription_id, ..
+                            }) = ClientMessage::from_json(json)
+                            {
+                                for res in responses.iter() {
+                                    let res = res.replace("{id}", &subscription_id.to_string());
+                                    ws.send(Message::Text(res)).await.unwrap();
+                                }
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        Url::parse(&format!("ws://{addr}")).unwrap()
+    }
+
+    fn opts() -> ProbeOptions {
+        let opts = ProbeOptions::new().timeout(Duration::from_secs(2));
+        #[cfg(feature = "nip11")]
+        let opts = opts.nip11(false);
+        opts
+    }
+
+    #[tokio::test]
+    async fn test_probe() {
+        let url = spawn_relay(vec![String::from(r#"["EOSE","{id}"]"#)]).await;
+        let report = probe(url, opts()).await;
+        assert!(report.is_ok());
+        assert!(report.tcp_connect.is_some());
+        assert!(report.websocket_connect.is_some());
+        assert!(report.eose.is_some());
+        assert!(!report.auth_required);
+        assert!(report.certificate.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_probe_no_eose() {
+        let url = spawn_relay(Vec::new()).await;
+        let report = probe(url, opts().timeout(Duration::from_millis(500))).await;
+        assert_eq!(report.failure, Some(ProbeFailure::NoEose));
+        assert!(report.websocket_connect.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_probe_auth_required() {
+        let url = spawn_relay(vec![
+            String::from(r#"["AUTH","challenge"]"#),
+            String::from(r#"["CLOSED","{id}","auth-required: we only serve members"]"#),
+        ])
+        .await;
+        let report = probe(url, opts()).await;
+        assert!(report.auth_required);
+        assert_eq!(
+            report.failure,
+            Some(ProbeFailure::QueryClosed(String::from(
+                "auth-required: we only serve members"
+            )))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_probe_connect_failures() {
+        let url = Url::parse("https://relay.example.com").unwrap();
+        let report = probe(url, opts()).await;
+        assert_eq!(report.failure, Some(ProbeFailure::InvalidUrl));
+
+        // Closed port
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        let url = Url::parse(&format!("ws://{addr}")).unwrap();
+        let report = probe(url, opts()).await;
+        assert!(matches!(report.failure, Some(ProbeFailure::Connect(_))));
+
+        // Not a websocket
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            drop(stream);
+        });
+        let url = Url::parse(&format!("ws://{addr}")).unwrap();
+        let report = probe(url, opts()).await;
+        assert!(matches!(report.failure, Some(ProbeFailure::WebSocket(_))));
+    }
+
+    #[test]
+    fn test_parse_certificate() {
+        let der: Vec<u8> = (0..CERT.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&CERT[i..i + 2], 16).unwrap())
+            .collect();
+        let cert = parse_certificate(&der).unwrap();
+        assert_eq!(cert.issuer, "C=US, O=Nostr Test CA, CN=relay.example.com");
+        assert_eq!(cert.subject, cert.issuer);
+        assert_eq!(cert.not_before, Timestamp::from(1792265173));
+        assert_eq!(cert.not_after, Timestamp::from(1792351573));
+
+        assert!(parse_certificate(&der[..100]).is_none());
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time(0x17, b"700101000000Z"), Some(Timestamp::from(0)));
+        assert_eq!(
+            parse_time(0x18, b"20240229120000Z"),
+            Some(Timestamp::from(1709208000))
+        );
+        assert_eq!(parse_time(0x17, b"2402291200Z"), None);
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! SOCKS5 proxy with authentication
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use async_utility::time;
+use async_wsocket::futures_util::StreamExt;
+use async_wsocket::{Sink, Stream};
+use nostr::Url;
+use thiserror::Error;
+use tokio_socks::tcp::Socks5Stream;
+
+/// Default connection timeout
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// SOCKS5 username/password authentication (RFC 1929)
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct SocksAuth {
+    /// Username
+    pub username: String,
+    /// Password
+    pub password: String,
+}
+
+impl fmt::Debug for SocksAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SocksAuth")
+            .field("username", &self.username)
+            .field("password", &"***")
+            .finish()
+    }
+}
+
+impl SocksAuth {
+    /// New SOCKS5 credentials
+    pub fn new<S1, S2>(username: S1, password: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        Self {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub(super) enum Error {
+    #[error(transparent)]
+    Socks(#[from] tokio_socks::Error),
+    #[error(transparent)]
+    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    #[error("invalid url: missing host or port")]
+    InvalidUrl,
+    #[error("timeout")]
+    Timeout,
+}
+
+/// Connect to the relay through a SOCKS5 proxy requiring username/password authentication
+pub(super) async fn connect(
+    url: &Url,
+    proxy: SocketAddr,
+    auth: &SocksAuth,
+    timeout: Option<Duration>,
+) -> Result<(Sink, Stream), Error> {
+    let host: &str = url.host_str().ok_or(Error::InvalidUrl)?;
+    let port: u16 = url.port_or_known_default().ok_or(Error::InvalidUrl)?;
+
+    let timeout: Duration = timeout.unwrap_or(DEFAULT_TIMEOUT);
+    time::timeout(Some(timeout), async {
+        let stream = Socks5Stream::connect_with_password(
+            proxy,
+            (host, port),
+            &auth.username,
+            &auth.password,
+        )
+        .await?;
+        let (ws, _) =
+            tokio_tungstenite::client_async_tls(url.as_str(), stream.into_inner()).await?;
+        Ok(ws.split())
+    })
+    .await
+    .ok_or(Error::Timeout)?
+}
+
+#[cfg(test)]
+mod tests {
+    use async_wsocket::futures_util::SinkExt;
+    use async_wsocket::WsMessage;
+    use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    use super::*;
+
+    const USERNAME: &str = "user";
+    const PASSWORD: &str = "secret";
+
+    /// Minimal SOCKS5 proxy supporting only the username/password method and `CONNECT` to IPv4/domain
+    async fn handle_socks(mut client: TcpStream) -> io::Result<()> {
+        // Greeting
+        let mut buf = [0u8; 2];
+        client.read_exact(&mut buf).await?;
+        let mut methods = vec![0u8; buf[1] as usize];
+        client.read_exact(&mut methods).await?;
+        if !methods.contains(&0x02) {
+            client.write_all(&[0x05, 0xFF]).await?;
+            return Ok(());
+        }
+        client.write_all(&[0x05, 0x02]).await?;
+
+        // RFC 1929 sub-negotiation
+        let ver = client.read_u8().await?;
+        if ver != 0x01 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported auth version: {ver:#04x}"),
+            ));
+        }
+        let mut username = vec![0u8; client.read_u8().await? as usize];
+        client.read_exact(&mut username).await?;
+        let mut password = vec![0u8; client.read_u8().await? as usize];
+        client.read_exact(&mut password).await?;
+        if username != USERNAME.as_bytes() || password != PASSWORD.as_bytes() {
+            client.write_all(&[0x01, 0x01]).await?;
+            return Ok(());
+        }
+        client.write_all(&[0x01, 0x00]).await?;
+
+        // Request
+        let mut buf = [0u8; 4];
+        client.read_exact(&mut buf).await?;
+        let host: String = match buf[3] {
+            0x01 => {
+                let mut ip = [0u8; 4];
+                client.read_exact(&mut ip).await?;
+                std::net::Ipv4Addr::from(ip).to_string()
+            }
+            0x03 => {
+                let mut domain = vec![0u8; client.read_u8().await? as usize];
+                client.read_exact(&mut domain).await?;
+                String::from_utf8_lossy(&domain).to_string()
+            }
+            atyp => {
+                // Address type not supported
+                client
+                    .write_all(&[0x05, 0x08, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
+                    .await?;
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("unsupported address type: {atyp:#04x}"),
+                ));
+            }
+        };
+        let port = client.read_u16().await?;
+
+        let mut target = TcpStream::connect((host.as_str(), port)).await?;
+        client
+            .write_all(&[0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
+            .await?;
+        io::copy_bidirectional(&mut client, &mut target).await?;
+        Ok(())
+    }
+
+    async fn spawn_proxy() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle_socks(stream));
+            }
+        });
+        addr
+    }
+
+    /// Echo websocket server
+    async fn spawn_relay() -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    while let Some(Ok(msg)) = ws.next().await {
+                        if msg.is_text() {
+                            ws.send(msg).await.unwrap();
+                        }
+                    }
+                });
+            }
+        });
+        Url::parse(&format!("ws://{addr}")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_socks5_auth() {
+        let proxy = spawn_proxy().await;
+        let url = spawn_relay().await;
+        let timeout = Some(Duration::from_secs(5));
+
+        let auth = SocksAuth::new(USERNAME, PASSWORD);
+        let (mut tx, mut rx) = connect(&url, proxy, &auth, timeout).await.unwrap();
+        tx.send(WsMessage::Text(String::from("hello")))
+            .await
+            .unwrap();
+        let msg = rx.next().await.unwrap().unwrap();
+        assert_eq!(msg, WsMessage::Text(String::from("hello")));
+
+        // Wrong credentials
+        let auth = SocksAuth::new(USERNAME, "wrong");
+        assert!(matches!(
+            connect(&url, proxy, &auth, timeout).await,
+            Err(Error::Socks(_))
+        ));
+    }
+}
@@ -87,6 +87,7 @@ pub struct RelayConnectionStats {
     bytes_received: Arc<AtomicUsize>,
     connected_at: Arc<AtomicU64>,
     first_connection_timestamp: Arc<AtomicU64>,
+    violations: Arc<AtomicUsize>,
     #[cfg(not(target_arch = "wasm32"))]
     latencies: Arc<RwLock<VecDeque<Duration>>>,
     #[cfg(not(target_arch = "wasm32"))]
@@ -109,6 +110,7 @@ impl RelayConnectionStats {
             bytes_received: Arc::new(AtomicUsize::new(0)),
             connected_at: Arc::new(AtomicU64::new(0)),
             first_connection_timestamp: Arc::new(AtomicU64::new(0)),
+            violations: Arc::new(AtomicUsize::new(0)),
             #[cfg(not(target_arch = "wasm32"))]
             latencies: Arc::new(RwLock::new(VecDeque::new())),
             #[cfg(not(target_arch = "wasm32"))]
@@ -157,6 +159,11 @@ impl RelayConnectionStats {
         Timestamp::from(self.first_connection_timestamp.load(Ordering::SeqCst))
     }
 
+    /// Number of flagged unsolicited events
+    pub fn violations(&self) -> usize {
+        self.violations.load(Ordering::SeqCst)
+    }
+
     /// Calculate latency
     #[cfg(not(target_arch = "wasm32"))]
     pub async fn latency(&self) -> Option<Duration> {
@@ -181,6 +188,11 @@ impl RelayConnectionStats {
         }
     }
 
+    /// Increase violations counter and return the new value
+    pub(crate) fn new_violation(&self) -> usize {
+        self.violations.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
     pub(crate) fn add_bytes_sent(&self, size: usize) {
         self.bytes_sent.fetch_add(size, Ordering::SeqCst);
     }
@@ -0,0 +1,25 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Relay subscription
+
+use nostr::Timestamp;
+
+/// Subscription activity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubscriptionActivity {
+    /// When the subscription was created
+    pub subscribed_at: Timestamp,
+    /// When the last event was received for this subscription
+    pub last_event_at: Option<Timestamp>,
+}
+
+impl SubscriptionActivity {
+    /// Last time the subscription was active
+    ///
+    /// Return the timestamp of the last received event or, if no event has been received yet, the subscription timestamp.
+    pub fn last_active_at(&self) -> Timestamp {
+        self.last_event_at.unwrap_or(self.subscribed_at)
+    }
+}
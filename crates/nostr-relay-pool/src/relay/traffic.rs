@@ -0,0 +1,400 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Subscription traffic stats
+
+use std::collections::{BTreeMap, HashMap};
+use std::mem;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use lru::LruCache;
+
+use nostr::serde_json::{json, Map, Value};
+use nostr::{Kind, SubscriptionId};
+
+#[derive(Debug, Default)]
+struct Counters {
+    received: AtomicU64,
+    bytes: AtomicU64,
+    duplicates: AtomicU64,
+    dropped_pow: AtomicU64,
+    dropped_policy: AtomicU64,
+    delivered: AtomicU64,
+    kinds: RwLock<HashMap<u64, AtomicU64>>,
+}
+
+impl Counters {
+    fn add_kind(&self, kind: u64) {
+        let kinds = self.kinds.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(counter) = kinds.get(&kind) {
+            counter.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        drop(kinds);
+
+        let mut kinds = self.kinds.write().unwrap_or_else(|e| e.into_inner());
+        kinds
+            .entry(kind)
+            .or_default()
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> SubscriptionStats {
+        let kinds = self.kinds.read().unwrap_or_else(|e| e.into_inner());
+        SubscriptionStats {
+            received: self.received.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            duplicates: self.duplicates.load(Ordering::Relaxed),
+            dropped_pow: self.dropped_pow.load(Ordering::Relaxed),
+            dropped_policy: self.dropped_policy.load(Ordering::Relaxed),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            kinds: kinds
+                .iter()
+                .map(|(kind, counter)| (Kind::from(*kind), counter.load(Ordering::Relaxed)))
+                .collect(),
+        }
+    }
+}
+
+/// Per-subscription counters of the incoming events
+///
+/// The counters are kept also after the subscription is closed:
+/// when full, the counters of the oldest subscription are dropped.
+#[derive(Debug, Clone)]
+pub(crate) struct TrafficMeter {
+    subscriptions: Arc<RwLock<LruCache<SubscriptionId, Arc<Counters>>>>,
+}
+
+impl TrafficMeter {
+    pub fn new(capacity: usize) -> Self {
+        let capacity: NonZeroUsize =
+            NonZeroUsize::new(capacity.max(1)).expect("Capacity must not be zero");
+        Self {
+            subscriptions: Arc::new(RwLock::new(LruCache::new(capacity))),
+        }
+    }
+
+    fn counters(&self, id: &SubscriptionId) -> Arc<Counters> {
+        let subscriptions = self.subscriptions.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(counters) = subscriptions.peek(id) {
+            return counters.clone();
+        }
+        drop(subscriptions);
+
+        let mut subscriptions = self
+            .subscriptions
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        subscriptions
+            .get_or_insert(id.clone(), Arc::default)
+            .clone()
+    }
+
+    /// Event received (before any check)
+    pub fn received(&self, id: &SubscriptionId, kind: u64, bytes: usize) {
+        let counters = self.counters(id);
+        counters.received.fetch_add(1, Ordering::Relaxed);
+        counters.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+        counters.add_kind(kind);
+    }
+
+    /// Event already received (from this or another subscription/relay)
+    pub fn duplicate(&self, id: &SubscriptionId) {
+        self.counters(id).duplicates.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Event dropped for insufficient POW
+    pub fn dropped_pow(&self, id: &SubscriptionId) {
+        self.counters(id)
+            .dropped_pow
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Event dropped by a policy (unsolicited, deleted, expired, invalid, not matching filters, ...)
+    pub fn dropped_policy(&self, id: &SubscriptionId) {
+        self.counters(id)
+            .dropped_policy
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Event delivered to the notification consumers
+    pub fn delivered(&self, id: &SubscriptionId) {
+        self.counters(id).delivered.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Event already delivered and then dropped by a consumer policy (ex. spam detector)
+    pub fn undelivered(&self, id: &SubscriptionId) {
+        let counters = self.counters(id);
+        let _ = counters
+            .delivered
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+        counters.dropped_policy.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self, id: &SubscriptionId) -> Option<SubscriptionStats> {
+        let subscriptions = self.subscriptions.read().unwrap_or_else(|e| e.into_inner());
+        subscriptions.peek(id).map(|c| c.snapshot())
+    }
+
+    /// Number of tracked subscriptions, max number of tracked subscriptions and approximate size, in bytes
+    pub fn usage(&self) -> (usize, usize, usize) {
+        let subscriptions = self.subscriptions.read().unwrap_or_else(|e| e.into_inner());
+        let bytes: usize = subscriptions
+            .iter()
+            .map(|(id, c)| {
+                let kinds: usize = c.kinds.read().unwrap_or_else(|e| e.into_inner()).len();
+                mem::size_of::<Counters>()
+                    + id.to_string().len()
+                    + kinds * mem::size_of::<(u64, AtomicU64)>()
+            })
+            .sum();
+        (subscriptions.len(), subscriptions.cap().get(), bytes)
+    }
+
+    pub fn all(&self) -> HashMap<SubscriptionId, SubscriptionStats> {
+        let subscriptions = self.subscriptions.read().unwrap_or_else(|e| e.into_inner());
+        subscriptions
+            .iter()
+            .map(|(id, c)| (id.clone(), c.snapshot()))
+            .collect()
+    }
+
+    pub fn reset(&self, id: Option<&SubscriptionId>) {
+        let mut subscriptions = self
+            .subscriptions
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        match id {
+            Some(id) => {
+                subscriptions.pop(id);
+            }
+            None => subscriptions.clear(),
+        }
+    }
+}
+
+fn percent(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+/// Subscription stats
+///
+/// Snapshot of the counters of the incoming events.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubscriptionStats {
+    /// Events received
+    pub received: u64,
+    /// Bytes received (events only)
+    pub bytes: u64,
+    /// Events already received (from another subscription or relay)
+    pub duplicates: u64,
+    /// Events dropped for insufficient POW
+    pub dropped_pow: u64,
+    /// Events dropped by a policy (unsolicited, deleted, expired, invalid, not matching filters, spam, ...)
+    pub dropped_policy: u64,
+    /// Events delivered to the notification consumers
+    pub delivered: u64,
+    /// Events received per kind
+    pub kinds: BTreeMap<Kind, u64>,
+}
+
+impl SubscriptionStats {
+    /// Add the counters of `other` to these
+    pub fn merge(&mut self, other: &Self) {
+        self.received += other.received;
+        self.bytes += other.bytes;
+        self.duplicates += other.duplicates;
+        self.dropped_pow += other.dropped_pow;
+        self.dropped_policy += other.dropped_policy;
+        self.delivered += other.delivered;
+        for (kind, count) in other.kinds.iter() {
+            *self.kinds.entry(*kind).or_default() += count;
+        }
+    }
+
+    /// Percentage of the received events delivered to consumers
+    pub fn delivered_percent(&self) -> f64 {
+        percent(self.delivered, self.received)
+    }
+
+    /// Percentage of the received events that were duplicates
+    pub fn duplicates_percent(&self) -> f64 {
+        percent(self.duplicates, self.received)
+    }
+
+    /// Percentage of the received events dropped (POW or policy)
+    pub fn dropped_percent(&self) -> f64 {
+        percent(self.dropped_pow + self.dropped_policy, self.received)
+    }
+
+    /// Percentage of the received events of a kind
+    pub fn kind_percent(&self, kind: &Kind) -> f64 {
+        percent(
+            self.kinds.get(kind).copied().unwrap_or_default(),
+            self.received,
+        )
+    }
+
+    fn to_value(&self) -> Value {
+        let kinds: Map<String, Value> = self
+            .kinds
+            .iter()
+            .map(|(kind, count)| {
+                (
+                    kind.as_u64().to_string(),
+                    json!({ "count": count, "percent": self.kind_percent(kind) }),
+                )
+            })
+            .collect();
+        json!({
+            "received": self.received,
+            "bytes": self.bytes,
+            "duplicates": self.duplicates,
+            "dropped_pow": self.dropped_pow,
+            "dropped_policy": self.dropped_policy,
+            "delivered": self.delivered,
+            "delivered_percent": self.delivered_percent(),
+            "duplicates_percent": self.duplicates_percent(),
+            "dropped_percent": self.dropped_percent(),
+            "kinds": kinds,
+        })
+    }
+
+    /// Serialize as JSON
+    pub fn as_json(&self) -> String {
+        self.to_value().to_string()
+    }
+}
+
+/// Traffic report of all the subscriptions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrafficReport {
+    /// Stats per subscription
+    pub subscriptions: HashMap<SubscriptionId, SubscriptionStats>,
+}
+
+impl TrafficReport {
+    /// Sum of the stats of all the subscriptions
+    pub fn total(&self) -> SubscriptionStats {
+        let mut total = SubscriptionStats::default();
+        for stats in self.subscriptions.values() {
+            total.merge(stats);
+        }
+        total
+    }
+
+    /// Percentage of the total received events received by a subscription
+    pub fn share_percent(&self, id: &SubscriptionId) -> f64 {
+        let received: u64 = self
+            .subscriptions
+            .get(id)
+            .map(|s| s.received)
+            .unwrap_or_default();
+        percent(received, self.total().received)
+    }
+
+    /// Serialize as JSON
+    pub fn as_json(&self) -> String {
+        let total: SubscriptionStats = self.total();
+        let subscriptions: Map<String, Value> = self
+            .subscriptions
+            .iter()
+            .map(|(id, stats)| {
+                let mut value: Value = stats.to_value();
+                value["share_percent"] = json!(percent(stats.received, total.received));
+                (id.to_string(), value)
+            })
+            .collect();
+        json!({
+            "total": total.to_value(),
+            "subscriptions": subscriptions,
+        })
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traffic_meter() {
+        let meter = TrafficMeter::new(100);
+        let id = SubscriptionId::new("feed");
+
+        meter.received(&id, 1, 100);
+        meter.received(&id, 1, 100);
+        meter.received(&id, 7, 50);
+        meter.received(&id, 3, 50);
+        meter.delivered(&id);
+        meter.delivered(&id);
+        meter.duplicate(&id);
+        meter.dropped_pow(&id);
+        meter.undelivered(&id);
+
+        let stats = meter.stats(&id).unwrap();
+        assert_eq!(stats.received, 4);
+        assert_eq!(stats.bytes, 300);
+        assert_eq!(stats.delivered, 1);
+        assert_eq!(stats.duplicates, 1);
+        assert_eq!(stats.dropped_pow, 1);
+        assert_eq!(stats.dropped_policy, 1);
+        assert_eq!(stats.kinds.get(&Kind::TextNote), Some(&2));
+        assert_eq!(stats.delivered_percent(), 25.0);
+        assert_eq!(stats.kind_percent(&Kind::TextNote), 50.0);
+
+        meter.reset(Some(&id));
+        assert!(meter.stats(&id).is_none());
+    }
+
+    #[test]
+    fn test_traffic_report() {
+        let meter = TrafficMeter::new(100);
+        let a = SubscriptionId::new("a");
+        let b = SubscriptionId::new("b");
+        for _ in 0..3 {
+            meter.received(&a, 1, 10);
+        }
+        meter.received(&b, 0, 10);
+
+        let report = TrafficReport {
+            subscriptions: meter.all(),
+        };
+        assert_eq!(report.total().received, 4);
+        assert_eq!(report.share_percent(&a), 75.0);
+
+        let json: Value = nostr::serde_json::from_str(&report.as_json()).unwrap();
+        assert_eq!(json["total"]["received"], 4);
+        assert_eq!(json["subscriptions"]["a"]["share_percent"], 75.0);
+        assert_eq!(json["subscriptions"]["b"]["kinds"]["0"]["count"], 1);
+    }
+
+    #[test]
+    fn test_traffic_meter_capacity() {
+        let meter = TrafficMeter::new(2);
+        let a = SubscriptionId::new("a");
+        let b = SubscriptionId::new("b");
+        let c = SubscriptionId::new("c");
+
+        meter.received(&a, 1, 10);
+        meter.received(&b, 1, 10);
+        meter.received(&a, 1, 10);
+        meter.received(&c, 1, 10);
+
+        // Oldest subscription dropped, also if more recently updated
+        assert!(meter.stats(&a).is_none());
+        assert_eq!(meter.stats(&b).unwrap().received, 1);
+        assert_eq!(meter.stats(&c).unwrap().received, 1);
+
+        let (entries, limit, ..) = meter.usage();
+        assert_eq!(entries, 2);
+        assert_eq!(limit, 2);
+    }
+}
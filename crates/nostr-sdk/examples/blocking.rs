@@ -0,0 +1,37 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+use std::time::Duration;
+
+use nostr_sdk::blocking::Client;
+use nostr_sdk::prelude::*;
+
+const BECH32_SK: &str = "nsec1ufnus6pju578ste3v90xd5m2decpuzpql2295m3sknqcjzyys9ls0qlc85";
+
+fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let secret_key = SecretKey::from_bech32(BECH32_SK)?;
+    let my_keys = Keys::new(secret_key);
+
+    let client = Client::new(&my_keys);
+    client.add_relay("wss://relay.damus.io")?;
+    client.add_relay("wss://nos.lol")?;
+
+    client.connect();
+
+    // Publish a text note
+    let event_id: EventId =
+        client.send_event_builder(EventBuilder::text_note("Hello from a blocking client", []))?;
+    println!("Published note: {event_id}");
+
+    // Fetch replies
+    let filter = Filter::new().kind(Kind::TextNote).event(event_id);
+    let replies: Vec<Event> = client.fetch_events(vec![filter], Some(Duration::from_secs(10)))?;
+    for reply in replies.into_iter() {
+        println!("{}: {}", reply.author(), reply.content());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+use axum::extract::Request;
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::Response;
+use axum::routing::get;
+use axum::{Extension, Router};
+use nostr_sdk::prelude::*;
+
+const BASE_URL: &str = "http://127.0.0.1:3000";
+const MAX_AGE: u64 = 60;
+
+/// Verify the NIP98 `Authorization` header and store the author in the request extensions
+async fn nip98_auth(mut req: Request, next: Next) -> Result<Response, StatusCode> {
+    let header: &str = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let path: &str = req
+        .uri()
+        .path_and_query()
+        .map(|p| p.as_str())
+        .unwrap_or("/");
+    let url: Url = Url::parse(BASE_URL)
+        .and_then(|base| base.join(path))
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let public_key: PublicKey =
+        nip98::verify_auth_header(header, &url, req.method().as_str(), MAX_AGE)
+            .map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+    req.extensions_mut().insert(public_key);
+    Ok(next.run(req).await)
+}
+
+async fn whoami(Extension(public_key): Extension<PublicKey>) -> String {
+    public_key.to_bech32().unwrap_or_default()
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    // Client side: build the header to send with the request
+    let keys = Keys::generate();
+    let url = Url::parse(&format!("{BASE_URL}/whoami"))?;
+    let event: Event = nip98::create_auth_event(&url, "GET", None, &keys)?;
+    println!("Authorization: {}", nip98::auth_header(&event));
+
+    // Server side
+    let app = Router::new()
+        .route("/whoami", get(whoami))
+        .layer(middleware::from_fn(nip98_auth));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await?;
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
@@ -0,0 +1,266 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Blocking client
+//!
+//! Synchronous facade over the async [`Client`](crate::Client), for consumers without an async runtime.
+//!
+//! **The methods must not be called from an async context** (they would panic).
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use nostr::{Event, EventBuilder, EventId, Filter, SubscriptionId, TryIntoUrl};
+use nostr_relay_pool::pool;
+use nostr_relay_pool::RelayPoolNotification;
+use nostr_signer::NostrSigner;
+use tokio::runtime::{Builder, Handle, Runtime};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::client::{self, Error};
+use crate::{ClientBuilder, Options};
+
+#[derive(Debug)]
+struct InnerClient {
+    client: Option<client::Client>,
+    runtime: Option<Runtime>,
+}
+
+impl InnerClient {
+    fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        match &self.runtime {
+            Some(runtime) => runtime.block_on(future),
+            None => unreachable!("runtime is available until drop"),
+        }
+    }
+
+    fn client(&self) -> &client::Client {
+        match &self.client {
+            Some(client) => client,
+            None => unreachable!("client is available until drop"),
+        }
+    }
+}
+
+/// Shutdown the client while the runtime is still alive, then the runtime
+fn shutdown(runtime: Runtime, client: Option<client::Client>) {
+    if let Some(client) = client {
+        if let Err(e) = runtime.block_on(client.shutdown()) {
+            tracing::error!("Impossible to shutdown client: {e}");
+        }
+    }
+    runtime.shutdown_timeout(Duration::from_secs(1));
+}
+
+impl Drop for InnerClient {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            let client: Option<client::Client> = self.client.take();
+            match Handle::try_current() {
+                // Dropped in an async context: blocking would panic, shutdown in another thread
+                Ok(..) => {
+                    let _ = std::thread::spawn(move || shutdown(runtime, client));
+                }
+                Err(..) => shutdown(runtime, client),
+            }
+        }
+    }
+}
+
+/// Blocking Nostr client
+///
+/// Own an internal runtime, shared by all the clones of the client:
+/// the client and the runtime are shut down when the last clone is dropped (also from an async context).
+/// Can be safely used from multiple threads.
+#[derive(Debug, Clone)]
+pub struct Client {
+    inner: Arc<InnerClient>,
+}
+
+impl Client {
+    /// Create a new blocking [`Client`] with signer
+    pub fn new<S>(signer: S) -> Self
+    where
+        S: Into<NostrSigner>,
+    {
+        Self::with_opts(signer, Options::default())
+    }
+
+    /// Create a new blocking [`Client`] with [`Options`]
+    pub fn with_opts<S>(signer: S, opts: Options) -> Self
+    where
+        S: Into<NostrSigner>,
+    {
+        Self::from_builder(ClientBuilder::new().signer(signer).opts(opts))
+    }
+
+    /// Compose blocking [`Client`] from [`ClientBuilder`]
+    pub fn from_builder(builder: ClientBuilder) -> Self {
+        let runtime: Runtime = Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .expect("Can't start Tokio runtime");
+
+        // The client must be built inside the runtime
+        let client: client::Client = {
+            let _guard = runtime.enter();
+            builder.build()
+        };
+
+        Self {
+            inner: Arc::new(InnerClient {
+                client: Some(client),
+                runtime: Some(runtime),
+            }),
+        }
+    }
+
+    /// Add new relay
+    ///
+    /// Return `false` if the relay already exists.
+    pub fn add_relay<U>(&self, url: U) -> Result<bool, Error>
+    where
+        U: TryIntoUrl,
+        pool::Error: From<<U as TryIntoUrl>::Err>,
+    {
+        self.inner.block_on(self.inner.client().add_relay(url))
+    }
+
+    /// Disconnect and remove relay
+    pub fn remove_relay<U>(&self, url: U) -> Result<(), Error>
+    where
+        U: TryIntoUrl,
+        pool::Error: From<<U as TryIntoUrl>::Err>,
+    {
+        self.inner.block_on(self.inner.client().remove_relay(url))
+    }
+
+    /// Connect relays
+    pub fn connect(&self) {
+        self.inner.block_on(self.inner.client().connect())
+    }
+
+    /// Disconnect relays
+    pub fn disconnect(&self) -> Result<(), Error> {
+        self.inner.block_on(self.inner.client().disconnect())
+    }
+
+    /// Send event
+    pub fn send_event(&self, event: Event) -> Result<EventId, Error> {
+        self.inner.block_on(self.inner.client().send_event(event))
+    }
+
+    /// Sign and send event
+    pub fn send_event_builder(&self, builder: EventBuilder) -> Result<EventId, Error> {
+        self.inner
+            .block_on(self.inner.client().send_event_builder(builder))
+    }
+
+    /// Fetch events of filters from **all relays**
+    ///
+    /// If timeout is set to `None`, the default from [`Options`] will be used.
+    pub fn fetch_events(
+        &self,
+        filters: Vec<Filter>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Event>, Error> {
+        self.inner
+            .block_on(self.inner.client().get_events_of(filters, timeout))
+    }
+
+    /// Subscribe to filters
+    ///
+    /// Events are received through [`Client::notifications`].
+    pub fn subscribe(&self, filters: Vec<Filter>) -> SubscriptionId {
+        self.inner
+            .block_on(self.inner.client().subscribe(filters, None))
+    }
+
+    /// Unsubscribe
+    pub fn unsubscribe(&self, id: SubscriptionId) {
+        self.inner.block_on(self.inner.client().unsubscribe(id))
+    }
+
+    /// Get new notification listener
+    pub fn notifications(&self) -> Notifications {
+        Notifications {
+            receiver: self.inner.client().notifications(),
+        }
+    }
+}
+
+/// Blocking iterator over [`RelayPoolNotification`]s
+///
+/// Stop when the client is shut down.
+#[derive(Debug)]
+pub struct Notifications {
+    receiver: broadcast::Receiver<RelayPoolNotification>,
+}
+
+impl Iterator for Notifications {
+    type Item = RelayPoolNotification;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.receiver.blocking_recv() {
+                Ok(RelayPoolNotification::Shutdown) => return None,
+                Ok(notification) => return Some(notification),
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Notifications listener lagged: {skipped} skipped");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::Keys;
+
+    use super::*;
+
+    #[test]
+    fn test_drop_outside_runtime() {
+        let client = Client::new(Keys::generate());
+        let other = client.clone();
+        drop(client);
+
+        // Clones keep working
+        assert!(other.add_relay("wss://relay.example.com").unwrap());
+        let notifications = other.notifications();
+        drop(other);
+
+        // Shut down on last drop: the iterator must stop
+        let _ = notifications.count();
+    }
+
+    #[test]
+    fn test_runtime_shutdown_on_drop() {
+        let client = Client::new(Keys::generate());
+        let handle: Handle = client.inner.runtime.as_ref().unwrap().handle().clone();
+        drop(client);
+
+        // Tasks spawned on a shut down runtime are cancelled
+        let rt = Builder::new_current_thread().build().unwrap();
+        let res = rt.block_on(handle.spawn(async {}));
+        assert!(res.unwrap_err().is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_drop_in_async_context() {
+        let client = Client::new(Keys::generate());
+        let notifications = client.notifications();
+        drop(client);
+
+        // Must not panic and must shutdown in background
+        tokio::task::spawn_blocking(move || notifications.count())
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Published events audit
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use async_utility::thread;
+use nostr::{Event, EventId, Filter, Url};
+use nostr_relay_pool::{FilterOptions, Relay, RelaySendOptions};
+use serde::{Deserialize, Serialize};
+
+/// Default number of IDs per filter
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// Audit options
+#[derive(Debug, Clone, Copy)]
+pub struct AuditOptions {
+    pub(super) batch_size: usize,
+    pub(super) timeout: Duration,
+    pub(super) republish: bool,
+    pub(super) republish_interval: Duration,
+}
+
+impl Default for AuditOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: DEFAULT_BATCH_SIZE,
+            timeout: Duration::from_secs(10),
+            republish: false,
+            republish_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+impl AuditOptions {
+    /// New default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Max number of IDs per filter (default: 100)
+    ///
+    /// If lower, the `max_limit` advertised by the relay in its NIP11 document is used.
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// Timeout for every batch (default: 10 secs)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Republish the missing events (default: false)
+    pub fn republish(mut self, republish: bool) -> Self {
+        self.republish = republish;
+        self
+    }
+
+    /// Interval between republished events, per relay (default: 500 millisecs)
+    pub fn republish_interval(mut self, interval: Duration) -> Self {
+        self.republish_interval = interval;
+        self
+    }
+}
+
+/// Relay audit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayAudit {
+    /// Relay url
+    pub url: Url,
+    /// Events missing on the relay
+    pub missing: Vec<EventId>,
+    /// Missing events republished
+    pub republished: Vec<EventId>,
+    /// Missing events failed to republish
+    pub failed: Vec<EventId>,
+    /// Error that stopped the audit, if any
+    pub error: Option<String>,
+}
+
+/// Published events audit report
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditReport {
+    /// Number of checked events
+    pub events_checked: usize,
+    /// Per-relay audit
+    pub relays: Vec<RelayAudit>,
+}
+
+impl AuditReport {
+    /// Check if all the events are stored on all the relays
+    pub fn is_complete(&self) -> bool {
+        self.relays
+            .iter()
+            .all(|r| r.error.is_none() && r.missing.len() == r.republished.len())
+    }
+}
+
+/// Batch size for the relay
+fn batch_size(size: usize, max_limit: Option<i32>) -> usize {
+    match max_limit {
+        Some(max) if max > 0 => size.min(max as usize),
+        _ => size,
+    }
+}
+
+/// Check the events stored on the relay and, if enabled, republish the missing ones
+pub(super) async fn audit_relay(
+    relay: Relay,
+    events: Vec<Event>,
+    opts: AuditOptions,
+) -> RelayAudit {
+    let mut audit = RelayAudit {
+        url: relay.url(),
+        missing: Vec::new(),
+        republished: Vec::new(),
+        failed: Vec::new(),
+        error: None,
+    };
+
+    #[cfg(feature = "nip11")]
+    let max_limit: Option<i32> = relay.document().await.limitation.and_then(|l| l.max_limit);
+    #[cfg(not(feature = "nip11"))]
+    let max_limit: Option<i32> = None;
+
+    let size: usize = batch_size(opts.batch_size, max_limit);
+
+    for batch in events.chunks(size) {
+        let ids = batch.iter().map(|e| e.id);
+        let filter: Filter = Filter::new().ids(ids).limit(batch.len());
+        match relay
+            .get_events_of(vec![filter], opts.timeout, FilterOptions::ExitOnEOSE)
+            .await
+        {
+            Ok(found) => {
+                let found: HashSet<EventId> = found.into_iter().map(|e| e.id).collect();
+                audit
+                    .missing
+                    .extend(batch.iter().map(|e| e.id).filter(|id| !found.contains(id)));
+            }
+            Err(e) => {
+                audit.error = Some(e.to_string());
+                return audit;
+            }
+        }
+    }
+
+    if opts.republish {
+        let missing: HashSet<EventId> = audit.missing.iter().copied().collect();
+        let send_opts = RelaySendOptions::new().skip_disconnected(false);
+        for event in events.into_iter().filter(|e| missing.contains(&e.id)) {
+            let id: EventId = event.id;
+            match relay.send_event(event, send_opts).await {
+                Ok(..) => audit.republished.push(id),
+                Err(e) => {
+                    tracing::error!("Failed to republish {id} to {}: {e}", relay.url());
+                    audit.failed.push(id);
+                }
+            }
+            thread::sleep(opts.republish_interval).await;
+        }
+    }
+
+    audit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_size() {
+        assert_eq!(batch_size(100, None), 100);
+        assert_eq!(batch_size(100, Some(20)), 20);
+        assert_eq!(batch_size(100, Some(500)), 100);
+        assert_eq!(batch_size(100, Some(0)), 100);
+    }
+
+    #[test]
+    fn test_report_serialization() {
+        let report = AuditReport {
+            events_checked: 2,
+            relays: vec![RelayAudit {
+                url: Url::parse("wss://relay.damus.io").unwrap(),
+                missing: vec![EventId::all_zeros()],
+                republished: Vec::new(),
+                failed: Vec::new(),
+                error: None,
+            }],
+        };
+        let json: String = nostr::serde_json::to_string(&report).unwrap();
+        let parsed: AuditReport = nostr::serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, report);
+        assert!(!report.is_complete());
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Authentication (NIP42)
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use nostr::Url;
+use nostr_relay_pool::AuthReason;
+use tokio::sync::RwLock;
+
+/// Authentication callback
+///
+/// Consulted when a relay requests the authentication (ex. to prompt the user).
+pub trait AuthCallback: fmt::Debug {
+    /// Return `true` to authenticate to the relay
+    fn allow(&self, relay_url: &Url, reason: &AuthReason) -> bool;
+}
+
+/// Automatic authentication policy
+///
+/// Signing the auth event reveals the public key to the relay: restrict it to the trusted relays to avoid tracking.
+#[derive(Debug, Clone, Default)]
+pub enum AuthPolicy {
+    /// Never authenticate automatically
+    #[default]
+    Disabled,
+    /// Authenticate to all relays
+    All,
+    /// Authenticate only to these relays
+    OnlyRelays(HashSet<Url>),
+    /// Ask the callback
+    ///
+    /// The decision is cached per relay for the session.
+    Callback(Arc<dyn AuthCallback + Send + Sync>),
+}
+
+impl AuthPolicy {
+    /// Check if the automatic authentication is enabled
+    pub fn is_enabled(&self) -> bool {
+        !matches!(self, Self::Disabled)
+    }
+}
+
+/// Authentication session state
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AuthSession {
+    challenges: Arc<RwLock<HashMap<Url, String>>>,
+    decisions: Arc<RwLock<HashMap<Url, bool>>>,
+    listening: Arc<AtomicBool>,
+}
+
+impl AuthSession {
+    /// Return `true` only the first time it's called
+    pub fn start_listening(&self) -> bool {
+        !self.listening.swap(true, Ordering::SeqCst)
+    }
+
+    pub async fn set_challenge(&self, relay_url: Url, challenge: String) {
+        let mut challenges = self.challenges.write().await;
+        challenges.insert(relay_url, challenge);
+    }
+
+    pub async fn challenge(&self, relay_url: &Url) -> Option<String> {
+        let challenges = self.challenges.read().await;
+        challenges.get(relay_url).cloned()
+    }
+
+    /// Check if the policy allows the authentication to the relay
+    pub async fn is_allowed(
+        &self,
+        policy: &AuthPolicy,
+        relay_url: &Url,
+        reason: &AuthReason,
+    ) -> bool {
+        match policy {
+            AuthPolicy::Disabled => false,
+            AuthPolicy::All => true,
+            AuthPolicy::OnlyRelays(relays) => relays.contains(relay_url),
+            AuthPolicy::Callback(callback) => {
+                if let Some(allowed) = self.decisions.read().await.get(relay_url) {
+                    return *allowed;
+                }
+
+                let allowed: bool = callback.allow(relay_url, reason);
+                let mut decisions = self.decisions.write().await;
+                decisions.insert(relay_url.clone(), allowed);
+                allowed
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use nostr::SubscriptionId;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct CountingCallback {
+        calls: AtomicUsize,
+    }
+
+    impl AuthCallback for CountingCallback {
+        fn allow(&self, relay_url: &Url, _reason: &AuthReason) -> bool {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            relay_url.as_str() == "wss://trusted.example.com/"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_auth_policy() {
+        let session = AuthSession::default();
+        let trusted = Url::parse("wss://trusted.example.com").unwrap();
+        let other = Url::parse("wss://other.example.com").unwrap();
+        let reason = AuthReason::Subscription(SubscriptionId::new("test"));
+
+        assert!(
+            !session
+                .is_allowed(&AuthPolicy::Disabled, &trusted, &reason)
+                .await
+        );
+        assert!(session.is_allowed(&AuthPolicy::All, &other, &reason).await);
+
+        let policy = AuthPolicy::OnlyRelays([trusted.clone()].into_iter().collect());
+        assert!(session.is_allowed(&policy, &trusted, &reason).await);
+        assert!(!session.is_allowed(&policy, &other, &reason).await);
+
+        // Callback decisions are cached per relay
+        let callback = Arc::new(CountingCallback::default());
+        let policy = AuthPolicy::Callback(callback.clone());
+        assert!(session.is_allowed(&policy, &trusted, &reason).await);
+        assert!(session.is_allowed(&policy, &trusted, &reason).await);
+        assert!(!session.is_allowed(&policy, &other, &reason).await);
+        assert!(!session.is_allowed(&policy, &other, &reason).await);
+        assert_eq!(callback.calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! First-run relay bootstrap
+
+use std::time::Duration;
+
+use nostr::nips::nip65;
+use nostr::serde_json::{Map, Value};
+use nostr::{Event, Kind, PublicKey, RelayMetadata, Url};
+
+/// Default discovery relays
+pub const DEFAULT_DISCOVERY_RELAYS: [&str; 3] = [
+    "wss://purplepag.es",
+    "wss://relay.nostr.band",
+    "wss://relay.damus.io",
+];
+
+/// Bootstrap options
+#[derive(Debug, Clone)]
+pub struct BootstrapOptions {
+    pub(super) discovery_relays: Vec<Url>,
+    pub(super) default_relays: Vec<Url>,
+    pub(super) public_key: Option<PublicKey>,
+    pub(super) timeout: Duration,
+}
+
+impl Default for BootstrapOptions {
+    fn default() -> Self {
+        Self {
+            discovery_relays: DEFAULT_DISCOVERY_RELAYS
+                .iter()
+                .filter_map(|url| Url::parse(url).ok())
+                .collect(),
+            default_relays: Vec::new(),
+            public_key: None,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl BootstrapOptions {
+    /// New default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set discovery relays, used to look up the user relay list (default: [`DEFAULT_DISCOVERY_RELAYS`])
+    pub fn discovery_relays<I>(mut self, relays: I) -> Self
+    where
+        I: IntoIterator<Item = Url>,
+    {
+        self.discovery_relays = relays.into_iter().collect();
+        self
+    }
+
+    /// Set relays to install if no relay list is found (default: none)
+    pub fn default_relays<I>(mut self, relays: I) -> Self
+    where
+        I: IntoIterator<Item = Url>,
+    {
+        self.default_relays = relays.into_iter().collect();
+        self
+    }
+
+    /// Public key to look up (default: the signer public key, if any)
+    ///
+    /// If no public key is available, the user is considered new and the default relays are installed.
+    pub fn public_key(mut self, public_key: PublicKey) -> Self {
+        self.public_key = Some(public_key);
+        self
+    }
+
+    /// Timeout for the relay list lookup (default: 10 secs)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Where the bootstrapped relays come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BootstrapSource {
+    /// NIP65 relay list (kind 10002)
+    RelayList,
+    /// Relays in the contact list content (kind 3)
+    ContactList,
+    /// [`BootstrapOptions::default_relays`]
+    Defaults,
+}
+
+/// Bootstrap report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootstrapReport {
+    /// Source of the imported relays
+    pub source: BootstrapSource,
+    /// Imported relays
+    pub relays: Vec<(Url, Option<RelayMetadata>)>,
+    /// Discovery relays removed after the lookup
+    pub removed_discovery_relays: Vec<Url>,
+}
+
+/// Extract relays from the NIP65 relay list
+pub(super) fn relays_from_relay_list(event: &Event) -> Vec<(Url, Option<RelayMetadata>)> {
+    nip65::extract_relay_list(event)
+        .into_iter()
+        .filter_map(|(url, metadata)| Some((Url::try_from(url).ok()?, metadata)))
+        .collect()
+}
+
+/// Extract relays from the (legacy) contact list content
+///
+/// Format: `{"<url>": {"read": <bool>, "write": <bool>}}`
+pub(super) fn relays_from_contact_list(event: &Event) -> Vec<(Url, Option<RelayMetadata>)> {
+    if event.kind != Kind::ContactList {
+        return Vec::new();
+    }
+
+    let map: Map<String, Value> = match nostr::serde_json::from_str(event.content()) {
+        Ok(map) => map,
+        Err(_) => return Vec::new(),
+    };
+
+    map.into_iter()
+        .filter_map(|(url, value)| {
+            let url: Url = Url::parse(&url).ok()?;
+            let read: bool = value.get("read").and_then(Value::as_bool).unwrap_or(true);
+            let write: bool = value.get("write").and_then(Value::as_bool).unwrap_or(true);
+            let metadata: Option<RelayMetadata> = match (read, write) {
+                (true, false) => Some(RelayMetadata::Read),
+                (false, true) => Some(RelayMetadata::Write),
+                (false, false) => return None,
+                (true, true) => None,
+            };
+            Some((url, metadata))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys};
+
+    use super::*;
+
+    #[test]
+    fn test_relays_from_contact_list() {
+        let keys = Keys::generate();
+        let content = r#"{"wss://relay.damus.io":{"read":true,"write":true},"wss://nos.lol":{"read":true,"write":false},"invalid":{}}"#;
+        let event = EventBuilder::new(Kind::ContactList, content, [])
+            .to_event(&keys)
+            .unwrap();
+        let mut relays = relays_from_contact_list(&event);
+        relays.sort();
+        assert_eq!(
+            relays,
+            vec![
+                (
+                    Url::parse("wss://nos.lol").unwrap(),
+                    Some(RelayMetadata::Read)
+                ),
+                (Url::parse("wss://relay.damus.io").unwrap(), None),
+            ]
+        );
+    }
+}
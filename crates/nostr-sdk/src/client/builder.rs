@@ -8,6 +8,7 @@ use std::sync::Arc;
 
 use nostr_database::memory::MemoryDatabase;
 use nostr_database::{DynNostrDatabase, IntoNostrDatabase};
+use nostr_relay_pool::RelayPoolOptions;
 use nostr_signer::NostrSigner;
 #[cfg(feature = "nip57")]
 use nostr_zapper::{DynNostrZapper, IntoNostrZapper};
@@ -24,6 +25,10 @@ pub struct ClientBuilder {
     pub zapper: Option<Arc<DynNostrZapper>>,
     /// Database
     pub database: Arc<DynNostrDatabase>,
+    /// Relay pool options
+    ///
+    /// If `None`, the deprecated [`Options::pool`] is used.
+    pub pool: Option<RelayPoolOptions>,
     /// Client options
     pub opts: Options,
 }
@@ -35,6 +40,7 @@ impl Default for ClientBuilder {
             #[cfg(feature = "nip57")]
             zapper: None,
             database: Arc::new(MemoryDatabase::default()),
+            pool: None,
             opts: Options::default(),
         }
     }
@@ -85,12 +91,25 @@ impl ClientBuilder {
         self
     }
 
+    /// Set relay pool options
+    ///
+    /// Used only to build the relay pool: can't be changed later.
+    pub fn pool(mut self, opts: RelayPoolOptions) -> Self {
+        self.pool = Some(opts);
+        self
+    }
+
     /// Set opts
     pub fn opts(mut self, opts: Options) -> Self {
         self.opts = opts;
         self
     }
 
+    #[allow(deprecated)]
+    pub(crate) fn get_pool_opts(&self) -> RelayPoolOptions {
+        self.pool.unwrap_or(self.opts.pool)
+    }
+
     /// Build [`Client`]
     pub fn build(self) -> Client {
         Client::from_builder(self)
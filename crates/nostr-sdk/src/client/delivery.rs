@@ -0,0 +1,323 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Delivery log
+//!
+//! Opt-in record of the events accepted by relays, used as delivery receipts.
+//! Check [`Client::with_delivery_log`](super::Client::with_delivery_log).
+
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::mem;
+use std::sync::{Arc, RwLock};
+
+use nostr::serde_json;
+use nostr::{EventBuilder, EventId, JsonUtil, Kind, Tag, Timestamp, UncheckedUrl, Url};
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the identifier (`d` tag) of the delivery attestations
+pub const ATTESTATION_IDENTIFIER_PREFIX: &str = "delivery-attestation:";
+/// Default max number of events with receipts
+pub const DEFAULT_MAX_EVENTS: usize = 10_000;
+
+/// Delivery receipt
+///
+/// `OK` messages aren't signed: the receipt is evidence collected by this client,
+/// not a proof signed by the relay.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliveryReceipt {
+    /// Event ID
+    pub event_id: EventId,
+    /// Relay URL
+    pub relay_url: Url,
+    /// When the `OK` was received
+    pub accepted_at: Timestamp,
+    /// Raw `OK` message
+    pub ok: String,
+    /// Relay public key, if advertised in the NIP11 document (requires `nip11` feature)
+    ///
+    /// Resolved by [`Client::attest_delivery`](super::Client::attest_delivery).
+    pub relay_pubkey: Option<String>,
+    /// Result of the last verification query (`None` if not verified yet)
+    pub retrievable: Option<bool>,
+}
+
+#[derive(Debug, Default)]
+struct InnerDeliveryLog {
+    receipts: HashMap<EventId, Vec<DeliveryReceipt>>,
+    /// Events in recording order, to drop the oldest
+    order: VecDeque<EventId>,
+}
+
+/// Delivery log
+///
+/// Record a [`DeliveryReceipt`] for every event accepted by a relay.
+#[derive(Debug, Clone)]
+pub struct DeliveryLog {
+    inner: Arc<RwLock<InnerDeliveryLog>>,
+    max_events: usize,
+}
+
+impl Default for DeliveryLog {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(InnerDeliveryLog::default())),
+            max_events: DEFAULT_MAX_EVENTS,
+        }
+    }
+}
+
+impl DeliveryLog {
+    /// New empty log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Max number of events with receipts (default: [`DEFAULT_MAX_EVENTS`])
+    ///
+    /// When full, the receipts of the oldest recorded event are dropped.
+    pub fn max_events(mut self, max: usize) -> Self {
+        self.max_events = max.max(1);
+        self
+    }
+
+    /// Record a receipt, replacing the previous one of the same relay
+    pub(crate) fn record(&self, receipt: DeliveryReceipt) {
+        let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+
+        if !inner.receipts.contains_key(&receipt.event_id) {
+            while inner.order.len() >= self.max_events {
+                match inner.order.pop_front() {
+                    Some(oldest) => {
+                        inner.receipts.remove(&oldest);
+                    }
+                    None => break,
+                }
+            }
+            inner.order.push_back(receipt.event_id);
+        }
+
+        let receipts = inner.receipts.entry(receipt.event_id).or_default();
+        match receipts
+            .iter_mut()
+            .find(|r| r.relay_url == receipt.relay_url)
+        {
+            Some(r) => *r = receipt,
+            None => receipts.push(receipt),
+        }
+    }
+
+    fn update<F>(&self, event_id: &EventId, relay_url: &Url, f: F)
+    where
+        F: Fn(&mut DeliveryReceipt),
+    {
+        let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(receipts) = inner.receipts.get_mut(event_id) {
+            for receipt in receipts.iter_mut().filter(|r| &r.relay_url == relay_url) {
+                f(receipt);
+            }
+        }
+    }
+
+    pub(crate) fn set_retrievable(&self, event_id: &EventId, relay_url: &Url, retrievable: bool) {
+        self.update(event_id, relay_url, |r| r.retrievable = Some(retrievable));
+    }
+
+    pub(crate) fn set_relay_pubkey(&self, event_id: &EventId, relay_url: &Url, pubkey: String) {
+        self.update(event_id, relay_url, |r| {
+            r.relay_pubkey = Some(pubkey.clone())
+        });
+    }
+
+    fn filter<F>(&self, f: F) -> Vec<DeliveryReceipt>
+    where
+        F: Fn(&DeliveryReceipt) -> bool,
+    {
+        let inner = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        inner
+            .order
+            .iter()
+            .filter_map(|id| inner.receipts.get(id))
+            .flatten()
+            .filter(|r| f(r))
+            .cloned()
+            .collect()
+    }
+
+    /// Get all the receipts, in recording order
+    pub fn all(&self) -> Vec<DeliveryReceipt> {
+        self.filter(|_| true)
+    }
+
+    /// Get the receipts of an event
+    pub fn receipts(&self, event_id: &EventId) -> Vec<DeliveryReceipt> {
+        let inner = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        inner.receipts.get(event_id).cloned().unwrap_or_default()
+    }
+
+    /// Get the receipts of a relay
+    pub fn by_relay(&self, relay_url: &Url) -> Vec<DeliveryReceipt> {
+        self.filter(|r| &r.relay_url == relay_url)
+    }
+
+    /// Get the receipts received in a time range (inclusive)
+    pub fn between(&self, since: Timestamp, until: Timestamp) -> Vec<DeliveryReceipt> {
+        self.filter(|r| r.accepted_at >= since && r.accepted_at <= until)
+    }
+
+    /// Number of receipts
+    pub fn len(&self) -> usize {
+        let inner = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        inner.receipts.values().map(|r| r.len()).sum()
+    }
+
+    /// Check if the log is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all the receipts
+    pub fn clear(&self) {
+        let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        inner.receipts.clear();
+        inner.order.clear();
+    }
+
+    /// Export all the receipts as JSON lines
+    pub fn export<W>(&self, mut writer: W) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        for receipt in self.all().iter() {
+            serde_json::to_writer(&mut writer, receipt)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()
+    }
+
+    /// Number of events, max number of events and approximate size in bytes
+    pub(crate) fn usage(&self) -> (usize, usize, usize) {
+        let inner = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        let bytes: usize = inner
+            .receipts
+            .values()
+            .map(|receipts| {
+                mem::size_of::<(EventId, Vec<DeliveryReceipt>)>()
+                    + receipts
+                        .iter()
+                        .map(|r| {
+                            mem::size_of::<DeliveryReceipt>()
+                                + r.relay_url.as_str().len()
+                                + r.ok.len()
+                                + r.relay_pubkey.as_ref().map_or(0, |p| p.len())
+                        })
+                        .sum::<usize>()
+            })
+            .sum();
+        (inner.order.len(), self.max_events, bytes)
+    }
+}
+
+/// Build the attestation of the delivery receipts of an event
+///
+/// Parameterized replaceable event (kind `30078`), with the receipts as JSON content.
+pub(crate) fn attestation(event_id: EventId, receipts: &[DeliveryReceipt]) -> EventBuilder {
+    let mut tags: Vec<Tag> = Vec::with_capacity(receipts.len() + 2);
+    tags.push(Tag::Identifier(format!(
+        "{ATTESTATION_IDENTIFIER_PREFIX}{event_id}"
+    )));
+    tags.push(Tag::event(event_id));
+    for receipt in receipts.iter() {
+        tags.push(Tag::Relay(UncheckedUrl::from(
+            receipt.relay_url.to_string(),
+        )));
+    }
+    let content: String = serde_json::to_string(receipts).unwrap_or_default();
+    EventBuilder::new(Kind::ApplicationSpecificData, content, tags)
+}
+
+impl JsonUtil for DeliveryReceipt {
+    type Err = serde_json::Error;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt(n: u8, relay: &str, accepted_at: u64) -> DeliveryReceipt {
+        DeliveryReceipt {
+            event_id: EventId::from_slice(&[n; 32]).unwrap(),
+            relay_url: Url::parse(relay).unwrap(),
+            accepted_at: Timestamp::from(accepted_at),
+            ok: String::from("[\"OK\",\"...\",true,\"\"]"),
+            relay_pubkey: None,
+            retrievable: None,
+        }
+    }
+
+    #[test]
+    fn test_delivery_log_queries() {
+        let log = DeliveryLog::new();
+        log.record(receipt(1, "wss://relay.damus.io", 10));
+        log.record(receipt(1, "wss://nos.lol", 20));
+        log.record(receipt(2, "wss://nos.lol", 30));
+
+        let id = EventId::from_slice(&[1; 32]).unwrap();
+        let url = Url::parse("wss://nos.lol").unwrap();
+        assert_eq!(log.receipts(&id).len(), 2);
+        assert_eq!(log.by_relay(&url).len(), 2);
+        assert_eq!(
+            log.between(Timestamp::from(15), Timestamp::from(30)).len(),
+            2
+        );
+
+        log.set_retrievable(&id, &url, true);
+        assert_eq!(log.by_relay(&url)[0].retrievable, Some(true));
+        assert_eq!(log.by_relay(&url)[1].retrievable, None);
+
+        let mut buf: Vec<u8> = Vec::new();
+        log.export(&mut buf).unwrap();
+        let lines: Vec<DeliveryReceipt> = String::from_utf8(buf)
+            .unwrap()
+            .lines()
+            .map(|l| DeliveryReceipt::from_json(l).unwrap())
+            .collect();
+        assert_eq!(lines, log.all());
+
+        log.clear();
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn test_delivery_log_capacity() {
+        let log = DeliveryLog::new().max_events(2);
+        log.record(receipt(1, "wss://relay.damus.io", 10));
+        log.record(receipt(1, "wss://relay.damus.io", 11));
+        log.record(receipt(2, "wss://nos.lol", 20));
+        log.record(receipt(3, "wss://nos.lol", 30));
+
+        // Oldest event dropped, duplicated receipt replaced
+        assert!(log
+            .receipts(&EventId::from_slice(&[1; 32]).unwrap())
+            .is_empty());
+        assert_eq!(log.len(), 2);
+        let (entries, max, bytes) = log.usage();
+        assert_eq!((entries, max), (2, 2));
+        assert!(bytes > 0);
+    }
+
+    #[test]
+    fn test_attestation() {
+        let r = receipt(1, "wss://nos.lol", 10);
+        let builder = attestation(r.event_id, std::slice::from_ref(&r));
+        let event = builder.to_event(&nostr::Keys::generate()).unwrap();
+        assert_eq!(event.kind(), Kind::ApplicationSpecificData);
+        assert_eq!(
+            event.identifier(),
+            Some(format!("{ATTESTATION_IDENTIFIER_PREFIX}{}", r.event_id).as_str())
+        );
+        let receipts: Vec<DeliveryReceipt> = serde_json::from_str(event.content()).unwrap();
+        assert_eq!(receipts, vec![r]);
+    }
+}
@@ -0,0 +1,401 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Live feed
+
+use std::num::NonZeroUsize;
+use std::time::Duration;
+
+use lru::LruCache;
+use nostr::{Event, EventId, Kind, PublicKey, SubscriptionId, Timestamp, Url};
+use tokio::sync::mpsc::Receiver;
+
+/// Default max number of events loaded from the database
+pub const DEFAULT_CACHE_LIMIT: usize = 500;
+/// Default feed channel size
+pub const DEFAULT_CHANNEL_SIZE: usize = 1024;
+/// Default max number of events remembered for deduplication
+pub const DEFAULT_DEDUP_CAPACITY: usize = 10_000;
+
+/// Live feed options
+#[derive(Debug, Clone, Copy)]
+pub struct LiveFeedOptions {
+    pub(super) cache_limit: usize,
+    pub(super) channel_size: usize,
+    pub(super) gap_fill_timeout: Option<Duration>,
+    pub(super) dedup_capacity: usize,
+}
+
+impl Default for LiveFeedOptions {
+    fn default() -> Self {
+        Self {
+            cache_limit: DEFAULT_CACHE_LIMIT,
+            channel_size: DEFAULT_CHANNEL_SIZE,
+            gap_fill_timeout: None,
+            dedup_capacity: DEFAULT_DEDUP_CAPACITY,
+        }
+    }
+}
+
+impl LiveFeedOptions {
+    /// New default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Max number of events loaded from the database (default: 500)
+    ///
+    /// The filter `limit`, if set, takes precedence.
+    /// It's also the page size of the gap-fill and, if the database has no events, the max number of events fetched from relays.
+    pub fn cache_limit(mut self, limit: usize) -> Self {
+        self.cache_limit = limit;
+        self
+    }
+
+    /// Number of items buffered before the feed stops reading (default: 1024)
+    pub fn channel_size(mut self, size: usize) -> Self {
+        self.channel_size = size.max(1);
+        self
+    }
+
+    /// Timeout of every gap-fill query (default: the client timeout)
+    pub fn gap_fill_timeout(mut self, timeout: Duration) -> Self {
+        self.gap_fill_timeout = Some(timeout);
+        self
+    }
+
+    /// Max number of events remembered for deduplication (default: 10_000)
+    ///
+    /// When full, the least recently seen events are forgotten.
+    pub fn dedup_capacity(mut self, capacity: usize) -> Self {
+        self.dedup_capacity = capacity.max(1);
+        self
+    }
+}
+
+/// Origin of a [`FeedItem`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FeedOrigin {
+    /// Local database
+    Cache,
+    /// Relay
+    Relay(Url),
+}
+
+/// Live feed item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedItem {
+    /// Event
+    pub event: Event,
+    /// Origin
+    pub origin: FeedOrigin,
+}
+
+/// Feed that starts from the database and continues with the events received from relays
+///
+/// Items are yielded in three phases:
+/// 1. events stored in the database, newest first;
+/// 2. events published since the newest cached one (gap-fill, fetched by pages), newest first;
+///    if the database has no events, only the newest ones, up to the cache limit;
+/// 3. live events, in the order they are received.
+///
+/// The same event is never yielded twice, and replaceable events are skipped if a newer version was already yielded
+/// (among the last [`LiveFeedOptions::dedup_capacity`] ones).
+///
+/// # Backpressure
+///
+/// Up to [`LiveFeedOptions::channel_size`] items are buffered: when the buffer is full the feed stops reading
+/// until the next item is consumed. Database and gap-fill items are never dropped. Live events are read from the
+/// client notifications, so if the consumer falls behind more than the notification channel size, some of them are skipped.
+///
+/// The subscription is closed when the feed is dropped.
+#[derive(Debug)]
+pub struct LiveFeed {
+    pub(super) receiver: Receiver<FeedItem>,
+    pub(super) subscription_id: SubscriptionId,
+}
+
+impl LiveFeed {
+    /// Get the ID of the live subscription
+    pub fn subscription_id(&self) -> &SubscriptionId {
+        &self.subscription_id
+    }
+
+    /// Get next item
+    ///
+    /// Return `None` when the client is shut down.
+    pub async fn next(&mut self) -> Option<FeedItem> {
+        self.receiver.recv().await
+    }
+}
+
+type ReplaceableKey = (Kind, PublicKey, Option<String>);
+
+/// Deduplicate events across the feed phases
+#[derive(Debug)]
+pub(super) struct FeedDedup {
+    ids: LruCache<EventId, ()>,
+    replaceable: LruCache<ReplaceableKey, (Timestamp, EventId)>,
+    newest: Option<Timestamp>,
+}
+
+impl FeedDedup {
+    pub fn new(capacity: usize) -> Self {
+        let capacity: NonZeroUsize =
+            NonZeroUsize::new(capacity.max(1)).expect("Capacity must not be zero");
+        Self {
+            ids: LruCache::new(capacity),
+            replaceable: LruCache::new(capacity),
+            newest: None,
+        }
+    }
+
+    /// Check if the event must be yielded
+    pub fn accept(&mut self, event: &Event) -> bool {
+        if self.ids.put(event.id, ()).is_some() {
+            return false;
+        }
+
+        if event.is_replaceable() || event.is_parameterized_replaceable() {
+            let identifier: Option<String> = if event.is_parameterized_replaceable() {
+                event.identifier().map(String::from)
+            } else {
+                None
+            };
+            let key: ReplaceableKey = (event.kind, event.pubkey, identifier);
+            if let Some((created_at, id)) = self.replaceable.get(&key) {
+                // On ties, relays keep the event with the lowest ID
+                let is_older: bool = event.created_at < *created_at
+                    || (event.created_at == *created_at && event.id > *id);
+                if is_older {
+                    return false;
+                }
+            }
+            self.replaceable.put(key, (event.created_at, event.id));
+        }
+
+        if self.newest.map_or(true, |newest| event.created_at > newest) {
+            self.newest = Some(event.created_at);
+        }
+
+        true
+    }
+
+    /// Newest `created_at` accepted
+    pub fn newest(&self) -> Option<Timestamp> {
+        self.newest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Reverse;
+
+    use async_utility::futures_util::{SinkExt, StreamExt};
+    use async_utility::{thread, time};
+    use nostr::{ClientMessage, EventBuilder, Filter, JsonUtil, Keys, RelayMessage, Tag};
+    use nostr_database::{MemoryDatabase, MemoryDatabaseOptions, NostrDatabase};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::Message;
+
+    use super::*;
+    use crate::{Client, ClientBuilder};
+
+    fn event(keys: &Keys, kind: Kind, created_at: u64, tags: Vec<Tag>) -> Event {
+        EventBuilder::new(kind, created_at.to_string(), tags)
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_dedup_replaceable() {
+        let keys = Keys::generate();
+        let mut dedup = FeedDedup::new(10);
+
+        let new = event(&keys, Kind::Metadata, 2, Vec::new());
+        let old = event(&keys, Kind::Metadata, 1, Vec::new());
+        assert!(dedup.accept(&new));
+        assert!(!dedup.accept(&new));
+        assert!(!dedup.accept(&old));
+
+        // Different identifier
+        let a = event(
+            &keys,
+            Kind::InterestSets,
+            1,
+            vec![Tag::Identifier("a".into())],
+        );
+        let b = event(
+            &keys,
+            Kind::InterestSets,
+            1,
+            vec![Tag::Identifier("b".into())],
+        );
+        assert!(dedup.accept(&a));
+        assert!(dedup.accept(&b));
+
+        assert_eq!(dedup.newest(), Some(Timestamp::from(2)));
+    }
+
+    #[test]
+    fn test_dedup_capacity() {
+        let keys = Keys::generate();
+        let mut dedup = FeedDedup::new(2);
+
+        let events: Vec<Event> = (1..=3)
+            .map(|i| event(&keys, Kind::TextNote, i, Vec::new()))
+            .collect();
+        for event in events.iter() {
+            assert!(dedup.accept(event));
+        }
+
+        // The least recently seen event is forgotten
+        assert!(!dedup.accept(&events[2]));
+        assert!(dedup.accept(&events[0]));
+        assert_eq!(dedup.ids.len(), 2);
+    }
+
+    async fn client_with_events(events: &[Event]) -> Client {
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        });
+        for event in events.iter() {
+            database.save_event(event).await.unwrap();
+        }
+        ClientBuilder::new().database(database).build()
+    }
+
+    #[tokio::test]
+    async fn test_live_feed_cache_order() {
+        let keys = Keys::generate();
+        let events: Vec<Event> = (1..=5)
+            .map(|i| event(&keys, Kind::TextNote, i, Vec::new()))
+            .collect();
+        let client = client_with_events(&events).await;
+
+        let filter = Filter::new().kind(Kind::TextNote).limit(3);
+        let mut feed = client.live_feed(filter).await.unwrap();
+
+        // Newest first, up to the limit
+        for created_at in [5, 4, 3] {
+            let item = feed.next().await.unwrap();
+            assert_eq!(item.origin, FeedOrigin::Cache);
+            assert_eq!(item.event.created_at, Timestamp::from(created_at));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_live_feed_backpressure() {
+        let keys = Keys::generate();
+        let events: Vec<Event> = (1..=10)
+            .map(|i| event(&keys, Kind::TextNote, i, Vec::new()))
+            .collect();
+        let client = client_with_events(&events).await;
+
+        let opts = LiveFeedOptions::new().channel_size(1);
+        let mut feed = client
+            .live_feed_with_opts(Filter::new().kind(Kind::TextNote), opts)
+            .await
+            .unwrap();
+
+        // Slow consumer: nothing is dropped
+        let mut received: Vec<Timestamp> = Vec::new();
+        for _ in 0..10 {
+            thread::sleep(Duration::from_millis(10)).await;
+            received.push(feed.next().await.unwrap().event.created_at);
+        }
+        let expected: Vec<Timestamp> = (1..=10).rev().map(Timestamp::from).collect();
+        assert_eq!(received, expected);
+
+        // No duplicates after the cache phase
+        let next = time::timeout(Some(Duration::from_millis(200)), feed.next()).await;
+        assert!(next.is_none());
+    }
+
+    /// Minimal relay: answer to every `REQ` with the matching events (newest first, up to the limit) and `EOSE`
+    async fn spawn_relay(events: Vec<Event>) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let events = events.clone();
+                tokio::spawn(async move {
+                    let mut ws = match tokio_tungstenite::accept_async(stream).await {
+                        Ok(ws) => ws,
+                        Err(_) => return,
+                    };
+                    while let Some(Ok(msg)) = ws.next().await {
+                        let json = match msg {
+                            Message::Text(json) => json,
+                            _ => continue,
+                        };
+                        if let Ok(ClientMessage::Req {
+                            subscription_id,
+                            filters,
+                        }) = ClientMessage::from_json(json)
+                        {
+                            let mut msgs: Vec<RelayMessage> = Vec::new();
+                            for filter in filters.iter() {
+                                let mut matching: Vec<&Event> =
+                                    events.iter().filter(|e| filter.match_event(e)).collect();
+                                matching.sort_by_key(|e| Reverse(e.created_at));
+                                matching.truncate(filter.limit.unwrap_or(usize::MAX));
+                                msgs.extend(matching.into_iter().map(|e| {
+                                    RelayMessage::event(subscription_id.clone(), e.clone())
+                                }));
+                            }
+                            msgs.push(RelayMessage::eose(subscription_id));
+                            for msg in msgs.into_iter() {
+                                if ws.send(Message::Text(msg.as_json())).await.is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        Url::parse(&format!("ws://{addr}")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_live_feed_gap_fill() {
+        let keys = Keys::generate();
+        let events: Vec<Event> = (1..=10)
+            .map(|i| event(&keys, Kind::TextNote, i, Vec::new()))
+            .collect();
+        let client = client_with_events(&events[..3]).await;
+        let url = spawn_relay(events).await;
+        client.add_relay(url.clone()).await.unwrap();
+        client.connect_relay(url.clone()).await.unwrap();
+        time::timeout(Some(Duration::from_secs(5)), async {
+            while !client.relay(&url).await.unwrap().is_connected().await {
+                thread::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        // The limit is the page size: the whole gap is filled anyway
+        let filter = Filter::new().kind(Kind::TextNote).limit(2);
+        let opts = LiveFeedOptions::new().gap_fill_timeout(Duration::from_secs(5));
+        let mut feed = client.live_feed_with_opts(filter, opts).await.unwrap();
+
+        let mut received: Vec<(u64, FeedOrigin)> = Vec::new();
+        for _ in 0..9 {
+            let item = time::timeout(Some(Duration::from_secs(5)), feed.next())
+                .await
+                .unwrap()
+                .unwrap();
+            received.push((item.event.created_at.as_u64(), item.origin));
+        }
+
+        let mut expected: Vec<(u64, FeedOrigin)> =
+            vec![(3, FeedOrigin::Cache), (2, FeedOrigin::Cache)];
+        expected.extend((4..=10).rev().map(|i| (i, FeedOrigin::Relay(url.clone()))));
+        assert_eq!(received, expected);
+    }
+}
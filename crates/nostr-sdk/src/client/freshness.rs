@@ -0,0 +1,351 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Data freshness tracking
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use nostr::serde_json::{self, Value};
+use nostr::{Filter, SubscriptionId, Timestamp, Url};
+use nostr_relay_pool::{FetchReport, RelayFetchStatus};
+
+/// Subscription ID used to record the EOSE of the fetches (ex. [`Client::get_events_of`](super::Client::get_events_of))
+pub const FETCH_SUBSCRIPTION_ID: &str = "fetch";
+/// Default max number of tracked (subscription, filter) pairs
+pub const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+type Key = (SubscriptionId, u64);
+
+#[derive(Debug, Default)]
+struct InnerFreshnessTracker {
+    eose: HashMap<Key, Vec<(Url, Timestamp)>>,
+    /// Entries ordered by latest EOSE, used to evict the oldest one
+    order: BTreeSet<(Timestamp, Key)>,
+    connected: HashSet<Url>,
+}
+
+impl InnerFreshnessTracker {
+    fn remove(&mut self, key: &Key) {
+        if let Some(relays) = self.eose.remove(key) {
+            if let Some(latest) = latest_eose(&relays) {
+                self.order.remove(&(latest, key.clone()));
+            }
+        }
+    }
+}
+
+fn latest_eose(relays: &[(Url, Timestamp)]) -> Option<Timestamp> {
+    relays.iter().map(|(_, t)| *t).max()
+}
+
+/// Track when relays sent the EOSE for each filter
+///
+/// The local data is "fresh" for a filter if all the connected relays sent the EOSE for it recently.
+/// Check [`Client::with_freshness_tracker`](super::Client::with_freshness_tracker).
+#[derive(Debug, Clone)]
+pub struct FreshnessTracker {
+    inner: Arc<RwLock<InnerFreshnessTracker>>,
+    cache_max_age: Option<Duration>,
+    max_entries: usize,
+}
+
+impl Default for FreshnessTracker {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(InnerFreshnessTracker::default())),
+            cache_max_age: None,
+            max_entries: DEFAULT_MAX_ENTRIES,
+        }
+    }
+}
+
+impl FreshnessTracker {
+    /// New empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Max number of tracked (subscription, filter) pairs (default: [`DEFAULT_MAX_ENTRIES`])
+    ///
+    /// When full, the entry with the oldest EOSE is dropped.
+    pub fn max_entries(mut self, max: usize) -> Self {
+        self.max_entries = max.max(1);
+        self
+    }
+
+    /// Serve the fetches from the database when the filters are fresh (default: None)
+    ///
+    /// If set, [`Client::get_events_of`](super::Client::get_events_of) skips the relays
+    /// if all the filters are fresh within `max_age`.
+    pub fn cache_max_age(mut self, max_age: Option<Duration>) -> Self {
+        self.cache_max_age = max_age;
+        self
+    }
+
+    pub(crate) fn get_cache_max_age(&self) -> Option<Duration> {
+        self.cache_max_age
+    }
+
+    /// Record the EOSE of a relay for a filter
+    ///
+    /// Replace the previous EOSE of the relay.
+    pub fn record_eose(
+        &self,
+        subscription_id: &SubscriptionId,
+        filter: &Filter,
+        relay_url: Url,
+        timestamp: Timestamp,
+    ) {
+        let key = (subscription_id.clone(), filter_hash(filter));
+        let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+
+        if !inner.eose.contains_key(&key) {
+            while inner.eose.len() >= self.max_entries {
+                match inner.order.iter().next().map(|(_, key)| key.clone()) {
+                    Some(oldest) => inner.remove(&oldest),
+                    None => break,
+                }
+            }
+        }
+
+        let relays = inner.eose.entry(key.clone()).or_default();
+        let previous: Option<Timestamp> = latest_eose(relays);
+        match relays.iter_mut().find(|(url, _)| url == &relay_url) {
+            Some((_, t)) => *t = timestamp,
+            None => relays.push((relay_url, timestamp)),
+        }
+        let latest: Option<Timestamp> = latest_eose(relays);
+
+        // Keep the order in sync
+        if previous != latest {
+            if let Some(previous) = previous {
+                inner.order.remove(&(previous, key.clone()));
+            }
+            if let Some(latest) = latest {
+                inner.order.insert((latest, key));
+            }
+        }
+    }
+
+    /// Record the relays that sent the EOSE in a fetch
+    pub(crate) fn record_fetch(&self, filters: &[Filter], report: &FetchReport) {
+        let id = SubscriptionId::new(FETCH_SUBSCRIPTION_ID);
+        let now = Timestamp::now();
+        for (url, relay) in report.relays.iter() {
+            if relay.status == RelayFetchStatus::Eose {
+                for filter in filters.iter() {
+                    self.record_eose(&id, filter, url.clone(), now);
+                }
+            }
+        }
+    }
+
+    /// Forget the EOSE of a subscription
+    pub fn forget(&self, subscription_id: &SubscriptionId) {
+        let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        inner.eose.retain(|(id, _), _| id != subscription_id);
+        inner.order.retain(|(_, (id, _))| id != subscription_id);
+    }
+
+    /// Update the connection status of a relay
+    pub fn set_connected(&self, relay_url: Url, connected: bool) {
+        let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        if connected {
+            inner.connected.insert(relay_url);
+        } else {
+            inner.connected.remove(&relay_url);
+        }
+    }
+
+    /// Number of tracked (subscription, filter) pairs, the max number and the approximate size, in bytes
+    pub(crate) fn usage(&self) -> (usize, usize, usize) {
+        let inner = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        let bytes: usize = inner
+            .eose
+            .iter()
+            .map(|((id, _), relays)| {
+                mem::size_of::<(Key, Vec<(Url, Timestamp)>)>()
+                    + mem::size_of::<(Timestamp, Key)>()
+                    + id.to_string().len() * 2
+                    + relays
+                        .iter()
+                        .map(|(url, _)| mem::size_of::<(Url, Timestamp)>() + url.as_str().len())
+                        .sum::<usize>()
+            })
+            .sum();
+        (inner.eose.len(), self.max_entries, bytes)
+    }
+
+    /// Check if all the currently connected relays sent the EOSE for `filter` within `max_age`
+    ///
+    /// Return `false` if no relay is connected.
+    pub fn is_fresh(&self, filter: &Filter, max_age: Duration) -> bool {
+        self.is_fresh_at(filter, max_age, Timestamp::now())
+    }
+
+    fn is_fresh_at(&self, filter: &Filter, max_age: Duration, now: Timestamp) -> bool {
+        let hash: u64 = filter_hash(filter);
+        let inner = self.inner.read().unwrap_or_else(|e| e.into_inner());
+
+        if inner.connected.is_empty() {
+            return false;
+        }
+
+        // Latest EOSE per relay, from any subscription
+        let mut latest: HashMap<&Url, Timestamp> = HashMap::new();
+        for ((_, h), relays) in inner.eose.iter() {
+            if *h != hash {
+                continue;
+            }
+            for (url, timestamp) in relays.iter() {
+                let t = latest.entry(url).or_insert(*timestamp);
+                if *timestamp > *t {
+                    *t = *timestamp;
+                }
+            }
+        }
+
+        let oldest: Timestamp = now - max_age;
+        inner
+            .connected
+            .iter()
+            .all(|url| matches!(latest.get(url), Some(t) if *t >= oldest))
+    }
+}
+
+/// Hash of the filter, independent of the order of its sets
+fn filter_hash(filter: &Filter) -> u64 {
+    match serde_json::to_value(filter) {
+        Ok(value) => value_hash(&value),
+        Err(_) => 0,
+    }
+}
+
+fn value_hash(value: &Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, u64)> =
+                map.iter().map(|(k, v)| (k, value_hash(v))).collect();
+            entries.sort();
+            entries.hash(&mut hasher);
+        }
+        Value::Array(values) => {
+            let mut hashes: Vec<u64> = values.iter().map(value_hash).collect();
+            hashes.sort_unstable();
+            hashes.hash(&mut hasher);
+        }
+        value => value.to_string().hash(&mut hasher),
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::Kind;
+
+    use super::*;
+
+    #[test]
+    fn test_is_fresh() {
+        let tracker = FreshnessTracker::new();
+        let id = SubscriptionId::new("test");
+        let filter = Filter::new().kind(Kind::TextNote);
+        let relay1 = Url::parse("wss://relay1.example.com").unwrap();
+        let relay2 = Url::parse("wss://relay2.example.com").unwrap();
+        let max_age = Duration::from_secs(60);
+        let now = Timestamp::from(1_000);
+
+        // No connected relays
+        tracker.record_eose(&id, &filter, relay1.clone(), now);
+        assert!(!tracker.is_fresh_at(&filter, max_age, now));
+
+        // Missing EOSE of relay2
+        tracker.set_connected(relay1.clone(), true);
+        tracker.set_connected(relay2.clone(), true);
+        assert!(!tracker.is_fresh_at(&filter, max_age, now));
+
+        // Relay2 disconnected
+        tracker.set_connected(relay2.clone(), false);
+        assert!(tracker.is_fresh_at(&filter, max_age, now));
+        assert!(!tracker.is_fresh_at(&Filter::new().kind(Kind::Metadata), max_age, now));
+
+        // Too old
+        assert!(!tracker.is_fresh_at(&filter, max_age, now + Duration::from_secs(61)));
+
+        // Forget
+        tracker.forget(&id);
+        assert!(!tracker.is_fresh_at(&filter, max_age, now));
+    }
+
+    #[test]
+    fn test_filter_hash() {
+        let a = Filter::new().kinds([Kind::TextNote, Kind::Metadata, Kind::Repost]);
+        let b = Filter::new().kinds([Kind::Repost, Kind::TextNote, Kind::Metadata]);
+        assert_eq!(filter_hash(&a), filter_hash(&b));
+        assert_ne!(filter_hash(&a), filter_hash(&a.clone().limit(1)));
+    }
+
+    #[test]
+    fn test_max_entries() {
+        let tracker = FreshnessTracker::new().max_entries(2);
+        let relay = Url::parse("wss://relay.example.com").unwrap();
+        let filter = Filter::new().kind(Kind::TextNote);
+
+        tracker.record_eose(
+            &SubscriptionId::new("a"),
+            &filter,
+            relay.clone(),
+            Timestamp::from(1),
+        );
+        tracker.record_eose(
+            &SubscriptionId::new("b"),
+            &filter,
+            relay.clone(),
+            Timestamp::from(3),
+        );
+        tracker.record_eose(
+            &SubscriptionId::new("c"),
+            &filter,
+            relay.clone(),
+            Timestamp::from(2),
+        );
+
+        let ids = |tracker: &FreshnessTracker| -> HashSet<String> {
+            let inner = tracker.inner.read().unwrap();
+            assert_eq!(inner.eose.len(), inner.order.len());
+            inner.eose.keys().map(|(id, _)| id.to_string()).collect()
+        };
+        let current = ids(&tracker);
+        assert_eq!(current.len(), 2);
+        assert!(!current.contains("a"));
+
+        // A newer EOSE moves the entry to the end
+        tracker.record_eose(
+            &SubscriptionId::new("c"),
+            &filter,
+            relay.clone(),
+            Timestamp::from(4),
+        );
+        tracker.record_eose(
+            &SubscriptionId::new("d"),
+            &filter,
+            relay.clone(),
+            Timestamp::from(5),
+        );
+        let current = ids(&tracker);
+        assert!(!current.contains("b"));
+        assert!(current.contains("c"));
+        assert!(current.contains("d"));
+
+        // Forgotten entries are not left in the order
+        tracker.forget(&SubscriptionId::new("c"));
+        assert_eq!(ids(&tracker).len(), 1);
+    }
+}
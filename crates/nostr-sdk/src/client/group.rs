@@ -0,0 +1,100 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Relay groups
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use nostr::{EventId, Url};
+use nostr_relay_pool::RelayServiceFlags;
+use tokio::sync::RwLock;
+
+/// Named set of relays
+///
+/// Groups overlap with the relay pool: they allow to route events and subscriptions
+/// to a logical partition of the relays (ex. `dms` for privacy relays, `public` for public relays).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayGroup {
+    /// Group name
+    pub name: String,
+    /// Relays
+    pub relays: Vec<Url>,
+    /// Flags applied to the relays of the group when the group is added
+    pub flags: RelayServiceFlags,
+}
+
+impl RelayGroup {
+    /// New relay group with default flags
+    pub fn new<S, I>(name: S, relays: I) -> Self
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = Url>,
+    {
+        Self {
+            name: name.into(),
+            relays: relays.into_iter().collect(),
+            flags: RelayServiceFlags::default(),
+        }
+    }
+
+    /// Set flags
+    pub fn flags(mut self, flags: RelayServiceFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+}
+
+/// Publish result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishResult {
+    /// Event ID
+    pub id: EventId,
+    /// Relays that accepted the event
+    pub success: HashSet<Url>,
+    /// Relays that failed or rejected the event, with the error
+    pub failed: HashMap<Url, String>,
+}
+
+impl PublishResult {
+    /// Check if at least one relay accepted the event
+    pub fn is_success(&self) -> bool {
+        !self.success.is_empty()
+    }
+}
+
+/// Event set publishing strategy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PublishSetStrategy {
+    /// Stop at the first event not accepted by any relay and return an error
+    ///
+    /// Events already sent can't be undone: relays may have stored a partial set.
+    #[default]
+    AllOrNone,
+    /// Send all the events, regardless of failures
+    BestEffort,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RelayGroups {
+    groups: Arc<RwLock<BTreeMap<String, RelayGroup>>>,
+}
+
+impl RelayGroups {
+    /// Add or replace group
+    pub async fn insert(&self, group: RelayGroup) {
+        let mut groups = self.groups.write().await;
+        groups.insert(group.name.clone(), group);
+    }
+
+    pub async fn get(&self, name: &str) -> Option<RelayGroup> {
+        let groups = self.groups.read().await;
+        groups.get(name).cloned()
+    }
+
+    pub async fn names(&self) -> Vec<String> {
+        let groups = self.groups.read().await;
+        groups.keys().cloned().collect()
+    }
+}
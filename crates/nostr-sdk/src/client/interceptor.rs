@@ -0,0 +1,39 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Event interceptors
+
+use std::fmt;
+
+use nostr::{EventBuilder, Kind};
+
+/// Event interceptor
+///
+/// Implement this trait to add tags or adjust the content of the events signed by the `Client`
+/// (ex. organization tags or NIP32 self-labels). Check [`Options::event_interceptor`](super::Options::event_interceptor).
+///
+/// The interceptors run once per event, right before signing, in registration order.
+/// They never run on already signed events (ex. passed to `Client::send_event`)
+/// and on private kinds (NIP04 DMs, seals and gift wraps).
+pub trait EventInterceptor: fmt::Debug + Send + Sync {
+    /// Intercept the event before signing
+    fn intercept(&self, builder: EventBuilder) -> EventBuilder;
+
+    /// Intercept also the rumors of the gift wraps (default: false)
+    ///
+    /// The rumor is never signed, but its tags and content reach the receiver.
+    fn intercept_rumors(&self) -> bool {
+        false
+    }
+}
+
+/// Check if the kind must never be intercepted
+///
+/// DMs content is encrypted, seals must have no tags and gift wraps must not leak metadata.
+pub(crate) fn is_private(kind: &Kind) -> bool {
+    matches!(
+        kind,
+        Kind::EncryptedDirectMessage | Kind::Seal | Kind::GiftWrap | Kind::SealedDirect
+    )
+}
@@ -0,0 +1,39 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP21 mentions
+
+use nostr::nips::nip01::Coordinate;
+use nostr::{Event, Metadata, PublicKey};
+
+/// Resolved NIP21 mention
+///
+/// <https://github.com/nostr-protocol/nips/blob/master/21.md>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolvedMention {
+    /// Profile (`npub` or `nprofile`)
+    Profile {
+        /// Public key
+        public_key: PublicKey,
+        /// Metadata (`None` if not found)
+        metadata: Option<Metadata>,
+    },
+    /// Text note (`note` or `nevent` of kind `1`)
+    Note {
+        /// Event
+        event: Event,
+    },
+    /// Event of any other kind (`note` or `nevent`)
+    Event {
+        /// Event
+        event: Event,
+    },
+    /// Addressable event (`naddr`)
+    Address {
+        /// Coordinate
+        coordinate: Coordinate,
+        /// Latest event (`None` if not found)
+        event: Option<Event>,
+    },
+}
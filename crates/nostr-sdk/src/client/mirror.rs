@@ -0,0 +1,142 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Relay-to-relay mirroring
+
+use std::time::Duration;
+
+use nostr::{Event, EventId, Timestamp};
+use nostr_relay_pool::relay::Error as RelayError;
+use nostr_relay_pool::{Relay, RelaySendOptions};
+
+/// Default number of events per batch
+pub const DEFAULT_BATCH_SIZE: u32 = 100;
+
+/// Mirror options
+#[derive(Debug, Clone, Copy)]
+pub struct MirrorOptions {
+    pub(super) since: Option<Timestamp>,
+    pub(super) batch_size: u32,
+    pub(super) dry_run: bool,
+    pub(super) timeout: Duration,
+}
+
+impl Default for MirrorOptions {
+    fn default() -> Self {
+        Self {
+            since: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+            dry_run: false,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl MirrorOptions {
+    /// New default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mirror only the events created after this timestamp (default: None)
+    ///
+    /// Override the `since` of the filters.
+    pub fn since(mut self, since: Option<Timestamp>) -> Self {
+        self.since = since;
+        self
+    }
+
+    /// Number of events sent at once to the target relay (default: 100)
+    pub fn batch_size(mut self, size: u32) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// Fetch the events from the source relay without publishing them (default: false)
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Timeout for every request (default: 10 secs)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Mirror report
+#[derive(Debug, Default)]
+pub struct MirrorReport {
+    /// Events accepted by the target relay (or that would be sent, if dry run)
+    pub events_mirrored: u64,
+    /// Events not accepted by the target relay
+    pub errors: Vec<(EventId, RelayError)>,
+}
+
+/// Send a batch of events to the target relay and update the report
+pub(super) async fn publish_batch(
+    relay: &Relay,
+    batch: Vec<Event>,
+    opts: RelaySendOptions,
+    report: &mut MirrorReport,
+) {
+    let ids: Vec<EventId> = batch.iter().map(|e| e.id).collect();
+    match relay.batch_event(batch, opts).await {
+        Ok(()) => report.events_mirrored += ids.len() as u64,
+        Err(RelayError::PartialPublish {
+            published,
+            not_published,
+        }) => {
+            report.events_mirrored += published.len() as u64;
+            for (id, message) in not_published.into_iter() {
+                report
+                    .errors
+                    .push((id, RelayError::EventNotPublished(message)));
+            }
+        }
+        Err(RelayError::EventsNotPublished(not_published)) => {
+            for (id, message) in not_published.into_iter() {
+                report
+                    .errors
+                    .push((id, RelayError::EventNotPublished(message)));
+            }
+        }
+        Err(RelayError::EventNotPublished(message)) if ids.len() == 1 => {
+            report
+                .errors
+                .push((ids[0], RelayError::EventNotPublished(message)));
+        }
+        Err(e) => {
+            tracing::error!("Impossible to mirror events to {}: {e}", relay.url());
+            let message: String = e.to_string();
+            for id in ids.into_iter() {
+                report
+                    .errors
+                    .push((id, RelayError::EventNotPublished(message.clone())));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirror_options() {
+        let opts = MirrorOptions::new();
+        assert_eq!(opts.since, None);
+        assert_eq!(opts.batch_size, DEFAULT_BATCH_SIZE);
+        assert!(!opts.dry_run);
+
+        let opts = MirrorOptions::new()
+            .since(Some(Timestamp::from(1_000)))
+            .batch_size(0)
+            .dry_run(true);
+        assert_eq!(opts.since, Some(Timestamp::from(1_000)));
+        assert_eq!(opts.batch_size, 1);
+        assert!(opts.dry_run);
+    }
+}
@@ -4,34 +4,92 @@
 
 //! Client
 
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
 
+use async_utility::{thread, time};
 use nostr::event::builder::Error as EventBuilderError;
 use nostr::prelude::*;
+#[cfg(feature = "nip44")]
+use nostr::serde_json::{Map, Value};
 use nostr::types::metadata::Error as MetadataError;
-use nostr_database::DynNostrDatabase;
+use nostr_database::{DatabaseError, DynNostrDatabase, Order};
 use nostr_relay_pool::pool::{self, Error as RelayPoolError, RelayPool};
 use nostr_relay_pool::relay::Error as RelayError;
 use nostr_relay_pool::{
-    FilterOptions, NegentropyOptions, Relay, RelayOptions, RelayPoolNotification, RelaySendOptions,
-    SubscribeAutoCloseOptions, SubscribeOptions,
+    AckStatus, AuthReason, FetchOutput, FetchPolicy, FilterOptions, MemoryReport, MemoryUsage,
+    NegentropyOptions, Relay, RelayOptions, RelayPoolNotification, RelaySendOptions, RelayStatus,
+    SubscribeAutoCloseOptions, SubscribeOptions, SubscriptionActivity, SubscriptionStats,
+    TrafficReport,
 };
 use nostr_signer::prelude::*;
 #[cfg(feature = "nip57")]
 use nostr_zapper::{DynNostrZapper, IntoNostrZapper, ZapperError};
+#[cfg(feature = "nip44")]
+use serde::{de::DeserializeOwned, Serialize};
 use thiserror::Error;
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::{broadcast, mpsc, RwLock};
 
+pub mod audit;
+pub mod auth;
+pub mod bootstrap;
 pub mod builder;
+pub mod delivery;
+pub mod feed;
+pub mod freshness;
+pub mod group;
+pub mod interceptor;
+mod mention;
+pub mod mirror;
+mod offline;
 pub mod options;
+pub mod pagination;
+pub mod plan;
+pub mod reaction;
+mod replace;
+#[cfg(feature = "nip44")]
+pub mod settings;
+pub mod spam;
+pub mod sync;
+pub mod timeline;
+pub mod warmup;
+mod watch;
 #[cfg(feature = "nip57")]
 mod zapper;
 
+pub use self::audit::{AuditOptions, AuditReport, RelayAudit};
+use self::auth::AuthSession;
+pub use self::auth::{AuthCallback, AuthPolicy};
+pub use self::bootstrap::{BootstrapOptions, BootstrapReport, BootstrapSource};
 pub use self::builder::ClientBuilder;
+pub use self::delivery::{DeliveryLog, DeliveryReceipt};
+use self::feed::FeedDedup;
+pub use self::feed::{FeedItem, FeedOrigin, LiveFeed, LiveFeedOptions};
+pub use self::freshness::FreshnessTracker;
+use self::group::RelayGroups;
+pub use self::group::{PublishResult, PublishSetStrategy, RelayGroup};
+pub use self::interceptor::EventInterceptor;
+pub use self::mention::ResolvedMention;
+pub use self::mirror::{MirrorOptions, MirrorReport};
+use self::offline::OfflineQueue;
 pub use self::options::Options;
+pub use self::pagination::{Pagination, PaginationCursor, PaginationSource};
+pub use self::plan::{RelaySendPlan, SendPlan, SendPlanReason};
+pub use self::reaction::ReactionSummary;
+use self::replace::ReplaceAction;
+pub use self::replace::{ReplaceConflictStrategy, ReplaceResult};
+#[cfg(feature = "nip44")]
+pub use self::settings::{SettingsConflict, SyncOutcome};
+#[cfg(feature = "nip44")]
+use self::settings::{SettingsPayload, SettingsSync};
+pub use self::spam::{BuiltinSpamDetector, SpamDetector, SpamScore};
+pub use self::sync::SyncReport;
+pub use self::timeline::Timeline;
+pub use self::warmup::{WarmupError, WarmupReport};
+use self::watch::SelfWatch;
 #[cfg(feature = "nip57")]
 pub use self::zapper::{ZapDetails, ZapEntity};
 
@@ -57,6 +115,9 @@ pub enum Error {
     /// Metadata error
     #[error(transparent)]
     Metadata(#[from] MetadataError),
+    /// Database error
+    #[error(transparent)]
+    Database(#[from] DatabaseError),
     /// Signer not configured
     #[error("signer not configured")]
     SignerNotConfigured,
@@ -72,6 +133,14 @@ pub enum Error {
     #[cfg(feature = "nip57")]
     #[error(transparent)]
     LnUrlPay(#[from] lnurl_pay::Error),
+    /// LNURL Pay HTTP error
+    #[cfg(feature = "nip57")]
+    #[error(transparent)]
+    LnUrlHttp(#[from] nostr::util::http::Error),
+    /// Reqwest error
+    #[cfg(feature = "nip57")]
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
     /// Event not found
     #[error("event not found: {0}")]
     EventNotFound(EventId),
@@ -81,15 +150,89 @@ pub enum Error {
     /// Metadata not found
     #[error("metadata not found")]
     MetadataNotFound,
+    /// Event kind is not replaceable
+    #[error("kind {0} is not replaceable")]
+    NotReplaceable(Kind),
+    /// Mention not resolved
+    #[error("mention not resolved")]
+    MentionNotResolved,
+    /// Event tags have lint errors (see [`Options::strict_send`])
+    #[error("invalid tags: {}", .0.iter().map(|w| w.to_string()).collect::<Vec<_>>().join(", "))]
+    InvalidTags(Vec<LintWarning>),
+    /// NIP51 error
+    #[error(transparent)]
+    NIP51(#[from] nostr::nips::nip51::Error),
+    /// NIP58 error
+    #[error(transparent)]
+    NIP58(#[from] nostr::nips::nip58::Error),
+    /// Interest list not found or empty
+    #[error("interests not found")]
+    InterestsNotFound,
+    /// JSON error
+    #[error(transparent)]
+    Json(#[from] nostr::serde_json::Error),
+    /// Settings must serialize to a JSON object
+    #[error("settings must be a JSON object")]
+    SettingsNotObject,
+    /// Timeout
+    #[error("timeout")]
+    Timeout,
+    /// Relay group not found
+    #[error("relay group not found: {0}")]
+    RelayGroupNotFound(String),
+    /// Delivery log not set (see [`Client::with_delivery_log`])
+    #[error("delivery log not set")]
+    DeliveryLogNotSet,
+    /// No delivery receipt for the event
+    #[error("delivery receipt not found: {0}")]
+    DeliveryReceiptNotFound(EventId),
+    /// No connected relay supports NIP50 search
+    #[error("no relay supports NIP50 search")]
+    SearchNotSupported,
+    /// Relays online but the offline queue couldn't be sent: the event has been queued after it
+    #[error("event {event_id} queued: impossible to send the offline queue: {error}")]
+    EventQueued {
+        /// ID of the queued event
+        event_id: EventId,
+        /// Error of the offline queue sending
+        error: Box<Error>,
+    },
+    /// Event of a set not accepted by any relay (see [`PublishSetStrategy::AllOrNone`])
+    #[error("event set publishing failed at event {failed}")]
+    PublishSetFailed {
+        /// ID of the failed event
+        failed: EventId,
+        /// Results of the events sent, including the failed one
+        results: Vec<PublishResult>,
+    },
 }
 
 /// Nostr client
+///
+/// Cloning is cheap: all the clones share the same relay pool, signer, zapper and internal state,
+/// so the [`Client`] can be cloned and moved to every task that needs it (no need of `Arc<Client>`).
+///
+/// Changes made through a clone (i.e. [`Client::set_signer`], [`Client::update_difficulty`],
+/// [`Client::update_min_pow_difficulty`], relays added or removed) are visible to all the other clones.
+/// All the other [`Options`] are fixed when the client is built.
+///
+/// [`Client::shutdown`] shuts down the shared relay pool, so it affects all the clones.
+///
+/// To build a handle with a different signer on the same connections, check [`Client::new_from_pool`].
 #[derive(Debug, Clone)]
 pub struct Client {
     pool: RelayPool,
     signer: Arc<RwLock<Option<NostrSigner>>>,
     #[cfg(feature = "nip57")]
     zapper: Arc<RwLock<Option<Arc<DynNostrZapper>>>>,
+    offline_queue: OfflineQueue,
+    self_watch: SelfWatch,
+    auth: AuthSession,
+    groups: RelayGroups,
+    #[cfg(feature = "nip44")]
+    settings: SettingsSync,
+    freshness: Option<FreshnessTracker>,
+    delivery: Option<DeliveryLog>,
     opts: Options,
 }
 
@@ -137,22 +280,230 @@ impl Client {
         ClientBuilder::new().signer(signer).opts(opts).build()
     }
 
+    /// Create a new [`Client`] handle on an existing [`RelayPool`]
+    ///
+    /// The handle shares with the other users of the pool the relays, the WebSocket connections,
+    /// the subscriptions, the notifications and the database:
+    /// relays added or removed and [`Client::shutdown`] affect all of them.
+    ///
+    /// The signer and all the other client state (options, offline queue, groups, ...) are **NOT** shared:
+    /// use [`Client::clone`] to get a handle that shares everything.
+    ///
+    /// The `opts` (proxy, min POW, ...) are applied to the relays added through this handle.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use nostr_sdk::prelude::*;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let client = Client::new(Keys::generate());
+    /// client.add_relay("wss://relay.damus.io").await.unwrap();
+    ///
+    /// // Publish with another identity through the same connections
+    /// let other = Client::new_from_pool(client.pool(), Keys::generate(), Options::default());
+    /// # }
+    /// ```
+    pub fn new_from_pool<S>(pool: RelayPool, signer: S, opts: Options) -> Self
+    where
+        S: Into<NostrSigner>,
+    {
+        Self::compose(pool, ClientBuilder::new().signer(signer).opts(opts))
+    }
+
     /// Compose [`Client`] from [`ClientBuilder`]
     pub fn from_builder(builder: ClientBuilder) -> Self {
+        let pool: RelayPool =
+            RelayPool::with_database(builder.get_pool_opts(), builder.database.clone());
+        Self::compose(pool, builder)
+    }
+
+    fn compose(pool: RelayPool, builder: ClientBuilder) -> Self {
         Self {
-            pool: RelayPool::with_database(builder.opts.pool, builder.database),
+            pool,
             signer: Arc::new(RwLock::new(builder.signer)),
             #[cfg(feature = "nip57")]
             zapper: Arc::new(RwLock::new(builder.zapper)),
+            offline_queue: OfflineQueue::default(),
+            self_watch: SelfWatch::default(),
+            auth: AuthSession::default(),
+            groups: RelayGroups::default(),
+            #[cfg(feature = "nip44")]
+            settings: SettingsSync::default(),
+            freshness: None,
+            delivery: None,
             opts: builder.opts,
         }
     }
 
+    /// Track the EOSE sent by relays for each filter, to know if the local data is fresh
+    ///
+    /// If [`FreshnessTracker::cache_max_age`] is set, fetches of fresh filters are served from the database.
+    ///
+    /// **Must be called inside the async runtime**: this method spawn the notifications listener.
+    pub fn with_freshness_tracker(mut self, tracker: FreshnessTracker) -> Self {
+        self.freshness = Some(tracker.clone());
+
+        let pool: RelayPool = self.pool.clone();
+        let _ = thread::spawn(async move {
+            let mut notifications = pool.notifications();
+
+            for (url, relay) in pool.relays().await.into_iter() {
+                tracker.set_connected(url, relay.is_connected().await);
+            }
+
+            while let Ok(notification) = notifications.recv().await {
+                match notification {
+                    RelayPoolNotification::RelayStatus { relay_url, status } => {
+                        tracker.set_connected(relay_url, status == RelayStatus::Connected);
+                    }
+                    RelayPoolNotification::Message {
+                        relay_url,
+                        message: RelayMessage::EndOfStoredEvents(subscription_id),
+                    } => {
+                        // Only pool subscriptions: the fetches are recorded by the client
+                        if let Some(filters) = pool.subscription(&subscription_id).await {
+                            let now = Timestamp::now();
+                            for filter in filters.iter() {
+                                tracker.record_eose(
+                                    &subscription_id,
+                                    filter,
+                                    relay_url.clone(),
+                                    now,
+                                );
+                            }
+                        }
+                    }
+                    RelayPoolNotification::Shutdown => break,
+                    _ => (),
+                }
+            }
+        });
+
+        self
+    }
+
+    /// Get the [`FreshnessTracker`], if set
+    pub fn freshness_tracker(&self) -> Option<&FreshnessTracker> {
+        self.freshness.as_ref()
+    }
+
+    /// Record a [`DeliveryReceipt`] for every event accepted by a relay (`OK` with `true` status)
+    ///
+    /// Disabled by default: when not set, nothing is recorded and no listener is spawned.
+    /// Check [`Client::attest_delivery`] to verify the deliveries.
+    ///
+    /// **Must be called inside the async runtime**: this method spawn the notifications listener.
+    pub fn with_delivery_log(mut self, log: DeliveryLog) -> Self {
+        self.delivery = Some(log.clone());
+
+        // Subscribe before spawning, to not miss the notifications sent in the meantime.
+        // The listener doesn't hold the pool: it stops at pool shutdown.
+        let mut notifications = self.pool.notifications();
+        let _ = thread::spawn(async move {
+            while let Ok(notification) = notifications.recv().await {
+                match notification {
+                    RelayPoolNotification::Message {
+                        relay_url,
+                        message:
+                            message @ RelayMessage::Ok {
+                                event_id,
+                                status: true,
+                                ..
+                            },
+                    } => {
+                        log.record(DeliveryReceipt {
+                            event_id,
+                            relay_url,
+                            accepted_at: Timestamp::now(),
+                            ok: message.as_json(),
+                            relay_pubkey: None,
+                            retrievable: None,
+                        });
+                    }
+                    RelayPoolNotification::Shutdown => break,
+                    _ => (),
+                }
+            }
+        });
+
+        self
+    }
+
+    /// Get the [`DeliveryLog`], if set
+    pub fn delivery_log(&self) -> Option<&DeliveryLog> {
+        self.delivery.as_ref()
+    }
+
+    /// Verify the delivery of an event and build a signed attestation
+    ///
+    /// For every receipt of the event whose relay advertises a public key in the NIP11 document,
+    /// query the relay to confirm that the event is retrievable (see [`DeliveryReceipt::retrievable`]).
+    ///
+    /// Return the attestation: a parameterized replaceable event (kind `30078`) with the receipts as content,
+    /// signed with the client signer but **NOT** published, to archive or publish it as needed.
+    pub async fn attest_delivery(
+        &self,
+        event_id: EventId,
+        timeout: Duration,
+    ) -> Result<Event, Error> {
+        let log: &DeliveryLog = self.delivery.as_ref().ok_or(Error::DeliveryLogNotSet)?;
+
+        let receipts: Vec<DeliveryReceipt> = log.receipts(&event_id);
+        if receipts.is_empty() {
+            return Err(Error::DeliveryReceiptNotFound(event_id));
+        }
+
+        let filter: Filter = Filter::new().id(event_id);
+        for receipt in receipts.iter() {
+            let relay: Relay = match self.pool.relay(&receipt.relay_url).await {
+                Ok(relay) => relay,
+                Err(_) => continue,
+            };
+
+            // Resolve the relay public key
+            if receipt.relay_pubkey.is_none() {
+                #[cfg(feature = "nip11")]
+                match relay.document().await.pubkey {
+                    Some(pubkey) => log.set_relay_pubkey(&event_id, &receipt.relay_url, pubkey),
+                    None => continue,
+                }
+                #[cfg(not(feature = "nip11"))]
+                continue;
+            }
+
+            let retrievable: bool = relay
+                .get_events_of(vec![filter.clone()], timeout, FilterOptions::ExitOnEOSE)
+                .await
+                .map(|events| !events.is_empty())
+                .unwrap_or_default();
+            log.set_retrievable(&event_id, &receipt.relay_url, retrievable);
+        }
+
+        let receipts: Vec<DeliveryReceipt> = log.receipts(&event_id);
+        self.sign_event_builder(delivery::attestation(event_id, &receipts))
+            .await
+    }
+
     /// Update default difficulty for new [`Event`]
+    ///
+    /// Propagated to all the clones of this client.
     pub fn update_difficulty(&self, difficulty: u8) {
         self.opts.update_difficulty(difficulty);
     }
 
+    /// Update minimum POW difficulty for received events
+    ///
+    /// Applied to the relays already added and propagated to all the clones of this client.
+    pub async fn update_min_pow_difficulty(&self, difficulty: u8) {
+        // Update the options first: a relay added concurrently reads the new value
+        // or is already in the pool
+        self.opts.update_min_pow_difficulty(difficulty);
+        for relay in self.pool.relays().await.into_values() {
+            relay.opts().update_pow_difficulty(difficulty);
+        }
+    }
+
     /// Get current nostr signer
     ///
     /// Rise error if it not set.
@@ -162,6 +513,8 @@ impl Client {
     }
 
     /// Set nostr signer
+    ///
+    /// Propagated to all the clones of this client.
     pub async fn set_signer(&self, signer: Option<NostrSigner>) {
         let mut s = self.signer.write().await;
         *s = signer;
@@ -223,6 +576,8 @@ impl Client {
     }
 
     /// Completely shutdown [`Client`]
+    ///
+    /// The relay pool is shared, so also all the clones of this client are shut down.
     pub async fn shutdown(self) -> Result<(), Error> {
         Ok(self.pool.shutdown().await?)
     }
@@ -274,19 +629,41 @@ impl Client {
         U: TryIntoUrl,
         pool::Error: From<<U as TryIntoUrl>::Err>,
     {
+        let url: Url = url.try_into_url().map_err(pool::Error::from)?;
+        let opts: RelayOptions = self.default_relay_opts();
+        let added: bool = self.internal_add_relay(url.clone(), opts).await?;
+
+        // Catch up with a concurrent `Client::update_min_pow_difficulty`
+        if added {
+            self.refresh_min_pow_difficulty(url).await?;
+        }
+
+        Ok(added)
+    }
+
+    async fn refresh_min_pow_difficulty(&self, url: Url) -> Result<(), Error> {
+        let relay: Relay = self.pool.relay(url).await?;
+        relay
+            .opts()
+            .update_pow_difficulty(self.opts.get_min_pow_difficulty());
+        Ok(())
+    }
+
+    /// Relay options built from client [`Options`]
+    fn default_relay_opts(&self) -> RelayOptions {
         let opts: RelayOptions = RelayOptions::new();
 
         // Set proxy
         #[cfg(not(target_arch = "wasm32"))]
-        let opts: RelayOptions = opts.proxy(self.opts.proxy);
+        let opts: RelayOptions = match (self.opts.proxy, self.opts.proxy_auth.clone()) {
+            (Some(proxy), Some(auth)) => opts.proxy_with_auth(proxy, auth),
+            (proxy, _) => opts.proxy(proxy),
+        };
 
         // Set min POW difficulty and limits
-        let opts: RelayOptions = opts
-            .pow(self.opts.get_min_pow_difficulty())
-            .limits(self.opts.relay_limits);
-
-        // Add relay
-        self.add_relay_with_opts(url, opts).await
+        opts.pow(self.opts.get_min_pow_difficulty())
+            .limits(self.opts.relay_limits)
+            .health_thresholds(self.opts.relay_health_thresholds)
     }
 
     /// Add new relay with custom [`RelayOptions`]
@@ -320,7 +697,18 @@ impl Client {
         U: TryIntoUrl,
         pool::Error: From<<U as TryIntoUrl>::Err>,
     {
-        Ok(self.pool.add_relay(url, opts).await?)
+        let url: Url = url.try_into_url().map_err(pool::Error::from)?;
+        self.internal_add_relay(url, opts).await
+    }
+
+    async fn internal_add_relay(&self, url: Url, opts: RelayOptions) -> Result<bool, Error> {
+        let added: bool = self.pool.add_relay(url, opts).await?;
+
+        if self.opts.get_auth_policy().is_enabled() && self.auth.start_listening() {
+            self.spawn_auth_listener();
+        }
+
+        Ok(added)
     }
 
     /// Add multiple relays
@@ -338,6 +726,123 @@ impl Client {
         Ok(())
     }
 
+    /// Add relay group
+    ///
+    /// Relays of the group not already in the pool are added (remember to call `client.connect()`!).
+    /// The group flags are added to the flags of all its relays.
+    /// If a group with the same name already exists, it's replaced.
+    pub async fn add_relay_group(&self, group: RelayGroup) -> Result<(), Error> {
+        for url in group.relays.iter() {
+            let opts: RelayOptions = self.default_relay_opts().flags(group.flags);
+            if !self.add_relay_with_opts(url.clone(), opts).await? {
+                let relay: Relay = self.relay(url.clone()).await?;
+                relay.flags().add(group.flags);
+            }
+        }
+        self.groups.insert(group).await;
+        Ok(())
+    }
+
+    /// Get names of the relay groups
+    pub async fn relay_groups(&self) -> Vec<String> {
+        self.groups.names().await
+    }
+
+    /// Bootstrap relays of a first-run client
+    ///
+    /// If a public key is available (see [`BootstrapOptions::public_key`]), connect to the discovery relays,
+    /// look up the user relay list (kind 10002, falling back to the relays in the kind 3 content) and import the found relays.
+    /// Discovery relays not part of the imported ones are removed at the end.
+    /// If no public key is available or no relay list is found, install [`BootstrapOptions::default_relays`].
+    ///
+    /// All the relays are added with the client [`Options`] (proxy included).
+    /// The routine can be cancelled by dropping the future: relays already imported are kept.
+    pub async fn bootstrap(&self, opts: BootstrapOptions) -> Result<BootstrapReport, Error> {
+        let public_key: Option<PublicKey> = match opts.public_key {
+            Some(public_key) => Some(public_key),
+            None => match self.signer().await {
+                Ok(signer) => Some(signer.public_key().await?),
+                Err(_) => None,
+            },
+        };
+
+        let mut source: BootstrapSource = BootstrapSource::Defaults;
+        let mut relays: Vec<(Url, Option<RelayMetadata>)> = Vec::new();
+        let mut added_discovery_relays: Vec<Url> = Vec::new();
+
+        if let Some(public_key) = public_key {
+            // Add and connect discovery relays
+            for url in opts.discovery_relays.iter() {
+                if self.add_relay(url.clone()).await? {
+                    added_discovery_relays.push(url.clone());
+                }
+                self.connect_relay(url.clone()).await?;
+            }
+
+            // Look up the relay list
+            let filter: Filter = Filter::new()
+                .author(public_key)
+                .kinds([Kind::RelayList, Kind::ContactList]);
+            let events: Vec<Event> = self
+                .get_events_from(
+                    opts.discovery_relays.clone(),
+                    vec![filter],
+                    Some(opts.timeout),
+                )
+                .await?;
+
+            let latest = |kind: Kind| {
+                events
+                    .iter()
+                    .filter(|e| e.kind == kind)
+                    .max_by_key(|e| e.created_at)
+            };
+
+            if let Some(event) = latest(Kind::RelayList) {
+                relays = bootstrap::relays_from_relay_list(event);
+                source = BootstrapSource::RelayList;
+            }
+
+            if relays.is_empty() {
+                if let Some(event) = latest(Kind::ContactList) {
+                    relays = bootstrap::relays_from_contact_list(event);
+                    source = BootstrapSource::ContactList;
+                }
+            }
+        }
+
+        if relays.is_empty() {
+            relays = opts.default_relays.into_iter().map(|u| (u, None)).collect();
+            source = BootstrapSource::Defaults;
+        }
+
+        // Import relays
+        for (url, metadata) in relays.iter() {
+            let relay_opts: RelayOptions = match metadata {
+                Some(RelayMetadata::Read) => self.default_relay_opts().write(false),
+                Some(RelayMetadata::Write) => self.default_relay_opts().read(false),
+                None => self.default_relay_opts(),
+            };
+            self.add_relay_with_opts(url.clone(), relay_opts).await?;
+            self.connect_relay(url.clone()).await?;
+        }
+
+        // Remove discovery relays not otherwise needed
+        let mut removed_discovery_relays: Vec<Url> = Vec::new();
+        for url in added_discovery_relays.into_iter() {
+            if !relays.iter().any(|(u, ..)| u == &url) {
+                self.remove_relay(url.clone()).await?;
+                removed_discovery_relays.push(url);
+            }
+        }
+
+        Ok(BootstrapReport {
+            source,
+            relays,
+            removed_discovery_relays,
+        })
+    }
+
     /// Disconnect and remove relay
     ///
     /// # Example
@@ -421,6 +926,10 @@ impl Client {
 
     /// Connect relays
     ///
+    /// Non-blocking: the connections are established in background,
+    /// unless a connection timeout is set in [`Options`].
+    /// Use [`Client::warmup`] to wait for the relays to be connected.
+    ///
     /// # Example
     /// ```rust,no_run
     /// use nostr_sdk::prelude::*;
@@ -436,6 +945,62 @@ impl Client {
         self.pool.connect(self.opts.connection_timeout).await;
     }
 
+    /// Connect relays and wait up to `timeout` for them to be connected
+    ///
+    /// Same as [`Client::connect`], adding the blocking wait: return as soon as every relay is connected
+    /// or has failed its connection attempt.
+    /// Succeed if at least one relay is connected, otherwise return [`WarmupError::AllRelaysFailed`].
+    pub async fn warmup(&self, timeout: Duration) -> Result<WarmupReport, WarmupError> {
+        let relays: HashMap<Url, Relay> = self.pool.relays().await;
+
+        if relays.is_empty() {
+            return Err(WarmupError::NoRelays);
+        }
+
+        self.connect().await;
+
+        time::timeout(Some(timeout), async {
+            loop {
+                let mut pending: bool = false;
+                for relay in relays.values() {
+                    if warmup::is_pending(relay.status().await) {
+                        pending = true;
+                        break;
+                    }
+                }
+
+                if !pending {
+                    break;
+                }
+
+                thread::sleep(warmup::POLL_INTERVAL).await;
+            }
+        })
+        .await;
+
+        let mut report: WarmupReport = WarmupReport::default();
+        for (url, relay) in relays.into_iter() {
+            let status: RelayStatus = relay.status().await;
+            if status == RelayStatus::Connected {
+                report.connected.push(url);
+            } else if warmup::is_pending(status) {
+                report.timed_out.push(url);
+            } else {
+                let error: RelayError = match relay.last_connection_error().await {
+                    Some(e) => RelayError::ConnectionFailed(e),
+                    None => RelayError::NotConnected,
+                };
+                report.failed.push((url, error));
+            }
+        }
+
+        if report.connected.is_empty() {
+            return Err(WarmupError::AllRelaysFailed(report));
+        }
+
+        Ok(report)
+    }
+
     /// Disconnect from all relays
     ///
     /// # Example
@@ -463,6 +1028,50 @@ impl Client {
         self.pool.subscription(id).await
     }
 
+    /// Get activity of subscriptions, per relay
+    ///
+    /// Useful to debug subscriptions: the timestamp of the last received event is used to evict
+    /// the least recently active subscriptions when the relay limit is reached (see [`SubscriptionLimitPolicy`](crate::SubscriptionLimitPolicy)).
+    pub async fn subscriptions_activity(
+        &self,
+    ) -> HashMap<Url, HashMap<SubscriptionId, SubscriptionActivity>> {
+        self.pool.subscriptions_activity().await
+    }
+
+    /// Get the number of entries and the approximate size of the internal collections, per component
+    ///
+    /// Check [`MemoryLimits`](nostr_relay_pool::MemoryLimits) for the ceilings of the relay pool collections.
+    pub async fn memory_report(&self) -> MemoryReport {
+        let mut report: MemoryReport = self.pool.memory_report().await;
+
+        let (entries, bytes) = self.offline_queue.usage().await;
+        report.insert(
+            "sdk.offline_queue",
+            MemoryUsage::new(entries, Some(self.opts.get_offline_queue_capacity()), bytes),
+        );
+
+        if let Some(freshness) = &self.freshness {
+            let (entries, max, bytes) = freshness.usage();
+            report.insert("sdk.freshness", MemoryUsage::new(entries, Some(max), bytes));
+        }
+
+        if let Some(delivery) = &self.delivery {
+            let (entries, max, bytes) = delivery.usage();
+            report.insert("sdk.delivery", MemoryUsage::new(entries, Some(max), bytes));
+        }
+
+        report
+    }
+
+    /// Get the acknowledgement (`OK` message) status of a sent event, per relay
+    ///
+    /// Pending acknowledgements are marked as [`AckStatus::TimedOut`] and notified with
+    /// [`RelayPoolNotification::AckTimeout`] after [`RelayPoolOptions::ack_timeout`](nostr_relay_pool::RelayPoolOptions::ack_timeout).
+    #[inline]
+    pub fn ack_status(&self, event_id: &EventId) -> HashMap<Url, AckStatus> {
+        self.pool.ack_status(event_id)
+    }
+
     /// Subscribe to filters
     ///
     /// This method create a new subscription. None of the previous subscriptions will be edited/closed when you call this!
@@ -569,6 +1178,111 @@ impl Client {
         self.pool.unsubscribe_all(opts).await;
     }
 
+    /// Wait for the first event matching the filter
+    ///
+    /// Subscribe to the filter until a matching event is received or the `timeout` expires.
+    /// Events already saved into the database aren't notified again: if one matches the filter, it's returned immediately.
+    ///
+    /// Return [`Error::Timeout`] if no event is received in time (or if the client is shutdown).
+    pub async fn wait_for_event(&self, filter: Filter, timeout: Duration) -> Result<Event, Error> {
+        let id = SubscriptionId::generate();
+
+        // Listen before subscribing, to not miss the event
+        let notifications = self.notifications();
+        self.subscribe_with_id(id.clone(), vec![filter.clone()], None)
+            .await;
+
+        let res = self
+            .wait_for_matching_event(notifications, None, &filter, timeout)
+            .await;
+
+        self.unsubscribe(id).await;
+
+        res
+    }
+
+    /// Wait for the first event matching the filter from a specific relay
+    ///
+    /// Check [`Client::wait_for_event`].
+    pub async fn wait_for_event_from(
+        &self,
+        relay: Url,
+        filter: Filter,
+        timeout: Duration,
+    ) -> Result<Event, Error> {
+        let relay: Relay = self.relay(relay).await?;
+        let id = SubscriptionId::generate();
+        let send_opts: RelaySendOptions = self.opts.get_wait_for_subscription();
+
+        // Listen before subscribing, to not miss the event
+        let notifications = self.notifications();
+        let opts: SubscribeOptions = SubscribeOptions::default().send_opts(send_opts);
+        relay
+            .subscribe_with_id(id.clone(), vec![filter.clone()], opts)
+            .await?;
+
+        let relay_url: Url = relay.url();
+        let res = self
+            .wait_for_matching_event(notifications, Some(&relay_url), &filter, timeout)
+            .await;
+
+        if let Err(e) = relay.unsubscribe(id, send_opts).await {
+            tracing::error!("Impossible to unsubscribe from {relay_url}: {e}");
+        }
+
+        res
+    }
+
+    async fn wait_for_matching_event(
+        &self,
+        mut notifications: broadcast::Receiver<RelayPoolNotification>,
+        relay_url: Option<&Url>,
+        filter: &Filter,
+        timeout: Duration,
+    ) -> Result<Event, Error> {
+        // Already saved events are not notified
+        let database = self.database();
+        for event in database
+            .query(vec![filter.clone()], Order::Desc)
+            .await?
+            .into_iter()
+        {
+            match relay_url {
+                Some(url) => {
+                    let seen_on: Option<HashSet<Url>> =
+                        database.event_seen_on_relays(event.id).await?;
+                    if seen_on.map_or(false, |relays| relays.contains(url)) {
+                        return Ok(event);
+                    }
+                }
+                None => return Ok(event),
+            }
+        }
+
+        // Don't match the subscription ID:
+        // if the event is received by another subscription first, it's notified only once
+        time::timeout(Some(timeout), async {
+            loop {
+                match notifications.recv().await {
+                    Ok(RelayPoolNotification::Event {
+                        relay_url: url,
+                        event,
+                        ..
+                    }) => {
+                        if relay_url.map_or(true, |u| u == &url) && filter.match_event(&event) {
+                            return Ok(*event);
+                        }
+                    }
+                    Ok(RelayPoolNotification::Shutdown)
+                    | Err(broadcast::error::RecvError::Closed) => return Err(Error::Timeout),
+                    Ok(..) | Err(broadcast::error::RecvError::Lagged(..)) => (),
+                }
+            }
+        })
+        .await
+        .ok_or(Error::Timeout)?
+    }
+
     /// Get events of filters
     ///
     /// If timeout is set to `None`, the default from [`Options`] will be used.
@@ -616,9 +1330,52 @@ impl Client {
             Some(t) => t,
             None => self.opts.timeout,
         };
+
+        if let (Some(tracker), FilterOptions::ExitOnEOSE) = (&self.freshness, opts) {
+            if let Some(max_age) = tracker.get_cache_max_age() {
+                if !filters.is_empty() && filters.iter().all(|f| tracker.is_fresh(f, max_age)) {
+                    return Ok(self.database().query(filters, Order::Desc).await?);
+                }
+            }
+
+            let relays = self.pool.relays().await;
+            let policy: FetchPolicy = FetchPolicy::new().hard_deadline(timeout);
+            let output: FetchOutput = self
+                .pool
+                .get_events_with_policy(relays.into_keys(), filters.clone(), policy)
+                .await?;
+            tracker.record_fetch(&filters, &output.report);
+            return Ok(output.events);
+        }
+
         Ok(self.pool.get_events_of(filters, timeout, opts).await?)
     }
 
+    /// Get events of filters from **all relays**, grouped by filter
+    ///
+    /// The filters are sent in a single REQ and the returned collections are aligned with them:
+    /// `result[i]` contains the events matching `filters[i]`.
+    /// Filters may overlap, so an event can be included in more than one collection.
+    ///
+    /// If timeout is set to `None`, the default from [`Options`] will be used.
+    pub async fn get_events_map(
+        &self,
+        filters: Vec<Filter>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Vec<Event>>, Error> {
+        let timeout: Duration = timeout.unwrap_or(self.opts.timeout);
+        let relays = self.pool.relays().await;
+        Ok(self
+            .pool
+            .get_events_map_from(
+                relays.into_keys(),
+                filters,
+                timeout,
+                FilterOptions::ExitOnEOSE,
+            )
+            .await?)
+    }
+
     /// Get events of filters from specific relays
     ///
     /// Get events both from **local database** and **relays**
@@ -642,6 +1399,106 @@ impl Client {
             .await?)
     }
 
+    /// Full-text search (NIP50)
+    ///
+    /// Query only the relays that advertise [NIP50](https://github.com/nostr-protocol/nips/blob/master/50.md)
+    /// support in their NIP11 document, skipping the others.
+    /// Return [`Error::SearchNotSupported`] if none of them supports it.
+    ///
+    /// The query syntax, the matching and the ranking depend on the relay implementation,
+    /// so the same query may return different results on different relays.
+    /// The results of all the relays are merged and capped to `limit`: the relevance order is not preserved.
+    #[cfg(feature = "nip11")]
+    pub async fn search<S>(
+        &self,
+        query: S,
+        kinds: Vec<Kind>,
+        limit: u32,
+        timeout: Duration,
+    ) -> Result<Vec<Event>, Error>
+    where
+        S: Into<String>,
+    {
+        let mut urls: Vec<Url> = Vec::new();
+        for (url, relay) in self.pool.relays().await.into_iter() {
+            let supported: bool = relay
+                .document()
+                .await
+                .supported_nips
+                .map_or(false, |nips| nips.contains(&50));
+            if relay.is_connected().await && supported {
+                urls.push(url);
+            } else {
+                tracing::debug!("Skipping {url} for search: NIP50 not supported");
+            }
+        }
+
+        if urls.is_empty() {
+            return Err(Error::SearchNotSupported);
+        }
+
+        let filter: Filter = Filter::new()
+            .search(query)
+            .kinds(kinds)
+            .limit(limit as usize);
+        let mut events: Vec<Event> = self
+            .pool
+            .get_events_from(urls, vec![filter], timeout, FilterOptions::ExitOnEOSE)
+            .await?;
+        events.truncate(limit as usize);
+        Ok(events)
+    }
+
+    /// Search profiles (NIP50)
+    ///
+    /// Same as [`Client::search`], for [`Kind::Metadata`] events.
+    /// Keep only the latest metadata of each author and skip the invalid ones.
+    #[cfg(feature = "nip11")]
+    pub async fn search_profiles<S>(
+        &self,
+        query: S,
+        limit: u32,
+        timeout: Duration,
+    ) -> Result<Vec<Metadata>, Error>
+    where
+        S: Into<String>,
+    {
+        let events: Vec<Event> = self
+            .search(query, vec![Kind::Metadata], limit, timeout)
+            .await?;
+
+        let mut latest: HashMap<PublicKey, &Event> = HashMap::with_capacity(events.len());
+        for event in events.iter() {
+            match latest.get(&event.author()) {
+                Some(e) if e.created_at() >= event.created_at() => {}
+                _ => {
+                    latest.insert(event.author(), event);
+                }
+            }
+        }
+
+        Ok(events
+            .iter()
+            .filter(|event| matches!(latest.get(&event.author()), Some(e) if e.id() == event.id()))
+            .filter_map(|event| Metadata::from_event(event).ok())
+            .collect())
+    }
+
+    /// Get events of filters from **all relays**, following a [`FetchPolicy`]
+    ///
+    /// Return early with partial results (check [`FetchPolicy::soft_deadline`]) and a per-relay report.
+    pub async fn get_events_with_policy(
+        &self,
+        filters: Vec<Filter>,
+        policy: FetchPolicy,
+    ) -> Result<FetchOutput, Error> {
+        let relays = self.pool.relays().await;
+        Ok(self
+            .pool
+            .get_events_with_policy(relays.into_keys(), filters, policy)
+            .await?)
+    }
+
     /// Send client message to **all relays**
     pub async fn send_msg(&self, msg: ClientMessage) -> Result<(), Error> {
         let opts: RelaySendOptions = self.opts.get_wait_for_send();
@@ -687,34 +1544,704 @@ impl Client {
     ///
     /// This method will wait for the `OK` message from the relay.
     /// If you not want to wait for the `OK` message, use `send_msg` method instead.
+    ///
+    /// If the offline queue is enabled (see [`Options::offline_queue_capacity`]) and all relays are disconnected,
+    /// the event is queued and sent when a relay reconnects. Ephemeral events are never queued.
+    /// If relays are online but the queued events can't be sent, the event is queued after them
+    /// and [`Error::EventQueued`] is returned.
     pub async fn send_event(&self, event: Event) -> Result<EventId, Error> {
+        self.check_strict_send(&event)?;
+
+        // Ephemeral events are never queued
+        if self.opts.get_offline_queue_capacity() > 0 && !event.is_ephemeral() {
+            let event_id: EventId = event.id;
+
+            if !self.is_online().await {
+                self.queue_offline_event(event).await;
+                return Ok(event_id);
+            }
+
+            // Send queued events before the new one
+            if let Err(e) = self.flush_offline_queue().await {
+                self.queue_offline_event(event).await;
+                return Err(Error::EventQueued {
+                    event_id,
+                    error: Box::new(e),
+                });
+            }
+        }
+
         let opts: RelaySendOptions = self.opts.get_wait_for_send();
         Ok(self.pool.send_event(event, opts).await?)
     }
 
-    /// Send multiple [`Event`] at once to **all relays**.
-    pub async fn batch_event(
-        &self,
-        events: Vec<Event>,
-        opts: RelaySendOptions,
-    ) -> Result<(), Error> {
-        Ok(self.pool.batch_event(events, opts).await?)
+    /// Compute where an [`Event`] would be sent, without sending it
+    ///
+    /// Use [`Client::execute`] to send the event exactly as planned.
+    pub async fn plan_send(&self, event: &Event) -> SendPlan {
+        let skip_disconnected: bool = self.opts.get_skip_disconnected_relays();
+        let mut relays: Vec<RelaySendPlan> = Vec::new();
+        for (url, relay) in self.pool.relays().await.into_iter() {
+            let reason: SendPlanReason = if !relay.flags().has_write() {
+                SendPlanReason::WriteDisabled
+            } else if skip_disconnected && relay.is_unreachable().await {
+                SendPlanReason::Unreachable
+            } else if relay.send_rate_limited() {
+                SendPlanReason::WriteRateLimited
+            } else {
+                SendPlanReason::Write
+            };
+            relays.push(RelaySendPlan { url, reason });
+        }
+        relays.sort_by(|a, b| a.url.cmp(&b.url));
+
+        let queue_offline: bool = self.opts.get_offline_queue_capacity() > 0
+            && !event.is_ephemeral()
+            && !self.is_online().await;
+
+        SendPlan {
+            event: event.clone(),
+            relays,
+            queue_offline,
+        }
     }
 
-    /// Send event to **specific relays**.
+    /// Execute a [`SendPlan`]
     ///
-    /// This method will wait for the `OK` message from the relay.
-    /// If you not want to wait for the `OK` message, use `send_msg` method instead.
-    pub async fn send_event_to<I, U>(&self, urls: I, event: Event) -> Result<EventId, Error>
-    where
-        I: IntoIterator<Item = U>,
-        U: TryIntoUrl,
-        pool::Error: From<<U as TryIntoUrl>::Err>,
-    {
-        let opts: RelaySendOptions = self.opts.get_wait_for_send();
+    /// The event is sent only to the relays targeted by the plan (or put in the offline queue, if planned).
+    pub async fn execute(&self, plan: SendPlan) -> Result<EventId, Error> {
+        let event_id: EventId = plan.event.id;
+
+        if plan.queue_offline {
+            self.queue_offline_event(plan.event).await;
+            return Ok(event_id);
+        }
+
+        if self.opts.get_offline_queue_capacity() > 0 {
+            // Send queued events before the new one (restored on failure)
+            let _ = self.flush_offline_queue().await;
+        }
+
+        let targets: Vec<Url> = plan.targets().cloned().collect();
+        let opts: RelaySendOptions = self.opts.get_wait_for_send();
+        Ok(self.pool.send_event_to(targets, plan.event, opts).await?)
+    }
+
+    /// Get number of events waiting in the offline queue
+    pub async fn offline_queue_len(&self) -> usize {
+        self.offline_queue.len().await
+    }
+
+    /// Clear the offline queue, returning the events that were waiting to be sent
+    pub async fn clear_offline_queue(&self) -> Vec<Event> {
+        self.offline_queue.take().await
+    }
+
+    /// Check the tags lint errors, if [`Options::strict_send`] is enabled
+    fn check_strict_send(&self, event: &Event) -> Result<(), Error> {
+        if self.opts.get_strict_send() {
+            let errors: Vec<LintWarning> =
+                event.lint().into_iter().filter(|w| w.is_error()).collect();
+            if !errors.is_empty() {
+                return Err(Error::InvalidTags(errors));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check if at least one relay is connected
+    async fn is_online(&self) -> bool {
+        for relay in self.pool.relays().await.into_values() {
+            if relay.is_connected().await {
+                return true;
+            }
+        }
+        false
+    }
+
+    async fn queue_offline_event(&self, event: Event) {
+        let capacity: usize = self.opts.get_offline_queue_capacity();
+        let event_id: EventId = event.id;
+        if self.offline_queue.push(event, capacity).await {
+            tracing::debug!("All relays disconnected: queued event {event_id}");
+            self.spawn_offline_queue_listener();
+        }
+    }
+
+    /// Send queued events, in FIFO order
+    async fn flush_offline_queue(&self) -> Result<(), Error> {
+        let _guard = self.offline_queue.lock_flush().await;
+
+        let events: Vec<Event> = self.offline_queue.take().await;
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let opts: RelaySendOptions = self.opts.get_wait_for_send();
+        match self.pool.batch_event(events.clone(), opts).await {
+            Ok(..) => {
+                tracing::debug!("Sent {} queued events", events.len());
+                Ok(())
+            }
+            Err(e) => {
+                let capacity: usize = self.opts.get_offline_queue_capacity();
+                self.offline_queue.restore(events, capacity).await;
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Flush the offline queue when a relay reconnects
+    fn spawn_offline_queue_listener(&self) {
+        if !self.offline_queue.set_listening() {
+            return;
+        }
+
+        let client: Client = self.clone();
+        let _ = thread::spawn(async move {
+            let mut notifications = client.pool.notifications();
+            while let Ok(notification) = notifications.recv().await {
+                match notification {
+                    RelayPoolNotification::RelayStatus {
+                        status: RelayStatus::Connected,
+                        ..
+                    } => {
+                        if let Err(e) = client.flush_offline_queue().await {
+                            tracing::error!("Impossible to send queued events: {e}");
+                        }
+
+                        if client.offline_queue.len().await == 0 {
+                            break;
+                        }
+                    }
+                    RelayPoolNotification::Shutdown => break,
+                    _ => (),
+                }
+            }
+
+            client.offline_queue.unset_listening();
+
+            // Check if an event was queued in the meantime
+            if client.offline_queue.len().await > 0 {
+                client.spawn_offline_queue_listener();
+            }
+        });
+    }
+
+    /// Watch the user's own replaceable events of the given kinds
+    ///
+    /// Maintain a dedicated subscription on the events authored by the signer public key and
+    /// emit a [`RelayPoolNotification::SelfUpdated`] only when the newest known version of a kind changes.
+    /// Duplicates received from multiple relays are coalesced and the subscription is restored on reconnect.
+    ///
+    /// Calling this method again adds the kinds to the watched ones.
+    pub async fn watch_self<I>(&self, kinds: I) -> Result<SubscriptionId, Error>
+    where
+        I: IntoIterator<Item = Kind>,
+    {
+        let kinds: Vec<Kind> = kinds.into_iter().collect();
+        if let Some(kind) = kinds.iter().find(|k| !k.is_replaceable()) {
+            return Err(Error::NotReplaceable(*kind));
+        }
+
+        let signer = self.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+
+        let (kinds, first) = self.self_watch.add_kinds(kinds).await;
+        let filter: Filter = Filter::new().author(public_key).kinds(kinds);
+
+        // Seed with the versions already known, without notifying them
+        let stored: Vec<Event> = self
+            .database()
+            .query(vec![filter.clone()], Order::Desc)
+            .await?;
+        for event in stored.iter() {
+            self.self_watch.update(event).await;
+        }
+
+        let id: SubscriptionId = SubscriptionId::new("watch-self");
+        self.subscribe_with_id(id.clone(), vec![filter], None).await;
+
+        if first {
+            self.spawn_self_watch_listener(public_key);
+        }
+
+        Ok(id)
+    }
+
+    /// Get a [`LiveFeed`] of events matching the filter
+    ///
+    /// Start from the events stored in the database and continue with the events received from relays.
+    /// Check [`LiveFeed`] for the ordering and deduplication guarantees.
+    pub async fn live_feed(&self, filter: Filter) -> Result<LiveFeed, Error> {
+        self.live_feed_with_opts(filter, LiveFeedOptions::default())
+            .await
+    }
+
+    /// Get a [`LiveFeed`] of events matching the filter, with [`LiveFeedOptions`]
+    pub async fn live_feed_with_opts(
+        &self,
+        filter: Filter,
+        opts: LiveFeedOptions,
+    ) -> Result<LiveFeed, Error> {
+        // Listen notifications before anything else, to not miss live events
+        let mut notifications = self.pool.notifications();
+
+        // Cache
+        let limit: usize = filter.limit.unwrap_or(opts.cache_limit);
+        let cached: Vec<Event> = self
+            .database()
+            .query(vec![filter.clone().limit(limit)], Order::Desc)
+            .await?;
+
+        let id: SubscriptionId = SubscriptionId::generate();
+        let (tx, rx) = mpsc::channel::<FeedItem>(opts.channel_size);
+
+        let client: Client = self.clone();
+        let sub_id: SubscriptionId = id.clone();
+        let _ = thread::spawn(async move {
+            let mut dedup = FeedDedup::new(opts.dedup_capacity);
+
+            for event in cached.into_iter() {
+                if dedup.accept(&event) {
+                    let item = FeedItem {
+                        event,
+                        origin: FeedOrigin::Cache,
+                    };
+                    if tx.send(item).await.is_err() {
+                        return;
+                    }
+                }
+            }
+
+            // Gap-fill: all the events published since the newest cached one, by pages.
+            // Without cached events, only the newest ones (a single page).
+            let (gap_filter, max_pages): (Filter, Option<usize>) = match dedup.newest() {
+                Some(newest) => (filter.clone().since(newest), None),
+                None => (filter.clone(), Some(1)),
+            };
+            let pagination =
+                Pagination::new(client.clone(), gap_filter, limit).timeout(opts.gap_fill_timeout);
+            let mut pages: usize = 0;
+            while max_pages.map_or(true, |max| pages < max) && pagination.has_more().await {
+                let events: Vec<Event> = match pagination.next_page().await {
+                    Ok(events) => events,
+                    Err(e) => {
+                        tracing::error!("Live feed gap-fill failed: {e}");
+                        break;
+                    }
+                };
+                pages += 1;
+
+                // Pages are sorted newest first
+                for event in events.into_iter() {
+                    if dedup.accept(&event) {
+                        let origin: FeedOrigin = client.feed_origin(event.id).await;
+                        if tx.send(FeedItem { event, origin }).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+
+            // Live
+            let live_filter: Filter = match dedup.newest() {
+                Some(newest) => filter.clone().since(newest),
+                None => filter.clone().since(Timestamp::now()),
+            };
+            client
+                .subscribe_with_id(sub_id.clone(), vec![live_filter.clone()], None)
+                .await;
+
+            loop {
+                tokio::select! {
+                    notification = notifications.recv() => match notification {
+                        Ok(RelayPoolNotification::Event {
+                            relay_url,
+                            subscription_id,
+                            event,
+                        }) => {
+                            if subscription_id != sub_id
+                                || !live_filter.match_event(&event)
+                                || !dedup.accept(&event)
+                            {
+                                continue;
+                            }
+
+                            let item = FeedItem {
+                                event: *event,
+                                origin: FeedOrigin::Relay(relay_url),
+                            };
+                            if tx.send(item).await.is_err() {
+                                break;
+                            }
+                        }
+                        Ok(RelayPoolNotification::Shutdown) => return,
+                        Ok(_) => (),
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            tracing::warn!("Live feed lagged: {skipped} events skipped");
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return,
+                    },
+                    _ = tx.closed() => break,
+                }
+            }
+
+            // Feed dropped
+            client.unsubscribe(sub_id).await;
+        });
+
+        Ok(LiveFeed {
+            receiver: rx,
+            subscription_id: id,
+        })
+    }
+
+    /// Get a [`Timeline`] to paginate through the relays history, from the newest to the oldest events
+    ///
+    /// Like [`Client::paginate`], but merging the events of multiple filters.
+    /// Check [`Timeline`] for the pagination details.
+    pub fn timeline(&self, filters: Vec<Filter>, page_size: usize) -> Timeline {
+        Timeline::new(self.clone(), filters, page_size)
+    }
+
+    /// Get a [`Pagination`] to page through the events history, from the newest to the oldest events
+    ///
+    /// Events sharing a timestamp across a page boundary are never skipped.
+    /// Check [`Pagination`] for the pagination details.
+    pub fn paginate(&self, filter: Filter, page_size: usize) -> Pagination {
+        Pagination::new(self.clone(), filter, page_size)
+    }
+
+    async fn feed_origin(&self, event_id: EventId) -> FeedOrigin {
+        match self.database().event_seen_on_relays(event_id).await {
+            Ok(Some(relays)) => match relays.into_iter().next() {
+                Some(url) => FeedOrigin::Relay(url),
+                None => FeedOrigin::Cache,
+            },
+            _ => FeedOrigin::Cache,
+        }
+    }
+
+    /// Subscribe to the hashtags of the user interest list
+    ///
+    /// Fetch the interest list (`kind:10015`) of the signer public key and subscribe to the text notes tagged with its hashtags.
+    ///
+    /// Return the [`SubscriptionId`] and the interests.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub async fn subscribe_to_interests(
+        &self,
+        timeout: Duration,
+    ) -> Result<(SubscriptionId, Vec<String>), Error> {
+        let signer = self.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+
+        let filter: Filter = Filter::new()
+            .author(public_key)
+            .kind(Kind::Interests)
+            .limit(1);
+        let events: Vec<Event> = self.get_events_of(vec![filter], Some(timeout)).await?;
+        let event: &Event = events
+            .iter()
+            .max_by_key(|e| e.created_at)
+            .ok_or(Error::InterestsNotFound)?;
+
+        let interests: Vec<String> = InterestSet::from_event(event)?.hashtags;
+        if interests.is_empty() {
+            return Err(Error::InterestsNotFound);
+        }
+
+        let filter: Filter = Filter::new()
+            .kind(Kind::TextNote)
+            .hashtags(interests.clone())
+            .since(Timestamp::now());
+        let id: SubscriptionId = self.subscribe(vec![filter], None).await;
+
+        Ok((id, interests))
+    }
+
+    fn spawn_self_watch_listener(&self, public_key: PublicKey) {
+        let client: Client = self.clone();
+        let _ = thread::spawn(async move {
+            let mut notifications = client.pool.notifications();
+            while let Ok(notification) = notifications.recv().await {
+                match notification {
+                    RelayPoolNotification::Event { event, .. } => {
+                        if event.pubkey == public_key
+                            && client.self_watch.is_watched(&event.kind).await
+                            && client.self_watch.update(&event).await
+                        {
+                            client
+                                .pool
+                                .send_notification(RelayPoolNotification::SelfUpdated {
+                                    kind: event.kind,
+                                    event,
+                                });
+                        }
+                    }
+                    RelayPoolNotification::Shutdown => break,
+                    _ => (),
+                }
+            }
+        });
+    }
+
+    fn spawn_auth_listener(&self) {
+        let client: Client = self.clone();
+        let _ = thread::spawn(async move {
+            let mut notifications = client.pool.notifications();
+            while let Ok(notification) = notifications.recv().await {
+                match notification {
+                    RelayPoolNotification::Message {
+                        relay_url,
+                        message: RelayMessage::Auth { challenge },
+                    } => client.auth.set_challenge(relay_url, challenge).await,
+                    RelayPoolNotification::Message {
+                        relay_url,
+                        message:
+                            RelayMessage::Ok {
+                                event_id,
+                                status: false,
+                                message,
+                            },
+                    } => {
+                        if let Some(MachineReadablePrefix::AuthRequired) =
+                            MachineReadablePrefix::parse(&message)
+                        {
+                            client
+                                .handle_auth_required(relay_url, AuthReason::Publish(event_id))
+                                .await;
+                        }
+                    }
+                    RelayPoolNotification::SubscriptionClosed {
+                        relay_url,
+                        subscription_id,
+                        prefix: Some(MachineReadablePrefix::AuthRequired),
+                        ..
+                    } => {
+                        client
+                            .handle_auth_required(
+                                relay_url,
+                                AuthReason::Subscription(subscription_id),
+                            )
+                            .await;
+                    }
+                    RelayPoolNotification::Shutdown => break,
+                    _ => (),
+                }
+            }
+        });
+    }
+
+    async fn handle_auth_required(&self, relay_url: Url, reason: AuthReason) {
+        let policy: &AuthPolicy = self.opts.get_auth_policy();
+        if !self.auth.is_allowed(policy, &relay_url, &reason).await {
+            tracing::debug!("Authentication to {relay_url} declined by policy");
+            self.pool
+                .send_notification(RelayPoolNotification::AuthDeclined { relay_url, reason });
+            return;
+        }
+
+        if let Err(e) = self.authenticate_and_retry(&relay_url, reason).await {
+            tracing::error!("Failed to authenticate to {relay_url}: {e}");
+        }
+    }
+
+    async fn authenticate_and_retry(
+        &self,
+        relay_url: &Url,
+        reason: AuthReason,
+    ) -> Result<(), Error> {
+        let challenge: String = match self.auth.challenge(relay_url).await {
+            Some(challenge) => challenge,
+            None => {
+                tracing::warn!("Authentication to {relay_url} required but no challenge received");
+                return Ok(());
+            }
+        };
+
+        let relay: Relay = self.relay(relay_url.clone()).await?;
+        let builder = EventBuilder::auth(challenge, relay_url.clone());
+        let event: Event = self.sign_event_builder(builder).await?;
+        relay.send_event(event, RelaySendOptions::default()).await?;
+        tracing::info!("Authenticated to {relay_url}");
+
+        match reason {
+            AuthReason::Subscription(id) => {
+                if let Some(filters) = relay.subscription(&id).await {
+                    relay
+                        .subscribe_with_id(id, filters, SubscribeOptions::default())
+                        .await?;
+                }
+            }
+            AuthReason::Publish(id) => {
+                let event: Event = self.database().event_by_id(id).await?;
+                relay.send_event(event, RelaySendOptions::default()).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send multiple [`Event`] at once to **all relays**.
+    pub async fn batch_event(
+        &self,
+        events: Vec<Event>,
+        opts: RelaySendOptions,
+    ) -> Result<(), Error> {
+        Ok(self.pool.batch_event(events, opts).await?)
+    }
+
+    /// Send event to **specific relays**.
+    ///
+    /// This method will wait for the `OK` message from the relay.
+    /// If you not want to wait for the `OK` message, use `send_msg` method instead.
+    pub async fn send_event_to<I, U>(&self, urls: I, event: Event) -> Result<EventId, Error>
+    where
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        pool::Error: From<<U as TryIntoUrl>::Err>,
+    {
+        self.check_strict_send(&event)?;
+        let opts: RelaySendOptions = self.opts.get_wait_for_send();
         Ok(self.pool.send_event_to(urls, event, opts).await?)
     }
 
+    /// Send event to the relays of a [`RelayGroup`]
+    ///
+    /// Return the per-relay outcome.
+    pub async fn send_event_to_group(
+        &self,
+        group_name: &str,
+        event: Event,
+    ) -> Result<PublishResult, Error> {
+        let group: RelayGroup = self
+            .groups
+            .get(group_name)
+            .await
+            .ok_or_else(|| Error::RelayGroupNotFound(group_name.to_string()))?;
+
+        if !event.kind.is_ephemeral() {
+            self.database().save_event(&event).await?;
+        }
+
+        self.publish_to(group.relays, event).await
+    }
+
+    /// Publish multiple events that must be delivered together, in order, to the write relays
+    ///
+    /// With [`PublishSetStrategy::AllOrNone`], publishing stops at the first event not accepted by any relay
+    /// and [`Error::PublishSetFailed`] is returned.
+    /// Events already accepted by relays **can't be undone**: the set may be partially published.
+    ///
+    /// If [`Options::strict_send`] is enabled, all the events are checked before sending anything.
+    pub async fn publish_event_set(
+        &self,
+        events: Vec<Event>,
+        strategy: PublishSetStrategy,
+    ) -> Result<Vec<PublishResult>, Error> {
+        for event in events.iter() {
+            self.check_strict_send(event)?;
+        }
+
+        let urls: Vec<Url> = self
+            .pool
+            .relays()
+            .await
+            .into_iter()
+            .filter(|(_, relay)| relay.flags().has_write())
+            .map(|(url, _)| url)
+            .collect();
+
+        let mut results: Vec<PublishResult> = Vec::with_capacity(events.len());
+        for event in events.into_iter() {
+            if !event.kind.is_ephemeral() {
+                self.database().save_event(&event).await?;
+            }
+
+            let result: PublishResult = self.publish_to(urls.clone(), event).await?;
+            let failed: Option<EventId> = (!result.is_success()).then_some(result.id);
+            results.push(result);
+
+            if let (Some(failed), PublishSetStrategy::AllOrNone) = (failed, strategy) {
+                return Err(Error::PublishSetFailed { failed, results });
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Send event to relays, waiting for the `OK` of each one
+    async fn publish_to(&self, urls: Vec<Url>, event: Event) -> Result<PublishResult, Error> {
+        let opts: RelaySendOptions = self.opts.get_wait_for_send();
+        let mut result = PublishResult {
+            id: event.id,
+            success: HashSet::new(),
+            failed: HashMap::new(),
+        };
+
+        let mut handles = Vec::with_capacity(urls.len());
+        for url in urls.into_iter() {
+            let relay: Relay = match self.relay(url.clone()).await {
+                Ok(relay) => relay,
+                Err(e) => {
+                    result.failed.insert(url, e.to_string());
+                    continue;
+                }
+            };
+            let event: Event = event.clone();
+            let handle = thread::spawn(async move {
+                let res = relay.send_event(event, opts).await;
+                (url, res)
+            })
+            .map_err(RelayPoolError::from)?;
+            handles.push(handle);
+        }
+
+        for handle in handles.into_iter() {
+            let (url, res) = handle.join().await.map_err(RelayPoolError::from)?;
+            match res {
+                Ok(..) => {
+                    result.success.insert(url);
+                }
+                Err(e) => {
+                    result.failed.insert(url, e.to_string());
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Subscribe to the relays of a [`RelayGroup`]
+    pub async fn subscribe_to_group(
+        &self,
+        group_name: &str,
+        filters: Vec<Filter>,
+    ) -> Result<SubscriptionId, Error> {
+        let group: RelayGroup = self
+            .groups
+            .get(group_name)
+            .await
+            .ok_or_else(|| Error::RelayGroupNotFound(group_name.to_string()))?;
+
+        let id: SubscriptionId = SubscriptionId::generate();
+        let opts: SubscribeOptions =
+            SubscribeOptions::default().send_opts(self.opts.get_wait_for_subscription());
+        for url in group.relays.into_iter() {
+            let relay: Relay = self.relay(url.clone()).await?;
+            if let Err(e) = relay
+                .subscribe_with_id(id.clone(), filters.clone(), opts)
+                .await
+            {
+                tracing::error!("Failed to subscribe to {url}: {e}");
+            }
+        }
+
+        Ok(id)
+    }
+
     /// Send multiple [`Event`] at once to **specific relays**.
     pub async fn batch_event_to<I, U>(
         &self,
@@ -731,18 +2258,34 @@ impl Client {
     }
 
     /// Signs the [`EventBuilder`] into an [`Event`] using the [`NostrSigner`]
+    ///
+    /// The client tag is added, if set in [`Options::client_tag`], then the [`Options::event_interceptor`]s are run.
     pub async fn sign_event_builder(&self, builder: EventBuilder) -> Result<Event, Error> {
         let signer = self.signer().await?;
+        let builder: EventBuilder = self.prepare_event_builder(builder);
+        self.sign_prepared_event_builder(&signer, builder).await
+    }
 
-        let public_key = signer.public_key().await?;
-        let difficulty: u8 = self.opts.get_difficulty();
-        let unsigned = if difficulty > 0 {
-            builder.to_unsigned_pow_event(public_key, difficulty)
-        } else {
-            builder.to_unsigned_event(public_key)
+    /// Add the client tag and run the event interceptors
+    fn prepare_event_builder(&self, builder: EventBuilder) -> EventBuilder {
+        let builder: EventBuilder = match self.opts.get_client_tag(&builder.kind()) {
+            Some(client) => builder.client(client),
+            None => builder,
         };
+        self.opts.intercept_event(builder)
+    }
 
-        Ok(signer.sign_event(unsigned).await?)
+    async fn sign_prepared_event_builder(
+        &self,
+        signer: &NostrSigner,
+        builder: EventBuilder,
+    ) -> Result<Event, Error> {
+        let difficulty: u8 = self.opts.get_difficulty();
+        if difficulty > 0 {
+            Ok(builder.sign_pow(signer, difficulty).await?)
+        } else {
+            Ok(builder.sign(signer).await?)
+        }
     }
 
     /// Take an [`EventBuilder`], sign it by using the [`NostrSigner`] and broadcast to **all relays**.
@@ -770,6 +2313,243 @@ impl Client {
         self.send_event_to(urls, event).await
     }
 
+    /// Take a replaceable [`EventBuilder`], sign it and broadcast to **all relays**,
+    /// checking for conflicts with the current version of the event.
+    ///
+    /// Before publishing, the current version of the event (same kind, author and `d` tag) is fetched from relays
+    /// and compared with the new one according to the [`ReplaceConflictStrategy`] set in [`Options`].
+    /// If the current version must be overwritten but it's newer, the `created_at` of the new event is
+    /// bumped to make sure that relays will replace it.
+    /// The event is signed only once, after the comparison.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/01.md>
+    pub async fn replace_event(&self, builder: EventBuilder) -> Result<ReplaceResult, Error> {
+        let signer = self.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+        let builder: EventBuilder = self.prepare_event_builder(builder);
+
+        // Compare the event that will be actually published, before signing it:
+        // the signer is called only once, with the final `created_at`
+        let unsigned: UnsignedEvent = builder.clone().to_unsigned_event(public_key);
+        let builder: EventBuilder = builder.custom_created_at(unsigned.created_at);
+
+        if !unsigned.kind.is_replaceable() && !unsigned.kind.is_parameterized_replaceable() {
+            return Err(Error::NotReplaceable(unsigned.kind));
+        }
+
+        let identifier: Option<&str> = unsigned.tags.iter().find_map(|t| match t {
+            Tag::Identifier(id) => Some(id.as_str()),
+            _ => None,
+        });
+        let current: Event = match self
+            .current_replaceable_event(public_key, unsigned.kind, identifier)
+            .await?
+        {
+            Some(current) => current,
+            None => {
+                let event: Event = self.sign_prepared_event_builder(&signer, builder).await?;
+                let new_id: EventId = self.send_event(event).await?;
+                return Ok(ReplaceResult::Replaced {
+                    old_id: None,
+                    new_id,
+                });
+            }
+        };
+
+        // The POW changes the ID: it's known in advance only without it
+        let id: Option<EventId> = if self.opts.get_difficulty() > 0 {
+            None
+        } else {
+            Some(unsigned.id)
+        };
+        let strategy: ReplaceConflictStrategy = self.opts.get_replace_conflict_strategy();
+        let builder: EventBuilder = match replace::check_conflict(
+            &current,
+            unsigned.created_at,
+            id,
+            strategy,
+            Timestamp::now(),
+        ) {
+            ReplaceAction::Publish => builder,
+            // Make sure that the new event will replace the current one
+            ReplaceAction::BumpCreatedAt(created_at) => builder.custom_created_at(created_at),
+            ReplaceAction::Skip(result) => return Ok(result),
+        };
+
+        let event: Event = self.sign_prepared_event_builder(&signer, builder).await?;
+        let new_id: EventId = self.send_event(event).await?;
+        Ok(ReplaceResult::Replaced {
+            old_id: Some(current.id),
+            new_id,
+        })
+    }
+
+    /// Take a replaceable [`EventBuilder`], sign it and broadcast to **all relays**,
+    /// only if it's newer than the current version of the event.
+    ///
+    /// The current version of the event (same kind, author and `d` tag) is fetched from relays:
+    /// the new event is published only if its `created_at` is strictly greater,
+    /// so an older version is never published over a newer one.
+    /// Unlike [`Client::replace_event`], the [`ReplaceConflictStrategy`] is ignored and `created_at` is never bumped.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/01.md>
+    pub async fn publish_replaceable_event(
+        &self,
+        builder: EventBuilder,
+    ) -> Result<ReplaceResult, Error> {
+        let event: Event = self.sign_event_builder(builder).await?;
+
+        if !event.kind.is_replaceable() && !event.kind.is_parameterized_replaceable() {
+            return Err(Error::NotReplaceable(event.kind));
+        }
+
+        if let Some(current) = self
+            .current_replaceable_event(event.pubkey, event.kind, event.identifier())
+            .await?
+        {
+            if let Some(result) = replace::check_older(&current, &event) {
+                return Ok(result);
+            }
+        }
+
+        let id: EventId = self.send_event(event).await?;
+        Ok(ReplaceResult::Published(id))
+    }
+
+    /// Get the current version of the replaceable event from relays
+    async fn current_replaceable_event(
+        &self,
+        public_key: PublicKey,
+        kind: Kind,
+        identifier: Option<&str>,
+    ) -> Result<Option<Event>, Error> {
+        let mut filter: Filter = Filter::new().author(public_key).kind(kind);
+        if kind.is_parameterized_replaceable() {
+            filter = filter.identifier(identifier.unwrap_or_default());
+        }
+        Ok(self
+            .get_events_of(vec![filter], None)
+            .await?
+            .into_iter()
+            .max_by(|a, b| {
+                // On ties, relays keep the event with the lowest ID
+                a.created_at
+                    .cmp(&b.created_at)
+                    .then_with(|| b.id.cmp(&a.id))
+            }))
+    }
+
+    /// Sync app settings across devices
+    ///
+    /// Settings are stored in the `kind:30078` event (NIP78) of the `app_id`, encrypted to self with NIP44.
+    /// The newest remote version is fetched, decrypted and three-way merged with `local`,
+    /// using the last version synced in this session as base (last-writer-wins per top-level key, see [`SettingsConflict`]).
+    /// If the merged settings differ from the remote ones, they are published with an increased revision.
+    ///
+    /// If the remote version can't be decrypted or parsed, `local` is returned untouched,
+    /// nothing is published and a [`SettingsConflict::Corrupted`] is reported.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/78.md>
+    #[cfg(feature = "nip44")]
+    pub async fn sync_settings<S, T>(&self, app_id: S, local: T) -> Result<SyncOutcome<T>, Error>
+    where
+        S: Into<String>,
+        T: Serialize + DeserializeOwned,
+    {
+        let app_id: String = app_id.into();
+        let signer = self.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+
+        let local_data: Map<String, Value> = match nostr::serde_json::to_value(&local)? {
+            Value::Object(map) => map,
+            _ => return Err(Error::SettingsNotObject),
+        };
+
+        // Get newest remote version
+        let filter: Filter = Filter::new()
+            .author(public_key)
+            .kind(Kind::ApplicationSpecificData)
+            .identifier(app_id.clone());
+        let remote: Option<Event> = self
+            .get_events_of(vec![filter], None)
+            .await?
+            .into_iter()
+            .max_by_key(|e| e.created_at);
+
+        let remote: Option<SettingsPayload> = match remote {
+            Some(event) => {
+                let payload: Result<SettingsPayload, Error> = async {
+                    let json: String = signer.nip44_decrypt(public_key, &event.content).await?;
+                    Ok(SettingsPayload::from_json(json)?)
+                }
+                .await;
+                match payload {
+                    Ok(payload) => Some(payload),
+                    Err(e) => {
+                        tracing::warn!("Remote settings for '{app_id}' are corrupted: {e}");
+                        let revision: u64 = self
+                            .settings
+                            .base(&app_id)
+                            .await
+                            .map(|b| b.revision)
+                            .unwrap_or_default();
+                        return Ok(SyncOutcome {
+                            value: local,
+                            revision,
+                            published: false,
+                            conflicts: vec![SettingsConflict::Corrupted {
+                                event_id: event.id,
+                                error: e.to_string(),
+                            }],
+                        });
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let base: Option<SettingsPayload> = self.settings.base(&app_id).await;
+        let (merged, conflicts) = match &remote {
+            Some(remote) => {
+                settings::merge(&local_data, &remote.data, base.as_ref().map(|b| &b.data))
+            }
+            None => (local_data, Vec::new()),
+        };
+
+        let remote_revision: u64 = remote.as_ref().map(|r| r.revision).unwrap_or_default();
+        let mut payload = SettingsPayload {
+            revision: remote_revision,
+            data: merged,
+        };
+
+        let published: bool = remote
+            .as_ref()
+            .map(|r| r.data != payload.data)
+            .unwrap_or(true);
+        if published {
+            let base_revision: u64 = base.map(|b| b.revision).unwrap_or_default();
+            payload.revision = remote_revision.max(base_revision) + 1;
+            let content: String = signer.nip44_encrypt(public_key, payload.as_json()).await?;
+            let builder = EventBuilder::new(
+                Kind::ApplicationSpecificData,
+                content,
+                [Tag::Identifier(app_id.clone())],
+            );
+            self.send_event_builder(builder).await?;
+        }
+
+        let value: T = nostr::serde_json::from_value(Value::Object(payload.data.clone()))?;
+        let revision: u64 = payload.revision;
+        self.settings.set_base(app_id, payload).await;
+
+        Ok(SyncOutcome {
+            value,
+            revision,
+            published,
+            conflicts,
+        })
+    }
+
     /// Get public key metadata
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/01.md>
@@ -779,12 +2559,134 @@ impl Client {
             .kind(Kind::Metadata)
             .limit(1);
         let events: Vec<Event> = self.get_events_of(vec![filter], None).await?;
-        match events.first() {
-            Some(event) => Ok(Metadata::from_json(event.content())?),
+        match events.iter().max_by_key(|event| event.created_at()) {
+            Some(event) => Ok(Metadata::from_event(event)?),
             None => Err(Error::MetadataNotFound),
         }
     }
 
+    /// Get the badges displayed on a profile
+    ///
+    /// Return empty [`ProfileBadges`] if the profile badges event is not found.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/58.md>
+    pub async fn get_profile_badges(
+        &self,
+        public_key: PublicKey,
+        timeout: Duration,
+    ) -> Result<ProfileBadges, Error> {
+        let filter: Filter = Filter::new()
+            .author(public_key)
+            .kind(Kind::ProfileBadges)
+            .identifier(PROFILE_BADGES_IDENTIFIER)
+            .limit(1);
+        let events: Vec<Event> = self.get_events_of(vec![filter], Some(timeout)).await?;
+        match events.iter().max_by_key(|event| event.created_at()) {
+            Some(event) => Ok(ProfileBadges::from_event(event)?),
+            None => Ok(ProfileBadges::default()),
+        }
+    }
+
+    /// Resolve a NIP21 mention (`nostr:npub1...`, `nostr:note1...`, ...)
+    ///
+    /// Check [`Client::resolve_mentions`] for more details.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/21.md>
+    pub async fn resolve_mention(
+        &self,
+        uri: Nip21,
+        timeout: Duration,
+    ) -> Result<ResolvedMention, Error> {
+        let mut resolved = self.resolve_mentions(vec![uri.clone()], timeout).await?;
+        resolved.remove(&uri).ok_or(Error::MentionNotResolved)
+    }
+
+    /// Resolve NIP21 mentions in batch
+    ///
+    /// Events are taken from the database when available: only the missing ones are fetched from relays.
+    /// Mentioned notes and events that can't be found are not included in the output.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/21.md>
+    pub async fn resolve_mentions(
+        &self,
+        uris: Vec<Nip21>,
+        timeout: Duration,
+    ) -> Result<HashMap<Nip21, ResolvedMention>, Error> {
+        let mut ids: BTreeSet<EventId> = BTreeSet::new();
+        let mut authors: BTreeSet<PublicKey> = BTreeSet::new();
+        let mut coordinates: BTreeSet<Coordinate> = BTreeSet::new();
+
+        for uri in uris.iter() {
+            match uri {
+                Nip21::Pubkey(public_key) => {
+                    authors.insert(*public_key);
+                }
+                Nip21::Profile(profile) => {
+                    authors.insert(profile.public_key);
+                }
+                Nip21::EventId(id) => {
+                    ids.insert(*id);
+                }
+                Nip21::Event(event) => {
+                    ids.insert(event.event_id);
+                }
+                Nip21::Coordinate(coordinate) => {
+                    coordinates.insert(coordinate.clone());
+                }
+            }
+        }
+
+        // Get cached events
+        let filters: Vec<Filter> = mentions_filters(&ids, &authors, &coordinates);
+        let mut events: Vec<Event> = if filters.is_empty() {
+            Vec::new()
+        } else {
+            self.database().query(filters, Order::Desc).await?
+        };
+
+        // Fetch missing events from relays
+        for event in events.iter() {
+            ids.remove(&event.id);
+            if event.kind == Kind::Metadata {
+                authors.remove(&event.pubkey);
+            }
+            coordinates.retain(|c| !coordinate_match(c, event));
+        }
+        let filters: Vec<Filter> = mentions_filters(&ids, &authors, &coordinates);
+        if !filters.is_empty() {
+            match self.get_events_of(filters, Some(timeout)).await {
+                Ok(fetched) => events.extend(fetched),
+                Err(e) => tracing::warn!("Impossible to fetch mentions: {e}"),
+            }
+        }
+
+        // Keep latest events
+        events.sort_by_key(|e| Reverse(e.created_at));
+
+        let mut resolved: HashMap<Nip21, ResolvedMention> = HashMap::with_capacity(uris.len());
+        for uri in uris.into_iter() {
+            let mention: Option<ResolvedMention> = match &uri {
+                Nip21::Pubkey(public_key) => Some(profile_mention(*public_key, &events)),
+                Nip21::Profile(profile) => Some(profile_mention(profile.public_key, &events)),
+                Nip21::EventId(id) => event_mention(id, &events),
+                Nip21::Event(event) => event_mention(&event.event_id, &events),
+                Nip21::Coordinate(coordinate) => Some(ResolvedMention::Address {
+                    coordinate: coordinate.clone(),
+                    event: events
+                        .iter()
+                        .find(|e| coordinate_match(coordinate, e))
+                        .cloned(),
+                }),
+            };
+
+            if let Some(mention) = mention {
+                resolved.insert(uri, mention);
+            }
+        }
+
+        Ok(resolved)
+    }
+
     /// Update metadata
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/01.md>
@@ -812,6 +2714,39 @@ impl Client {
         self.send_event_builder(builder).await
     }
 
+    /// Fetch the current metadata, apply the changes and publish the updated metadata
+    ///
+    /// Fields not set in the closure, including custom/unknown ones, are kept unchanged.
+    /// Use [`Metadata::diff`] to get the list of changes before calling this method.
+    ///
+    /// Return [`Error::MetadataNotFound`] if no metadata is found: use [`Client::set_metadata`] to publish the first one.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/01.md>
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use nostr_sdk::prelude::*;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// #   let my_keys = Keys::generate();
+    /// #   let client = Client::new(&my_keys);
+    /// client
+    ///     .update_metadata(|metadata| metadata.about("New description"))
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub async fn update_metadata<F>(&self, f: F) -> Result<EventId, Error>
+    where
+        F: FnOnce(Metadata) -> Metadata,
+    {
+        let signer = self.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+        let metadata: Metadata = self.metadata(public_key).await?;
+        self.set_metadata(&f(metadata)).await
+    }
+
     /// Set relay list (NIP65)
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/65.md>
@@ -823,6 +2758,60 @@ impl Client {
         self.send_event_builder(builder).await
     }
 
+    /// Announce the relays of the pool (NIP65)
+    ///
+    /// Build a relay list with the write relays of the pool (marked as `write` if not also read relays)
+    /// and the `extra_relays` (marked as `write`), and publish it to the connected write relays.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/65.md>
+    pub async fn broadcast_public_key(&self, extra_relays: Vec<Url>) -> Result<EventId, Error> {
+        let mut list: Vec<(Url, Option<RelayMetadata>)> = Vec::new();
+
+        for (url, relay) in self.relays().await.into_iter() {
+            let flags = relay.flags();
+            if flags.has_write() {
+                let metadata: Option<RelayMetadata> = if flags.has_read() {
+                    None
+                } else {
+                    Some(RelayMetadata::Write)
+                };
+                list.push((url, metadata));
+            }
+        }
+
+        for url in extra_relays.into_iter() {
+            if !list.iter().any(|(u, ..)| u == &url) {
+                list.push((url, Some(RelayMetadata::Write)));
+            }
+        }
+
+        let relays = list
+            .into_iter()
+            .map(|(url, metadata)| (UncheckedUrl::from(url), metadata));
+        self.set_relay_list(relays).await
+    }
+
+    /// Get own relay list (NIP65)
+    ///
+    /// Return `None` if the relay list event is not found.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/65.md>
+    pub async fn get_own_relay_list(
+        &self,
+    ) -> Result<Option<Vec<(Url, Option<RelayMetadata>)>>, Error> {
+        let signer = self.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+        let filter: Filter = Filter::new()
+            .author(public_key)
+            .kind(Kind::RelayList)
+            .limit(1);
+        let events: Vec<Event> = self.get_events_of(vec![filter], None).await?;
+        Ok(events
+            .iter()
+            .max_by_key(|event| event.created_at())
+            .map(bootstrap::relays_from_relay_list))
+    }
+
     /// Publish text note
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/01.md>
@@ -961,6 +2950,43 @@ impl Client {
         Ok(contacts)
     }
 
+    /// Get own classified listings
+    ///
+    /// Keep only the latest version of each listing.
+    /// Drafts ([`ListingStatus::Draft`]) are returned only if `include_drafts` is `true`.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/99.md>
+    pub async fn get_own_listings(
+        &self,
+        include_drafts: bool,
+        timeout: Duration,
+    ) -> Result<Vec<ClassifiedListing>, Error> {
+        let signer = self.signer().await?;
+        let public_key = signer.public_key().await?;
+        let filter: Filter = Filter::new()
+            .author(public_key)
+            .kind(Kind::ClassifiedListing);
+        let events: Vec<Event> = self.get_events_of(vec![filter], Some(timeout)).await?;
+
+        let mut latest: HashMap<String, (Timestamp, ClassifiedListing)> = HashMap::new();
+        for event in events.iter() {
+            if let Ok(listing) = ClassifiedListing::from_event(event) {
+                match latest.get(&listing.identifier) {
+                    Some((created_at, ..)) if *created_at >= event.created_at() => {}
+                    _ => {
+                        latest.insert(listing.identifier.clone(), (event.created_at(), listing));
+                    }
+                }
+            }
+        }
+
+        Ok(latest
+            .into_values()
+            .map(|(_, listing)| listing)
+            .filter(|listing| include_drafts || !listing.is_draft())
+            .collect())
+    }
+
     /// Send encrypted direct message
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/04.md>
@@ -1107,6 +3133,27 @@ impl Client {
         self.send_event_builder(builder).await
     }
 
+    /// Get reactions summary of an event
+    ///
+    /// If a signer is set, [`ReactionSummary::did_react`] tells if the client public key reacted.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/25.md>
+    pub async fn get_reactions(
+        &self,
+        event_id: EventId,
+        timeout: Duration,
+    ) -> Result<ReactionSummary, Error> {
+        let filter: Filter = Filter::new().kind(Kind::Reaction).event(event_id);
+        let events: Vec<Event> = self.get_events_of(vec![filter], Some(timeout)).await?;
+
+        let public_key: Option<PublicKey> = match self.signer().await {
+            Ok(signer) => Some(signer.public_key().await?),
+            Err(_) => None,
+        };
+
+        Ok(ReactionSummary::from_events(events, public_key))
+    }
+
     /// Create new channel
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
@@ -1231,6 +3278,7 @@ impl Client {
         // Compose rumor
         let signer: NostrSigner = self.signer().await?;
         let public_key: PublicKey = signer.public_key().await?;
+        let rumor: EventBuilder = self.opts.intercept_rumor(rumor);
         let rumor = rumor.to_unsigned_event(public_key);
 
         // Compose seal
@@ -1284,6 +3332,175 @@ impl Client {
         Ok(self.pool.reconcile(filter, opts).await?)
     }
 
+    /// Negentropy sync with a relay (NIP77)
+    ///
+    /// Reconcile the events stored in database matching the filter with the relay, without transferring them.
+    /// Return which events the relay needs ([`NegentropyResult::have`])
+    /// and which ones are missing locally ([`NegentropyResult::need`]).
+    ///
+    /// Use [`Client::reconcile`] to also upload and download the events.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/77.md>
+    pub async fn sync_with_negentropy<U>(
+        &self,
+        url: U,
+        filter: Filter,
+        timeout: Duration,
+    ) -> Result<NegentropyResult, Error>
+    where
+        U: TryIntoUrl,
+        pool::Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let relay: Relay = self.relay(url).await?;
+        Ok(relay.sync_with_negentropy(filter, timeout).await?)
+    }
+
+    /// Import all the events of a public key
+    ///
+    /// Fetch all the events matching the public key and kinds from the connected relays and store them into the database.
+    /// If `kinds` is empty, all the events authored by the public key are fetched.
+    ///
+    /// Used to onboard an account or to sync it across devices.
+    pub async fn sync(
+        &self,
+        public_key: PublicKey,
+        kinds: Vec<Kind>,
+        timeout: Duration,
+    ) -> Result<SyncReport, Error> {
+        let filters: Vec<Filter> = sync::batch_filters(public_key, &kinds);
+
+        // Events already stored, to tell new events from duplicates
+        let database = self.database();
+        let known: HashSet<EventId> = database
+            .event_ids_by_filters(filters.clone(), Order::Desc)
+            .await?
+            .into_iter()
+            .collect();
+
+        let mut relays_queried: Vec<Url> = Vec::new();
+        let mut handles = Vec::new();
+        for (url, relay) in self.relays().await.into_iter() {
+            if !relay.is_connected().await {
+                continue;
+            }
+
+            let filters: Vec<Filter> = filters.clone();
+            let handle = thread::spawn(sync::fetch_all(relay, filters, timeout))
+                .map_err(RelayPoolError::from)?;
+            handles.push(handle);
+            relays_queried.push(url);
+        }
+
+        let mut ids: HashSet<EventId> = HashSet::new();
+        let mut report = SyncReport {
+            relays_queried,
+            ..Default::default()
+        };
+
+        for handle in handles.into_iter() {
+            let events: Vec<Event> = handle.join().await.map_err(RelayPoolError::from)?;
+            for event in events.into_iter() {
+                if !ids.insert(event.id) {
+                    continue;
+                }
+
+                database.save_event(&event).await?;
+
+                report.events_fetched += 1;
+                if known.contains(&event.id) {
+                    report.events_duplicate += 1;
+                } else {
+                    report.events_new += 1;
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Check which of the user's own events are missing on the write relays
+    ///
+    /// The events matching the filter and authored by the signer public key are loaded from the database
+    /// (ephemeral and expired events are excluded) and looked up on every write relay, by ID, in batches.
+    /// If [`AuditOptions::republish`] is enabled, the missing events are sent again, at a throttled rate.
+    pub async fn audit_published(
+        &self,
+        filter: Filter,
+        opts: AuditOptions,
+    ) -> Result<AuditReport, Error> {
+        let signer = self.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+
+        let events: Vec<Event> = self
+            .database()
+            .query(vec![filter.author(public_key)], Order::Desc)
+            .await?
+            .into_iter()
+            .filter(|e| !e.is_ephemeral() && !e.is_expired())
+            .collect();
+
+        let mut handles = Vec::new();
+        for relay in self.relays().await.into_values() {
+            if !relay.flags().has_write() {
+                continue;
+            }
+
+            let handle = thread::spawn(audit::audit_relay(relay, events.clone(), opts))
+                .map_err(RelayPoolError::from)?;
+            handles.push(handle);
+        }
+
+        let mut report = AuditReport {
+            events_checked: events.len(),
+            relays: Vec::with_capacity(handles.len()),
+        };
+        for handle in handles.into_iter() {
+            report
+                .relays
+                .push(handle.join().await.map_err(RelayPoolError::from)?);
+        }
+
+        Ok(report)
+    }
+
+    /// Copy the events matching the filters from a relay to another one
+    ///
+    /// The events are fetched from `source_relay` until EOSE (see [`MirrorOptions::since`])
+    /// and sent to `target_relay` in batches, oldest first. Both relays must be added to the client.
+    /// The events rejected by the target relay are collected in [`MirrorReport::errors`].
+    pub async fn mirror_events(
+        &self,
+        source_relay: Url,
+        target_relay: Url,
+        filters: Vec<Filter>,
+        opts: MirrorOptions,
+    ) -> Result<MirrorReport, Error> {
+        let source: Relay = self.relay(source_relay).await?;
+        let target: Relay = self.relay(target_relay).await?;
+
+        let filters: Vec<Filter> = match opts.since {
+            Some(since) => filters.into_iter().map(|f| f.since(since)).collect(),
+            None => filters,
+        };
+
+        let mut events: Vec<Event> = sync::fetch_all(source, filters, opts.timeout).await;
+        events.sort_by_key(|e| e.created_at);
+
+        let mut report = MirrorReport::default();
+
+        if opts.dry_run {
+            report.events_mirrored = events.len() as u64;
+            return Ok(report);
+        }
+
+        let send_opts = RelaySendOptions::new().timeout(Some(opts.timeout));
+        for batch in events.chunks(opts.batch_size as usize) {
+            mirror::publish_batch(&target, batch.to_vec(), send_opts, &mut report).await;
+        }
+
+        Ok(report)
+    }
+
     /// Negentropy reconciliation with items
     pub async fn reconcile_with_items(
         &self,
@@ -1294,12 +3511,424 @@ impl Client {
         Ok(self.pool.reconcile_with_items(filter, items, opts).await?)
     }
 
+    /// Score an event with the spam detector set in [`Options::spam_detector`]
+    ///
+    /// Return [`SpamScore::CLEAN`] if no detector is set.
+    pub fn detect_spam(&self, event: &Event) -> SpamScore {
+        match self.opts.get_spam_detector() {
+            Some(detector) => detector.score(event),
+            None => SpamScore::CLEAN,
+        }
+    }
+
+    fn is_spam(&self, event: &Event) -> bool {
+        match self.opts.get_spam_detector() {
+            Some(detector) => {
+                detector.ingest(event);
+                detector.score(event).as_f32() > self.opts.get_spam_threshold()
+            }
+            None => false,
+        }
+    }
+
     /// Handle notifications
+    ///
+    /// Events scoring above the spam threshold are dropped (see [`Options::spam_detector`])
+    /// and accounted as dropped in the traffic stats (see [`Client::traffic_report`]).
     pub async fn handle_notifications<F, Fut>(&self, func: F) -> Result<(), Error>
     where
         F: Fn(RelayPoolNotification) -> Fut,
         Fut: Future<Output = Result<bool>>,
     {
-        Ok(self.pool.handle_notifications(func).await?)
+        Ok(self
+            .pool
+            .handle_notifications(|notification| {
+                let spam: Option<(Url, SubscriptionId)> = match &notification {
+                    RelayPoolNotification::Event {
+                        relay_url,
+                        subscription_id,
+                        event,
+                    } if self.is_spam(event) => Some((relay_url.clone(), subscription_id.clone())),
+                    _ => None,
+                };
+                let fut = match spam {
+                    Some(..) => {
+                        tracing::debug!("Dropped spam event");
+                        None
+                    }
+                    None => Some(func(notification)),
+                };
+                async move {
+                    match (fut, spam) {
+                        (Some(fut), ..) => fut.await,
+                        (None, Some((relay_url, subscription_id))) => {
+                            if let Ok(relay) = self.pool.relay(relay_url).await {
+                                relay.record_undelivered(&subscription_id);
+                            }
+                            Ok(false)
+                        }
+                        (None, None) => Ok(false),
+                    }
+                }
+            })
+            .await?)
+    }
+
+    /// Get the incoming traffic stats of a subscription, summed over all relays
+    ///
+    /// Check [`Client::traffic_report`] for an example.
+    pub async fn subscription_stats(&self, id: &SubscriptionId) -> SubscriptionStats {
+        self.pool.subscription_stats(id).await
+    }
+
+    /// Get the incoming traffic stats of all the subscriptions, summed over all relays
+    ///
+    /// Useful to find the subscriptions pulling mostly junk: a low delivered percentage means that
+    /// the events are mostly duplicates or dropped (POW, policies, spam), so the filters can be tightened.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use nostr_sdk::prelude::*;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// #   let client = Client::default();
+    /// let report = client.traffic_report().await;
+    /// for (id, stats) in report.subscriptions.iter() {
+    ///     if stats.received > 100 && stats.delivered_percent() < 10.0 {
+    ///         // Find the kinds that flood the subscription
+    ///         for (kind, count) in stats.kinds.iter() {
+    ///             println!("{id}: kind {kind} = {count} ({:.1}%)", stats.kind_percent(kind));
+    ///         }
+    ///         // ...and narrow the filter (i.e. `Filter::kinds`, `Filter::authors` or `Filter::since`)
+    ///     }
+    /// }
+    ///
+    /// // Log as JSON
+    /// println!("{}", report.as_json());
+    ///
+    /// // Start a new measurement
+    /// client.reset_traffic_stats(None).await;
+    /// # }
+    /// ```
+    pub async fn traffic_report(&self) -> TrafficReport {
+        self.pool.traffic_report().await
+    }
+
+    /// Reset the incoming traffic stats of a subscription or, if `None`, of all the subscriptions
+    pub async fn reset_traffic_stats(&self, id: Option<&SubscriptionId>) {
+        self.pool.reset_traffic_stats(id).await
+    }
+}
+
+fn mentions_filters(
+    ids: &BTreeSet<EventId>,
+    authors: &BTreeSet<PublicKey>,
+    coordinates: &BTreeSet<Coordinate>,
+) -> Vec<Filter> {
+    let mut filters: Vec<Filter> = Vec::new();
+    if !ids.is_empty() {
+        filters.push(Filter::new().ids(ids.iter().copied()));
+    }
+    if !authors.is_empty() {
+        filters.push(
+            Filter::new()
+                .authors(authors.iter().copied())
+                .kind(Kind::Metadata),
+        );
+    }
+    filters.extend(coordinates.iter().map(Filter::from));
+    filters
+}
+
+fn coordinate_match(coordinate: &Coordinate, event: &Event) -> bool {
+    event.kind == coordinate.kind
+        && event.pubkey == coordinate.public_key
+        && event.identifier().unwrap_or_default() == coordinate.identifier
+}
+
+/// `events` must be sorted by `created_at` (newest first)
+fn profile_mention(public_key: PublicKey, events: &[Event]) -> ResolvedMention {
+    let metadata: Option<Metadata> = events
+        .iter()
+        .find(|e| e.kind == Kind::Metadata && e.pubkey == public_key)
+        .and_then(|e| Metadata::from_event(e).ok());
+    ResolvedMention::Profile {
+        public_key,
+        metadata,
+    }
+}
+
+fn event_mention(id: &EventId, events: &[Event]) -> Option<ResolvedMention> {
+    let event: Event = events.iter().find(|e| &e.id == id)?.clone();
+    if event.kind == Kind::TextNote {
+        Some(ResolvedMention::Note { event })
+    } else {
+        Some(ResolvedMention::Event { event })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_utility::futures_util::{SinkExt, StreamExt};
+    use nostr::message::relay::NegentropyErrorCode;
+    use nostr_database::{MemoryDatabase, MemoryDatabaseOptions};
+    use nostr_relay_pool::{MemoryLimits, RelayPoolOptions};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc::UnboundedSender;
+    use tokio_tungstenite::tungstenite::Message;
+
+    use super::*;
+
+    const TASKS: u8 = 8;
+
+    /// Minimal relay: reject every event and `NEG-OPEN`, and forward the `NEG-CLOSE` subscription IDs
+    async fn spawn_relay(closed: UnboundedSender<SubscriptionId>) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let closed = closed.clone();
+                tokio::spawn(async move {
+                    // Not a websocket (i.e. NIP11 document request)
+                    let mut ws = match tokio_tungstenite::accept_async(stream).await {
+                        Ok(ws) => ws,
+                        Err(_) => return,
+                    };
+                    while let Some(Ok(msg)) = ws.next().await {
+                        let json = match msg {
+                            Message::Text(json) => json,
+                            _ => continue,
+                        };
+                        match ClientMessage::from_json(json) {
+                            Ok(ClientMessage::NegOpen {
+                                subscription_id, ..
+                            }) => {
+                                let msg = RelayMessage::NegErr {
+                                    subscription_id,
+                                    code: NegentropyErrorCode::ResultsTooBig,
+                                };
+                                if ws.send(Message::Text(msg.as_json())).await.is_err() {
+                                    return;
+                                }
+                            }
+                            Ok(ClientMessage::NegClose { subscription_id }) => {
+                                let _ = closed.send(subscription_id);
+                            }
+                            Ok(ClientMessage::Event(event)) => {
+                                let msg = RelayMessage::ok(event.id, false, "blocked");
+                                if ws.send(Message::Text(msg.as_json())).await.is_err() {
+                                    return;
+                                }
+                            }
+                            _ => (),
+                        }
+                    }
+                });
+            }
+        });
+        Url::parse(&format!("ws://{addr}")).unwrap()
+    }
+
+    async fn wait_connected(client: &Client, url: &Url) {
+        time::timeout(Some(Duration::from_secs(5)), async {
+            while !client.relay(url).await.unwrap().is_connected().await {
+                thread::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_send_event_offline_queue_not_sent() {
+        let (closed_tx, _closed_rx) = mpsc::unbounded_channel();
+        let url = spawn_relay(closed_tx).await;
+        let opts = Options::new().offline_queue_capacity(10);
+        let keys = Keys::generate();
+        let client = Client::with_opts(&keys, opts);
+        client.add_relay(url.clone()).await.unwrap();
+        client.connect_relay(url.clone()).await.unwrap();
+        wait_connected(&client, &url).await;
+
+        let queued = EventBuilder::text_note("queued", [])
+            .to_event(&keys)
+            .unwrap();
+        client.offline_queue.push(queued.clone(), 10).await;
+
+        // Online, but the queue is rejected: the event is queued after it
+        let event = EventBuilder::text_note("new", []).to_event(&keys).unwrap();
+        let res = client.send_event(event.clone()).await;
+        assert!(matches!(res, Err(Error::EventQueued { event_id, .. }) if event_id == event.id));
+        let events = client.clear_offline_queue().await;
+        assert_eq!(events, vec![queued, event]);
+    }
+
+    #[tokio::test]
+    async fn test_sync_with_negentropy_error() {
+        let (closed_tx, mut closed_rx) = mpsc::unbounded_channel();
+        let url = spawn_relay(closed_tx).await;
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        });
+        let client = ClientBuilder::new().database(database).build();
+        client.add_relay(url.clone()).await.unwrap();
+        client.connect_relay(url.clone()).await.unwrap();
+        wait_connected(&client, &url).await;
+
+        let res = client
+            .sync_with_negentropy(url, Filter::new(), Duration::from_secs(5))
+            .await;
+        assert!(matches!(
+            res,
+            Err(Error::Relay(RelayError::NegentropyReconciliation(
+                NegentropyErrorCode::ResultsTooBig
+            )))
+        ));
+
+        // Closed even on error
+        time::timeout(Some(Duration::from_secs(5)), closed_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_new_from_pool() {
+        let client = Client::new(Keys::generate());
+        let keys = Keys::generate();
+        let opts = Options::new().min_pow(8);
+        let other = Client::new_from_pool(client.pool(), keys.clone(), opts);
+
+        // Relays are shared
+        let url = Url::parse("wss://relay.example.com").unwrap();
+        client.add_relay(url.clone()).await.unwrap();
+        assert!(other.relays().await.contains_key(&url));
+
+        // Signer is not
+        let signer = other.signer().await.unwrap();
+        assert_eq!(signer.public_key().await.unwrap(), keys.public_key());
+        client.set_signer(None).await;
+        assert!(other.signer().await.is_ok());
+
+        // Options are kept
+        assert_eq!(other.opts.get_min_pow_difficulty(), 8);
+        let url = Url::parse("wss://relay.example.org").unwrap();
+        other.add_relay(url.clone()).await.unwrap();
+        let relay = other.relay(url).await.unwrap();
+        assert_eq!(relay.opts().get_pow_difficulty(), 8);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_clones() {
+        let client = Client::default();
+
+        let mut handles = Vec::with_capacity(TASKS as usize);
+        for i in 0..TASKS {
+            let client: Client = client.clone();
+            handles.push(tokio::spawn(async move {
+                client.set_signer(Some(Keys::generate().into())).await;
+                client.update_difficulty(i);
+
+                let filter = Filter::new().kind(Kind::TextNote).limit(1);
+                let id: SubscriptionId = client.subscribe(vec![filter], None).await;
+
+                // No relays: must fail without blocking
+                let res = client
+                    .send_event_builder(EventBuilder::text_note("test", []))
+                    .await;
+                assert!(res.is_err());
+
+                assert!(client.signer().await.is_ok());
+                id
+            }));
+        }
+
+        let ids = time::timeout(Some(Duration::from_secs(10)), async {
+            let mut ids: HashSet<SubscriptionId> = HashSet::new();
+            for handle in handles.into_iter() {
+                ids.insert(handle.await.unwrap());
+            }
+            ids
+        })
+        .await
+        .expect("deadlock");
+
+        // All the updates made by the clones are visible here
+        assert_eq!(ids.len(), TASKS as usize);
+        assert_eq!(client.subscriptions().await.len(), TASKS as usize);
+        assert!(client.signer().await.is_ok());
+        assert!(client.opts.get_difficulty() < TASKS);
+
+        client.update_min_pow_difficulty(10).await;
+        assert_eq!(client.clone().opts.get_min_pow_difficulty(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_update_min_pow_difficulty() {
+        let client = Client::new(Keys::generate());
+        let url = Url::parse("wss://relay.example.com").unwrap();
+        client.add_relay(url.clone()).await.unwrap();
+
+        // Applied to the relays already added
+        client.clone().update_min_pow_difficulty(12).await;
+        let relay = client.relay(url).await.unwrap();
+        assert_eq!(relay.opts().get_pow_difficulty(), 12);
+
+        // And to the new ones
+        client.add_relay("wss://other.example.com").await.unwrap();
+        let relay = client.relay("wss://other.example.com").await.unwrap();
+        assert_eq!(relay.opts().get_pow_difficulty(), 12);
+    }
+
+    #[tokio::test]
+    #[allow(deprecated)]
+    async fn test_pool_options_from_builder() {
+        async fn acks_limit(client: &Client) -> Option<usize> {
+            let report = client.pool().memory_report().await;
+            report.components.get("pool.acks").unwrap().limit
+        }
+
+        let pool_opts = RelayPoolOptions::new().memory_limits(MemoryLimits::new().acks(5));
+
+        let client = ClientBuilder::new().pool(pool_opts).build();
+        assert_eq!(acks_limit(&client).await, Some(5));
+
+        // Deprecated options still honored
+        let client = ClientBuilder::new()
+            .opts(Options::new().pool(pool_opts))
+            .build();
+        assert_eq!(acks_limit(&client).await, Some(5));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_shutdown() {
+        let client = Client::new(Keys::generate());
+
+        let mut handles = Vec::with_capacity(TASKS as usize);
+        for i in 0..TASKS {
+            let client: Client = client.clone();
+            handles.push(tokio::spawn(async move {
+                if i == 0 {
+                    client.shutdown().await.unwrap();
+                } else {
+                    let filter = Filter::new().kind(Kind::TextNote).limit(1);
+                    client.subscribe(vec![filter], None).await;
+                    client.set_signer(None).await;
+                    let _ = client
+                        .send_event_builder(EventBuilder::text_note("test", []))
+                        .await;
+                }
+            }));
+        }
+
+        time::timeout(Some(Duration::from_secs(10)), async {
+            for handle in handles.into_iter() {
+                handle.await.unwrap();
+            }
+        })
+        .await
+        .expect("deadlock");
     }
 }
@@ -0,0 +1,210 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Offline event queue
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use nostr::{Event, JsonUtil, Kind, PublicKey};
+use tokio::sync::{Mutex, MutexGuard};
+
+/// FIFO queue of events waiting for a relay connection
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OfflineQueue {
+    events: Arc<Mutex<VecDeque<Event>>>,
+    /// Lock held while the queue is being flushed, to keep events in order
+    flushing: Arc<Mutex<()>>,
+    listening: Arc<AtomicBool>,
+}
+
+impl OfflineQueue {
+    /// Push event at the end of the queue
+    ///
+    /// If the queue is full, the oldest event is dropped.
+    /// Ephemeral events are never queued: return `false` if the event was rejected.
+    pub async fn push(&self, event: Event, capacity: usize) -> bool {
+        if event.is_ephemeral() {
+            tracing::debug!("Ephemeral event {} not queued", event.id);
+            return false;
+        }
+
+        let mut events = self.events.lock().await;
+        while events.len() >= capacity {
+            match events.pop_front() {
+                Some(dropped) => {
+                    tracing::warn!("Offline queue is full: dropped event {}", dropped.id)
+                }
+                None => break,
+            }
+        }
+        events.push_back(event);
+        true
+    }
+
+    /// Put back events at the front of the queue, without exceeding the capacity
+    pub async fn restore(&self, restored: Vec<Event>, capacity: usize) {
+        let mut events = self.events.lock().await;
+        for event in restored.into_iter().rev() {
+            if events.len() >= capacity {
+                tracing::warn!("Offline queue is full: dropped event {}", event.id);
+                continue;
+            }
+            events.push_front(event);
+        }
+    }
+
+    pub async fn len(&self) -> usize {
+        let events = self.events.lock().await;
+        events.len()
+    }
+
+    /// Number of events and their approximate size, in bytes
+    pub async fn usage(&self) -> (usize, usize) {
+        let events = self.events.lock().await;
+        let bytes: usize = events.iter().map(|e| e.as_json().len()).sum();
+        (events.len(), bytes)
+    }
+
+    /// Remove all events from the queue
+    ///
+    /// Replaceable events are collapsed to the newest instance.
+    pub async fn take(&self) -> Vec<Event> {
+        let mut events = self.events.lock().await;
+        collapse_replaceable(events.drain(..).collect())
+    }
+
+    /// Wait for other flushes to complete
+    pub async fn lock_flush(&self) -> MutexGuard<'_, ()> {
+        self.flushing.lock().await
+    }
+
+    /// Mark the reconnection listener as spawned
+    ///
+    /// Return `false` if the listener was already spawned.
+    pub fn set_listening(&self) -> bool {
+        !self.listening.swap(true, Ordering::SeqCst)
+    }
+
+    pub fn unset_listening(&self) {
+        self.listening.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Identity of a replaceable event: (kind, author, identifier)
+type ReplaceableIdentity = (Kind, PublicKey, Option<String>);
+
+fn replaceable_identity(event: &Event) -> Option<ReplaceableIdentity> {
+    if event.is_replaceable() {
+        Some((event.kind, event.pubkey, None))
+    } else if event.is_parameterized_replaceable() {
+        let identifier: &str = event.identifier().unwrap_or_default();
+        Some((event.kind, event.pubkey, Some(identifier.to_string())))
+    } else {
+        None
+    }
+}
+
+/// Keep only the newest instance of every replaceable event, preserving the order
+fn collapse_replaceable(events: Vec<Event>) -> Vec<Event> {
+    let mut newest: HashMap<ReplaceableIdentity, usize> = HashMap::new();
+
+    for (index, event) in events.iter().enumerate() {
+        if let Some(identity) = replaceable_identity(event) {
+            let is_newest: bool = match newest.get(&identity) {
+                // On ties, relays keep the event with the lowest ID
+                Some(current) => {
+                    let current: &Event = &events[*current];
+                    event.created_at > current.created_at
+                        || (event.created_at == current.created_at && event.id < current.id)
+                }
+                None => true,
+            };
+
+            if is_newest {
+                newest.insert(identity, index);
+            }
+        }
+    }
+
+    events
+        .into_iter()
+        .enumerate()
+        .filter(|(index, event)| match replaceable_identity(event) {
+            Some(identity) => newest.get(&identity) == Some(index),
+            None => true,
+        })
+        .map(|(_, event)| event)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys, Metadata, Tag, Timestamp};
+
+    use super::*;
+
+    fn build(keys: &Keys, builder: EventBuilder, created_at: u64) -> Event {
+        builder
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_offline_queue_reconnect() {
+        let keys = Keys::generate();
+        let queue = OfflineQueue::default();
+
+        let note = build(&keys, EventBuilder::text_note("Hello", []), 1);
+        let metadata_old = build(
+            &keys,
+            EventBuilder::metadata(&Metadata::new().name("old")),
+            2,
+        );
+        let presence = build(
+            &keys,
+            EventBuilder::new(Kind::Custom(20_001), "online", []),
+            3,
+        );
+        let metadata_new = build(
+            &keys,
+            EventBuilder::metadata(&Metadata::new().name("new")),
+            4,
+        );
+        let set_a = build(
+            &keys,
+            EventBuilder::new(Kind::Custom(30_001), "a", [Tag::Identifier("a".into())]),
+            5,
+        );
+        let set_b = build(
+            &keys,
+            EventBuilder::new(Kind::Custom(30_001), "b", [Tag::Identifier("b".into())]),
+            6,
+        );
+
+        assert!(queue.push(note.clone(), 10).await);
+        assert!(queue.push(metadata_old, 10).await);
+        assert!(!queue.push(presence, 10).await);
+        assert!(queue.push(metadata_new.clone(), 10).await);
+        assert!(queue.push(set_a.clone(), 10).await);
+        assert!(queue.push(set_b.clone(), 10).await);
+        assert_eq!(queue.len().await, 5);
+
+        // Reconnect: flush
+        let events = queue.take().await;
+        assert_eq!(events, vec![note, metadata_new, set_a, set_b]);
+        assert_eq!(queue.len().await, 0);
+    }
+
+    #[test]
+    fn test_collapse_replaceable_keeps_newest() {
+        let keys = Keys::generate();
+        let newest = build(&keys, EventBuilder::metadata(&Metadata::new().name("b")), 2);
+        let oldest = build(&keys, EventBuilder::metadata(&Metadata::new().name("a")), 1);
+        let events = collapse_replaceable(vec![newest.clone(), oldest]);
+        assert_eq!(events, vec![newest]);
+    }
+}
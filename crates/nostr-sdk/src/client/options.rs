@@ -10,10 +10,27 @@ use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
+use nostr::nips::nip89::ClientTag;
+#[cfg(feature = "nip57")]
+use nostr::util::http::RetryPolicy;
+use nostr::{EventBuilder, Kind};
 use nostr_relay_pool::relay::options::DEFAULT_SEND_TIMEOUT;
-use nostr_relay_pool::{RelayLimits, RelayPoolOptions, RelaySendOptions};
+#[cfg(not(target_arch = "wasm32"))]
+use nostr_relay_pool::SocksAuth;
+use nostr_relay_pool::{RelayHealthThresholds, RelayLimits, RelayPoolOptions, RelaySendOptions};
+
+use super::auth::AuthPolicy;
+use super::interceptor::{self, EventInterceptor};
+use super::replace::ReplaceConflictStrategy;
+use super::spam::SpamDetector;
 
 /// Options
+///
+/// Difficulty and min POW difficulty are shared between the clones of the `Client`
+/// and can be changed at runtime (see `Client::update_difficulty` and `Client::update_min_pow_difficulty`).
+/// All the other options are fixed when the `Client` is built.
+///
+/// The options used only to build the `Client` (i.e. the relay pool options) are set in the `ClientBuilder`.
 #[derive(Debug, Clone)]
 pub struct Options {
     /// Wait for the msg to be sent (default: true)
@@ -28,6 +45,29 @@ pub struct Options {
     ///
     /// If the relay made just 1 attempt, the relay will not be skipped
     skip_disconnected_relays: Arc<AtomicBool>,
+    /// Offline queue capacity (default: 0, disabled)
+    offline_queue_capacity: usize,
+    /// Replace conflict strategy (default: fail if newer)
+    replace_conflict_strategy: ReplaceConflictStrategy,
+    /// Client tag (default: None)
+    client_tag: Option<ClientTag>,
+    /// Add client tag to private kinds (default: false)
+    client_tag_private_kinds: bool,
+    /// Add client tag to ephemeral kinds (default: false)
+    client_tag_ephemeral_kinds: bool,
+    /// Event interceptors, in registration order
+    event_interceptors: Vec<Arc<dyn EventInterceptor>>,
+    /// Spam detector (default: None)
+    spam_detector: Option<Arc<dyn SpamDetector + Send + Sync>>,
+    /// Spam threshold (default: 0.8)
+    spam_threshold: f32,
+    /// Automatic authentication policy (default: disabled)
+    auth_policy: AuthPolicy,
+    /// Refuse to send events with lint errors (default: false)
+    strict_send: bool,
+    /// LNURL-pay endpoint resolution retry policy (default: [`RetryPolicy::default`])
+    #[cfg(feature = "nip57")]
+    lnurl_retry_policy: RetryPolicy,
     /// Timeout (default: 60)
     ///
     /// Used in `get_events_of`, `req_events_of` and similar as default timeout.
@@ -41,13 +81,20 @@ pub struct Options {
     /// Proxy
     #[cfg(not(target_arch = "wasm32"))]
     pub proxy: Option<SocketAddr>,
+    /// SOCKS5 proxy authentication
+    #[cfg(not(target_arch = "wasm32"))]
+    pub proxy_auth: Option<SocksAuth>,
     /// Default limits for new added relays
     pub relay_limits: RelayLimits,
+    /// Default health thresholds for new added relays
+    pub relay_health_thresholds: RelayHealthThresholds,
     /// Pool Options
+    #[deprecated(since = "0.30.0", note = "use `ClientBuilder::pool` instead")]
     pub pool: RelayPoolOptions,
 }
 
 impl Default for Options {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             wait_for_send: Arc::new(AtomicBool::new(true)),
@@ -56,12 +103,27 @@ impl Default for Options {
             min_pow_difficulty: Arc::new(AtomicU8::new(0)),
             req_filters_chunk_size: Arc::new(AtomicU8::new(10)),
             skip_disconnected_relays: Arc::new(AtomicBool::new(true)),
+            offline_queue_capacity: 0,
+            replace_conflict_strategy: ReplaceConflictStrategy::default(),
+            client_tag: None,
+            client_tag_private_kinds: false,
+            client_tag_ephemeral_kinds: false,
+            event_interceptors: Vec::new(),
+            spam_detector: None,
+            spam_threshold: 0.8,
+            auth_policy: AuthPolicy::default(),
+            strict_send: false,
+            #[cfg(feature = "nip57")]
+            lnurl_retry_policy: RetryPolicy::default(),
             timeout: Duration::from_secs(60),
             connection_timeout: None,
             send_timeout: Some(DEFAULT_SEND_TIMEOUT),
             #[cfg(not(target_arch = "wasm32"))]
             proxy: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            proxy_auth: None,
             relay_limits: RelayLimits::default(),
+            relay_health_thresholds: RelayHealthThresholds::default(),
             pool: RelayPoolOptions::default(),
         }
     }
@@ -167,6 +229,169 @@ impl Options {
         self.skip_disconnected_relays.load(Ordering::SeqCst)
     }
 
+    /// Offline queue capacity (default: 0, disabled)
+    ///
+    /// If greater than `0`, events sent with `Client::send_event` while all relays are disconnected
+    /// are queued and sent, in FIFO order, when a relay reconnects.
+    /// When the queue is full, the oldest event is dropped.
+    ///
+    /// Ephemeral events are never queued and replaceable events are collapsed to the newest one before the flush.
+    pub fn offline_queue_capacity(self, capacity: usize) -> Self {
+        Self {
+            offline_queue_capacity: capacity,
+            ..self
+        }
+    }
+
+    pub(crate) fn get_offline_queue_capacity(&self) -> usize {
+        self.offline_queue_capacity
+    }
+
+    /// Strategy used by `Client::replace_event` when a replaceable event already exists (default: fail if newer)
+    pub fn replace_conflict_strategy(mut self, strategy: ReplaceConflictStrategy) -> Self {
+        self.replace_conflict_strategy = strategy;
+        self
+    }
+
+    pub(crate) fn get_replace_conflict_strategy(&self) -> ReplaceConflictStrategy {
+        self.replace_conflict_strategy
+    }
+
+    /// Client tag added to the events signed by the `Client` (default: None)
+    ///
+    /// The tag is not added if the event already has one.
+    /// By default, private kinds (DMs, seals and gift wraps) and ephemeral kinds are skipped.
+    /// Set to `None` to disable it.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/89.md>
+    pub fn client_tag(mut self, client: Option<ClientTag>) -> Self {
+        self.client_tag = client;
+        self
+    }
+
+    /// Add client tag also to private kinds: DMs, seals and gift wraps (default: false)
+    pub fn client_tag_private_kinds(mut self, enable: bool) -> Self {
+        self.client_tag_private_kinds = enable;
+        self
+    }
+
+    /// Add client tag also to ephemeral kinds (default: false)
+    pub fn client_tag_ephemeral_kinds(mut self, enable: bool) -> Self {
+        self.client_tag_ephemeral_kinds = enable;
+        self
+    }
+
+    /// Get the client tag to add to an event of `kind`, if any
+    pub(crate) fn get_client_tag(&self, kind: &Kind) -> Option<ClientTag> {
+        let client: &ClientTag = self.client_tag.as_ref()?;
+
+        if interceptor::is_private(kind) && !self.client_tag_private_kinds {
+            return None;
+        }
+
+        if kind.is_ephemeral() && !self.client_tag_ephemeral_kinds {
+            return None;
+        }
+
+        Some(client.clone())
+    }
+
+    /// Add event interceptor
+    ///
+    /// The interceptors run on the events signed by the `Client`, in registration order.
+    /// Check [`EventInterceptor`].
+    pub fn event_interceptor<T>(mut self, interceptor: T) -> Self
+    where
+        T: EventInterceptor + 'static,
+    {
+        self.event_interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    /// Run the interceptors on an event to sign
+    pub(crate) fn intercept_event(&self, builder: EventBuilder) -> EventBuilder {
+        if interceptor::is_private(&builder.kind()) {
+            return builder;
+        }
+
+        self.event_interceptors
+            .iter()
+            .fold(builder, |builder, interceptor| {
+                interceptor.intercept(builder)
+            })
+    }
+
+    /// Run the interceptors that opted in on a gift wrap rumor
+    pub(crate) fn intercept_rumor(&self, rumor: EventBuilder) -> EventBuilder {
+        self.event_interceptors
+            .iter()
+            .filter(|interceptor| interceptor.intercept_rumors())
+            .fold(rumor, |rumor, interceptor| interceptor.intercept(rumor))
+    }
+
+    /// Spam detector (default: None)
+    ///
+    /// Events scoring above the [`Options::spam_threshold`] are dropped before reaching `Client::handle_notifications`.
+    pub fn spam_detector(mut self, detector: Arc<dyn SpamDetector + Send + Sync>) -> Self {
+        self.spam_detector = Some(detector);
+        self
+    }
+
+    pub(crate) fn get_spam_detector(&self) -> Option<&Arc<dyn SpamDetector + Send + Sync>> {
+        self.spam_detector.as_ref()
+    }
+
+    /// Spam score above which events are dropped (default: 0.8)
+    pub fn spam_threshold(mut self, threshold: f32) -> Self {
+        self.spam_threshold = threshold;
+        self
+    }
+
+    pub(crate) fn get_spam_threshold(&self) -> f32 {
+        self.spam_threshold
+    }
+
+    /// Automatic authentication policy (NIP42) (default: disabled)
+    ///
+    /// When a relay closes a subscription or rejects an event with `auth-required`, the policy is consulted:
+    /// if allowed, the client authenticates and retries, otherwise [`RelayPoolNotification::AuthDeclined`] is sent.
+    ///
+    /// [`RelayPoolNotification::AuthDeclined`]: nostr_relay_pool::RelayPoolNotification::AuthDeclined
+    pub fn auth_policy(mut self, policy: AuthPolicy) -> Self {
+        self.auth_policy = policy;
+        self
+    }
+
+    pub(crate) fn get_auth_policy(&self) -> &AuthPolicy {
+        &self.auth_policy
+    }
+
+    /// Refuse to send events with error-severity tag lint findings (default: false)
+    ///
+    /// Check [`Event::lint`](nostr::Event::lint).
+    pub fn strict_send(mut self, strict: bool) -> Self {
+        self.strict_send = strict;
+        self
+    }
+
+    pub(crate) fn get_strict_send(&self) -> bool {
+        self.strict_send
+    }
+
+    /// Retry policy of the LNURL-pay endpoint resolution, used to zap (default: [`RetryPolicy::default`])
+    ///
+    /// The invoice request is never retried.
+    #[cfg(feature = "nip57")]
+    pub fn lnurl_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.lnurl_retry_policy = policy;
+        self
+    }
+
+    #[cfg(feature = "nip57")]
+    pub(crate) fn get_lnurl_retry_policy(&self) -> &RetryPolicy {
+        &self.lnurl_retry_policy
+    }
+
     /// Set default timeout
     pub fn timeout(self, timeout: Duration) -> Self {
         Self { timeout, ..self }
@@ -188,10 +413,19 @@ impl Options {
         }
     }
 
-    /// Proxy
+    /// Proxy (SOCKS5 without authentication)
     #[cfg(not(target_arch = "wasm32"))]
     pub fn proxy(mut self, proxy: Option<SocketAddr>) -> Self {
         self.proxy = proxy;
+        self.proxy_auth = None;
+        self
+    }
+
+    /// SOCKS5 proxy with username/password authentication
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn proxy_with_auth(mut self, proxy: SocketAddr, auth: SocksAuth) -> Self {
+        self.proxy = Some(proxy);
+        self.proxy_auth = Some(auth);
         self
     }
 
@@ -201,8 +435,107 @@ impl Options {
         self
     }
 
+    /// Set custom relay health thresholds (see `Relay::health`)
+    pub fn relay_health_thresholds(mut self, thresholds: RelayHealthThresholds) -> Self {
+        self.relay_health_thresholds = thresholds;
+        self
+    }
+
     /// Set pool options
+    #[deprecated(since = "0.30.0", note = "use `ClientBuilder::pool` instead")]
+    #[allow(deprecated)]
     pub fn pool(self, opts: RelayPoolOptions) -> Self {
         Self { pool: opts, ..self }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nostr::Tag;
+
+    use super::*;
+
+    #[test]
+    fn test_client_tag_exclusions() {
+        let opts = Options::new();
+        assert!(opts.get_client_tag(&Kind::TextNote).is_none());
+
+        let opts = Options::new().client_tag(Some(ClientTag::new("nostr-sdk")));
+        assert!(opts.get_client_tag(&Kind::TextNote).is_some());
+        assert!(opts.get_client_tag(&Kind::EncryptedDirectMessage).is_none());
+        assert!(opts.get_client_tag(&Kind::Seal).is_none());
+        assert!(opts.get_client_tag(&Kind::GiftWrap).is_none());
+        assert!(opts.get_client_tag(&Kind::SealedDirect).is_none());
+        assert!(opts.get_client_tag(&Kind::Ephemeral(20001)).is_none());
+
+        let opts = opts
+            .client_tag_private_kinds(true)
+            .client_tag_ephemeral_kinds(true);
+        assert!(opts.get_client_tag(&Kind::GiftWrap).is_some());
+        assert!(opts.get_client_tag(&Kind::Ephemeral(20001)).is_some());
+
+        // Disabled globally
+        let opts = opts.client_tag(None);
+        assert!(opts.get_client_tag(&Kind::TextNote).is_none());
+    }
+
+    #[derive(Debug)]
+    struct AddTag {
+        value: &'static str,
+        rumors: bool,
+    }
+
+    impl EventInterceptor for AddTag {
+        fn intercept(&self, builder: EventBuilder) -> EventBuilder {
+            builder.add_tags([Tag::Hashtag(self.value.to_string())])
+        }
+
+        fn intercept_rumors(&self) -> bool {
+            self.rumors
+        }
+    }
+
+    fn hashtags(builder: &EventBuilder) -> Vec<&str> {
+        builder
+            .tags()
+            .iter()
+            .filter_map(|tag| match tag {
+                Tag::Hashtag(t) => Some(t.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_event_interceptors() {
+        let opts = Options::new()
+            .event_interceptor(AddTag {
+                value: "org",
+                rumors: false,
+            })
+            .event_interceptor(AddTag {
+                value: "label",
+                rumors: true,
+            });
+
+        // Registration order
+        let builder = opts.intercept_event(EventBuilder::text_note("hello", []));
+        assert_eq!(hashtags(&builder), vec!["org", "label"]);
+        assert_eq!(builder.content(), "hello");
+
+        // Private kinds
+        for kind in [
+            Kind::EncryptedDirectMessage,
+            Kind::Seal,
+            Kind::GiftWrap,
+            Kind::SealedDirect,
+        ] {
+            let builder = opts.intercept_event(EventBuilder::new(kind, "secret", []));
+            assert!(builder.tags().is_empty());
+        }
+
+        // Rumors: opt-in only
+        let rumor = opts.intercept_rumor(EventBuilder::new(Kind::SealedDirect, "hi", []));
+        assert_eq!(hashtags(&rumor), vec!["label"]);
+    }
+}
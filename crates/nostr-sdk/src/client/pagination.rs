@@ -0,0 +1,305 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Pagination
+
+use std::collections::{BTreeSet, HashSet};
+use std::time::Duration;
+
+use nostr::{Event, EventId, Filter, JsonUtil, Timestamp};
+use nostr_database::Order;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use super::{Client, Error};
+
+/// Pagination source
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PaginationSource {
+    /// Relays (and local database, see `Client::get_events_of`)
+    #[default]
+    Relays,
+    /// Local database only
+    Database,
+}
+
+/// Pagination cursor
+///
+/// Opaque position of a [`Pagination`]: serialize it with [`PaginationCursor::as_json`] to persist it
+/// and resume the pagination later (i.e. after an app restart) with [`Pagination::resume`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaginationCursor {
+    /// Created at of the oldest returned event (inclusive upper bound of the next page)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    until: Option<Timestamp>,
+    /// IDs of the returned events created at `until`
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    boundary: BTreeSet<EventId>,
+    /// No more events to fetch
+    #[serde(default)]
+    exhausted: bool,
+}
+
+impl PaginationCursor {
+    /// Check if there may be more events to fetch
+    #[inline]
+    pub fn has_more(&self) -> bool {
+        !self.exhausted
+    }
+
+    /// Advance the cursor with a page, returning the events not already returned
+    fn advance(&mut self, events: Vec<Event>, page_size: usize) -> Vec<Event> {
+        let mut ids: HashSet<EventId> = HashSet::with_capacity(events.len());
+        let mut events: Vec<Event> = events
+            .into_iter()
+            .filter(|e| match self.until {
+                Some(until) => {
+                    e.created_at < until
+                        || (e.created_at == until && !self.boundary.contains(&e.id))
+                }
+                None => true,
+            })
+            .filter(|e| ids.insert(e.id))
+            .collect();
+
+        // Events from multiple relays: keep the newest
+        events.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(a.id.cmp(&b.id)));
+
+        // Short page: end reached
+        if events.len() < page_size {
+            self.exhausted = true;
+        }
+
+        events.truncate(page_size);
+
+        if let Some(oldest) = events.last().map(|e| e.created_at) {
+            if self.until != Some(oldest) {
+                self.until = Some(oldest);
+                self.boundary.clear();
+            }
+            self.boundary.extend(
+                events
+                    .iter()
+                    .filter(|e| e.created_at == oldest)
+                    .map(|e| e.id),
+            );
+        }
+
+        events
+    }
+}
+
+impl JsonUtil for PaginationCursor {
+    type Err = Error;
+}
+
+/// Pagination through the events history, from the newest to the oldest
+///
+/// Every page contains at most `page_size` events.
+/// The next page is requested with `until` set to the oldest event of the last page (inclusive),
+/// skipping the events already returned: events sharing the same timestamp are never lost or duplicated.
+///
+/// The pagination ends when a page comes back with less than `page_size` events.
+#[derive(Debug)]
+pub struct Pagination {
+    client: Client,
+    filters: Vec<Filter>,
+    page_size: usize,
+    source: PaginationSource,
+    timeout: Option<Duration>,
+    cursor: Mutex<PaginationCursor>,
+}
+
+impl Pagination {
+    /// New pagination
+    ///
+    /// The `until` of the filter, if set, is the starting point of the first page.
+    pub fn new(client: Client, filter: Filter, page_size: usize) -> Self {
+        Self::with_filters(client, vec![filter], page_size)
+    }
+
+    /// New pagination over multiple filters
+    ///
+    /// The events matching any of the filters are merged in the same pages.
+    pub(crate) fn with_filters(client: Client, filters: Vec<Filter>, page_size: usize) -> Self {
+        Self {
+            client,
+            filters,
+            page_size: page_size.max(1),
+            source: PaginationSource::default(),
+            timeout: None,
+            cursor: Mutex::new(PaginationCursor::default()),
+        }
+    }
+
+    /// Set source (default: [`PaginationSource::Relays`])
+    pub fn source(mut self, source: PaginationSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Timeout of every page request to relays (default: the client timeout)
+    pub(crate) fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Resume from a cursor
+    pub fn resume(mut self, cursor: PaginationCursor) -> Self {
+        self.cursor = Mutex::new(cursor);
+        self
+    }
+
+    /// Get current cursor
+    pub async fn cursor(&self) -> PaginationCursor {
+        self.cursor.lock().await.clone()
+    }
+
+    /// Check if there may be more events to fetch
+    pub async fn has_more(&self) -> bool {
+        self.cursor.lock().await.has_more()
+    }
+
+    /// Restore the initial state
+    pub fn reset(&mut self) -> &mut Self {
+        *self.cursor.get_mut() = PaginationCursor::default();
+        self
+    }
+
+    /// Fetch the next page
+    ///
+    /// Return an empty list if the end of history was reached.
+    pub async fn next_page(&self) -> Result<Vec<Event>, Error> {
+        // Held until the page is fetched, to not return the same page twice
+        let mut cursor = self.cursor.lock().await;
+
+        if !cursor.has_more() {
+            return Ok(Vec::new());
+        }
+
+        // Request also the already returned events at the boundary, to fill the page anyway
+        let limit: usize = self.page_size + cursor.boundary.len();
+        let filters: Vec<Filter> = self
+            .filters
+            .iter()
+            .cloned()
+            .map(|filter| {
+                let filter: Filter = filter.limit(limit);
+                match cursor.until {
+                    Some(until) => filter.until(until),
+                    None => filter,
+                }
+            })
+            .collect();
+
+        let events: Vec<Event> = match self.source {
+            PaginationSource::Relays => self.client.get_events_of(filters, self.timeout).await?,
+            PaginationSource::Database => {
+                self.client.database().query(filters, Order::Desc).await?
+            }
+        };
+
+        Ok(cursor.advance(events, self.page_size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys, Kind};
+    use nostr_database::{MemoryDatabase, MemoryDatabaseOptions, NostrDatabase};
+
+    use super::*;
+    use crate::ClientBuilder;
+
+    fn event(keys: &Keys, created_at: u64, content: &str) -> Event {
+        EventBuilder::new(Kind::TextNote, content, Vec::new())
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cursor_same_timestamp() {
+        let keys = Keys::generate();
+        // 3 events sharing the same timestamp, across a page boundary
+        let events: Vec<Event> = vec![
+            event(&keys, 3, "a"),
+            event(&keys, 2, "b"),
+            event(&keys, 2, "c"),
+            event(&keys, 2, "d"),
+            event(&keys, 1, "e"),
+        ];
+
+        let mut cursor = PaginationCursor::default();
+        let first = cursor.advance(events.clone(), 2);
+        assert_eq!(first.len(), 2);
+        assert!(cursor.has_more());
+
+        // The relay returns again the events at the boundary
+        let second = cursor.advance(events.clone(), 2);
+        assert_eq!(second.len(), 2);
+        assert!(second.iter().all(|e| !first.contains(e)));
+
+        let third = cursor.advance(events.clone(), 2);
+        assert_eq!(third.len(), 1);
+        assert!(!cursor.has_more());
+
+        let mut all: Vec<Event> = first.into_iter().chain(second).chain(third).collect();
+        all.sort();
+        let mut expected = events;
+        expected.sort();
+        assert_eq!(all, expected);
+    }
+
+    #[test]
+    fn test_cursor_json() {
+        let keys = Keys::generate();
+        let mut cursor = PaginationCursor::default();
+        cursor.advance(vec![event(&keys, 2, "a"), event(&keys, 1, "b")], 2);
+
+        let json: String = cursor.as_json();
+        assert_eq!(PaginationCursor::from_json(json).unwrap(), cursor);
+    }
+
+    #[tokio::test]
+    async fn test_pagination_database() {
+        let keys = Keys::generate();
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        });
+        // Same timestamp for all the events
+        for i in 0..5 {
+            database
+                .save_event(&event(&keys, 10, &i.to_string()))
+                .await
+                .unwrap();
+        }
+        let client = ClientBuilder::new().database(database).build();
+
+        let filter = Filter::new().author(keys.public_key());
+        let pagination = client
+            .paginate(filter, 2)
+            .source(PaginationSource::Database);
+
+        let mut ids: HashSet<EventId> = HashSet::new();
+        while pagination.has_more().await {
+            for event in pagination.next_page().await.unwrap() {
+                assert!(ids.insert(event.id));
+            }
+        }
+        assert_eq!(ids.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_reset() {
+        let mut pagination = Pagination::new(Client::default(), Filter::new(), 10);
+        pagination.cursor.get_mut().exhausted = true;
+        assert!(pagination.next_page().await.unwrap().is_empty());
+
+        pagination.reset();
+        assert!(pagination.has_more().await);
+        assert_eq!(pagination.cursor().await, PaginationCursor::default());
+    }
+}
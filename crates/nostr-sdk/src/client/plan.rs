@@ -0,0 +1,81 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Send plan
+
+use nostr::{Event, Url};
+
+/// Why a relay is (or isn't) targeted by a [`SendPlan`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SendPlanReason {
+    /// Relay has the `WRITE` flag
+    Write,
+    /// Relay has the `WRITE` flag but the hourly bandwidth cap is reached:
+    /// the event is held until the next window.
+    WriteRateLimited,
+    /// Relay doesn't have the `WRITE` flag
+    WriteDisabled,
+    /// Relay is disconnected and has a poor connection history
+    Unreachable,
+}
+
+impl SendPlanReason {
+    /// Check if the relay would be targeted
+    pub fn is_targeted(&self) -> bool {
+        matches!(self, Self::Write | Self::WriteRateLimited)
+    }
+}
+
+/// Routing decision for a single relay
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RelaySendPlan {
+    /// Relay url
+    pub url: Url,
+    /// Reason
+    pub reason: SendPlanReason,
+}
+
+impl RelaySendPlan {
+    /// Check if the relay would be targeted
+    pub fn is_targeted(&self) -> bool {
+        self.reason.is_targeted()
+    }
+}
+
+/// Where an event would be sent
+///
+/// Built by [`Client::plan_send`](super::Client::plan_send) and executed with [`Client::execute`](super::Client::execute).
+#[derive(Debug, Clone)]
+pub struct SendPlan {
+    pub(super) event: Event,
+    pub(super) relays: Vec<RelaySendPlan>,
+    pub(super) queue_offline: bool,
+}
+
+impl SendPlan {
+    /// Get event
+    pub fn event(&self) -> &Event {
+        &self.event
+    }
+
+    /// Get routing decision for every relay in the pool
+    pub fn relays(&self) -> &[RelaySendPlan] {
+        &self.relays
+    }
+
+    /// Get urls of the relays that would be targeted
+    pub fn targets(&self) -> impl Iterator<Item = &Url> + '_ {
+        self.relays
+            .iter()
+            .filter(|r| r.is_targeted())
+            .map(|r| &r.url)
+    }
+
+    /// Check if the event would be put in the offline queue instead of being sent
+    ///
+    /// Check [`Options::offline_queue_capacity`](super::Options::offline_queue_capacity).
+    pub fn is_queued_offline(&self) -> bool {
+        self.queue_offline
+    }
+}
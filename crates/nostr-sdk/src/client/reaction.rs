@@ -0,0 +1,111 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Reactions
+
+use std::collections::HashMap;
+
+use nostr::{Event, PublicKey};
+
+/// Reactions summary of an event
+///
+/// <https://github.com/nostr-protocol/nips/blob/master/25.md>
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReactionSummary {
+    /// Total number of reactions
+    pub total_count: u64,
+    /// Public keys that reacted, grouped by reaction content (i.e. `+`, `-`, `🔥`)
+    ///
+    /// An empty content is interpreted as `+`.
+    pub by_content: HashMap<String, Vec<PublicKey>>,
+    /// If the client public key reacted
+    pub did_react: bool,
+}
+
+impl ReactionSummary {
+    pub(crate) fn from_events<I>(events: I, public_key: Option<PublicKey>) -> Self
+    where
+        I: IntoIterator<Item = Event>,
+    {
+        let mut summary = Self::default();
+
+        for event in events.into_iter() {
+            let content: &str = match event.content().trim() {
+                "" => "+",
+                content => content,
+            };
+            let pubkeys: &mut Vec<PublicKey> =
+                summary.by_content.entry(content.to_string()).or_default();
+
+            // Count the same reaction only once per author
+            if !pubkeys.contains(&event.author()) {
+                pubkeys.push(event.author());
+                summary.total_count += 1;
+            }
+
+            if public_key == Some(event.author()) {
+                summary.did_react = true;
+            }
+        }
+
+        summary
+    }
+
+    /// Get the `n` most popular reactions, with their count
+    ///
+    /// Reactions with the same count are sorted by content.
+    pub fn top_reactions(&self, n: usize) -> Vec<(String, u64)> {
+        let mut reactions: Vec<(String, u64)> = self
+            .by_content
+            .iter()
+            .map(|(content, pubkeys)| (content.clone(), pubkeys.len() as u64))
+            .collect();
+        reactions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        reactions.truncate(n);
+        reactions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys, Kind};
+
+    use super::*;
+
+    fn reaction(keys: &Keys, content: &str) -> Event {
+        EventBuilder::new(Kind::Reaction, content, [])
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_reaction_summary() {
+        let me = Keys::generate();
+        let alice = Keys::generate();
+        let bob = Keys::generate();
+
+        let events = vec![
+            reaction(&alice, "+"),
+            reaction(&bob, ""),
+            reaction(&me, "🔥"),
+            reaction(&alice, "🔥"),
+            // Duplicated reaction
+            reaction(&alice, "+"),
+            reaction(&bob, "-"),
+        ];
+
+        let summary = ReactionSummary::from_events(events, Some(me.public_key()));
+        assert_eq!(summary.total_count, 5);
+        assert!(summary.did_react);
+        assert_eq!(summary.by_content.get("+").unwrap().len(), 2);
+        assert_eq!(
+            summary.top_reactions(2),
+            vec![(String::from("+"), 2), (String::from("🔥"), 2)]
+        );
+
+        let summary = ReactionSummary::from_events(Vec::new(), Some(me.public_key()));
+        assert!(!summary.did_react);
+        assert!(summary.top_reactions(3).is_empty());
+    }
+}
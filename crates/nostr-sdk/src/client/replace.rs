@@ -0,0 +1,211 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Replaceable events
+
+use std::time::Duration;
+
+use nostr::{Event, EventId, Timestamp};
+
+/// What to do when a replaceable event already exists on relays
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReplaceConflictStrategy {
+    /// Always publish the new event, also if the current one is newer
+    OverwriteAlways,
+    /// Skip the new event if the current one is newer
+    #[default]
+    FailIfNewer,
+    /// Publish the new event only if the current one is older than [`Duration`]
+    ///
+    /// Useful to avoid to overwrite a recent change made by another client.
+    OverwriteIfOlderThan(Duration),
+}
+
+/// Result of [`Client::replace_event`](super::Client::replace_event)
+/// and [`Client::publish_replaceable_event`](super::Client::publish_replaceable_event)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplaceResult {
+    /// Event published (returned by [`Client::publish_replaceable_event`](super::Client::publish_replaceable_event))
+    Published(EventId),
+    /// Event replaced
+    Replaced {
+        /// ID of the replaced event (`None` if no event was found on relays)
+        old_id: Option<EventId>,
+        /// ID of the new event
+        new_id: EventId,
+    },
+    /// Event not published
+    Skipped {
+        /// Timestamp of the current event on relays
+        existing_created_at: Timestamp,
+        /// Reason
+        reason: String,
+    },
+}
+
+/// What to do with the new event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ReplaceAction {
+    /// Publish it as it is
+    Publish,
+    /// Publish it with a new `created_at`, to replace the current one
+    BumpCreatedAt(Timestamp),
+    /// Don't publish it
+    Skip(ReplaceResult),
+}
+
+/// Check if the current event would win over the new one on relays
+///
+/// On ties, relays keep the event with the lowest ID.
+/// If the ID of the new event is not known yet (i.e. POW still to compute), the current event wins the ties.
+fn is_newer(current: &Event, created_at: Timestamp, id: Option<EventId>) -> bool {
+    current.created_at > created_at
+        || (current.created_at == created_at && id.map_or(true, |id| current.id < id))
+}
+
+/// Compare the current event with the new one, before signing it, according to the [`ReplaceConflictStrategy`]
+///
+/// `id` is the ID that the new event will have once signed, if already known.
+pub(crate) fn check_conflict(
+    current: &Event,
+    created_at: Timestamp,
+    id: Option<EventId>,
+    strategy: ReplaceConflictStrategy,
+    now: Timestamp,
+) -> ReplaceAction {
+    let is_newer: bool = is_newer(current, created_at, id);
+
+    match strategy {
+        ReplaceConflictStrategy::OverwriteAlways => {}
+        ReplaceConflictStrategy::FailIfNewer => {
+            if is_newer {
+                return ReplaceAction::Skip(ReplaceResult::Skipped {
+                    existing_created_at: current.created_at,
+                    reason: format!("a newer event was already published: {}", current.id),
+                });
+            }
+        }
+        ReplaceConflictStrategy::OverwriteIfOlderThan(duration) => {
+            if current.created_at + duration > now {
+                return ReplaceAction::Skip(ReplaceResult::Skipped {
+                    existing_created_at: current.created_at,
+                    reason: format!(
+                        "current event {} was published less than {} secs ago",
+                        current.id,
+                        duration.as_secs()
+                    ),
+                });
+            }
+        }
+    }
+
+    if is_newer {
+        ReplaceAction::BumpCreatedAt(current.created_at + 1u64)
+    } else {
+        ReplaceAction::Publish
+    }
+}
+
+/// Skip the new (signed) event if it's not strictly newer than the current one
+pub(crate) fn check_older(current: &Event, new: &Event) -> Option<ReplaceResult> {
+    if current.created_at >= new.created_at {
+        return Some(ReplaceResult::Skipped {
+            existing_created_at: current.created_at,
+            reason: format!(
+                "event {} on relays is not older than the new one",
+                current.id
+            ),
+        });
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys, Metadata};
+
+    use super::*;
+
+    fn metadata(keys: &Keys, name: &str, created_at: u64) -> Event {
+        EventBuilder::metadata(&Metadata::new().name(name))
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_check_conflict() {
+        let keys = Keys::generate();
+        let now = Timestamp::from(1_000);
+        let current = metadata(&keys, "current", 900);
+
+        // Older current event
+        let new = metadata(&keys, "new", 950);
+        let strategy = ReplaceConflictStrategy::FailIfNewer;
+        assert_eq!(
+            check_conflict(&current, new.created_at, Some(new.id), strategy, now),
+            ReplaceAction::Publish
+        );
+
+        // Newer current event
+        let new = metadata(&keys, "new", 850);
+        assert!(matches!(
+            check_conflict(&current, new.created_at, Some(new.id), strategy, now),
+            ReplaceAction::Skip(ReplaceResult::Skipped { .. })
+        ));
+        let strategy = ReplaceConflictStrategy::OverwriteAlways;
+        assert_eq!(
+            check_conflict(&current, new.created_at, Some(new.id), strategy, now),
+            ReplaceAction::BumpCreatedAt(Timestamp::from(901))
+        );
+
+        // Current event too recent
+        let strategy = ReplaceConflictStrategy::OverwriteIfOlderThan(Duration::from_secs(200));
+        assert!(matches!(
+            check_conflict(&current, new.created_at, Some(new.id), strategy, now),
+            ReplaceAction::Skip(..)
+        ));
+        let strategy = ReplaceConflictStrategy::OverwriteIfOlderThan(Duration::from_secs(50));
+        assert_eq!(
+            check_conflict(&current, new.created_at, Some(new.id), strategy, now),
+            ReplaceAction::BumpCreatedAt(Timestamp::from(901))
+        );
+    }
+
+    #[test]
+    fn test_check_conflict_tie() {
+        // Same `created_at`: the result depends on the IDs of the signed events
+        let keys = Keys::generate();
+        let now = Timestamp::from(1_000);
+        let a = metadata(&keys, "a", 900);
+        let b = metadata(&keys, "b", 900);
+        let (lowest, highest) = if a.id < b.id { (a, b) } else { (b, a) };
+
+        let strategy = ReplaceConflictStrategy::FailIfNewer;
+        assert_eq!(
+            check_conflict(&highest, lowest.created_at, Some(lowest.id), strategy, now),
+            ReplaceAction::Publish
+        );
+        assert!(matches!(
+            check_conflict(&lowest, highest.created_at, Some(highest.id), strategy, now),
+            ReplaceAction::Skip(..)
+        ));
+
+        // ID not known yet: the current event wins
+        let strategy = ReplaceConflictStrategy::OverwriteAlways;
+        assert_eq!(
+            check_conflict(&highest, lowest.created_at, None, strategy, now),
+            ReplaceAction::BumpCreatedAt(Timestamp::from(901))
+        );
+    }
+
+    #[test]
+    fn test_check_older() {
+        let keys = Keys::generate();
+        let current = metadata(&keys, "current", 900);
+        assert!(check_older(&current, &metadata(&keys, "new", 901)).is_none());
+        assert!(check_older(&current, &metadata(&keys, "new", 900)).is_some());
+        assert!(check_older(&current, &metadata(&keys, "new", 899)).is_some());
+    }
+}
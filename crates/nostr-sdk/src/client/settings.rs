@@ -0,0 +1,178 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Settings sync (NIP78 app data, self-encrypted with NIP44)
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use nostr::serde_json::{self, Map, Value};
+use nostr::{EventId, JsonUtil};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+/// Settings payload, stored encrypted in the `kind:30078` event
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SettingsPayload {
+    /// Revision counter, increased at every publish
+    pub revision: u64,
+    /// Settings (top-level keys)
+    pub data: Map<String, Value>,
+}
+
+impl JsonUtil for SettingsPayload {
+    type Err = serde_json::Error;
+}
+
+/// Settings sync conflict
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsConflict {
+    /// Key changed both locally and remotely since the last sync
+    ///
+    /// The local value is kept (last writer), the remote one is reported to allow to restore it.
+    Key {
+        /// Top-level key
+        key: String,
+        /// Kept local value
+        local: Value,
+        /// Discarded remote value
+        remote: Value,
+    },
+    /// Remote settings can't be decrypted or parsed
+    ///
+    /// Local settings are returned untouched and nothing is published.
+    Corrupted {
+        /// Remote event ID
+        event_id: EventId,
+        /// Error
+        error: String,
+    },
+}
+
+/// Settings sync outcome
+#[derive(Debug, Clone)]
+pub struct SyncOutcome<T> {
+    /// Merged settings
+    pub value: T,
+    /// Revision of the merged settings
+    pub revision: u64,
+    /// Merged settings published to relays
+    pub published: bool,
+    /// Conflicts
+    pub conflicts: Vec<SettingsConflict>,
+}
+
+/// Last synced settings, per app ID (base of the three-way merge)
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SettingsSync {
+    bases: Arc<RwLock<HashMap<String, SettingsPayload>>>,
+}
+
+impl SettingsSync {
+    pub async fn base(&self, app_id: &str) -> Option<SettingsPayload> {
+        let bases = self.bases.read().await;
+        bases.get(app_id).cloned()
+    }
+
+    pub async fn set_base(&self, app_id: String, payload: SettingsPayload) {
+        let mut bases = self.bases.write().await;
+        bases.insert(app_id, payload);
+    }
+}
+
+/// Three-way merge of top-level keys
+///
+/// Without a base (first sync of the session), the remote values win and local-only keys are kept.
+pub(crate) fn merge(
+    local: &Map<String, Value>,
+    remote: &Map<String, Value>,
+    base: Option<&Map<String, Value>>,
+) -> (Map<String, Value>, Vec<SettingsConflict>) {
+    let keys: BTreeSet<&String> = local.keys().chain(remote.keys()).collect();
+
+    let mut merged: Map<String, Value> = Map::new();
+    let mut conflicts: Vec<SettingsConflict> = Vec::new();
+
+    for key in keys.into_iter() {
+        let l: Option<&Value> = local.get(key);
+        let r: Option<&Value> = remote.get(key);
+        let b: Option<&Value> = match base {
+            Some(base) => base.get(key),
+            None => {
+                if let Some(value) = r.or(l) {
+                    merged.insert(key.clone(), value.clone());
+                }
+                continue;
+            }
+        };
+
+        let value: Option<&Value> = if l == r || r == b {
+            l
+        } else if l == b {
+            r
+        } else {
+            // Changed on both sides
+            if let (Some(l), Some(r)) = (l, r) {
+                conflicts.push(SettingsConflict::Key {
+                    key: key.clone(),
+                    local: l.clone(),
+                    remote: r.clone(),
+                });
+            }
+            l.or(r)
+        };
+
+        if let Some(value) = value {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+
+    (merged, conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::serde_json::json;
+
+    use super::*;
+
+    fn map(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => panic!("not an object"),
+        }
+    }
+
+    #[test]
+    fn test_merge_without_base() {
+        let local = map(json!({"theme": "light", "local_only": true}));
+        let remote = map(json!({"theme": "dark", "lang": "en"}));
+        let (merged, conflicts) = merge(&local, &remote, None);
+        assert_eq!(
+            merged,
+            map(json!({"theme": "dark", "lang": "en", "local_only": true}))
+        );
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_merge_with_base() {
+        let base = map(json!({"theme": "light", "lang": "en", "muted": [], "nsfw": false}));
+        let local = map(json!({"theme": "dark", "lang": "en", "muted": ["a"], "nsfw": false}));
+        let remote = map(json!({"theme": "light", "lang": "it", "muted": ["b"]}));
+        let (merged, conflicts) = merge(&local, &remote, Some(&base));
+        assert_eq!(
+            merged,
+            map(json!({"theme": "dark", "lang": "it", "muted": ["a"]}))
+        );
+        assert_eq!(
+            conflicts,
+            vec![SettingsConflict::Key {
+                key: String::from("muted"),
+                local: json!(["a"]),
+                remote: json!(["b"]),
+            }]
+        );
+    }
+}
@@ -0,0 +1,308 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Spam detection
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+
+use lru::LruCache;
+use nostr::{Event, Kind, PublicKey, Timestamp};
+
+const DAY: u64 = 60 * 60 * 24;
+const MAX_TRACKED_AUTHORS: usize = 10_000;
+
+/// Spam score
+///
+/// From `0.0` (clean) to `1.0` (definite spam).
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct SpamScore(f32);
+
+impl SpamScore {
+    /// Clean
+    pub const CLEAN: Self = Self(0.0);
+    /// Definite spam
+    pub const SPAM: Self = Self(1.0);
+
+    /// New spam score
+    ///
+    /// The value is clamped between `0.0` and `1.0`.
+    pub fn new(score: f32) -> Self {
+        if score.is_nan() {
+            return Self::CLEAN;
+        }
+        Self(score.clamp(0.0, 1.0))
+    }
+
+    /// Get score
+    pub fn as_f32(&self) -> f32 {
+        self.0
+    }
+}
+
+/// Spam detector
+///
+/// Implement this trait to plug a custom detector into the `Client`.
+pub trait SpamDetector: fmt::Debug {
+    /// Record a received event
+    ///
+    /// Called once per received event, before [`SpamDetector::score`]. Default: no-op.
+    fn ingest(&self, _event: &Event) {}
+
+    /// Score the event
+    ///
+    /// Must not change the detector state: the same event can be scored multiple times.
+    fn score(&self, event: &Event) -> SpamScore;
+}
+
+#[derive(Debug, Default)]
+struct AuthorActivity {
+    /// Number of text notes seen
+    seen: usize,
+    /// Number of text notes seen per `created_at`, in the last day of the newest one
+    created_at: BTreeMap<Timestamp, usize>,
+}
+
+impl AuthorActivity {
+    fn insert(&mut self, created_at: Timestamp) {
+        self.seen += 1;
+        *self.created_at.entry(created_at).or_default() += 1;
+
+        // Keep only the last day of the newest text note
+        if let Some(newest) = self.created_at.keys().next_back().copied() {
+            let since: Timestamp = newest - DAY;
+            self.created_at = self.created_at.split_off(&since);
+        }
+    }
+
+    /// Number of text notes created in the day before `until`
+    fn last_day(&self, until: Timestamp) -> usize {
+        self.created_at
+            .range((until - DAY)..=until)
+            .map(|(_, n)| n)
+            .sum()
+    }
+}
+
+/// Builtin spam detector
+///
+/// Heuristics (scores are summed):
+/// * POW below the minimum difficulty (`0.5`);
+/// * unknown author publishing more than 100 text notes per day, by `created_at` (`0.5`);
+/// * text notes with more than 3 URLs (`0.4`);
+/// * very short text notes among the first 5 seen from an author (`0.3`).
+///
+/// The activity of the least recently active authors is evicted after 10000 tracked authors.
+#[derive(Debug)]
+pub struct BuiltinSpamDetector {
+    min_pow: u8,
+    known_authors: HashSet<PublicKey>,
+    activity: Mutex<LruCache<PublicKey, AuthorActivity>>,
+}
+
+impl Default for BuiltinSpamDetector {
+    fn default() -> Self {
+        let capacity: NonZeroUsize =
+            NonZeroUsize::new(MAX_TRACKED_AUTHORS).expect("Capacity must not be zero");
+        Self {
+            min_pow: 0,
+            known_authors: HashSet::new(),
+            activity: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+}
+
+impl BuiltinSpamDetector {
+    /// Max text notes per day for unknown authors
+    pub const MAX_EVENTS_PER_DAY: usize = 100;
+    /// Max URLs in a text note
+    pub const MAX_URLS: usize = 3;
+    /// Text notes shorter than this are considered very short
+    pub const SHORT_CONTENT_LEN: usize = 10;
+    /// Number of text notes checked for very short content
+    pub const FIRST_EVENTS: usize = 5;
+
+    /// New builtin spam detector
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Minimum POW difficulty (default: 0, disabled)
+    pub fn min_pow(mut self, difficulty: u8) -> Self {
+        self.min_pow = difficulty;
+        self
+    }
+
+    /// Known authors (ex. contacts), not subject to the volume heuristic
+    pub fn known_authors<I>(mut self, authors: I) -> Self
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        self.known_authors = authors.into_iter().collect();
+        self
+    }
+
+    /// Get the author activity: (notes seen, notes in the day before the event)
+    fn activity(&self, event: &Event) -> (usize, usize) {
+        let activity = self.activity.lock().unwrap_or_else(|e| e.into_inner());
+        match activity.peek(&event.pubkey) {
+            Some(author) => (author.seen, author.last_day(event.created_at)),
+            None => (0, 0),
+        }
+    }
+}
+
+impl SpamDetector for BuiltinSpamDetector {
+    fn ingest(&self, event: &Event) {
+        if event.kind == Kind::TextNote {
+            let mut activity = self.activity.lock().unwrap_or_else(|e| e.into_inner());
+            activity
+                .get_or_insert_mut(event.pubkey, AuthorActivity::default)
+                .insert(event.created_at);
+        }
+    }
+
+    fn score(&self, event: &Event) -> SpamScore {
+        let mut score: f32 = 0.0;
+
+        if self.min_pow > 0 && event.id.leading_zero_bits() < self.min_pow {
+            score += 0.5;
+        }
+
+        if event.kind == Kind::TextNote {
+            let (seen, last_day) = self.activity(event);
+
+            if last_day > Self::MAX_EVENTS_PER_DAY && !self.known_authors.contains(&event.pubkey) {
+                score += 0.5;
+            }
+
+            let urls: usize = event.content.matches("http://").count()
+                + event.content.matches("https://").count();
+            if urls > Self::MAX_URLS {
+                score += 0.4;
+            }
+
+            if seen <= Self::FIRST_EVENTS
+                && event.content.trim().chars().count() < Self::SHORT_CONTENT_LEN
+            {
+                score += 0.3;
+            }
+        }
+
+        SpamScore::new(score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys};
+
+    use super::*;
+
+    #[test]
+    fn test_spam_score_clamp() {
+        assert_eq!(SpamScore::new(2.0), SpamScore::SPAM);
+        assert_eq!(SpamScore::new(-1.0), SpamScore::CLEAN);
+        assert_eq!(SpamScore::new(f32::NAN), SpamScore::CLEAN);
+    }
+
+    #[test]
+    fn test_builtin_detector() {
+        let keys = Keys::generate();
+        let detector = BuiltinSpamDetector::new();
+
+        let clean = EventBuilder::text_note("Hello, this is a normal note", [])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(detector.score(&clean), SpamScore::CLEAN);
+
+        let links = EventBuilder::text_note(
+            "https://a.com https://b.com https://c.com https://d.com buy now",
+            [],
+        )
+        .to_event(&keys)
+        .unwrap();
+        assert!(detector.score(&links) > SpamScore::CLEAN);
+
+        // Very short content from a new author
+        let short = EventBuilder::text_note("gm", [])
+            .to_event(&Keys::generate())
+            .unwrap();
+        assert!(detector.score(&short) > SpamScore::CLEAN);
+
+        // Scoring is pure
+        assert_eq!(detector.score(&short), detector.score(&short));
+        for _ in 0..10 {
+            detector.score(&clean);
+        }
+        assert_eq!(detector.score(&clean), SpamScore::CLEAN);
+
+        // High volume
+        let spammer = Keys::generate();
+        let now = Timestamp::now();
+        let mut last = SpamScore::CLEAN;
+        for i in 0..=BuiltinSpamDetector::MAX_EVENTS_PER_DAY {
+            let event = EventBuilder::text_note(format!("Message number {i}"), [])
+                .custom_created_at(now - (BuiltinSpamDetector::MAX_EVENTS_PER_DAY - i) as u64)
+                .to_event(&spammer)
+                .unwrap();
+            detector.ingest(&event);
+            last = detector.score(&event);
+        }
+        assert!(last >= SpamScore::new(0.5));
+
+        // Same volume spread over days (ex. history backfill)
+        let author = Keys::generate();
+        for i in 0..=BuiltinSpamDetector::MAX_EVENTS_PER_DAY {
+            let event = EventBuilder::text_note(format!("Message number {i}"), [])
+                .custom_created_at(now - i as u64 * DAY)
+                .to_event(&author)
+                .unwrap();
+            detector.ingest(&event);
+            assert_eq!(detector.score(&event), SpamScore::CLEAN);
+        }
+
+        // POW
+        let detector = BuiltinSpamDetector::new().min_pow(20);
+        assert!(detector.score(&clean) >= SpamScore::new(0.5));
+    }
+
+    #[test]
+    fn test_builtin_detector_eviction() {
+        let detector = BuiltinSpamDetector {
+            activity: Mutex::new(LruCache::new(NonZeroUsize::new(2).unwrap())),
+            ..Default::default()
+        };
+        let spammer = Keys::generate();
+        let now = Timestamp::now();
+        for i in 0..=BuiltinSpamDetector::MAX_EVENTS_PER_DAY {
+            let event = EventBuilder::text_note(format!("Message number {i}"), [])
+                .custom_created_at(now - i as u64)
+                .to_event(&spammer)
+                .unwrap();
+            detector.ingest(&event);
+        }
+
+        // A new author evicts only the least recently active one
+        let first = EventBuilder::text_note("Hello, this is a normal note", [])
+            .to_event(&Keys::generate())
+            .unwrap();
+        detector.ingest(&first);
+        let event = EventBuilder::text_note("Another message", [])
+            .to_event(&spammer)
+            .unwrap();
+        detector.ingest(&event);
+        let second = EventBuilder::text_note("Hello, this is a normal note", [])
+            .to_event(&Keys::generate())
+            .unwrap();
+        detector.ingest(&second);
+
+        assert!(detector.score(&event) >= SpamScore::new(0.5));
+        let activity = detector.activity.lock().unwrap();
+        assert_eq!(activity.len(), 2);
+        assert!(!activity.contains(&first.pubkey));
+    }
+}
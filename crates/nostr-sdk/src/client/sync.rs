@@ -0,0 +1,106 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Sync
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use nostr::{Event, EventId, Filter, Kind, PublicKey, Timestamp, Url};
+use nostr_relay_pool::{FilterOptions, Relay};
+
+/// Max number of kinds per filter
+pub(super) const KINDS_BATCH_SIZE: usize = 10;
+
+/// Sync report
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    /// Unique events fetched from relays
+    pub events_fetched: u64,
+    /// Events not already stored in the database
+    pub events_new: u64,
+    /// Events already stored in the database
+    pub events_duplicate: u64,
+    /// Queried relays
+    pub relays_queried: Vec<Url>,
+}
+
+/// Split kinds in batches of filters
+pub(super) fn batch_filters(public_key: PublicKey, kinds: &[Kind]) -> Vec<Filter> {
+    if kinds.is_empty() {
+        return vec![Filter::new().author(public_key)];
+    }
+
+    kinds
+        .chunks(KINDS_BATCH_SIZE)
+        .map(|kinds| {
+            Filter::new()
+                .author(public_key)
+                .kinds(kinds.iter().copied())
+        })
+        .collect()
+}
+
+/// Fetch all the events of the filters from the relay
+///
+/// Relays cap the number of stored events returned for a single request,
+/// so the filters are re-sent with `until` set to the oldest received event until EOSE returns nothing new.
+pub(super) async fn fetch_all(relay: Relay, filters: Vec<Filter>, timeout: Duration) -> Vec<Event> {
+    let mut ids: HashSet<EventId> = HashSet::new();
+    let mut events: Vec<Event> = Vec::new();
+
+    for filter in filters.into_iter() {
+        let mut page_filter: Filter = filter.clone();
+        loop {
+            let page: Vec<Event> = match relay
+                .get_events_of(vec![page_filter], timeout, FilterOptions::ExitOnEOSE)
+                .await
+            {
+                Ok(page) => page,
+                Err(e) => {
+                    tracing::error!("Failed to sync events from {}: {e}", relay.url());
+                    break;
+                }
+            };
+
+            let mut oldest: Option<Timestamp> = None;
+            let mut new: usize = 0;
+            for event in page.into_iter() {
+                if oldest.map_or(true, |oldest| event.created_at < oldest) {
+                    oldest = Some(event.created_at);
+                }
+                if ids.insert(event.id) {
+                    new += 1;
+                    events.push(event);
+                }
+            }
+
+            match oldest {
+                // Keep the oldest timestamp (inclusive): other events may share it
+                Some(oldest) if new > 0 => page_filter = filter.clone().until(oldest),
+                _ => break,
+            }
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::Keys;
+
+    use super::*;
+
+    #[test]
+    fn test_batch_filters() {
+        let public_key = Keys::generate().public_key();
+        let kinds: Vec<Kind> = (0..25u64).map(Kind::from).collect();
+        let filters = batch_filters(public_key, &kinds);
+        assert_eq!(filters.len(), 3);
+        assert_eq!(filters[2].kinds.as_ref().map(|k| k.len()), Some(5));
+
+        assert_eq!(batch_filters(public_key, &[]).len(), 1);
+    }
+}
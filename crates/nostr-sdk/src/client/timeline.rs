@@ -0,0 +1,117 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Timeline
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use nostr::{Event, Filter};
+
+use super::pagination::{Pagination, PaginationSource};
+use super::{Client, Error};
+
+/// Cursor-based pagination through relays history, merging multiple filters
+///
+/// Every page contains at most `page_size` events, ordered from the newest to the oldest.
+/// Check [`Pagination`] for the pagination details: events sharing a timestamp across a page boundary are never skipped.
+#[derive(Debug)]
+pub struct Timeline {
+    pagination: Pagination,
+    has_more: AtomicBool,
+}
+
+impl Timeline {
+    /// New timeline
+    ///
+    /// The `until` of the filters, if set, is the starting point of the first page.
+    pub fn new(client: Client, filters: Vec<Filter>, page_size: usize) -> Self {
+        Self {
+            pagination: Pagination::with_filters(client, filters, page_size),
+            has_more: AtomicBool::new(true),
+        }
+    }
+
+    /// Set source (default: [`PaginationSource::Relays`])
+    pub fn source(mut self, source: PaginationSource) -> Self {
+        self.pagination = self.pagination.source(source);
+        self
+    }
+
+    /// Check if there may be more events to fetch
+    ///
+    /// Return `false` after a page with less than `page_size` events.
+    pub fn has_more(&self) -> bool {
+        self.has_more.load(Ordering::SeqCst)
+    }
+
+    /// Fetch the next page
+    ///
+    /// Return an empty list if the end of history was reached.
+    pub async fn next_page(&self) -> Result<Vec<Event>, Error> {
+        let page: Vec<Event> = self.pagination.next_page().await?;
+        self.has_more
+            .store(self.pagination.has_more().await, Ordering::SeqCst);
+        Ok(page)
+    }
+
+    /// Restore the initial state
+    pub fn reset(&mut self) -> &mut Self {
+        self.pagination.reset();
+        *self.has_more.get_mut() = true;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, EventId, Keys, Kind, Timestamp};
+    use nostr_database::{MemoryDatabase, MemoryDatabaseOptions, NostrDatabase};
+
+    use super::*;
+    use crate::ClientBuilder;
+
+    #[tokio::test]
+    async fn test_timeline_multiple_filters() {
+        let keys = Keys::generate();
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        });
+        for (i, kind) in [Kind::TextNote, Kind::Repost, Kind::TextNote, Kind::Repost]
+            .into_iter()
+            .enumerate()
+        {
+            let event = EventBuilder::new(kind, i.to_string(), [])
+                .custom_created_at(Timestamp::from(10))
+                .to_event(&keys)
+                .unwrap();
+            database.save_event(&event).await.unwrap();
+        }
+        let client = ClientBuilder::new().database(database).build();
+
+        let filters = vec![
+            Filter::new().author(keys.public_key()).kind(Kind::TextNote),
+            Filter::new().author(keys.public_key()).kind(Kind::Repost),
+        ];
+        let mut timeline = Timeline::new(client, filters, 3).source(PaginationSource::Database);
+
+        let mut ids: Vec<EventId> = Vec::new();
+        while timeline.has_more() {
+            ids.extend(
+                timeline
+                    .next_page()
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|e| e.id),
+            );
+        }
+        assert_eq!(ids.len(), 4);
+
+        // Restart from the newest
+        timeline.reset();
+        assert!(timeline.has_more());
+        assert_eq!(timeline.next_page().await.unwrap().len(), 3);
+    }
+}
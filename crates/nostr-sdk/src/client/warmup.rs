@@ -0,0 +1,89 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Warmup
+
+use std::time::Duration;
+
+use nostr::Url;
+use nostr_relay_pool::relay::Error as RelayError;
+use nostr_relay_pool::RelayStatus;
+use thiserror::Error;
+
+/// Interval between relay status checks
+pub(super) const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Warmup error
+#[derive(Debug, Error)]
+pub enum WarmupError {
+    /// No relay added to the pool
+    #[error("no relays")]
+    NoRelays,
+    /// No relay connected
+    #[error("all relays failed to connect")]
+    AllRelaysFailed(WarmupReport),
+}
+
+/// Warmup report
+#[derive(Debug, Default)]
+pub struct WarmupReport {
+    /// Connected relays
+    pub connected: Vec<Url>,
+    /// Relays that failed to connect, with the error of the last attempt
+    pub failed: Vec<(Url, RelayError)>,
+    /// Relays still connecting when the timeout expired
+    pub timed_out: Vec<Url>,
+}
+
+/// Check if the relay is still trying to establish the first connection
+#[inline]
+pub(super) fn is_pending(status: RelayStatus) -> bool {
+    matches!(
+        status,
+        RelayStatus::Initialized | RelayStatus::Pending | RelayStatus::Connecting
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use super::*;
+    use crate::Client;
+
+    #[tokio::test]
+    async fn test_warmup_without_relays() {
+        let client = Client::default();
+        let res = client.warmup(Duration::from_secs(1)).await;
+        assert!(matches!(res, Err(WarmupError::NoRelays)));
+    }
+
+    #[tokio::test]
+    async fn test_warmup_all_relays_failed() {
+        // Bind and drop a listener to get a closed local port
+        let port: u16 = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let url = Url::parse(&format!("ws://127.0.0.1:{port}")).unwrap();
+
+        let client = Client::default();
+        client.add_relay(url.clone()).await.unwrap();
+
+        match client.warmup(Duration::from_secs(10)).await {
+            Err(WarmupError::AllRelaysFailed(report)) => {
+                assert!(report.connected.is_empty());
+                assert!(report.timed_out.is_empty());
+                assert_eq!(report.failed.len(), 1);
+                assert_eq!(report.failed[0].0, url);
+                assert!(matches!(
+                    report.failed[0].1,
+                    RelayError::ConnectionFailed(..)
+                ));
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Self watcher
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use nostr::{Event, Kind};
+use tokio::sync::{Mutex, RwLock};
+
+/// Latest versions of the user's own replaceable events
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SelfWatch {
+    kinds: Arc<RwLock<BTreeSet<Kind>>>,
+    latest: Arc<Mutex<HashMap<Kind, Event>>>,
+}
+
+impl SelfWatch {
+    /// Add kinds to watch
+    ///
+    /// Return all the watched kinds and `true` if no kind was watched before.
+    pub async fn add_kinds<I>(&self, kinds: I) -> (BTreeSet<Kind>, bool)
+    where
+        I: IntoIterator<Item = Kind>,
+    {
+        let mut watched = self.kinds.write().await;
+        let first: bool = watched.is_empty();
+        watched.extend(kinds);
+        (watched.clone(), first)
+    }
+
+    pub async fn is_watched(&self, kind: &Kind) -> bool {
+        let watched = self.kinds.read().await;
+        watched.contains(kind)
+    }
+
+    /// Keep the event if newer than the current one
+    ///
+    /// Return `true` if the newest known version changed.
+    pub async fn update(&self, event: &Event) -> bool {
+        let mut latest = self.latest.lock().await;
+        if let Some(current) = latest.get(&event.kind) {
+            // On ties, relays keep the event with the lowest ID
+            let is_newer: bool = event.created_at > current.created_at
+                || (event.created_at == current.created_at && event.id < current.id);
+            if !is_newer {
+                return false;
+            }
+        }
+        latest.insert(event.kind, event.clone());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys, Timestamp};
+
+    use super::*;
+
+    fn contact_list(keys: &Keys, created_at: u64) -> Event {
+        EventBuilder::new(Kind::ContactList, "", [])
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_self_watch_latest_wins() {
+        let keys = Keys::generate();
+        let watch = SelfWatch::default();
+
+        let (kinds, first) = watch.add_kinds([Kind::ContactList]).await;
+        assert!(first);
+        assert_eq!(kinds.len(), 1);
+        let (kinds, first) = watch.add_kinds([Kind::RelayList]).await;
+        assert!(!first);
+        assert_eq!(kinds.len(), 2);
+        assert!(watch.is_watched(&Kind::RelayList).await);
+
+        let old = contact_list(&keys, 1);
+        let new = contact_list(&keys, 2);
+
+        assert!(watch.update(&old).await);
+        assert!(watch.update(&new).await);
+
+        // Same event from another relay
+        assert!(!watch.update(&new).await);
+
+        // Older event
+        assert!(!watch.update(&old).await);
+    }
+}
@@ -7,6 +7,8 @@ use std::str::FromStr;
 use lnurl_pay::api::Lud06OrLud16;
 use lnurl_pay::{LightningAddress, LnUrl};
 use nostr::prelude::*;
+use nostr::util::http::{self, RetryPolicy};
+use serde::Deserialize;
 
 use super::{Client, Error};
 
@@ -57,6 +59,90 @@ pub struct ZapDetails {
     message: String,
 }
 
+/// LNURL-pay response: the requested data or an error (LUD06)
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LnUrlResponse<T> {
+    Ok(T),
+    Error { reason: String },
+}
+
+impl<T> LnUrlResponse<T> {
+    fn into_result(self) -> Result<T, Error> {
+        match self {
+            Self::Ok(data) => Ok(data),
+            Self::Error { reason } => Err(Error::ImpossibleToZap(reason)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PayResponse {
+    callback: String,
+    #[serde(rename = "minSendable")]
+    min_sendable: u64,
+    #[serde(rename = "maxSendable")]
+    max_sendable: u64,
+}
+
+#[derive(Deserialize)]
+struct InvoiceResponse {
+    pr: String,
+}
+
+fn lnurl_endpoint(lud: &Lud06OrLud16) -> String {
+    match lud {
+        Lud06OrLud16::Lud06(lnurl) => lnurl.endpoint(),
+        Lud06OrLud16::Lud16(address) => address.endpoint(),
+    }
+}
+
+/// Get invoice from a LNURL-pay endpoint
+///
+/// The endpoint resolution is retried according to the [`RetryPolicy`].
+/// The invoice request isn't idempotent: it's sent only once, with the deadline of the policy.
+async fn get_invoice(
+    policy: &RetryPolicy,
+    endpoint: &str,
+    msats: u64,
+    comment: Option<String>,
+    zap_request: Option<String>,
+) -> Result<String, Error> {
+    let client = reqwest::Client::new();
+
+    // Resolve endpoint
+    let res = http::send(policy, || client.get(endpoint).send()).await?;
+    let pay: PayResponse = res
+        .error_for_status()?
+        .json::<LnUrlResponse<PayResponse>>()
+        .await?
+        .into_result()?;
+
+    if msats < pay.min_sendable || msats > pay.max_sendable {
+        return Err(Error::ImpossibleToZap(format!(
+            "amount must be between {} and {} msats",
+            pay.min_sendable, pay.max_sendable
+        )));
+    }
+
+    // Request invoice
+    let mut query: Vec<(&str, String)> = vec![("amount", msats.to_string())];
+    if let Some(comment) = comment {
+        query.push(("comment", comment));
+    }
+    if let Some(zap_request) = zap_request {
+        query.push(("nostr", zap_request));
+    }
+    let single: RetryPolicy = policy.clone().attempts(1);
+    let res = http::send(&single, || client.get(&pay.callback).query(&query).send()).await?;
+    let invoice: InvoiceResponse = res
+        .error_for_status()?
+        .json::<LnUrlResponse<InvoiceResponse>>()
+        .await?
+        .into_result()?;
+    Ok(invoice.pr)
+}
+
 impl ZapDetails {
     /// Create new Zap Details
     ///
@@ -146,6 +232,7 @@ impl Client {
         details: Option<ZapDetails>,
         event_id: Option<EventId>,
     ) -> Result<Vec<String>, Error> {
+        let policy: &RetryPolicy = self.opts.get_lnurl_retry_policy();
         let mut invoices: Vec<String> = Vec::with_capacity(2);
         let mut msats: u64 = satoshi * 1000;
 
@@ -156,12 +243,12 @@ impl Client {
 
         // Check if LUD is equal to Rust Nostr LUD
         if rust_nostr_lud != lud {
-            match lnurl_pay::api::get_invoice(
-                rust_nostr_lud,
+            match get_invoice(
+                policy,
+                &lnurl_endpoint(&rust_nostr_lud),
                 rust_nostr_msats,
                 Some(SUPPORT_RUST_NOSTR_MSG.to_string()),
                 None,
-                None,
             )
             .await
             {
@@ -202,9 +289,112 @@ impl Client {
 
         // Get invoice
         let invoice: String =
-            lnurl_pay::api::get_invoice(lud, msats, None, zap_request, None).await?;
+            get_invoice(policy, &lnurl_endpoint(&lud), msats, None, zap_request).await?;
         invoices.push(invoice);
 
         Ok(invoices)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use axum::extract::{Query, State};
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use nostr::serde_json::{json, Value};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Calls {
+        resolve: Arc<AtomicUsize>,
+        invoice: Arc<AtomicUsize>,
+    }
+
+    /// LNURL-pay server: the first resolution fails
+    async fn spawn_lnurl_server(calls: Calls, invoice_status: StatusCode) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let callback = format!("http://{addr}/callback");
+
+        let app = Router::new()
+            .route(
+                "/lnurlp",
+                get(move |State(calls): State<Calls>| async move {
+                    if calls.resolve.fetch_add(1, Ordering::SeqCst) == 0 {
+                        return Err(StatusCode::SERVICE_UNAVAILABLE);
+                    }
+                    Ok(Json(json!({
+                        "tag": "payRequest",
+                        "callback": callback,
+                        "minSendable": 1_000,
+                        "maxSendable": 1_000_000,
+                    })))
+                }),
+            )
+            .route(
+                "/callback",
+                get(
+                    move |State(calls): State<Calls>,
+                          Query(params): Query<HashMap<String, String>>| async move {
+                        calls.invoice.fetch_add(1, Ordering::SeqCst);
+                        if invoice_status != StatusCode::OK {
+                            return Err(invoice_status);
+                        }
+                        let pr: String = format!("lnbc{}", params["amount"]);
+                        Ok(Json::<Value>(json!({ "pr": pr })))
+                    },
+                ),
+            )
+            .with_state(calls);
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        format!("http://{addr}/lnurlp")
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::new().backoff(Duration::from_millis(10))
+    }
+
+    #[tokio::test]
+    async fn test_get_invoice_retry() {
+        let calls = Calls::default();
+        let endpoint = spawn_lnurl_server(calls.clone(), StatusCode::OK).await;
+
+        let invoice = get_invoice(&policy(), &endpoint, 2_000, None, None)
+            .await
+            .unwrap();
+        assert_eq!(invoice, "lnbc2000");
+        assert_eq!(calls.resolve.load(Ordering::SeqCst), 2);
+        assert_eq!(calls.invoice.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_invoice_not_retried() {
+        let calls = Calls::default();
+        let endpoint = spawn_lnurl_server(calls.clone(), StatusCode::SERVICE_UNAVAILABLE).await;
+
+        match get_invoice(&policy(), &endpoint, 2_000, None, None).await {
+            Err(Error::LnUrlHttp(e)) => assert_eq!(e.attempts.len(), 1),
+            res => panic!("unexpected result: {res:?}"),
+        }
+        assert_eq!(calls.invoice.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_invoice_amount_out_of_range() {
+        let calls = Calls::default();
+        let endpoint = spawn_lnurl_server(calls.clone(), StatusCode::OK).await;
+
+        let res = get_invoice(&policy(), &endpoint, 10, None, None).await;
+        assert!(matches!(res, Err(Error::ImpossibleToZap(..))));
+        assert_eq!(calls.invoice.load(Ordering::SeqCst), 0);
+    }
+}
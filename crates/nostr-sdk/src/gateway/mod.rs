@@ -0,0 +1,153 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! HTTP gateway client
+//!
+//! Publish and fetch events through a RESTful Nostr gateway,
+//! for environments where persistent WebSockets are not available.
+
+use async_utility::time;
+use nostr::{Event, Filter, JsonUtil, Url};
+use reqwest::{Client, RequestBuilder, Response};
+use thiserror::Error;
+
+pub mod options;
+
+pub use self::options::HttpGatewayOptions;
+
+/// [`HttpGatewayClient`] error
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Reqwest error
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+    /// Url error
+    #[error(transparent)]
+    Url(#[from] nostr::types::url::ParseError),
+    /// Gateway replied with an error status
+    #[error("gateway error: status={status}, message={message}")]
+    Gateway {
+        /// HTTP status code
+        status: u16,
+        /// Response body
+        message: String,
+    },
+    /// Timeout
+    #[error("timeout")]
+    Timeout,
+}
+
+/// HTTP gateway client
+///
+/// Expose a subset of the [`Client`](crate::Client) API over plain HTTP requests:
+/// * [`HttpGatewayClient::publish`]: `POST <base_url>/event` with the event JSON as body;
+/// * [`HttpGatewayClient::fetch`]: `POST <base_url>/req` with a JSON array of filters as body,
+///   expecting a JSON array of events in the response.
+///
+/// Events returned by the gateway are verified and the invalid ones are discarded.
+#[derive(Debug, Clone)]
+pub struct HttpGatewayClient {
+    client: Client,
+    opts: HttpGatewayOptions,
+}
+
+impl HttpGatewayClient {
+    /// Compose new [`HttpGatewayClient`]
+    pub fn new(opts: HttpGatewayOptions) -> Self {
+        Self {
+            client: Client::new(),
+            opts,
+        }
+    }
+
+    /// Get gateway base url
+    pub fn base_url(&self) -> &Url {
+        &self.opts.base_url
+    }
+
+    /// Publish event
+    pub async fn publish(&self, event: Event) -> Result<(), Error> {
+        let url: Url = self.endpoint("event")?;
+        let req = self
+            .client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .body(event.as_json());
+        self.send(req).await?;
+        Ok(())
+    }
+
+    /// Fetch events matching [`Filter`]
+    pub async fn fetch(&self, filter: Filter) -> Result<Vec<Event>, Error> {
+        let url: Url = self.endpoint("req")?;
+        let body: String = format!("[{}]", filter.as_json());
+        let req = self
+            .client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .body(body);
+        let res: Response = self.send(req).await?;
+        let events: Vec<Event> = time::timeout(Some(self.opts.timeout), res.json())
+            .await
+            .ok_or(Error::Timeout)??;
+        Ok(events
+            .into_iter()
+            .filter(|event| match event.verify() {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::warn!("Discarded invalid event {} from gateway: {e}", event.id());
+                    false
+                }
+            })
+            .collect())
+    }
+
+    fn endpoint(&self, path: &str) -> Result<Url, Error> {
+        let base: &str = self.opts.base_url.as_str().trim_end_matches('/');
+        Ok(Url::parse(&format!("{base}/{path}"))?)
+    }
+
+    async fn send(&self, mut req: RequestBuilder) -> Result<Response, Error> {
+        if let Some(token) = &self.opts.auth_token {
+            req = req.bearer_auth(token);
+        }
+
+        let res: Response = time::timeout(Some(self.opts.timeout), req.send())
+            .await
+            .ok_or(Error::Timeout)??;
+
+        let status = res.status();
+        if status.is_success() {
+            Ok(res)
+        } else {
+            let message: String = res.text().await.unwrap_or_default();
+            Err(Error::Gateway {
+                status: status.as_u16(),
+                message,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint() {
+        let url = Url::parse("https://gateway.example.com/v1/").unwrap();
+        let client = HttpGatewayClient::new(HttpGatewayOptions::new(url));
+        assert_eq!(
+            client.endpoint("event").unwrap().as_str(),
+            "https://gateway.example.com/v1/event"
+        );
+
+        let url = Url::parse("https://gateway.example.com/v1").unwrap();
+        let client = HttpGatewayClient::new(HttpGatewayOptions::new(url));
+        assert_eq!(
+            client.endpoint("req").unwrap().as_str(),
+            "https://gateway.example.com/v1/req"
+        );
+    }
+}
@@ -0,0 +1,43 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! HTTP gateway options
+
+use std::time::Duration;
+
+use nostr::Url;
+
+/// HTTP gateway options
+#[derive(Debug, Clone)]
+pub struct HttpGatewayOptions {
+    pub(super) base_url: Url,
+    pub(super) auth_token: Option<String>,
+    pub(super) timeout: Duration,
+}
+
+impl HttpGatewayOptions {
+    /// New default HTTP gateway options
+    pub fn new(base_url: Url) -> Self {
+        Self {
+            base_url,
+            auth_token: None,
+            timeout: Duration::from_secs(20),
+        }
+    }
+
+    /// Set bearer token sent in the `Authorization` header (default: None)
+    pub fn auth_token<S>(mut self, token: Option<S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.auth_token = token.map(|t| t.into());
+        self
+    }
+
+    /// Set requests timeout (default: 20 secs)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
@@ -24,10 +24,16 @@ pub use nostr_database::{self as database, NostrDatabase, NostrDatabaseExt, Prof
 #[cfg(all(target_arch = "wasm32", feature = "indexeddb"))]
 pub use nostr_indexeddb::{IndexedDBError, WebDatabase};
 pub use nostr_relay_pool::{
-    self as pool, AtomicRelayServiceFlags, FilterOptions, NegentropyDirection, NegentropyOptions,
-    Relay, RelayConnectionStats, RelayOptions, RelayPool, RelayPoolNotification, RelayPoolOptions,
-    RelaySendOptions, RelayServiceFlags, RelayStatus, SubscribeAutoCloseOptions, SubscribeOptions,
+    self as pool, AckStatus, AtomicRelayServiceFlags, AuthReason, FetchOutput, FetchPolicy,
+    FetchReport, FilterOptions, MemoryLimits, MemoryReport, MemoryUsage, NegentropyDirection,
+    NegentropyOptions, Relay, RelayConnectionStats, RelayHealth, RelayHealthDetails,
+    RelayHealthThresholds, RelayNotice, RelayOptions, RelayPool, RelayPoolNotification,
+    RelayPoolOptions, RelaySendOptions, RelayServiceFlags, RelayStatus, ReorderOptions,
+    SubscribeAutoCloseOptions, SubscribeOptions, SubscriptionActivity, SubscriptionLimitPolicy,
+    SubscriptionStats, TrafficReport, UnsolicitedEventPolicy,
 };
+#[cfg(not(target_arch = "wasm32"))]
+pub use nostr_relay_pool::{ProbeFailure, ProbeOptions, ProbeReport, SocksAuth, TlsCertificate};
 #[cfg(feature = "rocksdb")]
 pub use nostr_rocksdb::RocksDatabase;
 pub use nostr_signer::{self as signer, NostrSigner, NostrSignerType};
@@ -47,10 +53,16 @@ use tokio::runtime::Runtime;
 #[cfg(feature = "blocking")]
 pub use tokio::task::spawn_blocking;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod client;
+#[cfg(feature = "http-gateway")]
+pub mod gateway;
 pub mod prelude;
 
 pub use self::client::{Client, ClientBuilder, Options};
+#[cfg(feature = "http-gateway")]
+pub use self::gateway::{HttpGatewayClient, HttpGatewayOptions};
 
 #[cfg(feature = "blocking")]
 static RUNTIME: Lazy<Runtime> = Lazy::new(|| Runtime::new().expect("Can't start Tokio runtime"));
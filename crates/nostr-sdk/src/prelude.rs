@@ -18,4 +18,6 @@ pub use nostr_zapper::prelude::*;
 
 // Internal modules
 pub use crate::client::*;
+#[cfg(feature = "http-gateway")]
+pub use crate::gateway::*;
 pub use crate::*;
@@ -10,8 +10,9 @@
 
 use std::fmt;
 
-use nostr::key;
+use async_trait::async_trait;
 use nostr::prelude::*;
+use nostr::{event, key};
 use thiserror::Error;
 
 #[cfg(feature = "nip46")]
@@ -27,6 +28,9 @@ pub enum Error {
     /// Keys error
     #[error(transparent)]
     Keys(#[from] key::Error),
+    /// Event error
+    #[error(transparent)]
+    Event(#[from] event::Error),
     /// Unsigned event error
     #[error(transparent)]
     Unsigned(#[from] unsigned::Error),
@@ -46,6 +50,17 @@ pub enum Error {
     #[cfg(feature = "nip46")]
     #[error(transparent)]
     NIP46(#[from] nip46::Error),
+    /// The signer returned an event that not match the requested one
+    #[error("signed event not match the requested one: expected={expected}, received={received}")]
+    EventMismatch {
+        /// Expected event ID
+        expected: EventId,
+        /// Received event ID
+        received: EventId,
+    },
+    /// The signer returned an event with an invalid signature
+    #[error("signed event has an invalid signature: {0}")]
+    InvalidSignature(EventId),
 }
 
 /// Nostr Signer Type
@@ -130,15 +145,33 @@ impl NostrSigner {
         self.sign_event(unsigned).await
     }
 
+    /// Sign an [EventBuilder] with POW
+    pub async fn sign_event_builder_pow(
+        &self,
+        builder: EventBuilder,
+        difficulty: u8,
+    ) -> Result<Event, Error> {
+        let public_key: PublicKey = self.public_key().await?;
+        let unsigned: UnsignedEvent = builder.to_unsigned_pow_event(public_key, difficulty);
+        self.sign_event(unsigned).await
+    }
+
     /// Sign an [UnsignedEvent]
+    ///
+    /// The event returned by the signer is checked against the requested one:
+    /// an [Error::EventMismatch] is returned if the [EventId] not match the requested one or the event content,
+    /// an [Error::InvalidSignature] if the signature is invalid.
     pub async fn sign_event(&self, unsigned: UnsignedEvent) -> Result<Event, Error> {
-        match self {
-            Self::Keys(keys) => Ok(unsigned.sign(keys)?),
+        let expected: EventId = unsigned.id;
+        let event: Event = match self {
+            Self::Keys(keys) => unsigned.sign(keys)?,
             #[cfg(all(feature = "nip07", target_arch = "wasm32"))]
-            Self::NIP07(nip07) => Ok(nip07.sign_event(unsigned).await?),
+            Self::NIP07(nip07) => nip07.sign_event(unsigned).await?,
             #[cfg(feature = "nip46")]
-            Self::NIP46(nip46) => Ok(nip46.sign_event(unsigned).await?),
-        }
+            Self::NIP46(nip46) => nip46.sign_event(unsigned).await?,
+        };
+
+        check_signed_event(expected, event)
     }
 
     /// NIP04 encrypt
@@ -219,6 +252,57 @@ impl NostrSigner {
     }
 }
 
+/// Check that the event returned by a signer match the requested one
+fn check_signed_event(expected: EventId, event: Event) -> Result<Event, Error> {
+    // Check event ID, also against the signed content
+    let received: EventId = EventId::new(
+        &event.author(),
+        event.created_at(),
+        &event.kind(),
+        event.tags(),
+        event.content(),
+    );
+    if event.id() != expected || received != expected {
+        return Err(Error::EventMismatch { expected, received });
+    }
+
+    // Check signature
+    if event.verify_signature().is_err() {
+        return Err(Error::InvalidSignature(expected));
+    }
+
+    Ok(event)
+}
+
+/// [EventBuilder] extension to sign with [NostrSigner]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait EventBuilderExt {
+    /// Build and sign the event with [NostrSigner]
+    ///
+    /// An [Error::EventMismatch] or [Error::InvalidSignature] is returned if the signer return an event that not match the requested one
+    /// or with an invalid signature.
+    async fn sign(self, signer: &NostrSigner) -> Result<Event, Error>;
+
+    /// Build and sign the event with POW with [NostrSigner]
+    ///
+    /// An [Error::EventMismatch] or [Error::InvalidSignature] is returned if the signer return an event that not match the requested one
+    /// or with an invalid signature.
+    async fn sign_pow(self, signer: &NostrSigner, difficulty: u8) -> Result<Event, Error>;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl EventBuilderExt for EventBuilder {
+    async fn sign(self, signer: &NostrSigner) -> Result<Event, Error> {
+        signer.sign_event_builder(self).await
+    }
+
+    async fn sign_pow(self, signer: &NostrSigner, difficulty: u8) -> Result<Event, Error> {
+        signer.sign_event_builder_pow(self, difficulty).await
+    }
+}
+
 impl From<Keys> for NostrSigner {
     fn from(keys: Keys) -> Self {
         Self::Keys(keys)
@@ -244,3 +328,86 @@ impl From<Nip46Signer> for NostrSigner {
         Self::nip46(nip46)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_event_builder_sign() {
+        let keys = Keys::generate();
+        let signer = NostrSigner::from(&keys);
+
+        let event = EventBuilder::text_note("test", [])
+            .sign(&signer)
+            .await
+            .unwrap();
+        assert_eq!(event.author(), keys.public_key());
+        event.verify().unwrap();
+
+        let event = EventBuilder::text_note("test", [])
+            .sign_pow(&signer, 8)
+            .await
+            .unwrap();
+        assert!(event.id().check_pow(8));
+    }
+
+    #[test]
+    fn test_event_mismatch() {
+        let keys = Keys::generate();
+        let unsigned =
+            EventBuilder::text_note("requested", []).to_unsigned_event(keys.public_key());
+        let expected: EventId = unsigned.id;
+
+        // The signer returned a different event
+        let other = EventBuilder::text_note("other", [])
+            .to_event(&keys)
+            .unwrap();
+        let received: EventId = other.id();
+        let sig = other.signature();
+        match check_signed_event(expected, other) {
+            Err(Error::EventMismatch {
+                expected: e,
+                received: r,
+            }) => {
+                assert_eq!(e, expected);
+                assert_eq!(r, received);
+            }
+            res => panic!("Unexpected result: {res:?}"),
+        }
+
+        // Same ID but different content
+        let tampered = Event::new(
+            expected,
+            keys.public_key(),
+            unsigned.created_at,
+            unsigned.kind,
+            [],
+            "tampered",
+            sig,
+        );
+        assert!(matches!(
+            check_signed_event(expected, tampered),
+            Err(Error::EventMismatch { .. })
+        ));
+
+        // Invalid signature
+        let invalid = Event::new(
+            expected,
+            keys.public_key(),
+            unsigned.created_at,
+            unsigned.kind,
+            unsigned.tags.clone(),
+            unsigned.content.clone(),
+            sig,
+        );
+        match check_signed_event(expected, invalid) {
+            Err(Error::InvalidSignature(id)) => assert_eq!(id, expected),
+            res => panic!("Unexpected result: {res:?}"),
+        }
+
+        // Same event
+        let event = unsigned.sign(&keys).unwrap();
+        assert!(check_signed_event(expected, event).is_ok());
+    }
+}
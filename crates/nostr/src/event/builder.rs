@@ -15,27 +15,33 @@ use bitcoin::secp256k1::rand::{CryptoRng, Rng};
 use bitcoin::secp256k1::{self, Secp256k1, Signing};
 use serde_json::{json, Value};
 
-use super::kind::{Kind, NIP90_JOB_REQUEST_RANGE, NIP90_JOB_RESULT_RANGE};
+use super::kind::{Kind, KindCategory, NIP90_JOB_REQUEST_RANGE, NIP90_JOB_RESULT_RANGE};
+use super::lint::{self, LintWarning};
 use super::tag::ImageDimensions;
 use super::{Event, EventId, Marker, Tag, TagKind, UnsignedEvent};
 use crate::key::{self, Keys, PublicKey};
 use crate::nips::nip01::Coordinate;
 #[cfg(feature = "nip04")]
 use crate::nips::nip04;
+#[cfg(all(feature = "std", feature = "nip05"))]
+use crate::nips::nip05;
 use crate::nips::nip15::{ProductData, StallData};
 #[cfg(all(feature = "std", feature = "nip44"))]
 use crate::nips::nip44::{self, Version};
 #[cfg(all(feature = "std", feature = "nip46"))]
 use crate::nips::nip46::Message as NostrConnectMessage;
+use crate::nips::nip51;
 use crate::nips::nip51::{ArticlesCuration, Bookmarks, Emojis, Interests, MuteList};
 use crate::nips::nip53::LiveEvent;
 #[cfg(feature = "nip57")]
 use crate::nips::nip57::ZapRequestData;
-use crate::nips::nip58::Error as Nip58Error;
+use crate::nips::nip58;
+use crate::nips::nip58::{Error as Nip58Error, ProfileBadge};
+use crate::nips::nip89::ClientTag;
 use crate::nips::nip90::DataVendingMachineStatus;
 use crate::nips::nip94::FileMetadata;
 use crate::nips::nip98::HttpData;
-use crate::nips::{nip13, nip58};
+use crate::nips::nip99::{ClassifiedListing, ListingStatus};
 #[cfg(feature = "std")]
 use crate::types::time::Instant;
 use crate::types::time::TimeSupplier;
@@ -83,6 +89,8 @@ pub enum Error {
     /// NIP44 error
     #[cfg(all(feature = "std", feature = "nip44"))]
     NIP44(nip44::Error),
+    /// NIP51 error
+    NIP51(nip51::Error),
     /// NIP58 error
     NIP58(nip58::Error),
     /// Wrong kind
@@ -92,6 +100,8 @@ pub enum Error {
         /// The expected kind (single or range)
         expected: WrongKindError,
     },
+    /// Invoice (`bolt11`) set without the amount
+    InvoiceWithoutAmount,
 }
 
 #[cfg(feature = "std")]
@@ -110,10 +120,12 @@ impl fmt::Display for Error {
             Self::NIP04(e) => write!(f, "NIP04: {e}"),
             #[cfg(all(feature = "std", feature = "nip44"))]
             Self::NIP44(e) => write!(f, "NIP44: {e}"),
+            Self::NIP51(e) => write!(f, "NIP51: {e}"),
             Self::NIP58(e) => write!(f, "NIP58: {e}"),
             Self::WrongKind { received, expected } => {
                 write!(f, "Wrong kind: received={received}, expected={expected}")
             }
+            Self::InvoiceWithoutAmount => write!(f, "Invoice set without amount"),
         }
     }
 }
@@ -163,6 +175,12 @@ impl From<nip44::Error> for Error {
     }
 }
 
+impl From<nip51::Error> for Error {
+    fn from(e: nip51::Error) -> Self {
+        Self::NIP51(e)
+    }
+}
+
 impl From<nip58::Error> for Error {
     fn from(e: nip58::Error) -> Self {
         Self::NIP58(e)
@@ -193,12 +211,101 @@ impl EventBuilder {
         }
     }
 
+    /// Get human-readable [`KindCategory`] of a [`Kind`]
+    ///
+    /// Shorthand for [`Kind::category`].
+    #[inline]
+    pub fn kind_classification(kind: Kind) -> KindCategory {
+        kind.category()
+    }
+
+    /// Get [`Kind`]
+    #[inline]
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    /// Get tags
+    #[inline]
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    /// Get content
+    #[inline]
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Add tags
+    pub fn add_tags<I>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = Tag>,
+    {
+        self.tags.extend(tags);
+        self
+    }
+
+    /// Replace content
+    pub fn replace_content<S>(mut self, content: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.content = content.into();
+        self
+    }
+
+    /// Lint tags, without failing the build
+    ///
+    /// Check [`LintWarning`] for the findings.
+    pub fn lint(&self) -> Vec<LintWarning> {
+        lint::lint_tags(self.tags.iter())
+    }
+
+    /// Add client tag
+    ///
+    /// If a client tag already exists, it's kept and the new one is ignored.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/89.md>
+    pub fn client(mut self, client: ClientTag) -> Self {
+        if !self.tags.iter().any(ClientTag::is_client_tag) {
+            self.tags.push(client.into());
+        }
+        self
+    }
+
     /// Set a custom `created_at` UNIX timestamp
     pub fn custom_created_at(mut self, created_at: Timestamp) -> Self {
         self.custom_created_at = Some(created_at);
         self
     }
 
+    /// Set the private entries of a NIP51 list
+    ///
+    /// The entries are encrypted to self with NIP44 and replace the content.
+    ///
+    /// ```rust,no_run
+    /// use nostr::nips::nip51::MuteList;
+    /// use nostr::prelude::*;
+    ///
+    /// # let keys = Keys::generate();
+    /// # let public = MuteList::default();
+    /// # let private = MuteList::default();
+    /// let builder = EventBuilder::mute_list(public).private_entries(&keys, private).unwrap();
+    /// ```
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    #[cfg(all(feature = "std", feature = "nip44"))]
+    pub fn private_entries<T>(mut self, keys: &Keys, entries: T) -> Result<Self, Error>
+    where
+        T: Into<Vec<Tag>>,
+    {
+        let entries: Vec<Tag> = entries.into();
+        self.content =
+            nip51::encrypt_private_entries(keys.secret_key()?, &keys.public_key(), &entries)?;
+        Ok(self)
+    }
+
     /// Build [`Event`]
     pub fn to_event_with_ctx<C, R, T>(
         self,
@@ -287,7 +394,7 @@ impl EventBuilder {
                 .unwrap_or_else(|| Timestamp::now_with_supplier(supplier));
             let id = EventId::new(&pubkey, created_at, &self.kind, &tags, &self.content);
 
-            if nip13::get_leading_zero_bits(id.inner()) >= difficulty {
+            if id.check_pow(difficulty) {
                 #[cfg(feature = "std")]
                 tracing::debug!(
                     "{} iterations in {} ms. Avg rate {} hashes/second",
@@ -397,6 +504,27 @@ impl EventBuilder {
         Self::new(Kind::TextNote, content, tags)
     }
 
+    /// Status update (text note) with fluent optional fields
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/01.md>
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use nostr::EventBuilder;
+    ///
+    /// let builder = EventBuilder::status_update("GM!")
+    ///     .hashtag("gm")
+    ///     .subject("Morning")
+    ///     .build();
+    /// ```
+    #[inline]
+    pub fn status_update<S>(content: S) -> TextNoteBuilder
+    where
+        S: Into<String>,
+    {
+        TextNoteBuilder::new(content)
+    }
+
     /// Text note reply
     ///
     /// If no `root` is passed, the `rely_to` will be used for root `e` tag.
@@ -640,6 +768,9 @@ impl EventBuilder {
 
     /// Channel metadata
     ///
+    /// The `channel_id` is the [`EventId`] of the channel creation event (kind 40).
+    /// To share a channel with relay hints, use [`Nip19Event`](crate::nips::nip19::Nip19Event) (`nevent`).
+    ///
     /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
     pub fn channel_metadata(
         channel_id: EventId,
@@ -659,6 +790,9 @@ impl EventBuilder {
 
     /// Channel message
     ///
+    /// The `channel_id` is the [`EventId`] of the channel creation event (kind 40).
+    /// To share a channel with relay hints, use [`Nip19Event`](crate::nips::nip19::Nip19Event) (`nevent`).
+    ///
     /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
     pub fn channel_msg<S>(channel_id: EventId, relay_url: Url, content: S) -> Self
     where
@@ -772,6 +906,53 @@ impl EventBuilder {
         Self::new(Kind::LiveEventMessage, content, tags)
     }
 
+    /// Classified Listing
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/99.md>
+    pub fn classified_listing(listing: ClassifiedListing) -> Self {
+        let content: String = listing.content.clone();
+        let tags: Vec<Tag> = listing.into();
+        Self::new(Kind::ClassifiedListing, content, tags)
+    }
+
+    /// Draft Classified Listing
+    ///
+    /// Same as [`EventBuilder::classified_listing`] but with [`ListingStatus::Draft`] status:
+    /// the listing is not yet ready for public display.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/99.md>
+    pub fn classified_listing_draft(mut listing: ClassifiedListing) -> Self {
+        listing.status = ListingStatus::Draft;
+        Self::classified_listing(listing)
+    }
+
+    /// Identity verification request
+    ///
+    /// Kind `30078` event, with `d` tag [`IDENTITY_VERIFICATION_IDENTIFIER`](crate::nips::nip05::IDENTITY_VERIFICATION_IDENTIFIER),
+    /// published by NIP05 providers to link the public key to an identity on an external platform.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/05.md>
+    #[cfg(all(feature = "std", feature = "nip05"))]
+    pub fn identity_verification_request<S, P>(identifier: S, platform: P, proof_url: Url) -> Self
+    where
+        S: Into<String>,
+        P: Into<String>,
+    {
+        let tag = |name: &str, value: String| {
+            Tag::Generic(TagKind::Custom(String::from(name)), vec![value])
+        };
+        Self::new(
+            Kind::ApplicationSpecificData,
+            "",
+            [
+                Tag::Identifier(nip05::IDENTITY_VERIFICATION_IDENTIFIER.to_string()),
+                tag("identifier", identifier.into()),
+                tag("platform", platform.into()),
+                tag("proof", proof_url.to_string()),
+            ],
+        )
+    }
+
     /// Create report event
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/56.md>
@@ -985,8 +1166,28 @@ impl EventBuilder {
 
     /// Create a profile badges event
     ///
+    /// Badges are displayed in order.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/58.md>
+    pub fn profile_badges(badges: Vec<ProfileBadge>) -> Self {
+        let mut tags: Vec<Tag> = Vec::with_capacity(1 + badges.len() * 2);
+        tags.push(Tag::Identifier(
+            nip58::PROFILE_BADGES_IDENTIFIER.to_string(),
+        ));
+        for badge in badges.into_iter() {
+            tags.push(Tag::A {
+                coordinate: badge.definition_coordinate,
+                relay_url: None,
+            });
+            tags.push(Tag::event(badge.award_event_id));
+        }
+        Self::new(Kind::ProfileBadges, "", tags)
+    }
+
+    /// Create a profile badges event from badge definitions and awards
+    ///
     /// <https://github.com/nostr-protocol/nips/blob/master/58.md>
-    pub fn profile_badges(
+    pub fn profile_badges_from_events(
         badge_definitions: Vec<Event>,
         badge_awards: Vec<Event>,
         pubkey_awarded: &PublicKey,
@@ -1016,7 +1217,7 @@ impl EventBuilder {
         }
 
         // Add identifier `d` tag
-        let id_tag: Tag = Tag::Identifier("profile_badges".to_string());
+        let id_tag: Tag = Tag::Identifier(nip58::PROFILE_BADGES_IDENTIFIER.to_string());
         let mut tags: Vec<Tag> = vec![id_tag];
 
         let badge_definitions_identifiers = badge_definitions.into_iter().filter_map(|event| {
@@ -1026,11 +1227,11 @@ impl EventBuilder {
 
         let badge_awards_identifiers = badge_awards.into_iter().filter_map(|event| {
             let (_, relay_url) = nip58::extract_awarded_public_key(event.tags(), pubkey_awarded)?;
-            let (id, a_tag) = event.iter_tags().find_map(|t| match t {
-                Tag::A { coordinate, .. } => Some((coordinate.identifier.clone(), t.clone())),
+            let (coordinate, a_tag) = event.iter_tags().find_map(|t| match t {
+                Tag::A { coordinate, .. } => Some((coordinate.clone(), t.clone())),
                 _ => None,
             })?;
-            Some((event, id, a_tag, relay_url))
+            Some((event, coordinate, a_tag, relay_url))
         });
 
         // This collection has been filtered for the needed tags
@@ -1038,12 +1239,15 @@ impl EventBuilder {
 
         for (badge_definition, badge_award) in users_badges {
             match (badge_definition, badge_award) {
-                ((_, identifier), (_, badge_id, ..)) if badge_id != identifier => {
+                // The award must reference the definition: same kind, author and identifier
+                ((definition, identifier), (_, coordinate, ..))
+                    if coordinate.kind != Kind::BadgeDefinition
+                        || coordinate.public_key != definition.author()
+                        || coordinate.identifier != identifier =>
+                {
                     return Err(Error::NIP58(Nip58Error::MismatchedBadgeDefinitionOrAward));
                 }
-                ((_, identifier), (badge_award_event, badge_id, a_tag, relay_url))
-                    if badge_id == identifier =>
-                {
+                (_, (badge_award_event, _, a_tag, relay_url)) => {
                     let badge_definition_event_tag: Tag = a_tag;
                     let badge_award_event_tag: Tag = Tag::Event {
                         event_id: badge_award_event.id(),
@@ -1052,7 +1256,6 @@ impl EventBuilder {
                     };
                     tags.extend_from_slice(&[badge_definition_event_tag, badge_award_event_tag]);
                 }
-                _ => {}
             }
         }
 
@@ -1121,25 +1324,43 @@ impl EventBuilder {
 
     /// Data Vending Machine - Job Feedback
     ///
+    /// The `bolt11` invoice requires the `amount_millisats`: return [`Error::InvoiceWithoutAmount`] otherwise.
+    ///
     /// <https://github.com/nostr-protocol/nips/blob/master/90.md>
     pub fn job_feedback(
         job_request: &Event,
         status: DataVendingMachineStatus,
         extra_info: Option<String>,
-        amount_millisats: u64,
+        amount_millisats: Option<u64>,
         bolt11: Option<String>,
         payload: Option<String>,
-    ) -> Self {
-        let tags = [
+    ) -> Result<Self, Error> {
+        if !job_request.is_job_request() {
+            return Err(Error::WrongKind {
+                received: job_request.kind(),
+                expected: WrongKindError::Range(NIP90_JOB_REQUEST_RANGE),
+            });
+        }
+
+        if amount_millisats.is_none() && bolt11.is_some() {
+            return Err(Error::InvoiceWithoutAmount);
+        }
+
+        let mut tags: Vec<Tag> = vec![
             Tag::DataVendingMachineStatus { status, extra_info },
             Tag::event(job_request.id()),
             Tag::public_key(job_request.author()),
-            Tag::Amount {
-                millisats: amount_millisats,
-                bolt11,
-            },
         ];
-        Self::new(Kind::JobFeedback, payload.unwrap_or_default(), tags)
+
+        if let Some(millisats) = amount_millisats {
+            tags.push(Tag::Amount { millisats, bolt11 });
+        }
+
+        Ok(Self::new(
+            Kind::JobFeedback,
+            payload.unwrap_or_default(),
+            tags,
+        ))
     }
 
     /// File metadata
@@ -1335,6 +1556,20 @@ impl EventBuilder {
         Self::new(Kind::Interests, "", tags)
     }
 
+    /// Interest list
+    ///
+    /// Like [`EventBuilder::interests`] but with hashtags only.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub fn interest_list<I, S>(interests: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let tags = interests.into_iter().map(|i| Tag::Hashtag(i.into()));
+        Self::new(Kind::Interests, "", tags)
+    }
+
     /// Emojis
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
@@ -1354,6 +1589,21 @@ impl EventBuilder {
         Self::new(Kind::FollowSets, "", tags)
     }
 
+    /// Follow set
+    ///
+    /// Like [`EventBuilder::follow_sets`] but with the set identifier (`d` tag).
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub fn follow_set<S, I>(identifier: S, public_keys: I) -> Self
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = PublicKey>,
+    {
+        let mut tags: Vec<Tag> = vec![Tag::Identifier(identifier.into())];
+        tags.extend(public_keys.into_iter().map(Tag::public_key));
+        Self::new(Kind::FollowSets, "", tags)
+    }
+
     /// Relay sets
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
@@ -1373,6 +1623,20 @@ impl EventBuilder {
         Self::new(Kind::BookmarkSets, "", tags)
     }
 
+    /// Interest set
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub fn interest_set<S, I, T>(identifier: S, interests: I) -> Self
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let mut tags: Vec<Tag> = vec![Tag::Identifier(identifier.into())];
+        tags.extend(interests.into_iter().map(|i| Tag::Hashtag(i.into())));
+        Self::new(Kind::InterestSets, "", tags)
+    }
+
     /// Article Curation sets
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
@@ -1406,6 +1670,139 @@ impl EventBuilder {
     }
 }
 
+/// Text note builder
+///
+/// Check [`EventBuilder::status_update`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextNoteBuilder {
+    content: String,
+    tags: Vec<Tag>,
+}
+
+impl TextNoteBuilder {
+    /// New text note builder
+    pub fn new<S>(content: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            content: content.into(),
+            tags: Vec::new(),
+        }
+    }
+
+    /// Reply to event (NIP10 `e` and `p` tags)
+    ///
+    /// If no `root` is passed, the note is a direct reply: `reply_to` is tagged with the `root` marker.
+    /// Otherwise, `root` is tagged with the `root` marker and `reply_to` with the `reply` one.
+    /// The authors of the events are tagged with `p` tags.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/10.md>
+    pub fn reply_to(
+        mut self,
+        reply_to: &Event,
+        root: Option<&Event>,
+        relay_hint: Option<Url>,
+    ) -> Self {
+        let relay_url: Option<UncheckedUrl> = relay_hint.map(|u| UncheckedUrl::from(u.to_string()));
+
+        match root {
+            Some(root) => {
+                self.tags.push(Tag::Event {
+                    event_id: root.id(),
+                    relay_url: relay_url.clone(),
+                    marker: Some(Marker::Root),
+                });
+                self.tags.push(Tag::Event {
+                    event_id: reply_to.id(),
+                    relay_url,
+                    marker: Some(Marker::Reply),
+                });
+                self.tags.push(Tag::public_key(root.author()));
+                if reply_to.author() != root.author() {
+                    self.tags.push(Tag::public_key(reply_to.author()));
+                }
+            }
+            None => {
+                self.tags.push(Tag::Event {
+                    event_id: reply_to.id(),
+                    relay_url,
+                    marker: Some(Marker::Root),
+                });
+                self.tags.push(Tag::public_key(reply_to.author()));
+            }
+        }
+
+        self
+    }
+
+    /// Mention public key (`p` tag)
+    pub fn mention(mut self, public_key: PublicKey, relay_hint: Option<Url>) -> Self {
+        self.tags.push(Tag::PublicKey {
+            public_key,
+            relay_url: relay_hint.map(|u| UncheckedUrl::from(u.to_string())),
+            alias: None,
+            uppercase: false,
+        });
+        self
+    }
+
+    /// Add hashtag (`t` tag)
+    ///
+    /// The leading `#`, if any, is removed and the hashtag is lowercased.
+    pub fn hashtag(mut self, hashtag: &str) -> Self {
+        let hashtag: &str = hashtag.trim_start_matches('#');
+        self.tags.push(Tag::Hashtag(hashtag.to_lowercase()));
+        self
+    }
+
+    /// Mark content as sensitive (`content-warning` tag)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/36.md>
+    pub fn content_warning(mut self, reason: Option<String>) -> Self {
+        self.tags.push(Tag::ContentWarning { reason });
+        self
+    }
+
+    /// Set subject (`subject` tag)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/14.md>
+    pub fn subject<S>(mut self, subject: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.tags.retain(|t| !matches!(t, Tag::Subject(..)));
+        self.tags.push(Tag::Subject(subject.into()));
+        self
+    }
+
+    /// Add URL reference (`r` tag)
+    pub fn url(mut self, url: Url) -> Self {
+        self.tags.push(Tag::Reference(url.to_string()));
+        self
+    }
+
+    /// Add geohash (`g` tag)
+    pub fn geohash<S>(mut self, geohash: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.tags.push(Tag::Geohash(geohash.into()));
+        self
+    }
+
+    /// Build [`EventBuilder`]
+    pub fn build(self) -> EventBuilder {
+        EventBuilder::text_note(self.content, self.tags)
+    }
+}
+
+impl From<TextNoteBuilder> for EventBuilder {
+    fn from(builder: TextNoteBuilder) -> Self {
+        builder.build()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[cfg(feature = "std")]
@@ -1413,6 +1810,8 @@ mod tests {
 
     use super::*;
     #[cfg(feature = "std")]
+    use crate::nips::nip58::ProfileBadges;
+    #[cfg(feature = "std")]
     use crate::SecretKey;
 
     #[test]
@@ -1433,6 +1832,20 @@ mod tests {
         assert_eq!(event, deserialized);
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_client_tag() {
+        let keys = Keys::generate();
+
+        let event = EventBuilder::text_note("hello", [])
+            .client(ClientTag::new("nostr-sdk"))
+            .client(ClientTag::new("other"))
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.client(), Some(ClientTag::new("nostr-sdk")));
+        assert_eq!(event.tags().len(), 1);
+    }
+
     #[test]
     #[cfg(all(feature = "std", feature = "nip04"))]
     fn test_encrypted_direct_msg() {
@@ -1519,6 +1932,91 @@ mod tests {
         assert_eq!(Kind::BadgeDefinition, event_builder.kind);
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_status_update() {
+        let public_key =
+            PublicKey::from_hex("b2d670de53b27691c0c3400225b65c35a26d06093bcc41f48ffc71e0907f9d4a")
+                .unwrap();
+        let keys = Keys::generate();
+        let event = EventBuilder::text_note("GM", []).to_event(&keys).unwrap();
+        let relay = Url::parse("wss://relay.damus.io").unwrap();
+
+        let builder = EventBuilder::status_update("GM")
+            .reply_to(&event, None, Some(relay.clone()))
+            .mention(public_key, None)
+            .hashtag("#Nostr")
+            .content_warning(None)
+            .subject("old")
+            .subject("Morning")
+            .url(relay.clone())
+            .geohash("u4pruydqqvj")
+            .build();
+
+        assert_eq!(builder.kind, Kind::TextNote);
+        assert_eq!(builder.content, "GM");
+        assert_eq!(
+            builder.tags,
+            vec![
+                Tag::Event {
+                    event_id: event.id(),
+                    relay_url: Some(UncheckedUrl::from(relay.to_string())),
+                    marker: Some(Marker::Root),
+                },
+                Tag::public_key(keys.public_key()),
+                Tag::public_key(public_key),
+                Tag::Hashtag(String::from("nostr")),
+                Tag::ContentWarning { reason: None },
+                Tag::Subject(String::from("Morning")),
+                Tag::Reference(relay.to_string()),
+                Tag::Geohash(String::from("u4pruydqqvj")),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_status_update_nested_reply() {
+        let root_keys = Keys::generate();
+        let reply_keys = Keys::generate();
+        let root = EventBuilder::text_note("root", [])
+            .to_event(&root_keys)
+            .unwrap();
+        let reply = EventBuilder::text_note("reply", [])
+            .to_event(&reply_keys)
+            .unwrap();
+
+        let builder = EventBuilder::status_update("nested")
+            .reply_to(&reply, Some(&root), None)
+            .build();
+        assert_eq!(
+            builder.tags,
+            vec![
+                Tag::Event {
+                    event_id: root.id(),
+                    relay_url: None,
+                    marker: Some(Marker::Root),
+                },
+                Tag::Event {
+                    event_id: reply.id(),
+                    relay_url: None,
+                    marker: Some(Marker::Reply),
+                },
+                Tag::public_key(root_keys.public_key()),
+                Tag::public_key(reply_keys.public_key()),
+            ]
+        );
+
+        // Same author: single `p` tag
+        let reply = EventBuilder::text_note("reply", [])
+            .to_event(&root_keys)
+            .unwrap();
+        let builder = EventBuilder::status_update("nested")
+            .reply_to(&reply, Some(&root), None)
+            .build();
+        assert_eq!(builder.tags.len(), 3);
+    }
+
     #[test]
     fn test_badge_definition_event_builder_full() {
         let badge_id = String::from("bravery");
@@ -1700,15 +2198,68 @@ mod tests {
         );
         let example_event: Event = serde_json::from_str(&example_event_json).unwrap();
 
-        let badge_definitions = vec![bravery_badge_event, honor_badge_event];
+        let badge_definitions = vec![bravery_badge_event.clone(), honor_badge_event];
         let badge_awards = vec![bravery_badge_award, honor_badge_award];
         let profile_badges =
-            EventBuilder::profile_badges(badge_definitions, badge_awards, &pub_key)
+            EventBuilder::profile_badges_from_events(badge_definitions, badge_awards, &pub_key)
                 .unwrap()
                 .to_event(&keys)
                 .unwrap();
 
         assert_eq!(profile_badges.kind(), Kind::ProfileBadges);
         assert_eq!(profile_badges.tags(), example_event.tags());
+
+        // Same identifier but different author: the award doesn't reference the definition
+        let fake_bravery_badge_event =
+            self::EventBuilder::define_badge("bravery", None, None, None, None, Vec::new())
+                .to_event(&badge_two_keys)
+                .unwrap();
+        let bravery_badge_award =
+            self::EventBuilder::award_badge(&bravery_badge_event, awarded_pubkeys)
+                .unwrap()
+                .to_event(&badge_one_keys)
+                .unwrap();
+        assert!(matches!(
+            EventBuilder::profile_badges_from_events(
+                vec![fake_bravery_badge_event],
+                vec![bravery_badge_award],
+                &pub_key
+            ),
+            Err(Error::NIP58(Nip58Error::MismatchedBadgeDefinitionOrAward))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_profile_badges_roundtrip() {
+        let keys = Keys::generate();
+        let issuer =
+            PublicKey::from_str("232a4ba3df82ccc252a35abee7d87d1af8fc3cc749e4002c3691434da692b1df")
+                .unwrap();
+        let award_id =
+            EventId::from_hex("378f145897eea948952674269945e88612420db35791784abf0616b4fed56ef7")
+                .unwrap();
+        let badge = ProfileBadge::new(
+            Coordinate::new(Kind::BadgeDefinition, issuer).identifier("bravery"),
+            award_id,
+        );
+
+        let event = EventBuilder::profile_badges(vec![badge.clone()])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.kind(), Kind::ProfileBadges);
+        assert_eq!(
+            serde_json::to_string(event.tags()).unwrap(),
+            format!(
+                r#"[["d","profile_badges"],["a","30009:{issuer}:bravery"],["e","{award_id}"]]"#
+            )
+        );
+
+        let badges = ProfileBadges::from_event(&event).unwrap();
+        assert_eq!(badges.badges(), vec![badge]);
+
+        // Wrong kind
+        let event = EventBuilder::text_note("", []).to_event(&keys).unwrap();
+        assert!(ProfileBadges::from_event(&event).is_err());
     }
 }
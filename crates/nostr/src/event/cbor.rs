@@ -0,0 +1,284 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Compact CBOR encoding
+//!
+//! The [`Event`] is encoded as a CBOR map with integer keys:
+//!
+//! | Key | Field        | CBOR type                     |
+//! |-----|--------------|-------------------------------|
+//! | `0` | `id`         | byte string (32 bytes)        |
+//! | `1` | `pubkey`     | byte string (32 bytes)        |
+//! | `2` | `created_at` | unsigned integer              |
+//! | `3` | `kind`       | unsigned integer              |
+//! | `4` | `tags`       | array of arrays of tag values |
+//! | `5` | `content`    | text string                   |
+//! | `6` | `sig`        | byte string (64 bytes)        |
+//!
+//! Tag values that are 64 chars lowercase hex strings (ex. event IDs and public keys) are encoded as
+//! 32 bytes byte strings and decoded back as lowercase hex, all the other values as text strings.
+//!
+//! Unknown keys are ignored when decoding.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use bitcoin::secp256k1::schnorr::Signature;
+use ciborium::value::{Integer, Value};
+
+use super::{Error, Event, EventId, Kind, Tag};
+use crate::util::hex;
+use crate::{PublicKey, Timestamp};
+
+const ID: u64 = 0;
+const PUBKEY: u64 = 1;
+const CREATED_AT: u64 = 2;
+const KIND: u64 = 3;
+const TAGS: u64 = 4;
+const CONTENT: u64 = 5;
+const SIG: u64 = 6;
+
+impl Event {
+    /// Serialize as compact CBOR
+    ///
+    /// Check the [`cbor`](self) module for the schema.
+    pub fn to_cbor(&self) -> Result<Vec<u8>, Error> {
+        let tags: Vec<Value> = self
+            .iter_tags()
+            .map(|tag| Value::Array(tag.as_vec().into_iter().map(encode_tag_value).collect()))
+            .collect();
+        let value = Value::Map(vec![
+            (key(ID), Value::Bytes(self.id().to_bytes().to_vec())),
+            (key(PUBKEY), Value::Bytes(self.author().to_bytes().to_vec())),
+            (key(CREATED_AT), Value::from(self.created_at().as_u64())),
+            (key(KIND), Value::from(self.kind().as_u64())),
+            (key(TAGS), Value::Array(tags)),
+            (key(CONTENT), Value::Text(self.content().to_string())),
+            (
+                key(SIG),
+                Value::Bytes(self.signature().serialize().to_vec()),
+            ),
+        ]);
+
+        let mut buf: Vec<u8> = Vec::new();
+        ciborium::into_writer(&value, &mut buf).map_err(|e| Error::Cbor(e.to_string()))?;
+        Ok(buf)
+    }
+
+    /// Deserialize from compact CBOR
+    ///
+    /// **This method NOT verify the signature!**
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, Error> {
+        let value: Value = ciborium::from_reader(bytes).map_err(|e| Error::Cbor(e.to_string()))?;
+        let entries: Vec<(Value, Value)> = match value {
+            Value::Map(entries) => entries,
+            _ => return Err(cbor_error("expected map")),
+        };
+
+        let mut id: Option<EventId> = None;
+        let mut pubkey: Option<PublicKey> = None;
+        let mut created_at: Option<Timestamp> = None;
+        let mut kind: Option<Kind> = None;
+        let mut tags: Option<Vec<Tag>> = None;
+        let mut content: Option<String> = None;
+        let mut sig: Option<Signature> = None;
+
+        for (k, v) in entries.into_iter() {
+            let k: u64 = match k {
+                Value::Integer(i) => match u64::try_from(i) {
+                    Ok(k) => k,
+                    Err(_) => continue,
+                },
+                _ => continue,
+            };
+
+            match k {
+                ID => {
+                    id = Some(EventId::from_slice(&bytes_value(v, "id")?).map_err(cbor_error)?);
+                }
+                PUBKEY => {
+                    pubkey = Some(
+                        PublicKey::from_slice(&bytes_value(v, "pubkey")?).map_err(cbor_error)?,
+                    );
+                }
+                CREATED_AT => {
+                    created_at = Some(Timestamp::from(u64_value(v, "created_at")?));
+                }
+                KIND => {
+                    kind = Some(Kind::from(u64_value(v, "kind")?));
+                }
+                TAGS => {
+                    tags = Some(decode_tags(v)?);
+                }
+                CONTENT => match v {
+                    Value::Text(text) => content = Some(text),
+                    _ => return Err(cbor_error("invalid content")),
+                },
+                SIG => {
+                    sig = Some(Signature::from_slice(&bytes_value(v, "sig")?)?);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self::new(
+            id.ok_or_else(|| cbor_error("missing id"))?,
+            pubkey.ok_or_else(|| cbor_error("missing pubkey"))?,
+            created_at.ok_or_else(|| cbor_error("missing created_at"))?,
+            kind.ok_or_else(|| cbor_error("missing kind"))?,
+            tags.ok_or_else(|| cbor_error("missing tags"))?,
+            content.ok_or_else(|| cbor_error("missing content"))?,
+            sig.ok_or_else(|| cbor_error("missing sig"))?,
+        ))
+    }
+}
+
+#[inline]
+fn key(k: u64) -> Value {
+    Value::Integer(Integer::from(k))
+}
+
+fn cbor_error<E>(e: E) -> Error
+where
+    E: ToString,
+{
+    Error::Cbor(e.to_string())
+}
+
+fn bytes_value(value: Value, field: &str) -> Result<Vec<u8>, Error> {
+    match value {
+        Value::Bytes(bytes) => Ok(bytes),
+        _ => Err(cbor_error(format!("invalid {field}"))),
+    }
+}
+
+fn u64_value(value: Value, field: &str) -> Result<u64, Error> {
+    match value {
+        Value::Integer(i) => u64::try_from(i).map_err(|_| cbor_error(format!("invalid {field}"))),
+        _ => Err(cbor_error(format!("invalid {field}"))),
+    }
+}
+
+fn is_lowercase_hex32(s: &str) -> bool {
+    s.len() == 64 && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn encode_tag_value(value: String) -> Value {
+    if is_lowercase_hex32(&value) {
+        match hex::decode(&value) {
+            Ok(bytes) => Value::Bytes(bytes),
+            Err(_) => Value::Text(value),
+        }
+    } else {
+        Value::Text(value)
+    }
+}
+
+fn decode_tags(value: Value) -> Result<Vec<Tag>, Error> {
+    let tags: Vec<Value> = match value {
+        Value::Array(tags) => tags,
+        _ => return Err(cbor_error("invalid tags")),
+    };
+
+    let mut output: Vec<Tag> = Vec::with_capacity(tags.len());
+    for tag in tags.into_iter() {
+        let values: Vec<Value> = match tag {
+            Value::Array(values) => values,
+            _ => return Err(cbor_error("invalid tag")),
+        };
+        let values: Vec<String> = values
+            .into_iter()
+            .map(|v| match v {
+                Value::Text(text) => Ok(text),
+                Value::Bytes(bytes) if bytes.len() == 32 => Ok(hex::encode(bytes)),
+                _ => Err(cbor_error("invalid tag value")),
+            })
+            .collect::<Result<_, _>>()?;
+        output.push(Tag::parse(&values).map_err(cbor_error)?);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonUtil;
+
+    const JSON: &str = r#"{"content":"uRuvYr585B80L6rSJiHocw==?iv=oh6LVqdsYYol3JfFnXTbPA==","created_at":1640839235,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","kind":4,"pubkey":"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[["p","13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"]]}"#;
+
+    #[test]
+    fn test_cbor_json_round_trip() {
+        let event = Event::from_json(JSON).unwrap();
+
+        let cbor: Vec<u8> = event.to_cbor().unwrap();
+        let decoded = Event::from_cbor(&cbor).unwrap();
+        assert_eq!(decoded, event);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&decoded.as_json()).unwrap(),
+            serde_json::from_str::<serde_json::Value>(JSON).unwrap()
+        );
+        assert_eq!(Event::from_json(decoded.as_json()).unwrap(), event);
+        #[cfg(feature = "std")]
+        decoded.verify().unwrap();
+    }
+
+    #[test]
+    fn test_cbor_tag_values() {
+        let json = r#"{"id":"378f145897eea948952674269945e88612420db35791784abf0616b4fed56ef7","pubkey":"e8ed3798c6ffebffa08501ac39e271662bfd160f688f94c45d692d8767dd345a","created_at":1698412975,"kind":1,"tags":[["e","2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","","reply"],["t","2BE17AA3031BDCB006F0FCE80C146DEA9C1C0268B0AF2398BB673365C6444D45"],["client","rust-nostr"]],"content":"Hello","sig":"4b8b6a3e4f7d1b2c7d0f1a9c1b6a7e3c2d4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d"}"#;
+        let event = Event::from_json(json).unwrap();
+        let decoded = Event::from_cbor(&event.to_cbor().unwrap()).unwrap();
+        assert_eq!(decoded, event);
+        assert_eq!(decoded.tags(), event.tags());
+    }
+
+    #[test]
+    fn test_cbor_size() {
+        let event = Event::from_json(JSON).unwrap();
+        let json: usize = event.as_json().len();
+        let cbor: usize = event.to_cbor().unwrap().len();
+        // At least 30% smaller
+        assert!(cbor * 10 <= json * 7);
+    }
+
+    #[test]
+    fn test_invalid_cbor() {
+        assert!(Event::from_cbor(&[]).is_err());
+        assert!(Event::from_cbor(&[0xa0]).is_err()); // Empty map
+    }
+
+    #[test]
+    fn test_event_id_from_cbor_bytes() {
+        let event = Event::from_json(JSON).unwrap();
+        let mut buf: Vec<u8> = Vec::new();
+        ciborium::into_writer(&Value::Bytes(event.id().to_bytes().to_vec()), &mut buf).unwrap();
+        assert_eq!(EventId::from_cbor_bytes(&buf).unwrap(), event.id());
+        assert!(EventId::from_cbor_bytes(&[0x40]).is_err()); // Empty bytes
+    }
+}
+
+#[cfg(bench)]
+mod benches {
+    use test::{black_box, Bencher};
+
+    use super::*;
+    use crate::JsonUtil;
+
+    const JSON: &str = r#"{"content":"uRuvYr585B80L6rSJiHocw==?iv=oh6LVqdsYYol3JfFnXTbPA==","created_at":1640839235,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","kind":4,"pubkey":"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[["p","13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"]]}"#;
+
+    #[bench]
+    pub fn serialize_event_cbor(bh: &mut Bencher) {
+        let event = Event::from_json(JSON).unwrap();
+        bh.iter(|| {
+            black_box(event.to_cbor()).unwrap();
+        });
+    }
+
+    #[bench]
+    pub fn deserialize_event_cbor(bh: &mut Bencher) {
+        let cbor: Vec<u8> = Event::from_json(JSON).unwrap().to_cbor().unwrap();
+        bh.iter(|| {
+            black_box(Event::from_cbor(&cbor)).unwrap();
+        });
+    }
+}
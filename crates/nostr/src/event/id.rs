@@ -11,11 +11,14 @@ use core::str::FromStr;
 use bitcoin::hashes::sha256::Hash as Sha256Hash;
 use bitcoin::hashes::{FromSliceError, Hash};
 use bitcoin::hex::HexToArrayError;
+#[cfg(feature = "std")]
+use bitcoin::secp256k1::rand::rngs::OsRng;
+use bitcoin::secp256k1::rand::Rng;
 use serde_json::{json, Value};
 
 use super::{Kind, Tag};
 use crate::nips::nip13;
-use crate::nips::nip19::FromBech32;
+use crate::nips::nip19::{self, FromBech32, ToBech32, PREFIX_BECH32_NOTE_ID};
 use crate::nips::nip21::NostrURI;
 use crate::{PublicKey, Timestamp};
 
@@ -110,6 +113,17 @@ impl EventId {
         Ok(Self(Sha256Hash::from_slice(sl)?))
     }
 
+    /// [`EventId`] from a CBOR byte string
+    ///
+    /// Check the [`cbor`](super::cbor) module.
+    #[cfg(feature = "cbor")]
+    pub fn from_cbor_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        match ciborium::from_reader(bytes) {
+            Ok(ciborium::value::Value::Bytes(bytes)) => Self::from_slice(&bytes),
+            _ => Err(Error::InvalidEventId),
+        }
+    }
+
     /// [`EventId`] from hash
     pub fn from_hash(hash: Sha256Hash) -> Self {
         Self(hash)
@@ -120,6 +134,24 @@ impl EventId {
         Self(Sha256Hash::all_zeros())
     }
 
+    /// Generate random [`EventId`]
+    ///
+    /// Useful for tests: the ID doesn't match any event.
+    #[cfg(feature = "std")]
+    pub fn random() -> Self {
+        Self::random_with_rng(&mut OsRng)
+    }
+
+    /// Generate random [`EventId`] with custom [`Rng`]
+    pub fn random_with_rng<R>(rng: &mut R) -> Self
+    where
+        R: Rng + ?Sized,
+    {
+        let mut bytes = [0u8; 32];
+        rng.fill_bytes(&mut bytes);
+        Self(Sha256Hash::from_byte_array(bytes))
+    }
+
     /// Get as bytes
     pub fn as_bytes(&self) -> &[u8] {
         self.as_ref()
@@ -135,11 +167,58 @@ impl EventId {
         self.0.to_string()
     }
 
+    /// Get the first `chars` characters of the `hex` event ID
+    pub fn short_hex(&self, chars: usize) -> String {
+        let mut hex: String = self.to_hex();
+        hex.truncate(chars);
+        hex
+    }
+
+    /// Check if event ID starts with `prefix` (`hex` or `note` bech32)
+    ///
+    /// An empty prefix not match anything.
+    pub fn matches_prefix(&self, prefix: &str) -> bool {
+        nip19::matches_prefix(prefix, PREFIX_BECH32_NOTE_ID, &self.to_hex(), || {
+            self.to_bech32()
+        })
+    }
+
+    /// Check if the `hex` event ID starts with `prefix_hex` (case insensitive)
+    ///
+    /// Unlike [`EventId::matches_prefix`], an empty prefix match any event ID.
+    pub fn prefix_matches(&self, prefix_hex: &str) -> bool {
+        let hex: String = self.to_hex();
+        hex.len() >= prefix_hex.len()
+            && hex.as_bytes()[..prefix_hex.len()].eq_ignore_ascii_case(prefix_hex.as_bytes())
+    }
+
+    /// Count leading zero bits of the event ID
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/13.md>
+    pub fn leading_zero_bits(&self) -> u8 {
+        nip13::difficulty(self)
+    }
+
+    /// Count leading bits shared with `other` event ID
+    ///
+    /// Identical IDs share 256 bits, so the result saturates at `255`.
+    pub fn common_prefix_bits(&self, other: &EventId) -> u8 {
+        let mut bits: u16 = 0;
+        for (a, b) in self.as_bytes().iter().zip(other.as_bytes().iter()) {
+            let xor: u8 = a ^ b;
+            bits += xor.leading_zeros() as u16;
+            if xor != 0 {
+                break;
+            }
+        }
+        bits.min(u8::MAX as u16) as u8
+    }
+
     /// Check POW
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/13.md>
     pub fn check_pow(&self, difficulty: u8) -> bool {
-        nip13::get_leading_zero_bits(self.as_bytes()) >= difficulty
+        nip13::difficulty(self) >= difficulty
     }
 
     /// Get [`EventId`] as [`Sha256Hash`]
@@ -206,4 +285,37 @@ mod tests {
         assert!(id.check_pow(20));
         assert!(!id.check_pow(25));
     }
+
+    #[test]
+    fn test_short_hex_and_matches_prefix() {
+        let id =
+            EventId::from_hex("2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45")
+                .unwrap();
+        assert_eq!(id.short_hex(8), "2be17aa3");
+        assert_eq!(id.short_hex(100), id.to_hex());
+
+        assert!(id.matches_prefix("2be17a"));
+        assert!(id.matches_prefix(&id.to_bech32().unwrap()[..10]));
+        assert!(!id.matches_prefix("2be17b"));
+        assert!(!id.matches_prefix(""));
+    }
+
+    #[test]
+    fn test_vanity_helpers() {
+        let id =
+            EventId::from_hex("00000340cb60be5829fbf2712a285f12cf89e5db951c5303b731651f0d71ac1b")
+                .unwrap();
+        assert_eq!(id.leading_zero_bits(), 22);
+        assert!(id.prefix_matches("00000340CB"));
+        assert!(id.prefix_matches(""));
+        assert!(!id.prefix_matches("00001"));
+
+        let other =
+            EventId::from_hex("00000380cb60be5829fbf2712a285f12cf89e5db951c5303b731651f0d71ac1b")
+                .unwrap();
+        assert_eq!(id.common_prefix_bits(&other), 24);
+        assert_eq!(id.common_prefix_bits(&id), u8::MAX);
+
+        assert_ne!(EventId::random(), EventId::random());
+    }
 }
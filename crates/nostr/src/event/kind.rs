@@ -27,6 +27,23 @@ pub const EPHEMERAL_RANGE: Range<u64> = 20_000..30_000;
 /// Parameterized replaceable range
 pub const PARAMETERIZED_REPLACEABLE_RANGE: Range<u64> = 30_000..40_000;
 
+/// Human-readable [`Kind`] metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KindCategory {
+    /// NIP that defines the kind
+    pub nip: Option<u16>,
+    /// Name
+    pub name: &'static str,
+    /// Description
+    pub description: &'static str,
+    /// Replaceable kind
+    pub is_replaceable: bool,
+    /// Ephemeral kind
+    pub is_ephemeral: bool,
+    /// Addressable (parameterized replaceable) kind
+    pub is_addressable: bool,
+}
+
 /// Event [`Kind`]
 #[derive(Debug, Clone, Copy)]
 pub enum Kind {
@@ -186,6 +203,8 @@ pub enum Kind {
     FileMetadata,
     /// HTTP Auth (NIP98)
     HttpAuth,
+    /// Classified Listing (NIP99)
+    ClassifiedListing,
     /// Set stall (NIP15)
     SetStall,
     /// Set product (NIP15)
@@ -295,6 +314,221 @@ impl Kind {
     pub fn is_parameterized_replaceable(&self) -> bool {
         PARAMETERIZED_REPLACEABLE_RANGE.contains(&self.as_u64())
     }
+
+    /// Get human-readable [`KindCategory`]
+    pub fn category(&self) -> KindCategory {
+        let (nip, name, description): (Option<u16>, &'static str, &'static str) =
+            match Self::from(self.as_u64()) {
+                Self::Metadata => (Some(1), "Metadata", "User profile metadata"),
+                Self::TextNote => (Some(1), "Short Text Note", "Plain text note"),
+                Self::RecommendRelay => (
+                    Some(1),
+                    "Recommend Relay",
+                    "Relay recommendation (deprecated)",
+                ),
+                Self::ContactList => (Some(2), "Contacts", "Follow list"),
+                Self::OpenTimestamps => (
+                    Some(3),
+                    "OpenTimestamps Attestations",
+                    "OpenTimestamps attestation for an event",
+                ),
+                Self::EncryptedDirectMessage => (
+                    Some(4),
+                    "Encrypted Direct Messages",
+                    "NIP04 encrypted direct message",
+                ),
+                Self::EventDeletion => (Some(9), "Event Deletion", "Request to delete events"),
+                Self::Repost => (Some(18), "Repost", "Repost of a short text note"),
+                Self::GenericRepost => (Some(18), "Generic Repost", "Repost of any event kind"),
+                Self::Reaction => (Some(25), "Reaction", "Reaction to an event"),
+                Self::BadgeAward => (
+                    Some(58),
+                    "Badge Award",
+                    "Award of a badge to one or more users",
+                ),
+                Self::ChannelCreation => {
+                    (Some(28), "Channel Creation", "Public chat channel creation")
+                }
+                Self::ChannelMetadata => {
+                    (Some(28), "Channel Metadata", "Public chat channel metadata")
+                }
+                Self::ChannelMessage => {
+                    (Some(28), "Channel Message", "Public chat channel message")
+                }
+                Self::ChannelHideMessage => (
+                    Some(28),
+                    "Channel Hide Message",
+                    "Hide a public chat channel message",
+                ),
+                Self::ChannelMuteUser => (
+                    Some(28),
+                    "Channel Mute User",
+                    "Mute a user in public chat channels",
+                ),
+                Self::PublicChatReserved45 => (
+                    Some(28),
+                    "Public Chat Reserved",
+                    "Reserved for future public chat use",
+                ),
+                Self::PublicChatReserved46 => (
+                    Some(28),
+                    "Public Chat Reserved",
+                    "Reserved for future public chat use",
+                ),
+                Self::PublicChatReserved47 => (
+                    Some(28),
+                    "Public Chat Reserved",
+                    "Reserved for future public chat use",
+                ),
+                Self::PublicChatReserved48 => (
+                    Some(28),
+                    "Public Chat Reserved",
+                    "Reserved for future public chat use",
+                ),
+                Self::PublicChatReserved49 => (
+                    Some(28),
+                    "Public Chat Reserved",
+                    "Reserved for future public chat use",
+                ),
+                Self::WalletConnectInfo => (
+                    Some(47),
+                    "Wallet Service Info",
+                    "Capabilities of a wallet connect service",
+                ),
+                Self::Reporting => (Some(56), "Reporting", "Report of a user or event"),
+                Self::ZapPrivateMessage => (
+                    Some(57),
+                    "Zap Private Message",
+                    "Private message attached to a zap",
+                ),
+                Self::ZapRequest => (Some(57), "Zap Request", "Request for a lightning zap"),
+                Self::ZapReceipt => (Some(57), "Zap Receipt", "Receipt of a paid lightning zap"),
+                Self::MuteList => (
+                    Some(51),
+                    "Mute List",
+                    "Muted users, hashtags, words and threads",
+                ),
+                Self::PinList => (Some(51), "Pin List", "Events pinned to the user profile"),
+                Self::Bookmarks => (Some(51), "Bookmarks", "Bookmarked events"),
+                Self::Communities => (Some(51), "Communities", "Followed communities"),
+                Self::PublicChats => (Some(51), "Public Chats", "Joined public chat channels"),
+                Self::BlockedRelays => (
+                    Some(51),
+                    "Blocked Relays",
+                    "Relays that clients should never connect to",
+                ),
+                Self::SearchRelays => (Some(51), "Search Relays", "Relays to use for search"),
+                Self::SimpleGroups => (Some(51), "Simple Groups", "Joined simple groups"),
+                Self::Interests => (Some(51), "Interests", "Topics of interest"),
+                Self::Emojis => (Some(51), "Emojis", "Preferred emojis and emoji sets"),
+                Self::FollowSets => (Some(51), "Follow Sets", "Categorized groups of users"),
+                Self::RelaySets => (Some(51), "Relay Sets", "User-defined relay groups"),
+                Self::BookmarkSets => (Some(51), "Bookmark Sets", "Categorized bookmarks"),
+                Self::ArticlesCurationSets => (
+                    Some(51),
+                    "Articles Curation Sets",
+                    "Curated articles and notes",
+                ),
+                Self::VideosCurationSets => (Some(51), "Videos Curation Sets", "Curated videos"),
+                Self::InterestSets => (Some(51), "Interest Sets", "Categorized topics of interest"),
+                Self::EmojiSets => (Some(51), "Emoji Sets", "Categorized emoji groups"),
+                Self::ReleaseArtifactSets => (
+                    Some(51),
+                    "Release Artifact Sets",
+                    "Files of a software release",
+                ),
+                Self::RelayList => (
+                    Some(65),
+                    "Relay List Metadata",
+                    "Read and write relays of the user",
+                ),
+                Self::Authentication => (
+                    Some(42),
+                    "Client Authentication",
+                    "Authentication of a client to a relay",
+                ),
+                Self::WalletConnectRequest => (
+                    Some(47),
+                    "Wallet Connect Request",
+                    "Request to a wallet connect service",
+                ),
+                Self::WalletConnectResponse => (
+                    Some(47),
+                    "Wallet Connect Response",
+                    "Response of a wallet connect service",
+                ),
+                Self::NostrConnect => (
+                    Some(46),
+                    "Nostr Connect",
+                    "Remote signing request or response",
+                ),
+                Self::LiveEvent => (Some(53), "Live Event", "Live streaming event"),
+                Self::LiveEventMessage => (Some(53), "Live Event Message", "Live chat message"),
+                Self::ProfileBadges => (
+                    Some(58),
+                    "Profile Badges",
+                    "Badges displayed on the user profile",
+                ),
+                Self::BadgeDefinition => (Some(58), "Badge Definition", "Definition of a badge"),
+                Self::Seal => (Some(59), "Seal", "Signed and encrypted rumor"),
+                Self::GiftWrap => (
+                    Some(59),
+                    "Gift Wrap",
+                    "Encrypted seal signed by a random key",
+                ),
+                Self::SealedDirect => (
+                    Some(59),
+                    "Sealed Direct Message",
+                    "Gift wrapped sealed direct message",
+                ),
+                Self::LongFormTextNote => (
+                    Some(23),
+                    "Long-form Text Note",
+                    "Long-form content, like articles or blog posts",
+                ),
+                Self::ApplicationSpecificData => (
+                    Some(78),
+                    "Application-specific Data",
+                    "Arbitrary custom app data",
+                ),
+                Self::FileMetadata => (Some(94), "File Metadata", "Metadata of a shared file"),
+                Self::HttpAuth => (Some(98), "HTTP Auth", "Authorization of HTTP requests"),
+                Self::ClassifiedListing => (
+                    Some(99),
+                    "Classified Listing",
+                    "Listing of a product or service for sale",
+                ),
+                Self::SetStall => (Some(15), "Set Stall", "Marketplace stall"),
+                Self::SetProduct => (Some(15), "Set Product", "Marketplace product"),
+                Self::JobFeedback => (
+                    Some(90),
+                    "Job Feedback",
+                    "Status of a data vending machine job",
+                ),
+                Self::JobRequest(..) => {
+                    (Some(90), "Job Request", "Data vending machine job request")
+                }
+                Self::JobResult(..) => (Some(90), "Job Result", "Data vending machine job result"),
+                Self::Regular(..) => (Some(1), "Regular", "Regular event"),
+                Self::Replaceable(..) => (Some(1), "Replaceable", "Replaceable event"),
+                Self::Ephemeral(..) => (Some(1), "Ephemeral", "Ephemeral event"),
+                Self::ParameterizedReplaceable(..) => (
+                    Some(1),
+                    "Parameterized Replaceable",
+                    "Parameterized replaceable event",
+                ),
+                Self::Custom(..) => (None, "Unknown", "Unknown event kind"),
+            };
+
+        KindCategory {
+            nip,
+            name,
+            description,
+            is_replaceable: self.is_replaceable(),
+            is_ephemeral: self.is_ephemeral(),
+            is_addressable: self.is_parameterized_replaceable(),
+        }
+    }
 }
 
 impl fmt::Display for Kind {
@@ -369,6 +603,7 @@ impl From<u64> for Kind {
             30078 => Self::ApplicationSpecificData,
             1063 => Self::FileMetadata,
             27235 => Self::HttpAuth,
+            30402 => Self::ClassifiedListing,
             7000 => Self::JobFeedback,
             x if (NIP90_JOB_REQUEST_RANGE).contains(&x) => Self::JobRequest(x as u16),
             x if (NIP90_JOB_RESULT_RANGE).contains(&x) => Self::JobResult(x as u16),
@@ -448,6 +683,7 @@ impl From<Kind> for u64 {
             Kind::ApplicationSpecificData => 30078,
             Kind::FileMetadata => 1063,
             Kind::HttpAuth => 27235,
+            Kind::ClassifiedListing => 30402,
             Kind::JobFeedback => 7000,
             Kind::JobRequest(u) => u as u64,
             Kind::JobResult(u) => u as u64,
@@ -544,4 +780,25 @@ mod tests {
         assert!(Kind::ParameterizedReplaceable(32122).is_parameterized_replaceable());
         assert!(!Kind::ParameterizedReplaceable(1).is_parameterized_replaceable());
     }
+
+    #[test]
+    fn test_kind_category() {
+        let category = Kind::Metadata.category();
+        assert_eq!(category.nip, Some(1));
+        assert_eq!(category.name, "Metadata");
+        assert!(category.is_replaceable);
+        assert!(!category.is_addressable);
+
+        let category = Kind::LongFormTextNote.category();
+        assert_eq!(category.nip, Some(23));
+        assert!(category.is_addressable);
+
+        let category = Kind::Custom(20_100).category();
+        assert_eq!(category.name, "Ephemeral");
+        assert!(category.is_ephemeral);
+
+        let category = Kind::Custom(50_000).category();
+        assert_eq!(category.nip, None);
+        assert_eq!(category.name, "Unknown");
+    }
 }
@@ -0,0 +1,333 @@
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Tags lint
+//!
+//! Find malformed tags before the event is signed and published.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::tag::{Tag, TagKind};
+use crate::{Alphabet, SingleLetterTag, UncheckedUrl, Url};
+
+/// Max length of an indexable (single-letter) tag value
+pub const MAX_TAG_VALUE_LEN: usize = 1024;
+
+/// Lint severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintSeverity {
+    /// The event is valid but may be handled badly by relays or clients
+    Warning,
+    /// The event will be rejected or mis-indexed by relays
+    Error,
+}
+
+/// Lint warning
+///
+/// `index` is the position of the tag in the tags list.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LintWarning {
+    /// `p` tag value isn't a hex public key (ex. a bech32 `npub`)
+    InvalidPubkeyInTag {
+        /// Tag index
+        index: usize,
+        /// Value
+        value: String,
+    },
+    /// `e` tag value isn't a hex event ID (ex. wrong length)
+    InvalidEventIdInTag {
+        /// Tag index
+        index: usize,
+        /// Value
+        value: String,
+    },
+    /// Value that must be hex contains non-hex chars
+    NonHexValue {
+        /// Tag index
+        index: usize,
+        /// Value
+        value: String,
+    },
+    /// Relay hint isn't a `ws://` or `wss://` URL
+    BadRelayUrlHint {
+        /// Tag index
+        index: usize,
+        /// Relay hint
+        url: String,
+    },
+    /// Tag already present
+    DuplicateTag {
+        /// Tag index
+        index: usize,
+        /// Index of the first occurrence
+        first: usize,
+    },
+    /// Indexable tag value longer than [`MAX_TAG_VALUE_LEN`]
+    OversizedTagValue {
+        /// Tag index
+        index: usize,
+        /// Value length
+        len: usize,
+    },
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPubkeyInTag { index, value } => {
+                write!(f, "Tag {index}: invalid public key '{value}'")
+            }
+            Self::InvalidEventIdInTag { index, value } => {
+                write!(f, "Tag {index}: invalid event ID '{value}'")
+            }
+            Self::NonHexValue { index, value } => {
+                write!(f, "Tag {index}: non-hex value '{value}'")
+            }
+            Self::BadRelayUrlHint { index, url } => {
+                write!(f, "Tag {index}: bad relay url hint '{url}'")
+            }
+            Self::DuplicateTag { index, first } => {
+                write!(f, "Tag {index}: duplicate of tag {first}")
+            }
+            Self::OversizedTagValue { index, len } => {
+                write!(f, "Tag {index}: value too long ({len} bytes)")
+            }
+        }
+    }
+}
+
+impl LintWarning {
+    /// Get severity
+    pub fn severity(&self) -> LintSeverity {
+        match self {
+            Self::InvalidPubkeyInTag { .. }
+            | Self::InvalidEventIdInTag { .. }
+            | Self::NonHexValue { .. } => LintSeverity::Error,
+            Self::BadRelayUrlHint { .. }
+            | Self::DuplicateTag { .. }
+            | Self::OversizedTagValue { .. } => LintSeverity::Warning,
+        }
+    }
+
+    /// Check if has [`LintSeverity::Error`] severity
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        self.severity() == LintSeverity::Error
+    }
+}
+
+/// Lint tags
+///
+/// Custom tags are skipped.
+pub fn lint_tags<'a, I>(tags: I) -> Vec<LintWarning>
+where
+    I: IntoIterator<Item = &'a Tag>,
+{
+    let mut warnings: Vec<LintWarning> = Vec::new();
+    let mut seen: BTreeMap<Vec<String>, usize> = BTreeMap::new();
+
+    for (index, tag) in tags.into_iter().enumerate() {
+        let kind: TagKind = tag.kind();
+        if let TagKind::Custom(..) = kind {
+            continue;
+        }
+
+        let values: Vec<String> = tag.as_vec();
+
+        // Duplicates
+        match seen.get(&values) {
+            Some(first) => warnings.push(LintWarning::DuplicateTag {
+                index,
+                first: *first,
+            }),
+            None => {
+                seen.insert(values.clone(), index);
+            }
+        }
+
+        // Relay hints
+        for url in relay_hints(tag).into_iter() {
+            if !is_relay_url(url) {
+                warnings.push(LintWarning::BadRelayUrlHint {
+                    index,
+                    url: url.to_string(),
+                });
+            }
+        }
+
+        if let TagKind::SingleLetter(single) = kind {
+            if let Some(value) = values.get(1) {
+                // Oversized
+                if value.len() > MAX_TAG_VALUE_LEN {
+                    warnings.push(LintWarning::OversizedTagValue {
+                        index,
+                        len: value.len(),
+                    });
+                }
+
+                // Values of generic tags, not validated by the parser
+                if let Tag::Generic(..) = tag {
+                    lint_generic(index, single, &values, &mut warnings);
+                }
+            }
+        }
+    }
+
+    warnings
+}
+
+fn lint_generic(
+    index: usize,
+    single: SingleLetterTag,
+    values: &[String],
+    warnings: &mut Vec<LintWarning>,
+) {
+    let value: &str = &values[1];
+    match single.character {
+        Alphabet::P => {
+            if !is_hex(value, 64) {
+                warnings.push(if is_bech32_like(value) || is_hex(value, value.len()) {
+                    LintWarning::InvalidPubkeyInTag {
+                        index,
+                        value: value.to_string(),
+                    }
+                } else {
+                    LintWarning::NonHexValue {
+                        index,
+                        value: value.to_string(),
+                    }
+                });
+            }
+        }
+        Alphabet::E if !single.uppercase => {
+            if !is_hex(value, 64) {
+                warnings.push(if is_bech32_like(value) || is_hex(value, value.len()) {
+                    LintWarning::InvalidEventIdInTag {
+                        index,
+                        value: value.to_string(),
+                    }
+                } else {
+                    LintWarning::NonHexValue {
+                        index,
+                        value: value.to_string(),
+                    }
+                });
+            }
+        }
+        _ => return,
+    }
+
+    if let Some(url) = values.get(2) {
+        if !is_relay_url(&UncheckedUrl::from(url.as_str())) {
+            warnings.push(LintWarning::BadRelayUrlHint {
+                index,
+                url: url.clone(),
+            });
+        }
+    }
+}
+
+fn relay_hints(tag: &Tag) -> Vec<&UncheckedUrl> {
+    match tag {
+        Tag::Event { relay_url, .. }
+        | Tag::PublicKey { relay_url, .. }
+        | Tag::PubKeyLiveEvent { relay_url, .. }
+        | Tag::A { relay_url, .. } => relay_url.iter().collect(),
+        Tag::Relay(url) | Tag::RelayMetadata(url, ..) => vec![url],
+        Tag::Relays(urls) => urls.iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn is_relay_url(url: &UncheckedUrl) -> bool {
+    let url: String = url.to_string();
+    // Empty hints are allowed
+    if url.is_empty() {
+        return true;
+    }
+    match Url::parse(&url) {
+        Ok(url) => matches!(url.scheme(), "ws" | "wss"),
+        Err(..) => false,
+    }
+}
+
+#[inline]
+fn is_hex(value: &str, len: usize) -> bool {
+    !value.is_empty() && value.len() == len && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+#[inline]
+fn is_bech32_like(value: &str) -> bool {
+    ["npub1", "nprofile1", "note1", "nevent1"]
+        .iter()
+        .any(|prefix| value.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nips::nip19::ToBech32;
+    use crate::{EventId, Keys};
+
+    #[test]
+    fn test_lint_tags() {
+        let keys = Keys::generate();
+        let event_id = EventId::all_zeros();
+        let npub: String = keys.public_key().to_bech32().unwrap();
+        let tags = [
+            Tag::public_key(keys.public_key()),
+            Tag::Generic(
+                TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::P)),
+                vec![npub.clone()],
+            ),
+            Tag::Generic(
+                TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::E)),
+                vec![event_id.to_hex()[1..].to_string()],
+            ),
+            Tag::Generic(
+                TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::P)),
+                vec![String::from("z").repeat(64)],
+            ),
+            Tag::Event {
+                event_id,
+                relay_url: Some(UncheckedUrl::from("https://relay.example.com")),
+                marker: None,
+            },
+            Tag::public_key(keys.public_key()),
+            Tag::Hashtag(String::from("a").repeat(MAX_TAG_VALUE_LEN + 1)),
+            Tag::Generic(TagKind::Custom(String::from("custom")), vec![npub]),
+        ];
+
+        let warnings = lint_tags(tags.iter());
+        assert_eq!(
+            warnings,
+            vec![
+                LintWarning::InvalidPubkeyInTag {
+                    index: 1,
+                    value: keys.public_key().to_bech32().unwrap(),
+                },
+                LintWarning::InvalidEventIdInTag {
+                    index: 2,
+                    value: event_id.to_hex()[1..].to_string(),
+                },
+                LintWarning::NonHexValue {
+                    index: 3,
+                    value: String::from("z").repeat(64),
+                },
+                LintWarning::BadRelayUrlHint {
+                    index: 4,
+                    url: String::from("https://relay.example.com"),
+                },
+                LintWarning::DuplicateTag { index: 5, first: 0 },
+                LintWarning::OversizedTagValue {
+                    index: 6,
+                    len: MAX_TAG_VALUE_LEN + 1,
+                },
+            ]
+        );
+        assert_eq!(warnings.iter().filter(|w| w.is_error()).count(), 3);
+    }
+}
@@ -19,20 +19,33 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 
 pub mod builder;
+#[cfg(feature = "cbor")]
+pub mod cbor;
 pub mod id;
 pub mod kind;
+pub mod lint;
 pub mod partial;
 pub mod raw;
+pub mod set;
+pub mod strict;
 pub mod tag;
+pub mod tags;
 pub mod unsigned;
 
-pub use self::builder::EventBuilder;
+pub use self::builder::{EventBuilder, TextNoteBuilder};
 pub use self::id::EventId;
-pub use self::kind::Kind;
+pub use self::kind::{Kind, KindCategory};
+pub use self::lint::{LintSeverity, LintWarning};
 pub use self::partial::{MissingPartialEvent, PartialEvent};
+pub use self::set::EventSet;
+pub use self::strict::StrictOptions;
 pub use self::tag::{Marker, Tag, TagKind};
+pub use self::tags::Tags;
 pub use self::unsigned::UnsignedEvent;
 use crate::nips::nip01::Coordinate;
+use crate::nips::nip19::{FromBech32, Nip19, ToBech32};
+use crate::nips::nip89::ClientTag;
+use crate::nips::{nip21, nip26};
 #[cfg(feature = "std")]
 use crate::types::time::Instant;
 use crate::types::time::TimeSupplier;
@@ -40,6 +53,9 @@ use crate::types::time::TimeSupplier;
 use crate::SECP256K1;
 use crate::{JsonUtil, PublicKey, Timestamp};
 
+/// Max number of content chars in [`Event::display_summary`]
+pub const DISPLAY_CONTENT_MAX_CHARS: usize = 80;
+
 /// [`Event`] error
 #[derive(Debug, PartialEq, Eq)]
 pub enum Error {
@@ -51,6 +67,9 @@ pub enum Error {
     Json(String),
     /// Secp256k1 error
     Secp256k1(secp256k1::Error),
+    /// Error encoding or decoding CBOR data
+    #[cfg(feature = "cbor")]
+    Cbor(String),
 }
 
 #[cfg(feature = "std")]
@@ -63,6 +82,8 @@ impl fmt::Display for Error {
             Self::InvalidId => write!(f, "Invalid event id"),
             Self::Json(e) => write!(f, "Json: {e}"),
             Self::Secp256k1(e) => write!(f, "Secp256k1: {e}"),
+            #[cfg(feature = "cbor")]
+            Self::Cbor(e) => write!(f, "Cbor: {e}"),
         }
     }
 }
@@ -102,6 +123,12 @@ impl fmt::Debug for Event {
     }
 }
 
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display_summary())
+    }
+}
+
 impl PartialEq for Event {
     fn eq(&self, other: &Self) -> bool {
         self.inner.eq(&other.inner)
@@ -172,6 +199,35 @@ impl Event {
         Ok(serde_json::from_value(value)?)
     }
 
+    /// Deserialize [`Event`] from JSON, checking the NIP01 structure and the `created_at` window
+    ///
+    /// Use [`StrictOptions::relaxed`] to get the same behavior of [`Event::from_json`].
+    ///
+    /// **This method NOT verify the signature!**
+    #[cfg(feature = "std")]
+    pub fn from_json_strict<T>(json: T, opts: StrictOptions) -> Result<Self, strict::Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        strict::from_json(json.as_ref(), opts, Timestamp::now())
+    }
+
+    /// Deserialize [`Event`] from JSON, checking the NIP01 structure and the `created_at` window
+    /// against the time of a [`TimeSupplier`]
+    ///
+    /// **This method NOT verify the signature!**
+    pub fn from_json_strict_with_supplier<T, S>(
+        json: T,
+        opts: StrictOptions,
+        supplier: &S,
+    ) -> Result<Self, strict::Error>
+    where
+        T: AsRef<[u8]>,
+        S: TimeSupplier,
+    {
+        strict::from_json(json.as_ref(), opts, Timestamp::now_with_supplier(supplier))
+    }
+
     /// Get event ID
     #[inline]
     pub fn id(&self) -> EventId {
@@ -232,6 +288,22 @@ impl Event {
         self.inner.sig
     }
 
+    /// Serialize as JSON directly into the `writer`, without an intermediate [`String`]
+    #[cfg(feature = "std")]
+    pub fn write_json<W>(&self, writer: W) -> Result<(), Error>
+    where
+        W: std::io::Write,
+    {
+        Ok(serde_json::to_writer(writer, self)?)
+    }
+
+    /// Lint tags
+    ///
+    /// Check [`LintWarning`] for the findings.
+    pub fn lint(&self) -> Vec<LintWarning> {
+        lint::lint_tags(self.tags.iter())
+    }
+
     /// Verify both [`EventId`] and [`Signature`]
     #[cfg(feature = "std")]
     pub fn verify(&self) -> Result<(), Error> {
@@ -282,6 +354,18 @@ impl Event {
             .map_err(|_| Error::InvalidSignature)
     }
 
+    /// Get the effective author of the event
+    ///
+    /// Return the delegator if the event has a valid [NIP26](https://github.com/nostr-protocol/nips/blob/master/26.md) delegation tag,
+    /// otherwise the event author. The event ID and signature are **not** verified (see [`nip26::verify_delegated_event`]).
+    #[cfg(feature = "std")]
+    pub fn effective_author(&self) -> PublicKey {
+        match nip26::delegation_status_with_ctx(&SECP256K1, self) {
+            nip26::DelegationStatus::Valid { delegator } => delegator,
+            _ => self.author(),
+        }
+    }
+
     /// Check POW
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/13.md>
@@ -383,6 +467,13 @@ impl Event {
         self.inner.kind.is_parameterized_replaceable()
     }
 
+    /// Extract client tag, if exists.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/89.md>
+    pub fn client(&self) -> Option<ClientTag> {
+        self.iter_tags().find_map(ClientTag::from_tag)
+    }
+
     /// Extract identifier (`d` tag), if exists.
     #[inline]
     pub fn identifier(&self) -> Option<&str> {
@@ -424,6 +515,157 @@ impl Event {
             _ => None,
         })
     }
+
+    /// Check if the event mentions the [`PublicKey`]
+    ///
+    /// Check both `p` tags and inline `nostr:npub1...`/`nostr:nprofile1...` mentions in content.
+    pub fn mentions(&self, public_key: &PublicKey) -> bool {
+        self.mentions_any(core::slice::from_ref(public_key))
+    }
+
+    /// Check if the event mentions at least one of the [`PublicKey`]s
+    ///
+    /// Check both `p` tags and inline `nostr:npub1...`/`nostr:nprofile1...` mentions in content.
+    pub fn mentions_any(&self, public_keys: &[PublicKey]) -> bool {
+        if public_keys.is_empty() {
+            return false;
+        }
+
+        if self.public_keys().any(|p| public_keys.contains(p)) {
+            return true;
+        }
+
+        self.content_public_keys().any(|p| public_keys.contains(&p))
+    }
+
+    /// Extract public keys mentioned in content (`nostr:npub1...` and `nostr:nprofile1...`)
+    fn content_public_keys(&self) -> impl Iterator<Item = PublicKey> + '_ {
+        self.content
+            .match_indices(nip21::SCHEME)
+            .filter_map(move |(index, ..)| {
+                let rest: &str = self
+                    .content
+                    .get(index + nip21::SCHEME.len()..)?
+                    .strip_prefix(':')?;
+                let end: usize = rest
+                    .find(|c: char| !c.is_ascii_alphanumeric())
+                    .unwrap_or(rest.len());
+                match Nip19::from_bech32(&rest[..end]).ok()? {
+                    Nip19::Pubkey(public_key) => Some(public_key),
+                    Nip19::Profile(profile) => Some(profile.public_key),
+                    _ => None,
+                }
+            })
+    }
+
+    /// Get the thread root [`EventId`] (NIP10)
+    ///
+    /// Use the `e` tag with `root` marker.
+    /// For events without markers, fallback to the first positional `e` tag.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/10.md>
+    pub fn root_event_id(&self) -> Option<EventId> {
+        let mut first: Option<&EventId> = None;
+        let mut has_markers: bool = false;
+
+        for tag in self.iter_tags() {
+            if let Tag::Event {
+                event_id, marker, ..
+            } = tag
+            {
+                match marker {
+                    Some(Marker::Root) => return Some(*event_id),
+                    Some(..) => has_markers = true,
+                    None => (),
+                }
+
+                if first.is_none() {
+                    first = Some(event_id);
+                }
+            }
+        }
+
+        if has_markers {
+            None
+        } else {
+            first.copied()
+        }
+    }
+
+    /// Get the [`EventId`] of the event this is replying to (NIP10)
+    ///
+    /// Use the `e` tag with `reply` marker. If no `reply` marker is found but other markers are used,
+    /// the event is considered a direct reply to the `root`.
+    /// For events without markers, fallback to the last positional `e` tag.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/10.md>
+    pub fn reply_event_id(&self) -> Option<EventId> {
+        let mut root: Option<&EventId> = None;
+        let mut last: Option<&EventId> = None;
+        let mut has_markers: bool = false;
+
+        for tag in self.iter_tags() {
+            if let Tag::Event {
+                event_id, marker, ..
+            } = tag
+            {
+                match marker {
+                    Some(Marker::Reply) => return Some(*event_id),
+                    Some(Marker::Root) => {
+                        root = Some(event_id);
+                        has_markers = true;
+                    }
+                    Some(..) => has_markers = true,
+                    None => (),
+                }
+                last = Some(event_id);
+            }
+        }
+
+        if has_markers {
+            root.copied()
+        } else {
+            last.copied()
+        }
+    }
+
+    /// Check if the event is a reply to the [`EventId`] (NIP10)
+    ///
+    /// See [`Event::reply_event_id`].
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/10.md>
+    pub fn reply_to_event(&self, id: &EventId) -> bool {
+        self.reply_event_id().as_ref() == Some(id)
+    }
+
+    /// One-line human-readable summary, for logging
+    ///
+    /// `[kind=<name> id=<id> from=<npub> at=<datetime>] <content>`
+    ///
+    /// The ID is shortened to the first 8 hex chars, the `npub` to the first 8 chars after the `npub1` prefix,
+    /// the datetime is RFC 3339 and the content is truncated to [`DISPLAY_CONTENT_MAX_CHARS`] chars (with `...`).
+    pub fn display_summary(&self) -> String {
+        let id: String = self.id.to_hex();
+        let npub: String = self.pubkey.to_bech32().unwrap_or_default();
+
+        let mut content: String = self
+            .content
+            .chars()
+            .take(DISPLAY_CONTENT_MAX_CHARS)
+            .map(|c| if c == '\n' || c == '\r' { ' ' } else { c })
+            .collect();
+        if self.content.chars().count() > DISPLAY_CONTENT_MAX_CHARS {
+            content.push_str("...");
+        }
+
+        format!(
+            "[kind={} id={} from={} at={}] {content}",
+            self.kind.category().name,
+            id.get(..8).unwrap_or_default(),
+            npub.get(..13).unwrap_or_default(),
+            self.created_at.to_human_datetime(),
+        )
+    }
 }
 
 impl JsonUtil for Event {
@@ -441,7 +683,13 @@ impl JsonUtil for Event {
 }
 
 /// Event Intermediate used for de/serialization of [`Event`]
+///
+/// Unknown fields are ignored, unless the `serde-strict` feature is enabled:
+/// in that case events with unknown fields are rejected.
+/// Note that enabling `serde-strict` is a breaking change for events carrying custom (non-standard) fields,
+/// since they will no longer be deserialized.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "serde-strict", serde(deny_unknown_fields))]
 pub struct EventIntermediate {
     /// Id
     pub id: EventId,
@@ -451,8 +699,8 @@ pub struct EventIntermediate {
     pub created_at: Timestamp,
     /// Kind
     pub kind: Kind,
-    /// Vector of [`Tag`]
-    pub tags: Vec<Tag>,
+    /// Tags
+    pub tags: Tags,
     /// Content
     pub content: String,
     /// Signature
@@ -526,6 +774,39 @@ mod tests {
     #[cfg(feature = "std")]
     use crate::Keys;
 
+    #[test]
+    fn test_display_summary() {
+        let json = r#"{"content":"uRuvYr585B80L6rSJiHocw==?iv=oh6LVqdsYYol3JfFnXTbPA==","created_at":1640839235,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","kind":4,"pubkey":"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[["p","13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"]]}"#;
+        let event = Event::from_json(json).unwrap();
+        assert_eq!(
+            event.to_string(),
+            "[kind=Encrypted Direct Messages id=2be17aa3 from=npub1lpkyfgk7 at=2021-12-30T04:40:35Z] uRuvYr585B80L6rSJiHocw==?iv=oh6LVqdsYYol3JfFnXTbPA=="
+        );
+        assert_eq!(event.to_string(), event.display_summary());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_display_summary_truncated_content() {
+        let keys = Keys::generate();
+        let content: String = "é\n".repeat(50);
+        let event = EventBuilder::text_note(content, [])
+            .to_event(&keys)
+            .unwrap();
+        let summary: String = event.display_summary();
+        let (_, preview) = summary.split_once("] ").unwrap();
+        assert_eq!(preview, format!("{}...", "é ".repeat(40)));
+    }
+
+    #[test]
+    fn test_deser_unknown_fields() {
+        let json = r#"{"content":"uRuvYr585B80L6rSJiHocw==?iv=oh6LVqdsYYol3JfFnXTbPA==","created_at":1640839235,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","kind":4,"pubkey":"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[["p","13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"]],"custom":true}"#;
+        #[cfg(feature = "serde-strict")]
+        assert!(Event::from_json(json).is_err());
+        #[cfg(not(feature = "serde-strict"))]
+        assert!(Event::from_json(json).is_ok());
+    }
+
     #[test]
     fn test_tags_deser_without_recommended_relay() {
         // The TAG array has dynamic length because the third element(Recommended relay url) is optional
@@ -629,6 +910,103 @@ mod tests {
         let reserialized_json = event.as_json();
         assert_eq!(json, reserialized_json);
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_event_mentions() {
+        let keys = Keys::generate();
+        let mentioned =
+            PublicKey::from_hex("aa4fc8665f5696e33db7e1a572e3b0f5b3d615837b0f362dcb1c8068b098c7b4")
+                .unwrap();
+        let other =
+            PublicKey::from_hex("68d81165918100b7da43fc28f7d1fc12554466e1115886b9e7bb326f65ec4272")
+                .unwrap();
+
+        // `p` tag
+        let event = EventBuilder::text_note("hello", [Tag::public_key(mentioned)])
+            .to_event(&keys)
+            .unwrap();
+        assert!(event.mentions(&mentioned));
+        assert!(!event.mentions(&other));
+
+        // Inline mention
+        let event = EventBuilder::text_note(
+            "GM nostr:npub14f8usejl26twx0dhuxjh9cas7keav9vr0v8nvtwtrjqx3vycc76qqh9nsy!",
+            [],
+        )
+        .to_event(&keys)
+        .unwrap();
+        assert!(event.mentions(&mentioned));
+        assert!(!event.mentions(&other));
+        assert!(event.mentions_any(&[other, mentioned]));
+        assert!(!event.mentions_any(&[other]));
+        assert!(!event.mentions_any(&[]));
+
+        // Invalid inline mention
+        let event = EventBuilder::text_note("nostr: npub1 nostr:npub1abc", [])
+            .to_event(&keys)
+            .unwrap();
+        assert!(!event.mentions(&mentioned));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_event_reply_to_event() {
+        let keys = Keys::generate();
+        let root =
+            EventId::from_hex("fd40fc62d6349408c5b63d364c1f695b435cc596b58cfaa449519fbc5f2a41a4")
+                .unwrap();
+        let parent =
+            EventId::from_hex("a515bc18a06f0a3561075870f488365e71c5e90aa429a82845e9f7f0d66b6119")
+                .unwrap();
+
+        let marked = |event_id: EventId, marker: Marker| Tag::Event {
+            event_id,
+            relay_url: None,
+            marker: Some(marker),
+        };
+
+        // Marked `reply`
+        let event = EventBuilder::text_note(
+            "reply",
+            [marked(root, Marker::Root), marked(parent, Marker::Reply)],
+        )
+        .to_event(&keys)
+        .unwrap();
+        assert!(event.reply_to_event(&parent));
+        assert!(!event.reply_to_event(&root));
+        assert_eq!(event.root_event_id(), Some(root));
+        assert_eq!(event.reply_event_id(), Some(parent));
+
+        // Direct reply to root
+        let event = EventBuilder::text_note("reply", [marked(root, Marker::Root)])
+            .to_event(&keys)
+            .unwrap();
+        assert!(event.reply_to_event(&root));
+        assert_eq!(event.root_event_id(), Some(root));
+
+        // Marked `mention` only
+        let event = EventBuilder::text_note("mention", [marked(parent, Marker::Mention)])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.root_event_id(), None);
+        assert_eq!(event.reply_event_id(), None);
+
+        // Positional
+        let event = EventBuilder::text_note("reply", [Tag::event(root), Tag::event(parent)])
+            .to_event(&keys)
+            .unwrap();
+        assert!(event.reply_to_event(&parent));
+        assert!(!event.reply_to_event(&root));
+        assert_eq!(event.root_event_id(), Some(root));
+        assert_eq!(event.reply_event_id(), Some(parent));
+
+        // No `e` tags
+        let event = EventBuilder::text_note("note", []).to_event(&keys).unwrap();
+        assert!(!event.reply_to_event(&root));
+        assert_eq!(event.root_event_id(), None);
+        assert_eq!(event.reply_event_id(), None);
+    }
 }
 
 #[cfg(bench)]
@@ -653,4 +1031,28 @@ mod benches {
             black_box(event.as_json());
         });
     }
+
+    #[bench]
+    pub fn write_event_as_json(bh: &mut Bencher) {
+        let json = r#"{"content":"uRuvYr585B80L6rSJiHocw==?iv=oh6LVqdsYYol3JfFnXTbPA==","created_at":1640839235,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","kind":4,"pubkey":"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[["p","13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"]]}"#;
+        let event = Event::from_json(json).unwrap();
+        let mut buf: Vec<u8> = Vec::with_capacity(1024);
+        bh.iter(|| {
+            buf.clear();
+            std::io::Write::write_all(&mut buf, event.as_json().as_bytes()).unwrap();
+            black_box(&buf);
+        });
+    }
+
+    #[bench]
+    pub fn write_event_json(bh: &mut Bencher) {
+        let json = r#"{"content":"uRuvYr585B80L6rSJiHocw==?iv=oh6LVqdsYYol3JfFnXTbPA==","created_at":1640839235,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","kind":4,"pubkey":"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[["p","13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"]]}"#;
+        let event = Event::from_json(json).unwrap();
+        let mut buf: Vec<u8> = Vec::with_capacity(1024);
+        bh.iter(|| {
+            buf.clear();
+            event.write_json(&mut buf).unwrap();
+            black_box(&buf);
+        });
+    }
 }
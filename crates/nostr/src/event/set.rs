@@ -0,0 +1,79 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Event set
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use super::builder::Error;
+use super::builder::EventBuilder;
+#[cfg(feature = "std")]
+use super::Event;
+#[cfg(feature = "std")]
+use crate::Keys;
+
+/// Set of events that must be published together
+///
+/// Some protocols require multiple events to be meaningful (ex. NIP-58 badge definition and award).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventSet {
+    /// Events
+    pub events: Vec<EventBuilder>,
+}
+
+impl EventSet {
+    /// New event set
+    pub fn new<I>(events: I) -> Self
+    where
+        I: IntoIterator<Item = EventBuilder>,
+    {
+        Self {
+            events: events.into_iter().collect(),
+        }
+    }
+
+    /// Add an event
+    pub fn push(mut self, builder: EventBuilder) -> Self {
+        self.events.push(builder);
+        self
+    }
+
+    /// Sign all the events, preserving the order
+    ///
+    /// Fails if any event can't be signed.
+    #[cfg(feature = "std")]
+    pub fn sign_all(self, keys: &Keys) -> Result<Vec<Event>, Error> {
+        self.events.into_iter().map(|b| b.to_event(keys)).collect()
+    }
+}
+
+impl FromIterator<EventBuilder> for EventSet {
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = EventBuilder>,
+    {
+        Self::new(iter)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::Kind;
+
+    #[test]
+    fn test_sign_all() {
+        let keys = Keys::generate();
+        let set = EventSet::new([
+            EventBuilder::new(Kind::TextNote, "first", []),
+            EventBuilder::new(Kind::TextNote, "second", []),
+        ]);
+        let events = set.sign_all(&keys).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].content, "first");
+        assert_eq!(events[1].content, "second");
+        assert!(events.iter().all(|e| e.pubkey == keys.public_key()));
+    }
+}
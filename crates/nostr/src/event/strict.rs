@@ -0,0 +1,350 @@
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Strict event deserialization
+//!
+//! Check the NIP01 structure of the event JSON, before any signature verification.
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/01.md>
+
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::time::Duration;
+
+use serde_json::{Map, Value};
+
+use super::Event;
+use crate::Timestamp;
+
+/// Default max time in the future allowed for the `created_at` (15 min)
+pub const DEFAULT_MAX_FUTURE: Duration = Duration::from_secs(15 * 60);
+
+/// Max kind allowed by NIP01
+pub const MAX_KIND: u64 = 65535;
+
+const FIELDS: [&str; 7] = [
+    "id",
+    "pubkey",
+    "created_at",
+    "kind",
+    "tags",
+    "content",
+    "sig",
+];
+
+/// Strict deserialization error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Error deserializing JSON data
+    Json(String),
+    /// Event isn't a JSON object
+    NotAnObject,
+    /// Missing field
+    MissingField(&'static str),
+    /// Unknown top-level field
+    UnknownField(String),
+    /// `id` isn't a 64 chars lowercase hex string
+    InvalidId,
+    /// `pubkey` isn't a 64 chars lowercase hex string
+    InvalidPublicKey,
+    /// `sig` isn't a 128 chars lowercase hex string
+    InvalidSignature,
+    /// `created_at` isn't an unsigned integer
+    InvalidCreatedAt,
+    /// `created_at` too far in the future
+    CreatedAtTooFarInFuture {
+        /// Event timestamp
+        created_at: Timestamp,
+        /// Max allowed timestamp
+        max: Timestamp,
+    },
+    /// `created_at` too far in the past
+    CreatedAtTooOld {
+        /// Event timestamp
+        created_at: Timestamp,
+        /// Min allowed timestamp
+        min: Timestamp,
+    },
+    /// `kind` isn't an unsigned integer lower or equal to [`MAX_KIND`]
+    InvalidKind,
+    /// `tags` isn't an array of arrays of strings
+    InvalidTags,
+    /// `content` isn't a string
+    InvalidContent,
+    /// Event error
+    Event(super::Error),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "Json: {e}"),
+            Self::NotAnObject => write!(f, "event is not a JSON object"),
+            Self::MissingField(field) => write!(f, "missing field: {field}"),
+            Self::UnknownField(field) => write!(f, "unknown field: {field}"),
+            Self::InvalidId => write!(f, "invalid id"),
+            Self::InvalidPublicKey => write!(f, "invalid pubkey"),
+            Self::InvalidSignature => write!(f, "invalid sig"),
+            Self::InvalidCreatedAt => write!(f, "invalid created_at"),
+            Self::CreatedAtTooFarInFuture { created_at, max } => {
+                write!(f, "created_at too far in the future: {created_at} > {max}")
+            }
+            Self::CreatedAtTooOld { created_at, min } => {
+                write!(f, "created_at too old: {created_at} < {min}")
+            }
+            Self::InvalidKind => write!(f, "invalid kind"),
+            Self::InvalidTags => write!(f, "invalid tags"),
+            Self::InvalidContent => write!(f, "invalid content"),
+            Self::Event(e) => write!(f, "Event: {e}"),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e.to_string())
+    }
+}
+
+impl From<super::Error> for Error {
+    fn from(e: super::Error) -> Self {
+        Self::Event(e)
+    }
+}
+
+/// Strict deserialization options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StrictOptions {
+    structure: bool,
+    max_future: Option<Duration>,
+    max_past: Option<Duration>,
+}
+
+impl Default for StrictOptions {
+    fn default() -> Self {
+        Self {
+            structure: true,
+            max_future: Some(DEFAULT_MAX_FUTURE),
+            max_past: None,
+        }
+    }
+}
+
+impl StrictOptions {
+    /// New default (strict) options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Relaxed options: no checks, same behavior of `Event::from_json`
+    pub fn relaxed() -> Self {
+        Self {
+            structure: false,
+            max_future: None,
+            max_past: None,
+        }
+    }
+
+    /// Check the NIP01 structure of the JSON (default: true)
+    ///
+    /// Hex fields, types, kind range and no extra top-level fields.
+    pub fn structure(mut self, enable: bool) -> Self {
+        self.structure = enable;
+        self
+    }
+
+    /// Max time in the future allowed for the `created_at` (default: [`DEFAULT_MAX_FUTURE`])
+    pub fn max_future(mut self, max: Option<Duration>) -> Self {
+        self.max_future = max;
+        self
+    }
+
+    /// Max time in the past allowed for the `created_at` (default: None)
+    ///
+    /// Some NIPs legitimately backdate events: keep it disabled unless needed.
+    pub fn max_past(mut self, max: Option<Duration>) -> Self {
+        self.max_past = max;
+        self
+    }
+}
+
+#[inline]
+fn is_lowercase_hex(value: &Value, len: usize) -> bool {
+    match value.as_str() {
+        Some(s) => s.len() == len && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')),
+        None => false,
+    }
+}
+
+fn check_structure(map: &Map<String, Value>) -> Result<(), Error> {
+    if let Some(field) = map.keys().find(|k| !FIELDS.contains(&k.as_str())) {
+        return Err(Error::UnknownField(field.clone()));
+    }
+
+    let field = |name: &'static str| map.get(name).ok_or(Error::MissingField(name));
+
+    if !is_lowercase_hex(field("id")?, 64) {
+        return Err(Error::InvalidId);
+    }
+
+    if !is_lowercase_hex(field("pubkey")?, 64) {
+        return Err(Error::InvalidPublicKey);
+    }
+
+    if field("created_at")?.as_u64().is_none() {
+        return Err(Error::InvalidCreatedAt);
+    }
+
+    match field("kind")?.as_u64() {
+        Some(kind) if kind <= MAX_KIND => {}
+        _ => return Err(Error::InvalidKind),
+    }
+
+    let valid_tags: bool = match field("tags")?.as_array() {
+        Some(tags) => tags.iter().all(|tag| match tag.as_array() {
+            Some(values) => values.iter().all(|v| v.is_string()),
+            None => false,
+        }),
+        None => false,
+    };
+    if !valid_tags {
+        return Err(Error::InvalidTags);
+    }
+
+    if !field("content")?.is_string() {
+        return Err(Error::InvalidContent);
+    }
+
+    if !is_lowercase_hex(field("sig")?, 128) {
+        return Err(Error::InvalidSignature);
+    }
+
+    Ok(())
+}
+
+pub(super) fn from_json(json: &[u8], opts: StrictOptions, now: Timestamp) -> Result<Event, Error> {
+    let value: Value = serde_json::from_slice(json)?;
+
+    if opts.structure {
+        match &value {
+            Value::Object(map) => check_structure(map)?,
+            _ => return Err(Error::NotAnObject),
+        }
+    }
+
+    let event: Event = Event::from_value(value)?;
+
+    if let Some(max_future) = opts.max_future {
+        let max: Timestamp = now + max_future;
+        if event.created_at() > max {
+            return Err(Error::CreatedAtTooFarInFuture {
+                created_at: event.created_at(),
+                max,
+            });
+        }
+    }
+
+    if let Some(max_past) = opts.max_past {
+        let min: Timestamp = now - max_past;
+        if event.created_at() < min {
+            return Err(Error::CreatedAtTooOld {
+                created_at: event.created_at(),
+                min,
+            });
+        }
+    }
+
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVENT: &str = r#"{"content":"uRuvYr585B80L6rSJiHocw==?iv=oh6LVqdsYYol3JfFnXTbPA==","created_at":1640839235,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","kind":4,"pubkey":"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[["p","13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"]]}"#;
+    const NOW: u64 = 1640839235;
+
+    fn parse(json: &str, opts: StrictOptions) -> Result<Event, Error> {
+        from_json(json.as_bytes(), opts, Timestamp::from(NOW))
+    }
+
+    fn patch(field: &str, value: Value) -> String {
+        let mut map: Map<String, Value> = serde_json::from_str(EVENT).unwrap();
+        map.insert(field.to_string(), value);
+        Value::Object(map).to_string()
+    }
+
+    #[test]
+    fn test_strict_valid_event() {
+        assert!(parse(EVENT, StrictOptions::new()).is_ok());
+    }
+
+    #[test]
+    fn test_strict_structure() {
+        let opts = StrictOptions::new();
+
+        let upper_id = patch(
+            "id",
+            Value::String(
+                "2BE17AA3031BDCB006F0FCE80C146DEA9C1C0268B0AF2398BB673365C6444D45".to_string(),
+            ),
+        );
+        assert_eq!(parse(&upper_id, opts).unwrap_err(), Error::InvalidId);
+        // Relaxed mode accept it, like `Event::from_json`
+        assert!(parse(&upper_id, StrictOptions::relaxed()).is_ok());
+
+        let short_pubkey = patch("pubkey", Value::String("f86c44".to_string()));
+        assert_eq!(
+            parse(&short_pubkey, opts).unwrap_err(),
+            Error::InvalidPublicKey
+        );
+
+        let kind = patch("kind", Value::from(65536));
+        assert_eq!(parse(&kind, opts).unwrap_err(), Error::InvalidKind);
+
+        let tags = patch("tags", serde_json::json!([["p", 1]]));
+        assert_eq!(parse(&tags, opts).unwrap_err(), Error::InvalidTags);
+
+        let content = patch("content", Value::Null);
+        assert_eq!(parse(&content, opts).unwrap_err(), Error::InvalidContent);
+
+        let created_at = patch("created_at", Value::from(-1));
+        assert_eq!(
+            parse(&created_at, opts).unwrap_err(),
+            Error::InvalidCreatedAt
+        );
+
+        let extra = patch("custom", Value::Bool(true));
+        assert_eq!(
+            parse(&extra, opts).unwrap_err(),
+            Error::UnknownField(String::from("custom"))
+        );
+
+        assert_eq!(parse("[]", opts).unwrap_err(), Error::NotAnObject);
+    }
+
+    #[test]
+    fn test_strict_created_at_window() {
+        let opts = StrictOptions::new();
+
+        // Event in the future
+        let now = Timestamp::from(NOW) - DEFAULT_MAX_FUTURE - Duration::from_secs(1);
+        assert!(matches!(
+            from_json(EVENT.as_bytes(), opts, now).unwrap_err(),
+            Error::CreatedAtTooFarInFuture { .. }
+        ));
+        assert!(from_json(EVENT.as_bytes(), opts.max_future(None), now).is_ok());
+
+        // Backdated event
+        let now = Timestamp::from(NOW) + Duration::from_secs(3600);
+        assert!(from_json(EVENT.as_bytes(), opts, now).is_ok());
+        let opts = opts.max_past(Some(Duration::from_secs(60)));
+        assert!(matches!(
+            from_json(EVENT.as_bytes(), opts, now).unwrap_err(),
+            Error::CreatedAtTooOld { .. }
+        ));
+    }
+}
@@ -71,6 +71,8 @@ pub enum Error {
     InvalidHttpMethod(String),
     /// Invalid Relay Metadata
     InvalidRelayMetadata(String),
+    /// JSON error
+    Json(serde_json::Error),
 }
 
 #[cfg(feature = "std")]
@@ -97,6 +99,7 @@ impl fmt::Display for Error {
             Self::InvalidImageDimensions => write!(f, "Invalid image dimensions"),
             Self::InvalidHttpMethod(m) => write!(f, "Invalid HTTP method: {m}"),
             Self::InvalidRelayMetadata(s) => write!(f, "Invalid relay metadata: {s}"),
+            Self::Json(e) => write!(f, "Json: {e}"),
         }
     }
 }
@@ -161,6 +164,12 @@ impl From<crate::event::Error> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
 /// Marker
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Marker {
@@ -1013,6 +1022,12 @@ impl Tag {
         )
     }
 
+    /// Parse [`Tag`] from JSON array string (i.e. `["p", "<public-key>"]`)
+    pub fn try_from_str(tag: &str) -> Result<Self, Error> {
+        let tag: Vec<String> = serde_json::from_str(tag)?;
+        Self::parse(&tag)
+    }
+
     /// Get [`Tag`] as string vector
     ///
     /// Internally clone tag and convert it to `Vec<String>`. To avoid tag clone, use `Tag::to_vec`.
@@ -1450,6 +1465,10 @@ impl<'de> Deserialize<'de> for Tag {
     }
 }
 
+impl JsonUtil for Tag {
+    type Err = Error;
+}
+
 /// Supported external identity providers
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ExternalIdentity {
@@ -2255,6 +2274,46 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_tag_try_from_str() {
+        let json = r#"["p","13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"]"#;
+        let tag = Tag::try_from_str(json).unwrap();
+        assert_eq!(
+            tag,
+            Tag::public_key(
+                PublicKey::from_hex(
+                    "13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"
+                )
+                .unwrap()
+            )
+        );
+        assert_eq!(tag.as_json(), json);
+
+        assert!(matches!(
+            Tag::try_from_str(r#"["p", 1]"#).unwrap_err(),
+            Error::Json(..)
+        ));
+        assert!(matches!(
+            Tag::try_from_str("[]").unwrap_err(),
+            Error::KindNotFound
+        ));
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_tag_json_roundtrip(
+            kind in "(e|p|a|d|t|r|k|relays|expiration|amount|nonce|[a-zA-Z0-9_-]{1,10})",
+            values in proptest::collection::vec(".*", 0..5),
+        ) {
+            let mut tag: Vec<String> = vec![kind];
+            tag.extend(values);
+
+            if let Ok(tag) = Tag::parse(&tag) {
+                proptest::prop_assert_eq!(Tag::try_from_str(&tag.as_json()).unwrap(), tag);
+            }
+        }
+    }
 }
 
 #[cfg(bench)]
@@ -0,0 +1,185 @@
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Tags collection
+
+use alloc::vec::{self, Vec};
+use core::ops::{Deref, DerefMut};
+use core::slice;
+
+use serde::{Deserialize, Serialize};
+
+use super::tag::{Tag, TagKind};
+use crate::{EventId, PublicKey};
+
+/// Tags collection
+///
+/// Wrapper of `Vec<Tag>` with lookup helpers.
+/// Derefs to `Vec<Tag>`, so existing code using the `Vec<Tag>` API keeps working:
+/// use [`Tags::to_vec`] (or `Vec::from`) where an owned `Vec<Tag>` is required.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Tags(Vec<Tag>);
+
+impl Tags {
+    /// Construct new tags collection
+    #[inline]
+    pub fn new(tags: Vec<Tag>) -> Self {
+        Self(tags)
+    }
+
+    /// Get first tag with the identifier (ex. `p`, `e`, `d`, `client`)
+    pub fn get_first(&self, identifier: &str) -> Option<&Tag> {
+        let kind: TagKind = TagKind::from(identifier);
+        self.0.iter().find(|t| t.kind() == kind)
+    }
+
+    /// Get all tags with the identifier (ex. `p`, `e`, `d`, `client`)
+    pub fn get_all<'a>(&'a self, identifier: &str) -> impl Iterator<Item = &'a Tag> {
+        let kind: TagKind = TagKind::from(identifier);
+        self.0.iter().filter(move |t| t.kind() == kind)
+    }
+
+    /// Check if contains a tag with the identifier (ex. `p`, `e`, `d`, `client`)
+    #[inline]
+    pub fn contains(&self, identifier: &str) -> bool {
+        self.get_first(identifier).is_some()
+    }
+
+    /// Extract event IDs (`e` tags)
+    ///
+    /// **This method extract ONLY `Tag::Event`**
+    pub fn event_ids(&self) -> Vec<EventId> {
+        self.0
+            .iter()
+            .filter_map(|t| match t {
+                Tag::Event { event_id, .. } => Some(*event_id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Extract public keys (`p` tags)
+    ///
+    /// **This method extract ONLY `Tag::PublicKey`**
+    pub fn pubkeys(&self) -> Vec<PublicKey> {
+        self.0
+            .iter()
+            .filter_map(|t| match t {
+                Tag::PublicKey { public_key, .. } => Some(*public_key),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Clone into `Vec<Tag>`
+    #[inline]
+    pub fn to_vec(&self) -> Vec<Tag> {
+        self.0.clone()
+    }
+
+    /// Get inner `Vec<Tag>`
+    #[inline]
+    pub fn into_inner(self) -> Vec<Tag> {
+        self.0
+    }
+}
+
+impl Deref for Tags {
+    type Target = Vec<Tag>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Tags {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl AsRef<[Tag]> for Tags {
+    fn as_ref(&self) -> &[Tag] {
+        &self.0
+    }
+}
+
+impl From<Vec<Tag>> for Tags {
+    fn from(tags: Vec<Tag>) -> Self {
+        Self(tags)
+    }
+}
+
+impl From<Tags> for Vec<Tag> {
+    fn from(tags: Tags) -> Self {
+        tags.0
+    }
+}
+
+impl FromIterator<Tag> for Tags {
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = Tag>,
+    {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Tags {
+    type Item = Tag;
+    type IntoIter = vec::IntoIter<Tag>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Tags {
+    type Item = &'a Tag;
+    type IntoIter = slice::Iter<'a, Tag>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use core::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_tags_lookup() {
+        let public_key =
+            PublicKey::from_str("b2d670de53b27691c0c3400225b65c35a26d06093bcc41f48ffc71e0907f9d4a")
+                .unwrap();
+        let event_id =
+            EventId::from_hex("2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45")
+                .unwrap();
+        let tags: Tags = vec![
+            Tag::public_key(public_key),
+            Tag::event(event_id),
+            Tag::Hashtag(String::from("nostr")),
+            Tag::Hashtag(String::from("rust")),
+        ]
+        .into();
+
+        assert!(tags.contains("p"));
+        assert!(!tags.contains("d"));
+        assert_eq!(
+            tags.get_first("t"),
+            Some(&Tag::Hashtag(String::from("nostr")))
+        );
+        assert_eq!(tags.get_all("t").count(), 2);
+        assert_eq!(tags.event_ids(), vec![event_id]);
+        assert_eq!(tags.pubkeys(), vec![public_key]);
+
+        // Serialized as a plain array
+        let json: String = serde_json::to_string(&tags).unwrap();
+        let vec: Vec<Tag> = serde_json::from_str(&json).unwrap();
+        assert_eq!(Tags::from(vec), tags);
+    }
+}
@@ -172,7 +172,7 @@ impl From<Event> for UnsignedEvent {
             pubkey: event.pubkey,
             created_at: event.created_at,
             kind: event.kind,
-            tags: event.tags.clone(),
+            tags: event.tags.to_vec(),
             content: event.content.clone(),
         }
     }
@@ -5,6 +5,7 @@
 
 //! Keys
 
+use alloc::vec::Vec;
 use core::fmt;
 #[cfg(feature = "std")]
 use core::str::FromStr;
@@ -22,8 +23,10 @@ pub mod vanity;
 
 pub use self::public_key::PublicKey;
 pub use self::secret_key::SecretKey;
+use crate::event::unsigned::{self, UnsignedEvent};
 #[cfg(feature = "std")]
 use crate::SECP256K1;
+use crate::{Event, EventId};
 
 /// [`Keys`] error
 #[derive(Debug, Eq, PartialEq)]
@@ -34,6 +37,8 @@ pub enum Error {
     InvalidPublicKey,
     /// Secret key missing
     SkMissing,
+    /// Invalid secret key length (expected 32 bytes)
+    InvalidLength,
     /// Unsupported char
     InvalidChar(char),
     /// Secp256k1 error
@@ -49,6 +54,7 @@ impl fmt::Display for Error {
             Self::InvalidSecretKey => write!(f, "Invalid secret key"),
             Self::InvalidPublicKey => write!(f, "Invalid public key"),
             Self::SkMissing => write!(f, "Secret key missing"),
+            Self::InvalidLength => write!(f, "Invalid secret key length: expected 32 bytes"),
             Self::InvalidChar(c) => write!(f, "Unsupported char: {c}"),
             Self::Secp256k1(e) => write!(f, "Secp256k1: {e}"),
         }
@@ -84,6 +90,18 @@ impl Keys {
         Self::parse_with_ctx(&SECP256K1, secret_key)
     }
 
+    /// Initialize from **secret key** bytes
+    pub fn from_secret_bytes(bytes: &[u8; 32]) -> Result<Self, Error> {
+        Self::from_secret_bytes_with_ctx(&SECP256K1, bytes)
+    }
+
+    /// Initialize from **secret key** slice
+    ///
+    /// Return [`Error::InvalidLength`] if the slice isn't 32 bytes long.
+    pub fn from_secret_slice(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_secret_slice_with_ctx(&SECP256K1, bytes)
+    }
+
     /// Generate new random [`Keys`]
     pub fn generate() -> Self {
         Self::generate_with_ctx(&SECP256K1, &mut OsRng)
@@ -111,6 +129,31 @@ impl Keys {
     pub fn sign_schnorr(&self, message: &Message) -> Result<Signature, Error> {
         self.sign_schnorr_with_ctx(&SECP256K1, message, &mut OsRng)
     }
+
+    /// Sign a batch of [`UnsignedEvent`], building the keypair only once
+    ///
+    /// Fail if the public key of an event doesn't match these keys.
+    /// The gain over [`UnsignedEvent::sign`] is relevant only for keys without a cached keypair
+    /// (see [`Keys::generate_without_keypair`]).
+    pub fn sign_events(&self, events: Vec<UnsignedEvent>) -> Result<Vec<Event>, unsigned::Error> {
+        self.sign_events_with_ctx(&SECP256K1, &mut OsRng, events)
+    }
+
+    /// Sign a batch of 32-byte digests, building the keypair only once
+    pub fn sign_digest_batch(&self, digests: &[[u8; 32]]) -> Result<Vec<Signature>, Error> {
+        self.sign_digest_batch_with_ctx(&SECP256K1, &mut OsRng, digests)
+    }
+
+    /// Sign each [`EventId`] with its own [`Keys`]
+    pub fn multi_sign(messages: &[(EventId, &Keys)]) -> Result<Vec<(EventId, Signature)>, Error> {
+        messages
+            .iter()
+            .map(|(id, keys)| {
+                let message: Message = Message::from_digest(id.to_bytes());
+                Ok((*id, keys.sign_schnorr(&message)?))
+            })
+            .collect()
+    }
 }
 
 impl Keys {
@@ -139,6 +182,29 @@ impl Keys {
         Ok(Self::new_with_ctx(secp, secret_key))
     }
 
+    /// Initialize from **secret key** bytes
+    pub fn from_secret_bytes_with_ctx<C>(
+        secp: &Secp256k1<C>,
+        bytes: &[u8; 32],
+    ) -> Result<Self, Error>
+    where
+        C: Signing,
+    {
+        let secret_key: SecretKey = SecretKey::from_slice(bytes)?;
+        Ok(Self::new_with_ctx(secp, secret_key))
+    }
+
+    /// Initialize from **secret key** slice
+    ///
+    /// Return [`Error::InvalidLength`] if the slice isn't 32 bytes long.
+    pub fn from_secret_slice_with_ctx<C>(secp: &Secp256k1<C>, bytes: &[u8]) -> Result<Self, Error>
+    where
+        C: Signing,
+    {
+        let bytes: &[u8; 32] = bytes.try_into().map_err(|_| Error::InvalidLength)?;
+        Self::from_secret_bytes_with_ctx(secp, bytes)
+    }
+
     /// Initialize with public key only (no secret key).
     pub fn from_public_key(public_key: PublicKey) -> Self {
         Self {
@@ -217,6 +283,62 @@ impl Keys {
         let keypair: &Keypair = &self.key_pair(secp)?;
         Ok(secp.sign_schnorr_with_rng(message, keypair, rng))
     }
+
+    /// Sign a batch of [`UnsignedEvent`], building the keypair only once
+    ///
+    /// Fail if the public key of an event doesn't match these keys.
+    pub fn sign_events_with_ctx<C, R>(
+        &self,
+        secp: &Secp256k1<C>,
+        rng: &mut R,
+        events: Vec<UnsignedEvent>,
+    ) -> Result<Vec<Event>, unsigned::Error>
+    where
+        C: Signing,
+        R: Rng + CryptoRng,
+    {
+        let keypair: Keypair = self.key_pair(secp)?;
+        events
+            .into_iter()
+            .map(|unsigned| {
+                if unsigned.pubkey != self.public_key {
+                    return Err(unsigned::Error::Key(Error::InvalidPublicKey));
+                }
+                let message: Message = Message::from_digest(unsigned.id.to_bytes());
+                let sig: Signature = secp.sign_schnorr_with_rng(&message, &keypair, rng);
+                Ok(Event::new(
+                    unsigned.id,
+                    unsigned.pubkey,
+                    unsigned.created_at,
+                    unsigned.kind,
+                    unsigned.tags,
+                    unsigned.content,
+                    sig,
+                ))
+            })
+            .collect()
+    }
+
+    /// Sign a batch of 32-byte digests, building the keypair only once
+    pub fn sign_digest_batch_with_ctx<C, R>(
+        &self,
+        secp: &Secp256k1<C>,
+        rng: &mut R,
+        digests: &[[u8; 32]],
+    ) -> Result<Vec<Signature>, Error>
+    where
+        C: Signing,
+        R: Rng + CryptoRng,
+    {
+        let keypair: Keypair = self.key_pair(secp)?;
+        Ok(digests
+            .iter()
+            .map(|digest| {
+                let message: Message = Message::from_digest(*digest);
+                secp.sign_schnorr_with_rng(&message, &keypair, rng)
+            })
+            .collect())
+    }
 }
 
 #[cfg(feature = "std")]
@@ -234,3 +356,112 @@ impl Drop for Keys {
         self.secret_key = None;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET_KEY_HEX: &str = "6b911fd37cdf5c81d4c0adb1ab7fa822ed253ab0ad9aa18d77257c88b29b718e";
+
+    #[test]
+    fn test_keys_from_secret_bytes() {
+        let keys = Keys::parse(SECRET_KEY_HEX).unwrap();
+        let bytes: [u8; 32] = keys.secret_key().unwrap().to_secret_bytes();
+        assert_eq!(Keys::from_secret_bytes(&bytes).unwrap(), keys);
+        assert_eq!(Keys::from_secret_slice(&bytes).unwrap(), keys);
+    }
+
+    #[test]
+    fn test_keys_from_secret_slice_invalid_length() {
+        assert_eq!(
+            Keys::from_secret_slice(&[1u8; 31]).unwrap_err(),
+            Error::InvalidLength
+        );
+        assert_eq!(
+            Keys::from_secret_slice(&[1u8; 33]).unwrap_err(),
+            Error::InvalidLength
+        );
+    }
+
+    #[test]
+    fn test_keys_from_secret_bytes_invalid() {
+        assert!(Keys::from_secret_bytes(&[0u8; 32]).is_err());
+    }
+
+    #[test]
+    fn test_sign_events() {
+        let keys = Keys::parse(SECRET_KEY_HEX).unwrap();
+        let unsigned: Vec<UnsignedEvent> = (0..3)
+            .map(|i| {
+                crate::EventBuilder::text_note(i.to_string(), [])
+                    .to_unsigned_event(keys.public_key())
+            })
+            .collect();
+        let events = keys.sign_events(unsigned.clone()).unwrap();
+        assert_eq!(events.len(), 3);
+        for (event, unsigned) in events.iter().zip(unsigned.iter()) {
+            assert_eq!(event.id, unsigned.id);
+            assert!(event.verify().is_ok());
+        }
+
+        // Public key mismatch
+        let other = Keys::generate();
+        let unsigned = crate::EventBuilder::text_note("", []).to_unsigned_event(other.public_key());
+        assert!(keys.sign_events(vec![unsigned]).is_err());
+    }
+
+    #[test]
+    fn test_sign_digest_batch() {
+        let keys = Keys::parse(SECRET_KEY_HEX).unwrap();
+        let digests: Vec<[u8; 32]> = (0..3u8).map(|i| [i; 32]).collect();
+        let sigs = keys.sign_digest_batch(&digests).unwrap();
+        for (digest, sig) in digests.iter().zip(sigs.iter()) {
+            let message = Message::from_digest(*digest);
+            assert!(SECP256K1
+                .verify_schnorr(sig, &message, &keys.public_key())
+                .is_ok());
+        }
+
+        let other = Keys::generate();
+        let id = EventId::from_slice(&[1; 32]).unwrap();
+        let sigs = Keys::multi_sign(&[(id, &keys), (id, &other)]).unwrap();
+        let message = Message::from_digest(id.to_bytes());
+        assert!(SECP256K1
+            .verify_schnorr(&sigs[0].1, &message, &keys.public_key())
+            .is_ok());
+        assert!(SECP256K1
+            .verify_schnorr(&sigs[1].1, &message, &other.public_key())
+            .is_ok());
+    }
+}
+
+#[cfg(bench)]
+mod benches {
+    use test::{black_box, Bencher};
+
+    use super::*;
+
+    fn digests() -> Vec<[u8; 32]> {
+        (0..100u8).map(|i| [i; 32]).collect()
+    }
+
+    #[bench]
+    pub fn sign_digest_individual_100(bh: &mut Bencher) {
+        let keys = Keys::generate_without_keypair(&mut OsRng);
+        let digests = digests();
+        bh.iter(|| {
+            for digest in digests.iter() {
+                black_box(keys.sign_schnorr(&Message::from_digest(*digest))).unwrap();
+            }
+        });
+    }
+
+    #[bench]
+    pub fn sign_digest_batch_100(bh: &mut Bencher) {
+        let keys = Keys::generate_without_keypair(&mut OsRng);
+        let digests = digests();
+        bh.iter(|| {
+            black_box(keys.sign_digest_batch(&digests)).unwrap();
+        });
+    }
+}
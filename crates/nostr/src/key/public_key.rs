@@ -13,7 +13,7 @@ use bitcoin::secp256k1::XOnlyPublicKey;
 use serde::{Deserialize, Deserializer, Serialize};
 
 use super::Error;
-use crate::nips::nip19::FromBech32;
+use crate::nips::nip19::{self, FromBech32, ToBech32, PREFIX_BECH32_PUBLIC_KEY};
 use crate::nips::nip21::NostrURI;
 
 /// Public Key
@@ -87,6 +87,23 @@ impl PublicKey {
     pub fn to_bytes(&self) -> [u8; 32] {
         self.inner.serialize()
     }
+
+    /// Get shortened `bech32` public key (i.e. `npub1abc…xyz`)
+    ///
+    /// The HRP is preserved and `chars` characters are kept at both ends of the data part.
+    pub fn to_short_bech32(&self, chars: usize) -> Result<String, nip19::Error> {
+        let bech32: String = self.to_bech32()?;
+        Ok(nip19::shorten_bech32(&bech32, chars))
+    }
+
+    /// Check if public key starts with `prefix` (`hex` or `npub` bech32)
+    ///
+    /// An empty prefix not match anything.
+    pub fn matches_prefix(&self, prefix: &str) -> bool {
+        nip19::matches_prefix(prefix, PREFIX_BECH32_PUBLIC_KEY, &self.to_hex(), || {
+            self.to_bech32()
+        })
+    }
 }
 
 impl FromStr for PublicKey {
@@ -132,6 +149,31 @@ mod tests {
             "aa4fc8665f5696e33db7e1a572e3b0f5b3d615837b0f362dcb1c8068b098c7b4"
         );
     }
+
+    #[test]
+    fn test_public_key_short_bech32() {
+        let public_key =
+            PublicKey::from_hex("aa4fc8665f5696e33db7e1a572e3b0f5b3d615837b0f362dcb1c8068b098c7b4")
+                .unwrap();
+        assert_eq!(public_key.to_short_bech32(4).unwrap(), "npub14f8u…9nsy");
+        assert_eq!(
+            public_key.to_short_bech32(100).unwrap(),
+            "npub14f8usejl26twx0dhuxjh9cas7keav9vr0v8nvtwtrjqx3vycc76qqh9nsy"
+        );
+    }
+
+    #[test]
+    fn test_public_key_matches_prefix() {
+        let public_key =
+            PublicKey::from_hex("aa4fc8665f5696e33db7e1a572e3b0f5b3d615837b0f362dcb1c8068b098c7b4")
+                .unwrap();
+        assert!(public_key.matches_prefix("aa4fc8"));
+        assert!(public_key.matches_prefix("AA4FC8"));
+        assert!(public_key.matches_prefix("npub14f8use"));
+        assert!(!public_key.matches_prefix("aa4fc9"));
+        assert!(!public_key.matches_prefix("npub14f8usf"));
+        assert!(!public_key.matches_prefix(""));
+    }
 }
 
 #[cfg(bench)]
@@ -32,7 +32,7 @@ extern crate alloc;
 extern crate serde;
 
 #[doc(hidden)]
-#[cfg(any(feature = "nip04", feature = "nip44"))]
+#[cfg(any(feature = "nip04", feature = "nip44", feature = "nip98"))]
 pub use base64;
 #[doc(hidden)]
 #[cfg(feature = "nip06")]
@@ -47,6 +47,8 @@ pub mod key;
 pub mod message;
 pub mod nips;
 pub mod prelude;
+#[cfg(all(feature = "std", any(test, feature = "test-utils")))]
+pub mod test_utils;
 pub mod types;
 pub mod util;
 
@@ -55,18 +57,21 @@ pub use self::event::tag::{
     TagKind,
 };
 pub use self::event::{
-    Event, EventBuilder, EventId, Kind, MissingPartialEvent, PartialEvent, UnsignedEvent,
+    Event, EventBuilder, EventId, EventSet, Kind, LintSeverity, LintWarning, MissingPartialEvent,
+    PartialEvent, StrictOptions, Tags, TextNoteBuilder, UnsignedEvent,
 };
 pub use self::key::{Keys, PublicKey, SecretKey};
-pub use self::message::{ClientMessage, RawRelayMessage, RelayMessage, SubscriptionId};
+pub use self::message::{
+    ClientMessage, MachineReadablePrefix, RawRelayMessage, RelayMessage, SubscriptionId,
+};
 pub use self::nips::nip19::{FromBech32, ToBech32};
 pub use self::types::{
     Alphabet, Contact, Filter, GenericTagValue, Metadata, SingleLetterTag, Timestamp, TryIntoUrl,
-    UncheckedUrl, Url,
+    UncheckedUrl, Url, ZapTarget,
 };
-pub use self::util::JsonUtil;
 #[cfg(feature = "std")]
 pub use self::util::SECP256K1;
+pub use self::util::{FromRelayStr, JsonUtil};
 
 /// Result
 #[cfg(feature = "std")]
@@ -82,6 +82,64 @@ impl<'de> Deserialize<'de> for NegentropyErrorCode {
     }
 }
 
+/// Machine-readable prefix of `OK` and `CLOSED` messages
+///
+/// <https://github.com/nostr-protocol/nips/blob/master/01.md>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MachineReadablePrefix {
+    /// `duplicate`
+    Duplicate,
+    /// `pow`
+    Pow,
+    /// `blocked`
+    Blocked,
+    /// `rate-limited`
+    RateLimited,
+    /// `invalid`
+    Invalid,
+    /// `error`
+    Error,
+    /// `auth-required` (NIP42)
+    AuthRequired,
+    /// `restricted` (NIP42)
+    Restricted,
+}
+
+impl fmt::Display for MachineReadablePrefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Duplicate => write!(f, "duplicate"),
+            Self::Pow => write!(f, "pow"),
+            Self::Blocked => write!(f, "blocked"),
+            Self::RateLimited => write!(f, "rate-limited"),
+            Self::Invalid => write!(f, "invalid"),
+            Self::Error => write!(f, "error"),
+            Self::AuthRequired => write!(f, "auth-required"),
+            Self::Restricted => write!(f, "restricted"),
+        }
+    }
+}
+
+impl MachineReadablePrefix {
+    /// Parse the prefix of a message (ex. `rate-limited: slow down`)
+    ///
+    /// Return `None` if the message hasn't a known prefix.
+    pub fn parse(message: &str) -> Option<Self> {
+        let (prefix, _) = message.split_once(':')?;
+        match prefix.trim() {
+            "duplicate" => Some(Self::Duplicate),
+            "pow" => Some(Self::Pow),
+            "blocked" => Some(Self::Blocked),
+            "rate-limited" => Some(Self::RateLimited),
+            "invalid" => Some(Self::Invalid),
+            "error" => Some(Self::Error),
+            "auth-required" => Some(Self::AuthRequired),
+            "restricted" => Some(Self::Restricted),
+            _ => None,
+        }
+    }
+}
+
 /// Messages sent by relays, received by clients
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum RelayMessage {
@@ -542,4 +600,18 @@ mod tests {
 
         assert_eq!(msg, RelayMessage::from_json(SAMPLE_EVENT).unwrap());
     }
+
+    #[test]
+    fn test_machine_readable_prefix() {
+        assert_eq!(
+            MachineReadablePrefix::parse("rate-limited: slow down"),
+            Some(MachineReadablePrefix::RateLimited)
+        );
+        assert_eq!(
+            MachineReadablePrefix::parse("auth-required:we only serve authenticated users"),
+            Some(MachineReadablePrefix::AuthRequired)
+        );
+        assert_eq!(MachineReadablePrefix::parse("hello: world"), None);
+        assert_eq!(MachineReadablePrefix::parse("error"), None);
+    }
 }
@@ -15,6 +15,7 @@ pub mod nip05;
 pub mod nip06;
 #[cfg(all(feature = "nip07", target_arch = "wasm32"))]
 pub mod nip07;
+pub mod nip10;
 #[cfg(all(feature = "std", feature = "nip11"))]
 pub mod nip11;
 pub mod nip13;
@@ -39,6 +40,9 @@ pub mod nip58;
 #[cfg(feature = "nip59")]
 pub mod nip59;
 pub mod nip65;
+pub mod nip77;
+pub mod nip89;
 pub mod nip90;
 pub mod nip94;
 pub mod nip98;
+pub mod nip99;
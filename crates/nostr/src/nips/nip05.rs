@@ -17,8 +17,12 @@ use reqwest::Proxy;
 use serde_json::Value;
 use url::Url;
 
-use crate::nips::nip19::Nip19Profile;
-use crate::{key, PublicKey};
+use crate::nips::nip19::{Nip19Profile, ToBech32};
+use crate::util::http::{self, RetryPolicy};
+use crate::{key, Event, Kind, PublicKey, TagKind};
+
+/// Identifier (`d` tag) of the identity verification request events
+pub const IDENTITY_VERIFICATION_IDENTIFIER: &str = "identity-verification";
 
 /// `NIP05` error
 #[derive(Debug)]
@@ -29,10 +33,18 @@ pub enum Error {
     ImpossibleToVerify,
     /// Reqwest error
     Reqwest(reqwest::Error),
+    /// HTTP error (all attempts failed)
+    Http(http::Error),
     /// Error deserializing JSON data
     Json(serde_json::Error),
     /// Keys error
     Keys(key::Error),
+    /// Not an identity verification request event
+    InvalidVerificationRequest,
+    /// Missing tag in the identity verification request
+    MissingTag(&'static str),
+    /// Invalid proof URL
+    InvalidProofUrl(url::ParseError),
 }
 
 #[cfg(feature = "std")]
@@ -44,8 +56,12 @@ impl fmt::Display for Error {
             Self::InvalidFormat => write!(f, "invalid format"),
             Self::ImpossibleToVerify => write!(f, "impossible to verify"),
             Self::Reqwest(e) => write!(f, "{e}"),
+            Self::Http(e) => write!(f, "{e}"),
             Self::Json(e) => write!(f, "impossible to deserialize NIP05 data: {e}"),
             Self::Keys(e) => write!(f, "{e}"),
+            Self::InvalidVerificationRequest => write!(f, "not an identity verification request"),
+            Self::MissingTag(tag) => write!(f, "missing `{tag}` tag"),
+            Self::InvalidProofUrl(e) => write!(f, "invalid proof URL: {e}"),
         }
     }
 }
@@ -56,6 +72,12 @@ impl From<reqwest::Error> for Error {
     }
 }
 
+impl From<http::Error> for Error {
+    fn from(e: http::Error) -> Self {
+        Self::Http(e)
+    }
+}
+
 impl From<serde_json::Error> for Error {
     fn from(e: serde_json::Error) -> Self {
         Self::Json(e)
@@ -119,21 +141,13 @@ where
     Err(Error::ImpossibleToVerify)
 }
 
-/// Verify NIP05
-///
-/// **Proxy is ignored for WASM targets!**
-pub async fn verify<S>(
-    public_key: PublicKey,
-    nip05: S,
+async fn get_text(
+    url: &str,
     _proxy: Option<SocketAddr>,
-) -> Result<(), Error>
-where
-    S: AsRef<str>,
-{
+    policy: &RetryPolicy,
+) -> Result<String, Error> {
     use reqwest::Client;
 
-    let (url, name) = compose_url(nip05)?;
-
     #[cfg(not(target_arch = "wasm32"))]
     let client: Client = {
         let mut builder = Client::builder();
@@ -147,8 +161,74 @@ where
     #[cfg(target_arch = "wasm32")]
     let client: Client = Client::new();
 
-    let res = client.get(url).send().await?;
-    let json: Value = serde_json::from_str(&res.text().await?)?;
+    let res = http::send(policy, || client.get(url).send()).await?;
+    Ok(res.text().await?)
+}
+
+async fn get_json(
+    url: String,
+    proxy: Option<SocketAddr>,
+    policy: &RetryPolicy,
+) -> Result<Value, Error> {
+    let text: String = get_text(&url, proxy, policy).await?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(feature = "blocking")]
+fn get_json_blocking(
+    url: String,
+    proxy: Option<SocketAddr>,
+    policy: &RetryPolicy,
+) -> Result<Value, Error> {
+    use reqwest::blocking::Client;
+
+    let mut builder = Client::builder();
+    if let Some(proxy) = proxy {
+        let proxy = format!("socks5h://{proxy}");
+        builder = builder.proxy(Proxy::all(proxy)?);
+    }
+    let client: Client = builder.build()?;
+    let res = http::send_blocking(policy, |timeout| {
+        let req = client.get(&url);
+        match timeout {
+            Some(timeout) => req.timeout(timeout).send(),
+            None => req.send(),
+        }
+    })?;
+    Ok(serde_json::from_str(&res.text()?)?)
+}
+
+/// Verify NIP05
+///
+/// Use the default [`RetryPolicy`].
+///
+/// **Proxy is ignored for WASM targets!**
+pub async fn verify<S>(
+    public_key: PublicKey,
+    nip05: S,
+    proxy: Option<SocketAddr>,
+) -> Result<(), Error>
+where
+    S: AsRef<str>,
+{
+    verify_with_policy(public_key, nip05, proxy, &RetryPolicy::default()).await
+}
+
+/// Verify NIP05 with a custom [`RetryPolicy`]
+///
+/// **Proxy is ignored for WASM targets!**
+pub async fn verify_with_policy<S>(
+    public_key: PublicKey,
+    nip05: S,
+    proxy: Option<SocketAddr>,
+    policy: &RetryPolicy,
+) -> Result<(), Error>
+where
+    S: AsRef<str>,
+{
+    let (url, name) = compose_url(nip05)?;
+    let json: Value = get_json(url, proxy, policy).await?;
     verify_json(public_key, json, name)
 }
 
@@ -163,46 +243,36 @@ pub fn verify_blocking<S>(
 where
     S: AsRef<str>,
 {
-    use reqwest::blocking::Client;
-
     let (url, name) = compose_url(nip05)?;
-    let mut builder = Client::builder();
-    if let Some(proxy) = proxy {
-        let proxy = format!("socks5h://{proxy}");
-        builder = builder.proxy(Proxy::all(proxy)?);
-    }
-    let client: Client = builder.build()?;
-    let res = client.get(url).send()?;
-    let json: Value = serde_json::from_str(&res.text()?)?;
+    let json: Value = get_json_blocking(url, proxy, &RetryPolicy::default())?;
     verify_json(public_key, json, name)
 }
 
 /// Get [Nip19Profile] from NIP05 (public key and list of advertised relays)
 ///
+/// Use the default [`RetryPolicy`].
+///
 /// **Proxy is ignored for WASM targets!**
-pub async fn get_profile<S>(nip05: S, _proxy: Option<SocketAddr>) -> Result<Nip19Profile, Error>
+pub async fn get_profile<S>(nip05: S, proxy: Option<SocketAddr>) -> Result<Nip19Profile, Error>
 where
     S: AsRef<str>,
 {
-    use reqwest::Client;
+    get_profile_with_policy(nip05, proxy, &RetryPolicy::default()).await
+}
 
+/// Get [Nip19Profile] from NIP05 with a custom [`RetryPolicy`]
+///
+/// **Proxy is ignored for WASM targets!**
+pub async fn get_profile_with_policy<S>(
+    nip05: S,
+    proxy: Option<SocketAddr>,
+    policy: &RetryPolicy,
+) -> Result<Nip19Profile, Error>
+where
+    S: AsRef<str>,
+{
     let (url, name) = compose_url(nip05)?;
-
-    #[cfg(not(target_arch = "wasm32"))]
-    let client: Client = {
-        let mut builder = Client::builder();
-        if let Some(proxy) = _proxy {
-            let proxy = format!("socks5h://{proxy}");
-            builder = builder.proxy(Proxy::all(proxy)?);
-        }
-        builder.build()?
-    };
-
-    #[cfg(target_arch = "wasm32")]
-    let client: Client = Client::new();
-
-    let res = client.get(url).send().await?;
-    let json: Value = serde_json::from_str(&res.text().await?)?;
+    let json: Value = get_json(url, proxy, policy).await?;
 
     let public_key = get_key_from_json(json.clone(), name).ok_or(Error::ImpossibleToVerify)?;
     let relays = get_relays_from_json(json, public_key);
@@ -217,20 +287,121 @@ pub fn get_profile_blocking<S>(nip05: S, proxy: Option<SocketAddr>) -> Result<Ni
 where
     S: AsRef<str>,
 {
-    use reqwest::blocking::Client;
-
     let (url, name) = compose_url(nip05)?;
-    let mut builder = Client::builder();
-    if let Some(proxy) = proxy {
-        let proxy = format!("socks5h://{proxy}");
-        builder = builder.proxy(Proxy::all(proxy)?);
-    }
-    let client: Client = builder.build()?;
-    let res = client.get(url).send()?;
-    let json: Value = serde_json::from_str(&res.text()?)?;
+    let json: Value = get_json_blocking(url, proxy, &RetryPolicy::default())?;
 
     let public_key = get_key_from_json(json.clone(), name).ok_or(Error::ImpossibleToVerify)?;
     let relays = get_relays_from_json(json, public_key);
 
     Ok(Nip19Profile { public_key, relays })
 }
+
+/// Identity verification request
+///
+/// Published by NIP05 providers (kind `30078`, `d` tag [`IDENTITY_VERIFICATION_IDENTIFIER`])
+/// to link a public key to an identity on an external platform.
+/// The identity is verified if the proof URL contains the public key (hex or bech32).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentityVerificationRequest {
+    /// Public key to verify
+    pub public_key: PublicKey,
+    /// Identifier on the platform
+    pub identifier: String,
+    /// Platform (ex. `github`)
+    pub platform: String,
+    /// URL of the proof
+    pub proof_url: Url,
+}
+
+impl IdentityVerificationRequest {
+    /// Parse from [`Event`]
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        if event.kind() != Kind::ApplicationSpecificData
+            || event.identifier() != Some(IDENTITY_VERIFICATION_IDENTIFIER)
+        {
+            return Err(Error::InvalidVerificationRequest);
+        }
+
+        let value = |name: &'static str| -> Result<String, Error> {
+            event
+                .iter_tags()
+                .find(|tag| tag.kind() == TagKind::Custom(name.to_string()))
+                .and_then(|tag| tag.as_vec().get(1).cloned())
+                .ok_or(Error::MissingTag(name))
+        };
+
+        Ok(Self {
+            public_key: event.author(),
+            identifier: value("identifier")?,
+            platform: value("platform")?,
+            proof_url: Url::parse(&value("proof")?).map_err(Error::InvalidProofUrl)?,
+        })
+    }
+
+    /// Fetch the proof URL and check if contains the public key
+    ///
+    /// Use the default [`RetryPolicy`].
+    ///
+    /// **Proxy is ignored for WASM targets!**
+    pub async fn verify(&self, proxy: Option<SocketAddr>) -> Result<bool, Error> {
+        self.verify_with_policy(proxy, &RetryPolicy::default())
+            .await
+    }
+
+    /// Fetch the proof URL and check if contains the public key, with a custom [`RetryPolicy`]
+    ///
+    /// **Proxy is ignored for WASM targets!**
+    pub async fn verify_with_policy(
+        &self,
+        proxy: Option<SocketAddr>,
+        policy: &RetryPolicy,
+    ) -> Result<bool, Error> {
+        let text: String = get_text(self.proof_url.as_str(), proxy, policy).await?;
+        Ok(self.is_proof(&text))
+    }
+
+    fn is_proof(&self, text: &str) -> bool {
+        if text.contains(&self.public_key.to_hex()) {
+            return true;
+        }
+
+        match self.public_key.to_bech32() {
+            Ok(npub) => text.contains(&npub),
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_identity_verification_request() {
+        let keys = Keys::test(1);
+        let proof_url = Url::parse("https://gist.github.com/alice/abcdef").unwrap();
+        let event =
+            EventBuilder::identity_verification_request("alice", "github", proof_url.clone())
+                .to_event(&keys)
+                .unwrap();
+        assert_eq!(event.kind(), Kind::ApplicationSpecificData);
+
+        let request = IdentityVerificationRequest::from_event(&event).unwrap();
+        assert_eq!(request.public_key, keys.public_key());
+        assert_eq!(request.identifier, "alice");
+        assert_eq!(request.platform, "github");
+        assert_eq!(request.proof_url, proof_url);
+
+        let npub = keys.public_key().to_bech32().unwrap();
+        assert!(request.is_proof(&format!("Verifying my nostr key: {npub}")));
+        assert!(request.is_proof(&keys.public_key().to_hex()));
+        assert!(!request.is_proof("Verifying my nostr key"));
+
+        let event = EventBuilder::text_note("test", []).to_event(&keys).unwrap();
+        assert!(matches!(
+            IdentityVerificationRequest::from_event(&event),
+            Err(Error::InvalidVerificationRequest)
+        ));
+    }
+}
@@ -5,6 +5,17 @@
 //! NIP06
 //!
 //! <https://github.com/nostr-protocol/nips/blob/master/06.md>
+//!
+//! # Pseudonyms
+//!
+//! App-scoped pseudonymous identities are derived from the same mnemonic (see [`Keys::pseudonym`]):
+//!
+//! 1. `digest = SHA256("nostr-pseudonym/" || scope)`, with `scope` encoded as UTF-8
+//! 2. `digest` is split into 8 big-endian `u32`, each with the most significant bit cleared (non-hardened index)
+//! 3. the secret key is derived at `m/44'/1237'/0'/<c0>/<c1>/<c2>/<c3>/<c4>/<c5>/<c6>/<c7>`
+//!
+//! The scope components are non-hardened: anyone knowing the extended public key of `m/44'/1237'/0'` and
+//! a pseudonym secret key can compute the secret keys of all the pseudonyms. Never share the extended public key.
 
 use alloc::vec;
 use alloc::vec::Vec;
@@ -12,7 +23,10 @@ use core::fmt;
 use core::str::FromStr;
 
 use bip39::Mnemonic;
-use bitcoin::bip32::{ChildNumber, DerivationPath, Xpriv};
+use bitcoin::bip32::Xpriv;
+pub use bitcoin::bip32::{ChildNumber, DerivationPath};
+use bitcoin::hashes::sha256::Hash as Sha256Hash;
+use bitcoin::hashes::{Hash, HashEngine};
 use bitcoin::secp256k1::{Secp256k1, Signing};
 use bitcoin::Network;
 
@@ -22,6 +36,7 @@ use crate::{Keys, SecretKey};
 
 const PURPOSE: u32 = 44;
 const COIN: u32 = 1237;
+const PSEUDONYM_DOMAIN: &[u8] = b"nostr-pseudonym/";
 
 /// `NIP06` error
 #[derive(Debug, Eq, PartialEq)]
@@ -139,16 +154,6 @@ impl FromMnemonic for Keys {
         C: Signing,
         S: AsRef<str>,
     {
-        // Parse menmonic
-        let mnemonic: Mnemonic = Mnemonic::from_str(mnemonic.as_ref())?;
-
-        // Convert mnemonic to seed
-        let seed: [u8; 64] = mnemonic
-            .to_seed_normalized(passphrase.as_ref().map(|s| s.as_ref()).unwrap_or_default());
-
-        // Derive BIP32 root key
-        let root_key = Xpriv::new_master(Network::Bitcoin, &seed)?;
-
         // Unwrap idx
         let account: u32 = account.unwrap_or_default();
         let _type: u32 = r#type.unwrap_or_default();
@@ -164,13 +169,103 @@ impl FromMnemonic for Keys {
         ];
         let path = DerivationPath::from(path);
 
+        Self::from_mnemonic_with_path_with_ctx(secp, mnemonic, passphrase, &path)
+    }
+}
+
+impl Keys {
+    /// Derive from BIP-39 mnemonics with a **custom** BIP-32 derivation path (ENGLISH wordlist).
+    #[cfg(feature = "std")]
+    pub fn from_mnemonic_with_path<S>(
+        mnemonic: S,
+        passphrase: Option<S>,
+        path: &DerivationPath,
+    ) -> Result<Self, Error>
+    where
+        S: AsRef<str>,
+    {
+        Self::from_mnemonic_with_path_with_ctx(&SECP256K1, mnemonic, passphrase, path)
+    }
+
+    /// Derive from BIP-39 mnemonics with a **custom** BIP-32 derivation path (ENGLISH wordlist).
+    pub fn from_mnemonic_with_path_with_ctx<C, S>(
+        secp: &Secp256k1<C>,
+        mnemonic: S,
+        passphrase: Option<S>,
+        path: &DerivationPath,
+    ) -> Result<Self, Error>
+    where
+        C: Signing,
+        S: AsRef<str>,
+    {
+        // Parse menmonic
+        let mnemonic: Mnemonic = Mnemonic::from_str(mnemonic.as_ref())?;
+
+        // Convert mnemonic to seed
+        let seed: [u8; 64] = mnemonic
+            .to_seed_normalized(passphrase.as_ref().map(|s| s.as_ref()).unwrap_or_default());
+
+        // Derive BIP32 root key
+        let root_key = Xpriv::new_master(Network::Bitcoin, &seed)?;
+
         // Derive secret key
-        let child_xprv = root_key.derive_priv(secp, &path)?;
+        let child_xprv = root_key.derive_priv(secp, path)?;
         let secret_key = SecretKey::from(child_xprv.private_key);
 
         // Compose keys
         Ok(Self::new_with_ctx(secp, secret_key))
     }
+
+    /// Derive an app-scoped pseudonym from BIP-39 mnemonics (ENGLISH wordlist).
+    ///
+    /// The same `scope` (ex. a community name) always produce the same keys.
+    /// Check the [module docs](crate::nips::nip06) for the derivation scheme.
+    #[cfg(feature = "std")]
+    pub fn pseudonym<S>(mnemonic: S, passphrase: Option<S>, scope: &str) -> Result<Self, Error>
+    where
+        S: AsRef<str>,
+    {
+        Self::pseudonym_with_ctx(&SECP256K1, mnemonic, passphrase, scope)
+    }
+
+    /// Derive an app-scoped pseudonym from BIP-39 mnemonics (ENGLISH wordlist).
+    ///
+    /// Check the [module docs](crate::nips::nip06) for the derivation scheme.
+    pub fn pseudonym_with_ctx<C, S>(
+        secp: &Secp256k1<C>,
+        mnemonic: S,
+        passphrase: Option<S>,
+        scope: &str,
+    ) -> Result<Self, Error>
+    where
+        C: Signing,
+        S: AsRef<str>,
+    {
+        let path: DerivationPath = pseudonym_path(scope)?;
+        Self::from_mnemonic_with_path_with_ctx(secp, mnemonic, passphrase, &path)
+    }
+}
+
+/// Get the derivation path of a pseudonym
+///
+/// Check the [module docs](crate::nips::nip06) for the derivation scheme.
+pub fn pseudonym_path(scope: &str) -> Result<DerivationPath, Error> {
+    let mut engine = Sha256Hash::engine();
+    engine.input(PSEUDONYM_DOMAIN);
+    engine.input(scope.as_bytes());
+    let digest: [u8; 32] = Sha256Hash::from_engine(engine).to_byte_array();
+
+    let mut path: Vec<ChildNumber> = vec![
+        ChildNumber::from_hardened_idx(PURPOSE)?,
+        ChildNumber::from_hardened_idx(COIN)?,
+        ChildNumber::from_hardened_idx(0)?,
+    ];
+    for chunk in digest.chunks_exact(4) {
+        let index: u32 = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) & 0x7FFF_FFFF;
+        path.push(ChildNumber::from_normal_idx(index)?);
+    }
+
+    Ok(DerivationPath::from(path))
 }
 
 #[cfg(test)]
@@ -196,4 +291,67 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_from_mnemonic_with_path() {
+        let secp = Secp256k1::new();
+        let mnemonic =
+            "leader monkey parrot ring guide accident before fence cannon height naive bean";
+
+        let path = DerivationPath::from_str("m/44'/1237'/1'/0/0").unwrap();
+        let keys = Keys::from_mnemonic_with_path_with_ctx(&secp, mnemonic, None, &path).unwrap();
+        let expected =
+            Keys::from_mnemonic_with_ctx(&secp, mnemonic, None, Some(1), None, None).unwrap();
+        assert_eq!(keys.secret_key().unwrap(), expected.secret_key().unwrap());
+        assert_eq!(
+            keys.secret_key().unwrap(),
+            &SecretKey::from_str(
+                "3790c23940f62b23754115ef70f16e63cca8e9015a532b8a891171ccdadcf910"
+            )
+            .unwrap()
+        );
+    }
+
+    // Pinned outputs: changing them change the identities derived by all the users
+    #[test]
+    fn test_pseudonym() {
+        let secp = Secp256k1::new();
+        let mnemonic =
+            "leader monkey parrot ring guide accident before fence cannon height naive bean";
+
+        assert_eq!(
+            pseudonym_path("nostr.band").unwrap(),
+            DerivationPath::from_str("m/44'/1237'/0'/1780371511/1203585883/2009424054/1617883219/634026700/578352978/564242827/729688141").unwrap()
+        );
+
+        let list = vec![
+            (
+                "nostr.band",
+                None,
+                "f852296258a8e74f75715519e581a422c5a2ca7e9265848ded76dbd4e21ee128",
+                "8212727bca08a23a47054da835d056cee07030fbed06dbceac25c765a6b964a6",
+            ),
+            (
+                "bitcoin-devs",
+                None,
+                "bbd9e19e3a01b3a1806ff96767fc8c9c90ccc762850531bd3d5d326d3910f081",
+                "48a37b58f716c96168ae9fce27516a23a7206b705425892efd2274a2a29db395",
+            ),
+            (
+                "nostr.band",
+                Some("passphrase"),
+                "597cefe372a9783a9b776221b7f88069a66c0ed2de707e0c8da982906bbdd1e9",
+                "0e70c218e3274daf270294b069708dcb859f14ef235a976483a22f70436ecb5c",
+            ),
+        ];
+
+        for (scope, passphrase, expected_secret_key, expected_public_key) in list.into_iter() {
+            let keys = Keys::pseudonym_with_ctx(&secp, mnemonic, passphrase, scope).unwrap();
+            assert_eq!(
+                keys.secret_key().unwrap(),
+                &SecretKey::from_str(expected_secret_key).unwrap()
+            );
+            assert_eq!(keys.public_key().to_hex(), expected_public_key);
+        }
+    }
 }
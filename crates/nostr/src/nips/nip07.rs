@@ -41,6 +41,10 @@ pub enum Error {
     ObjectKeyNotFound(String),
     /// Invalid type: expected a string
     TypeMismatch(String),
+    /// Request rejected by the extension (ex. user denied the request)
+    Rejected(String),
+    /// Method not supported by the extension
+    Unsupported(String),
 }
 
 #[cfg(feature = "std")]
@@ -58,6 +62,8 @@ impl fmt::Display for Error {
             Self::NamespaceNotFound(n) => write!(f, "`{n}` namespace not found"),
             Self::ObjectKeyNotFound(n) => write!(f, "Key `{n}` not found in object"),
             Self::TypeMismatch(e) => write!(f, "Type mismatch: {e}"),
+            Self::Rejected(reason) => write!(f, "Request rejected: {reason}"),
+            Self::Unsupported(method) => write!(f, "`{method}` not supported by the extension"),
         }
     }
 }
@@ -92,6 +98,20 @@ impl From<JsValue> for Error {
     }
 }
 
+impl Error {
+    /// Compose [`Error::Rejected`] from a rejected JS promise
+    fn rejected(value: JsValue) -> Self {
+        let reason: String = match value.dyn_ref::<js_sys::Error>() {
+            Some(e) => String::from(e.message()),
+            None => value.as_string().unwrap_or_else(|| format!("{value:?}")),
+        };
+        Self::Rejected(reason)
+    }
+}
+
+/// Alias for [`Nip07Signer`]
+pub type BrowserSigner = Nip07Signer;
+
 /// NIP07 Signer for interaction with browser extensions (ex. Alby)
 ///
 /// <https://github.com/aljazceru/awesome-nostr#nip-07-browser-extensions>
@@ -118,15 +138,35 @@ impl Nip07Signer {
         Self::new().is_ok()
     }
 
+    /// Check if the extension support NIP04 encryption
+    pub fn supports_nip04(&self) -> bool {
+        self.nip04_obj().is_ok()
+    }
+
+    /// Check if the extension support NIP44 encryption
+    pub fn supports_nip44(&self) -> bool {
+        self.nip44_obj().is_ok()
+    }
+
     fn get_func<S>(&self, obj: &Object, name: S) -> Result<Function, Error>
     where
         S: AsRef<str>,
     {
         let name: &str = name.as_ref();
         let val: JsValue = Reflect::get(obj, &JsValue::from_str(name))
-            .map_err(|_| Error::NamespaceNotFound(name.to_string()))?;
+            .map_err(|_| Error::Unsupported(name.to_string()))?;
         val.dyn_into()
-            .map_err(|_| Error::NamespaceNotFound(name.to_string()))
+            .map_err(|_| Error::Unsupported(name.to_string()))
+    }
+
+    /// Call the function and await the returned promise
+    async fn call<F>(&self, func: F) -> Result<JsValue, Error>
+    where
+        F: FnOnce() -> Result<JsValue, JsValue>,
+    {
+        let value: JsValue = func()?;
+        let promise: Promise = Promise::resolve(&value);
+        JsFuture::from(promise).await.map_err(Error::rejected)
     }
 
     /// Get value from object key
@@ -138,8 +178,7 @@ impl Nip07Signer {
     /// Get Public Key
     pub async fn get_public_key(&self) -> Result<PublicKey, Error> {
         let func: Function = self.get_func(&self.nostr_obj, "getPublicKey")?;
-        let promise: Promise = Promise::resolve(&func.call0(&self.nostr_obj)?);
-        let result: JsValue = JsFuture::from(promise).await?;
+        let result: JsValue = self.call(|| func.call0(&self.nostr_obj)).await?;
         let public_key: String = result
             .as_string()
             .ok_or_else(|| Error::TypeMismatch(String::from("expected a hex string")))?;
@@ -189,9 +228,12 @@ impl Nip07Signer {
             &unsigned.content.as_str().into(),
         )?;
 
-        let promise: Promise = Promise::resolve(&func.call1(&self.nostr_obj, &unsigned_obj)?);
-        let result: JsValue = JsFuture::from(promise).await?;
-        let event_obj: Object = result.dyn_into()?;
+        let result: JsValue = self
+            .call(|| func.call1(&self.nostr_obj, &unsigned_obj))
+            .await?;
+        let event_obj: Object = result
+            .dyn_into()
+            .map_err(|_| Error::TypeMismatch(String::from("expected an event object")))?;
 
         // Extract signature from event object
         let sig: String = self
@@ -215,10 +257,10 @@ impl Nip07Signer {
 
     fn nip04_obj(&self) -> Result<Object, Error> {
         let namespace: JsValue = Reflect::get(&self.nostr_obj, &JsValue::from_str("nip04"))
-            .map_err(|_| Error::NamespaceNotFound(String::from("nip04")))?;
+            .map_err(|_| Error::Unsupported(String::from("nip04")))?;
         namespace
             .dyn_into()
-            .map_err(|_| Error::NamespaceNotFound(String::from("nip04")))
+            .map_err(|_| Error::Unsupported(String::from("nip04")))
     }
 
     /// NIP04 encrypt
@@ -230,12 +272,15 @@ impl Nip07Signer {
         let func: Function = self.get_func(&nip04_obj, "encrypt")?;
         let content: &[u8] = content.as_ref();
         let content: String = String::from_utf8_lossy(content).to_string();
-        let promise: Promise = Promise::resolve(&func.call2(
-            &nip04_obj,
-            &JsValue::from_str(&public_key.to_string()),
-            &JsValue::from_str(&content),
-        )?);
-        let result: JsValue = JsFuture::from(promise).await?;
+        let result: JsValue = self
+            .call(|| {
+                func.call2(
+                    &nip04_obj,
+                    &JsValue::from_str(&public_key.to_string()),
+                    &JsValue::from_str(&content),
+                )
+            })
+            .await?;
         result
             .as_string()
             .ok_or_else(|| Error::TypeMismatch(String::from("expected a string")))
@@ -252,12 +297,15 @@ impl Nip07Signer {
     {
         let nip04_obj: Object = self.nip04_obj()?;
         let func: Function = self.get_func(&nip04_obj, "decrypt")?;
-        let promise: Promise = Promise::resolve(&func.call2(
-            &nip04_obj,
-            &JsValue::from_str(&public_key.to_hex()),
-            &JsValue::from_str(ciphertext.as_ref()),
-        )?);
-        let result: JsValue = JsFuture::from(promise).await?;
+        let result: JsValue = self
+            .call(|| {
+                func.call2(
+                    &nip04_obj,
+                    &JsValue::from_str(&public_key.to_hex()),
+                    &JsValue::from_str(ciphertext.as_ref()),
+                )
+            })
+            .await?;
         result
             .as_string()
             .ok_or_else(|| Error::TypeMismatch(String::from("expected a string")))
@@ -265,10 +313,10 @@ impl Nip07Signer {
 
     fn nip44_obj(&self) -> Result<Object, Error> {
         let namespace: JsValue = Reflect::get(&self.nostr_obj, &JsValue::from_str("nip44"))
-            .map_err(|_| Error::NamespaceNotFound(String::from("nip44")))?;
+            .map_err(|_| Error::Unsupported(String::from("nip44")))?;
         namespace
             .dyn_into()
-            .map_err(|_| Error::NamespaceNotFound(String::from("nip44")))
+            .map_err(|_| Error::Unsupported(String::from("nip44")))
     }
 
     /// NIP44 encrypt
@@ -280,12 +328,15 @@ impl Nip07Signer {
         let func: Function = self.get_func(&nip44_obj, "encrypt")?;
         let content: &[u8] = content.as_ref();
         let content: String = String::from_utf8_lossy(content).to_string();
-        let promise: Promise = Promise::resolve(&func.call2(
-            &nip44_obj,
-            &JsValue::from_str(&public_key.to_hex()),
-            &JsValue::from_str(&content),
-        )?);
-        let result: JsValue = JsFuture::from(promise).await?;
+        let result: JsValue = self
+            .call(|| {
+                func.call2(
+                    &nip44_obj,
+                    &JsValue::from_str(&public_key.to_hex()),
+                    &JsValue::from_str(&content),
+                )
+            })
+            .await?;
         result
             .as_string()
             .ok_or_else(|| Error::TypeMismatch(String::from("expected a string")))
@@ -304,12 +355,15 @@ impl Nip07Signer {
         let func: Function = self.get_func(&nip44_obj, "decrypt")?;
         let ciphertext: &[u8] = ciphertext.as_ref();
         let ciphertext: String = String::from_utf8_lossy(ciphertext).to_string();
-        let promise: Promise = Promise::resolve(&func.call2(
-            &nip44_obj,
-            &JsValue::from_str(&public_key.to_string()),
-            &JsValue::from_str(&ciphertext),
-        )?);
-        let result: JsValue = JsFuture::from(promise).await?;
+        let result: JsValue = self
+            .call(|| {
+                func.call2(
+                    &nip44_obj,
+                    &JsValue::from_str(&public_key.to_string()),
+                    &JsValue::from_str(&ciphertext),
+                )
+            })
+            .await?;
         result
             .as_string()
             .ok_or_else(|| Error::TypeMismatch(String::from("expected a string")))
@@ -0,0 +1,441 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP10
+//!
+//! Target resolution of the events that refer to another event or profile
+//! (reactions, reposts, zap receipts and reports).
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/10.md>
+
+use alloc::vec::Vec;
+
+use crate::event::tag::Marker;
+use crate::nips::nip01::Coordinate;
+use crate::{Event, EventId, JsonUtil, Kind, PublicKey, Tag};
+
+/// Resolved target
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Target {
+    /// Event
+    Event {
+        /// Event ID
+        event_id: EventId,
+        /// Author of the event, if known
+        author: Option<PublicKey>,
+    },
+    /// Replaceable event
+    Coordinate {
+        /// Coordinate
+        coordinate: Coordinate,
+        /// ID of the specific version of the event, if known
+        event_id: Option<EventId>,
+    },
+    /// Profile (ex. zap or report of a user)
+    PublicKey(PublicKey),
+    /// No target found
+    Unknown,
+}
+
+/// Resolution mode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ResolveMode {
+    /// Tolerate the legacy layouts found in old events (default)
+    ///
+    /// * markers are case-insensitive (ex. `Reply`, `ROOT`);
+    /// * reposts without `e` tag are resolved from the reposted event in the content;
+    /// * zap receipts without `e`, `a` and `p` tags are resolved from the zap request in the `description` tag.
+    #[default]
+    Compatible,
+    /// Follow the spec only
+    Strict,
+}
+
+/// Resolve the target of an event, tolerating legacy tag layouts
+///
+/// Check [`resolve_target_with_mode`].
+pub fn resolve_target(event: &Event) -> Target {
+    resolve_target_with_mode(event, ResolveMode::Compatible)
+}
+
+/// Resolve the target of an event
+///
+/// Precedence:
+/// 1. `a` tag over `e` tag, for replaceable targets (the `e` tag is kept as the version of the event);
+/// 2. for reactions, the last `e` tag (NIP25);
+/// 3. for the other kinds, the `reply` marked `e` tag, then the `root` marked one, then the last unmarked one (positional layout).
+///    `mention` marked tags are never targets;
+/// 4. if no `e` or `a` tag is found, the `p` tag (ex. zap or report of a user).
+///
+/// The author is the last `p` tag for reactions and the first one for the other kinds.
+/// Uppercase `P` tags (ex. zap sender) are ignored.
+pub fn resolve_target_with_mode(event: &Event, mode: ResolveMode) -> Target {
+    let is_reaction: bool = event.kind() == Kind::Reaction;
+
+    let mut events: Vec<(EventId, Option<TargetMarker>)> = Vec::new();
+    let mut coordinates: Vec<&Coordinate> = Vec::new();
+    let mut public_keys: Vec<PublicKey> = Vec::new();
+
+    for tag in event.iter_tags() {
+        match tag {
+            Tag::Event {
+                event_id, marker, ..
+            } => {
+                let marker: Option<TargetMarker> = match marker {
+                    Some(marker) => match TargetMarker::parse(marker, mode) {
+                        Some(marker) => Some(marker),
+                        // Unknown marker
+                        None => continue,
+                    },
+                    None => None,
+                };
+                events.push((*event_id, marker));
+            }
+            Tag::EventReport(event_id, ..) => events.push((*event_id, None)),
+            Tag::A { coordinate, .. } => coordinates.push(coordinate),
+            Tag::PublicKey {
+                public_key,
+                uppercase: false,
+                ..
+            } => public_keys.push(*public_key),
+            Tag::PubKeyReport(public_key, ..) => public_keys.push(*public_key),
+            _ => (),
+        }
+    }
+
+    if mode == ResolveMode::Compatible && events.is_empty() && coordinates.is_empty() {
+        if let Some(target) = resolve_embedded(event, public_keys.is_empty()) {
+            return target;
+        }
+    }
+
+    let event_id: Option<EventId> = if is_reaction {
+        events
+            .iter()
+            .rev()
+            .find(|(_, marker)| *marker != Some(TargetMarker::Mention))
+            .map(|(id, _)| *id)
+    } else {
+        find_marked(&events, TargetMarker::Reply)
+            .or_else(|| find_marked(&events, TargetMarker::Root))
+            .or_else(|| {
+                events
+                    .iter()
+                    .rev()
+                    .find(|(_, marker)| marker.is_none())
+                    .map(|(id, _)| *id)
+            })
+    };
+
+    let author: Option<PublicKey> = if is_reaction {
+        public_keys.last().copied()
+    } else {
+        public_keys.first().copied()
+    };
+
+    match (coordinates.last(), event_id, author) {
+        (Some(coordinate), event_id, ..) => Target::Coordinate {
+            coordinate: (*coordinate).clone(),
+            event_id,
+        },
+        (None, Some(event_id), author) => Target::Event { event_id, author },
+        (None, None, Some(public_key)) => Target::PublicKey(public_key),
+        (None, None, None) => Target::Unknown,
+    }
+}
+
+/// Legacy reposts embed the reposted event in the content and legacy zap receipts only have the zap request
+fn resolve_embedded(event: &Event, no_public_keys: bool) -> Option<Target> {
+    match event.kind() {
+        Kind::Repost | Kind::GenericRepost => {
+            let reposted: Event = Event::from_json(event.content()).ok()?;
+            Some(Target::Event {
+                event_id: reposted.id(),
+                author: Some(reposted.author()),
+            })
+        }
+        Kind::ZapReceipt if no_public_keys => {
+            let description: &str = event.iter_tags().find_map(|tag| match tag {
+                Tag::Description(description) => Some(description.as_str()),
+                _ => None,
+            })?;
+            let zap_request: Event = Event::from_json(description).ok()?;
+            if zap_request.kind() != Kind::ZapRequest {
+                return None;
+            }
+            Some(resolve_target_with_mode(&zap_request, ResolveMode::Strict))
+        }
+        _ => None,
+    }
+}
+
+fn find_marked(
+    events: &[(EventId, Option<TargetMarker>)],
+    marker: TargetMarker,
+) -> Option<EventId> {
+    events
+        .iter()
+        .rev()
+        .find(|(_, m)| *m == Some(marker))
+        .map(|(id, _)| *id)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TargetMarker {
+    Root,
+    Reply,
+    Mention,
+}
+
+impl TargetMarker {
+    fn parse(marker: &Marker, mode: ResolveMode) -> Option<Self> {
+        match marker {
+            Marker::Root => Some(Self::Root),
+            Marker::Reply => Some(Self::Reply),
+            Marker::Mention => Some(Self::Mention),
+            Marker::Custom(marker) if mode == ResolveMode::Compatible => {
+                match marker.trim().to_lowercase().as_str() {
+                    "root" => Some(Self::Root),
+                    "reply" => Some(Self::Reply),
+                    "mention" => Some(Self::Mention),
+                    _ => None,
+                }
+            }
+            Marker::Custom(..) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use alloc::vec;
+
+    use super::*;
+    use crate::{EventBuilder, Keys, Timestamp};
+
+    fn event_id(n: u8) -> EventId {
+        EventId::from_slice(&[n; 32]).unwrap()
+    }
+
+    fn public_key(n: u8) -> PublicKey {
+        Keys::parse(format!("{:064x}", n)).unwrap().public_key()
+    }
+
+    fn tag(values: &[&str]) -> Tag {
+        Tag::parse(values).unwrap()
+    }
+
+    fn event(kind: Kind, content: &str, tags: Vec<Tag>) -> Event {
+        let keys = Keys::generate();
+        EventBuilder::new(kind, content, tags)
+            .custom_created_at(Timestamp::from(1_700_000_000))
+            .to_event(&keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_reaction_layouts() {
+        let root = event_id(1).to_hex();
+        let reply = event_id(2).to_hex();
+        let root_author = public_key(1).to_hex();
+        let reply_author = public_key(2).to_hex();
+
+        // Damus: `e` and `p` of the reacted event only
+        let damus = event(
+            Kind::Reaction,
+            "🤙",
+            vec![tag(&["e", &reply]), tag(&["p", &reply_author])],
+        );
+
+        // Amethyst: thread tags copied from the reacted event, reacted event last
+        let amethyst = event(
+            Kind::Reaction,
+            "+",
+            vec![
+                tag(&["e", &root, "", "root"]),
+                tag(&["p", &root_author]),
+                tag(&["e", &reply]),
+                tag(&["p", &reply_author]),
+            ],
+        );
+
+        // Older clients: positional tags, duplicated
+        let legacy = event(
+            Kind::Reaction,
+            "+",
+            vec![
+                tag(&["e", &root]),
+                tag(&["p", &root_author]),
+                tag(&["e", &root]),
+                tag(&["e", &reply, "wss://relay.example.com"]),
+                tag(&["p", &root_author]),
+                tag(&["p", &reply_author]),
+            ],
+        );
+
+        let expected = Target::Event {
+            event_id: event_id(2),
+            author: Some(public_key(2)),
+        };
+        for event in [damus, amethyst, legacy] {
+            assert_eq!(resolve_target(&event), expected);
+            assert_eq!(
+                resolve_target_with_mode(&event, ResolveMode::Strict),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_markers() {
+        let root = event_id(1).to_hex();
+        let reply = event_id(2).to_hex();
+        let mention = event_id(3).to_hex();
+
+        // Marked tags preferred over positional ones
+        let marked = event(
+            Kind::Repost,
+            "",
+            vec![
+                tag(&["e", &root, "", "root"]),
+                tag(&["e", &reply, "", "reply"]),
+                tag(&["e", &mention, "", "mention"]),
+            ],
+        );
+        assert_eq!(
+            resolve_target(&marked),
+            Target::Event {
+                event_id: event_id(2),
+                author: None
+            }
+        );
+
+        // Uppercase markers
+        let uppercase = event(
+            Kind::Repost,
+            "",
+            vec![
+                tag(&["e", &mention]),
+                tag(&["e", &root, "", "Root"]),
+                tag(&["e", &reply, "", "REPLY"]),
+            ],
+        );
+        assert_eq!(
+            resolve_target(&uppercase),
+            Target::Event {
+                event_id: event_id(2),
+                author: None
+            }
+        );
+        assert_eq!(
+            resolve_target_with_mode(&uppercase, ResolveMode::Strict),
+            Target::Event {
+                event_id: event_id(3),
+                author: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_replaceable_target() {
+        let author = public_key(1);
+        let coordinate = Coordinate::new(Kind::LongFormTextNote, author).identifier("article");
+        let zap_receipt = event(
+            Kind::ZapReceipt,
+            "",
+            vec![
+                tag(&["p", &author.to_hex()]),
+                tag(&["e", &event_id(1).to_hex()]),
+                Tag::A {
+                    coordinate: coordinate.clone(),
+                    relay_url: None,
+                },
+                tag(&["P", &public_key(2).to_hex()]),
+            ],
+        );
+        assert_eq!(
+            resolve_target(&zap_receipt),
+            Target::Coordinate {
+                coordinate,
+                event_id: Some(event_id(1))
+            }
+        );
+    }
+
+    #[test]
+    fn test_reports() {
+        let event_report = event(
+            Kind::Reporting,
+            "",
+            vec![
+                tag(&["e", &event_id(1).to_hex(), "spam"]),
+                tag(&["p", &public_key(1).to_hex()]),
+            ],
+        );
+        assert_eq!(
+            resolve_target(&event_report),
+            Target::Event {
+                event_id: event_id(1),
+                author: Some(public_key(1))
+            }
+        );
+
+        let profile_report = event(
+            Kind::Reporting,
+            "",
+            vec![tag(&["p", &public_key(1).to_hex(), "impersonation"])],
+        );
+        assert_eq!(
+            resolve_target(&profile_report),
+            Target::PublicKey(public_key(1))
+        );
+
+        assert_eq!(
+            resolve_target(&event(Kind::Reporting, "", Vec::new())),
+            Target::Unknown
+        );
+    }
+
+    #[test]
+    fn test_embedded_targets() {
+        // Legacy repost: reposted event in the content only
+        let reposted = event(Kind::TextNote, "hello", Vec::new());
+        let repost = event(Kind::Repost, &reposted.as_json(), Vec::new());
+        assert_eq!(
+            resolve_target(&repost),
+            Target::Event {
+                event_id: reposted.id(),
+                author: Some(reposted.author())
+            }
+        );
+        assert_eq!(
+            resolve_target_with_mode(&repost, ResolveMode::Strict),
+            Target::Unknown
+        );
+
+        // Legacy zap receipt: zap request in the description only
+        let zap_request = event(
+            Kind::ZapRequest,
+            "",
+            vec![
+                tag(&["p", &public_key(1).to_hex()]),
+                tag(&["e", &event_id(1).to_hex()]),
+            ],
+        );
+        let description: String = zap_request.as_json();
+        let zap_receipt = event(
+            Kind::ZapReceipt,
+            "",
+            vec![tag(&["description", &description])],
+        );
+        assert_eq!(
+            resolve_target(&zap_receipt),
+            Target::Event {
+                event_id: event_id(1),
+                author: Some(public_key(1))
+            }
+        );
+    }
+}
@@ -15,6 +15,7 @@ use std::net::SocketAddr;
 #[cfg(not(target_arch = "wasm32"))]
 use reqwest::Proxy;
 
+use crate::util::http::{self, RetryPolicy};
 use crate::{Timestamp, Url};
 
 /// `NIP11` error
@@ -28,6 +29,8 @@ pub enum Error {
     InvalidScheme,
     /// Reqwest error
     Reqwest(reqwest::Error),
+    /// HTTP error (all attempts failed)
+    Http(http::Error),
 }
 
 impl std::error::Error for Error {}
@@ -43,6 +46,7 @@ impl fmt::Display for Error {
             }
             Self::InvalidScheme => write!(f, "Provided URL scheme is not valid"),
             Self::Reqwest(e) => write!(f, "{e}"),
+            Self::Http(e) => write!(f, "{e}"),
         }
     }
 }
@@ -53,6 +57,12 @@ impl From<reqwest::Error> for Error {
     }
 }
 
+impl From<http::Error> for Error {
+    fn from(e: http::Error) -> Self {
+        Self::Http(e)
+    }
+}
+
 /// Relay information document
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct RelayInformationDocument {
@@ -187,8 +197,23 @@ impl RelayInformationDocument {
 
     /// Get Relay Information Document
     ///
+    /// Use the default [`RetryPolicy`].
+    ///
+    /// **Proxy is ignored for WASM targets!**
+    pub async fn get(url: Url, proxy: Option<SocketAddr>) -> Result<Self, Error> {
+        Self::get_with_policy(url, proxy, &RetryPolicy::default()).await
+    }
+
+    /// Get Relay Information Document with a custom [`RetryPolicy`]
+    ///
+    /// [`Error::Http`] is returned if all the attempts failed.
+    ///
     /// **Proxy is ignored for WASM targets!**
-    pub async fn get(url: Url, _proxy: Option<SocketAddr>) -> Result<Self, Error> {
+    pub async fn get_with_policy(
+        url: Url,
+        _proxy: Option<SocketAddr>,
+        policy: &RetryPolicy,
+    ) -> Result<Self, Error> {
         use reqwest::Client;
 
         #[cfg(not(target_arch = "wasm32"))]
@@ -205,19 +230,18 @@ impl RelayInformationDocument {
         let client: Client = Client::new();
 
         let url = Self::with_http_scheme(url)?;
-        let req = client
-            .get(url.to_string())
-            .header("Accept", "application/nostr+json");
-        match req.send().await {
-            Ok(response) => {
-                let json: String = response.text().await?;
-                tracing::debug!("Response: {json}");
-                match serde_json::from_slice(json.as_bytes()) {
-                    Ok(json) => Ok(json),
-                    Err(_) => Err(Error::InvalidInformationDocument),
-                }
-            }
-            Err(_) => Err(Error::InaccessibleInformationDocument),
+        let response = http::send(policy, || {
+            client
+                .get(url.as_str())
+                .header("Accept", "application/nostr+json")
+                .send()
+        })
+        .await?;
+        let json: String = response.text().await?;
+        tracing::debug!("Response: {json}");
+        match serde_json::from_slice(json.as_bytes()) {
+            Ok(json) => Ok(json),
+            Err(_) => Err(Error::InvalidInformationDocument),
         }
     }
 
@@ -234,19 +258,20 @@ impl RelayInformationDocument {
         }
         let client: Client = builder.build()?;
         let url = Self::with_http_scheme(url)?;
-        let req = client
-            .get(url.to_string())
-            .header("Accept", "application/nostr+json");
-        match req.send() {
-            Ok(response) => {
-                let json: String = response.text()?;
-                tracing::debug!("Response: {json}");
-                match serde_json::from_slice(json.as_bytes()) {
-                    Ok(json) => Ok(json),
-                    Err(_) => Err(Error::InvalidInformationDocument),
-                }
+        let response = http::send_blocking(&RetryPolicy::default(), |timeout| {
+            let req = client
+                .get(url.as_str())
+                .header("Accept", "application/nostr+json");
+            match timeout {
+                Some(timeout) => req.timeout(timeout).send(),
+                None => req.send(),
             }
-            Err(_) => Err(Error::InaccessibleInformationDocument),
+        })?;
+        let json: String = response.text()?;
+        tracing::debug!("Response: {json}");
+        match serde_json::from_slice(json.as_bytes()) {
+            Ok(json) => Ok(json),
+            Err(_) => Err(Error::InvalidInformationDocument),
         }
     }
 
@@ -6,10 +6,14 @@
 //! NIP13
 //!
 //! <https://github.com/nostr-protocol/nips/blob/master/13.md>
+//!
+//! Relays and spam filters can use [`difficulty`] and [`check_difficulty`] to enforce a minimum PoW on incoming events.
 
 use alloc::string::String;
 use alloc::vec::Vec;
 
+use crate::{Event, EventId, Tag};
+
 /// Gets the number of leading zero bits. Result is between 0 and 255.
 #[inline]
 pub fn get_leading_zero_bits<T>(h: T) -> u8
@@ -28,6 +32,46 @@ where
     res
 }
 
+/// Get the difficulty (number of leading zero bits) of an [`EventId`]
+///
+/// Computed on the raw 32 bytes. Result is between 0 and 255 (an all-zero ID is reported as 255).
+#[inline]
+pub fn difficulty(event_id: &EventId) -> u8 {
+    let bytes: &[u8] = event_id.as_bytes();
+    let mut high: [u8; 16] = [0u8; 16];
+    let mut low: [u8; 16] = [0u8; 16];
+    high.copy_from_slice(&bytes[..16]);
+    low.copy_from_slice(&bytes[16..]);
+    let high: u128 = u128::from_be_bytes(high);
+    let low: u128 = u128::from_be_bytes(low);
+    let zeros: u32 = if high == 0 {
+        128 + low.leading_zeros()
+    } else {
+        high.leading_zeros()
+    };
+    zeros.min(u8::MAX as u32) as u8
+}
+
+/// Check if [`Event`] has at least the `target` difficulty
+///
+/// The event must also commit to a target difficulty greater than or equal to `target` in the `nonce` tag,
+/// so an event can't claim accidental PoW.
+pub fn check_difficulty(event: &Event, target: u8) -> bool {
+    if target == 0 {
+        return true;
+    }
+
+    let committed: Option<u8> = event.iter_tags().find_map(|tag| match tag {
+        Tag::POW { difficulty, .. } => Some(*difficulty),
+        _ => None,
+    });
+
+    match committed {
+        Some(committed) => committed >= target && difficulty(&event.id) >= target,
+        None => false,
+    }
+}
+
 /// Returns all possible ID prefixes (hex) that have the specified number of leading zero bits.
 ///
 /// Possible values: 0-255
@@ -64,6 +108,7 @@ pub mod tests {
     use bitcoin::hashes::sha256::Hash as Sha256Hash;
 
     use super::*;
+    use crate::{EventBuilder, Keys};
 
     #[test]
     fn check_get_leading_zeroes() {
@@ -387,6 +432,49 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_difficulty() {
+        let id =
+            EventId::from_hex("0000000000000000000000000000000000000000000000000000000000000000")
+                .unwrap();
+        assert_eq!(difficulty(&id), 255);
+
+        let id =
+            EventId::from_hex("00000000000000000000000000000000ffffffffffffffffffffffffffffffff")
+                .unwrap();
+        assert_eq!(difficulty(&id), 128);
+
+        let id =
+            EventId::from_hex("000000000000000000000000000000000fffffffffffffffffffffffffffffff")
+                .unwrap();
+        assert_eq!(difficulty(&id), 132);
+
+        let id =
+            EventId::from_hex("000fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff")
+                .unwrap();
+        assert_eq!(difficulty(&id), 12);
+        assert_eq!(difficulty(&id), get_leading_zero_bits(id.as_bytes()));
+    }
+
+    #[test]
+    fn test_check_difficulty() {
+        let keys = Keys::generate();
+
+        let event = EventBuilder::text_note("PoW", [])
+            .to_pow_event(&keys, 8)
+            .unwrap();
+        assert!(check_difficulty(&event, 8));
+        assert!(check_difficulty(&event, 4));
+        assert!(!check_difficulty(&event, 9));
+
+        // No nonce tag
+        let event = EventBuilder::text_note("No PoW", [])
+            .to_event(&keys)
+            .unwrap();
+        assert!(check_difficulty(&event, 0));
+        assert!(!check_difficulty(&event, 1));
+    }
+
     #[test]
     fn check_find_prefixes_for_pow() {
         assert_eq!(get_prefixes_for_difficulty(0).is_empty(), true);
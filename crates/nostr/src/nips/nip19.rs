@@ -358,6 +358,38 @@ impl ToBech32 for EventId {
     }
 }
 
+/// Shorten `bech32` string, keeping the HRP and `chars` characters at both ends of the data part
+pub(crate) fn shorten_bech32(bech32: &str, chars: usize) -> String {
+    match bech32.rsplit_once('1') {
+        Some((hrp, data)) if chars.saturating_mul(2) < data.len() => {
+            format!("{hrp}1{}…{}", &data[..chars], &data[data.len() - chars..])
+        }
+        _ => bech32.to_string(),
+    }
+}
+
+/// Check if a `hex` or `bech32` encoded value starts with `prefix`
+///
+/// `prefix` is case insensitive. An empty prefix not match anything.
+pub(crate) fn matches_prefix<F>(prefix: &str, hrp: &str, hex: &str, bech32: F) -> bool
+where
+    F: FnOnce() -> Result<String, Error>,
+{
+    if prefix.is_empty() {
+        return false;
+    }
+
+    let prefix: String = prefix.to_lowercase();
+    if prefix.starts_with(hrp) {
+        match bech32() {
+            Ok(bech32) => bech32.starts_with(&prefix),
+            Err(_) => false,
+        }
+    } else {
+        hex.starts_with(&prefix)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Nip19Event {
     pub event_id: EventId,
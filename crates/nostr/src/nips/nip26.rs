@@ -24,11 +24,11 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{json, Value};
 
 use super::nip21;
-use crate::event::Event;
+use crate::event::{self, Event};
 use crate::key::{self, Keys};
-use crate::PublicKey;
 #[cfg(feature = "std")]
 use crate::SECP256K1;
+use crate::{PublicKey, Tag};
 
 const DELEGATION_KEYWORD: &str = "delegation";
 
@@ -191,6 +191,81 @@ where
     Ok(())
 }
 
+/// Delegation status of a verified [`Event`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum DelegationStatus {
+    /// The event has no delegation tag
+    NotDelegated,
+    /// The delegation tag is valid: the effective author is the delegator
+    Valid {
+        /// Delegator public key
+        delegator: PublicKey,
+    },
+    /// The event is valid but the delegation tag is not (invalid signature or conditions not satisfied)
+    Invalid(Error),
+}
+
+impl DelegationStatus {
+    /// Check if the delegation is valid
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid { .. })
+    }
+}
+
+/// Verify [`Event`] ID and signature and, if a delegation tag is present, the delegation
+///
+/// An invalid delegation doesn't make the event invalid: it's reported as [`DelegationStatus::Invalid`]
+/// and it's up to the caller decide what to do with it.
+#[cfg(feature = "std")]
+pub fn verify_delegated_event(event: &Event) -> Result<DelegationStatus, event::Error> {
+    verify_delegated_event_with_ctx(&SECP256K1, event)
+}
+
+/// Verify [`Event`] ID and signature and, if a delegation tag is present, the delegation
+///
+/// An invalid delegation doesn't make the event invalid: it's reported as [`DelegationStatus::Invalid`]
+/// and it's up to the caller decide what to do with it.
+pub fn verify_delegated_event_with_ctx<C>(
+    secp: &Secp256k1<C>,
+    event: &Event,
+) -> Result<DelegationStatus, event::Error>
+where
+    C: Verification,
+{
+    // Verify delegatee event
+    event.verify_with_ctx(secp)?;
+
+    Ok(delegation_status_with_ctx(secp, event))
+}
+
+pub(crate) fn delegation_status_with_ctx<C>(secp: &Secp256k1<C>, event: &Event) -> DelegationStatus
+where
+    C: Verification,
+{
+    let tag: DelegationTag = match event.iter_tags().find_map(|tag| match tag {
+        Tag::Delegation {
+            delegator,
+            conditions,
+            sig,
+        } => Some(DelegationTag {
+            delegator_pubkey: *delegator,
+            conditions: conditions.clone(),
+            signature: *sig,
+        }),
+        _ => None,
+    }) {
+        Some(tag) => tag,
+        None => return DelegationStatus::NotDelegated,
+    };
+
+    match tag.validate_with_ctx(secp, event.author(), &EventProperties::from_event(event)) {
+        Ok(()) => DelegationStatus::Valid {
+            delegator: tag.delegator_pubkey,
+        },
+        Err(e) => DelegationStatus::Invalid(e),
+    }
+}
+
 /// Delegation token
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct DelegationToken(String);
@@ -638,6 +713,57 @@ mod tests {
             .is_ok());
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_verify_delegated_event() {
+        use crate::{EventBuilder, Kind, Timestamp};
+
+        let delegator_keys = Keys::generate();
+        let delegatee_keys = Keys::generate();
+        let conditions =
+            Conditions::from_str("kind=1&created_at>1676067553&created_at<1678659553").unwrap();
+        let tag =
+            DelegationTag::new(&delegator_keys, delegatee_keys.public_key(), conditions).unwrap();
+        let delegation = Tag::Delegation {
+            delegator: tag.delegator_pubkey(),
+            conditions: tag.conditions(),
+            sig: tag.signature(),
+        };
+
+        // Valid delegation
+        let event = EventBuilder::new(Kind::TextNote, "Delegated", [delegation.clone()])
+            .custom_created_at(Timestamp::from(1677000000))
+            .to_event(&delegatee_keys)
+            .unwrap();
+        assert_eq!(
+            verify_delegated_event(&event).unwrap(),
+            DelegationStatus::Valid {
+                delegator: delegator_keys.public_key()
+            }
+        );
+        assert_eq!(event.effective_author(), delegator_keys.public_key());
+
+        // Conditions not satisfied: valid event, invalid delegation
+        let event = EventBuilder::new(Kind::Metadata, "{}", [delegation])
+            .custom_created_at(Timestamp::from(1677000000))
+            .to_event(&delegatee_keys)
+            .unwrap();
+        assert_eq!(
+            verify_delegated_event(&event).unwrap(),
+            DelegationStatus::Invalid(Error::ConditionsValidation(ValidationError::InvalidKind))
+        );
+        assert_eq!(event.effective_author(), delegatee_keys.public_key());
+
+        // Not delegated
+        let event = EventBuilder::new(Kind::TextNote, "Hello", [])
+            .to_event(&delegatee_keys)
+            .unwrap();
+        assert_eq!(
+            verify_delegated_event(&event).unwrap(),
+            DelegationStatus::NotDelegated
+        );
+    }
+
     #[test]
     fn test_delegation_tag_parse_and_validate() {
         let secp = Secp256k1::new();
@@ -225,6 +225,63 @@ where
     }
 }
 
+/// Derive the conversation key (v2)
+///
+/// ECDH between the keys, followed by HKDF-SHA256 extract.
+///
+/// **Low-level primitive**: use [`encrypt`] and [`decrypt`] unless the raw key is needed
+/// (ex. multi-party key agreement or sealed-sender constructions).
+pub fn conversation_key(secret_key: &SecretKey, public_key: &PublicKey) -> Result<[u8; 32], Error> {
+    let conversation_key: ConversationKey = ConversationKey::derive(secret_key, public_key);
+    let mut key: [u8; 32] = [0u8; 32];
+    key.copy_from_slice(conversation_key.as_bytes());
+    Ok(key)
+}
+
+/// Encrypt (v2) with a raw conversation key
+///
+/// **Low-level primitive**: use [`encrypt`] for most use cases. Check [`conversation_key`].
+#[cfg(feature = "std")]
+pub fn encrypt_with_key(key: &[u8; 32], plaintext: &str) -> Result<String, Error> {
+    encrypt_with_key_and_rng(&mut OsRng, key, plaintext)
+}
+
+/// Encrypt (v2) with a raw conversation key and custom Rng
+///
+/// **Low-level primitive**: use [`encrypt_with_rng`] for most use cases. Check [`conversation_key`].
+pub fn encrypt_with_key_and_rng<R>(
+    rng: &mut R,
+    key: &[u8; 32],
+    plaintext: &str,
+) -> Result<String, Error>
+where
+    R: RngCore,
+{
+    let conversation_key: ConversationKey = ConversationKey::from_slice(key)?;
+    let payload: Vec<u8> = v2::encrypt_to_bytes_with_rng(rng, &conversation_key, plaintext)?;
+    Ok(general_purpose::STANDARD.encode(payload))
+}
+
+/// Decrypt (v2) with a raw conversation key
+///
+/// Only v2 payloads are supported.
+///
+/// **Low-level primitive**: use [`decrypt`] for most use cases. Check [`conversation_key`].
+pub fn decrypt_with_key(key: &[u8; 32], payload: &str) -> Result<String, Error> {
+    // Decode base64 payload
+    let payload: Vec<u8> = general_purpose::STANDARD.decode(payload)?;
+
+    // Check version byte
+    let version: u8 = *payload.first().ok_or(Error::VersionNotFound)?;
+    if version != Version::V2.as_u8() {
+        return Err(Error::UnknownVersion(version));
+    }
+
+    let conversation_key: ConversationKey = ConversationKey::from_slice(key)?;
+    let bytes: Vec<u8> = v2::decrypt_to_bytes(&conversation_key, &payload)?;
+    String::from_utf8(bytes).map_err(|_| Error::Utf8Encode)
+}
+
 #[cfg(test)]
 #[cfg(feature = "std")]
 mod tests {
@@ -304,4 +361,42 @@ mod tests {
             String::from("a")
         );
     }
+
+    #[test]
+    fn test_nip44_raw_conversation_key() {
+        let secret_key =
+            SecretKey::from_str("0000000000000000000000000000000000000000000000000000000000000001")
+                .unwrap();
+        let other =
+            SecretKey::from_str("0000000000000000000000000000000000000000000000000000000000000002")
+                .unwrap();
+        let public_key = Keys::new(other).public_key();
+
+        let key: [u8; 32] = conversation_key(&secret_key, &public_key).unwrap();
+        assert_eq!(
+            util::hex::encode(key),
+            "c41c775356fd92eadc63ff5a0dc1da211b268cbea22316767095b2871ea1412d"
+        );
+
+        // Test vector
+        let payload = "AgAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABee0G5VSK0/9YypIObAtDKfYEAjD35uVkHyB0F4DwrcNaCXlCWZKaArsGrY6M9wnuTMxWfp1RTN9Xga8no+kF5Vsb";
+        assert_eq!(decrypt_with_key(&key, payload).unwrap(), "a");
+
+        // Interoperability with the high-level API
+        let encrypted: String = encrypt_with_key(&key, "hello").unwrap();
+        assert_eq!(
+            decrypt(&secret_key, &public_key, &encrypted).unwrap(),
+            "hello"
+        );
+        let encrypted: String = encrypt(&secret_key, &public_key, "hello", Version::V2).unwrap();
+        assert_eq!(decrypt_with_key(&key, &encrypted).unwrap(), "hello");
+
+        // V1 payloads are not supported
+        let payload =
+            "AUXEhLosA5eFMYOtumkiFW4Joq1OPmkU8k/25+3+VDFvOU39qkUDl1aiy8Q+0ozTwbhD57VJoIYayYS++hE=";
+        assert_eq!(
+            decrypt_with_key(&key, payload).unwrap_err(),
+            Error::UnknownVersion(1)
+        );
+    }
 }
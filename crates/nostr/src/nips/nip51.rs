@@ -5,13 +5,142 @@
 //!
 //! <https://github.com/nostr-protocol/nips/blob/master/51.md>
 
-use alloc::string::String;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use core::fmt;
 
 use super::nip01::Coordinate;
-use crate::{EventId, PublicKey, Tag, UncheckedUrl, Url};
+#[cfg(feature = "nip04")]
+use super::nip04;
+#[cfg(feature = "nip44")]
+use super::nip44;
+#[cfg(feature = "nip44")]
+use crate::SecretKey;
+use crate::{Event, EventId, Kind, PublicKey, Tag, UncheckedUrl, Url};
+
+/// NIP51 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Invalid kind
+    InvalidKind,
+    /// Error serializing or deserializing the private entries
+    Json(String),
+    /// NIP04 error
+    #[cfg(feature = "nip04")]
+    NIP04(nip04::Error),
+    /// NIP44 error
+    #[cfg(feature = "nip44")]
+    NIP44(nip44::Error),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidKind => write!(f, "invalid kind"),
+            Self::Json(e) => write!(f, "Json: {e}"),
+            #[cfg(feature = "nip04")]
+            Self::NIP04(e) => write!(f, "NIP04: {e}"),
+            #[cfg(feature = "nip44")]
+            Self::NIP44(e) => write!(f, "NIP44: {e}"),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e.to_string())
+    }
+}
+
+#[cfg(feature = "nip04")]
+impl From<nip04::Error> for Error {
+    fn from(e: nip04::Error) -> Self {
+        Self::NIP04(e)
+    }
+}
+
+#[cfg(feature = "nip44")]
+impl From<nip44::Error> for Error {
+    fn from(e: nip44::Error) -> Self {
+        Self::NIP44(e)
+    }
+}
+
+/// Encrypt the private entries of a list to self (NIP44)
+///
+/// The result is the `content` of the list event.
+#[cfg(all(feature = "std", feature = "nip44"))]
+pub fn encrypt_private_entries(
+    secret_key: &SecretKey,
+    public_key: &PublicKey,
+    entries: &[Tag],
+) -> Result<String, Error> {
+    let json: String = serde_json::to_string(entries)?;
+    Ok(nip44::encrypt(
+        secret_key,
+        public_key,
+        json,
+        nip44::Version::default(),
+    )?)
+}
+
+/// Decrypt the private entries of a list (`content` of the list event)
+///
+/// Support also the legacy NIP04 encryption (requires `nip04` feature).
+#[cfg(feature = "nip44")]
+pub fn decrypt_private_entries(
+    secret_key: &SecretKey,
+    public_key: &PublicKey,
+    content: &str,
+) -> Result<Vec<Tag>, Error> {
+    if content.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    #[cfg(feature = "nip04")]
+    if content.contains("?iv=") {
+        let json: String = nip04::decrypt(secret_key, public_key, content)?;
+        return Ok(serde_json::from_str(&json)?);
+    }
+
+    let json: String = nip44::decrypt(secret_key, public_key, content)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+macro_rules! impl_from_event {
+    ($name:ident, $($kind:path),+) => {
+        impl $name {
+            /// Parse the public entries from [`Event`]
+            pub fn from_event(event: &Event) -> Result<Self, Error> {
+                match event.kind() {
+                    $($kind)|+ => Ok(Self::from_tags(event.iter_tags())),
+                    _ => Err(Error::InvalidKind),
+                }
+            }
+
+            /// Decrypt and parse the private entries from [`Event`]
+            ///
+            /// The `secret_key` must be the one of the list author.
+            #[cfg(feature = "nip44")]
+            pub fn private_from_event(event: &Event, secret_key: &SecretKey) -> Result<Self, Error> {
+                match event.kind() {
+                    $($kind)|+ => {
+                        let entries: Vec<Tag> =
+                            decrypt_private_entries(secret_key, &event.author(), event.content())?;
+                        Ok(Self::from_tags(entries.iter()))
+                    }
+                    _ => Err(Error::InvalidKind),
+                }
+            }
+        }
+    };
+}
 
 /// Things the user doesn't want to see in their feeds
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct MuteList {
     /// Public Keys
     pub public_keys: Vec<PublicKey>,
@@ -23,6 +152,27 @@ pub struct MuteList {
     pub words: Vec<String>,
 }
 
+impl MuteList {
+    fn from_tags<'a, I>(tags: I) -> Self
+    where
+        I: Iterator<Item = &'a Tag>,
+    {
+        let mut list = Self::default();
+        for tag in tags {
+            match tag {
+                Tag::PublicKey { public_key, .. } => list.public_keys.push(*public_key),
+                Tag::Hashtag(hashtag) => list.hashtags.push(hashtag.clone()),
+                Tag::Event { event_id, .. } => list.event_ids.push(*event_id),
+                Tag::Word(word) => list.words.push(word.clone()),
+                _ => {}
+            }
+        }
+        list
+    }
+}
+
+impl_from_event!(MuteList, Kind::MuteList);
+
 impl From<MuteList> for Vec<Tag> {
     fn from(
         MuteList {
@@ -45,6 +195,7 @@ impl From<MuteList> for Vec<Tag> {
 }
 
 /// Uncategorized, "global" list of things a user wants to save
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Bookmarks {
     /// Event IDs
     pub event_ids: Vec<EventId>,
@@ -56,6 +207,27 @@ pub struct Bookmarks {
     pub urls: Vec<Url>,
 }
 
+impl Bookmarks {
+    fn from_tags<'a, I>(tags: I) -> Self
+    where
+        I: Iterator<Item = &'a Tag>,
+    {
+        let mut list = Self::default();
+        for tag in tags {
+            match tag {
+                Tag::Event { event_id, .. } => list.event_ids.push(*event_id),
+                Tag::A { coordinate, .. } => list.coordinate.push(coordinate.clone()),
+                Tag::Hashtag(hashtag) => list.hashtags.push(hashtag.clone()),
+                Tag::Url(url) => list.urls.push(url.clone()),
+                _ => {}
+            }
+        }
+        list
+    }
+}
+
+impl_from_event!(Bookmarks, Kind::Bookmarks, Kind::BookmarkSets);
+
 impl From<Bookmarks> for Vec<Tag> {
     fn from(
         Bookmarks {
@@ -78,6 +250,7 @@ impl From<Bookmarks> for Vec<Tag> {
 }
 
 /// Topics a user may be interested in and pointers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Interests {
     /// Hashtags
     pub hashtags: Vec<String>,
@@ -85,6 +258,25 @@ pub struct Interests {
     pub coordinate: Vec<Coordinate>,
 }
 
+impl Interests {
+    fn from_tags<'a, I>(tags: I) -> Self
+    where
+        I: Iterator<Item = &'a Tag>,
+    {
+        let mut list = Self::default();
+        for tag in tags {
+            match tag {
+                Tag::Hashtag(hashtag) => list.hashtags.push(hashtag.clone()),
+                Tag::A { coordinate, .. } => list.coordinate.push(coordinate.clone()),
+                _ => {}
+            }
+        }
+        list
+    }
+}
+
+impl_from_event!(Interests, Kind::Interests);
+
 impl From<Interests> for Vec<Tag> {
     fn from(
         Interests {
@@ -147,3 +339,136 @@ impl From<ArticlesCuration> for Vec<Tag> {
         tags
     }
 }
+
+/// Interest list (`kind:10015`) or interest set (`kind:30015`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterestSet {
+    /// Set identifier (`None` for the interest list)
+    pub identifier: Option<String>,
+    /// Hashtags
+    pub hashtags: Vec<String>,
+}
+
+impl InterestSet {
+    /// Parse interest list or interest set from [`Event`]
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        let identifier: Option<String> = match event.kind {
+            Kind::Interests => None,
+            Kind::InterestSets => event.identifier().map(String::from),
+            _ => return Err(Error::InvalidKind),
+        };
+
+        let hashtags: Vec<String> = event
+            .iter_tags()
+            .filter_map(|tag| match tag {
+                Tag::Hashtag(hashtag) => Some(hashtag.clone()),
+                _ => None,
+            })
+            .collect();
+
+        Ok(Self {
+            identifier,
+            hashtags,
+        })
+    }
+
+    /// Get interests (hashtags)
+    pub fn interests(&self) -> Vec<&str> {
+        self.hashtags.iter().map(|h| h.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_interest_set() {
+        let keys = Keys::generate();
+
+        let event = EventBuilder::interest_set("rust", ["rust", "nostr"])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.kind, Kind::InterestSets);
+        let set = InterestSet::from_event(&event).unwrap();
+        assert_eq!(set.identifier.as_deref(), Some("rust"));
+        assert_eq!(set.interests(), vec!["rust", "nostr"]);
+
+        let event = EventBuilder::interest_list(["bitcoin"])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.kind, Kind::Interests);
+        let set = InterestSet::from_event(&event).unwrap();
+        assert_eq!(set.identifier, None);
+        assert_eq!(set.interests(), vec!["bitcoin"]);
+
+        let event = EventBuilder::text_note("hello", [])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(InterestSet::from_event(&event), Err(Error::InvalidKind));
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", feature = "nip44"))]
+    fn test_mute_list_private_entries() {
+        let keys = Keys::generate();
+        let public = MuteList {
+            public_keys: vec![Keys::generate().public_key()],
+            hashtags: vec![String::from("spam")],
+            ..Default::default()
+        };
+        let private = MuteList {
+            words: vec![String::from("secret")],
+            event_ids: vec![EventId::all_zeros()],
+            ..Default::default()
+        };
+
+        let event = EventBuilder::mute_list(public.clone())
+            .private_entries(&keys, private.clone())
+            .unwrap()
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.kind, Kind::MuteList);
+        assert_eq!(MuteList::from_event(&event).unwrap(), public);
+        assert_eq!(
+            MuteList::private_from_event(&event, keys.secret_key().unwrap()).unwrap(),
+            private
+        );
+
+        // Wrong key
+        let other = Keys::generate();
+        assert!(MuteList::private_from_event(&event, other.secret_key().unwrap()).is_err());
+
+        // No private entries
+        let event = EventBuilder::bookmarks(Bookmarks::default())
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(
+            Bookmarks::private_from_event(&event, keys.secret_key().unwrap()).unwrap(),
+            Bookmarks::default()
+        );
+        assert_eq!(
+            Interests::from_event(&event).unwrap_err(),
+            Error::InvalidKind
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_follow_set() {
+        let keys = Keys::generate();
+        let public_key = Keys::generate().public_key();
+        let event = EventBuilder::follow_set("friends", [public_key])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.kind, Kind::FollowSets);
+        assert_eq!(event.identifier(), Some("friends"));
+        assert_eq!(
+            event.public_keys().copied().collect::<Vec<_>>(),
+            vec![public_key]
+        );
+    }
+}
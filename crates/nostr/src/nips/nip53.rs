@@ -13,19 +13,28 @@ use core::str::FromStr;
 
 use bitcoin::secp256k1::schnorr::Signature;
 
-use crate::{ImageDimensions, PublicKey, Tag, Timestamp, UncheckedUrl};
+use crate::{Event, ImageDimensions, Kind, PublicKey, Tag, Timestamp, UncheckedUrl};
 
 /// NIP53 Error
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum Error {
     /// Unknown [`LiveEventMarker`]
     UnknownLiveEventMarker(String),
+    /// Not a live event
+    WrongKind,
+    /// Missing identifier (`d` tag)
+    MissingIdentifier,
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::UnknownLiveEventMarker(u) => write!(f, "Unknown live event marker: {u}"),
+            Self::WrongKind => write!(f, "wrong kind"),
+            Self::MissingIdentifier => write!(f, "missing identifier"),
         }
     }
 }
@@ -104,7 +113,7 @@ where
 }
 
 /// Live Event Host
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LiveEventHost {
     /// Host public key
     pub public_key: PublicKey,
@@ -115,6 +124,7 @@ pub struct LiveEventHost {
 }
 
 /// Live Event
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LiveEvent {
     /// Unique event ID
     pub id: String,
@@ -134,7 +144,7 @@ pub struct LiveEvent {
     pub starts: Option<Timestamp>,
     /// Ends at
     pub ends: Option<Timestamp>,
-    /// Current status
+    /// Current status (`None` if unknown)
     pub status: Option<LiveEventStatus>,
     /// Current participants
     pub current_participants: Option<u64>,
@@ -150,6 +160,96 @@ pub struct LiveEvent {
     pub participants: Vec<(PublicKey, Option<UncheckedUrl>)>,
 }
 
+impl LiveEvent {
+    /// New live event
+    pub fn new<S>(id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            title: None,
+            summary: None,
+            image: None,
+            hashtags: Vec::new(),
+            streaming: None,
+            recording: None,
+            starts: None,
+            ends: None,
+            status: None,
+            current_participants: None,
+            total_participants: None,
+            relays: Vec::new(),
+            host: None,
+            speakers: Vec::new(),
+            participants: Vec::new(),
+        }
+    }
+
+    /// Parse from [`Event`]
+    ///
+    /// The same public key can have more roles (i.e. host and speaker).
+    /// Only the first host is kept.
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        if event.kind() != Kind::LiveEvent {
+            return Err(Error::WrongKind);
+        }
+
+        let mut live_event = Self::new("");
+        let mut identifier: Option<String> = None;
+
+        for tag in event.iter_tags() {
+            match tag {
+                Tag::Identifier(id) => identifier = Some(id.clone()),
+                Tag::Title(title) => live_event.title = Some(title.clone()),
+                Tag::Summary(summary) => live_event.summary = Some(summary.clone()),
+                Tag::Image(url, dim) => live_event.image = Some((url.clone(), *dim)),
+                Tag::Hashtag(hashtag) => live_event.hashtags.push(hashtag.clone()),
+                Tag::Streaming(url) => live_event.streaming = Some(url.clone()),
+                Tag::Recording(url) => live_event.recording = Some(url.clone()),
+                Tag::Starts(starts) => live_event.starts = Some(*starts),
+                Tag::Ends(ends) => live_event.ends = Some(*ends),
+                Tag::LiveEventStatus(status) => live_event.status = Some(status.clone()),
+                Tag::CurrentParticipants(n) => live_event.current_participants = Some(*n),
+                Tag::TotalParticipants(n) => live_event.total_participants = Some(*n),
+                Tag::Relays(relays) => live_event.relays.extend(relays.iter().cloned()),
+                Tag::PubKeyLiveEvent {
+                    public_key,
+                    relay_url,
+                    marker,
+                    proof,
+                } => {
+                    let relay_url: Option<UncheckedUrl> = relay_url
+                        .as_ref()
+                        .filter(|url| **url != UncheckedUrl::empty())
+                        .cloned();
+                    match marker {
+                        LiveEventMarker::Host => {
+                            if live_event.host.is_none() {
+                                live_event.host = Some(LiveEventHost {
+                                    public_key: *public_key,
+                                    relay_url,
+                                    proof: *proof,
+                                });
+                            }
+                        }
+                        LiveEventMarker::Speaker => {
+                            live_event.speakers.push((*public_key, relay_url))
+                        }
+                        LiveEventMarker::Participant => {
+                            live_event.participants.push((*public_key, relay_url))
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        live_event.id = identifier.ok_or(Error::MissingIdentifier)?;
+        Ok(live_event)
+    }
+}
+
 impl From<LiveEvent> for Vec<Tag> {
     fn from(live_event: LiveEvent) -> Self {
         let mut tags = Vec::new();
@@ -258,3 +358,53 @@ impl From<LiveEvent> for Vec<Tag> {
         tags
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_live_event_round_trip() {
+        let keys = Keys::generate();
+        let speaker = Keys::generate().public_key();
+
+        let mut live_event = LiveEvent::new("stream");
+        live_event.title = Some(String::from("Live coding"));
+        live_event.streaming = Some(UncheckedUrl::from("https://example.com/live.m3u8"));
+        live_event.status = Some(LiveEventStatus::Live);
+        live_event.starts = Some(Timestamp::from(1700000000));
+        live_event.current_participants = Some(42);
+        live_event.host = Some(LiveEventHost {
+            public_key: keys.public_key(),
+            relay_url: Some(UncheckedUrl::from("wss://relay.example.com")),
+            proof: None,
+        });
+        // Same public key with more roles
+        live_event.speakers.push((speaker, None));
+        live_event.participants.push((speaker, None));
+
+        let event = EventBuilder::live_event(live_event.clone())
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(LiveEvent::from_event(&event).unwrap(), live_event);
+    }
+
+    #[test]
+    fn test_live_event_missing_status() {
+        let keys = Keys::generate();
+        let event = EventBuilder::live_event(LiveEvent::new("stream"))
+            .to_event(&keys)
+            .unwrap();
+        let live_event = LiveEvent::from_event(&event).unwrap();
+        assert_eq!(live_event.status, None);
+
+        let event = EventBuilder::new(Kind::LiveEvent, "", [])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(
+            LiveEvent::from_event(&event).unwrap_err(),
+            Error::MissingIdentifier
+        );
+    }
+}
@@ -6,10 +6,15 @@
 //!
 //! <https://github.com/nostr-protocol/nips/blob/master/58.md>
 
+use alloc::string::String;
 use alloc::vec::Vec;
 use core::fmt;
 
-use crate::{Event, Kind, PublicKey, Tag, UncheckedUrl};
+use crate::nips::nip01::Coordinate;
+use crate::{Event, EventId, ImageDimensions, Kind, PublicKey, Tag, UncheckedUrl};
+
+/// Identifier of the profile badges event
+pub const PROFILE_BADGES_IDENTIFIER: &str = "profile_badges";
 
 #[derive(Debug)]
 /// [`BadgeAward`](crate::event::kind::Kind#variant.BadgeAward) error
@@ -44,6 +49,127 @@ impl fmt::Display for Error {
     }
 }
 
+/// Badge definition
+///
+/// Parsed [`Kind::BadgeDefinition`] event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadgeDefinition {
+    /// Badge ID (`d` tag)
+    pub id: String,
+    /// Author of the definition
+    pub author: PublicKey,
+    /// Name
+    pub name: Option<String>,
+    /// Description
+    pub description: Option<String>,
+    /// Image
+    pub image: Option<(UncheckedUrl, Option<ImageDimensions>)>,
+    /// Thumbnails
+    pub thumbnails: Vec<(UncheckedUrl, Option<ImageDimensions>)>,
+}
+
+impl BadgeDefinition {
+    /// Parse [`Kind::BadgeDefinition`] event
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        if event.kind() != Kind::BadgeDefinition {
+            return Err(Error::InvalidKind);
+        }
+
+        let mut id: Option<String> = None;
+        let mut name: Option<String> = None;
+        let mut description: Option<String> = None;
+        let mut image: Option<(UncheckedUrl, Option<ImageDimensions>)> = None;
+        let mut thumbnails: Vec<(UncheckedUrl, Option<ImageDimensions>)> = Vec::new();
+
+        for tag in event.iter_tags() {
+            match tag {
+                Tag::Identifier(value) => id = Some(value.clone()),
+                Tag::Name(value) => name = Some(value.clone()),
+                Tag::Description(value) => description = Some(value.clone()),
+                Tag::Image(url, dimensions) => image = Some((url.clone(), *dimensions)),
+                Tag::Thumb(url, dimensions) => thumbnails.push((url.clone(), *dimensions)),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            id: id.ok_or(Error::IdentifierTagNotFound)?,
+            author: event.author(),
+            name,
+            description,
+            image,
+            thumbnails,
+        })
+    }
+
+    /// Get the coordinate of the definition, referenced by the `a` tag of the awards
+    pub fn coordinate(&self) -> Coordinate {
+        Coordinate::new(Kind::BadgeDefinition, self.author).identifier(self.id.clone())
+    }
+}
+
+/// Badge displayed on a profile
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProfileBadge {
+    /// Coordinate of the badge definition
+    pub definition_coordinate: Coordinate,
+    /// ID of the badge award event
+    pub award_event_id: EventId,
+}
+
+impl ProfileBadge {
+    /// New profile badge
+    pub fn new(definition_coordinate: Coordinate, award_event_id: EventId) -> Self {
+        Self {
+            definition_coordinate,
+            award_event_id,
+        }
+    }
+}
+
+/// Profile badges
+///
+/// Badges that a user chose to display on the profile, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileBadges {
+    badges: Vec<ProfileBadge>,
+}
+
+impl ProfileBadges {
+    /// Parse [`Kind::ProfileBadges`] event
+    ///
+    /// Only the consecutive `a` and `e` tags pairs are considered: unpaired tags are ignored.
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        if event.kind() != Kind::ProfileBadges {
+            return Err(Error::InvalidKind);
+        }
+
+        if event.identifier() != Some(PROFILE_BADGES_IDENTIFIER) {
+            return Err(Error::IdentifierTagNotFound);
+        }
+
+        let tags: &[Tag] = event.tags();
+        let badges: Vec<ProfileBadge> = tags
+            .windows(2)
+            .filter_map(|pair| match pair {
+                [Tag::A { coordinate, .. }, Tag::Event { event_id, .. }]
+                    if coordinate.kind == Kind::BadgeDefinition =>
+                {
+                    Some(ProfileBadge::new(coordinate.clone(), *event_id))
+                }
+                _ => None,
+            })
+            .collect();
+
+        Ok(Self { badges })
+    }
+
+    /// Get badges
+    pub fn badges(&self) -> Vec<ProfileBadge> {
+        self.badges.clone()
+    }
+}
+
 /// Helper function to filter events for a specific [`Kind`]
 pub(crate) fn filter_for_kind(events: Vec<Event>, kind_needed: &Kind) -> Vec<Event> {
     events
@@ -66,3 +192,48 @@ pub(crate) fn extract_awarded_public_key(
         _ => None,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_badge_definition_from_event() {
+        let keys = Keys::generate();
+        let image = UncheckedUrl::from("https://nostr.build/i/image.png");
+        let thumb = UncheckedUrl::from("https://nostr.build/i/thumb.png");
+        let event = EventBuilder::define_badge(
+            "bravery",
+            Some("Bravery"),
+            None,
+            Some(image.clone()),
+            Some(ImageDimensions::new(1024, 1024)),
+            vec![(thumb.clone(), None)],
+        )
+        .to_event(&keys)
+        .unwrap();
+
+        let definition = BadgeDefinition::from_event(&event).unwrap();
+        assert_eq!(definition.id, "bravery");
+        assert_eq!(definition.name.as_deref(), Some("Bravery"));
+        assert_eq!(definition.description, None);
+        assert_eq!(
+            definition.image,
+            Some((image, Some(ImageDimensions::new(1024, 1024))))
+        );
+        assert_eq!(definition.thumbnails, vec![(thumb, None)]);
+        assert_eq!(
+            definition.coordinate(),
+            Coordinate::new(Kind::BadgeDefinition, keys.public_key()).identifier("bravery")
+        );
+
+        let event = EventBuilder::new(Kind::TextNote, "", [])
+            .to_event(&keys)
+            .unwrap();
+        assert!(matches!(
+            BadgeDefinition::from_event(&event),
+            Err(Error::InvalidKind)
+        ));
+    }
+}
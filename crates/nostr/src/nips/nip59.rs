@@ -11,12 +11,17 @@ use core::fmt;
 use bitcoin::secp256k1::{Secp256k1, Verification};
 
 use super::nip44;
+#[cfg(feature = "std")]
+use super::nip44::Version;
+use crate::event::builder;
+#[cfg(feature = "std")]
+use crate::event::builder::EventBuilder;
 use crate::event::unsigned::{self, UnsignedEvent};
 use crate::event::{self, Event};
 use crate::key::{self, Keys, SecretKey};
-#[cfg(feature = "std")]
-use crate::SECP256K1;
 use crate::{JsonUtil, Kind, PublicKey};
+#[cfg(feature = "std")]
+use crate::{Tag, Timestamp, SECP256K1};
 
 /// NIP59 error
 #[derive(Debug, PartialEq, Eq)]
@@ -29,8 +34,14 @@ pub enum Error {
     Unsigned(unsigned::Error),
     /// NIP44 error
     NIP44(nip44::Error),
+    /// Event builder error
+    EventBuilder(String),
     /// Not Gift Wrap event
     NotGiftWrap,
+    /// Not Seal event
+    NotSeal,
+    /// The public key of the rumor doesn't match the `seal` author
+    SenderMismatch,
 }
 
 #[cfg(feature = "std")]
@@ -43,7 +54,10 @@ impl fmt::Display for Error {
             Self::Event(e) => write!(f, "Event: {e}"),
             Self::Unsigned(e) => write!(f, "Unsigned event: {e}"),
             Self::NIP44(e) => write!(f, "NIP44: {e}"),
+            Self::EventBuilder(e) => write!(f, "Event builder: {e}"),
             Self::NotGiftWrap => write!(f, "Not Gift Wrap event"),
+            Self::NotSeal => write!(f, "Not Seal event"),
+            Self::SenderMismatch => write!(f, "Rumor public key doesn't match the seal author"),
         }
     }
 }
@@ -72,6 +86,12 @@ impl From<nip44::Error> for Error {
     }
 }
 
+impl From<builder::Error> for Error {
+    fn from(e: builder::Error) -> Self {
+        Self::EventBuilder(e.to_string())
+    }
+}
+
 /// Unwrapped Gift Wrap (NIP59)
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct UnwrappedGift {
@@ -129,6 +149,126 @@ pub fn extract_rumor(receiver_keys: &Keys, gift_wrap: &Event) -> Result<Unwrappe
     UnwrappedGift::from_gift_wrap(receiver_keys, gift_wrap)
 }
 
+/// Seal the `rumor` for the recipient (kind `13`)
+///
+/// The `rumor` author must be the sender.
+#[cfg(feature = "std")]
+pub fn seal(
+    rumor: UnsignedEvent,
+    sender_keys: &Keys,
+    recipient_pubkey: &PublicKey,
+) -> Result<Event, Error> {
+    if rumor.pubkey != sender_keys.public_key() {
+        return Err(Error::SenderMismatch);
+    }
+
+    let content: String = nip44::encrypt(
+        sender_keys.secret_key()?,
+        recipient_pubkey,
+        rumor.as_json(),
+        Version::default(),
+    )?;
+    Ok(EventBuilder::new(Kind::Seal, content, [])
+        .custom_created_at(Timestamp::tweaked())
+        .to_event(sender_keys)?)
+}
+
+/// Open a `seal` (kind `13`), returning the `rumor`
+///
+/// Internally verify the `seal` event and check that the `rumor` author is the `seal` author.
+#[cfg(feature = "std")]
+pub fn unseal(sealed: &Event, recipient_keys: &Keys) -> Result<UnsignedEvent, Error> {
+    unseal_with_ctx(&SECP256K1, sealed, recipient_keys)
+}
+
+/// Open a `seal` (kind `13`), returning the `rumor`
+///
+/// Internally verify the `seal` event and check that the `rumor` author is the `seal` author.
+pub fn unseal_with_ctx<C>(
+    secp: &Secp256k1<C>,
+    sealed: &Event,
+    recipient_keys: &Keys,
+) -> Result<UnsignedEvent, Error>
+where
+    C: Verification,
+{
+    if sealed.kind != Kind::Seal {
+        return Err(Error::NotSeal);
+    }
+
+    sealed.verify_with_ctx(secp)?;
+
+    let secret_key: &SecretKey = recipient_keys.secret_key()?;
+    let rumor: String = nip44::decrypt(secret_key, sealed.author_ref(), sealed.content())?;
+    let rumor: UnsignedEvent = UnsignedEvent::from_json(rumor)?;
+
+    if rumor.pubkey != sealed.author() {
+        return Err(Error::SenderMismatch);
+    }
+
+    Ok(rumor)
+}
+
+/// Wrap a `seal` for the recipient (kind `1059`), signing with one-time `random_keys`
+#[cfg(feature = "std")]
+pub fn wrap(
+    sealed: Event,
+    random_keys: &Keys,
+    recipient_pubkey: &PublicKey,
+) -> Result<Event, Error> {
+    if sealed.kind != Kind::Seal {
+        return Err(Error::NotSeal);
+    }
+
+    let content: String = nip44::encrypt(
+        random_keys.secret_key()?,
+        recipient_pubkey,
+        sealed.as_json(),
+        Version::default(),
+    )?;
+    Ok(EventBuilder::new(
+        Kind::GiftWrap,
+        content,
+        [Tag::public_key(*recipient_pubkey)],
+    )
+    .custom_created_at(Timestamp::tweaked())
+    .to_event(random_keys)?)
+}
+
+/// Unwrap a Gift Wrap (kind `1059`) and open its `seal`
+///
+/// Unlike [`UnwrappedGift::from_gift_wrap`], check also that the `rumor` author is the `seal` author.
+#[cfg(feature = "std")]
+pub fn unwrap(wrapped: &Event, recipient_keys: &Keys) -> Result<UnwrappedGift, Error> {
+    unwrap_with_ctx(&SECP256K1, wrapped, recipient_keys)
+}
+
+/// Unwrap a Gift Wrap (kind `1059`) and open its `seal`
+///
+/// Unlike [`UnwrappedGift::from_gift_wrap_with_ctx`], check also that the `rumor` author is the `seal` author.
+pub fn unwrap_with_ctx<C>(
+    secp: &Secp256k1<C>,
+    wrapped: &Event,
+    recipient_keys: &Keys,
+) -> Result<UnwrappedGift, Error>
+where
+    C: Verification,
+{
+    if wrapped.kind != Kind::GiftWrap {
+        return Err(Error::NotGiftWrap);
+    }
+
+    let secret_key: &SecretKey = recipient_keys.secret_key()?;
+    let sealed: String = nip44::decrypt(secret_key, wrapped.author_ref(), wrapped.content())?;
+    let sealed: Event = Event::from_json(sealed)?;
+    let rumor: UnsignedEvent = unseal_with_ctx(secp, &sealed, recipient_keys)?;
+
+    Ok(UnwrappedGift {
+        sender: sealed.author(),
+        rumor,
+    })
+}
+
 #[cfg(feature = "std")]
 #[cfg(test)]
 mod tests {
@@ -137,6 +277,11 @@ mod tests {
     use super::*;
     use crate::EventBuilder;
 
+    const SENDER_SECRET_KEY: &str =
+        "6b911fd37cdf5c81d4c0adb1ab7fa822ed253ab0ad9aa18d77257c88b29b718e";
+    const RECEIVER_SECRET_KEY: &str =
+        "7b911fd37cdf5c81d4c0adb1ab7fa822ed253ab0ad9aa18d77257c88b29b718e";
+
     #[test]
     fn test_extract_rumor() {
         let sender_keys = Keys::new(
@@ -175,4 +320,95 @@ mod tests {
             Error::NotGiftWrap
         );
     }
+
+    #[test]
+    fn test_seal_wrap_roundtrip() {
+        let sender_keys = Keys::parse(SENDER_SECRET_KEY).unwrap();
+        let receiver_keys = Keys::parse(RECEIVER_SECRET_KEY).unwrap();
+        let random_keys = Keys::generate();
+
+        let rumor: UnsignedEvent =
+            EventBuilder::text_note("Are you going to the party tonight?", [])
+                .to_unsigned_event(sender_keys.public_key());
+
+        // Seal
+        let sealed: Event = seal(rumor.clone(), &sender_keys, &receiver_keys.public_key()).unwrap();
+        assert_eq!(sealed.kind, Kind::Seal);
+        assert_eq!(sealed.author(), sender_keys.public_key());
+        assert!(sealed.tags.is_empty());
+        assert!(sealed.verify().is_ok());
+        assert_eq!(unseal(&sealed, &receiver_keys).unwrap(), rumor);
+        assert!(unseal(&sealed, &random_keys).is_err());
+
+        // Wrap
+        let wrapped: Event =
+            wrap(sealed.clone(), &random_keys, &receiver_keys.public_key()).unwrap();
+        assert_eq!(wrapped.kind, Kind::GiftWrap);
+        assert_eq!(wrapped.author(), random_keys.public_key());
+        assert_eq!(
+            wrapped.tags.to_vec(),
+            vec![Tag::public_key(receiver_keys.public_key())]
+        );
+        assert!(wrapped.verify().is_ok());
+
+        // Unwrap
+        let unwrapped: UnwrappedGift = unwrap(&wrapped, &receiver_keys).unwrap();
+        assert_eq!(
+            unwrapped,
+            UnwrappedGift {
+                sender: sender_keys.public_key(),
+                rumor: rumor.clone(),
+            }
+        );
+        assert_eq!(
+            UnwrappedGift::from_gift_wrap(&receiver_keys, &wrapped).unwrap(),
+            unwrapped
+        );
+        assert!(unwrap(&wrapped, &sender_keys).is_err());
+        assert!(unwrap(&wrapped, &random_keys).is_err());
+
+        // Wrong kinds
+        assert_eq!(
+            unwrap(&sealed, &receiver_keys).unwrap_err(),
+            Error::NotGiftWrap
+        );
+        assert_eq!(
+            unseal(&wrapped, &receiver_keys).unwrap_err(),
+            Error::NotSeal
+        );
+        assert_eq!(
+            wrap(wrapped, &random_keys, &receiver_keys.public_key()).unwrap_err(),
+            Error::NotSeal
+        );
+    }
+
+    #[test]
+    fn test_seal_sender_mismatch() {
+        let sender_keys = Keys::parse(SENDER_SECRET_KEY).unwrap();
+        let receiver_keys = Keys::parse(RECEIVER_SECRET_KEY).unwrap();
+
+        // Rumor of somebody else
+        let rumor: UnsignedEvent =
+            EventBuilder::text_note("Test", []).to_unsigned_event(receiver_keys.public_key());
+        assert_eq!(
+            seal(rumor.clone(), &sender_keys, &receiver_keys.public_key()).unwrap_err(),
+            Error::SenderMismatch
+        );
+
+        // Forged seal
+        let content: String = nip44::encrypt(
+            sender_keys.secret_key().unwrap(),
+            &receiver_keys.public_key(),
+            rumor.as_json(),
+            Version::default(),
+        )
+        .unwrap();
+        let forged: Event = EventBuilder::new(Kind::Seal, content, [])
+            .to_event(&sender_keys)
+            .unwrap();
+        assert_eq!(
+            unseal(&forged, &receiver_keys).unwrap_err(),
+            Error::SenderMismatch
+        );
+    }
 }
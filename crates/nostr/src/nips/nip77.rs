@@ -0,0 +1,164 @@
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP77
+//!
+//! Negentropy set reconciliation (client side).
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/77.md>
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use negentropy::{Bytes, Negentropy};
+
+use crate::{EventId, Timestamp};
+
+/// ID size
+pub const ID_SIZE: usize = 32;
+/// Frame size limit
+pub const FRAME_SIZE_LIMIT: u64 = 20_000;
+
+/// NIP77 error
+#[derive(Debug)]
+pub enum Error {
+    /// Negentropy error
+    Negentropy(negentropy::Error),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Negentropy(e) => write!(f, "Negentropy: {e}"),
+        }
+    }
+}
+
+impl From<negentropy::Error> for Error {
+    fn from(e: negentropy::Error) -> Self {
+        Self::Negentropy(e)
+    }
+}
+
+/// Reconciliation result
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NegentropyResult {
+    /// Events that the local set has and the relay needs
+    pub have: Vec<EventId>,
+    /// Events that the relay has and the local set needs
+    pub need: Vec<EventId>,
+    /// Next message to send to the relay (`None` if the reconciliation is complete)
+    pub next_message: Option<Vec<u8>>,
+}
+
+impl NegentropyResult {
+    /// Check if the reconciliation is complete
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.next_message.is_none()
+    }
+}
+
+/// Negentropy sync
+///
+/// Client side of the reconciliation: send the [`NegentropySync::initial_message`] to the relay
+/// (`NEG-OPEN`) and then pass every relay response (`NEG-MSG`) to [`NegentropySync::reconcile`],
+/// until the reconciliation is complete.
+pub struct NegentropySync {
+    negentropy: Negentropy,
+    initial_message: Vec<u8>,
+}
+
+impl fmt::Debug for NegentropySync {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NegentropySync")
+            .field("initial_message", &self.initial_message)
+            .finish()
+    }
+}
+
+impl NegentropySync {
+    /// New sync of the local events
+    pub fn new(local_events: Vec<(EventId, Timestamp)>) -> Result<Self, Error> {
+        let mut negentropy = Negentropy::new(ID_SIZE, Some(FRAME_SIZE_LIMIT))?;
+        for (id, timestamp) in local_events.into_iter() {
+            negentropy.add_item(timestamp.as_u64(), Bytes::from_slice(id.as_bytes()))?;
+        }
+        negentropy.seal()?;
+        let initial_message: Bytes = negentropy.initiate()?;
+        Ok(Self {
+            negentropy,
+            initial_message: initial_message.to_bytes(),
+        })
+    }
+
+    /// Initial message, to send with `NEG-OPEN`
+    pub fn initial_message(&self) -> Vec<u8> {
+        self.initial_message.clone()
+    }
+
+    /// Reconcile with a relay response
+    pub fn reconcile(&mut self, relay_response: &[u8]) -> Result<NegentropyResult, Error> {
+        let query: Bytes = Bytes::from_slice(relay_response);
+        let mut have_ids: Vec<Bytes> = Vec::new();
+        let mut need_ids: Vec<Bytes> = Vec::new();
+        let next_message: Option<Bytes> =
+            self.negentropy
+                .reconcile_with_ids(&query, &mut have_ids, &mut need_ids)?;
+        Ok(NegentropyResult {
+            have: into_event_ids(have_ids),
+            need: into_event_ids(need_ids),
+            next_message: next_message.map(|msg| msg.to_bytes()),
+        })
+    }
+}
+
+fn into_event_ids(ids: Vec<Bytes>) -> Vec<EventId> {
+    ids.into_iter()
+        .filter_map(|id| EventId::from_slice(id.as_bytes()).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(n: u8) -> EventId {
+        EventId::from_slice(&[n; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_negentropy_sync() {
+        let mut relay = Negentropy::new(ID_SIZE, Some(FRAME_SIZE_LIMIT)).unwrap();
+        for n in [1, 2, 3] {
+            relay
+                .add_item(n as u64, Bytes::from_slice(id(n).as_bytes()))
+                .unwrap();
+        }
+        relay.seal().unwrap();
+
+        let local = vec![(id(2), Timestamp::from(2)), (id(4), Timestamp::from(4))];
+        let mut sync = NegentropySync::new(local).unwrap();
+
+        let mut have: Vec<EventId> = Vec::new();
+        let mut need: Vec<EventId> = Vec::new();
+        let mut msg: Vec<u8> = sync.initial_message();
+        loop {
+            let response: Bytes = relay.reconcile(&Bytes::from(msg)).unwrap();
+            let result: NegentropyResult = sync.reconcile(response.as_bytes()).unwrap();
+            have.extend(result.have);
+            need.extend(result.need);
+            match result.next_message {
+                Some(next) => msg = next,
+                None => break,
+            }
+        }
+
+        need.sort();
+        assert_eq!(have, vec![id(4)]);
+        assert_eq!(need, vec![id(1), id(3)]);
+    }
+}
@@ -0,0 +1,118 @@
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP89
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/89.md>
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+use super::nip01::Coordinate;
+use crate::{Tag, TagKind, UncheckedUrl};
+
+/// Client tag kind
+pub const CLIENT_TAG: &str = "client";
+
+/// Client tag
+///
+/// Identify the app that published the event: `["client", <name>, <31990:pubkey:d>, <relay hint>]`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClientTag {
+    /// Client name
+    pub name: String,
+    /// Handler information (`kind:31990`) coordinate
+    pub handler: Option<Coordinate>,
+    /// Relay hint for the handler information
+    pub relay_hint: Option<UncheckedUrl>,
+}
+
+impl ClientTag {
+    /// New client tag
+    pub fn new<S>(name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            handler: None,
+            relay_hint: None,
+        }
+    }
+
+    /// Set handler information coordinate and relay hint
+    pub fn handler(mut self, handler: Coordinate, relay_hint: Option<UncheckedUrl>) -> Self {
+        self.handler = Some(handler);
+        self.relay_hint = relay_hint;
+        self
+    }
+
+    /// Check if [`Tag`] is a client tag
+    pub fn is_client_tag(tag: &Tag) -> bool {
+        matches!(tag, Tag::Generic(TagKind::Custom(kind), ..) if kind == CLIENT_TAG)
+    }
+
+    /// Parse client tag
+    ///
+    /// Return `None` if the tag is not a client tag.
+    pub fn from_tag(tag: &Tag) -> Option<Self> {
+        match tag {
+            Tag::Generic(TagKind::Custom(kind), values) if kind == CLIENT_TAG => {
+                let mut values = values.iter();
+                let name: String = values.next()?.clone();
+                let handler: Option<Coordinate> =
+                    values.next().and_then(|c| Coordinate::from_str(c).ok());
+                let relay_hint: Option<UncheckedUrl> = match handler {
+                    Some(..) => values.next().map(UncheckedUrl::from),
+                    None => None,
+                };
+                Some(Self {
+                    name,
+                    handler,
+                    relay_hint,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<ClientTag> for Tag {
+    fn from(client: ClientTag) -> Self {
+        let mut values: Vec<String> = Vec::with_capacity(3);
+        values.push(client.name);
+        if let Some(handler) = client.handler {
+            values.push(handler.to_string());
+            if let Some(relay_hint) = client.relay_hint {
+                values.push(relay_hint.to_string());
+            }
+        }
+        Self::Generic(TagKind::Custom(CLIENT_TAG.to_string()), values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Kind, PublicKey};
+
+    #[test]
+    fn test_client_tag() {
+        let public_key =
+            PublicKey::from_str("b2d670de53b27691c0c3400225b65c35a26d06093bcc41f48ffc71e0907f9d4a")
+                .unwrap();
+        let client = ClientTag::new("nostr-sdk").handler(
+            Coordinate::new(Kind::from(31990), public_key).identifier("1686066542546"),
+            Some(UncheckedUrl::from("wss://relay.damus.io")),
+        );
+        let tag: Tag = client.clone().into();
+        assert!(ClientTag::is_client_tag(&tag));
+        assert_eq!(ClientTag::from_tag(&tag), Some(client));
+
+        let tag = Tag::parse(&["client", "nostr-sdk"]).unwrap();
+        assert_eq!(ClientTag::from_tag(&tag), Some(ClientTag::new("nostr-sdk")));
+
+        assert_eq!(ClientTag::from_tag(&Tag::Hashtag("client".into())), None);
+    }
+}
@@ -6,20 +6,38 @@
 //!
 //! <https://github.com/nostr-protocol/nips/blob/master/90.md>
 
+use alloc::string::String;
 use core::fmt;
 use core::str::FromStr;
 
+use crate::{Event, EventId, Kind, PublicKey, Tag};
+
 /// DVM Error
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum Error {
     /// Unknown status
     UnknownStatus,
+    /// Wrong event kind
+    WrongKind(Kind),
+    /// Status tag not found
+    MissingStatus,
+    /// Job request event ID not found (no `e` tag)
+    MissingJobRequest,
+    /// Customer public key not found (no `p` tag)
+    MissingCustomer,
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::UnknownStatus => write!(f, "Unknown status"),
+            Self::WrongKind(kind) => write!(f, "Wrong kind: {kind}"),
+            Self::MissingStatus => write!(f, "missing status"),
+            Self::MissingJobRequest => write!(f, "missing job request event ID"),
+            Self::MissingCustomer => write!(f, "missing customer public key"),
         }
     }
 }
@@ -65,3 +83,174 @@ impl FromStr for DataVendingMachineStatus {
         }
     }
 }
+
+/// Data Vending Machine Job Feedback
+///
+/// <https://github.com/nostr-protocol/nips/blob/master/90.md>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataVendingMachineFeedback {
+    /// Job request event ID
+    pub job_request_id: EventId,
+    /// Customer public key
+    pub customer: PublicKey,
+    /// Status
+    pub status: DataVendingMachineStatus,
+    /// Extra info
+    pub extra_info: Option<String>,
+    /// Requested amount in millisats
+    pub amount_millisats: Option<u64>,
+    /// Bolt11 invoice
+    pub bolt11: Option<String>,
+    /// Payload (ex. partial results)
+    pub payload: Option<String>,
+}
+
+impl DataVendingMachineFeedback {
+    /// Parse [`Kind::JobFeedback`] event
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        if event.kind() != Kind::JobFeedback {
+            return Err(Error::WrongKind(event.kind()));
+        }
+
+        let mut job_request_id: Option<EventId> = None;
+        let mut customer: Option<PublicKey> = None;
+        let mut status: Option<(DataVendingMachineStatus, Option<String>)> = None;
+        let mut amount: Option<(u64, Option<String>)> = None;
+
+        for tag in event.iter_tags() {
+            match tag {
+                Tag::Event { event_id, .. } if job_request_id.is_none() => {
+                    job_request_id = Some(*event_id);
+                }
+                Tag::PublicKey {
+                    public_key,
+                    uppercase: false,
+                    ..
+                } if customer.is_none() => {
+                    customer = Some(*public_key);
+                }
+                Tag::DataVendingMachineStatus {
+                    status: s,
+                    extra_info,
+                } if status.is_none() => {
+                    status = Some((*s, extra_info.clone()));
+                }
+                Tag::Amount { millisats, bolt11 } if amount.is_none() => {
+                    amount = Some((*millisats, bolt11.clone()));
+                }
+                _ => {}
+            }
+        }
+
+        let (status, extra_info) = status.ok_or(Error::MissingStatus)?;
+        let (amount_millisats, bolt11) = match amount {
+            Some((millisats, bolt11)) => (Some(millisats), bolt11),
+            None => (None, None),
+        };
+        let content: &str = event.content();
+
+        Ok(Self {
+            job_request_id: job_request_id.ok_or(Error::MissingJobRequest)?,
+            customer: customer.ok_or(Error::MissingCustomer)?,
+            status,
+            extra_info,
+            amount_millisats,
+            bolt11,
+            payload: if content.is_empty() {
+                None
+            } else {
+                Some(content.to_string())
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_job_feedback() {
+        let customer = Keys::generate();
+        let provider = Keys::generate();
+
+        let job_request = EventBuilder::job_request(Kind::JobRequest(5001), [])
+            .unwrap()
+            .to_event(&customer)
+            .unwrap();
+        let event = EventBuilder::job_feedback(
+            &job_request,
+            DataVendingMachineStatus::PaymentRequired,
+            Some(String::from("pay first")),
+            Some(1000),
+            Some(String::from("lnbc1...")),
+            None,
+        )
+        .unwrap()
+        .to_event(&provider)
+        .unwrap();
+
+        let feedback = DataVendingMachineFeedback::from_event(&event).unwrap();
+        assert_eq!(
+            feedback,
+            DataVendingMachineFeedback {
+                job_request_id: job_request.id(),
+                customer: customer.public_key(),
+                status: DataVendingMachineStatus::PaymentRequired,
+                extra_info: Some(String::from("pay first")),
+                amount_millisats: Some(1000),
+                bolt11: Some(String::from("lnbc1...")),
+                payload: None,
+            }
+        );
+
+        let event = EventBuilder::job_feedback(
+            &job_request,
+            DataVendingMachineStatus::Partial,
+            None,
+            None,
+            None,
+            Some(String::from("partial result")),
+        )
+        .unwrap()
+        .to_event(&provider)
+        .unwrap();
+
+        let feedback = DataVendingMachineFeedback::from_event(&event).unwrap();
+        assert_eq!(feedback.status, DataVendingMachineStatus::Partial);
+        assert_eq!(feedback.amount_millisats, None);
+        assert_eq!(feedback.payload, Some(String::from("partial result")));
+
+        // Invoice without amount
+        assert!(matches!(
+            EventBuilder::job_feedback(
+                &job_request,
+                DataVendingMachineStatus::PaymentRequired,
+                None,
+                None,
+                Some(String::from("lnbc1...")),
+                None,
+            ),
+            Err(crate::event::builder::Error::InvoiceWithoutAmount)
+        ));
+
+        // Not a job request
+        let note = EventBuilder::text_note("hello", [])
+            .to_event(&customer)
+            .unwrap();
+        assert!(EventBuilder::job_feedback(
+            &note,
+            DataVendingMachineStatus::Processing,
+            None,
+            None,
+            None,
+            None
+        )
+        .is_err());
+        assert_eq!(
+            DataVendingMachineFeedback::from_event(&note).unwrap_err(),
+            Error::WrongKind(Kind::TextNote)
+        );
+    }
+}
@@ -9,13 +9,28 @@
 //!
 //! <https://github.com/nostr-protocol/nips/blob/master/98.md>
 
+#[cfg(all(feature = "std", feature = "nip98"))]
+use alloc::string::String;
 use alloc::vec::Vec;
 use core::fmt;
+#[cfg(all(feature = "std", feature = "nip98"))]
+use core::str::FromStr;
 
+#[cfg(all(feature = "std", feature = "nip98"))]
+use base64::engine::{general_purpose, Engine};
 use bitcoin::hashes::sha256::Hash as Sha256Hash;
+#[cfg(all(feature = "std", feature = "nip98"))]
+use bitcoin::hashes::Hash;
 
+use crate::event::{self, builder, tag};
+use crate::types::url::ParseError;
+#[cfg(all(feature = "std", feature = "nip98"))]
+use crate::{Event, EventBuilder, JsonUtil, Keys, Kind, PublicKey, Timestamp, Url};
 use crate::{HttpMethod, Tag, UncheckedUrl};
 
+/// Authorization header scheme
+pub const AUTH_SCHEME: &str = "Nostr";
+
 /// [`HttpData`] required tags
 #[derive(Debug)]
 pub enum RequiredTags {
@@ -41,6 +56,26 @@ pub enum Error {
     Hex(bitcoin::hashes::hex::HexToBytesError),
     /// Tag missing when parsing
     MissingTag(RequiredTags),
+    /// Tag error
+    Tag(tag::Error),
+    /// Event error
+    Event(event::Error),
+    /// Event builder error
+    EventBuilder(builder::Error),
+    /// Url parse error
+    Url(ParseError),
+    /// Invalid `Authorization` header
+    InvalidHeader,
+    /// Error while decoding from base64
+    Base64Decode,
+    /// Wrong event kind
+    WrongKind,
+    /// Event `created_at` outside of the allowed time window
+    Expired,
+    /// Url not match the expected one
+    UrlMismatch,
+    /// Method not match the expected one
+    MethodMismatch,
 }
 
 #[cfg(feature = "std")]
@@ -51,6 +86,16 @@ impl fmt::Display for Error {
         match self {
             Self::Hex(e) => write!(f, "{e}"),
             Self::MissingTag(tag) => write!(f, r#"missing tag "{tag}""#),
+            Self::Tag(e) => write!(f, "Tag: {e}"),
+            Self::Event(e) => write!(f, "Event: {e}"),
+            Self::EventBuilder(e) => write!(f, "Event builder: {e}"),
+            Self::Url(e) => write!(f, "Url: {e}"),
+            Self::InvalidHeader => write!(f, "invalid authorization header"),
+            Self::Base64Decode => write!(f, "error while decoding from base64"),
+            Self::WrongKind => write!(f, "wrong event kind"),
+            Self::Expired => write!(f, "event expired"),
+            Self::UrlMismatch => write!(f, "url not match"),
+            Self::MethodMismatch => write!(f, "method not match"),
         }
     }
 }
@@ -61,6 +106,30 @@ impl From<bitcoin::hashes::hex::HexToBytesError> for Error {
     }
 }
 
+impl From<tag::Error> for Error {
+    fn from(e: tag::Error) -> Self {
+        Self::Tag(e)
+    }
+}
+
+impl From<event::Error> for Error {
+    fn from(e: event::Error) -> Self {
+        Self::Event(e)
+    }
+}
+
+impl From<builder::Error> for Error {
+    fn from(e: builder::Error) -> Self {
+        Self::EventBuilder(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Self::Url(e)
+    }
+}
+
 /// HTTP Data
 pub struct HttpData {
     /// Absolute request URL
@@ -142,3 +211,113 @@ impl TryFrom<Vec<Tag>> for HttpData {
         })
     }
 }
+
+/// Create HTTP Auth event (`kind:27235`)
+///
+/// `payload_hash` is the SHA256 hash of the request body.
+#[cfg(all(feature = "std", feature = "nip98"))]
+pub fn create_auth_event(
+    url: &Url,
+    method: &str,
+    payload_hash: Option<[u8; 32]>,
+    keys: &Keys,
+) -> Result<Event, Error> {
+    let method: HttpMethod = HttpMethod::from_str(&method.to_uppercase())?;
+    let mut data: HttpData = HttpData::new(UncheckedUrl::from(url.to_string()), method);
+    if let Some(hash) = payload_hash {
+        data = data.payload(Sha256Hash::from_byte_array(hash));
+    }
+    Ok(EventBuilder::http_auth(data).to_event(keys)?)
+}
+
+/// Build the `Authorization` header value (`Nostr <base64-event-json>`)
+#[cfg(all(feature = "std", feature = "nip98"))]
+pub fn auth_header(event: &Event) -> String {
+    let encoded: String = general_purpose::STANDARD.encode(event.as_json());
+    format!("{AUTH_SCHEME} {encoded}")
+}
+
+/// Verify the `Authorization` header of an HTTP request
+///
+/// Check the signature, the kind, the `url` and `method` tags and that `created_at`
+/// is within `max_age_secs` from now. Return the [`PublicKey`] of the author.
+///
+/// The `payload` tag is **not** checked: compare it with the hash of the request body if required.
+#[cfg(all(feature = "std", feature = "nip98"))]
+pub fn verify_auth_header(
+    header: &str,
+    expected_url: &Url,
+    expected_method: &str,
+    max_age_secs: u64,
+) -> Result<PublicKey, Error> {
+    let (scheme, encoded) = header.trim().split_once(' ').ok_or(Error::InvalidHeader)?;
+    if !scheme.eq_ignore_ascii_case(AUTH_SCHEME) {
+        return Err(Error::InvalidHeader);
+    }
+
+    let json: Vec<u8> = general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|_| Error::Base64Decode)?;
+    let event: Event = Event::from_json(json)?;
+    event.verify()?;
+
+    if event.kind != Kind::HttpAuth {
+        return Err(Error::WrongKind);
+    }
+
+    let now: Timestamp = Timestamp::now();
+    let age: u64 = now.as_u64().abs_diff(event.created_at.as_u64());
+    if age > max_age_secs {
+        return Err(Error::Expired);
+    }
+
+    let data: HttpData = HttpData::try_from(event.tags.to_vec())?;
+
+    let url: Url = Url::try_from(data.url)?;
+    if &url != expected_url {
+        return Err(Error::UrlMismatch);
+    }
+
+    if !data
+        .method
+        .to_string()
+        .eq_ignore_ascii_case(expected_method)
+    {
+        return Err(Error::MethodMismatch);
+    }
+
+    Ok(event.pubkey)
+}
+
+#[cfg(all(test, feature = "std", feature = "nip98"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auth_header() {
+        let keys = Keys::generate();
+        let url = Url::parse("https://api.example.com/upload").unwrap();
+        let event = create_auth_event(&url, "post", Some([0u8; 32]), &keys).unwrap();
+        let header: String = auth_header(&event);
+        assert!(header.starts_with("Nostr "));
+
+        assert_eq!(
+            verify_auth_header(&header, &url, "POST", 60).unwrap(),
+            keys.public_key()
+        );
+
+        let other = Url::parse("https://api.example.com/other").unwrap();
+        assert!(matches!(
+            verify_auth_header(&header, &other, "POST", 60),
+            Err(Error::UrlMismatch)
+        ));
+        assert!(matches!(
+            verify_auth_header(&header, &url, "GET", 60),
+            Err(Error::MethodMismatch)
+        ));
+        assert!(matches!(
+            verify_auth_header("Bearer abc", &url, "POST", 60),
+            Err(Error::InvalidHeader)
+        ));
+    }
+}
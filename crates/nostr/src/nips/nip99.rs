@@ -0,0 +1,311 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP99
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/99.md>
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::{Event, Kind, Tag, TagKind, Timestamp, UncheckedUrl};
+
+/// NIP99 Error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Not a classified listing event
+    WrongKind,
+    /// Missing identifier (`d` tag)
+    MissingIdentifier,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongKind => write!(f, "wrong kind"),
+            Self::MissingIdentifier => write!(f, "missing identifier"),
+        }
+    }
+}
+
+/// Listing Status
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ListingStatus {
+    /// Active
+    Active,
+    /// Sold
+    Sold,
+    /// Draft
+    ///
+    /// Not yet ready for public display: other users' clients should ignore it.
+    Draft,
+    /// Custom
+    Custom(String),
+}
+
+impl fmt::Display for ListingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Active => write!(f, "active"),
+            Self::Sold => write!(f, "sold"),
+            Self::Draft => write!(f, "draft"),
+            Self::Custom(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+impl<S> From<S> for ListingStatus
+where
+    S: Into<String>,
+{
+    fn from(s: S) -> Self {
+        let s: String = s.into();
+        match s.as_str() {
+            "active" => Self::Active,
+            "sold" => Self::Sold,
+            "draft" => Self::Draft,
+            _ => Self::Custom(s),
+        }
+    }
+}
+
+/// Listing price
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Price {
+    /// Amount
+    pub amount: String,
+    /// Currency (ISO 4217 or crypto currency code)
+    pub currency: String,
+    /// Optional frequency (ex. `month`)
+    pub frequency: Option<String>,
+}
+
+/// Classified Listing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassifiedListing {
+    /// Unique identifier (`d` tag)
+    pub identifier: String,
+    /// Title
+    pub title: Option<String>,
+    /// Short tagline or summary
+    pub summary: Option<String>,
+    /// Description (markdown)
+    pub content: String,
+    /// First published timestamp
+    pub published_at: Option<Timestamp>,
+    /// Location
+    pub location: Option<String>,
+    /// Price
+    pub price: Option<Price>,
+    /// Status
+    pub status: ListingStatus,
+    /// Images
+    pub images: Vec<UncheckedUrl>,
+    /// Hashtags
+    pub hashtags: Vec<String>,
+}
+
+impl ClassifiedListing {
+    /// New active [`ClassifiedListing`]
+    pub fn new<S, C>(identifier: S, content: C) -> Self
+    where
+        S: Into<String>,
+        C: Into<String>,
+    {
+        Self {
+            identifier: identifier.into(),
+            title: None,
+            summary: None,
+            content: content.into(),
+            published_at: None,
+            location: None,
+            price: None,
+            status: ListingStatus::Active,
+            images: Vec::new(),
+            hashtags: Vec::new(),
+        }
+    }
+
+    /// Parse from [`Event`]
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        if event.kind() != Kind::ClassifiedListing {
+            return Err(Error::WrongKind);
+        }
+
+        let mut listing = Self::new("", event.content());
+        let mut identifier: Option<String> = None;
+
+        for tag in event.iter_tags() {
+            let tag: Vec<String> = tag.as_vec();
+            let value: Option<String> = tag.get(1).cloned();
+            match (tag.first().map(|k| k.as_str()), value) {
+                (Some("d"), Some(value)) => identifier = Some(value),
+                (Some("title"), Some(value)) => listing.title = Some(value),
+                (Some("summary"), Some(value)) => listing.summary = Some(value),
+                (Some("published_at"), Some(value)) => {
+                    listing.published_at = Timestamp::from_str(&value).ok()
+                }
+                (Some("location"), Some(value)) => listing.location = Some(value),
+                (Some("price"), Some(amount)) => {
+                    listing.price = Some(Price {
+                        amount,
+                        currency: tag.get(2).cloned().unwrap_or_default(),
+                        frequency: tag.get(3).cloned(),
+                    })
+                }
+                (Some("status"), Some(value)) => listing.status = ListingStatus::from(value),
+                (Some("image"), Some(value)) => listing.images.push(UncheckedUrl::from(value)),
+                (Some("t"), Some(value)) => listing.hashtags.push(value),
+                _ => {}
+            }
+        }
+
+        listing.identifier = identifier.ok_or(Error::MissingIdentifier)?;
+        Ok(listing)
+    }
+
+    /// Check if is visible to other users (only [`ListingStatus::Active`] listings)
+    #[inline]
+    pub fn is_visible(&self) -> bool {
+        self.status == ListingStatus::Active
+    }
+
+    /// Check if is a draft
+    #[inline]
+    pub fn is_draft(&self) -> bool {
+        self.status == ListingStatus::Draft
+    }
+}
+
+impl From<ClassifiedListing> for Vec<Tag> {
+    fn from(listing: ClassifiedListing) -> Self {
+        let ClassifiedListing {
+            identifier,
+            title,
+            summary,
+            content: _,
+            published_at,
+            location,
+            price,
+            status,
+            images,
+            hashtags,
+        } = listing;
+
+        let mut tags: Vec<Tag> = vec![Tag::Identifier(identifier)];
+
+        if let Some(title) = title {
+            tags.push(Tag::Title(title));
+        }
+
+        if let Some(summary) = summary {
+            tags.push(Tag::Summary(summary));
+        }
+
+        if let Some(published_at) = published_at {
+            tags.push(Tag::PublishedAt(published_at));
+        }
+
+        if let Some(location) = location {
+            tags.push(Tag::Generic(
+                TagKind::Custom(String::from("location")),
+                vec![location],
+            ));
+        }
+
+        if let Some(Price {
+            amount,
+            currency,
+            frequency,
+        }) = price
+        {
+            let mut values: Vec<String> = vec![amount, currency];
+            if let Some(frequency) = frequency {
+                values.push(frequency);
+            }
+            tags.push(Tag::Generic(TagKind::Custom(String::from("price")), values));
+        }
+
+        tags.push(Tag::Generic(TagKind::Status, vec![status.to_string()]));
+
+        for image in images.into_iter() {
+            tags.push(Tag::Image(image, None));
+        }
+
+        for hashtag in hashtags.into_iter() {
+            tags.push(Tag::Hashtag(hashtag));
+        }
+
+        tags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_listing_status() {
+        assert_eq!(ListingStatus::from("draft"), ListingStatus::Draft);
+        assert_eq!(ListingStatus::Draft.to_string(), "draft");
+        assert_eq!(
+            ListingStatus::from("reserved"),
+            ListingStatus::Custom(String::from("reserved"))
+        );
+    }
+
+    #[test]
+    fn test_classified_listing_round_trip() {
+        let keys = Keys::test(1);
+
+        let mut listing = ClassifiedListing::new("bike", "Lightly used road bike");
+        listing.title = Some(String::from("Road bike"));
+        listing.location = Some(String::from("Milan"));
+        listing.price = Some(Price {
+            amount: String::from("300"),
+            currency: String::from("EUR"),
+            frequency: None,
+        });
+        listing.hashtags.push(String::from("bike"));
+        assert!(listing.is_visible());
+
+        let event = EventBuilder::classified_listing(listing.clone())
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.kind(), Kind::ClassifiedListing);
+        assert_eq!(ClassifiedListing::from_event(&event).unwrap(), listing);
+
+        let event = EventBuilder::classified_listing_draft(listing)
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.kind(), Kind::ClassifiedListing);
+        let draft = ClassifiedListing::from_event(&event).unwrap();
+        assert!(draft.is_draft());
+        assert!(!draft.is_visible());
+    }
+
+    #[test]
+    fn test_classified_listing_errors() {
+        let keys = Keys::test(1);
+        let event = EventBuilder::text_note("test", []).to_event(&keys).unwrap();
+        assert_eq!(
+            ClassifiedListing::from_event(&event).unwrap_err(),
+            Error::WrongKind
+        );
+
+        let event = EventBuilder::new(Kind::ClassifiedListing, "test", [])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(
+            ClassifiedListing::from_event(&event).unwrap_err(),
+            Error::MissingIdentifier
+        );
+    }
+}
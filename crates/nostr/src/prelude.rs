@@ -35,6 +35,7 @@ pub use crate::nips::nip05::{self, *};
 pub use crate::nips::nip06::{self, *};
 #[cfg(all(feature = "nip07", target_arch = "wasm32"))]
 pub use crate::nips::nip07::{self, *};
+pub use crate::nips::nip10::{self, *};
 #[cfg(all(feature = "std", feature = "nip11"))]
 pub use crate::nips::nip11::{self, *};
 pub use crate::nips::nip13::{self, *};
@@ -51,15 +52,20 @@ pub use crate::nips::nip47::{self, *};
 pub use crate::nips::nip48::{self, *};
 #[cfg(feature = "nip49")]
 pub use crate::nips::nip49::{self, *};
+pub use crate::nips::nip51::{self, *};
 pub use crate::nips::nip53::{self, *};
 #[cfg(feature = "nip57")]
 pub use crate::nips::nip57::{self, *};
+pub use crate::nips::nip58::{self, *};
 #[cfg(feature = "nip59")]
 pub use crate::nips::nip59::{self, *};
 pub use crate::nips::nip65::{self, *};
+pub use crate::nips::nip77::{self, *};
+pub use crate::nips::nip89::{self, *};
 pub use crate::nips::nip90::{self, *};
 pub use crate::nips::nip94::{self, *};
 pub use crate::nips::nip98::{self, *};
+pub use crate::nips::nip99::{self, *};
 pub use crate::types::*;
 pub use crate::util::*;
 #[cfg(feature = "std")]
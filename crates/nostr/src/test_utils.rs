@@ -0,0 +1,234 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Test utils
+//!
+//! Deterministic keys, event factories and canned events for tests.
+//!
+//! **Available only with the `test-utils` feature: NEVER enable it in production builds!**
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use bitcoin::hashes::sha256::Hash as Sha256Hash;
+use bitcoin::hashes::{Hash, HashEngine};
+
+use crate::{Contact, Event, EventBuilder, Keys, Kind, Metadata, PublicKey, Tag, Timestamp};
+
+/// Default base timestamp of [`EventFactory`] (2023-11-14T22:13:20Z)
+pub const BASE_TIMESTAMP: u64 = 1_700_000_000;
+
+const TEST_KEY_DOMAIN: &[u8] = b"rust-nostr/test-utils/keys";
+
+impl Keys {
+    /// Deterministic test [`Keys`] from a seed byte
+    ///
+    /// The same seed always return the same keys.
+    ///
+    /// **These keys are public knowledge: use them ONLY for tests!**
+    pub fn test(seed: u8) -> Self {
+        let mut engine = Sha256Hash::engine();
+        engine.input(TEST_KEY_DOMAIN);
+        engine.input(&[seed]);
+        let hash: Sha256Hash = Sha256Hash::from_engine(engine);
+        Self::from_secret_bytes(hash.as_byte_array()).expect("valid test secret key")
+    }
+}
+
+/// Event factory
+///
+/// Produce signed events with chainable defaults:
+/// the `created_at` of each event is the base timestamp incremented by one second at every event.
+#[derive(Debug, Clone)]
+pub struct EventFactory {
+    keys: Keys,
+    kind: Kind,
+    next_created_at: Timestamp,
+}
+
+impl Default for EventFactory {
+    fn default() -> Self {
+        Self::new(Keys::test(0))
+    }
+}
+
+impl EventFactory {
+    /// New factory of [`Kind::TextNote`] events, starting from [`BASE_TIMESTAMP`]
+    pub fn new(keys: Keys) -> Self {
+        Self {
+            keys,
+            kind: Kind::TextNote,
+            next_created_at: Timestamp::from(BASE_TIMESTAMP),
+        }
+    }
+
+    /// Set author
+    pub fn author(self, keys: Keys) -> Self {
+        Self { keys, ..self }
+    }
+
+    /// Set base timestamp
+    pub fn base_timestamp(self, timestamp: Timestamp) -> Self {
+        Self {
+            next_created_at: timestamp,
+            ..self
+        }
+    }
+
+    /// Set kind
+    pub fn kind(self, kind: Kind) -> Self {
+        Self { kind, ..self }
+    }
+
+    /// Get author keys
+    pub fn keys(&self) -> &Keys {
+        &self.keys
+    }
+
+    /// Build a signed event with the default kind
+    pub fn event<S>(&mut self, content: S) -> Event
+    where
+        S: Into<String>,
+    {
+        self.event_with_tags(content, [])
+    }
+
+    /// Build a signed event with the default kind and custom tags
+    pub fn event_with_tags<S, I>(&mut self, content: S, tags: I) -> Event
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = Tag>,
+    {
+        let builder = EventBuilder::new(self.kind, content, tags);
+        self.sign(builder)
+    }
+
+    /// Sign an [`EventBuilder`], overwriting the `created_at` with the next factory timestamp
+    pub fn sign(&mut self, builder: EventBuilder) -> Event {
+        let created_at: Timestamp = self.next_created_at;
+        self.next_created_at = created_at + 1_u64;
+        builder
+            .custom_created_at(created_at)
+            .to_event(&self.keys)
+            .expect("valid test event")
+    }
+}
+
+/// Metadata event of `keys`
+pub fn metadata_event(keys: &Keys) -> Event {
+    let metadata = Metadata::new()
+        .name("test")
+        .display_name("Test User")
+        .about("Test account, not for production");
+    EventFactory::new(keys.clone()).sign(EventBuilder::metadata(&metadata))
+}
+
+/// Contact list event of `keys`
+pub fn contact_list_event<I>(keys: &Keys, public_keys: I) -> Event
+where
+    I: IntoIterator<Item = PublicKey>,
+{
+    let contacts = public_keys
+        .into_iter()
+        .map(|public_key| Contact::new::<String>(public_key, None, None));
+    EventFactory::new(keys.clone()).sign(EventBuilder::contact_list(contacts))
+}
+
+/// Text note of `author` followed by a `+` reaction from each of the `reactors`
+pub fn reaction_chain(author: &Keys, reactors: &[Keys]) -> (Event, Vec<Event>) {
+    let mut factory = EventFactory::new(author.clone());
+    let note: Event = factory.event("Reactions test");
+    let reactions: Vec<Event> = reactors
+        .iter()
+        .map(|keys| {
+            factory
+                .clone()
+                .author(keys.clone())
+                .sign(EventBuilder::reaction(&note, "+"))
+        })
+        .collect();
+    (note, reactions)
+}
+
+/// Gift-wrapped direct message pair: the first for the `receiver`, the second for the `sender`
+///
+/// <https://github.com/nostr-protocol/nips/blob/master/59.md>
+#[cfg(feature = "nip59")]
+pub fn gift_wrapped_dm_pair<S>(sender: &Keys, receiver: &PublicKey, message: S) -> (Event, Event)
+where
+    S: Into<String>,
+{
+    let rumor = EventBuilder::sealed_direct(*receiver, message)
+        .custom_created_at(Timestamp::from(BASE_TIMESTAMP))
+        .to_unsigned_event(sender.public_key());
+    let to_receiver: Event = EventBuilder::gift_wrap(sender, receiver, rumor.clone(), None)
+        .expect("valid test gift wrap");
+    let to_sender: Event = EventBuilder::gift_wrap(sender, &sender.public_key(), rumor, None)
+        .expect("valid test gift wrap");
+    (to_receiver, to_sender)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic_keys() {
+        assert_eq!(Keys::test(1).public_key(), Keys::test(1).public_key());
+        assert_ne!(Keys::test(1).public_key(), Keys::test(2).public_key());
+    }
+
+    #[test]
+    fn test_event_factory() {
+        let mut factory = EventFactory::new(Keys::test(1)).kind(Kind::Reaction);
+        let first: Event = factory.event("+");
+        let second: Event = factory.event("-");
+        first.verify().unwrap();
+        second.verify().unwrap();
+        assert_eq!(first.kind(), Kind::Reaction);
+        assert_eq!(first.author(), Keys::test(1).public_key());
+        assert_eq!(first.created_at(), Timestamp::from(BASE_TIMESTAMP));
+        assert_eq!(second.created_at(), Timestamp::from(BASE_TIMESTAMP + 1));
+    }
+
+    #[test]
+    fn test_canned_events() {
+        let keys = Keys::test(1);
+
+        let metadata: Event = metadata_event(&keys);
+        metadata.verify().unwrap();
+        assert_eq!(metadata.kind(), Kind::Metadata);
+
+        let contacts: Event = contact_list_event(&keys, [Keys::test(2).public_key()]);
+        contacts.verify().unwrap();
+        assert_eq!(contacts.public_keys().count(), 1);
+
+        let (note, reactions) = reaction_chain(&keys, &[Keys::test(2), Keys::test(3)]);
+        note.verify().unwrap();
+        assert_eq!(reactions.len(), 2);
+        for reaction in reactions.iter() {
+            reaction.verify().unwrap();
+            assert_eq!(reaction.event_ids().next(), Some(&note.id()));
+        }
+    }
+
+    #[cfg(feature = "nip59")]
+    #[test]
+    fn test_gift_wrapped_dm_pair() {
+        use crate::nips::nip59::UnwrappedGift;
+
+        let sender = Keys::test(1);
+        let receiver = Keys::test(2);
+        let (to_receiver, to_sender) =
+            gift_wrapped_dm_pair(&sender, &receiver.public_key(), "Hello");
+        to_receiver.verify().unwrap();
+        to_sender.verify().unwrap();
+
+        let gift = UnwrappedGift::from_gift_wrap(&receiver, &to_receiver).unwrap();
+        assert_eq!(gift.sender, sender.public_key());
+        assert_eq!(gift.rumor.content, "Hello");
+        let gift = UnwrappedGift::from_gift_wrap(&sender, &to_sender).unwrap();
+        assert_eq!(gift.rumor.content, "Hello");
+    }
+}
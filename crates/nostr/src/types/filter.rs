@@ -8,6 +8,7 @@
 #[cfg(not(feature = "std"))]
 use alloc::collections::{BTreeMap as AllocMap, BTreeSet as AllocSet};
 use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use core::fmt;
 use core::str::FromStr;
 #[cfg(feature = "std")]
@@ -318,6 +319,10 @@ impl IntoGenericTagValue for &str {
 /// Subscription filters
 ///
 /// <https://github.com/nostr-protocol/nips/blob/master/01.md>
+///
+/// NIP01 filters can't express a minimum PoW: relays that accept a PoW requirement (NIP13)
+/// as an extension should apply it with [`nip13::check_difficulty`](crate::nips::nip13::check_difficulty)
+/// after matching the filter.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Filter {
     /// List of [`EventId`]
@@ -350,6 +355,20 @@ pub struct Filter {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub limit: Option<usize>,
+    /// Maximum number of events to be returned in the initial query, per kind
+    ///
+    /// **This is a relay extension, not part of NIP-01**: relays that don't support it will ignore it.
+    #[cfg(feature = "nip-extensions")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub kinds_limit: Option<AllocMap<Kind, u64>>,
+    /// List of kind numbers to exclude
+    ///
+    /// **This is a relay extension, not part of NIP-01**: relays that don't support it will ignore it.
+    #[cfg(feature = "nip-extensions")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub exclude_kinds: Option<AllocSet<Kind>>,
     /// Generic tag queries
     #[serde(
         flatten,
@@ -670,6 +689,63 @@ impl Filter {
         self
     }
 
+    /// Add limit
+    ///
+    /// Alias of [`Filter::limit`]
+    #[inline]
+    pub fn limit_or(self, limit: usize) -> Self {
+        self.limit(limit)
+    }
+
+    /// Set the maximum number of events to be returned in the initial query, per kind
+    ///
+    /// Serialized as `"kinds_limit": {"<kind>": <limit>}`.
+    ///
+    /// **This is a relay extension, not part of NIP-01**: relays that don't support it will ignore it.
+    #[inline]
+    #[cfg(feature = "nip-extensions")]
+    pub fn per_kind_limit(mut self, limits: AllocMap<Kind, u64>) -> Self {
+        self.kinds_limit = Some(limits);
+        self
+    }
+
+    /// Remove per-kind limits
+    #[inline]
+    #[cfg(feature = "nip-extensions")]
+    pub fn remove_per_kind_limit(mut self) -> Self {
+        self.kinds_limit = None;
+        self
+    }
+
+    /// Exclude kinds: match every event except the ones of these kinds
+    ///
+    /// Serialized as `"exclude_kinds": [<kind>, ...]`.
+    ///
+    /// **This is a relay extension, not part of NIP-01**: only relays that implement it apply it,
+    /// the others ignore it and return also the excluded kinds.
+    /// With standard relays, subscribe with a broader filter and use this one only to evaluate the received events
+    /// locally, with [`Filter::match_event`].
+    #[inline]
+    #[cfg(feature = "nip-extensions")]
+    pub fn exclude_kinds<I>(mut self, kinds: I) -> Self
+    where
+        I: IntoIterator<Item = Kind>,
+    {
+        self.exclude_kinds = extend_or_collect(self.exclude_kinds, kinds);
+        self
+    }
+
+    /// Remove excluded kinds
+    #[inline]
+    #[cfg(feature = "nip-extensions")]
+    pub fn remove_exclude_kinds<I>(mut self, kinds: I) -> Self
+    where
+        I: IntoIterator<Item = Kind>,
+    {
+        self.exclude_kinds = remove_or_none(self.exclude_kinds, kinds);
+        self
+    }
+
     /// Add custom tag
     pub fn custom_tag<I, T>(mut self, tag: SingleLetterTag, values: I) -> Self
     where
@@ -757,6 +833,13 @@ impl Filter {
 
     #[inline]
     fn kind_match(&self, event: &Event) -> bool {
+        #[cfg(feature = "nip-extensions")]
+        if let Some(exclude_kinds) = &self.exclude_kinds {
+            if exclude_kinds.contains(&event.kind) {
+                return false;
+            }
+        }
+
         self.kinds.as_ref().map_or(true, |kinds| {
             kinds.is_empty() || kinds.contains(&event.kind)
         })
@@ -776,6 +859,124 @@ impl Filter {
     }
 }
 
+/// Target of zaps (see [`Filter::zaps_for`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ZapTarget {
+    /// Zapped event
+    Event(EventId),
+    /// Zapped public key
+    PublicKey(PublicKey),
+}
+
+impl From<EventId> for ZapTarget {
+    fn from(id: EventId) -> Self {
+        Self::Event(id)
+    }
+}
+
+impl From<PublicKey> for ZapTarget {
+    fn from(public_key: PublicKey) -> Self {
+        Self::PublicKey(public_key)
+    }
+}
+
+/// Templates for common queries
+impl Filter {
+    /// Profile metadata of a public key (latest event only)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/01.md>
+    pub fn profile(public_key: PublicKey) -> Self {
+        Self::new().author(public_key).kind(Kind::Metadata).limit(1)
+    }
+
+    /// Contact list of a public key (latest event only)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/02.md>
+    pub fn contact_list(public_key: PublicKey) -> Self {
+        Self::new()
+            .author(public_key)
+            .kind(Kind::ContactList)
+            .limit(1)
+    }
+
+    /// Relay list of a public key (latest event only)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/65.md>
+    pub fn relay_list(public_key: PublicKey) -> Self {
+        Self::new()
+            .author(public_key)
+            .kind(Kind::RelayList)
+            .limit(1)
+    }
+
+    /// Text notes of the public keys, published after `since`
+    pub fn text_notes_by<I>(public_keys: I, since: Timestamp) -> Self
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        Self::new()
+            .authors(public_keys)
+            .kind(Kind::TextNote)
+            .since(since)
+    }
+
+    /// Text notes replying to (or mentioning) an event
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/10.md>
+    pub fn replies_to(event_id: EventId) -> Self {
+        Self::new().kind(Kind::TextNote).event(event_id)
+    }
+
+    /// Reactions to an event
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/25.md>
+    pub fn reactions_to(event_id: EventId) -> Self {
+        Self::new().kind(Kind::Reaction).event(event_id)
+    }
+
+    /// Zap receipts of an event or a public key
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/57.md>
+    pub fn zaps_for<T>(target: T) -> Self
+    where
+        T: Into<ZapTarget>,
+    {
+        let filter = Self::new().kind(Kind::ZapReceipt);
+        match target.into() {
+            ZapTarget::Event(id) => filter.event(id),
+            ZapTarget::PublicKey(public_key) => filter.pubkey(public_key),
+        }
+    }
+
+    /// Encrypted direct messages between two public keys, in both directions
+    ///
+    /// Return two filters: the messages sent by `me` to `them` and the messages sent by `them` to `me`.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/04.md>
+    pub fn dms_with(me: PublicKey, them: PublicKey) -> Vec<Self> {
+        vec![
+            Self::new()
+                .author(me)
+                .kind(Kind::EncryptedDirectMessage)
+                .pubkey(them),
+            Self::new()
+                .author(them)
+                .kind(Kind::EncryptedDirectMessage)
+                .pubkey(me),
+        ]
+    }
+
+    /// Gift wraps for a public key
+    ///
+    /// The `created_at` of gift wraps is randomized up to 2 days in the past:
+    /// if a `since` is needed, move it back by at least 2 days to not miss events.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/59.md>
+    pub fn gift_wraps_for(public_key: PublicKey) -> Self {
+        Self::new().kind(Kind::GiftWrap).pubkey(public_key)
+    }
+}
+
 impl JsonUtil for Filter {
     type Err = serde_json::Error;
 }
@@ -975,6 +1176,46 @@ mod tests {
         assert_eq!(filter.as_json(), json.to_string());
     }
 
+    #[test]
+    #[cfg(feature = "nip-extensions")]
+    fn test_filter_per_kind_limit_serialization() {
+        let mut limits = AllocMap::new();
+        limits.insert(Kind::TextNote, 10);
+        let filter = Filter::new().per_kind_limit(limits);
+        let json = r#"{"kinds_limit":{"1":10}}"#;
+        assert_eq!(filter.as_json(), json);
+        assert_eq!(Filter::from_json(json).unwrap(), filter);
+    }
+
+    #[test]
+    #[cfg(feature = "nip-extensions")]
+    fn test_filter_exclude_kinds() {
+        use crate::{EventBuilder, Keys};
+
+        let filter = Filter::new().exclude_kinds(vec![Kind::Reaction]);
+        let json = r#"{"exclude_kinds":[7]}"#;
+        assert_eq!(filter.as_json(), json);
+        assert_eq!(Filter::from_json(json).unwrap(), filter);
+
+        let filter = filter.exclude_kinds(vec![Kind::Repost]);
+
+        let keys = Keys::generate();
+        let note = EventBuilder::text_note("test", []).to_event(&keys).unwrap();
+        let reaction = EventBuilder::new(Kind::Reaction, "+", [])
+            .to_event(&keys)
+            .unwrap();
+        assert!(filter.match_event(&note));
+        assert!(!filter.match_event(&reaction));
+
+        // Exclusion takes precedence
+        let filter = filter.kinds([Kind::Reaction]);
+        assert!(!filter.match_event(&reaction));
+
+        let filter = filter.remove_exclude_kinds([Kind::Reaction, Kind::Repost]);
+        assert!(filter.exclude_kinds.is_none());
+        assert!(filter.match_event(&reaction));
+    }
+
     #[test]
     fn test_filter_serialization_with_uppercase_tag() {
         let filter = Filter::new().custom_tag(
@@ -986,6 +1227,67 @@ mod tests {
         assert_eq!(filter.as_json(), json);
     }
 
+    #[test]
+    fn test_filter_templates() {
+        let public_key =
+            PublicKey::from_str("379e863e8357163b5bce5d2688dc4f1dcc2d505222fb8d74db600f30535dfdfe")
+                .unwrap();
+        let other =
+            PublicKey::from_str("b2d670de53b27691c0c3400225b65c35a26d06093bcc41f48ffc71e0907f9d4a")
+                .unwrap();
+        let event_id =
+            EventId::from_hex("70b10f70c1318967eddf12527799411b1a9780ad9c43858f5e5fcd45486a13a5")
+                .unwrap();
+
+        assert_eq!(
+            Filter::profile(public_key).as_json(),
+            r#"{"authors":["379e863e8357163b5bce5d2688dc4f1dcc2d505222fb8d74db600f30535dfdfe"],"kinds":[0],"limit":1}"#
+        );
+        assert_eq!(
+            Filter::contact_list(public_key).as_json(),
+            r#"{"authors":["379e863e8357163b5bce5d2688dc4f1dcc2d505222fb8d74db600f30535dfdfe"],"kinds":[3],"limit":1}"#
+        );
+        assert_eq!(
+            Filter::relay_list(public_key).as_json(),
+            r#"{"authors":["379e863e8357163b5bce5d2688dc4f1dcc2d505222fb8d74db600f30535dfdfe"],"kinds":[10002],"limit":1}"#
+        );
+        assert_eq!(
+            Filter::text_notes_by([public_key], Timestamp::from(1700000000)).as_json(),
+            r#"{"authors":["379e863e8357163b5bce5d2688dc4f1dcc2d505222fb8d74db600f30535dfdfe"],"kinds":[1],"since":1700000000}"#
+        );
+        assert_eq!(
+            Filter::replies_to(event_id).as_json(),
+            r##"{"kinds":[1],"#e":["70b10f70c1318967eddf12527799411b1a9780ad9c43858f5e5fcd45486a13a5"]}"##
+        );
+        assert_eq!(
+            Filter::reactions_to(event_id).as_json(),
+            r##"{"kinds":[7],"#e":["70b10f70c1318967eddf12527799411b1a9780ad9c43858f5e5fcd45486a13a5"]}"##
+        );
+        assert_eq!(
+            Filter::zaps_for(event_id).as_json(),
+            r##"{"kinds":[9735],"#e":["70b10f70c1318967eddf12527799411b1a9780ad9c43858f5e5fcd45486a13a5"]}"##
+        );
+        assert_eq!(
+            Filter::zaps_for(public_key).as_json(),
+            r##"{"kinds":[9735],"#p":["379e863e8357163b5bce5d2688dc4f1dcc2d505222fb8d74db600f30535dfdfe"]}"##
+        );
+        let dms: Vec<String> = Filter::dms_with(public_key, other)
+            .iter()
+            .map(|f| f.as_json())
+            .collect();
+        assert_eq!(
+            dms,
+            vec![
+                r##"{"authors":["379e863e8357163b5bce5d2688dc4f1dcc2d505222fb8d74db600f30535dfdfe"],"kinds":[4],"#p":["b2d670de53b27691c0c3400225b65c35a26d06093bcc41f48ffc71e0907f9d4a"]}"##,
+                r##"{"authors":["b2d670de53b27691c0c3400225b65c35a26d06093bcc41f48ffc71e0907f9d4a"],"kinds":[4],"#p":["379e863e8357163b5bce5d2688dc4f1dcc2d505222fb8d74db600f30535dfdfe"]}"##,
+            ]
+        );
+        assert_eq!(
+            Filter::gift_wraps_for(public_key).as_json(),
+            r##"{"kinds":[1059],"#p":["379e863e8357163b5bce5d2688dc4f1dcc2d505222fb8d74db600f30535dfdfe"]}"##
+        );
+    }
+
     #[test]
     fn test_filter_deserialization() {
         let json = r##"{"#a":["...", "test"],"#e":["70b10f70c1318967eddf12527799411b1a9780ad9c43858f5e5fcd45486a13a5"],"#p":["379e863e8357163b5bce5d2688dc4f1dcc2d505222fb8d74db600f30535dfdfe"],"search":"test","ids":["70b10f70c1318967eddf12527799411b1a9780ad9c43858f5e5fcd45486a13a5"]}"##;
@@ -6,7 +6,9 @@
 
 #[cfg(not(feature = "std"))]
 use alloc::collections::BTreeMap as AllocMap;
+use alloc::collections::BTreeSet;
 use alloc::string::String;
+use alloc::vec::Vec;
 use core::fmt;
 #[cfg(feature = "std")]
 use std::collections::HashMap as AllocMap;
@@ -14,15 +16,22 @@ use std::collections::HashMap as AllocMap;
 use serde::de::{Deserializer, MapAccess, Visitor};
 use serde::ser::{SerializeMap, Serializer};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 
-use crate::{JsonUtil, Url};
+use crate::{Event, JsonUtil, Kind, Url};
 
 /// [`Metadata`] error
 #[derive(Debug)]
 pub enum Error {
     /// Error serializing or deserializing JSON data
     Json(serde_json::Error),
+    /// Wrong event kind
+    WrongKind {
+        /// Expected kind
+        expected: Kind,
+        /// Found kind
+        found: Kind,
+    },
 }
 
 #[cfg(feature = "std")]
@@ -32,6 +41,9 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Json(e) => write!(f, "Json: {e}"),
+            Self::WrongKind { expected, found } => {
+                write!(f, "Wrong kind: expected={expected}, found={found}")
+            }
         }
     }
 }
@@ -42,7 +54,22 @@ impl From<serde_json::Error> for Error {
     }
 }
 
+/// Metadata field change
+///
+/// Returned by [`Metadata::diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataChange {
+    /// Field name
+    pub field: String,
+    /// Old value (`None` if the field was added)
+    pub old: Option<Value>,
+    /// New value (`None` if the field was removed)
+    pub new: Option<Value>,
+}
+
 /// Metadata
+///
+/// Fields not known by this struct are kept in [`Metadata::custom`], so they are preserved when re-publishing.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Metadata {
     /// Name
@@ -97,6 +124,18 @@ impl Metadata {
         Self::default()
     }
 
+    /// Parse [`Metadata`] from [`Kind::Metadata`] event
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        let kind: Kind = event.kind();
+        if kind != Kind::Metadata {
+            return Err(Error::WrongKind {
+                expected: Kind::Metadata,
+                found: kind,
+            });
+        }
+        Self::from_json(event.content())
+    }
+
     /// Set name
     pub fn name<S>(self, name: S) -> Self
     where
@@ -196,6 +235,57 @@ impl Metadata {
         self.custom.insert(field_name.into(), value.into());
         self
     }
+
+    /// Merge two [`Metadata`]
+    ///
+    /// Fields set in `overlay` override the ones in `base`, while fields missing in `overlay` are taken from `base`.
+    /// Custom fields are merged by key.
+    pub fn merge(base: Self, overlay: Self) -> Self {
+        let mut custom = base.custom;
+        custom.extend(overlay.custom);
+        Self {
+            name: overlay.name.or(base.name),
+            display_name: overlay.display_name.or(base.display_name),
+            about: overlay.about.or(base.about),
+            website: overlay.website.or(base.website),
+            picture: overlay.picture.or(base.picture),
+            banner: overlay.banner.or(base.banner),
+            nip05: overlay.nip05.or(base.nip05),
+            lud06: overlay.lud06.or(base.lud06),
+            lud16: overlay.lud16.or(base.lud16),
+            custom,
+        }
+    }
+
+    /// Get the list of fields changed from `self` to `other`
+    pub fn diff(&self, other: &Self) -> Vec<MetadataChange> {
+        let old: Map<String, Value> = self.as_map();
+        let new: Map<String, Value> = other.as_map();
+        let fields: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+        fields
+            .into_iter()
+            .filter_map(|field| {
+                let old: Option<&Value> = old.get(field);
+                let new: Option<&Value> = new.get(field);
+                if old != new {
+                    Some(MetadataChange {
+                        field: field.clone(),
+                        old: old.cloned(),
+                        new: new.cloned(),
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn as_map(&self) -> Map<String, Value> {
+        match serde_json::to_value(self) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        }
+    }
 }
 
 impl JsonUtil for Metadata {
@@ -253,6 +343,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{EventBuilder, Keys};
 
     #[test]
     fn test_deserialize_metadata() {
@@ -294,4 +385,98 @@ mod tests {
         );
         assert_eq!(metadata, Metadata::from_json(metadata.as_json()).unwrap());
     }
+    #[test]
+    fn test_merge_metadata() {
+        let base = Metadata::new()
+            .name("myname")
+            .about("Description")
+            .lud16("myname@example.com")
+            .custom_field("username", "myname")
+            .custom_field("reactions", false);
+        let overlay = Metadata::new()
+            .name("newname")
+            .display_name("New Name")
+            .custom_field("reactions", true);
+        assert_eq!(
+            Metadata::merge(base, overlay),
+            Metadata::new()
+                .name("newname")
+                .display_name("New Name")
+                .about("Description")
+                .lud16("myname@example.com")
+                .custom_field("username", "myname")
+                .custom_field("reactions", true)
+        );
+    }
+
+    #[test]
+    fn test_metadata_roundtrip_unknown_fields() {
+        let content = r#"{"name":"myname","lud06":"lnurl1dp68gurn8ghj7um9wfmxjcm99e3k7mf0v9cxj0m385ekvcenxc6r2c35xvukxefcv5mkvv34x5ekzd3ev56nyd3hxqurzepexejxxepnxscrvwfnv9nxzcn9xq6xyefhvgcxxcmyxymnserxfq5fns","nested":{"a":[1,2,{"b":null}],"c":{"d":"e"}},"int":42,"negative":-7,"big":18446744073709551615,"float":1.5,"bool":false}"#;
+        let metadata = Metadata::from_json(content).unwrap();
+        let expected: Value = serde_json::from_str(content).unwrap();
+        let json: Value = serde_json::from_str(&metadata.as_json()).unwrap();
+        assert_eq!(json, expected);
+
+        // Update a known field and check that nothing else changed
+        let updated = metadata.clone().name("newname");
+        let json: Value = serde_json::from_str(&updated.as_json()).unwrap();
+        let mut expected = expected;
+        expected["name"] = Value::from("newname");
+        assert_eq!(json, expected);
+
+        assert_eq!(
+            metadata.diff(&updated),
+            vec![MetadataChange {
+                field: String::from("name"),
+                old: Some(Value::from("myname")),
+                new: Some(Value::from("newname")),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_metadata_diff() {
+        let old = Metadata::new()
+            .name("myname")
+            .about("Description")
+            .custom_field("username", "myname");
+        let new = Metadata::new()
+            .name("myname")
+            .lud16("myname@example.com")
+            .custom_field("username", "newname");
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                MetadataChange {
+                    field: String::from("about"),
+                    old: Some(Value::from("Description")),
+                    new: None,
+                },
+                MetadataChange {
+                    field: String::from("lud16"),
+                    old: None,
+                    new: Some(Value::from("myname@example.com")),
+                },
+                MetadataChange {
+                    field: String::from("username"),
+                    old: Some(Value::from("myname")),
+                    new: Some(Value::from("newname")),
+                },
+            ]
+        );
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn test_metadata_from_event() {
+        let keys = Keys::generate();
+        let metadata = Metadata::new().name("myname").custom_field("int", 42);
+        let event = EventBuilder::metadata(&metadata).to_event(&keys).unwrap();
+        assert_eq!(Metadata::from_event(&event).unwrap(), metadata);
+
+        let event = EventBuilder::text_note("hello", [])
+            .to_event(&keys)
+            .unwrap();
+        assert!(Metadata::from_event(&event).is_err());
+    }
 }
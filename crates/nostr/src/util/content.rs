@@ -0,0 +1,417 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Content sanitization
+//!
+//! Preprocess the content received from relays before rendering it.
+//!
+//! All the operations work on Unicode scalar values and grapheme clusters,
+//! so emoji ZWJ sequences are kept together and UTF-16 surrogate pairs are never split.
+
+use alloc::string::{String, ToString};
+use core::fmt::Write;
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Default max number of consecutive newlines
+pub const DEFAULT_MAX_CONSECUTIVE_NEWLINES: usize = 2;
+/// Default max number of consecutive combining marks
+pub const DEFAULT_MAX_COMBINING_MARKS: usize = 8;
+/// Appended to truncated content
+pub const ELLIPSIS: char = '…';
+
+/// Control characters handling
+///
+/// Newlines and tabs are never considered control characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlChars {
+    /// Remove them
+    Strip,
+    /// Replace them with their escape sequence (ex. `\u{7}`)
+    Escape,
+}
+
+/// Sanitize options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SanitizeOptions {
+    control_chars: ControlChars,
+    normalize: bool,
+    remove_bidi_overrides: bool,
+    remove_zero_width: bool,
+    max_consecutive_newlines: Option<usize>,
+    max_combining_marks: Option<usize>,
+    max_graphemes: Option<usize>,
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        Self {
+            control_chars: ControlChars::Strip,
+            normalize: true,
+            remove_bidi_overrides: true,
+            remove_zero_width: true,
+            max_consecutive_newlines: Some(DEFAULT_MAX_CONSECUTIVE_NEWLINES),
+            max_combining_marks: Some(DEFAULT_MAX_COMBINING_MARKS),
+            max_graphemes: None,
+        }
+    }
+}
+
+impl SanitizeOptions {
+    /// New default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Control characters handling (default: [`ControlChars::Strip`])
+    pub fn control_chars(mut self, control_chars: ControlChars) -> Self {
+        self.control_chars = control_chars;
+        self
+    }
+
+    /// Normalize to NFC (default: true)
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Remove bidi embedding, override and isolate characters, used to spoof the text direction (default: true)
+    pub fn remove_bidi_overrides(mut self, remove: bool) -> Self {
+        self.remove_bidi_overrides = remove;
+        self
+    }
+
+    /// Remove zero-width characters (default: true)
+    ///
+    /// Joiners (ZWJ and ZWNJ) are required by emoji sequences and some scripts:
+    /// only the leading and repeated ones are removed.
+    pub fn remove_zero_width(mut self, remove: bool) -> Self {
+        self.remove_zero_width = remove;
+        self
+    }
+
+    /// Max number of consecutive newlines (default: [`DEFAULT_MAX_CONSECUTIVE_NEWLINES`])
+    pub fn max_consecutive_newlines(mut self, max: Option<usize>) -> Self {
+        self.max_consecutive_newlines = max;
+        self
+    }
+
+    /// Max number of consecutive combining marks (default: [`DEFAULT_MAX_COMBINING_MARKS`])
+    pub fn max_combining_marks(mut self, max: Option<usize>) -> Self {
+        self.max_combining_marks = max;
+        self
+    }
+
+    /// Truncate to the max number of grapheme clusters (default: None)
+    ///
+    /// Check [`truncate_graphemes`].
+    pub fn max_graphemes(mut self, max: Option<usize>) -> Self {
+        self.max_graphemes = max;
+        self
+    }
+}
+
+/// What was removed by [`sanitize`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SanitizeReport {
+    /// Control characters removed or escaped
+    pub control_chars: usize,
+    /// Bidi characters removed
+    pub bidi_overrides: usize,
+    /// Zero-width characters removed
+    pub zero_width: usize,
+    /// Combining marks removed
+    pub combining_marks: usize,
+    /// Newlines removed
+    pub newlines: usize,
+    /// Content truncated
+    pub truncated: bool,
+}
+
+impl SanitizeReport {
+    /// Check if nothing was removed
+    pub fn is_clean(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// Sanitize content for rendering
+///
+/// The content is normalized to NFC first, so the other checks see composed characters.
+pub fn sanitize(content: &str, opts: SanitizeOptions) -> (String, SanitizeReport) {
+    let mut report = SanitizeReport::default();
+
+    let content: String = if opts.normalize {
+        content.nfc().collect()
+    } else {
+        content.to_string()
+    };
+
+    let mut output: String = String::with_capacity(content.len());
+    let mut newlines: usize = 0;
+    let mut marks: usize = 0;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        // CRLF
+        if c == '\r' && chars.peek() == Some(&'\n') {
+            report.control_chars += 1;
+            continue;
+        }
+
+        if is_control(c) {
+            report.control_chars += 1;
+            if opts.control_chars == ControlChars::Escape {
+                let _ = write!(output, "\\u{{{:x}}}", c as u32);
+                newlines = 0;
+                marks = 0;
+            }
+            continue;
+        }
+
+        if opts.remove_bidi_overrides && is_bidi_override(c) {
+            report.bidi_overrides += 1;
+            continue;
+        }
+
+        if opts.remove_zero_width && is_zero_width(c) {
+            let keep: bool = is_joiner(c)
+                && matches!(output.chars().next_back(), Some(prev) if !is_joiner(prev));
+            if !keep {
+                report.zero_width += 1;
+                continue;
+            }
+        }
+
+        if is_combining_mark(c) {
+            marks += 1;
+            if matches!(opts.max_combining_marks, Some(max) if marks > max) {
+                report.combining_marks += 1;
+                continue;
+            }
+        } else {
+            marks = 0;
+        }
+
+        if c == '\n' {
+            newlines += 1;
+            if matches!(opts.max_consecutive_newlines, Some(max) if newlines > max) {
+                report.newlines += 1;
+                continue;
+            }
+        } else {
+            newlines = 0;
+        }
+
+        output.push(c);
+    }
+
+    if let Some(max) = opts.max_graphemes {
+        if let Some(index) = truncate_index(&output, max) {
+            output.truncate(index);
+            output.push(ELLIPSIS);
+            report.truncated = true;
+        }
+    }
+
+    (output, report)
+}
+
+/// Truncate to `n` grapheme clusters, appending [`ELLIPSIS`] if truncated
+pub fn truncate_graphemes(s: &str, n: usize) -> String {
+    match truncate_index(s, n) {
+        Some(index) => {
+            let mut truncated: String = String::with_capacity(index + ELLIPSIS.len_utf8());
+            truncated.push_str(&s[..index]);
+            truncated.push(ELLIPSIS);
+            truncated
+        }
+        None => s.to_string(),
+    }
+}
+
+/// Byte index of the `n`-th grapheme cluster, if any
+fn truncate_index(s: &str, n: usize) -> Option<usize> {
+    s.grapheme_indices(true).nth(n).map(|(index, _)| index)
+}
+
+fn is_control(c: char) -> bool {
+    c.is_control() && c != '\n' && c != '\t'
+}
+
+fn is_bidi_override(c: char) -> bool {
+    // Embeddings and overrides (LRE, RLE, PDF, LRO, RLO) and isolates (LRI, RLI, FSI, PDI)
+    matches!(c, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+fn is_zero_width(c: char) -> bool {
+    matches!(
+        c,
+        '\u{200B}'..='\u{200D}' | '\u{2060}'..='\u{2064}' | '\u{180E}' | '\u{FEFF}'
+    )
+}
+
+fn is_joiner(c: char) -> bool {
+    matches!(c, '\u{200C}' | '\u{200D}')
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    const FAMILY: &str = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{200D}\u{1F466}";
+    const THUMBS_UP_MEDIUM: &str = "\u{1F44D}\u{1F3FD}";
+
+    fn sanitize_default(content: &str) -> (String, SanitizeReport) {
+        sanitize(content, SanitizeOptions::default())
+    }
+
+    #[test]
+    fn test_control_chars() {
+        let content = "a\u{0}b\u{7}c\r\nd\te\u{9B}";
+
+        let (output, report) = sanitize_default(content);
+        assert_eq!(output, "abc\nd\te");
+        assert_eq!(report.control_chars, 4);
+
+        let opts = SanitizeOptions::new().control_chars(ControlChars::Escape);
+        let (output, report) = sanitize(content, opts);
+        assert_eq!(output, "a\\u{0}b\\u{7}c\nd\te\\u{9b}");
+        assert_eq!(report.control_chars, 4);
+    }
+
+    #[test]
+    fn test_newlines() {
+        let (output, report) = sanitize_default("a\n\n\n\n\nb\n\nc");
+        assert_eq!(output, "a\n\nb\n\nc");
+        assert_eq!(report.newlines, 3);
+
+        let opts = SanitizeOptions::new().max_consecutive_newlines(None);
+        let (output, report) = sanitize("a\n\n\n\n\nb", opts);
+        assert_eq!(output, "a\n\n\n\n\nb");
+        assert!(report.is_clean());
+    }
+
+    #[test]
+    fn test_normalize() {
+        let (output, report) = sanitize_default("e\u{301}");
+        assert_eq!(output, "\u{E9}");
+        assert!(report.is_clean());
+
+        let opts = SanitizeOptions::new().normalize(false);
+        let (output, _) = sanitize("e\u{301}", opts);
+        assert_eq!(output, "e\u{301}");
+    }
+
+    #[test]
+    fn test_bidi_spoofing() {
+        // Rendered as "invoiceexe.pdf"
+        let (output, report) = sanitize_default("invoice\u{202E}fdp.exe");
+        assert_eq!(output, "invoicefdp.exe");
+        assert_eq!(report.bidi_overrides, 1);
+
+        let (output, report) = sanitize_default("\u{2067}admin\u{2069}\u{202D}x\u{202C}");
+        assert_eq!(output, "adminx");
+        assert_eq!(report.bidi_overrides, 4);
+
+        // Marks are allowed
+        let (output, report) = sanitize_default("a\u{200F}b");
+        assert_eq!(output, "a\u{200F}b");
+        assert!(report.is_clean());
+
+        let opts = SanitizeOptions::new().remove_bidi_overrides(false);
+        let (output, _) = sanitize("invoice\u{202E}fdp.exe", opts);
+        assert_eq!(output, "invoice\u{202E}fdp.exe");
+    }
+
+    #[test]
+    fn test_zero_width_flood() {
+        let flood: String = "\u{200B}\u{FEFF}\u{2060}".repeat(10_000);
+        let content: String = format!("a{flood}b");
+        let (output, report) = sanitize_default(&content);
+        assert_eq!(output, "ab");
+        assert_eq!(report.zero_width, 30_000);
+
+        let content: String = format!("\u{200D}a{}b", "\u{200D}".repeat(10_000));
+        let (output, report) = sanitize_default(&content);
+        assert_eq!(output, "a\u{200D}b");
+        assert_eq!(report.zero_width, 10_000);
+    }
+
+    #[test]
+    fn test_combining_marks_flood() {
+        let content: String = format!("a{}", "\u{301}".repeat(10_000));
+        let (output, report) = sanitize_default(&content);
+        // `a` and the first mark are composed into `á`
+        let mut expected: String = String::from("\u{E1}");
+        expected.push_str(&"\u{301}".repeat(DEFAULT_MAX_COMBINING_MARKS));
+        assert_eq!(output, expected);
+        assert_eq!(
+            report.combining_marks,
+            10_000 - 1 - DEFAULT_MAX_COMBINING_MARKS
+        );
+
+        // The count is per base character
+        let content: String = format!("a{marks}b{marks}", marks = "\u{316}".repeat(3));
+        let opts = SanitizeOptions::new().max_combining_marks(Some(2));
+        let (output, report) = sanitize(&content, opts);
+        assert_eq!(output, "a\u{316}\u{316}b\u{316}\u{316}");
+        assert_eq!(report.combining_marks, 2);
+    }
+
+    #[test]
+    fn test_emoji() {
+        let content: String =
+            format!("{FAMILY}{THUMBS_UP_MEDIUM}\u{2764}\u{FE0F}1\u{FE0F}\u{20E3}");
+        let (output, report) = sanitize_default(&content);
+        assert_eq!(output, content);
+        assert!(report.is_clean());
+    }
+
+    #[test]
+    fn test_truncate_graphemes() {
+        let content: String = format!("{FAMILY}{THUMBS_UP_MEDIUM}x");
+        assert_eq!(truncate_graphemes(&content, 3), content);
+        assert_eq!(truncate_graphemes(&content, 10), content);
+        assert_eq!(
+            truncate_graphemes(&content, 2),
+            format!("{FAMILY}{THUMBS_UP_MEDIUM}{ELLIPSIS}")
+        );
+        assert_eq!(
+            truncate_graphemes(&content, 1),
+            format!("{FAMILY}{ELLIPSIS}")
+        );
+        assert_eq!(truncate_graphemes(&content, 0), ELLIPSIS.to_string());
+
+        // Flags
+        let flags = "\u{1F1FA}\u{1F1F8}\u{1F1EE}\u{1F1F9}";
+        assert_eq!(
+            truncate_graphemes(flags, 1),
+            format!("\u{1F1FA}\u{1F1F8}{ELLIPSIS}")
+        );
+
+        // A single grapheme with 10k combining marks is not split
+        let zalgo: String = format!("a{}b", "\u{316}".repeat(10_000));
+        let truncated: String = truncate_graphemes(&zalgo, 1);
+        assert_eq!(truncated.chars().count(), 10_002);
+        assert!(truncated.ends_with(ELLIPSIS));
+
+        // Never split a multi-byte char
+        let text: Vec<String> = (0..5).map(|n| truncate_graphemes("ñandú", n)).collect();
+        assert_eq!(text, ["…", "ñ…", "ña…", "ñan…", "ñand…"]);
+    }
+
+    #[test]
+    fn test_max_graphemes() {
+        let opts = SanitizeOptions::new().max_graphemes(Some(3));
+        let (output, report) = sanitize("a\u{202E}bcdef", opts);
+        assert_eq!(output, "abc…");
+        assert_eq!(report.bidi_overrides, 1);
+        assert!(report.truncated);
+    }
+}
@@ -0,0 +1,329 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! HTTP util
+//!
+//! Retry policy for the HTTP lookups (NIP05, NIP11 and LNURL)
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::future::Future;
+use core::time::Duration;
+
+use async_utility::{thread, time};
+use reqwest::{Response, StatusCode};
+
+use crate::types::time::Instant;
+
+/// Default max number of attempts
+pub const DEFAULT_ATTEMPTS: usize = 3;
+/// Default backoff before the first retry (doubled at every retry)
+pub const DEFAULT_BACKOFF: Duration = Duration::from_millis(250);
+/// Default overall deadline
+pub const DEFAULT_DEADLINE: Duration = Duration::from_secs(10);
+const DEFAULT_RETRY_ON_STATUS: [u16; 6] = [408, 429, 500, 502, 503, 504];
+
+/// HTTP attempt error
+#[derive(Debug)]
+pub enum AttemptError {
+    /// Transport error (DNS, connection, TLS, ...)
+    Transport(reqwest::Error),
+    /// Retryable status code
+    Status(StatusCode),
+    /// Overall deadline reached
+    DeadlineExceeded,
+}
+
+impl fmt::Display for AttemptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(e) => write!(f, "{e}"),
+            Self::Status(status) => write!(f, "status {status}"),
+            Self::DeadlineExceeded => write!(f, "deadline exceeded"),
+        }
+    }
+}
+
+/// HTTP error
+///
+/// Contains the failure of every attempt, in order.
+#[derive(Debug)]
+pub struct Error {
+    /// Attempts failures
+    pub attempts: Vec<AttemptError>,
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HTTP request failed")?;
+        for (index, e) in self.attempts.iter().enumerate() {
+            let sep: &str = if index == 0 { ": " } else { ", " };
+            write!(f, "{sep}attempt {}: {e}", index + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Retry policy
+///
+/// Only idempotent requests (ex. `GET`) must be retried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    attempts: usize,
+    backoff: Duration,
+    deadline: Option<Duration>,
+    retry_on_status: Vec<u16>,
+    retry_on_transport: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: DEFAULT_ATTEMPTS,
+            backoff: DEFAULT_BACKOFF,
+            deadline: Some(DEFAULT_DEADLINE),
+            retry_on_status: DEFAULT_RETRY_ON_STATUS.to_vec(),
+            retry_on_transport: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// New default policy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Single attempt, without deadline
+    pub fn none() -> Self {
+        Self::default().attempts(1).deadline(None)
+    }
+
+    /// Max number of attempts, including the first one (default: [`DEFAULT_ATTEMPTS`])
+    pub fn attempts(mut self, attempts: usize) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Backoff before the first retry, doubled at every retry (default: [`DEFAULT_BACKOFF`])
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Overall deadline, including backoffs (default: [`DEFAULT_DEADLINE`])
+    pub fn deadline(mut self, deadline: Option<Duration>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Status codes to retry (default: 408, 429, 500, 502, 503 and 504)
+    pub fn retry_on_status<I>(mut self, status: I) -> Self
+    where
+        I: IntoIterator<Item = u16>,
+    {
+        self.retry_on_status = status.into_iter().collect();
+        self
+    }
+
+    /// Retry on transport errors (default: true)
+    pub fn retry_on_transport(mut self, retry: bool) -> Self {
+        self.retry_on_transport = retry;
+        self
+    }
+
+    fn is_retryable_status(&self, status: StatusCode) -> bool {
+        self.retry_on_status.contains(&status.as_u16())
+    }
+}
+
+struct Attempts<'a> {
+    policy: &'a RetryPolicy,
+    started: Instant,
+    count: usize,
+    failures: Vec<AttemptError>,
+}
+
+impl<'a> Attempts<'a> {
+    fn new(policy: &'a RetryPolicy) -> Self {
+        Self {
+            policy,
+            started: Instant::now(),
+            count: 0,
+            failures: Vec::new(),
+        }
+    }
+
+    /// Time left before the deadline
+    fn remaining(&self) -> Option<Duration> {
+        self.policy
+            .deadline
+            .map(|deadline| deadline.saturating_sub(self.started.elapsed()))
+    }
+
+    /// Get backoff to wait before the next attempt
+    ///
+    /// Return `None` if no attempts are left.
+    fn next_backoff(&mut self) -> Option<Duration> {
+        if self.count >= self.policy.attempts {
+            return None;
+        }
+
+        let backoff: Duration = match self.count {
+            0 => Duration::ZERO,
+            n => {
+                let factor: u32 = 2u32.saturating_pow(n as u32 - 1);
+                self.policy.backoff.saturating_mul(factor)
+            }
+        };
+
+        if let Some(remaining) = self.remaining() {
+            if remaining.is_zero() || (self.count > 0 && remaining <= backoff) {
+                self.failures.push(AttemptError::DeadlineExceeded);
+                return None;
+            }
+        }
+
+        self.count += 1;
+        Some(backoff)
+    }
+
+    /// Record a failure and return `true` if the request can be retried
+    fn failed(&mut self, e: AttemptError) -> bool {
+        let retry: bool = match e {
+            AttemptError::Transport(..) => self.policy.retry_on_transport,
+            AttemptError::Status(..) => true,
+            AttemptError::DeadlineExceeded => false,
+        };
+        self.failures.push(e);
+        retry
+    }
+
+    fn into_error(self) -> Error {
+        Error {
+            attempts: self.failures,
+        }
+    }
+}
+
+/// Send a request, retrying according to the [`RetryPolicy`]
+///
+/// `request` is called once per attempt.
+/// Responses with a status not in [`RetryPolicy::retry_on_status`] are returned as they are.
+pub async fn send<F, Fut>(policy: &RetryPolicy, mut request: F) -> Result<Response, Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Response, reqwest::Error>>,
+{
+    let mut attempts = Attempts::new(policy);
+    while let Some(backoff) = attempts.next_backoff() {
+        if !backoff.is_zero() {
+            thread::sleep(backoff).await;
+        }
+
+        let res = match attempts.remaining() {
+            Some(remaining) => match time::timeout(Some(remaining), request()).await {
+                Some(res) => res,
+                None => {
+                    attempts.failed(AttemptError::DeadlineExceeded);
+                    break;
+                }
+            },
+            None => request().await,
+        };
+
+        let retry: bool = match res {
+            Ok(res) if !policy.is_retryable_status(res.status()) => return Ok(res),
+            Ok(res) => attempts.failed(AttemptError::Status(res.status())),
+            Err(e) => attempts.failed(AttemptError::Transport(e)),
+        };
+
+        if !retry {
+            break;
+        }
+    }
+    Err(attempts.into_error())
+}
+
+/// Send a blocking request, retrying according to the [`RetryPolicy`]
+///
+/// `request` is called once per attempt, with the time left before the deadline (to use as request timeout).
+/// Responses with a status not in [`RetryPolicy::retry_on_status`] are returned as they are.
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(feature = "blocking")]
+pub fn send_blocking<F>(
+    policy: &RetryPolicy,
+    mut request: F,
+) -> Result<reqwest::blocking::Response, Error>
+where
+    F: FnMut(Option<Duration>) -> Result<reqwest::blocking::Response, reqwest::Error>,
+{
+    let mut attempts = Attempts::new(policy);
+    while let Some(backoff) = attempts.next_backoff() {
+        if !backoff.is_zero() {
+            std::thread::sleep(backoff);
+        }
+
+        let retry: bool = match request(attempts.remaining()) {
+            Ok(res) if !policy.is_retryable_status(res.status()) => return Ok(res),
+            Ok(res) => attempts.failed(AttemptError::Status(res.status())),
+            Err(e) => attempts.failed(AttemptError::Transport(e)),
+        };
+
+        if !retry {
+            break;
+        }
+    }
+    Err(attempts.into_error())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::new()
+            .attempts(4)
+            .backoff(Duration::from_millis(100))
+            .deadline(None);
+        let mut attempts = Attempts::new(&policy);
+        assert_eq!(attempts.next_backoff(), Some(Duration::ZERO));
+        assert_eq!(attempts.next_backoff(), Some(Duration::from_millis(100)));
+        assert_eq!(attempts.next_backoff(), Some(Duration::from_millis(200)));
+        assert_eq!(attempts.next_backoff(), Some(Duration::from_millis(400)));
+        assert_eq!(attempts.next_backoff(), None);
+        assert!(attempts.failures.is_empty());
+    }
+
+    #[test]
+    fn test_deadline() {
+        let policy = RetryPolicy::new()
+            .backoff(Duration::from_secs(60))
+            .deadline(Some(Duration::from_secs(10)));
+        let mut attempts = Attempts::new(&policy);
+        assert_eq!(attempts.next_backoff(), Some(Duration::ZERO));
+        assert!(attempts.failed(AttemptError::Status(StatusCode::BAD_GATEWAY)));
+
+        // Backoff longer than the time left
+        assert_eq!(attempts.next_backoff(), None);
+        let e = attempts.into_error();
+        assert_eq!(
+            e.to_string(),
+            "HTTP request failed: attempt 1: status 502 Bad Gateway, attempt 2: deadline exceeded"
+        );
+    }
+
+    #[test]
+    fn test_retryable() {
+        let policy = RetryPolicy::new().retry_on_status([503]);
+        let mut attempts = Attempts::new(&policy);
+        assert!(attempts.failed(AttemptError::Status(StatusCode::SERVICE_UNAVAILABLE)));
+        assert!(!attempts.failed(AttemptError::DeadlineExceeded));
+        assert!(policy.is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!policy.is_retryable_status(StatusCode::BAD_GATEWAY));
+    }
+}
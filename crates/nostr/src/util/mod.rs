@@ -14,10 +14,15 @@ use once_cell::sync::Lazy;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
+pub mod content;
 pub mod hex;
 #[cfg(feature = "nip44")]
 pub mod hkdf;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod relay_url;
 
+pub use self::relay_url::{normalize_relay_url, FromRelayStr};
 use crate::nips::nip01::Coordinate;
 use crate::{EventId, PublicKey, SecretKey, Tag};
 
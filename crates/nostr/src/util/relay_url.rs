@@ -0,0 +1,91 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Relay URL normalization
+//!
+//! `wss://relay.example`, `wss://relay.example/`, `WSS://RELAY.EXAMPLE` and `wss://relay.example:443`
+//! are the same relay: compare the normalized form to avoid duplicates.
+//! Use [`FromRelayStr`] to parse relay URLs and [`normalize_relay_url`] to compare relay URLs kept as strings (i.e. in tags).
+
+use alloc::string::{String, ToString};
+
+use crate::types::url::{ParseError, Url};
+
+/// Normalize relay URL
+///
+/// * lowercase scheme and host;
+/// * remove default port (`443` for `wss`, `80` for `ws`);
+/// * remove trailing slash of bare URLs (the path of non-root URLs is preserved).
+pub fn normalize_relay_url(url: &str) -> Result<String, ParseError> {
+    let url: Url = Url::parse(url.trim())?;
+    let mut normalized: String = url.to_string();
+    if url.path() == "/" && url.query().is_none() && url.fragment().is_none() {
+        normalized.pop();
+    }
+    Ok(normalized)
+}
+
+/// Parse relay URL
+pub trait FromRelayStr: Sized {
+    /// Parse relay URL, normalizing it (see [`normalize_relay_url`])
+    fn from_relay_str(s: &str) -> Result<Self, ParseError>;
+}
+
+impl FromRelayStr for Url {
+    fn from_relay_str(s: &str) -> Result<Self, ParseError> {
+        Url::parse(&normalize_relay_url(s)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_relay_url() {
+        for url in [
+            "wss://relay.example",
+            "wss://relay.example/",
+            "WSS://RELAY.EXAMPLE",
+            "wss://Relay.Example:443/",
+        ] {
+            assert_eq!(normalize_relay_url(url).unwrap(), "wss://relay.example");
+        }
+
+        assert_eq!(
+            normalize_relay_url("ws://relay.example:80").unwrap(),
+            "ws://relay.example"
+        );
+        assert_eq!(
+            normalize_relay_url("wss://relay.example:4433/").unwrap(),
+            "wss://relay.example:4433"
+        );
+        assert_eq!(
+            normalize_relay_url("wss://relay.example/Inbox/").unwrap(),
+            "wss://relay.example/Inbox/"
+        );
+        assert!(normalize_relay_url("relay.example").is_err());
+    }
+
+    #[test]
+    fn test_from_relay_str() {
+        let expected: Url = Url::from_relay_str("wss://relay.example").unwrap();
+        for url in [
+            "wss://relay.example/",
+            "WSS://RELAY.EXAMPLE",
+            " wss://Relay.Example:443/ ",
+        ] {
+            assert_eq!(Url::from_relay_str(url).unwrap(), expected);
+        }
+
+        // Path preserved
+        assert_eq!(
+            Url::from_relay_str("wss://relay.example/Inbox")
+                .unwrap()
+                .path(),
+            "/Inbox"
+        );
+        assert!(Url::from_relay_str("relay.example").is_err());
+    }
+}